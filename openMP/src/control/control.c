@@ -2,52 +2,128 @@
 // Strategies:
 //   1) G-Atomic: single shared histogram with #pragma omp atomic
 //   2) TL-Local: thread-local histograms + manual reduction
+//   3) Striped: groups of consecutive bins, each behind one omp_lock_t of an
+//      array, set and unset around every increment
+//   4) Manual: the TL-Local parallel for under the name of the Rust manual
+//      strategy (plain std::threads over static ranges), which it maps to
+//      one-to-one with sched static and chunk 0
+//   5) Scoped: the same, under the name of the Rust scoped strategy (crossbeam
+//      scoped threads borrowing their ranges)
 //
 // Usage:
-//   ./hist_openmp <strategy> <dist> <N> <T> [sched] [chunk] [pad] [affinity]
-//   strategy: atomic | local
-//   dist:     uniform | skewed
+//   ./hist_openmp <strategy> <dist> <N> <T> [sched] [chunk] [pad] [affinity] [dump] [seed] [stripes]
+//   strategy: atomic | local | striped | manual | scoped
+//   dist:     uniform | skewed | zipf[:s] | gaussian[:mu:sigma] | file:path
+//             zipf: P(bin k) ~ 1 / (k + 1)^s (default s = 1); gaussian:
+//             N(mu, sigma) rounded and clamped to the bins (default 128:32);
+//             file: the first N bytes of a raw binary file, as the Rust
+//             --input reads them (recorded as dist=file, seed=0)
 //   N:        number of elements (e.g., 10000000)
 //   T:        number of threads (e.g., 1,2,4,8,16)
 //   sched:    static | dynamic | guided (default: static)
 //   chunk:    chunk size (0 = runtime default)
 //   pad:      0 | 1 (atomic only; 1 = padded bins)
-//   affinity: 0 | 1 (0 = no pinning, 1 = pin threads to cores)
+//   affinity: none | compact | scatter | list:C,C,... (0 and 1 for none and
+//             compact), as the Rust --affinity: compact is proc_bind(close);
+//             scatter runs with OMP_PROC_BIND=spread, OMP_PLACES=threads and
+//             list with OMP_PROC_BIND=close, OMP_PLACES={C},{C},...
+//   dump:     file to write the bins to as a canonical result dump, to check
+//             against the Rust histogram's --dump with run_all_benchmarks verify
+//             (- for none)
+//   seed:     input seed (default 123456789 uniform, 987654321 skewed,
+//             246813579 zipf, 135792468 gaussian), as the Rust --seed
+//   stripes:  striped only; locks over groups of 256 / stripes bins
+//             (default 16, at most 256)
 //
 // Output (CSV-style):
-//   hist,openmp,strategy=atomic,dist=uniform,N=10000000,T=8,sched=static,chunk=0,pad=0,affinity=0,time,0.123456,sec
-//   hist,openmp,strategy=atomic,dist=uniform,N=10000000,T=8,sched=static,chunk=0,pad=0,affinity=0,correct,1,boolean
+//   hist,openmp,strategy=atomic,dist=uniform,N=10000000,T=8,sched=static,chunk=0,pad=0,stripes=0,affinity=none,seed=123456789,time,0.123456,sec
+//   hist,openmp,strategy=atomic,dist=uniform,N=10000000,T=8,sched=static,chunk=0,pad=0,stripes=0,affinity=none,seed=123456789,correct,1,boolean
+//   hist,openmp,strategy=atomic,dist=uniform,N=10000000,T=8,sched=static,chunk=0,pad=0,stripes=0,affinity=none,seed=123456789,peak_rss,12345678,bytes
+//
+// Compiled with -DBENCH_FFI (rust/build.rs, --features ffi) main() is left
+// out and bench_hist() runs the strategies on an input the caller owns.
+
+#define _POSIX_C_SOURCE 200809L  // setenv
 
 #include <omp.h>
 #include <stdio.h>
 #include <stdlib.h>
 #include <stdint.h>
 #include <string.h>
+#include <math.h>
+#include <sys/resource.h>
+#include <unistd.h>
 
 #define BINS 256
+#define DEFAULT_STRIPES 16
+
+// default seeds of the inputs (rust/src/controllability/histogram.rs)
+#define UNIFORM_SEED  123456789ull
+#define SKEWED_SEED   987654321ull
+#define ZIPF_SEED     246813579ull
+#define GAUSSIAN_SEED 135792468ull
+
+// Peak resident set size of the whole process in bytes (Linux reports
+// ru_maxrss in KiB), to set beside the Rust binaries' peak_rss
+static long long peak_rss_bytes(void) {
+    struct rusage ru;
+    if (getrusage(RUSAGE_SELF, &ru) != 0) return -1;
+    return (long long)ru.ru_maxrss * 1024;
+}
 
-// LCG RNG (deterministic)
-static inline uint32_t lcg_next(uint32_t x) {
-    return x * 1664525u + 1013904223u;
+// Canonical result dump (rust/src/dump.rs, checked with run_all_benchmarks
+// verify): "BENCHDMP", version 1, the kernel id, the size, the value count
+// and the values, all little-endian; 0 when the file cannot be written
+static void put_le(FILE *f, uint64_t v, int bytes) {
+    for (int i = 0; i < bytes; ++i) fputc((int)((v >> (8 * i)) & 0xFF), f);
+}
+
+static int write_dump(const char *path, uint32_t kernel, uint64_t size,
+                      const uint64_t *values, uint64_t count) {
+    FILE *f = fopen(path, "wb");
+    if (!f) {
+        perror(path);
+        return 0;
+    }
+    fwrite("BENCHDMP", 1, 8, f);
+    put_le(f, 1, 4);
+    put_le(f, kernel, 4);
+    put_le(f, size, 8);
+    put_le(f, count, 8);
+    for (uint64_t i = 0; i < count; ++i) put_le(f, values[i], 8);
+    return fclose(f) == 0;
+}
+
+// SplitMix64 in counter form (deterministic): value i of stream `seed`,
+// identical for any thread count and to the Rust rng module
+static inline uint64_t splitmix64_at(uint64_t seed, uint64_t i) {
+    uint64_t z = seed + (i + 1) * 0x9E3779B97F4A7C15ull;
+    z = (z ^ (z >> 30)) * 0xBF58476D1CE4E5B9ull;
+    z = (z ^ (z >> 27)) * 0x94D049BB133111EBull;
+    return z ^ (z >> 31);
+}
+
+// each element uses the top 32 bits of its stream value
+static inline uint32_t draw(uint64_t seed, long long i) {
+    return (uint32_t)(splitmix64_at(seed, (uint64_t)i) >> 32);
 }
 
 // uniform [0,255]
-static void gen_uniform(uint8_t *data, long long N) {
-    uint32_t x = 123456789u;
+static void gen_uniform(uint8_t *data, long long N, uint64_t seed) {
+    #pragma omp parallel for schedule(static)
     for (long long i = 0; i < N; ++i) {
-        x = lcg_next(x);
-        data[i] = (uint8_t)(x & 0xFF);  // use low 8 bits
+        data[i] = (uint8_t)(draw(seed, i) & 0xFF);  // use low 8 bits
     }
 }
 
 // skewed, ~80% in first 20% bins (0..51)
-static void gen_skewed(uint8_t *data, long long N) {
+static void gen_skewed(uint8_t *data, long long N, uint64_t seed) {
     const int hot_bins = (int)(BINS * 0.2); // 51
-    uint32_t x = 987654321u;
     const uint32_t threshold = (uint32_t)(0.8 * 4294967295.0); // ~80%
 
+    #pragma omp parallel for schedule(static)
     for (long long i = 0; i < N; ++i) {
-        x = lcg_next(x);
+        uint32_t x = draw(seed, i);
         if (x < threshold) {
             // hot range
             data[i] = (uint8_t)(x % hot_bins); // 0 .. hot_bins-1
@@ -60,6 +136,108 @@ static void gen_skewed(uint8_t *data, long long N) {
     }
 }
 
+// the top 53 bits of value i of stream `seed` as a double in [0, 1)
+static inline double unit(uint64_t seed, long long i) {
+    return (double)(splitmix64_at(seed, (uint64_t)i) >> 11) / 9007199254740992.0;
+}
+
+// zipf, P(bin k) ~ 1 / (k + 1)^s: bin 0 the hottest, a heavy tail after it
+static void gen_zipf(uint8_t *data, long long N, double s, uint64_t seed) {
+    // running sums of the weights; a draw takes the first bin past u * total
+    double cdf[BINS];
+    double total = 0.0;
+    for (int k = 0; k < BINS; ++k) {
+        total += pow((double)(k + 1), -s);
+        cdf[k] = total;
+    }
+
+    #pragma omp parallel for schedule(static)
+    for (long long i = 0; i < N; ++i) {
+        double target = unit(seed, i) * total;
+        int lo = 0, hi = BINS - 1;
+        while (lo < hi) {
+            int mid = (lo + hi) / 2;
+            if (cdf[mid] <= target) lo = mid + 1; else hi = mid;
+        }
+        data[i] = (uint8_t)lo;
+    }
+}
+
+// gaussian N(mu, sigma), rounded to the nearest bin and clamped to [0,255]
+static void gen_gaussian(uint8_t *data, long long N, double mu, double sigma, uint64_t seed) {
+    const double tau = 6.283185307179586;
+
+    #pragma omp parallel for schedule(static)
+    for (long long i = 0; i < N; ++i) {
+        // Box-Muller on the two halves of the stream value, u1 in (0, 1]
+        uint64_t r = splitmix64_at(seed, (uint64_t)i);
+        double u1 = ((double)(r >> 32) + 1.0) / 4294967296.0;
+        double u2 = (double)(r & 0xFFFFFFFFull) / 4294967296.0;
+        double z = sqrt(-2.0 * log(u1)) * cos(tau * u2);
+        double x = round(mu + sigma * z);
+        data[i] = (uint8_t)(x < 0.0 ? 0.0 : x > 255.0 ? 255.0 : x);
+    }
+}
+
+// Parses `dist` into the generator's parameters and default seed; 0 when it
+// names no distribution
+typedef struct {
+    char kind;        // 'u'niform, 's'kewed, 'z'ipf, 'g'aussian, 'f'ile
+    double a, b;      // s, or mu and sigma
+    uint64_t seed;
+    const char *path; // the raw input of a file dist
+} dist_t;
+
+static int parse_dist(const char *dist, dist_t *d) {
+    char rest;
+    if (strcmp(dist, "uniform") == 0) {
+        *d = (dist_t){ 'u', 0.0, 0.0, UNIFORM_SEED, NULL };
+    } else if (strcmp(dist, "skewed") == 0) {
+        *d = (dist_t){ 's', 0.0, 0.0, SKEWED_SEED, NULL };
+    } else if (strcmp(dist, "zipf") == 0) {
+        *d = (dist_t){ 'z', 1.0, 0.0, ZIPF_SEED, NULL };
+    } else if (sscanf(dist, "zipf:%lf%c", &d->a, &rest) == 1 && d->a > 0.0) {
+        d->kind = 'z';
+        d->seed = ZIPF_SEED;
+    } else if (strcmp(dist, "gaussian") == 0) {
+        *d = (dist_t){ 'g', 128.0, 32.0, GAUSSIAN_SEED, NULL };
+    } else if (sscanf(dist, "gaussian:%lf:%lf%c", &d->a, &d->b, &rest) == 2 && d->b > 0.0) {
+        d->kind = 'g';
+        d->seed = GAUSSIAN_SEED;
+    } else if (strncmp(dist, "file:", 5) == 0 && dist[5] != '\0') {
+        *d = (dist_t){ 'f', 0.0, 0.0, 0, dist + 5 };
+    } else {
+        return 0;
+    }
+    return 1;
+}
+
+// the first N bytes of a raw binary file (the Rust histogram's --input), or
+// 0 when it cannot be read or is shorter
+static int read_input(uint8_t *data, long long N, const char *path) {
+    FILE *f = fopen(path, "rb");
+    if (!f) {
+        perror(path);
+        return 0;
+    }
+    size_t got = fread(data, 1, (size_t)N, f);
+    fclose(f);
+    if (got != (size_t)N) {
+        fprintf(stderr, "%s: %zu bytes, %lld needed\n", path, got, N);
+        return 0;
+    }
+    return 1;
+}
+
+static void generate(uint8_t *data, long long N, const dist_t *d, uint64_t seed) {
+    switch (d->kind) {
+    case 'u': gen_uniform(data, N, seed); break;
+    case 's': gen_skewed(data, N, seed); break;
+    case 'z': gen_zipf(data, N, d->a, seed); break;
+    default:  gen_gaussian(data, N, d->a, d->b, seed); break;
+    }
+}
+
 // padded atomic bins (reduce false sharing)
 typedef struct {
     unsigned long long value;
@@ -205,6 +383,54 @@ static double hist_local(const uint8_t *data,
     return t1 - t0;
 }
 
+// Strategy 3: stripe s guards bins [ceil(s * BINS / stripes),
+// ceil((s + 1) * BINS / stripes)), so bin v takes lock v * stripes / BINS
+static double hist_striped(const uint8_t *data,
+                           unsigned long long *hist,
+                           long long N,
+                           int T,
+                           int stripes,
+                           int use_affinity) {
+    for (int b = 0; b < BINS; ++b) hist[b] = 0ULL;
+
+    omp_lock_t locks[BINS];
+    for (int s = 0; s < stripes; ++s) omp_init_lock(&locks[s]);
+
+    omp_set_num_threads(T);
+
+    double t0 = omp_get_wtime();
+
+    // native proc_bind for thread affinity
+    if (use_affinity) {
+        #pragma omp parallel proc_bind(close)
+        {
+            #pragma omp for schedule(runtime)
+            for (long long i = 0; i < N; ++i) {
+                uint8_t v = data[i];
+                int s = v * stripes / BINS;
+                omp_set_lock(&locks[s]);
+                hist[v] += 1ULL;
+                omp_unset_lock(&locks[s]);
+            }
+        }
+    } else {
+        #pragma omp parallel
+        {
+            #pragma omp for schedule(runtime)
+            for (long long i = 0; i < N; ++i) {
+                uint8_t v = data[i];
+                int s = v * stripes / BINS;
+                omp_set_lock(&locks[s]);
+                hist[v] += 1ULL;
+                omp_unset_lock(&locks[s]);
+            }
+        }
+    }
+
+    double t1 = omp_get_wtime();
+    for (int s = 0; s < stripes; ++s) omp_destroy_lock(&locks[s]);
+    return t1 - t0;
+}
 
 static int check_correct(const unsigned long long *hist, long long N) {
     unsigned long long total = 0;
@@ -214,35 +440,139 @@ static int check_correct(const unsigned long long *hist, long long N) {
     return (total == (unsigned long long)N) ? 1 : 0;
 }
 
+#ifdef BENCH_FFI
+// One timed histogram of data[0..N) into hist[BINS] with T threads and a
+// static schedule of the given chunk (0 = runtime default); `strategy` is 1
+// for local, 2 for striped with `stripes` locks, else atomic, with padded
+// bins when `pad` is set.
+double bench_hist(const uint8_t *data, unsigned long long *hist, long long N,
+                  int T, int strategy, int pad, int stripes, int chunk, int affinity) {
+    omp_set_schedule(omp_sched_static, chunk < 0 ? 0 : chunk);
+    if (strategy == 1) {
+        return hist_local(data, hist, N, T, affinity);
+    }
+    if (strategy == 2) {
+        return hist_striped(data, hist, N, T, stripes, affinity);
+    }
+    if (!pad) {
+        return hist_atomic(data, hist, N, T, affinity);
+    }
+    padded_bin_t hist_padded[BINS];
+    double elapsed = hist_atomic_padded(data, hist_padded, N, T, affinity);
+    for (int b = 0; b < BINS; ++b) {
+        hist[b] = hist_padded[b].value;
+    }
+    return elapsed;
+}
+#else
+// The affinity argument, normalized to its name in *affinity: 1 for compact,
+// which the kernels bind with proc_bind(close), 0 otherwise, -1 when it is
+// unknown. OMP_PROC_BIND and OMP_PLACES are read once, when the runtime
+// starts, so for scatter and list they are set here and the program started
+// again, unless they already hold those values.
+static int bind_threads(const char **affinity, char **argv) {
+    const char *a = *affinity;
+    if (strcmp(a, "none") == 0 || strcmp(a, "0") == 0) {
+        *affinity = "none";
+        return 0;
+    }
+    if (strcmp(a, "compact") == 0 || strcmp(a, "1") == 0) {
+        *affinity = "compact";
+        return 1;
+    }
+
+    char places[4096] = "threads";
+    const char *bind = "spread";
+    if (strncmp(a, "list:", 5) == 0) {
+        // list:0,2,4 -> {0},{2},{4}
+        size_t len = 0;
+        for (const char *cpu = a + 5;;) {
+            char *end;
+            unsigned long id = strtoul(cpu, &end, 10);
+            if (end == cpu || (*end != ',' && *end != '\0')) return -1;
+            int written = snprintf(places + len, sizeof places - len, "%s{%lu}", len ? "," : "", id);
+            if (written < 0 || (size_t)written >= sizeof places - len) return -1;
+            len += (size_t)written;
+            if (*end == '\0') break;
+            cpu = end + 1;
+        }
+        bind = "close";
+    } else if (strcmp(a, "scatter") != 0) {
+        return -1;
+    }
+
+    const char *env_bind = getenv("OMP_PROC_BIND");
+    const char *env_places = getenv("OMP_PLACES");
+    if (env_bind && env_places && strcmp(env_bind, bind) == 0 && strcmp(env_places, places) == 0) {
+        return 0;
+    }
+    setenv("OMP_PROC_BIND", bind, 1);
+    setenv("OMP_PLACES", places, 1);
+    execv("/proc/self/exe", argv);
+    perror("execv");
+    return -1;
+}
+
 int main(int argc, char **argv) {
     if (argc < 5) {
         fprintf(stderr,
-                "usage: %s <strategy> <dist> <N> <T> [sched] [chunk] [pad]\n"
-                "  strategy: atomic | local\n"
-                "  dist:     uniform | skewed\n"
+                "usage: %s <strategy> <dist> <N> <T> [sched] [chunk] [pad] [affinity] [dump] [seed] [stripes]\n"
+                "  strategy: atomic | local | striped | manual | scoped\n"
+                "  dist:     uniform | skewed | zipf[:s] | gaussian[:mu:sigma] | file:path\n"
                 "  N:        number of elements (e.g. 10000000)\n"
                 "  T:        threads (e.g. 1,2,4,8,16)\n"
                 "  sched:    static | dynamic | guided (default: static)\n"
                 "  chunk:    chunk size (0 = runtime default)\n"
-                "  pad:      0 | 1 (atomic only; 1 = padded bins)\n",
+                "  pad:      0 | 1 (atomic only; 1 = padded bins)\n"
+                "  affinity: none | compact | scatter | list:C,C,... (0, 1: none, compact)\n"
+                "  dump:     file for the bins as a result dump (- for none)\n"
+                "  seed:     input seed (default per distribution)\n"
+                "  stripes:  striped only; locks over groups of bins (default 16)\n",
                 argv[0]);
         return 1;
     }
 
-    const char *strategy = argv[1];   // "atomic" or "local"
-    const char *dist     = argv[2];   // "uniform" or "skewed"
+    const char *strategy = argv[1];   // "atomic", "local", "striped", "manual" or "scoped"
+    const char *dist     = argv[2];   // "uniform", "skewed", "zipf[:s]", "gaussian[:mu:sigma]" or "file:path"
     long long N          = atoll(argv[3]);
     int T                = atoi(argv[4]);
 
     const char *sched = (argc > 5) ? argv[5] : "static";
     int chunk         = (argc > 6) ? atoi(argv[6]) : 0;
     int pad           = (argc > 7) ? atoi(argv[7]) : 0;
-    int affinity      = (argc > 8) ? atoi(argv[8]) : 0;
+    const char *affinity_name = (argc > 8) ? argv[8] : "none";
+    const char *dump  = (argc > 9 && strcmp(argv[9], "-") != 0) ? argv[9] : NULL;
+
+    dist_t d;
+    if (!parse_dist(dist, &d)) {
+        fprintf(stderr, "unknown dist: %s (use uniform|skewed|zipf[:s]|gaussian[:mu:sigma]|file:path, s and sigma > 0)\n", dist);
+        return 1;
+    }
+    uint64_t seed = (argc > 10) ? strtoull(argv[10], NULL, 10) : d.seed;
+    int stripes   = (argc > 11) ? atoi(argv[11]) : DEFAULT_STRIPES;
+    // the records spell out the default parameters, as the Rust ones do
+    if (strcmp(dist, "zipf") == 0) dist = "zipf:1";
+    if (strcmp(dist, "gaussian") == 0) dist = "gaussian:128:32";
+    // a file input is recorded as dist=file, seed=0, as the Rust --input run
+    if (d.kind == 'f') {
+        dist = "file";
+        seed = 0;
+    }
 
     if (N <= 0 || T <= 0) {
         fprintf(stderr, "N and T must be positive.\n");
         return 1;
     }
+    int affinity = bind_threads(&affinity_name, argv);
+    if (affinity < 0) {
+        fprintf(stderr, "unknown affinity: %s (use none|compact|scatter|list:C,C,...)\n", affinity_name);
+        return 1;
+    }
+    // the other strategies record stripes=0, and the runner passes that on
+    if (strcmp(strategy, "striped") == 0 && (stripes < 1 || stripes > BINS)) {
+        fprintf(stderr, "stripes must be in 1..%d.\n", BINS);
+        return 1;
+    }
 
     // configure OpenMP schedule 
     if (chunk < 0) chunk = 0;
@@ -266,17 +596,17 @@ int main(int argc, char **argv) {
     }
 
     //input data 
-    if (strcmp(dist, "uniform") == 0) {
-        gen_uniform(data, N);
-    } else if (strcmp(dist, "skewed") == 0) {
-        gen_skewed(data, N);
+    if (d.kind == 'f') {
+        if (!read_input(data, N, d.path)) {
+            free(data);
+            return 2;
+        }
     } else {
-        fprintf(stderr, "unknown dist: %s (use uniform|skewed)\n", dist);
-        free(data);
-        return 1;
+        generate(data, N, &d, seed);
     }
 
     double elapsed = 0.0;
+    if (strcmp(strategy, "striped") != 0) stripes = 0; // stripes only lock the striped bins
     if (strcmp(strategy, "atomic") == 0) {
         if (pad) {
             elapsed = hist_atomic_padded(data, hist_padded, N, T, affinity);
@@ -287,21 +617,32 @@ int main(int argc, char **argv) {
         } else {
             elapsed = hist_atomic(data, hist, N, T, affinity);
         }
-    } else if (strcmp(strategy, "local") == 0) {
+    } else if (strcmp(strategy, "local") == 0 || strcmp(strategy, "manual") == 0 || strcmp(strategy, "scoped") == 0) {
         pad = 0; // ignore pad in local strategy
         elapsed = hist_local(data, hist, N, T, affinity);
+    } else if (strcmp(strategy, "striped") == 0) {
+        pad = 0; // ignore pad in striped strategy
+        elapsed = hist_striped(data, hist, N, T, stripes, affinity);
     } else {
-        fprintf(stderr, "unknown strategy: %s (use atomic|local)\n", strategy);
+        fprintf(stderr, "unknown strategy: %s (use atomic|local|striped|manual|scoped)\n", strategy);
         free(data);
         return 1;
     }
 
     int correct = check_correct(hist, N);
-    printf("hist,openmp,strategy=%s,dist=%s,N=%lld,T=%d,sched=%s,chunk=%d,pad=%d,affinity=%d,time,%.6f,sec\n",
-           strategy, dist, N, T, sched, chunk, pad, affinity, elapsed);
-    printf("hist,openmp,strategy=%s,dist=%s,N=%lld,T=%d,sched=%s,chunk=%d,pad=%d,affinity=%d,correct,%d,boolean\n",
-           strategy, dist, N, T, sched, chunk, pad, affinity, correct);
+    printf("hist,openmp,strategy=%s,dist=%s,N=%lld,T=%d,sched=%s,chunk=%d,pad=%d,stripes=%d,affinity=%s,seed=%llu,time,%.6f,sec\n",
+           strategy, dist, N, T, sched, chunk, pad, stripes, affinity_name, (unsigned long long)seed, elapsed);
+    printf("hist,openmp,strategy=%s,dist=%s,N=%lld,T=%d,sched=%s,chunk=%d,pad=%d,stripes=%d,affinity=%s,seed=%llu,correct,%d,boolean\n",
+           strategy, dist, N, T, sched, chunk, pad, stripes, affinity_name, (unsigned long long)seed, correct);
+    printf("hist,openmp,strategy=%s,dist=%s,N=%lld,T=%d,sched=%s,chunk=%d,pad=%d,stripes=%d,affinity=%s,seed=%llu,peak_rss,%lld,bytes\n",
+           strategy, dist, N, T, sched, chunk, pad, stripes, affinity_name, (unsigned long long)seed, peak_rss_bytes());
 
     free(data);
+    if (dump) {
+        uint64_t bins[BINS];
+        for (int b = 0; b < BINS; ++b) bins[b] = hist[b];
+        if (!write_dump(dump, 1, (uint64_t)N, bins, BINS)) return 2;
+    }
     return correct ? 0 : 3;
 }
+#endif
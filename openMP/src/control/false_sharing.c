@@ -0,0 +1,199 @@
+// False-sharing benchmark for "amount of control" (OpenMP version)
+// T threads each increment a counter of their own N times, with the
+// counters side by side (adjacent, eight to a 64-byte line) or one per
+// cache line (padded), as rust/src/controllability/false_sharing.rs does.
+// Both layouts are timed; the adjacent record adds slowdown, its time over
+// the padded time.
+//
+// Usage:
+//   ./false_sharing_openmp <update> <N> <T> [affinity]
+//   update:   plain (counts[t] += 1 through a volatile pointer) |
+//             atomic (#pragma omp atomic, relaxed)
+//   N:        increments per thread (e.g., 10000000)
+//   T:        number of threads (e.g., 1,2,4,8,16)
+//   affinity: none | compact | scatter | list:C,C,... as for control_openmp:
+//             compact is proc_bind(close); scatter runs with
+//             OMP_PROC_BIND=spread, OMP_PLACES=threads and list with
+//             OMP_PROC_BIND=close, OMP_PLACES={C},{C},...
+//
+// Output (CSV-style):
+//   false_sharing,openmp,update=plain,layout=adjacent,N=10000000,T=8,affinity=none,time,0.123456,sec
+//   false_sharing,openmp,update=plain,layout=adjacent,N=10000000,T=8,affinity=none,per,12.345,ns
+//   false_sharing,openmp,update=plain,layout=adjacent,N=10000000,T=8,affinity=none,slowdown,8.12,x
+//   false_sharing,openmp,update=plain,layout=adjacent,N=10000000,T=8,affinity=none,correct,1,boolean
+//   ... the same without slowdown for layout=padded, then
+//   false_sharing,openmp,update=plain,N=10000000,T=8,affinity=none,peak_rss,12345678,bytes
+
+#define _POSIX_C_SOURCE 200809L  // setenv
+
+#include <omp.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/resource.h>
+#include <unistd.h>
+
+// counters per 64-byte line (crunchy1), the stride of the padded layout
+#define LINE_WORDS 8
+
+// Peak resident set size of the whole process in bytes (Linux reports
+// ru_maxrss in KiB), to set beside the Rust binaries' peak_rss
+static long long peak_rss_bytes(void) {
+    struct rusage ru;
+    if (getrusage(RUSAGE_SELF, &ru) != 0) return -1;
+    return (long long)ru.ru_maxrss * 1024;
+}
+
+// The affinity argument, normalized to its name in *affinity: 1 for compact,
+// bound with proc_bind(close), 0 otherwise, -1 when it is unknown. For
+// scatter and list, OMP_PROC_BIND and OMP_PLACES are set and the program
+// started again (as control.c does).
+static int bind_threads(const char **affinity, char **argv) {
+    const char *a = *affinity;
+    if (strcmp(a, "none") == 0 || strcmp(a, "0") == 0) {
+        *affinity = "none";
+        return 0;
+    }
+    if (strcmp(a, "compact") == 0 || strcmp(a, "1") == 0) {
+        *affinity = "compact";
+        return 1;
+    }
+
+    char places[4096] = "threads";
+    const char *bind = "spread";
+    if (strncmp(a, "list:", 5) == 0) {
+        // list:0,2,4 -> {0},{2},{4}
+        size_t len = 0;
+        for (const char *cpu = a + 5;;) {
+            char *end;
+            unsigned long id = strtoul(cpu, &end, 10);
+            if (end == cpu || (*end != ',' && *end != '\0')) return -1;
+            int written = snprintf(places + len, sizeof places - len, "%s{%lu}", len ? "," : "", id);
+            if (written < 0 || (size_t)written >= sizeof places - len) return -1;
+            len += (size_t)written;
+            if (*end == '\0') break;
+            cpu = end + 1;
+        }
+        bind = "close";
+    } else if (strcmp(a, "scatter") != 0) {
+        return -1;
+    }
+
+    const char *env_bind = getenv("OMP_PROC_BIND");
+    const char *env_places = getenv("OMP_PLACES");
+    if (env_bind && env_places && strcmp(env_bind, bind) == 0 && strcmp(env_places, places) == 0) {
+        return 0;
+    }
+    setenv("OMP_PROC_BIND", bind, 1);
+    setenv("OMP_PLACES", places, 1);
+    execv("/proc/self/exe", argv);
+    perror("execv");
+    return -1;
+}
+
+// thread t increments counts[t * stride] N times
+static void increment(unsigned long long *counts, int stride, long long N, int atomic) {
+    unsigned long long *mine = &counts[omp_get_thread_num() * stride];
+    if (atomic) {
+        for (long long r = 0; r < N; ++r) {
+            #pragma omp atomic
+            *mine += 1ULL;
+        }
+    } else {
+        // volatile: one load and one store per increment, as the Rust
+        // relaxed load and store
+        volatile unsigned long long *count = mine;
+        for (long long r = 0; r < N; ++r) {
+            *count = *count + 1ULL;
+        }
+    }
+}
+
+// one layout: the T counters `stride` words apart; returns the seconds, or
+// -1 when the counters cannot be allocated or a count is wrong
+static double run_layout(int stride, long long N, int T, int atomic, int compact, int *correct) {
+    unsigned long long *counts = (unsigned long long*) calloc((size_t)T * stride, sizeof(unsigned long long));
+    if (!counts) return -1.0;
+    omp_set_num_threads(T);
+
+    double t0 = omp_get_wtime();
+    if (compact) {
+        #pragma omp parallel proc_bind(close)
+        increment(counts, stride, N, atomic);
+    } else {
+        #pragma omp parallel
+        increment(counts, stride, N, atomic);
+    }
+    double elapsed = omp_get_wtime() - t0;
+
+    *correct = 1;
+    for (int t = 0; t < T; ++t) *correct = *correct && counts[t * stride] == (unsigned long long)N;
+    free(counts);
+    return elapsed;
+}
+
+int main(int argc, char **argv) {
+    if (argc < 4) {
+        fprintf(stderr,
+                "usage: %s <update> <N> <T> [affinity]\n"
+                "  update:   plain | atomic\n"
+                "  N:        increments per thread (e.g. 10000000)\n"
+                "  T:        threads (e.g. 1,2,4,8,16)\n"
+                "  affinity: none | compact | scatter | list:C,C,... (0, 1: none, compact)\n",
+                argv[0]);
+        return 1;
+    }
+
+    const char *update = argv[1];
+    long long N        = atoll(argv[2]);
+    int T              = atoi(argv[3]);
+    const char *affinity_name = (argc > 4) ? argv[4] : "none";
+
+    if (N <= 0 || T <= 0) {
+        fprintf(stderr, "N and T must be positive.\n");
+        return 1;
+    }
+    if (strcmp(update, "plain") != 0 && strcmp(update, "atomic") != 0) {
+        fprintf(stderr, "unknown update: %s (use plain|atomic)\n", update);
+        return 1;
+    }
+    int compact = bind_threads(&affinity_name, argv);
+    if (compact < 0) {
+        fprintf(stderr, "unknown affinity: %s (use none|compact|scatter|list:C,C,...)\n", affinity_name);
+        return 1;
+    }
+    int atomic = strcmp(update, "atomic") == 0;
+
+    // warm-up: start the team once, outside the timed regions
+    omp_set_num_threads(T);
+    #pragma omp parallel
+    {
+    }
+
+    int adjacent_ok, padded_ok;
+    double adjacent = run_layout(1, N, T, atomic, compact, &adjacent_ok);
+    double padded = run_layout(LINE_WORDS, N, T, atomic, compact, &padded_ok);
+    if (adjacent < 0 || padded < 0) {
+        fprintf(stderr, "Memory allocation failed\n");
+        return 2;
+    }
+
+    printf("false_sharing,openmp,update=%s,layout=adjacent,N=%lld,T=%d,affinity=%s,time,%.6f,sec\n",
+           update, N, T, affinity_name, adjacent);
+    printf("false_sharing,openmp,update=%s,layout=adjacent,N=%lld,T=%d,affinity=%s,per,%.3f,ns\n",
+           update, N, T, affinity_name, adjacent / (double)N * 1e9);
+    printf("false_sharing,openmp,update=%s,layout=adjacent,N=%lld,T=%d,affinity=%s,slowdown,%.2f,x\n",
+           update, N, T, affinity_name, adjacent / padded);
+    printf("false_sharing,openmp,update=%s,layout=adjacent,N=%lld,T=%d,affinity=%s,correct,%d,boolean\n",
+           update, N, T, affinity_name, adjacent_ok);
+    printf("false_sharing,openmp,update=%s,layout=padded,N=%lld,T=%d,affinity=%s,time,%.6f,sec\n",
+           update, N, T, affinity_name, padded);
+    printf("false_sharing,openmp,update=%s,layout=padded,N=%lld,T=%d,affinity=%s,per,%.3f,ns\n",
+           update, N, T, affinity_name, padded / (double)N * 1e9);
+    printf("false_sharing,openmp,update=%s,layout=padded,N=%lld,T=%d,affinity=%s,correct,%d,boolean\n",
+           update, N, T, affinity_name, padded_ok);
+    printf("false_sharing,openmp,update=%s,N=%lld,T=%d,affinity=%s,peak_rss,%lld,bytes\n",
+           update, N, T, affinity_name, peak_rss_bytes());
+
+    return adjacent_ok && padded_ok ? 0 : 3;
+}
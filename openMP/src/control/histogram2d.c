@@ -0,0 +1,190 @@
+// 2D histogram benchmark for "amount of control" (OpenMP version)
+// N pairs (x, y) of bytes binned into a 256 x 256 grid, cell x * 256 + y,
+// as rust/src/controllability/histogram2d.rs does.
+// Strategies:
+//   1) G-Atomic: single shared grid with #pragma omp atomic
+//   2) TL-Local: a grid per thread, merged cell by cell in parallel
+//
+// Usage:
+//   ./hist2d_openmp <strategy> <dist> <N> <T> [chunk] [seed]
+//   strategy: atomic | local
+//   dist:     uniform | diagonal
+//             x and y are bytes 4 and 5 of SplitMix64 value i of the seed;
+//             diagonal replaces y by x + (y mod 17) - 8, wrapped
+//   N:        number of pairs (e.g., 10000000)
+//   T:        number of threads (e.g., 1,2,4,8,16)
+//   chunk:    static schedule chunk size (0 = runtime default)
+//   seed:     input seed (default 271828182), as the Rust --seed
+//
+// Output (CSV-style):
+//   hist2d,openmp,strategy=atomic,dist=uniform,N=10000000,T=8,chunk=0,seed=271828182,time,0.123456,sec
+//   hist2d,openmp,strategy=atomic,dist=uniform,N=10000000,T=8,chunk=0,seed=271828182,correct,1,boolean
+//   hist2d,openmp,strategy=atomic,dist=uniform,N=10000000,T=8,chunk=0,seed=271828182,peak_rss,12345678,bytes
+
+#include <omp.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <stdint.h>
+#include <string.h>
+#include <sys/resource.h>
+
+#define SIDE 256
+#define CELLS (SIDE * SIDE)
+#define BAND 8
+#define DEFAULT_SEED 271828182ull
+
+// Peak resident set size of the whole process in bytes (Linux reports
+// ru_maxrss in KiB), to set beside the Rust binaries' peak_rss
+static long long peak_rss_bytes(void) {
+    struct rusage ru;
+    if (getrusage(RUSAGE_SELF, &ru) != 0) return -1;
+    return (long long)ru.ru_maxrss * 1024;
+}
+
+// value i of the SplitMix64 stream `seed` (rust/src/rng.rs)
+static inline uint64_t splitmix64_at(uint64_t seed, uint64_t i) {
+    uint64_t z = seed + (i + 1) * 0x9E3779B97F4A7C15ull;
+    z = (z ^ (z >> 30)) * 0xBF58476D1CE4E5B9ull;
+    z = (z ^ (z >> 27)) * 0x94D049BB133111EBull;
+    return z ^ (z >> 31);
+}
+
+static void gen_pairs(uint8_t *xs, uint8_t *ys, long long N, int diagonal, uint64_t seed) {
+    #pragma omp parallel for schedule(static)
+    for (long long i = 0; i < N; ++i) {
+        uint64_t r = splitmix64_at(seed, (uint64_t)i);
+        uint8_t x = (uint8_t)(r >> 32);
+        uint8_t y = (uint8_t)(r >> 40);
+        xs[i] = x;
+        ys[i] = diagonal ? (uint8_t)(x + y % (2 * BAND + 1) - BAND) : y;
+    }
+}
+
+// Strategy 1: Global histogram with atomic updates
+static double hist2d_atomic(const uint8_t *xs, const uint8_t *ys, unsigned long long *grid, long long N, int T) {
+    omp_set_num_threads(T);
+
+    double t0 = omp_get_wtime();
+    for (long long c = 0; c < CELLS; ++c) grid[c] = 0ULL;
+
+    #pragma omp parallel for schedule(runtime)
+    for (long long i = 0; i < N; ++i) {
+        #pragma omp atomic
+        grid[xs[i] * SIDE + ys[i]] += 1ULL;
+    }
+    return omp_get_wtime() - t0;
+}
+
+// Strategy 2: a grid per thread, then every thread sums a share of the cells
+// over all grids; -1 when the grids cannot be allocated
+static double hist2d_local(const uint8_t *xs, const uint8_t *ys, unsigned long long *grid, long long N, int T) {
+    omp_set_num_threads(T);
+    unsigned long long **locals = (unsigned long long**) calloc((size_t)T, sizeof(unsigned long long*));
+    if (!locals) return -1.0;
+
+    double t0 = omp_get_wtime();
+
+    // 512 KiB each, too big for the stack; calloc maps fresh zero pages, so
+    // each grid is first touched by the thread counting into it
+    int failed = 0;
+    for (int t = 0; t < T; ++t) {
+        locals[t] = (unsigned long long*) calloc(CELLS, sizeof(unsigned long long));
+        failed = failed || !locals[t];
+    }
+
+    if (!failed) {
+        #pragma omp parallel
+        {
+            unsigned long long *local = locals[omp_get_thread_num()];
+
+            #pragma omp for schedule(runtime)
+            for (long long i = 0; i < N; ++i) {
+                local[xs[i] * SIDE + ys[i]] += 1ULL;
+            }
+
+            // merge, after the loop's implicit barrier
+            #pragma omp for schedule(static)
+            for (long long c = 0; c < CELLS; ++c) {
+                unsigned long long sum = 0ULL;
+                for (int t = 0; t < T; ++t) sum += locals[t][c];
+                grid[c] = sum;
+            }
+        }
+    }
+    double elapsed = omp_get_wtime() - t0;
+
+    for (int t = 0; t < T; ++t) free(locals[t]);
+    free(locals);
+    return failed ? -1.0 : elapsed;
+}
+
+int main(int argc, char **argv) {
+    if (argc < 5) {
+        fprintf(stderr,
+                "usage: %s <strategy> <dist> <N> <T> [chunk] [seed]\n"
+                "  strategy: atomic | local\n"
+                "  dist:     uniform | diagonal\n"
+                "  N:        number of pairs (e.g. 10000000)\n"
+                "  T:        threads (e.g. 1,2,4,8,16)\n"
+                "  chunk:    static schedule chunk size (0 = runtime default)\n"
+                "  seed:     input seed (default 271828182)\n",
+                argv[0]);
+        return 1;
+    }
+
+    const char *strategy = argv[1];
+    const char *dist     = argv[2];
+    long long N          = atoll(argv[3]);
+    int T                = atoi(argv[4]);
+    int chunk            = (argc > 5) ? atoi(argv[5]) : 0;
+    uint64_t seed        = (argc > 6) ? strtoull(argv[6], NULL, 10) : DEFAULT_SEED;
+
+    if (N <= 0 || T <= 0) {
+        fprintf(stderr, "N and T must be positive.\n");
+        return 1;
+    }
+    if (strcmp(strategy, "atomic") != 0 && strcmp(strategy, "local") != 0) {
+        fprintf(stderr, "unknown strategy: %s (use atomic|local)\n", strategy);
+        return 1;
+    }
+    if (strcmp(dist, "uniform") != 0 && strcmp(dist, "diagonal") != 0) {
+        fprintf(stderr, "unknown dist: %s (use uniform|diagonal)\n", dist);
+        return 1;
+    }
+    if (chunk < 0) chunk = 0;
+    omp_set_schedule(omp_sched_static, chunk);
+    omp_set_num_threads(T);
+
+    uint8_t *xs = (uint8_t*) malloc((size_t)N);
+    uint8_t *ys = (uint8_t*) malloc((size_t)N);
+    unsigned long long *grid = (unsigned long long*) malloc(CELLS * sizeof(unsigned long long));
+    unsigned long long *expected = (unsigned long long*) calloc(CELLS, sizeof(unsigned long long));
+    if (!xs || !ys || !grid || !expected) {
+        fprintf(stderr, "Memory allocation failed\n");
+        return 2;
+    }
+
+    gen_pairs(xs, ys, N, strcmp(dist, "diagonal") == 0, seed);
+
+    double elapsed = strcmp(strategy, "atomic") == 0
+        ? hist2d_atomic(xs, ys, grid, N, T)
+        : hist2d_local(xs, ys, grid, N, T);
+    if (elapsed < 0) {
+        fprintf(stderr, "Memory allocation failed\n");
+        return 2;
+    }
+
+    // against a sequential count
+    for (long long i = 0; i < N; ++i) expected[xs[i] * SIDE + ys[i]] += 1ULL;
+    int correct = memcmp(grid, expected, CELLS * sizeof(unsigned long long)) == 0;
+
+    printf("hist2d,openmp,strategy=%s,dist=%s,N=%lld,T=%d,chunk=%d,seed=%llu,time,%.6f,sec\n",
+           strategy, dist, N, T, chunk, (unsigned long long)seed, elapsed);
+    printf("hist2d,openmp,strategy=%s,dist=%s,N=%lld,T=%d,chunk=%d,seed=%llu,correct,%d,boolean\n",
+           strategy, dist, N, T, chunk, (unsigned long long)seed, correct);
+    printf("hist2d,openmp,strategy=%s,dist=%s,N=%lld,T=%d,chunk=%d,seed=%llu,peak_rss,%lld,bytes\n",
+           strategy, dist, N, T, chunk, (unsigned long long)seed, peak_rss_bytes());
+
+    free(xs); free(ys); free(grid); free(expected);
+    return correct ? 0 : 3;
+}
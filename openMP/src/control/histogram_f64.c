@@ -0,0 +1,236 @@
+// Floating-point histogram benchmark for "amount of control" (OpenMP version)
+// N doubles in [0, 1) binned by range lookup over uniform or user-supplied
+// bin edges, as rust/src/controllability/histogram_f64.rs does.
+// Strategies:
+//   1) G-Atomic: single shared histogram with #pragma omp atomic
+//   2) TL-Local: a histogram per thread, merged under a critical section
+//
+// Usage:
+//   ./fhist_openmp <strategy> <N> <T> <bins> <edges> [chunk] [seed]
+//   strategy: atomic | local
+//   N:        number of values (e.g., 10000000); value i is the top 53 bits of
+//             SplitMix64 value i of the seed, scaled to [0, 1)
+//   T:        number of threads (e.g., 1,2,4,8,16)
+//   bins:     equal-width bins over [0, 1) when edges is uniform
+//   edges:    uniform, or colon-separated increasing edges e0:e1:...:ek (bins
+//             is then k; values outside [e0, ek) are not counted)
+//   chunk:    static schedule chunk size (0 = runtime default)
+//   seed:     input seed (default 161803398), as the Rust --seed
+//
+// Output (CSV-style):
+//   fhist,openmp,strategy=atomic,edges=uniform,bins=256,N=10000000,T=8,chunk=0,seed=161803398,time,0.123456,sec
+//   fhist,openmp,strategy=atomic,edges=uniform,bins=256,N=10000000,T=8,chunk=0,seed=161803398,correct,1,boolean
+//   fhist,openmp,strategy=atomic,edges=uniform,bins=256,N=10000000,T=8,chunk=0,seed=161803398,peak_rss,12345678,bytes
+
+#include <omp.h>
+#include <math.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <stdint.h>
+#include <string.h>
+#include <sys/resource.h>
+
+#define DEFAULT_SEED 161803398ull
+
+// Peak resident set size of the whole process in bytes (Linux reports
+// ru_maxrss in KiB), to set beside the Rust binaries' peak_rss
+static long long peak_rss_bytes(void) {
+    struct rusage ru;
+    if (getrusage(RUSAGE_SELF, &ru) != 0) return -1;
+    return (long long)ru.ru_maxrss * 1024;
+}
+
+// value i of the SplitMix64 stream `seed` (rust/src/rng.rs)
+static inline uint64_t splitmix64_at(uint64_t seed, uint64_t i) {
+    uint64_t z = seed + (i + 1) * 0x9E3779B97F4A7C15ull;
+    z = (z ^ (z >> 30)) * 0xBF58476D1CE4E5B9ull;
+    z = (z ^ (z >> 27)) * 0x94D049BB133111EBull;
+    return z ^ (z >> 31);
+}
+
+// the bins: `nbins` equal ones over [0, 1) when edges is NULL, else
+// [edges[i], edges[i + 1]) for i < nbins
+typedef struct {
+    long nbins;
+    const double *edges;
+} bins_t;
+
+// the bin of v, or -1 outside the edges; a binary search for the last edge
+// <= v, like the Rust partition_point
+static inline long find_bin(const bins_t *b, double v) {
+    if (!b->edges) {
+        if (!(v >= 0.0 && v < 1.0)) return -1;
+        long bin = (long)(v * (double)b->nbins);
+        return bin < b->nbins ? bin : b->nbins - 1;
+    }
+    if (!(v >= b->edges[0] && v < b->edges[b->nbins])) return -1;
+    long lo = 0, hi = b->nbins + 1;
+    while (lo < hi) {
+        long mid = lo + (hi - lo) / 2;
+        if (b->edges[mid] <= v) lo = mid + 1; else hi = mid;
+    }
+    return lo - 1;
+}
+
+// parses e0:e1:...:ek into a fresh array of k + 1 edges; NULL unless there are
+// at least two finite, strictly increasing ones
+static double *parse_edges(const char *s, long *nbins) {
+    long count = 1;
+    for (const char *p = s; *p; ++p) count += *p == ':';
+    double *edges = (double*) malloc((size_t)count * sizeof(double));
+    if (!edges || count < 2) { free(edges); return NULL; }
+    const char *p = s;
+    for (long i = 0; i < count; ++i) {
+        char *end;
+        edges[i] = strtod(p, &end);
+        if (end == p || (*end != ':' && *end != '\0') || !isfinite(edges[i]) || (i > 0 && edges[i] <= edges[i - 1])) {
+            free(edges);
+            return NULL;
+        }
+        p = end + 1;
+    }
+    *nbins = count - 1;
+    return edges;
+}
+
+static void gen_values(double *values, long long N, uint64_t seed) {
+    #pragma omp parallel for schedule(static)
+    for (long long i = 0; i < N; ++i) {
+        values[i] = (double)(splitmix64_at(seed, (uint64_t)i) >> 11) / 9007199254740992.0;
+    }
+}
+
+// Strategy 1: Global histogram with atomic updates
+static double fhist_atomic(const double *values, const bins_t *b, unsigned long long *hist, long long N, int T) {
+    omp_set_num_threads(T);
+
+    double t0 = omp_get_wtime();
+    for (long i = 0; i < b->nbins; ++i) hist[i] = 0ULL;
+
+    #pragma omp parallel for schedule(runtime)
+    for (long long i = 0; i < N; ++i) {
+        long bin = find_bin(b, values[i]);
+        if (bin >= 0) {
+            #pragma omp atomic
+            hist[bin] += 1ULL;
+        }
+    }
+    return omp_get_wtime() - t0;
+}
+
+// Strategy 2: a histogram per thread, added to the shared one at the end; -1
+// when a thread cannot allocate its histogram
+static double fhist_local(const double *values, const bins_t *b, unsigned long long *hist, long long N, int T) {
+    omp_set_num_threads(T);
+    int failed = 0;
+
+    double t0 = omp_get_wtime();
+    for (long i = 0; i < b->nbins; ++i) hist[i] = 0ULL;
+
+    #pragma omp parallel
+    {
+        unsigned long long *local = (unsigned long long*) calloc((size_t)b->nbins, sizeof(unsigned long long));
+        if (!local) {
+            #pragma omp atomic write
+            failed = 1;
+        }
+
+        #pragma omp for schedule(runtime)
+        for (long long i = 0; i < N; ++i) {
+            long bin = find_bin(b, values[i]);
+            if (bin >= 0 && local) local[bin] += 1ULL;
+        }
+
+        if (local) {
+            #pragma omp critical
+            for (long i = 0; i < b->nbins; ++i) hist[i] += local[i];
+        }
+        free(local);
+    }
+    double elapsed = omp_get_wtime() - t0;
+    return failed ? -1.0 : elapsed;
+}
+
+int main(int argc, char **argv) {
+    if (argc < 6) {
+        fprintf(stderr,
+                "usage: %s <strategy> <N> <T> <bins> <edges> [chunk] [seed]\n"
+                "  strategy: atomic | local\n"
+                "  N:        number of values (e.g. 10000000)\n"
+                "  T:        threads (e.g. 1,2,4,8,16)\n"
+                "  bins:     equal-width bins over [0, 1) (e.g. 256)\n"
+                "  edges:    uniform | e0:e1:...:ek (then bins is k)\n"
+                "  chunk:    static schedule chunk size (0 = runtime default)\n"
+                "  seed:     input seed (default 161803398)\n",
+                argv[0]);
+        return 1;
+    }
+
+    const char *strategy = argv[1];
+    long long N          = atoll(argv[2]);
+    int T                = atoi(argv[3]);
+    long nbins           = atol(argv[4]);
+    const char *edges_s  = argv[5];
+    int chunk            = (argc > 6) ? atoi(argv[6]) : 0;
+    uint64_t seed        = (argc > 7) ? strtoull(argv[7], NULL, 10) : DEFAULT_SEED;
+
+    if (N <= 0 || T <= 0) {
+        fprintf(stderr, "N and T must be positive.\n");
+        return 1;
+    }
+    if (strcmp(strategy, "atomic") != 0 && strcmp(strategy, "local") != 0) {
+        fprintf(stderr, "unknown strategy: %s (use atomic|local)\n", strategy);
+        return 1;
+    }
+    bins_t b = { nbins, NULL };
+    double *edges = NULL;
+    if (strcmp(edges_s, "uniform") != 0) {
+        edges = parse_edges(edges_s, &b.nbins);
+        if (!edges) {
+            fprintf(stderr, "bad edges: %s (use uniform or increasing e0:e1:...:ek)\n", edges_s);
+            return 1;
+        }
+        b.edges = edges;
+    } else if (nbins <= 0) {
+        fprintf(stderr, "bins must be positive.\n");
+        return 1;
+    }
+    if (chunk < 0) chunk = 0;
+    omp_set_schedule(omp_sched_static, chunk);
+    omp_set_num_threads(T);
+
+    double *values = (double*) malloc((size_t)N * sizeof(double));
+    unsigned long long *hist = (unsigned long long*) malloc((size_t)b.nbins * sizeof(unsigned long long));
+    unsigned long long *expected = (unsigned long long*) calloc((size_t)b.nbins, sizeof(unsigned long long));
+    if (!values || !hist || !expected) {
+        fprintf(stderr, "Memory allocation failed\n");
+        return 2;
+    }
+
+    gen_values(values, N, seed);
+
+    double elapsed = strcmp(strategy, "atomic") == 0
+        ? fhist_atomic(values, &b, hist, N, T)
+        : fhist_local(values, &b, hist, N, T);
+    if (elapsed < 0) {
+        fprintf(stderr, "Memory allocation failed\n");
+        return 2;
+    }
+
+    // against a sequential count
+    for (long long i = 0; i < N; ++i) {
+        long bin = find_bin(&b, values[i]);
+        if (bin >= 0) expected[bin] += 1ULL;
+    }
+    int correct = memcmp(hist, expected, (size_t)b.nbins * sizeof(unsigned long long)) == 0;
+
+    printf("fhist,openmp,strategy=%s,edges=%s,bins=%ld,N=%lld,T=%d,chunk=%d,seed=%llu,time,%.6f,sec\n",
+           strategy, edges_s, b.nbins, N, T, chunk, (unsigned long long)seed, elapsed);
+    printf("fhist,openmp,strategy=%s,edges=%s,bins=%ld,N=%lld,T=%d,chunk=%d,seed=%llu,correct,%d,boolean\n",
+           strategy, edges_s, b.nbins, N, T, chunk, (unsigned long long)seed, correct);
+    printf("fhist,openmp,strategy=%s,edges=%s,bins=%ld,N=%lld,T=%d,chunk=%d,seed=%llu,peak_rss,%lld,bytes\n",
+           strategy, edges_s, b.nbins, N, T, chunk, (unsigned long long)seed, peak_rss_bytes());
+
+    free(values); free(hist); free(expected); free(edges);
+    return correct ? 0 : 3;
+}
@@ -1,5 +1,18 @@
 // Prefix Sum (exclusive scan) -- OpenMP version (for Programmability benchmark)
 // Setup: N = 10^7, A[i]=1, T = 8; correctness only, no performance timing.
+// Output: one line of key=value pairs, with the peak RSS of the run in bytes.
+// With arguments N and T are taken from them, the scan (phases 1 to 2) is
+// timed, and the output is CSV-style lines in the Rust records' schema (for
+// run_all_benchmarks --openmp-dir):
+//   ./mp_prefix_sum <N> <T> [dump]
+//   prefix_sum,openmp,N=10000000,T=8,par_time,0.012345,sec
+//   prefix_sum,openmp,N=10000000,T=8,correct,1,boolean
+//   prefix_sum,openmp,N=10000000,T=8,peak_rss,161816576,bytes
+// A dump file after them gets the checksum of the inclusive sums (each
+// exclusive one plus its element) as a canonical result dump, to check
+// against prefix_sum --dump FILE, whose scan is inclusive.
+// Compiled with -DBENCH_FFI (rust/build.rs, --features ffi) main() is left
+// out and bench_prefix_sum() scans an input the caller owns.
 // Architecture: Two-phase block-wise scan
 //   1) Each thread performs a serial exclusive scan on its own block and records its total sum in block_sum[tid]
 //   2) The main thread performs a serial prefix sum on block_sum to produce block_off[]
@@ -13,40 +26,68 @@
 #include <stdio.h>
 #include <stdlib.h>
 #include <stdint.h>
+#include <sys/resource.h>
 
-#define N (10000000LL)  // Input array length (fixed at 1e7)
-#define T (8)            // Number of threads (fixed at 8)
+#define DEFAULT_N (10000000LL)  // Input array length (1e7 unless given)
+#define DEFAULT_T (8)            // Number of threads (8 unless given)
 
-int main(void) {
+// Peak resident set size of the whole process in bytes (Linux reports
+// ru_maxrss in KiB), to set beside the Rust binaries' peak_rss
+static long long peak_rss_bytes(void) {
+    struct rusage ru;
+    if (getrusage(RUSAGE_SELF, &ru) != 0) return -1;
+    return (long long)ru.ru_maxrss * 1024;
+}
 
-    omp_set_num_threads(T);
+// Canonical result dump (rust/src/dump.rs, checked with run_all_benchmarks
+// verify): "BENCHDMP", version 1, the kernel id, the size, the value count
+// and the values, all little-endian; 0 when the file cannot be written
+static void put_le(FILE *f, uint64_t v, int bytes) {
+    for (int i = 0; i < bytes; ++i) fputc((int)((v >> (8 * i)) & 0xFF), f);
+}
 
-    long long *in  = (long long*) malloc(sizeof(long long) * N);
-    long long *out = (long long*) malloc(sizeof(long long) * N);
-    if (!in || !out) {
-        fprintf(stderr, "Memory allocation failed\n");
-        return 2;
+static int write_dump(const char *path, uint32_t kernel, uint64_t size,
+                      const uint64_t *values, uint64_t count) {
+    FILE *f = fopen(path, "wb");
+    if (!f) {
+        perror(path);
+        return 0;
     }
+    fwrite("BENCHDMP", 1, 8, f);
+    put_le(f, 1, 4);
+    put_le(f, kernel, 4);
+    put_le(f, size, 8);
+    put_le(f, count, 8);
+    for (uint64_t i = 0; i < count; ++i) put_le(f, values[i], 8);
+    return fclose(f) == 0;
+}
 
-    #pragma omp parallel
-    {
-        int tid = omp_get_thread_num();
-        long long beg = (N * tid) / T;
-        long long end = (N * (tid + 1)) / T;
-        for (long long i = beg; i < end; ++i) {
-            in[i] = 1;
-        }
+// FNV-1a (64-bit) over a word's little-endian bytes, chained from `hash`
+// (FNV_OFFSET to start), like golden::checksum on the Rust side
+#define FNV_OFFSET 0xcbf29ce484222325ull
+static uint64_t fnv1a(uint64_t hash, uint64_t word) {
+    for (int i = 0; i < 8; ++i) {
+        hash ^= (word >> (8 * i)) & 0xFF;
+        hash *= 0x100000001b3ull;
     }
+    return hash;
+}
+
+// The timed scan (phases 1 to 2) of in[0..N) into out with T threads; returns
+// its time in seconds, or -1 when the block arrays cannot be allocated
+static double exclusive_scan(const long long *in, long long *out, long long N, int T) {
+    omp_set_num_threads(T);
 
     // arrays for per-block sums and offsets
     long long *block_sum = (long long*) malloc(sizeof(long long) * T);
     long long *block_off = (long long*) malloc(sizeof(long long) * T);
     if (!block_sum || !block_off) {
-        fprintf(stderr, "Memory allocation failed\n");
-        free(in); free(out);
-        return 2;
+        free(block_sum); free(block_off);
+        return -1.0;
     }
 
+    double t0 = omp_get_wtime();
+
     // Phase 1: Each thread performs an exclusive scan within its own block
     //           and records the total block sum into block_sum[tid]
     #pragma omp parallel
@@ -83,6 +124,61 @@ int main(void) {
             out[i] += off;
         }
     }
+    double par_time = omp_get_wtime() - t0;
+
+    free(block_off); free(block_sum);
+    return par_time;
+}
+
+#ifdef BENCH_FFI
+// Entry point for the Rust benchmarks: the timed scan of an input the caller
+// owns
+double bench_prefix_sum(const long long *in, long long *out, long long N, int T) {
+    return exclusive_scan(in, out, N, T);
+}
+#else
+int main(int argc, char **argv) {
+    long long N = DEFAULT_N;
+    int T = DEFAULT_T;
+    int cell = argc == 3 || argc == 4;
+    const char *dump = argc == 4 ? argv[3] : NULL;
+    if (cell) {
+        N = atoll(argv[1]);
+        T = atoi(argv[2]);
+        if (N <= 0 || T <= 0) {
+            fprintf(stderr, "N and T must be positive.\n");
+            return 1;
+        }
+    } else if (argc != 1) {
+        fprintf(stderr, "usage: %s [<N> <T> [dump]]\n", argv[0]);
+        return 1;
+    }
+
+    omp_set_num_threads(T);
+
+    long long *in  = (long long*) malloc(sizeof(long long) * N);
+    long long *out = (long long*) malloc(sizeof(long long) * N);
+    if (!in || !out) {
+        fprintf(stderr, "Memory allocation failed\n");
+        return 2;
+    }
+
+    #pragma omp parallel
+    {
+        int tid = omp_get_thread_num();
+        long long beg = (N * tid) / T;
+        long long end = (N * (tid + 1)) / T;
+        for (long long i = beg; i < end; ++i) {
+            in[i] = 1;
+        }
+    }
+
+    double par_time = exclusive_scan(in, out, N, T);
+    if (par_time < 0) {
+        fprintf(stderr, "Memory allocation failed\n");
+        free(in); free(out);
+        return 2;
+    }
 
 
     int ok = 1;
@@ -102,9 +198,25 @@ int main(void) {
         }
     }
 
-    printf("bench=scan lang=openmp N=%lld T=%d correct=%d\n", (long long)N, T, ok);
+    if (cell) {
+        printf("prefix_sum,openmp,N=%lld,T=%d,par_time,%.6f,sec\n", N, T, par_time);
+        printf("prefix_sum,openmp,N=%lld,T=%d,correct,%d,boolean\n", N, T, ok);
+        printf("prefix_sum,openmp,N=%lld,T=%d,peak_rss,%lld,bytes\n", N, T, peak_rss_bytes());
+    } else {
+        printf("bench=scan lang=openmp N=%lld T=%d correct=%d peak_rss=%lld\n", N, T, ok, peak_rss_bytes());
+    }
+
+    int written = 1;
+    if (dump) {
+        uint64_t checksum = FNV_OFFSET;
+        for (long long i = 0; i < N; ++i) {
+            checksum = fnv1a(checksum, (uint64_t)(out[i] + in[i]));
+        }
+        written = write_dump(dump, 3, (uint64_t)N, &checksum, 1);
+    }
 
-    free(block_off); free(block_sum);
     free(out); free(in);
+    if (!written) return 2;
     return ok ? 0 : 3;
 }
+#endif
@@ -0,0 +1,183 @@
+// Segmented prefix sum (inclusive scan restarted at flagged elements) --
+// OpenMP version (for Programmability benchmark)
+// Setup: N = 10^7 ones, T = 8; element i starts a segment when SplitMix64
+// value i of the seed (20240603) is a multiple of L = 1000, and element 0
+// always does, exactly as rust/src/programmability/segmented_scan.rs draws
+// them. Output: one line of key=value pairs with the scan time, the segment
+// count and the peak RSS of the run in bytes.
+// With arguments N, T, L and the seed are taken from them, and the output is
+// CSV-style lines in the Rust records' schema (for run_all_benchmarks
+// --openmp-dir):
+//   ./mp_segmented_scan <N> <T> <L> <seed>
+//   segmented_scan,openmp,N=10000000,T=8,segment=1000,seed=20240603,par_time,0.012345,sec
+//   segmented_scan,openmp,N=10000000,T=8,segment=1000,seed=20240603,correct,1,boolean
+//   segmented_scan,openmp,N=10000000,T=8,segment=1000,seed=20240603,peak_rss,161816576,bytes
+// Architecture: three-phase block-wise scan, as prefix_sum.c, where a carry
+// only reaches a block's elements before its first segment start
+//   1) Each thread scans its own block, restarting at every flag, and records
+//      the block's last sum and whether the block holds a flag
+//   2) The main thread chains the carries: a block passes on its last sum,
+//      plus its own carry when it holds no flag
+//   3) Each thread adds its carry to its block up to the first flag
+//
+// Parallel constructs used (for programmability metric counting):
+//   - omp_set_num_threads           (thread configuration)
+//   - #pragma omp parallel          (parallel regions, used twice)
+//   - #pragma omp parallel for      (input generation and the check)
+
+#include <omp.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <stdint.h>
+#include <sys/resource.h>
+
+#define DEFAULT_N (10000000LL)      // Input array length (1e7 unless given)
+#define DEFAULT_T (8)                // Number of threads (8 unless given)
+#define DEFAULT_L (1000LL)           // Mean segment length
+#define DEFAULT_SEED (20240603ull)   // Seed of the segment starts
+
+// Peak resident set size of the whole process in bytes (Linux reports
+// ru_maxrss in KiB), to set beside the Rust binaries' peak_rss
+static long long peak_rss_bytes(void) {
+    struct rusage ru;
+    if (getrusage(RUSAGE_SELF, &ru) != 0) return -1;
+    return (long long)ru.ru_maxrss * 1024;
+}
+
+// value i of the SplitMix64 stream `seed` (rust/src/rng.rs)
+static inline uint64_t splitmix64_at(uint64_t seed, uint64_t i) {
+    uint64_t z = seed + (i + 1) * 0x9E3779B97F4A7C15ull;
+    z = (z ^ (z >> 30)) * 0xBF58476D1CE4E5B9ull;
+    z = (z ^ (z >> 27)) * 0x94D049BB133111EBull;
+    return z ^ (z >> 31);
+}
+
+// The timed scan (phases 1 to 3) of in[0..N) with segment starts flags[]
+// into out with T threads; returns its time in seconds, or -1 when the block
+// arrays cannot be allocated
+static double segmented_scan(const long long *in, const unsigned char *flags, long long *out, long long N, int T) {
+    omp_set_num_threads(T);
+
+    long long *block_last = (long long*) malloc(sizeof(long long) * T);
+    long long *block_carry = (long long*) malloc(sizeof(long long) * T);
+    int *block_flagged = (int*) malloc(sizeof(int) * T);
+    if (!block_last || !block_carry || !block_flagged) {
+        free(block_last); free(block_carry); free(block_flagged);
+        return -1.0;
+    }
+
+    double t0 = omp_get_wtime();
+
+    // Phase 1: Each thread scans its own block, restarting at every flag
+    #pragma omp parallel
+    {
+        int tid = omp_get_thread_num();
+        long long beg = (N * tid) / T;
+        long long end = (N * (tid + 1)) / T;
+
+        long long run = 0;
+        int flagged = 0;
+        for (long long i = beg; i < end; ++i) {
+            if (flags[i]) {
+                run = 0;
+                flagged = 1;
+            }
+            run += in[i];
+            out[i] = run;
+        }
+        block_last[tid] = run;
+        block_flagged[tid] = flagged;
+    }
+
+    // Phase 2: Serial chain of the carries; a block holding no flag passes
+    // its own carry on with its sum
+    long long carry = 0;
+    for (int t = 0; t < T; ++t) {
+        block_carry[t] = carry;
+        carry = block_flagged[t] ? block_last[t] : carry + block_last[t];
+    }
+
+    // Phase 3: Each block adds its carry up to its first segment start
+    #pragma omp parallel
+    {
+        int tid = omp_get_thread_num();
+        long long beg = (N * tid) / T;
+        long long end = (N * (tid + 1)) / T;
+        long long off = block_carry[tid];
+
+        for (long long i = beg; i < end && !flags[i]; ++i) {
+            out[i] += off;
+        }
+    }
+    double par_time = omp_get_wtime() - t0;
+
+    free(block_last); free(block_carry); free(block_flagged);
+    return par_time;
+}
+
+int main(int argc, char **argv) {
+    long long N = DEFAULT_N;
+    int T = DEFAULT_T;
+    long long L = DEFAULT_L;
+    uint64_t seed = DEFAULT_SEED;
+    int cell = argc == 5;
+    if (cell) {
+        N = atoll(argv[1]);
+        T = atoi(argv[2]);
+        L = atoll(argv[3]);
+        seed = strtoull(argv[4], NULL, 10);
+        if (N <= 0 || T <= 0 || L <= 0) {
+            fprintf(stderr, "N, T and L must be positive.\n");
+            return 1;
+        }
+    } else if (argc != 1) {
+        fprintf(stderr, "usage: %s [<N> <T> <L> <seed>]\n", argv[0]);
+        return 1;
+    }
+
+    omp_set_num_threads(T);
+
+    long long *in  = (long long*) malloc(sizeof(long long) * N);
+    long long *out = (long long*) malloc(sizeof(long long) * N);
+    unsigned char *flags = (unsigned char*) malloc(N);
+    if (!in || !out || !flags) {
+        fprintf(stderr, "Memory allocation failed\n");
+        return 2;
+    }
+
+    long long segments = 0;
+    #pragma omp parallel for schedule(static) reduction(+:segments)
+    for (long long i = 0; i < N; ++i) {
+        in[i] = 1;
+        flags[i] = i == 0 || splitmix64_at(seed, (uint64_t)i) % (uint64_t)L == 0;
+        segments += flags[i];
+    }
+
+    double par_time = segmented_scan(in, flags, out, N, T);
+    if (par_time < 0) {
+        fprintf(stderr, "Memory allocation failed\n");
+        free(in); free(out); free(flags);
+        return 2;
+    }
+
+    // on ones, each sum is the element's position in its segment; the
+    // position is found by walking back to the segment's start
+    int ok = 1;
+    #pragma omp parallel for schedule(static) reduction(&&:ok)
+    for (long long i = 0; i < N; ++i) {
+        long long start = i;
+        while (!flags[start]) --start;
+        ok = ok && out[i] == i - start + 1;
+    }
+
+    if (cell) {
+        printf("segmented_scan,openmp,N=%lld,T=%d,segment=%lld,seed=%llu,par_time,%.6f,sec\n", N, T, L, (unsigned long long)seed, par_time);
+        printf("segmented_scan,openmp,N=%lld,T=%d,segment=%lld,seed=%llu,correct,%d,boolean\n", N, T, L, (unsigned long long)seed, ok);
+        printf("segmented_scan,openmp,N=%lld,T=%d,segment=%lld,seed=%llu,peak_rss,%lld,bytes\n", N, T, L, (unsigned long long)seed, peak_rss_bytes());
+    } else {
+        printf("bench=segmented_scan lang=openmp N=%lld T=%d L=%lld segments=%lld time=%.6f correct=%d peak_rss=%lld\n", N, T, L, segments, par_time, ok, peak_rss_bytes());
+    }
+
+    free(out); free(in); free(flags);
+    return ok ? 0 : 3;
+}
@@ -2,43 +2,103 @@
 // Measures:
 //   1) Parallel region overhead: repeated `#pragma omp parallel {}`
 //   2) Barrier overhead: single parallel region with repeated `#pragma omp barrier`
-//   3) Critical section overhead: `#pragma omp critical` (mutex equivalent)
-//   4) Atomic operations overhead: `#pragma omp atomic`
+//   3) Critical section overhead: `#pragma omp critical` (mutex equivalent),
+//      contended; uncontended, every thread sets an omp_lock_t of its own
+//   4) Atomic operations overhead: `#pragma omp atomic` with the seq_cst,
+//      acq_rel and relaxed (the default) memory orders, and a plain
+//      per-thread counter as the baseline without atomicity or contention
+//   5) With N, instead: parallel-for overhead, R runs of `#pragma omp parallel
+//      for` adding 1 to each of N elements (parfor in runtime_overhead)
 //
 // Usage:
-//   ./overhead_openmp <T> <R>
+//   ./overhead_openmp <T> <R> [N]
 //   T = number of threads (e.g., 1,2,4,8,16)
 //   R = number of repetitions (e.g., 100000)
+//   N = elements of the parallel-for loop, which alone is then measured
 //
 // Output: CSV-style lines with unified units (ms for total, ns for per-op), e.g.:
-//   overhead,openmp,T=8,R=100000,parallel_total,12.34,ms
-//   overhead,openmp,T=8,R=100000,parallel_per,123.45,ns
-//   overhead,openmp,T=8,R=100000,barrier_total,34.56,ms
-//   overhead,openmp,T=8,R=100000,barrier_per,345.67,ns
-//   overhead,openmp,T=8,R=100000,critical_total,45.67,ms
-//   overhead,openmp,T=8,R=100000,critical_per,567.89,ns
-//   overhead,openmp,T=8,R=100000,atomic_total,23.45,ms
-//   overhead,openmp,T=8,R=100000,atomic_per,234.56,ns
+//   overhead,openmp,primitive=parallel,T=8,R=100000,total,12.34,ms
+//   overhead,openmp,primitive=parallel,T=8,R=100000,per_op,123.45,ns
+//   overhead,openmp,primitive=barrier,T=8,R=100000,total,34.56,ms
+//   overhead,openmp,primitive=barrier,T=8,R=100000,per_op,345.67,ns
+//   overhead,openmp,primitive=critical,T=8,R=100000,contended=1,total,45.67,ms
+//   overhead,openmp,primitive=critical,T=8,R=100000,contended=1,per_op,567.89,ns
+//   ... the same two lines for contended=0
+//   overhead,openmp,primitive=atomic,T=8,R=100000,order=seqcst,total,23.45,ms
+//   overhead,openmp,primitive=atomic,T=8,R=100000,order=seqcst,per_op,234.56,ns
+//   ... the same two lines for order=acqrel, order=relaxed and order=local
+//   overhead,openmp,T=8,R=100000,peak_rss,1234567,bytes
+// and with N:
+//   overhead,openmp,primitive=parfor,T=8,N=1000,R=100000,total,12.34,ms
+//   overhead,openmp,primitive=parfor,T=8,N=1000,R=100000,per_op,123.45,ns
+//   overhead,openmp,T=8,N=1000,R=100000,peak_rss,1234567,bytes
 //
-// Note: All "per" values are per-operation costs normalized by (iterations * threads)
+// Note: All per_op values are per-operation costs normalized by (iterations * threads),
+// but parfor's, the cost of one loop
 
 #include <omp.h>
 #include <stdio.h>
 #include <stdlib.h>
+#include <sys/resource.h>
+
+// memory orders of Test 4, named as the Rust records' order param
+#define ORDERS 4
+static const char *ORDER_NAMES[ORDERS] = {"seqcst", "acqrel", "relaxed", "local"};
+
+// Peak resident set size of the whole process in bytes (Linux reports
+// ru_maxrss in KiB), to set beside the Rust binaries' peak_rss
+static long long peak_rss_bytes(void) {
+    struct rusage ru;
+    if (getrusage(RUSAGE_SELF, &ru) != 0) return -1;
+    return (long long)ru.ru_maxrss * 1024;
+}
+
+// Test 5: R parallel loops over N elements; returns 0, or 2 when the array
+// cannot be allocated and 3 when an element was not incremented R times
+static int parallel_for(int T, long long R, long long N) {
+    long long *a = (long long*) calloc((size_t)N, sizeof(long long));
+    if (!a) {
+        fprintf(stderr, "Memory allocation failed\n");
+        return 2;
+    }
+
+    double t0 = omp_get_wtime();
+    for (long long r = 0; r < R; ++r) {
+        #pragma omp parallel for
+        for (long long i = 0; i < N; ++i) {
+            a[i] += 1;
+        }
+    }
+    double time_parfor = omp_get_wtime() - t0;
+
+    int correct = 1;
+    for (long long i = 0; i < N; ++i) correct = correct && a[i] == R;
+    free(a);
+
+    printf("overhead,openmp,primitive=parfor,T=%d,N=%lld,R=%lld,total,%.6f,ms\n",
+           T, N, R, time_parfor * 1000.0);
+    printf("overhead,openmp,primitive=parfor,T=%d,N=%lld,R=%lld,per_op,%.3f,ns\n",
+           T, N, R, time_parfor / (double)R * 1e9);
+    printf("overhead,openmp,T=%d,N=%lld,R=%lld,peak_rss,%lld,bytes\n",
+           T, N, R, peak_rss_bytes());
+    return correct ? 0 : 3;
+}
 
 int main(int argc, char** argv) {
     if (argc < 3) {
-        fprintf(stderr, "usage: %s <T> <R>\n", argv[0]);
+        fprintf(stderr, "usage: %s <T> <R> [N]\n", argv[0]);
         fprintf(stderr, "  T = number of threads (e.g. 1,2,4,8,16)\n");
         fprintf(stderr, "  R = number of repetitions (e.g. 100000)\n");
+        fprintf(stderr, "  N = elements of a parallel for, measured alone (e.g. 1000)\n");
         return 1;
     }
 
     int T = atoi(argv[1]);       // thread count
     long long R = atoll(argv[2]); // repetitions
+    long long N = (argc > 3) ? atoll(argv[3]) : 0; // parallel-for elements
 
-    if (T <= 0 || R <= 0) {
-        fprintf(stderr, "T and R must be positive\n");
+    if (T <= 0 || R <= 0 || (argc > 3 && N <= 0)) {
+        fprintf(stderr, "T, R and N must be positive\n");
         return 1;
     }
 
@@ -50,6 +110,10 @@ int main(int argc, char** argv) {
         // empty warm-up parallel region
     }
 
+    if (N > 0) {
+        return parallel_for(T, R, N);
+    }
+
     // -------------------------------
     // Test 1: Parallel region overhead
     // -------------------------------
@@ -96,10 +160,55 @@ int main(int argc, char** argv) {
     double time_critical = t1 - t0;
     double per_critical = time_critical / (double)(R * T);
 
+    // uncontended: a lock and counter per thread, a cache line apart
+    struct { omp_lock_t lock; long long counter; char pad[64]; } *own = calloc((size_t)T, sizeof *own);
+    if (!own) {
+        fprintf(stderr, "Memory allocation failed\n");
+        return 2;
+    }
+    for (int t = 0; t < T; ++t) omp_init_lock(&own[t].lock);
+    t0 = omp_get_wtime();
+    #pragma omp parallel
+    {
+        int t = omp_get_thread_num();
+        for (long long r = 0; r < R; ++r) {
+            omp_set_lock(&own[t].lock);
+            own[t].counter++;
+            omp_unset_lock(&own[t].lock);
+        }
+    }
+    t1 = omp_get_wtime();
+    double time_uncontended = t1 - t0;
+    double per_uncontended = time_uncontended / (double)(R * T);
+    for (int t = 0; t < T; ++t) omp_destroy_lock(&own[t].lock);
+    free(own);
+
     // ----------------------------
-    // Test 4: Atomic operations overhead
+    // Test 4: Atomic operations overhead, per memory order
     // ----------------------------
     long long atomic_counter = 0;
+    double time_atomic[ORDERS];
+
+    t0 = omp_get_wtime();
+    #pragma omp parallel
+    {
+        for (long long r = 0; r < R; ++r) {
+            #pragma omp atomic seq_cst
+            atomic_counter++;
+        }
+    }
+    time_atomic[0] = omp_get_wtime() - t0;
+
+    t0 = omp_get_wtime();
+    #pragma omp parallel
+    {
+        for (long long r = 0; r < R; ++r) {
+            #pragma omp atomic acq_rel
+            atomic_counter++;
+        }
+    }
+    time_atomic[1] = omp_get_wtime() - t0;
+
     t0 = omp_get_wtime();
     #pragma omp parallel
     {
@@ -108,28 +217,48 @@ int main(int argc, char** argv) {
             atomic_counter++;
         }
     }
-    t1 = omp_get_wtime();
-    double time_atomic = t1 - t0;
-    double per_atomic = time_atomic / (double)(R * T);
+    time_atomic[2] = omp_get_wtime() - t0;
+
+    // baseline: every thread counts in a variable of its own; volatile keeps
+    // one load and store per iteration
+    t0 = omp_get_wtime();
+    #pragma omp parallel
+    {
+        volatile long long local = 0;
+        for (long long r = 0; r < R; ++r) {
+            local = local + 1;
+        }
+        #pragma omp atomic
+        atomic_counter += local;
+    }
+    time_atomic[3] = omp_get_wtime() - t0;
 
     // Unified output format with high precision (milliseconds for total, nanoseconds for per-op)
     // Matches Rust output format for easy comparison
-    printf("overhead,openmp,T=%d,R=%lld,parallel_total,%.6f,ms\n",
+    printf("overhead,openmp,primitive=parallel,T=%d,R=%lld,total,%.6f,ms\n",
            T, R, time_parallel * 1000.0);
-    printf("overhead,openmp,T=%d,R=%lld,parallel_per,%.3f,ns\n",
+    printf("overhead,openmp,primitive=parallel,T=%d,R=%lld,per_op,%.3f,ns\n",
            T, R, per_parallel * 1e9);
-    printf("overhead,openmp,T=%d,R=%lld,barrier_total,%.6f,ms\n",
+    printf("overhead,openmp,primitive=barrier,T=%d,R=%lld,total,%.6f,ms\n",
            T, R, time_barrier * 1000.0);
-    printf("overhead,openmp,T=%d,R=%lld,barrier_per,%.3f,ns\n",
+    printf("overhead,openmp,primitive=barrier,T=%d,R=%lld,per_op,%.3f,ns\n",
            T, R, per_barrier * 1e9);
-    printf("overhead,openmp,T=%d,R=%lld,critical_total,%.6f,ms\n",
+    printf("overhead,openmp,primitive=critical,T=%d,R=%lld,contended=1,total,%.6f,ms\n",
            T, R, time_critical * 1000.0);
-    printf("overhead,openmp,T=%d,R=%lld,critical_per,%.3f,ns\n",
+    printf("overhead,openmp,primitive=critical,T=%d,R=%lld,contended=1,per_op,%.3f,ns\n",
            T, R, per_critical * 1e9);
-    printf("overhead,openmp,T=%d,R=%lld,atomic_total,%.6f,ms\n",
-           T, R, time_atomic * 1000.0);
-    printf("overhead,openmp,T=%d,R=%lld,atomic_per,%.3f,ns\n",
-           T, R, per_atomic * 1e9);
+    printf("overhead,openmp,primitive=critical,T=%d,R=%lld,contended=0,total,%.6f,ms\n",
+           T, R, time_uncontended * 1000.0);
+    printf("overhead,openmp,primitive=critical,T=%d,R=%lld,contended=0,per_op,%.3f,ns\n",
+           T, R, per_uncontended * 1e9);
+    for (int o = 0; o < ORDERS; ++o) {
+        printf("overhead,openmp,primitive=atomic,T=%d,R=%lld,order=%s,total,%.6f,ms\n",
+               T, R, ORDER_NAMES[o], time_atomic[o] * 1000.0);
+        printf("overhead,openmp,primitive=atomic,T=%d,R=%lld,order=%s,per_op,%.3f,ns\n",
+               T, R, ORDER_NAMES[o], time_atomic[o] / (double)(R * T) * 1e9);
+    }
+    printf("overhead,openmp,T=%d,R=%lld,peak_rss,%lld,bytes\n",
+           T, R, peak_rss_bytes());
 
     return 0;
 }
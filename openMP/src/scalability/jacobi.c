@@ -0,0 +1,151 @@
+// OpenMP Jacobi Stencil Benchmark (Scalability)
+// Laplace's equation on an n x n grid by Jacobi iteration, as
+// rust/src/scalability/jacobi.rs solves it: every interior point becomes
+// the mean of its four neighbours from the previous grid, and the grids
+// swap. The top row is held at 1, the other edges at 0, and the interior
+// starts at 0. Each iteration is one `parallel for reduction(max:change)`
+// over the interior rows, whose implicit barrier ends the iteration; the
+// solve stops once no point changed by more than tol, or after iters
+// iterations. The sequential solve is timed too, as the baseline of
+// speedup, and the grids must agree bit for bit.
+//
+// Usage:
+//   ./mp_jacobi <n> <iters> <tol> <T>
+//   n:     grid order, boundary included (at least 3; e.g., 1024)
+//   iters: most iterations (e.g., 1000)
+//   tol:   stop once no point changes by more than tol (e.g., 0.000001)
+//   T:     number of threads (e.g., 1,2,4,8,16)
+//
+// Output (CSV-style, the Rust records' schema):
+//   jacobi,openmp,n=1024,iters=1000,tol=0.000001,T=8,time,0.123456,sec
+//   jacobi,openmp,n=1024,iters=1000,tol=0.000001,T=8,per,0.123456,ms
+//   jacobi,openmp,n=1024,iters=1000,tol=0.000001,T=8,iterations,1000,iterations
+//   jacobi,openmp,n=1024,iters=1000,tol=0.000001,T=8,converged,0,boolean
+//   jacobi,openmp,n=1024,iters=1000,tol=0.000001,T=8,speedup,6.10,x
+//   jacobi,openmp,n=1024,iters=1000,tol=0.000001,T=8,correct,1,boolean
+//   jacobi,openmp,n=1024,iters=1000,tol=0.000001,T=8,peak_rss,16777216,bytes
+
+#include <math.h>
+#include <omp.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/resource.h>
+
+// Peak resident set size of the whole process in bytes (Linux reports
+// ru_maxrss in KiB), to set beside the Rust binaries' peak_rss
+static long long peak_rss_bytes(void) {
+    struct rusage ru;
+    if (getrusage(RUSAGE_SELF, &ru) != 0) return -1;
+    return (long long)ru.ru_maxrss * 1024;
+}
+
+// 1 along the top row, 0 everywhere else
+static void initial(double *grid, long long n) {
+    for (long long i = 0; i < n * n; ++i) grid[i] = i < n ? 1.0 : 0.0;
+}
+
+// interior row i of next from old; returns its largest change
+static double sweep_row(const double *old, double *next, long long n, long long i) {
+    double change = 0.0;
+    for (long long j = 1; j < n - 1; ++j) {
+        double v = 0.25 * (old[(i - 1) * n + j] + old[(i + 1) * n + j] + old[i * n + j - 1] + old[i * n + j + 1]);
+        double d = fabs(v - old[i * n + j]);
+        if (d > change) change = d;
+        next[i * n + j] = v;
+    }
+    return change;
+}
+
+// the solve from *cur and *next, both initial; leaves the grid in *cur and
+// returns the iterations run
+static long long jacobi(double **cur, double **next, long long n, long long iters, double tol, int parallel) {
+    for (long long iteration = 1; iteration <= iters; ++iteration) {
+        double *old = *cur, *new = *next;
+        double change = 0.0;
+        if (parallel) {
+            #pragma omp parallel for schedule(static) reduction(max:change)
+            for (long long i = 1; i < n - 1; ++i) {
+                double d = sweep_row(old, new, n, i);
+                if (d > change) change = d;
+            }
+        } else {
+            for (long long i = 1; i < n - 1; ++i) {
+                double d = sweep_row(old, new, n, i);
+                if (d > change) change = d;
+            }
+        }
+        *cur = new;
+        *next = old;
+        if (change <= tol) return iteration;
+    }
+    return iters;
+}
+
+int main(int argc, char **argv) {
+    if (argc != 5) {
+        fprintf(stderr,
+                "usage: %s <n> <iters> <tol> <T>\n"
+                "  n:     grid order, boundary included (e.g. 1024)\n"
+                "  iters: most iterations (e.g. 1000)\n"
+                "  tol:   stop once no point changes by more than tol (e.g. 0.000001)\n"
+                "  T:     threads (e.g. 1,2,4,8,16)\n",
+                argv[0]);
+        return 1;
+    }
+
+    long long n = atoll(argv[1]);
+    long long iters = atoll(argv[2]);
+    const char *tol_arg = argv[3];
+    double tol = atof(tol_arg);
+    int T = atoi(argv[4]);
+    if (n < 3 || iters <= 0 || T <= 0 || !(tol >= 0.0)) {
+        fprintf(stderr, "n must be at least 3, iters and T positive, tol non-negative.\n");
+        return 1;
+    }
+
+    size_t bytes = (size_t)(n * n) * sizeof(double);
+    double *a = (double*) malloc(bytes), *b = (double*) malloc(bytes);
+    double *c = (double*) malloc(bytes), *d = (double*) malloc(bytes);
+    if (!a || !b || !c || !d) {
+        fprintf(stderr, "Memory allocation failed\n");
+        return 2;
+    }
+    omp_set_num_threads(T);
+
+    initial(a, n);
+    initial(b, n);
+    double t0 = omp_get_wtime();
+    jacobi(&a, &b, n, iters, tol, 0);
+    double seq_time = omp_get_wtime() - t0;
+
+    initial(c, n);
+    initial(d, n);
+    t0 = omp_get_wtime();
+    long long iterations = jacobi(&c, &d, n, iters, tol, 1);
+    double elapsed = omp_get_wtime() - t0;
+
+    // every point is the same expression in any order
+    int correct = memcmp(a, c, bytes) == 0;
+    const char *fmt = "jacobi,openmp,n=%lld,iters=%lld,tol=%s,T=%d,";
+    printf(fmt, n, iters, tol_arg, T);
+    printf("time,%.6f,sec\n", elapsed);
+    printf(fmt, n, iters, tol_arg, T);
+    printf("per,%.6f,ms\n", elapsed * 1e3 / (double)iterations);
+    printf(fmt, n, iters, tol_arg, T);
+    printf("iterations,%lld,iterations\n", iterations);
+    printf(fmt, n, iters, tol_arg, T);
+    printf("converged,%d,boolean\n", iterations < iters);
+    printf(fmt, n, iters, tol_arg, T);
+    printf("speedup,%.2f,x\n", seq_time / elapsed);
+    printf(fmt, n, iters, tol_arg, T);
+    printf("correct,%d,boolean\n", correct);
+    printf(fmt, n, iters, tol_arg, T);
+    printf("peak_rss,%lld,bytes\n", peak_rss_bytes());
+
+    free(d);
+    free(c);
+    free(b);
+    free(a);
+    return correct ? 0 : 3;
+}
@@ -7,14 +7,73 @@
 //   ------------------------------------------------------
 //   Problem Size: n = 256
 //   ...
-//   Threads =  1 ... Time: xxxxs (baseline)
-//   Threads =  2 ... Time: xxxxs, Speedup: xx.x, Efficiency: yy.yy%
+//   Threads =  1 ... Time: xxxxs (baseline), GFLOPS: zz.zz
+//   Threads =  2 ... Time: xxxxs, Speedup: xx.x, Efficiency: yy.yy%, GFLOPS: zz.zz
+//   ...
+//   Peak RSS: xxxx bytes   (the whole run, i.e. the largest n)
+//
+// With arguments it times one cell instead and prints CSV-style lines in
+// the Rust records' schema (for run_all_benchmarks --openmp-dir):
+//   ./mp_matrix_multiply <n> <T> [dump]
+//   matmul,openmp,n=1024,T=8,time,1.234567,sec
+//   matmul,openmp,n=1024,T=8,gflops,1.739481,GFLOP/s
+//   matmul,openmp,n=1024,T=8,correct,1,boolean
+//   matmul,openmp,n=1024,T=8,peak_rss,25821184,bytes
+// and, given a dump file, writes the checksum of C there as a canonical
+// result dump (to check against matrix_multiply --size n --dump FILE)
+//
+// Compiled with -DBENCH_FFI (rust/build.rs, --features ffi) main() is left
+// out and bench_matmul() multiplies matrices the caller owns.
 
 #include <stdio.h>
 #include <stdlib.h>
 #include <math.h>
 #include <omp.h>
 #include <stdint.h>
+#include <string.h>
+#include <sys/resource.h>
+
+// Peak resident set size of the whole process in bytes (Linux reports
+// ru_maxrss in KiB), to set beside the Rust binaries' peak_rss
+static long long peak_rss_bytes(void) {
+    struct rusage ru;
+    if (getrusage(RUSAGE_SELF, &ru) != 0) return -1;
+    return (long long)ru.ru_maxrss * 1024;
+}
+
+// Canonical result dump (rust/src/dump.rs, checked with run_all_benchmarks
+// verify): "BENCHDMP", version 1, the kernel id, the size, the value count
+// and the values, all little-endian; 0 when the file cannot be written
+static void put_le(FILE *f, uint64_t v, int bytes) {
+    for (int i = 0; i < bytes; ++i) fputc((int)((v >> (8 * i)) & 0xFF), f);
+}
+
+static int write_dump(const char *path, uint32_t kernel, uint64_t size,
+                      const uint64_t *values, uint64_t count) {
+    FILE *f = fopen(path, "wb");
+    if (!f) {
+        perror(path);
+        return 0;
+    }
+    fwrite("BENCHDMP", 1, 8, f);
+    put_le(f, 1, 4);
+    put_le(f, kernel, 4);
+    put_le(f, size, 8);
+    put_le(f, count, 8);
+    for (uint64_t i = 0; i < count; ++i) put_le(f, values[i], 8);
+    return fclose(f) == 0;
+}
+
+// FNV-1a (64-bit) over a word's little-endian bytes, chained from `hash`
+// (FNV_OFFSET to start), like golden::checksum on the Rust side
+#define FNV_OFFSET 0xcbf29ce484222325ull
+static uint64_t fnv1a(uint64_t hash, uint64_t word) {
+    for (int i = 0; i < 8; ++i) {
+        hash ^= (word >> (8 * i)) & 0xFF;
+        hash *= 0x100000001b3ull;
+    }
+    return hash;
+}
 
 // ------------ 64-byte aligned allocation ------------
 
@@ -86,7 +145,84 @@ static int check_all_equal(const double *C, int n, double target, double tol) {
     return ok;
 }
 
-int main(void) {
+// One timed multiply with T threads; *ok tells whether C came out right
+static double time_cell(double *A, double *B, double *C, int n, int T, int *ok) {
+    omp_set_num_threads(T);
+    zero_matrix(C, n);
+    double t0 = omp_get_wtime();
+    mm_naive(A, B, C, n);
+    double t1 = omp_get_wtime();
+    *ok = check_all_equal(C, n, 2.0 * (double)n, 1e-9);
+    return t1 - t0;
+}
+
+// GFLOP/s of one n x n multiply in t seconds: 2 n^3 flops, as in the Rust
+// records' gflops
+static double gflops(int n, double t) {
+    return 2.0 * (double)n * (double)n * (double)n / t / 1e9;
+}
+
+// ./mp_matrix_multiply <n> <T> [dump]: a single cell as CSV lines
+static int run_cell(int n, int T, const char *dump) {
+    size_t bytes = (size_t)n * (size_t)n * sizeof(double);
+    double *A = (double*) alloc64(bytes);
+    double *B = (double*) alloc64(bytes);
+    double *C = (double*) alloc64(bytes);
+    if (!A || !B || !C) {
+        fprintf(stderr, "malloc failed for n=%d\n", n);
+        return 2;
+    }
+    init_ones(A, B, n);
+
+    int ok = 0;
+    double t = time_cell(A, B, C, n, T, &ok);
+    printf("matmul,openmp,n=%d,T=%d,time,%.6f,sec\n", n, T, t);
+    printf("matmul,openmp,n=%d,T=%d,gflops,%.6f,GFLOP/s\n", n, T, gflops(n, t));
+    printf("matmul,openmp,n=%d,T=%d,correct,%d,boolean\n", n, T, ok);
+    printf("matmul,openmp,n=%d,T=%d,peak_rss,%lld,bytes\n", n, T, peak_rss_bytes());
+
+    int written = 1;
+    if (dump) {
+        uint64_t checksum = FNV_OFFSET;
+        for (long long i = 0; i < (long long)n * n; ++i) {
+            uint64_t bits;
+            memcpy(&bits, &C[i], sizeof bits);
+            checksum = fnv1a(checksum, bits);
+        }
+        written = write_dump(dump, 2, (uint64_t)n, &checksum, 1);
+    }
+
+    free(C);
+    free(B);
+    free(A);
+    if (!written) return 2;
+    return ok ? 0 : 3;
+}
+
+#ifdef BENCH_FFI
+// One timed multiply C = A * B of n x n row-major matrices with T threads
+double bench_matmul(const double *A, const double *B, double *C, int n, int T) {
+    omp_set_num_threads(T);
+    double t0 = omp_get_wtime();
+    mm_naive((double*) A, (double*) B, C, n);
+    return omp_get_wtime() - t0;
+}
+#else
+int main(int argc, char **argv) {
+    if (argc == 3 || argc == 4) {
+        int n = atoi(argv[1]);
+        int T = atoi(argv[2]);
+        if (n <= 0 || T <= 0) {
+            fprintf(stderr, "n and T must be positive.\n");
+            return 1;
+        }
+        return run_cell(n, T, argc == 4 ? argv[3] : NULL);
+    }
+    if (argc != 1) {
+        fprintf(stderr, "usage: %s [<n> <T> [dump]]\n", argv[0]);
+        return 1;
+    }
+
     // Problem sizes and thread-count sets (kept consistent with the Rust version)
     const int Ns[] = {256, 512, 1024, 1536, 2048};
     const int n_cnt = (int)(sizeof(Ns) / sizeof(Ns[0]));
@@ -122,30 +258,22 @@ int main(void) {
 
         for (int ti = 0; ti < t_cnt; ++ti) {
             int T = Ts[ti];
-            omp_set_num_threads(T);
-
-            // Time a single run
-            zero_matrix(C, n);
-            double t0 = omp_get_wtime();
-            mm_naive(A, B, C, n);
-            double t1 = omp_get_wtime();
-            double t = t1 - t0;
-
-            // Correctness check using the result of this run
-            int ok = check_all_equal(C, n, 2.0 * (double)n, 1e-9);
+            // Time a single run, checked for correctness
+            int ok = 0;
+            double t = time_cell(A, B, C, n, T, &ok);
 
             if (ti == 0) {
                 // baseline: T = 1
                 t_base = t;
-                printf("Threads = %2d ... Time: %.6lfs (baseline)%s\n",
-                       T, t, ok ? "" : "  [INCORRECT]");
+                printf("Threads = %2d ... Time: %.6lfs (baseline), GFLOPS: %.2lf%s\n",
+                       T, t, gflops(n, t), ok ? "" : "  [INCORRECT]");
             } else {
                 double speedup    = t_base / t;
                 double efficiency = (speedup / (double)T) * 100.0; // Percentage
 
                 printf("Threads = %2d ... Time: %.6lfs, "
-                       "Speedup: %.2lfx, Efficiency: %.2lf%%%s\n",
-                       T, t, speedup, efficiency,
+                       "Speedup: %.2lfx, Efficiency: %.2lf%%, GFLOPS: %.2lf%s\n",
+                       T, t, speedup, efficiency, gflops(n, t),
                        ok ? "" : "  [INCORRECT]");
             }
             fflush(stdout);  // Print as we go
@@ -158,5 +286,7 @@ int main(void) {
         free(A);
     }
 
+    printf("Peak RSS: %lld bytes\n", peak_rss_bytes());
     return 0;
 }
+#endif
@@ -0,0 +1,110 @@
+// OpenMP Monte Carlo Pi Benchmark (Scalability)
+// N points thrown into the unit square, pi estimated as 4 times the
+// fraction inside the quarter circle, as rust/src/scalability/monte_carlo.rs
+// does: each thread of a `parallel reduction(+:hits)` region counts its
+// static chunk of the points with a SplitMix64 generator of its own,
+// positioned at the chunk. Point i is values 2i and 2i + 1 of the stream
+// seed, their top 53 bits scaled to [0, 1), so the hits do not depend on T
+// and must equal those of the sequential loop, timed too as the baseline of
+// speedup.
+//
+// Usage:
+//   ./mp_monte_carlo <N> <T> <seed>
+//   N:    samples (e.g., 1000000000)
+//   T:    number of threads (e.g., 1,2,4,8,16)
+//   seed: seed of the points (e.g., 20240606)
+//
+// Output (CSV-style, the Rust records' schema):
+//   monte_carlo,openmp,N=1000000000,T=8,seed=20240606,time,0.123456,sec
+//   monte_carlo,openmp,N=1000000000,T=8,seed=20240606,rate,8100000000,samples/s
+//   monte_carlo,openmp,N=1000000000,T=8,seed=20240606,speedup,7.95,x
+//   monte_carlo,openmp,N=1000000000,T=8,seed=20240606,pi,3.141592653,estimate
+//   monte_carlo,openmp,N=1000000000,T=8,seed=20240606,correct,1,boolean
+//   monte_carlo,openmp,N=1000000000,T=8,seed=20240606,peak_rss,1234567,bytes
+
+#include <omp.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <sys/resource.h>
+
+#define GAMMA 0x9E3779B97F4A7C15ull
+
+// Peak resident set size of the whole process in bytes (Linux reports
+// ru_maxrss in KiB), to set beside the Rust binaries' peak_rss
+static long long peak_rss_bytes(void) {
+    struct rusage ru;
+    if (getrusage(RUSAGE_SELF, &ru) != 0) return -1;
+    return (long long)ru.ru_maxrss * 1024;
+}
+
+// the next value of a SplitMix64 generator, as rust/src/rng.rs SplitMix64
+static inline uint64_t splitmix64_next(uint64_t *state) {
+    uint64_t z = (*state += GAMMA);
+    z = (z ^ (z >> 30)) * 0xBF58476D1CE4E5B9ull;
+    z = (z ^ (z >> 27)) * 0x94D049BB133111EBull;
+    return z ^ (z >> 31);
+}
+
+static inline double unit(uint64_t *state) {
+    return (double)(splitmix64_next(state) >> 11) / 9007199254740992.0;
+}
+
+// hits among points start..end, by a generator of the caller's own
+static long long count_hits(uint64_t seed, long long start, long long end) {
+    uint64_t state = seed + 2 * (uint64_t)start * GAMMA;
+    long long hits = 0;
+    for (long long i = start; i < end; ++i) {
+        double x = unit(&state), y = unit(&state);
+        hits += x * x + y * y < 1.0;
+    }
+    return hits;
+}
+
+int main(int argc, char **argv) {
+    if (argc != 4) {
+        fprintf(stderr,
+                "usage: %s <N> <T> <seed>\n"
+                "  N:    samples (e.g. 1000000000)\n"
+                "  T:    threads (e.g. 1,2,4,8,16)\n"
+                "  seed: seed of the points (e.g. 20240606)\n",
+                argv[0]);
+        return 1;
+    }
+
+    long long N = atoll(argv[1]);
+    int T = atoi(argv[2]);
+    uint64_t seed = strtoull(argv[3], NULL, 10);
+    if (N <= 0 || T <= 0) {
+        fprintf(stderr, "N and T must be positive.\n");
+        return 1;
+    }
+    omp_set_num_threads(T);
+
+    double t0 = omp_get_wtime();
+    long long expected = count_hits(seed, 0, N);
+    double seq_time = omp_get_wtime() - t0;
+
+    long long hits = 0;
+    t0 = omp_get_wtime();
+    #pragma omp parallel reduction(+:hits)
+    {
+        int t = omp_get_thread_num();
+        long long chunk = (N + omp_get_num_threads() - 1) / omp_get_num_threads();
+        long long start = t * chunk < N ? t * chunk : N;
+        long long end = start + chunk < N ? start + chunk : N;
+        hits += count_hits(seed, start, end);
+    }
+    double elapsed = omp_get_wtime() - t0;
+
+    // the points do not depend on the chunking
+    int correct = hits == expected;
+    unsigned long long s = (unsigned long long)seed;
+    printf("monte_carlo,openmp,N=%lld,T=%d,seed=%llu,time,%.6f,sec\n", N, T, s, elapsed);
+    printf("monte_carlo,openmp,N=%lld,T=%d,seed=%llu,rate,%.0f,samples/s\n", N, T, s, (double)N / elapsed);
+    printf("monte_carlo,openmp,N=%lld,T=%d,seed=%llu,speedup,%.2f,x\n", N, T, s, seq_time / elapsed);
+    printf("monte_carlo,openmp,N=%lld,T=%d,seed=%llu,pi,%.9f,estimate\n", N, T, s, 4.0 * (double)hits / (double)N);
+    printf("monte_carlo,openmp,N=%lld,T=%d,seed=%llu,correct,%d,boolean\n", N, T, s, correct);
+    printf("monte_carlo,openmp,N=%lld,T=%d,seed=%llu,peak_rss,%lld,bytes\n", N, T, s, peak_rss_bytes());
+    return correct ? 0 : 3;
+}
@@ -0,0 +1,135 @@
+// OpenMP All-Pairs N-Body Benchmark (Scalability)
+// N bodies under their mutual gravity, as rust/src/scalability/nbody.rs
+// moves them: every step each body's acceleration from all N bodies
+// (softened by EPS2), then v += a dt, x += v dt. Each step is a
+// `parallel for schedule(static)` over the bodies for the accelerations and
+// one for the positions. The bodies start at rest, uniform in [-1, 1)^3
+// from the SplitMix64 stream BODY_SEED (coordinate k of body i is value
+// 3i + k), each of mass 1 / N. The same steps on one thread are timed too
+// as the baseline of speedup, and the two runs must agree bit for bit.
+//
+// Usage:
+//   ./mp_nbody <N> <steps> <T>
+//   N:     bodies (e.g., 16384)
+//   steps: time steps (e.g., 10)
+//   T:     number of threads (e.g., 1,2,4,8,16)
+//
+// Output (CSV-style, the Rust records' schema):
+//   nbody,openmp,N=16384,steps=10,T=8,time,0.123456,sec
+//   nbody,openmp,N=16384,steps=10,T=8,per,12.345600,ms
+//   nbody,openmp,N=16384,steps=10,T=8,rate,21743486000,interactions/s
+//   nbody,openmp,N=16384,steps=10,T=8,speedup,7.60,x
+//   nbody,openmp,N=16384,steps=10,T=8,correct,1,boolean
+//   nbody,openmp,N=16384,steps=10,T=8,peak_rss,12345678,bytes
+
+#include <math.h>
+#include <omp.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/resource.h>
+
+#define DT 0.01
+#define EPS2 0.01
+#define BODY_SEED 20240605ull
+
+// Peak resident set size of the whole process in bytes (Linux reports
+// ru_maxrss in KiB), to set beside the Rust binaries' peak_rss
+static long long peak_rss_bytes(void) {
+    struct rusage ru;
+    if (getrusage(RUSAGE_SELF, &ru) != 0) return -1;
+    return (long long)ru.ru_maxrss * 1024;
+}
+
+// SplitMix64 value i of stream seed, as rust/src/rng.rs `at`
+static inline uint64_t splitmix64_at(uint64_t seed, uint64_t i) {
+    uint64_t z = seed + (i + 1) * 0x9E3779B97F4A7C15ull;
+    z = (z ^ (z >> 30)) * 0xBF58476D1CE4E5B9ull;
+    z = (z ^ (z >> 27)) * 0x94D049BB133111EBull;
+    return z ^ (z >> 31);
+}
+
+// steps time steps over pos and vel (3 N each) with T threads, or on the
+// calling thread when parallel is 0
+static void nbody(double *pos, double *vel, const double *mass, long long N, long long steps, int parallel) {
+    for (long long s = 0; s < steps; ++s) {
+        #pragma omp parallel for schedule(static) if(parallel)
+        for (long long i = 0; i < N; ++i) {
+            double xi = pos[3 * i], yi = pos[3 * i + 1], zi = pos[3 * i + 2];
+            double ax = 0.0, ay = 0.0, az = 0.0;
+            for (long long j = 0; j < N; ++j) {
+                double dx = pos[3 * j] - xi, dy = pos[3 * j + 1] - yi, dz = pos[3 * j + 2] - zi;
+                double r2 = dx * dx + dy * dy + dz * dz + EPS2;
+                double f = mass[j] / (r2 * sqrt(r2));
+                ax += dx * f;
+                ay += dy * f;
+                az += dz * f;
+            }
+            vel[3 * i] += ax * DT;
+            vel[3 * i + 1] += ay * DT;
+            vel[3 * i + 2] += az * DT;
+        }
+        #pragma omp parallel for schedule(static) if(parallel)
+        for (long long k = 0; k < 3 * N; ++k) pos[k] += vel[k] * DT;
+    }
+}
+
+int main(int argc, char **argv) {
+    if (argc != 4) {
+        fprintf(stderr,
+                "usage: %s <N> <steps> <T>\n"
+                "  N:     bodies (e.g. 16384)\n"
+                "  steps: time steps (e.g. 10)\n"
+                "  T:     threads (e.g. 1,2,4,8,16)\n",
+                argv[0]);
+        return 1;
+    }
+
+    long long N = atoll(argv[1]);
+    long long steps = atoll(argv[2]);
+    int T = atoi(argv[3]);
+    if (N <= 0 || steps <= 0 || T <= 0) {
+        fprintf(stderr, "N, steps and T must be positive.\n");
+        return 1;
+    }
+
+    size_t bytes = (size_t)(3 * N) * sizeof(double);
+    double *pos0 = (double*) malloc(bytes), *seq_pos = (double*) malloc(bytes), *par_pos = (double*) malloc(bytes);
+    double *seq_vel = (double*) calloc((size_t)(3 * N), sizeof(double)), *par_vel = (double*) calloc((size_t)(3 * N), sizeof(double));
+    double *mass = (double*) malloc((size_t)N * sizeof(double));
+    if (!pos0 || !seq_pos || !par_pos || !seq_vel || !par_vel || !mass) {
+        fprintf(stderr, "Memory allocation failed\n");
+        return 2;
+    }
+    for (long long k = 0; k < 3 * N; ++k) pos0[k] = (double)(splitmix64_at(BODY_SEED, (uint64_t)k) >> 11) / 9007199254740992.0 * 2.0 - 1.0;
+    for (long long i = 0; i < N; ++i) mass[i] = 1.0 / (double)N;
+    memcpy(seq_pos, pos0, bytes);
+    memcpy(par_pos, pos0, bytes);
+    omp_set_num_threads(T);
+
+    double t0 = omp_get_wtime();
+    nbody(seq_pos, seq_vel, mass, N, steps, 0);
+    double seq_time = omp_get_wtime() - t0;
+
+    t0 = omp_get_wtime();
+    nbody(par_pos, par_vel, mass, N, steps, 1);
+    double elapsed = omp_get_wtime() - t0;
+
+    int correct = memcmp(seq_pos, par_pos, bytes) == 0 && memcmp(seq_vel, par_vel, bytes) == 0;
+    double interactions = (double)N * (double)N * (double)steps;
+    printf("nbody,openmp,N=%lld,steps=%lld,T=%d,time,%.6f,sec\n", N, steps, T, elapsed);
+    printf("nbody,openmp,N=%lld,steps=%lld,T=%d,per,%.6f,ms\n", N, steps, T, elapsed * 1e3 / (double)steps);
+    printf("nbody,openmp,N=%lld,steps=%lld,T=%d,rate,%.0f,interactions/s\n", N, steps, T, interactions / elapsed);
+    printf("nbody,openmp,N=%lld,steps=%lld,T=%d,speedup,%.2f,x\n", N, steps, T, seq_time / elapsed);
+    printf("nbody,openmp,N=%lld,steps=%lld,T=%d,correct,%d,boolean\n", N, steps, T, correct);
+    printf("nbody,openmp,N=%lld,steps=%lld,T=%d,peak_rss,%lld,bytes\n", N, steps, T, peak_rss_bytes());
+
+    free(mass);
+    free(par_vel);
+    free(seq_vel);
+    free(par_pos);
+    free(seq_pos);
+    free(pos0);
+    return correct ? 0 : 3;
+}
@@ -0,0 +1,168 @@
+// OpenMP Reduction Benchmark (Scalability)
+// N doubles summed (sum), or the dot product of two vectors of N (dot),
+// reduced one of four ways, as rust/src/scalability/reduction.rs does:
+//   sequential  one loop on the initial thread
+//   builtin     `#pragma omp parallel for reduction(+:sum)`
+//   chunked     by hand: each thread sums its static chunk into a private
+//               partial, stored in partials[thread]; the initial thread adds
+//               the T partials after the region
+//   atomic      `#pragma omp atomic` adding every element to a shared sum
+// Element i of x is (i mod 1000) / 1024 and of y (i mod 7 + 1) / 4, so every
+// partial sum is exact and all four agree bit for bit with the Rust runs.
+// The sequential loop is timed too, as the baseline of speedup.
+//
+// Usage:
+//   ./mp_reduction <op> <strategy> <N> <T>
+//   op:       sum | dot
+//   strategy: sequential | builtin | chunked | atomic
+//   N:        elements (e.g., 100000000)
+//   T:        number of threads (e.g., 1,2,4,8,16)
+//
+// Output (CSV-style, the Rust records' schema):
+//   reduction,openmp,op=sum,strategy=builtin,N=100000000,T=8,time,0.012345,sec
+//   reduction,openmp,op=sum,strategy=builtin,N=100000000,T=8,speedup,6.10,x
+//   reduction,openmp,op=sum,strategy=builtin,N=100000000,T=8,correct,1,boolean
+//   reduction,openmp,op=sum,strategy=builtin,N=100000000,T=8,peak_rss,12345678,bytes
+
+#include <omp.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/resource.h>
+
+// Peak resident set size of the whole process in bytes (Linux reports
+// ru_maxrss in KiB), to set beside the Rust binaries' peak_rss
+static long long peak_rss_bytes(void) {
+    struct rusage ru;
+    if (getrusage(RUSAGE_SELF, &ru) != 0) return -1;
+    return (long long)ru.ru_maxrss * 1024;
+}
+
+// x and y as in the Rust version; y is NULL for sum
+static double term(const double *x, const double *y, long long i) {
+    return y ? x[i] * y[i] : x[i];
+}
+
+static double reduce_sequential(const double *x, const double *y, long long N) {
+    double sum = 0.0;
+    for (long long i = 0; i < N; ++i) sum += term(x, y, i);
+    return sum;
+}
+
+static double reduce_builtin(const double *x, const double *y, long long N) {
+    double sum = 0.0;
+    #pragma omp parallel for reduction(+:sum)
+    for (long long i = 0; i < N; ++i) sum += term(x, y, i);
+    return sum;
+}
+
+// -1 with partials left unset when they cannot be allocated
+static double reduce_chunked(const double *x, const double *y, long long N, int T) {
+    double *partials = (double*) calloc((size_t)T, sizeof(double));
+    if (!partials) return -1.0;
+    #pragma omp parallel
+    {
+        int t = omp_get_thread_num();
+        int threads = omp_get_num_threads();
+        long long chunk = (N + threads - 1) / threads;
+        long long start = t * chunk;
+        long long end = start + chunk < N ? start + chunk : N;
+        double partial = 0.0;
+        for (long long i = start; i < end; ++i) partial += term(x, y, i);
+        partials[t] = partial;
+    }
+    double sum = 0.0;
+    for (int t = 0; t < T; ++t) sum += partials[t];
+    free(partials);
+    return sum;
+}
+
+static double reduce_atomic(const double *x, const double *y, long long N) {
+    double sum = 0.0;
+    #pragma omp parallel for schedule(static)
+    for (long long i = 0; i < N; ++i) {
+        double t = term(x, y, i);
+        #pragma omp atomic
+        sum += t;
+    }
+    return sum;
+}
+
+int main(int argc, char **argv) {
+    if (argc != 5) {
+        fprintf(stderr,
+                "usage: %s <op> <strategy> <N> <T>\n"
+                "  op:       sum | dot\n"
+                "  strategy: sequential | builtin | chunked | atomic\n"
+                "  N:        elements (e.g. 100000000)\n"
+                "  T:        threads (e.g. 1,2,4,8,16)\n",
+                argv[0]);
+        return 1;
+    }
+
+    const char *op = argv[1];
+    const char *strategy = argv[2];
+    long long N = atoll(argv[3]);
+    int T = atoi(argv[4]);
+
+    if (N <= 0 || T <= 0) {
+        fprintf(stderr, "N and T must be positive.\n");
+        return 1;
+    }
+    if (strcmp(op, "sum") != 0 && strcmp(op, "dot") != 0) {
+        fprintf(stderr, "unknown op: %s (use sum|dot)\n", op);
+        return 1;
+    }
+    if (strcmp(strategy, "sequential") != 0 && strcmp(strategy, "builtin") != 0 &&
+        strcmp(strategy, "chunked") != 0 && strcmp(strategy, "atomic") != 0) {
+        fprintf(stderr, "unknown strategy: %s (use sequential|builtin|chunked|atomic)\n", strategy);
+        return 1;
+    }
+
+    int dot = strcmp(op, "dot") == 0;
+    double *x = (double*) malloc((size_t)N * sizeof(double));
+    double *y = dot ? (double*) malloc((size_t)N * sizeof(double)) : NULL;
+    if (!x || (dot && !y)) {
+        fprintf(stderr, "Memory allocation failed\n");
+        return 2;
+    }
+    omp_set_num_threads(T);
+    // first touch by the threads that reduce the elements
+    #pragma omp parallel for schedule(static)
+    for (long long i = 0; i < N; ++i) {
+        x[i] = (double)(i % 1000) / 1024.0;
+        if (dot) y[i] = (double)(i % 7 + 1) / 4.0;
+    }
+
+    double t0 = omp_get_wtime();
+    double expected = reduce_sequential(x, y, N);
+    double seq_time = omp_get_wtime() - t0;
+
+    t0 = omp_get_wtime();
+    double sum;
+    if (strcmp(strategy, "sequential") == 0) {
+        sum = reduce_sequential(x, y, N);
+    } else if (strcmp(strategy, "builtin") == 0) {
+        sum = reduce_builtin(x, y, N);
+    } else if (strcmp(strategy, "chunked") == 0) {
+        sum = reduce_chunked(x, y, N, T);
+    } else {
+        sum = reduce_atomic(x, y, N);
+    }
+    double elapsed = omp_get_wtime() - t0;
+    if (sum < 0) {
+        fprintf(stderr, "Memory allocation failed\n");
+        return 2;
+    }
+
+    // every partial is exact, so any order gives the sequential total
+    int correct = sum == expected;
+    printf("reduction,openmp,op=%s,strategy=%s,N=%lld,T=%d,time,%.6f,sec\n", op, strategy, N, T, elapsed);
+    printf("reduction,openmp,op=%s,strategy=%s,N=%lld,T=%d,speedup,%.2f,x\n", op, strategy, N, T, seq_time / elapsed);
+    printf("reduction,openmp,op=%s,strategy=%s,N=%lld,T=%d,correct,%d,boolean\n", op, strategy, N, T, correct);
+    printf("reduction,openmp,op=%s,strategy=%s,N=%lld,T=%d,peak_rss,%lld,bytes\n", op, strategy, N, T, peak_rss_bytes());
+
+    free(y);
+    free(x);
+    return correct ? 0 : 3;
+}
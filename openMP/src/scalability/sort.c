@@ -0,0 +1,312 @@
+// OpenMP Sort Benchmark (Scalability)
+// N random u64 or f64 keys sorted by the two sorts of
+// rust/src/scalability/sort.rs:
+//   merge   a merge sort with OpenMP tasks: both halves sorted as two tasks,
+//           then merged by a parallel merge that splits the larger run at
+//           its median and the smaller at the same key, also as two tasks
+//           (`taskwait` joining each pair); runs of at most cutoff elements
+//           are sorted (qsort) or merged by one thread. The halves alternate
+//           between the array and one scratch buffer
+//   sample  a sample sort: buckets - 1 splitters picked from oversample
+//           evenly spaced keys per bucket, each thread's static chunk
+//           counted and then scattered into the buckets, and the buckets
+//           sorted (qsort) by a `parallel for schedule(dynamic, 1)`;
+//           imbalance is the largest bucket over the mean
+// Key i is value i of the SplitMix64 stream seed: the raw u64, or its top
+// 53 bits scaled to [0, 1) for f64. The merge sort outside a parallel
+// region, where every task runs at once on the initial thread, is timed too
+// as the baseline of speedup, and the parallel sort must reproduce its
+// output.
+//
+// Usage:
+//   ./mp_sort <type> <algo> <N> <T> <cutoff> <oversample> <buckets> <seed>
+//   type:       u64 | f64
+//   algo:       merge | sample
+//   N:          keys (e.g., 100000000)
+//   T:          number of threads (e.g., 1,2,4,8,16)
+//   cutoff:     largest run sorted or merged by one thread (e.g., 4096)
+//   oversample: sample: keys sampled per bucket (e.g., 32; 0 for merge)
+//   buckets:    sample: buckets (e.g., 256; 0 for merge)
+//   seed:       seed of the keys (e.g., 20240604)
+//
+// Output (CSV-style, the Rust records' schema: cutoff only for merge,
+// oversample and buckets only for sample):
+//   sort,openmp,type=u64,algo=sample,N=100000000,T=8,oversample=32,buckets=256,seed=20240604,time,0.123456,sec
+//   sort,openmp,type=u64,algo=sample,N=100000000,T=8,oversample=32,buckets=256,seed=20240604,speedup,6.10,x
+//   sort,openmp,type=u64,algo=sample,N=100000000,T=8,oversample=32,buckets=256,seed=20240604,imbalance,1.08,x
+//   sort,openmp,type=u64,algo=sample,N=100000000,T=8,oversample=32,buckets=256,seed=20240604,correct,1,boolean
+//   sort,openmp,type=u64,algo=sample,N=100000000,T=8,oversample=32,buckets=256,seed=20240604,peak_rss,2400000000,bytes
+
+#include <omp.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/resource.h>
+
+// Peak resident set size of the whole process in bytes (Linux reports
+// ru_maxrss in KiB), to set beside the Rust binaries' peak_rss
+static long long peak_rss_bytes(void) {
+    struct rusage ru;
+    if (getrusage(RUSAGE_SELF, &ru) != 0) return -1;
+    return (long long)ru.ru_maxrss * 1024;
+}
+
+// SplitMix64 value i of stream seed, as rust/src/rng.rs `at`
+static inline uint64_t splitmix64_at(uint64_t seed, uint64_t i) {
+    uint64_t z = seed + (i + 1) * 0x9E3779B97F4A7C15ull;
+    z = (z ^ (z >> 30)) * 0xBF58476D1CE4E5B9ull;
+    z = (z ^ (z >> 27)) * 0x94D049BB133111EBull;
+    return z ^ (z >> 31);
+}
+
+// Keys are held as 64-bit words; f64 keys are the bits of their doubles,
+// compared as doubles
+static int f64_keys;
+
+static inline double as_f64(uint64_t bits) {
+    double d;
+    memcpy(&d, &bits, sizeof d);
+    return d;
+}
+
+static inline int less(uint64_t a, uint64_t b) {
+    return f64_keys ? as_f64(a) < as_f64(b) : a < b;
+}
+
+static int cmp_keys(const void *x, const void *y) {
+    uint64_t a = *(const uint64_t*)x, b = *(const uint64_t*)y;
+    return less(b, a) - less(a, b);
+}
+
+// key i: the raw value, or its top 53 bits scaled to [0, 1)
+static uint64_t key(uint64_t seed, long long i) {
+    uint64_t r = splitmix64_at(seed, (uint64_t)i);
+    if (!f64_keys) return r;
+    double d = (double)(r >> 11) / 9007199254740992.0;
+    memcpy(&r, &d, sizeof r);
+    return r;
+}
+
+// first position of b[0..nb) not less than x (strict), or not at most x
+static long long split(const uint64_t *b, long long nb, uint64_t x, int strict) {
+    long long lo = 0, hi = nb;
+    while (lo < hi) {
+        long long m = lo + (hi - lo) / 2;
+        if (strict ? less(b[m], x) : !less(x, b[m])) lo = m + 1; else hi = m;
+    }
+    return lo;
+}
+
+// the sorted runs a and b merged into out, ties from a first; the larger
+// run split at its median, the smaller where that key would go, the two
+// halves merged as two tasks down to cutoff (and at least 2) elements
+static void merge(const uint64_t *a, long long na, const uint64_t *b, long long nb, uint64_t *out, long long cutoff) {
+    if (na + nb <= (cutoff > 2 ? cutoff : 2)) {
+        long long i = 0, j = 0;
+        for (long long k = 0; k < na + nb; ++k) out[k] = (j == nb || (i < na && !less(b[j], a[i]))) ? a[i++] : b[j++];
+        return;
+    }
+    long long i, j;
+    if (na >= nb) {
+        i = na / 2;
+        j = split(b, nb, a[i], 1);
+    } else {
+        j = nb / 2;
+        i = split(a, na, b[j], 0);
+    }
+    #pragma omp task
+    merge(a, i, b, j, out, cutoff);
+    merge(a + i, na - i, b + j, nb - j, out + i + j, cutoff);
+    #pragma omp taskwait
+}
+
+static void sort_into(uint64_t *v, uint64_t *out, long long n, long long cutoff);
+
+// v sorted in place, with buf as scratch: the halves sorted into buf as two
+// tasks, then merged back into v
+static void sort_in(uint64_t *v, uint64_t *buf, long long n, long long cutoff) {
+    if (n <= cutoff) {
+        qsort(v, (size_t)n, sizeof(uint64_t), cmp_keys);
+        return;
+    }
+    long long mid = n / 2;
+    #pragma omp task
+    sort_into(v, buf, mid, cutoff);
+    sort_into(v + mid, buf + mid, n - mid, cutoff);
+    #pragma omp taskwait
+    merge(buf, mid, buf + mid, n - mid, v, cutoff);
+}
+
+// v sorted into out, leaving v as scratch
+static void sort_into(uint64_t *v, uint64_t *out, long long n, long long cutoff) {
+    if (n <= cutoff) {
+        qsort(v, (size_t)n, sizeof(uint64_t), cmp_keys);
+        memcpy(out, v, (size_t)n * sizeof(uint64_t));
+        return;
+    }
+    long long mid = n / 2;
+    #pragma omp task
+    sort_in(v, out, mid, cutoff);
+    sort_in(v + mid, out + mid, n - mid, cutoff);
+    #pragma omp taskwait
+    merge(v, mid, v + mid, n - mid, out, cutoff);
+}
+
+// the bucket of key: how many of the splitters are at most key
+static long long bucket(const uint64_t *splitters, long long count, uint64_t key) {
+    return split(splitters, count, key, 0);
+}
+
+// v sample-sorted into out with T threads; returns the largest bucket, or
+// -1 when the counts cannot be allocated
+static long long sample_sort(const uint64_t *v, uint64_t *out, long long n, int T, long long oversample, long long buckets) {
+    long long count = oversample * buckets < n ? oversample * buckets : n;
+    uint64_t *samples = (uint64_t*) malloc((size_t)count * sizeof(uint64_t));
+    uint64_t *splitters = (uint64_t*) malloc((size_t)buckets * sizeof(uint64_t));
+    long long *counts = (long long*) calloc((size_t)T * (size_t)buckets, sizeof(long long));
+    long long *starts = (long long*) malloc((size_t)(buckets + 1) * sizeof(long long));
+    if (!samples || !splitters || !counts || !starts) return -1;
+    for (long long i = 0; i < count; ++i) samples[i] = v[i * n / count];
+    qsort(samples, (size_t)count, sizeof(uint64_t), cmp_keys);
+    for (long long b = 1; b < buckets; ++b) splitters[b - 1] = samples[b * count / buckets];
+
+    long long chunk = (n + T - 1) / T;
+    #pragma omp parallel num_threads(T)
+    {
+        int t = omp_get_thread_num();
+        long long start = t * chunk < n ? t * chunk : n;
+        long long end = start + chunk < n ? start + chunk : n;
+        long long *mine = counts + (long long)t * buckets;
+        for (long long i = start; i < end; ++i) mine[bucket(splitters, buckets - 1, v[i])]++;
+        #pragma omp barrier
+        // bucket by bucket, each thread's keys after the previous threads'
+        #pragma omp single
+        {
+            long long at = 0;
+            for (long long b = 0; b < buckets; ++b) {
+                starts[b] = at;
+                for (int u = 0; u < T; ++u) {
+                    long long c = counts[(long long)u * buckets + b];
+                    counts[(long long)u * buckets + b] = at;
+                    at += c;
+                }
+            }
+            starts[buckets] = at;
+        }
+        for (long long i = start; i < end; ++i) out[mine[bucket(splitters, buckets - 1, v[i])]++] = v[i];
+        #pragma omp barrier
+        #pragma omp for schedule(dynamic, 1)
+        for (long long b = 0; b < buckets; ++b) qsort(out + starts[b], (size_t)(starts[b + 1] - starts[b]), sizeof(uint64_t), cmp_keys);
+    }
+
+    long long largest = 0;
+    for (long long b = 0; b < buckets; ++b)
+        if (starts[b + 1] - starts[b] > largest) largest = starts[b + 1] - starts[b];
+    free(starts);
+    free(counts);
+    free(splitters);
+    free(samples);
+    return largest;
+}
+
+int main(int argc, char **argv) {
+    if (argc != 9) {
+        fprintf(stderr,
+                "usage: %s <type> <algo> <N> <T> <cutoff> <oversample> <buckets> <seed>\n"
+                "  type:       u64 | f64\n"
+                "  algo:       merge | sample\n"
+                "  N:          keys (e.g. 100000000)\n"
+                "  T:          threads (e.g. 1,2,4,8,16)\n"
+                "  cutoff:     largest run sorted or merged by one thread (e.g. 4096)\n"
+                "  oversample: sample: keys sampled per bucket (e.g. 32; 0 for merge)\n"
+                "  buckets:    sample: buckets (e.g. 256; 0 for merge)\n"
+                "  seed:       seed of the keys (e.g. 20240604)\n",
+                argv[0]);
+        return 1;
+    }
+
+    const char *type = argv[1];
+    const char *algo = argv[2];
+    long long N = atoll(argv[3]);
+    int T = atoi(argv[4]);
+    long long cutoff = atoll(argv[5]);
+    long long oversample = atoll(argv[6]);
+    long long buckets = atoll(argv[7]);
+    uint64_t seed = strtoull(argv[8], NULL, 10);
+    if (N <= 0 || T <= 0 || cutoff <= 0) {
+        fprintf(stderr, "N, T and cutoff must be positive.\n");
+        return 1;
+    }
+    if (strcmp(type, "u64") != 0 && strcmp(type, "f64") != 0) {
+        fprintf(stderr, "unknown type: %s (use u64|f64)\n", type);
+        return 1;
+    }
+    int sample = strcmp(algo, "sample") == 0;
+    if (!sample && strcmp(algo, "merge") != 0) {
+        fprintf(stderr, "unknown algo: %s (use merge|sample)\n", algo);
+        return 1;
+    }
+    if (sample && (oversample <= 0 || buckets <= 0)) {
+        fprintf(stderr, "oversample and buckets must be positive.\n");
+        return 1;
+    }
+
+    f64_keys = strcmp(type, "f64") == 0;
+    size_t bytes = (size_t)N * sizeof(uint64_t);
+    uint64_t *keys = (uint64_t*) malloc(bytes), *seq = (uint64_t*) malloc(bytes);
+    uint64_t *par = (uint64_t*) malloc(bytes), *buf = (uint64_t*) malloc(bytes);
+    if (!keys || !seq || !par || !buf) {
+        fprintf(stderr, "Memory allocation failed\n");
+        return 2;
+    }
+    omp_set_num_threads(T);
+    #pragma omp parallel for schedule(static)
+    for (long long i = 0; i < N; ++i) keys[i] = key(seed, i);
+
+    // outside a parallel region every task runs at once: the baseline
+    memcpy(seq, keys, bytes);
+    double t0 = omp_get_wtime();
+    sort_in(seq, buf, N, cutoff);
+    double seq_time = omp_get_wtime() - t0;
+
+    // the merge sort sorts par in place, the sample sort into buf
+    memcpy(par, keys, bytes);
+    uint64_t *sorted = par;
+    long long largest = 0;
+    t0 = omp_get_wtime();
+    if (sample) {
+        largest = sample_sort(par, buf, N, T, oversample, buckets);
+        sorted = buf;
+    } else {
+        #pragma omp parallel
+        #pragma omp single
+        sort_in(par, buf, N, cutoff);
+    }
+    double elapsed = omp_get_wtime() - t0;
+    if (largest < 0) {
+        fprintf(stderr, "Memory allocation failed\n");
+        return 2;
+    }
+
+    int correct = memcmp(seq, sorted, bytes) == 0;
+    // the params of the record, with only the tuning the sort reads
+    char label[256];
+    unsigned long long s = (unsigned long long)seed;
+    if (sample)
+        snprintf(label, sizeof label, "sort,openmp,type=%s,algo=%s,N=%lld,T=%d,oversample=%lld,buckets=%lld,seed=%llu,", type, algo, N, T, oversample, buckets, s);
+    else
+        snprintf(label, sizeof label, "sort,openmp,type=%s,algo=%s,N=%lld,T=%d,cutoff=%lld,seed=%llu,", type, algo, N, T, cutoff, s);
+    printf("%stime,%.6f,sec\n", label, elapsed);
+    printf("%sspeedup,%.2f,x\n", label, seq_time / elapsed);
+    if (sample)
+        printf("%simbalance,%.2f,x\n", label, (double)largest * (double)buckets / (double)N);
+    printf("%scorrect,%d,boolean\n", label, correct);
+    printf("%speak_rss,%lld,bytes\n", label, peak_rss_bytes());
+
+    free(buf);
+    free(par);
+    free(seq);
+    free(keys);
+    return correct ? 0 : 3;
+}
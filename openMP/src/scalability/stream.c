@@ -0,0 +1,130 @@
+// OpenMP STREAM Benchmark (Scalability)
+// McCalpin's four kernels over three arrays a, b, c of N doubles, as
+// rust/src/scalability/stream.rs runs them:
+//   copy    c = a
+//   scale   b = s * c
+//   add     c = a + b
+//   triad   a = b + s * c
+// with s = 3, each a `parallel for schedule(static)`, after arrays first
+// touched by the same static loops. Every kernel runs once untimed, then
+// NTIMES times in a row; bandwidth is the bytes STREAM counts (16 N for
+// copy and scale, 24 N for add and triad) over the best time. From
+// a = 1, b = 2, c = 0 every element ends as a = 15, b = 3, c = 4.
+//
+// Usage:
+//   ./mp_stream <N> <T>
+//   N: elements per array (e.g., 100000000)
+//   T: number of threads (e.g., 1,2,4,8,16)
+//
+// Output (CSV-style, the Rust records' schema, one block per kernel):
+//   stream,openmp,op=triad,N=100000000,T=8,time,0.123456,sec
+//   stream,openmp,op=triad,N=100000000,T=8,bandwidth,19.440,GB/s
+//   stream,openmp,op=triad,N=100000000,T=8,correct,1,boolean
+//   stream,openmp,N=100000000,T=8,peak_rss,2400000000,bytes
+
+#include <omp.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <sys/resource.h>
+
+// timed runs of each kernel, as the Rust default --reps
+#define NTIMES 10
+#define SCALAR 3.0
+
+static const char *OPS[4] = {"copy", "scale", "add", "triad"};
+// words read and written per element by each kernel
+static const int WORDS[4] = {2, 2, 3, 3};
+
+// Peak resident set size of the whole process in bytes (Linux reports
+// ru_maxrss in KiB), to set beside the Rust binaries' peak_rss
+static long long peak_rss_bytes(void) {
+    struct rusage ru;
+    if (getrusage(RUSAGE_SELF, &ru) != 0) return -1;
+    return (long long)ru.ru_maxrss * 1024;
+}
+
+// one pass of kernel k
+static void stream(int k, double *a, double *b, double *c, long long N) {
+    switch (k) {
+    case 0:
+        #pragma omp parallel for schedule(static)
+        for (long long i = 0; i < N; ++i) c[i] = a[i];
+        break;
+    case 1:
+        #pragma omp parallel for schedule(static)
+        for (long long i = 0; i < N; ++i) b[i] = SCALAR * c[i];
+        break;
+    case 2:
+        #pragma omp parallel for schedule(static)
+        for (long long i = 0; i < N; ++i) c[i] = a[i] + b[i];
+        break;
+    default:
+        #pragma omp parallel for schedule(static)
+        for (long long i = 0; i < N; ++i) a[i] = b[i] + SCALAR * c[i];
+        break;
+    }
+}
+
+int main(int argc, char **argv) {
+    if (argc != 3) {
+        fprintf(stderr,
+                "usage: %s <N> <T>\n"
+                "  N: elements per array (e.g. 100000000)\n"
+                "  T: threads (e.g. 1,2,4,8,16)\n",
+                argv[0]);
+        return 1;
+    }
+
+    long long N = atoll(argv[1]);
+    int T = atoi(argv[2]);
+    if (N <= 0 || T <= 0) {
+        fprintf(stderr, "N and T must be positive.\n");
+        return 1;
+    }
+
+    double *a = (double*) malloc((size_t)N * sizeof(double));
+    double *b = (double*) malloc((size_t)N * sizeof(double));
+    double *c = (double*) malloc((size_t)N * sizeof(double));
+    if (!a || !b || !c) {
+        fprintf(stderr, "Memory allocation failed\n");
+        return 2;
+    }
+    omp_set_num_threads(T);
+
+    // first touch by the static chunks that stream the elements
+    #pragma omp parallel for schedule(static)
+    for (long long i = 0; i < N; ++i) {
+        a[i] = 1.0;
+        b[i] = 2.0;
+        c[i] = 0.0;
+    }
+
+    double best[4];
+    for (int k = 0; k < 4; ++k) {
+        stream(k, a, b, c, N);  // warm-up
+        best[k] = -1.0;
+        for (int r = 0; r < NTIMES; ++r) {
+            double t0 = omp_get_wtime();
+            stream(k, a, b, c, N);
+            double t = omp_get_wtime() - t0;
+            if (best[k] < 0 || t < best[k]) best[k] = t;
+        }
+    }
+
+    double eb = SCALAR * 1.0, ec = 1.0 + eb, ea = eb + SCALAR * ec;
+    int correct = 1;
+    for (long long i = 0; i < N; ++i) correct = correct && a[i] == ea && b[i] == eb && c[i] == ec;
+
+    for (int k = 0; k < 4; ++k) {
+        double bandwidth = (double)WORDS[k] * sizeof(double) * (double)N / best[k] / 1e9;
+        printf("stream,openmp,op=%s,N=%lld,T=%d,time,%.6f,sec\n", OPS[k], N, T, best[k]);
+        printf("stream,openmp,op=%s,N=%lld,T=%d,bandwidth,%.3f,GB/s\n", OPS[k], N, T, bandwidth);
+        printf("stream,openmp,op=%s,N=%lld,T=%d,correct,%d,boolean\n", OPS[k], N, T, correct);
+    }
+    printf("stream,openmp,N=%lld,T=%d,peak_rss,%lld,bytes\n", N, T, peak_rss_bytes());
+
+    free(c);
+    free(b);
+    free(a);
+    return correct ? 0 : 3;
+}
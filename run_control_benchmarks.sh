@@ -1,13 +1,15 @@
 #!/bin/bash
 
 # Controllability Benchmarks (Histogram)
-# Tests: Shared/Private Variables, Granularity Control, False Sharing Control
+# Tests: Shared/Private Variables, Granularity Control, False Sharing Control,
+# Thread Affinity Control, Heavy-Tailed Inputs, Lock Striping, 2D Histogram,
+# Floating-Point Histogram, False Sharing
 
 set -e  
 
 OUTPUT_FILE="controllability_results.csv"
 THREAD_COUNTS=(1 2 4 8 16)
-STRATEGIES=("atomic" "local")
+STRATEGIES=("atomic" "local" "manual" "scoped")  # manual, scoped: std / crossbeam threads in Rust, local in OpenMP
 DISTRIBUTIONS=("uniform" "skewed")
 N=10000000  # 10 million elements
 
@@ -20,19 +22,39 @@ GRAINS=(0 1000 10000)  # 0 = auto
 
 # Padding options for false sharing control
 PADS=(0 1)  # 0 = no padding, 1 = padded
+AFFINITIES=("none" "compact" "scatter")  # OMP_PROC_BIND false, close, spread
+
+# Heavy-tailed and concentrated inputs (zipf:s, gaussian:mu:sigma)
+HEAVY_DISTRIBUTIONS=("zipf:1" "zipf:1.5" "gaussian:128:8")
+
+# Lock counts of the striped strategy (1 = one global lock, 256 = one per bin)
+STRIPES=(1 4 16 64 256)
+
+# Inputs of the 2D histogram (a dense 256 x 256 grid, or a sparse diagonal band)
+GRID_DISTRIBUTIONS=("uniform" "diagonal")
+FLOAT_BINS=("16" "256" "4096")
+TAIL_EDGES="0:0.5:0.9:0.99:0.999:1"
+
+# Counter updates of the false-sharing benchmark (a load and a store, or an atomic add)
+UPDATES=("plain" "atomic")
 
 echo "=============================================="
 echo "Controllability Benchmarks"
 echo "=============================================="
-echo "Testing four aspects of control:"
-echo "  1. Shared vs Private Variables (atomic vs local)"
+echo "Testing nine aspects of control:"
+echo "  1. Shared vs Private Variables (atomic vs local vs manual/scoped threads)"
 echo "  2. Granularity Control (scheduling/chunk/grain)"
 echo "  3. False Sharing Control (padding)"
 echo "  4. Thread Affinity Control (thread-to-core pinning)"
+echo "  5. Heavy-Tailed Inputs (zipf and gaussian bins)"
+echo "  6. Lock Striping (an array of locks over groups of bins)"
+echo "  7. 2D Histogram (a 256 x 256 grid of bins)"
+echo "  8. Floating-Point Histogram (doubles binned by range lookup)"
+echo "  9. False Sharing (adjacent vs cache-line-padded counters)"
 echo ""
 echo "N=$N elements"
 echo ""
-echo "OpenMP: Uses native proc_bind clause"
+echo "OpenMP: Uses native proc_bind clause (OMP_PROC_BIND/OMP_PLACES for scatter)"
 echo "Rust:   Uses core_affinity crate"
 echo "=============================================="
 echo ""
@@ -42,7 +64,10 @@ rm -f "$OUTPUT_FILE"
 # Compile OpenMP version
 echo "Compiling OpenMP version..."
 cd openMP/src/control
-gcc -O3 -march=native -fopenmp -std=c11 control.c -o control_openmp
+gcc -O3 -march=native -fopenmp -std=c11 control.c -o control_openmp -lm
+gcc -O3 -march=native -fopenmp -std=c11 histogram2d.c -o hist2d_openmp
+gcc -O3 -march=native -fopenmp -std=c11 histogram_f64.c -o fhist_openmp -lm
+gcc -O3 -march=native -fopenmp -std=c11 false_sharing.c -o false_sharing_openmp
 cd ../../..
 
 # ============================================
@@ -53,7 +78,7 @@ echo "=========================================="
 echo "Running OpenMP Benchmarks"
 echo "=========================================="
 
-# 1. Shared vs Private: atomic vs local strategies
+# 1. Shared vs Private: atomic vs local (and manual, scoped) strategies
 echo ""
 echo "1. Testing Shared/Private Variable Control..."
 for DIST in "${DISTRIBUTIONS[@]}"; do
@@ -96,13 +121,73 @@ echo ""
 echo "4. Testing Thread Affinity Control (OpenMP)..."
 for DIST in "uniform"; do
     for T in "${THREAD_COUNTS[@]}"; do  # Use all thread counts for consistency
-        for AFFINITY in "${PADS[@]}"; do  # 0 = no pinning, 1 = pinned
+        for AFFINITY in "${AFFINITIES[@]}"; do
             echo "  OpenMP atomic: affinity=$AFFINITY, T=$T"
             ./openMP/src/control/control_openmp "atomic" "$DIST" "$N" "$T" "static" 0 0 "$AFFINITY" >> "$OUTPUT_FILE"
         done
     done
 done
 
+# 5. Heavy-Tailed Inputs: contention concentrated on a few hot bins
+echo ""
+echo "5. Testing Heavy-Tailed Inputs (OpenMP)..."
+for DIST in "${HEAVY_DISTRIBUTIONS[@]}"; do
+    for T in "${THREAD_COUNTS[@]}"; do
+        for STRATEGY in "${STRATEGIES[@]}"; do
+            echo "  OpenMP: $STRATEGY, dist=$DIST, T=$T"
+            ./openMP/src/control/control_openmp "$STRATEGY" "$DIST" "$N" "$T" "static" 0 0 >> "$OUTPUT_FILE"
+        done
+    done
+done
+
+# 6. Lock Striping: between one shared lock and one lock per bin
+echo ""
+echo "6. Testing Lock Striping (OpenMP)..."
+for DIST in "skewed"; do
+    for T in "${THREAD_COUNTS[@]}"; do
+        for S in "${STRIPES[@]}"; do
+            echo "  OpenMP striped: stripes=$S, T=$T, dist=$DIST"
+            ./openMP/src/control/control_openmp "striped" "$DIST" "$N" "$T" "static" 0 0 0 - 987654321 "$S" >> "$OUTPUT_FILE"
+        done
+    done
+done
+
+# 7. 2D Histogram: 65536 bins, out of cache, each local grid 512 KiB
+echo ""
+echo "7. Testing the 2D Histogram (OpenMP)..."
+for DIST in "${GRID_DISTRIBUTIONS[@]}"; do
+    for T in "${THREAD_COUNTS[@]}"; do
+        for STRATEGY in "atomic" "local"; do
+            echo "  OpenMP 2D: $STRATEGY, dist=$DIST, T=$T"
+            ./openMP/src/control/hist2d_openmp "$STRATEGY" "$DIST" "$N" "$T" >> "$OUTPUT_FILE"
+        done
+    done
+done
+
+# 8. Floating-Point Histogram: a multiply per value for equal bins, a binary search for the tail edges
+echo ""
+echo "8. Testing the Floating-Point Histogram (OpenMP)..."
+for T in "${THREAD_COUNTS[@]}"; do
+    for STRATEGY in "atomic" "local"; do
+        for B in "${FLOAT_BINS[@]}"; do
+            echo "  OpenMP f64: $STRATEGY, bins=$B, T=$T"
+            ./openMP/src/control/fhist_openmp "$STRATEGY" "$N" "$T" "$B" uniform >> "$OUTPUT_FILE"
+        done
+        echo "  OpenMP f64: $STRATEGY, edges=$TAIL_EDGES, T=$T"
+        ./openMP/src/control/fhist_openmp "$STRATEGY" "$N" "$T" 0 "$TAIL_EDGES" >> "$OUTPUT_FILE"
+    done
+done
+
+# 9. False Sharing: per-thread counters side by side vs a cache line apart
+echo ""
+echo "9. Testing False Sharing (OpenMP)..."
+for T in "${THREAD_COUNTS[@]}"; do
+    for U in "${UPDATES[@]}"; do
+        echo "  OpenMP false sharing: update=$U, T=$T"
+        ./openMP/src/control/false_sharing_openmp "$U" "$N" "$T" >> "$OUTPUT_FILE"
+    done
+done
+
 # ============================================
 # Rust/Rayon Benchmarks
 # ============================================
@@ -111,21 +196,21 @@ echo "=========================================="
 echo "Compiling Rust version..."
 echo "=========================================="
 cd rust
-cargo build --release --bin histogram 2>&1 | grep -v "Compiling\|Finished" || true
+cargo build --release --bin histogram --bin histogram2d --bin histogram_f64 --bin false_sharing 2>&1 | grep -v "Compiling\|Finished" || true
 
 echo ""
 echo "=========================================="
 echo "Running Rust/Rayon Benchmarks"
 echo "=========================================="
 
-# 1. Shared vs Private: atomic vs local strategies
+# 1. Shared vs Private: atomic vs local (and manual, scoped) strategies
 echo ""
 echo "1. Testing Shared/Private Variable Control..."
 for DIST in "${DISTRIBUTIONS[@]}"; do
     for T in "${THREAD_COUNTS[@]}"; do
         for STRATEGY in "${STRATEGIES[@]}"; do
             echo "  Rayon: $STRATEGY, dist=$DIST, T=$T (default settings)"
-            cargo run --release --bin histogram -- "$STRATEGY" "$DIST" "$N" "$T" 0 0 2>/dev/null >> "../$OUTPUT_FILE"
+            cargo run --release --bin histogram -- --strategy "$STRATEGY" --dist "$DIST" --size "$N" --threads "$T" 2>/dev/null >> "../$OUTPUT_FILE"
         done
     done
 done
@@ -137,7 +222,7 @@ for DIST in "uniform"; do
     for T in "${THREAD_COUNTS[@]}"; do  # Use all thread counts for consistency
         for GRAIN in "${GRAINS[@]}"; do
             echo "  Rayon atomic: grain=$GRAIN, T=$T"
-            cargo run --release --bin histogram -- "atomic" "$DIST" "$N" "$T" "$GRAIN" 0 2>/dev/null >> "../$OUTPUT_FILE"
+            cargo run --release --bin histogram -- --strategy atomic --dist "$DIST" --size "$N" --threads "$T" --grain "$GRAIN" 2>/dev/null >> "../$OUTPUT_FILE"
         done
     done
 done
@@ -149,7 +234,7 @@ for DIST in "skewed"; do
     for T in "${THREAD_COUNTS[@]}"; do  # Use all thread counts for consistency
         for PAD in "${PADS[@]}"; do
             echo "  Rayon atomic: pad=$PAD, T=$T, dist=$DIST"
-            cargo run --release --bin histogram -- "atomic" "$DIST" "$N" "$T" 0 "$PAD" 0 2>/dev/null >> "../$OUTPUT_FILE"
+            cargo run --release --bin histogram -- --strategy atomic --dist "$DIST" --size "$N" --threads "$T" --pad "$PAD" 2>/dev/null >> "../$OUTPUT_FILE"
         done
     done
 done
@@ -159,13 +244,73 @@ echo ""
 echo "4. Testing Thread Affinity Control (Rayon)..."
 for DIST in "uniform"; do
     for T in "${THREAD_COUNTS[@]}"; do  # Use all thread counts for consistency
-        for AFFINITY in "${PADS[@]}"; do  # 0 = no pinning, 1 = pinned
+        for AFFINITY in "${AFFINITIES[@]}"; do
             echo "  Rayon atomic: affinity=$AFFINITY, T=$T"
-            cargo run --release --bin histogram -- "atomic" "$DIST" "$N" "$T" 0 0 "$AFFINITY" 2>/dev/null >> "../$OUTPUT_FILE"
+            cargo run --release --bin histogram -- --strategy atomic --dist "$DIST" --size "$N" --threads "$T" --affinity "$AFFINITY" 2>/dev/null >> "../$OUTPUT_FILE"
+        done
+    done
+done
+
+# 5. Heavy-Tailed Inputs: contention concentrated on a few hot bins
+echo ""
+echo "5. Testing Heavy-Tailed Inputs (Rayon)..."
+for DIST in "${HEAVY_DISTRIBUTIONS[@]}"; do
+    for T in "${THREAD_COUNTS[@]}"; do
+        for STRATEGY in "${STRATEGIES[@]}"; do
+            echo "  Rayon: $STRATEGY, dist=$DIST, T=$T"
+            cargo run --release --bin histogram -- --strategy "$STRATEGY" --dist "$DIST" --size "$N" --threads "$T" 2>/dev/null >> "../$OUTPUT_FILE"
+        done
+    done
+done
+
+# 6. Lock Striping: between one shared lock and one lock per bin
+echo ""
+echo "6. Testing Lock Striping (Rayon)..."
+for DIST in "skewed"; do
+    for T in "${THREAD_COUNTS[@]}"; do
+        for S in "${STRIPES[@]}"; do
+            echo "  Rayon striped: stripes=$S, T=$T, dist=$DIST"
+            cargo run --release --bin histogram -- --strategy striped --dist "$DIST" --size "$N" --threads "$T" --stripes "$S" 2>/dev/null >> "../$OUTPUT_FILE"
         done
     done
 done
 
+# 7. 2D Histogram: 65536 bins, out of cache, each local grid 512 KiB
+echo ""
+echo "7. Testing the 2D Histogram (Rayon)..."
+for DIST in "${GRID_DISTRIBUTIONS[@]}"; do
+    for T in "${THREAD_COUNTS[@]}"; do
+        for STRATEGY in "atomic" "local"; do
+            echo "  Rayon 2D: $STRATEGY, dist=$DIST, T=$T"
+            cargo run --release --bin histogram2d -- --strategy "$STRATEGY" --dist "$DIST" --size "$N" --threads "$T" 2>/dev/null >> "../$OUTPUT_FILE"
+        done
+    done
+done
+
+# 8. Floating-Point Histogram: a multiply per value for equal bins, a binary search for the tail edges
+echo ""
+echo "8. Testing the Floating-Point Histogram (Rayon)..."
+for T in "${THREAD_COUNTS[@]}"; do
+    for STRATEGY in "atomic" "local"; do
+        for B in "${FLOAT_BINS[@]}"; do
+            echo "  Rayon f64: $STRATEGY, bins=$B, T=$T"
+            cargo run --release --bin histogram_f64 -- --strategy "$STRATEGY" --size "$N" --threads "$T" --bins "$B" 2>/dev/null >> "../$OUTPUT_FILE"
+        done
+        echo "  Rayon f64: $STRATEGY, edges=$TAIL_EDGES, T=$T"
+        cargo run --release --bin histogram_f64 -- --strategy "$STRATEGY" --size "$N" --threads "$T" --edges "$TAIL_EDGES" 2>/dev/null >> "../$OUTPUT_FILE"
+    done
+done
+
+# 9. False Sharing: per-thread counters side by side vs a cache line apart
+echo ""
+echo "9. Testing False Sharing (Rust)..."
+for T in "${THREAD_COUNTS[@]}"; do
+    for U in "${UPDATES[@]}"; do
+        echo "  Rust false sharing: update=$U, T=$T"
+        cargo run --release --bin false_sharing -- --update "$U" --size "$N" --threads "$T" 2>/dev/null >> "../$OUTPUT_FILE"
+    done
+done
+
 cd ..
 
 # ============================================
@@ -184,11 +329,16 @@ head -30 "$OUTPUT_FILE"
 echo "..."
 echo ""
 echo "Analysis areas:"
-echo "  1. Shared vs Private: Compare 'atomic' vs 'local' strategies"
+echo "  1. Shared vs Private: Compare 'atomic' vs 'local' strategies, and Rayon's local vs manual and scoped threads"
 echo "  2. Granularity: Compare OpenMP sched/chunk vs Rayon grain"
 echo "  3. False Sharing: Compare pad=0 vs pad=1 performance"
-echo "  4. Thread Affinity: Compare affinity=0 vs affinity=1 performance"
+echo "  4. Thread Affinity: Compare affinity=none vs compact vs scatter performance"
+echo "  5. Heavy-Tailed Inputs: Compare zipf/gaussian with uniform, atomic vs local vs manual/scoped"
+echo "  6. Lock Striping: Compare stripes=1..256 with atomic and local"
+echo "  7. 2D Histogram: Compare atomic vs local on 65536 bins, dense and diagonal"
+echo "  8. Floating-Point Histogram: Compare equal bins (arithmetic) with tail edges (binary search)"
+echo "  9. False Sharing: slowdown of adjacent over padded counters, plain and atomic"
 echo ""
-echo "Note: OpenMP uses native proc_bind(close) clause"
+echo "Note: OpenMP uses native proc_bind(close) clause, OMP_PROC_BIND=spread for scatter"
 echo "      Rust uses external core_affinity crate"
 echo ""
@@ -10,6 +10,7 @@ echo ""
 OUTPUT_FILE="runtime_overhead_results.csv"
 THREAD_COUNTS=(1 2 4 8 16)
 ITERATIONS=(10000 25000 50000 75000 100000)
+PARFOR_SIZES=(10 100 1000 10000 100000 1000000 10000000)
 
 rm -f "$OUTPUT_FILE"
 
@@ -24,12 +25,21 @@ for T in "${THREAD_COUNTS[@]}"; do
         ./openMP/src/runtime_overhead/overhead_openmp $T $R >> "$OUTPUT_FILE"
     done
 done
+for T in "${THREAD_COUNTS[@]}"; do
+    for N in "${PARFOR_SIZES[@]}"; do
+        # R = 10^8 / N within 10..100000, as the Rust parfor cells
+        R=$((100000000 / N))
+        R=$((R < 10 ? 10 : R > 100000 ? 100000 : R))
+        echo "  - parfor T=$T, N=$N, R=$R"
+        ./openMP/src/runtime_overhead/overhead_openmp $T $R $N >> "$OUTPUT_FILE"
+    done
+done
 
 echo ""
 echo "Running Rust benchmarks..."
 cd rust
 cargo build --release --bin runtime_overhead 2>&1 | grep -v "Compiling\|Finished" || true
-cargo run --release --bin runtime_overhead 2>/dev/null >> "../$OUTPUT_FILE"
+cargo run --release --bin runtime_overhead -- --threads "$(IFS=,; echo "${THREAD_COUNTS[*]}")" --iters "$(IFS=,; echo "${ITERATIONS[*]}")" 2>/dev/null >> "../$OUTPUT_FILE"
 cd ..
 
 echo ""
@@ -1,22 +1,25 @@
 #!/bin/bash
 
-# Programmability Benchmark (Prefix Sum)
+# Programmability Benchmarks (Prefix Sum, Segmented Scan)
 
 set -e
 
-echo "=== Programmability Benchmarks (Prefix Sum) ==="
+echo "=== Programmability Benchmarks (Prefix Sum, Segmented Scan) ==="
 echo ""
 echo "Compiling OpenMP..."
 cd openMP/src/programmability
 gcc -O3 -march=native -fopenmp -std=c11 -o mp_prefix_sum prefix_sum.c
+gcc -O3 -march=native -fopenmp -std=c11 -o mp_segmented_scan segmented_scan.c
 cd ../../..
 
 echo "Running OpenMP..."
 ./openMP/src/programmability/mp_prefix_sum
+./openMP/src/programmability/mp_segmented_scan
 echo ""
 echo "Running Rust..."
 cd rust
-cargo build --release --bin prefix_sum 2>&1 | grep -v "Compiling\|Finished" || true
+cargo build --release --bin prefix_sum --bin segmented_scan 2>&1 | grep -v "Compiling\|Finished" || true
 cargo run --release --bin prefix_sum 2>/dev/null
+cargo run --release --bin segmented_scan 2>/dev/null
 cd ..
 echo ""
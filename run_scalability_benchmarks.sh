@@ -1,14 +1,20 @@
 #!/bin/bash
 
-# Scalability Benchmark (Matrix Multiply)
+# Scalability Benchmark (Matrix Multiply, Reduction, STREAM, Jacobi, Sort, N-Body, Monte Carlo)
 
 set -e
 
-echo "=== Scalability Benchmarks (Matrix Multiply) ==="
+echo "=== Scalability Benchmarks (Matrix Multiply, Reduction, STREAM, Jacobi, Sort, N-Body, Monte Carlo) ==="
 echo ""
 echo "Compiling OpenMP..."
 cd openMP/src/scalability
 gcc -O3 -march=native -fopenmp -std=c11 -o mp_matrix_multiply matrix_multiply.c -lm
+gcc -O3 -march=native -fopenmp -std=c11 -o mp_reduction reduction.c
+gcc -O3 -march=native -fopenmp -std=c11 -o mp_stream stream.c
+gcc -O3 -march=native -fopenmp -std=c11 -o mp_jacobi jacobi.c -lm
+gcc -O3 -march=native -fopenmp -std=c11 -o mp_sort sort.c
+gcc -O3 -march=native -fopenmp -std=c11 -o mp_nbody nbody.c -lm
+gcc -O3 -march=native -fopenmp -std=c11 -o mp_monte_carlo monte_carlo.c
 cd ../../..
 
 echo "Running OpenMP..."
@@ -16,14 +22,122 @@ echo "Running OpenMP..."
 echo ""
 echo "Running Rust..."
 cd rust
-cargo build --release --bin matrix_multiply 2>&1 | grep -v "Compiling\|Finished" || true
+cargo build --release --bin matrix_multiply --bin reduction --bin stream --bin jacobi --bin sort --bin nbody --bin monte_carlo 2>&1 | grep -v "Compiling\|Finished" || true
 cargo run --release --bin matrix_multiply 2>/dev/null | tee ../rust_scalability_results.txt
 cd ..
 echo ""
 
+# Reduction: the sum and the dot product of N doubles, four ways
+REDUCTION_FILE="reduction_results.csv"
+REDUCTION_N=100000000
+THREAD_COUNTS=(1 2 4 8 16)
+OPS=("sum" "dot")
+STRATEGIES=("sequential" "builtin" "chunked" "atomic")
+rm -f "$REDUCTION_FILE"
+
+echo "Running reductions (N=$REDUCTION_N)..."
+for OP in "${OPS[@]}"; do
+    for S in "${STRATEGIES[@]}"; do
+        for T in "${THREAD_COUNTS[@]}"; do
+            echo "  op=$OP, strategy=$S, T=$T"
+            ./openMP/src/scalability/mp_reduction "$OP" "$S" "$REDUCTION_N" "$T" >> "$REDUCTION_FILE"
+            ./rust/target/release/reduction --op "$OP" --strategy "$S" --size "$REDUCTION_N" --threads "$T" 2>/dev/null >> "$REDUCTION_FILE"
+        done
+    done
+done
+echo ""
+
+# STREAM: copy, scale, add and triad bandwidth over three arrays of N doubles
+STREAM_FILE="stream_results.csv"
+STREAM_N=100000000
+rm -f "$STREAM_FILE"
+
+echo "Running STREAM (N=$STREAM_N)..."
+for T in "${THREAD_COUNTS[@]}"; do
+    echo "  T=$T"
+    ./openMP/src/scalability/mp_stream "$STREAM_N" "$T" >> "$STREAM_FILE"
+    ./rust/target/release/stream --size "$STREAM_N" --threads "$T" 2>/dev/null >> "$STREAM_FILE"
+done
+echo ""
+
+# Jacobi: a 5-point stencil on n x n grids, to a tolerance or ITERS iterations
+JACOBI_FILE="jacobi_results.csv"
+JACOBI_SIZES=(1024 4096)
+JACOBI_ITERS=1000
+JACOBI_TOL=0.000001
+rm -f "$JACOBI_FILE"
+
+echo "Running Jacobi (iters=$JACOBI_ITERS, tol=$JACOBI_TOL)..."
+for N in "${JACOBI_SIZES[@]}"; do
+    for T in "${THREAD_COUNTS[@]}"; do
+        echo "  n=$N, T=$T"
+        ./openMP/src/scalability/mp_jacobi "$N" "$JACOBI_ITERS" "$JACOBI_TOL" "$T" >> "$JACOBI_FILE"
+        ./rust/target/release/jacobi --size "$N" --iters "$JACOBI_ITERS" --tol "$JACOBI_TOL" --threads "$T" 2>/dev/null >> "$JACOBI_FILE"
+    done
+done
+echo ""
+
+# Sort: N random keys, merge sort with tasks / rayon::join and sample sort
+# (and rayon's sorts)
+SORT_FILE="sort_results.csv"
+SORT_N=100000000
+SORT_CUTOFF=4096
+SORT_OVERSAMPLE=32
+SORT_BUCKETS=256
+SORT_SEED=20240604
+rm -f "$SORT_FILE"
+
+echo "Running sorts (N=$SORT_N, cutoff=$SORT_CUTOFF, oversample=$SORT_OVERSAMPLE, buckets=$SORT_BUCKETS)..."
+for K in u64 f64; do
+    for T in "${THREAD_COUNTS[@]}"; do
+        echo "  type=$K, T=$T"
+        ./openMP/src/scalability/mp_sort "$K" merge "$SORT_N" "$T" "$SORT_CUTOFF" 0 0 "$SORT_SEED" >> "$SORT_FILE"
+        ./openMP/src/scalability/mp_sort "$K" sample "$SORT_N" "$T" "$SORT_CUTOFF" "$SORT_OVERSAMPLE" "$SORT_BUCKETS" "$SORT_SEED" >> "$SORT_FILE"
+        ./rust/target/release/sort --type "$K" --size "$SORT_N" --threads "$T" --cutoff "$SORT_CUTOFF" \
+            --oversample "$SORT_OVERSAMPLE" --buckets "$SORT_BUCKETS" --seed "$SORT_SEED" 2>/dev/null >> "$SORT_FILE"
+    done
+done
+echo ""
+
+# N-body: STEPS all-pairs gravity steps over N bodies
+NBODY_FILE="nbody_results.csv"
+NBODY_SIZES=(4096 16384)
+NBODY_STEPS=10
+rm -f "$NBODY_FILE"
+
+echo "Running N-body (steps=$NBODY_STEPS)..."
+for N in "${NBODY_SIZES[@]}"; do
+    for T in "${THREAD_COUNTS[@]}"; do
+        echo "  N=$N, T=$T"
+        ./openMP/src/scalability/mp_nbody "$N" "$NBODY_STEPS" "$T" >> "$NBODY_FILE"
+        ./rust/target/release/nbody --size "$N" --steps "$NBODY_STEPS" --threads "$T" 2>/dev/null >> "$NBODY_FILE"
+    done
+done
+echo ""
+
+# Monte Carlo pi: N samples, a generator per thread and one reduction
+MONTE_CARLO_FILE="monte_carlo_results.csv"
+MONTE_CARLO_N=1000000000
+MONTE_CARLO_SEED=20240606
+rm -f "$MONTE_CARLO_FILE"
+
+echo "Running Monte Carlo pi (N=$MONTE_CARLO_N)..."
+for T in "${THREAD_COUNTS[@]}"; do
+    echo "  T=$T"
+    ./openMP/src/scalability/mp_monte_carlo "$MONTE_CARLO_N" "$T" "$MONTE_CARLO_SEED" >> "$MONTE_CARLO_FILE"
+    ./rust/target/release/monte_carlo --size "$MONTE_CARLO_N" --threads "$T" --seed "$MONTE_CARLO_SEED" 2>/dev/null >> "$MONTE_CARLO_FILE"
+done
+echo ""
+
 echo "=== Scalability benchmarks completed! ==="
 echo ""
 echo "Output files:"
 echo "  - openmp_scalability_results.txt"
 echo "  - rust_scalability_results.txt"
+echo "  - reduction_results.csv"
+echo "  - stream_results.csv"
+echo "  - jacobi_results.csv"
+echo "  - sort_results.csv"
+echo "  - nbody_results.csv"
+echo "  - monte_carlo_results.csv"
 echo ""
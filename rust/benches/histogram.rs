@@ -0,0 +1,46 @@
+// Criterion harness for the histogram strategies (cargo bench --bench histogram)
+// 2^22 bytes of uniform and of skewed input, binned with every strategy on a
+// Rayon pool of all logical CPUs: shared atomic bins, contiguous and padded,
+// thread-local histograms merged at the end, and 16 lock stripes (plus the std::simd local
+// path with --features simd on nightly); the manual and scoped strategies spawn as
+// many plain std::threads or crossbeam scoped threads themselves. The per-chunk local kernel is also measured on its own.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use openmp_rust_benchmarks::backend::{Backend, Placement, Pool};
+use openmp_rust_benchmarks::controllability::histogram::{gen_skewed, gen_uniform, hist_atomic, hist_local, hist_manual, hist_scoped, hist_striped, local_hist_scalar, STRIPES};
+use openmp_rust_benchmarks::pages::Pages;
+use openmp_rust_benchmarks::threads;
+use std::hint::black_box;
+use std::sync::Arc;
+
+const N: usize = 1 << 22;
+
+fn histogram(c: &mut Criterion) {
+    let pool = Pool::new(Backend::Rayon, threads::logical());
+    let mut strategies = vec![("atomic", false), ("atomic_padded", true), ("local", false), ("striped", false)];
+    if cfg!(feature = "simd") {
+        strategies.push(("local_simd", true));
+    }
+
+    let mut group = c.benchmark_group("histogram");
+    group.throughput(Throughput::Bytes(N as u64));
+    for (dist, data) in [("uniform", gen_uniform(N, 123456789, Pages::Default)), ("skewed", gen_skewed(N, 987654321, Pages::Default))] {
+        for &(strategy, flag) in &strategies {
+            group.bench_with_input(BenchmarkId::new(strategy, dist), &data[..], |b, data| {
+                b.iter(|| match strategy {
+                    "atomic" | "atomic_padded" => hist_atomic(&pool, black_box(data), 0, flag),
+                    "striped" => hist_striped(&pool, black_box(data), 0, STRIPES),
+                    _ => hist_local(&pool, black_box(data), 0, flag),
+                })
+            });
+        }
+        let shared = Arc::new(data.to_vec());
+        group.bench_function(BenchmarkId::new("manual", dist), |b| b.iter(|| hist_manual(black_box(&shared), pool.threads(), &Placement::default())));
+        group.bench_with_input(BenchmarkId::new("scoped", dist), &data[..], |b, data| b.iter(|| hist_scoped(black_box(data), pool.threads(), &Placement::default())));
+        group.bench_with_input(BenchmarkId::new("chunk_scalar", dist), &data[..], |b, data| b.iter(|| local_hist_scalar(black_box(data))));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, histogram);
+criterion_main!(benches);
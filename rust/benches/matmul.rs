@@ -0,0 +1,37 @@
+// Criterion harness for the matrix multiply inner kernel (cargo bench --bench matmul)
+// `multiply_rows`, the kernel every parallel matmul task runs, computing all
+// n rows on the calling thread: the i-j-k scalar loop and, with --features
+// simd on nightly, the std::simd i-k-j row kernel; and `multiply_rows_tiled`,
+// the cache-blocked kernel, with 32 x 32 and 64 x 64 tiles; and
+// `multiply_rows_transposed`, the dot products against B^T (B is constant,
+// so B serves as its own transpose). Without a pool in the way the
+// measurements isolate the loop nest itself.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use openmp_rust_benchmarks::pages::Pages;
+use openmp_rust_benchmarks::scalability::matrix_multiply::{create_matrix, multiply_rows, multiply_rows_tiled, multiply_rows_transposed};
+use std::hint::black_box;
+
+const SIZES: [usize; 3] = [64, 128, 256];
+
+fn matmul(c: &mut Criterion) {
+    let mut group = c.benchmark_group("matmul_rows");
+    for n in SIZES {
+        let (a, b) = (create_matrix(n, 1.0, Pages::Default), create_matrix(n, 2.0, Pages::Default));
+        let mut rows = vec![0.0; n * n];
+        // multiply-adds per product
+        group.throughput(Throughput::Elements((n * n * n) as u64));
+        let kernels: &[(&str, bool)] = if cfg!(feature = "simd") { &[("scalar", false), ("simd", true)] } else { &[("scalar", false)] };
+        for &(name, simd) in kernels {
+            group.bench_with_input(BenchmarkId::new(name, n), &n, |bench, &n| bench.iter(|| multiply_rows(black_box(&a), black_box(&b), n, simd, 0, &mut rows)));
+        }
+        for block in [32, 64] {
+            group.bench_with_input(BenchmarkId::new(format!("tiled{}", block), n), &n, |bench, &n| bench.iter(|| multiply_rows_tiled(black_box(&a), black_box(&b), n, block, 0, &mut rows)));
+        }
+        group.bench_with_input(BenchmarkId::new("transposed", n), &n, |bench, &n| bench.iter(|| multiply_rows_transposed(black_box(&a), black_box(&b), n, 0, &mut rows)));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, matmul);
+criterion_main!(benches);
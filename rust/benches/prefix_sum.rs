@@ -0,0 +1,40 @@
+// Criterion harness for the prefix sum (cargo bench --bench prefix_sum)
+// The sequential scan, the two-pass parallel scan, the Blelloch tree scan and
+// the Hillis-Steele scan over 2^22 ones, the parallel ones on every backend at
+// 1 thread and at all logical CPUs. Criterion
+// compares each run with the last one saved under target/criterion, so a
+// change to the kernel shows up as a significant difference or none.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use openmp_rust_benchmarks::backend::{Backend, Pool};
+use openmp_rust_benchmarks::pages::Pages;
+use openmp_rust_benchmarks::programmability::prefix_sum::{prefix_sum_blelloch, prefix_sum_hillis_steele, prefix_sum_parallel, prefix_sum_sequential};
+use openmp_rust_benchmarks::threads;
+use std::hint::black_box;
+
+const N: usize = 1 << 22;
+
+fn prefix_sum(c: &mut Criterion) {
+    let input = vec![1u64; N];
+    let mut group = c.benchmark_group("prefix_sum");
+    group.throughput(Throughput::Elements(N as u64));
+
+    group.bench_function("sequential", |b| b.iter(|| prefix_sum_sequential(black_box(&input), Pages::Default)));
+    let mut counts = vec![1, threads::logical()];
+    counts.dedup();
+    for backend in Backend::ALL {
+        for &threads in &counts {
+            let pool = Pool::new(backend, threads);
+            let id = BenchmarkId::new(format!("parallel/{}", backend.name()), threads);
+            group.bench_with_input(id, &pool, |b, pool| b.iter(|| prefix_sum_parallel(pool, black_box(&input), Pages::Default)));
+            let id = BenchmarkId::new(format!("blelloch/{}", backend.name()), threads);
+            group.bench_with_input(id, &pool, |b, pool| b.iter(|| prefix_sum_blelloch(pool, black_box(&input), Pages::Default)));
+            let id = BenchmarkId::new(format!("hillis-steele/{}", backend.name()), threads);
+            group.bench_with_input(id, &pool, |b, pool| b.iter(|| prefix_sum_hillis_steele(pool, black_box(&input), Pages::Default)));
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, prefix_sum);
+criterion_main!(benches);
@@ -0,0 +1,68 @@
+// Build facts for the result metadata (src/metadata.rs)
+// The compiler version, the cargo profile and the git commit of the sources
+// are only known while building, so they are handed to the crate as
+// environment variables. The commit is re-read whenever HEAD moves.
+// With --features ffi the OpenMP C kernels are compiled here too, with the
+// flags of the run_*.sh scripts and BENCH_FFI defined (no main()), and
+// linked with libgomp (src/ffi.rs). With --features blas the system OpenBLAS
+// is linked for the dgemm reference (src/blas.rs).
+
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+// the C programs holding the kernels src/ffi.rs calls, under openMP/src
+#[cfg(feature = "ffi")]
+const OPENMP_SOURCES: [&str; 3] = ["control/control.c", "scalability/matrix_multiply.c", "programmability/prefix_sum.c"];
+
+// stdout of a successful command, trimmed
+fn output(program: &str, args: &[&str]) -> Option<String> {
+    Command::new(program)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(feature = "ffi")]
+fn compile_openmp_kernels(manifest: &str) {
+    let src = Path::new(manifest).join("../openMP/src");
+    let sources: Vec<_> = OPENMP_SOURCES.iter().map(|file| src.join(file)).collect();
+    cc::Build::new()
+        .files(&sources)
+        .define("BENCH_FFI", None)
+        .opt_level(3)
+        .flag("-march=native")
+        .flag("-fopenmp")
+        .flag("-std=c11")
+        // the helpers only main() uses
+        .flag("-Wno-unused-function")
+        .compile("openmp_kernels");
+    println!("cargo:rustc-link-lib=gomp");
+    for source in &sources {
+        println!("cargo:rerun-if-changed={}", source.display());
+    }
+}
+
+fn main() {
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let version = output(&rustc, &["--version"]).unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=BENCH_RUSTC_VERSION={}", version);
+    println!("cargo:rustc-env=BENCH_PROFILE={}", env::var("PROFILE").unwrap_or_else(|_| "unknown".to_string()));
+
+    let manifest = env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string());
+    let commit = output("git", &["-C", &manifest, "rev-parse", "HEAD"]).unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=BENCH_GIT_COMMIT={}", commit);
+    if let Some(git_dir) = output("git", &["-C", &manifest, "rev-parse", "--absolute-git-dir"]) {
+        // a path that does not exist would rerun the script on every build
+        for watched in ["HEAD", "refs", "packed-refs"].map(|name| Path::new(&git_dir).join(name)).iter().filter(|path| path.exists()) {
+            println!("cargo:rerun-if-changed={}", watched.display());
+        }
+    }
+    #[cfg(feature = "ffi")]
+    compile_openmp_kernels(&manifest);
+    #[cfg(feature = "blas")]
+    println!("cargo:rustc-link-lib=openblas");
+    println!("cargo:rerun-if-changed=build.rs");
+}
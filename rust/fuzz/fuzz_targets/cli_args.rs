@@ -0,0 +1,60 @@
+// Fuzzes the argument layer shared by the benchmark binaries
+// The input is split at NUL bytes into an argument list, which goes through
+// the clap command of every registered benchmark and through the value
+// parsers the binaries share. Bad input has to come back as an Err; any panic
+// is a bug.
+//   cargo +nightly fuzz run cli_args
+
+#![no_main]
+
+use clap::{FromArgMatches, Parser};
+use libfuzzer_sys::fuzz_target;
+use openmp_rust_benchmarks::backend::Backend;
+use openmp_rust_benchmarks::cli::{self, Output};
+use openmp_rust_benchmarks::controllability::histogram;
+use openmp_rust_benchmarks::kernels;
+use openmp_rust_benchmarks::logging::LogFormat;
+use openmp_rust_benchmarks::multiproc::Ipc;
+use openmp_rust_benchmarks::pages::Pages;
+use openmp_rust_benchmarks::results::Format;
+use openmp_rust_benchmarks::stats::Filter;
+use openmp_rust_benchmarks::threads;
+use openmp_rust_benchmarks::upload::Uploader;
+
+fuzz_target!(|data: &[u8]| {
+    let text = String::from_utf8_lossy(data);
+    let args: Vec<String> = std::iter::once("bin")
+        .chain(text.split('\0'))
+        .map(str::to_string)
+        .collect();
+
+    for benchmark in kernels::REGISTRY {
+        if let Ok(matches) = benchmark.command().try_get_matches_from(&args) {
+            // whatever clap accepted has to convert to the typed options
+            Output::from_arg_matches(&matches).unwrap();
+        }
+    }
+    if let Ok(parsed) = histogram::Args::try_parse_from(&args) {
+        assert!(parsed.size > 0 && parsed.threads > 0);
+    }
+
+    for value in &args[1..] {
+        if let Ok(v) = cli::positive(value) {
+            assert!(v > 0);
+        }
+        let _ = cli::switch(value);
+        let _ = Format::parse(value);
+        let _ = Uploader::new(value);
+        let _ = LogFormat::parse(value);
+        let _ = Backend::parse(value);
+        let _ = Ipc::parse(value);
+        let _ = Pages::parse(value);
+        let _ = Filter::parse(value);
+        if let Ok(t) = threads::parse_spec(value) {
+            assert!(t > 0);
+        }
+        if let Ok(counts) = threads::parse_list(value) {
+            assert!(counts.windows(2).all(|w| w[0] < w[1]) && counts[0] >= 1);
+        }
+    }
+});
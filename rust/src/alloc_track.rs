@@ -0,0 +1,138 @@
+// Heap allocation tracking (feature "alloc-track")
+// With the feature enabled the crate installs a counting wrapper around the
+// system allocator as the global allocator of every binary. Kernels open a
+// Region around the measured code and attach its counts to their record:
+//   allocs       allocation calls (realloc counts as one)
+//   alloc_bytes  bytes requested by those calls
+//   peak_heap    highest live heap while the region was open
+// Regions are not nested: starting one resets the peak. Without the feature
+// regions are free and report nothing, so records keep their usual metrics.
+
+#[cfg(feature = "alloc-track")]
+mod tracking {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    pub static ALLOCS: AtomicU64 = AtomicU64::new(0);
+    pub static BYTES: AtomicU64 = AtomicU64::new(0);
+    pub static LIVE: AtomicU64 = AtomicU64::new(0);
+    pub static PEAK: AtomicU64 = AtomicU64::new(0);
+
+    fn grew(requested: usize, live_delta: u64) {
+        ALLOCS.fetch_add(1, Ordering::Relaxed);
+        BYTES.fetch_add(requested as u64, Ordering::Relaxed);
+        let live = LIVE.fetch_add(live_delta, Ordering::Relaxed) + live_delta;
+        PEAK.fetch_max(live, Ordering::Relaxed);
+    }
+
+    pub struct TrackingAllocator;
+
+    unsafe impl GlobalAlloc for TrackingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc(layout);
+            if !ptr.is_null() {
+                grew(layout.size(), layout.size() as u64);
+            }
+            ptr
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc_zeroed(layout);
+            if !ptr.is_null() {
+                grew(layout.size(), layout.size() as u64);
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout);
+            LIVE.fetch_sub(layout.size() as u64, Ordering::Relaxed);
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_ptr = System.realloc(ptr, layout, new_size);
+            if !new_ptr.is_null() {
+                if new_size >= layout.size() {
+                    grew(new_size, (new_size - layout.size()) as u64);
+                } else {
+                    grew(new_size, 0);
+                    LIVE.fetch_sub((layout.size() - new_size) as u64, Ordering::Relaxed);
+                }
+            }
+            new_ptr
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: TrackingAllocator = TrackingAllocator;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocStats {
+    pub allocs: u64,
+    pub bytes: u64,
+    pub peak: u64,
+}
+
+/// Counts the allocations made between `start` and `finish`, on any thread.
+pub struct Region {
+    #[cfg(feature = "alloc-track")]
+    allocs: u64,
+    #[cfg(feature = "alloc-track")]
+    bytes: u64,
+}
+
+impl Region {
+    #[cfg(feature = "alloc-track")]
+    pub fn start() -> Self {
+        use std::sync::atomic::Ordering;
+        use tracking::{ALLOCS, BYTES, LIVE, PEAK};
+
+        PEAK.store(LIVE.load(Ordering::Relaxed), Ordering::Relaxed);
+        Region {
+            allocs: ALLOCS.load(Ordering::Relaxed),
+            bytes: BYTES.load(Ordering::Relaxed),
+        }
+    }
+
+    #[cfg(not(feature = "alloc-track"))]
+    pub fn start() -> Self {
+        Region {}
+    }
+
+    /// The region's counts; None when built without "alloc-track".
+    #[cfg(feature = "alloc-track")]
+    pub fn finish(self) -> Option<AllocStats> {
+        use std::sync::atomic::Ordering;
+        use tracking::{ALLOCS, BYTES, PEAK};
+
+        Some(AllocStats {
+            allocs: ALLOCS.load(Ordering::Relaxed) - self.allocs,
+            bytes: BYTES.load(Ordering::Relaxed) - self.bytes,
+            peak: PEAK.load(Ordering::Relaxed),
+        })
+    }
+
+    #[cfg(not(feature = "alloc-track"))]
+    pub fn finish(self) -> Option<AllocStats> {
+        None
+    }
+}
+
+#[cfg(all(test, feature = "alloc-track"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_region_counts() {
+        let region = Region::start();
+        let v: Vec<u64> = std::hint::black_box(Vec::with_capacity(1000));
+        drop(v);
+        let stats = region.finish().unwrap();
+
+        // other test threads may allocate concurrently, so lower bounds only
+        assert!(stats.allocs >= 1);
+        assert!(stats.bytes >= 8000);
+        assert!(stats.peak >= 8000);
+    }
+}
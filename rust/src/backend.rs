@@ -0,0 +1,560 @@
+// Parallel execution backends
+// Kernels are written once against the chunk primitives of `Pool`; the
+// backend only decides how those chunks are executed:
+//   rayon      work-stealing Rayon pool, adaptive splitting
+//   threads    std::thread::scope, chunks dealt round-robin to T threads
+//   crossbeam  crossbeam scoped threads, same static schedule
+//   workers    persistent fixed-size pool fed boxed jobs over a channel
+//   tokio-blocking / tokio-async (feature "tokio")
+//              the static schedule's per-thread work submitted to a tokio
+//              runtime via spawn_blocking, or as plain async tasks on T workers
+// The static backends mirror OpenMP's `schedule(static, chunk)`.
+
+use rayon::prelude::*;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Rayon,
+    Threads,
+    Crossbeam,
+    Workers,
+    #[cfg(feature = "tokio")]
+    TokioBlocking,
+    #[cfg(feature = "tokio")]
+    TokioAsync,
+}
+
+impl Backend {
+    #[cfg(not(feature = "tokio"))]
+    pub const ALL: [Backend; 4] = [Backend::Rayon, Backend::Threads, Backend::Crossbeam, Backend::Workers];
+    #[cfg(feature = "tokio")]
+    pub const ALL: [Backend; 6] = [
+        Backend::Rayon,
+        Backend::Threads,
+        Backend::Crossbeam,
+        Backend::Workers,
+        Backend::TokioBlocking,
+        Backend::TokioAsync,
+    ];
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "rayon" => Ok(Backend::Rayon),
+            "threads" => Ok(Backend::Threads),
+            "crossbeam" => Ok(Backend::Crossbeam),
+            "workers" => Ok(Backend::Workers),
+            #[cfg(feature = "tokio")]
+            "tokio-blocking" => Ok(Backend::TokioBlocking),
+            #[cfg(feature = "tokio")]
+            "tokio-async" => Ok(Backend::TokioAsync),
+            _ => Err(format!("unknown backend: {} (use {})", s, Backend::ALL.map(Backend::name).join("|"))),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Rayon => "rayon",
+            Backend::Threads => "threads",
+            Backend::Crossbeam => "crossbeam",
+            Backend::Workers => "workers",
+            #[cfg(feature = "tokio")]
+            Backend::TokioBlocking => "tokio-blocking",
+            #[cfg(feature = "tokio")]
+            Backend::TokioAsync => "tokio-async",
+        }
+    }
+}
+
+/// Where the threads of a run are pinned, after OMP_PROC_BIND / OMP_PLACES.
+/// The places are the CPUs the process may run on, in OS order, or an
+/// explicit list.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Affinity {
+    /// no pinning
+    #[default]
+    None,
+    /// thread i on CPU i, neighbours side by side (proc_bind close)
+    Compact,
+    /// the threads evenly spaced over all CPUs (proc_bind spread)
+    Scatter,
+    /// thread i on the i-th CPU of the list, placed as close does
+    List(Vec<usize>),
+}
+
+impl Affinity {
+    /// none | compact | scatter | list:C,C,...; 0 and 1 stand for none and
+    /// compact, the old on/off switch.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "none" | "0" => Ok(Affinity::None),
+            "compact" | "1" => Ok(Affinity::Compact),
+            "scatter" => Ok(Affinity::Scatter),
+            _ => {
+                let list = s.strip_prefix("list:").ok_or_else(|| format!("unknown affinity: {} (use none|compact|scatter|list:C,C,...)", s))?;
+                let cpus = list
+                    .split(',')
+                    .map(|cpu| cpu.trim().parse::<usize>().map_err(|_| format!("bad CPU {:?} in {}", cpu, s)))
+                    .collect::<Result<Vec<usize>, String>>()?;
+                Ok(Affinity::List(cpus))
+            }
+        }
+    }
+
+    /// The policy as `parse` reads it, for records: none, compact, scatter
+    /// or list:C,C,...
+    pub fn name(&self) -> String {
+        match self {
+            Affinity::None => "none".to_string(),
+            Affinity::Compact => "compact".to_string(),
+            Affinity::Scatter => "scatter".to_string(),
+            Affinity::List(cpus) => format!("list:{}", cpus.iter().map(usize::to_string).collect::<Vec<_>>().join(",")),
+        }
+    }
+
+    /// The CPUs of `threads` threads. With T threads on P places, close puts
+    /// thread i on place i, or on place i·P/T when T > P, so neighbours
+    /// share; spread always takes place i·P/T.
+    pub fn placement(&self, threads: usize) -> Placement {
+        let places = match self {
+            Affinity::None => return Placement::default(),
+            Affinity::List(cpus) => cpus.clone(),
+            _ => match core_affinity::get_core_ids() {
+                Some(ids) if !ids.is_empty() => ids.into_iter().map(|id| id.id).collect(),
+                _ => return Placement::default(),
+            },
+        };
+        let p = places.len();
+        let spread = *self == Affinity::Scatter || threads > p;
+        let cpus = (0..threads).map(|i| places[if spread { i * p / threads } else { i }]).collect();
+        Placement(Some(cpus))
+    }
+}
+
+/// The CPU of every thread of a run, from `Affinity::placement`; empty
+/// without pinning.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Placement(Option<Arc<[usize]>>);
+
+impl Placement {
+    /// Pins the calling thread, the run's thread `t`, to its CPU (no-op
+    /// without pinning or past the last thread).
+    pub fn pin(&self, t: usize) {
+        if let Some(&cpu) = self.0.as_ref().and_then(|cpus| cpus.get(t)) {
+            core_affinity::set_for_current(core_affinity::CoreId { id: cpu });
+        }
+    }
+
+    pub fn cpus(&self) -> Option<&[usize]> {
+        self.0.as_deref()
+    }
+}
+
+pub struct Pool {
+    backend: Backend,
+    threads: usize,
+    placement: Placement,
+    rayon: Option<rayon::ThreadPool>,
+    workers: Option<WorkerPool>,
+    #[cfg(feature = "tokio")]
+    tokio: Option<tokio::runtime::Runtime>,
+}
+
+impl Pool {
+    pub fn new(backend: Backend, threads: usize) -> Self {
+        Self::with_affinity(backend, threads, &Affinity::None)
+    }
+
+    // worker i is the placement's thread i
+    pub fn with_affinity(backend: Backend, threads: usize, affinity: &Affinity) -> Self {
+        let placement = affinity.placement(threads);
+        let pin = placement.clone();
+        let rayon = match backend {
+            Backend::Rayon => Some(
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .start_handler(move |i| pin.pin(i))
+                    .build()
+                    .unwrap(),
+            ),
+            _ => None,
+        };
+
+        Pool {
+            backend,
+            threads,
+            rayon,
+            workers: (backend == Backend::Workers).then(|| WorkerPool::new(threads, placement.clone())),
+            #[cfg(feature = "tokio")]
+            tokio: tokio_runtime(backend, threads, placement.clone()),
+            placement,
+        }
+    }
+
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    // chunk length used when the caller asks for "auto" granularity:
+    // Rayon splits adaptively down to single items, static backends give
+    // every thread one contiguous block
+    pub fn default_grain(&self, len: usize) -> usize {
+        match self.backend {
+            Backend::Rayon => 1,
+            _ => len.div_ceil(self.threads).max(1),
+        }
+    }
+
+    /// Runs `op` inside the Rayon pool, so that `rayon::join` in it forks
+    /// onto the pool's workers; the static backends have no fork-join and run
+    /// `op` on the calling thread.
+    pub fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        match &self.rayon {
+            Some(pool) => pool.install(op),
+            None => op(),
+        }
+    }
+
+    /// Calls `f(start, chunk)` for every `chunk`-sized piece of `data`.
+    pub fn for_each_chunk<T, F>(&self, data: &[T], chunk: usize, f: F)
+    where
+        T: Sync,
+        F: Fn(usize, &[T]) + Sync,
+    {
+        match &self.rayon {
+            Some(pool) => pool.install(|| {
+                data.par_chunks(chunk)
+                    .enumerate()
+                    .for_each(|(i, c)| f(i * chunk, c));
+            }),
+            None => {
+                self.run_static(self.deal(data.chunks(chunk), chunk), |list| {
+                    for (start, c) in list {
+                        f(start, c);
+                    }
+                });
+            }
+        }
+    }
+
+    /// Calls `f(start, chunk)` for every `chunk`-sized piece of `data`, mutably.
+    pub fn for_each_chunk_mut<T, F>(&self, data: &mut [T], chunk: usize, f: F)
+    where
+        T: Send,
+        F: Fn(usize, &mut [T]) + Sync,
+    {
+        match &self.rayon {
+            Some(pool) => pool.install(|| {
+                data.par_chunks_mut(chunk)
+                    .enumerate()
+                    .for_each(|(i, c)| f(i * chunk, c));
+            }),
+            None => {
+                self.run_static(self.deal(data.chunks_mut(chunk), chunk), |list| {
+                    for (start, c) in list {
+                        f(start, c);
+                    }
+                });
+            }
+        }
+    }
+
+    /// Maps every chunk to a partial result and combines the partials with `reduce`.
+    /// Static backends fold their chunks per thread and merge the T partials at the end.
+    pub fn map_reduce<T, A, ID, M, R>(&self, data: &[T], chunk: usize, identity: ID, map: M, reduce: R) -> A
+    where
+        T: Sync,
+        A: Send,
+        ID: Fn() -> A + Sync,
+        M: Fn(usize, &[T]) -> A + Sync,
+        R: Fn(A, A) -> A + Sync,
+    {
+        match &self.rayon {
+            Some(pool) => pool.install(|| {
+                data.par_chunks(chunk)
+                    .enumerate()
+                    .map(|(i, c)| map(i * chunk, c))
+                    .reduce(&identity, &reduce)
+            }),
+            None => self
+                .run_static(self.deal(data.chunks(chunk), chunk), |list| {
+                    list.into_iter()
+                        .fold(identity(), |acc, (start, c)| reduce(acc, map(start, c)))
+                })
+                .into_iter()
+                .fold(identity(), &reduce),
+        }
+    }
+
+    // deal chunks round-robin: thread t gets chunks t, t+T, t+2T, ...
+    fn deal<C>(&self, chunks: impl Iterator<Item = C>, chunk: usize) -> Vec<Vec<(usize, C)>> {
+        let mut lists: Vec<Vec<(usize, C)>> = (0..self.threads).map(|_| Vec::new()).collect();
+        for (i, c) in chunks.enumerate() {
+            lists[i % self.threads].push((i * chunk, c));
+        }
+        lists.retain(|l| !l.is_empty());
+        lists
+    }
+
+    // run job(input) on one scoped thread per input, results in input order
+    fn run_static<J, R, F>(&self, inputs: Vec<J>, job: F) -> Vec<R>
+    where
+        J: Send,
+        R: Send,
+        F: Fn(J) -> R + Sync,
+    {
+        let pin = &self.placement;
+        let job = &job;
+
+        match self.backend {
+            Backend::Threads => std::thread::scope(|s| {
+                let handles: Vec<_> = inputs
+                    .into_iter()
+                    .enumerate()
+                    .map(|(t, input)| {
+                        s.spawn(move || {
+                            pin.pin(t);
+                            job(input)
+                        })
+                    })
+                    .collect();
+                handles.into_iter().map(|h| h.join().unwrap()).collect()
+            }),
+            Backend::Crossbeam => crossbeam::thread::scope(|s| {
+                let handles: Vec<_> = inputs
+                    .into_iter()
+                    .enumerate()
+                    .map(|(t, input)| {
+                        s.spawn(move |_| {
+                            pin.pin(t);
+                            job(input)
+                        })
+                    })
+                    .collect();
+                handles.into_iter().map(|h| h.join().unwrap()).collect()
+            })
+            .unwrap(),
+            Backend::Workers => {
+                let workers = self.workers.as_ref().unwrap();
+                run_detached(inputs, job, |j| workers.execute(j))
+            }
+            #[cfg(feature = "tokio")]
+            Backend::TokioBlocking => {
+                let rt = self.tokio.as_ref().unwrap();
+                run_detached(inputs, job, |j| {
+                    rt.spawn_blocking(j);
+                })
+            }
+            #[cfg(feature = "tokio")]
+            Backend::TokioAsync => {
+                let rt = self.tokio.as_ref().unwrap();
+                run_detached(inputs, job, |j| {
+                    rt.spawn(async move { j() });
+                })
+            }
+            Backend::Rayon => unreachable!("rayon pools do not use the static scheduler"),
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+fn tokio_runtime(backend: Backend, threads: usize, placement: Placement) -> Option<tokio::runtime::Runtime> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let mut builder = match backend {
+        // compute runs on the blocking pool; one worker drives the futures
+        Backend::TokioBlocking => {
+            let mut b = tokio::runtime::Builder::new_multi_thread();
+            b.worker_threads(1).max_blocking_threads(threads);
+            b
+        }
+        Backend::TokioAsync => {
+            let mut b = tokio::runtime::Builder::new_multi_thread();
+            b.worker_threads(threads);
+            b
+        }
+        _ => return None,
+    };
+
+    let next_core = AtomicUsize::new(0);
+    builder.on_thread_start(move || {
+        placement.pin(next_core.fetch_add(1, Ordering::Relaxed));
+    });
+
+    Some(builder.build().unwrap())
+}
+
+// Fixed-size pool of persistent workers fed boxed jobs over a channel: the
+// pre-Rayon style many production codebases still use.
+struct WorkerPool {
+    sender: Option<mpsc::Sender<DetachedJob>>,
+    workers: Vec<thread::JoinHandle<()>>,
+}
+
+impl WorkerPool {
+    fn new(threads: usize, placement: Placement) -> Self {
+        let (sender, receiver) = mpsc::channel::<DetachedJob>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let workers = (0..threads)
+            .map(|t| {
+                let receiver = Arc::clone(&receiver);
+                let placement = placement.clone();
+                thread::spawn(move || {
+                    placement.pin(t);
+                    loop {
+                        let job = receiver.lock().unwrap().recv();
+                        match job {
+                            // a panicking job must not take its worker down
+                            Ok(job) => {
+                                let _ = panic::catch_unwind(AssertUnwindSafe(job));
+                            }
+                            Err(_) => break,
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        WorkerPool {
+            sender: Some(sender),
+            workers,
+        }
+    }
+
+    fn execute(&self, job: DetachedJob) {
+        self.sender.as_ref().unwrap().send(job).unwrap();
+    }
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        drop(self.sender.take());
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+type DetachedJob = Box<dyn FnOnce() + Send + 'static>;
+
+// Hands one job per input to `submit` (an executor that needs 'static jobs)
+// and blocks until every job has finished; results come back in input order.
+fn run_detached<J, R, F, S>(inputs: Vec<J>, job: &F, submit: S) -> Vec<R>
+where
+    J: Send,
+    R: Send,
+    F: Fn(J) -> R + Sync,
+    S: Fn(DetachedJob),
+{
+    let expected = inputs.len();
+    let (tx, rx) = mpsc::channel();
+
+    let jobs: Vec<Box<dyn FnOnce() + Send + '_>> = inputs
+        .into_iter()
+        .enumerate()
+        .map(|(t, input)| {
+            let tx = tx.clone();
+            Box::new(move || {
+                let _ = tx.send((t, job(input)));
+            }) as Box<dyn FnOnce() + Send + '_>
+        })
+        .collect();
+    drop(tx);
+
+    // SAFETY: the jobs borrow data that outlives this call. Each job owns a
+    // clone of `tx`, dropped only once the job has run (or unwound, or been
+    // discarded unrun), and we drain `rx` until every clone is gone before
+    // returning or propagating a panic. This is the guarantee scoped threads
+    // give, enforced by hand.
+    let jobs: Vec<DetachedJob> = jobs
+        .into_iter()
+        .map(|j| unsafe { std::mem::transmute::<Box<dyn FnOnce() + Send + '_>, DetachedJob>(j) })
+        .collect();
+
+    let submitted = panic::catch_unwind(AssertUnwindSafe(|| {
+        for j in jobs {
+            submit(j);
+        }
+    }));
+
+    let mut results: Vec<(usize, R)> = rx.into_iter().collect();
+    if let Err(e) = submitted {
+        panic::resume_unwind(e);
+    }
+    assert_eq!(results.len(), expected, "a parallel job panicked");
+
+    results.sort_by_key(|(t, _)| *t);
+    results.into_iter().map(|(_, r)| r).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    #[test]
+    fn test_primitives_agree() {
+        let data: Vec<u64> = (0..1000).collect();
+
+        for backend in Backend::ALL {
+            let pool = Pool::new(backend, 3);
+
+            let total = AtomicU64::new(0);
+            pool.for_each_chunk(&data, 7, |_, c| {
+                total.fetch_add(c.iter().sum::<u64>(), Ordering::Relaxed);
+            });
+            assert_eq!(total.load(Ordering::Relaxed), 499_500, "{:?}", backend);
+
+            let sum = pool.map_reduce(&data, 64, || 0u64, |_, c| c.iter().sum(), |a, b| a + b);
+            assert_eq!(sum, 499_500, "{:?}", backend);
+
+            let mut out = vec![0u64; 1000];
+            pool.for_each_chunk_mut(&mut out, 10, |start, c| {
+                for (i, v) in c.iter_mut().enumerate() {
+                    *v = (start + i) as u64;
+                }
+            });
+            assert_eq!(out, data, "{:?}", backend);
+        }
+    }
+
+    #[test]
+    fn test_affinity() {
+        for name in ["none", "compact", "scatter", "list:0,2,4"] {
+            assert_eq!(Affinity::parse(name).map(|a| a.name()), Ok(name.to_string()));
+        }
+        assert_eq!(Affinity::parse("0"), Ok(Affinity::None));
+        assert_eq!(Affinity::parse("1"), Ok(Affinity::Compact));
+        for bad in ["spread", "list:", "list:0,,2", "list:x"] {
+            assert!(Affinity::parse(bad).is_err(), "{}", bad);
+        }
+
+        // close: one thread per place, then neighbours share
+        let list = Affinity::List(vec![4, 5]);
+        assert_eq!(list.placement(1).cpus(), Some(&[4][..]));
+        assert_eq!(list.placement(4).cpus(), Some(&[4, 4, 5, 5][..]));
+        assert_eq!(Affinity::None.placement(4).cpus(), None);
+
+        // on every CPU, compact and scatter agree
+        let cpus = core_affinity::get_core_ids().map_or(0, |ids| ids.len());
+        if cpus > 0 {
+            assert_eq!(Affinity::Scatter.placement(cpus), Affinity::Compact.placement(cpus));
+            assert_eq!(Affinity::Scatter.placement(1).cpus().map(<[usize]>::len), Some(1));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "a parallel job panicked")]
+    fn test_worker_panic_propagates() {
+        let pool = Pool::new(Backend::Workers, 2);
+        let data = [0u8; 16];
+        pool.for_each_chunk(&data, 4, |start, _| assert!(start != 8));
+    }
+}
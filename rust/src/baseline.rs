@@ -0,0 +1,193 @@
+// Performance baselines (run_all_benchmarks save-baseline / compare)
+// A baseline is the --format json document of a run: its metadata and
+// records. `compare` matches the records of a new run with it by label
+// (kernel, impl and params) and compares the time metrics (units sec, ms,
+// ns, s), which report the best run; the statistics beside them (_mean,
+// _median, _stddev, _ci95) and derived metrics such as speedups are left out.
+// A timing that grew by more than the threshold is a regression, one that
+// shrank by more is an improvement.
+
+use crate::metadata::Metadata;
+use crate::results::{self, Metric, Record};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Suffixes of the `Record::timing` metrics that summarize the runs rather
+/// than time one.
+pub const SUMMARIES: [&str; 4] = ["_mean", "_median", "_stddev", "_ci95"];
+
+/// Reads the records of a baseline file: a --format json document, or a
+/// plain array of records.
+pub fn load(path: &Path) -> Result<Vec<Record>, String> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum File {
+        Document { records: Vec<Record> },
+        Records(Vec<Record>),
+    }
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    match serde_json::from_str(&text) {
+        Ok(File::Document { records } | File::Records(records)) => Ok(records),
+        Err(_) => Err(format!("{}: not a --format json document of records", path.display())),
+    }
+}
+
+/// Writes `records` as a baseline, with this machine's metadata.
+pub fn save(path: &Path, records: &[Record]) -> Result<(), String> {
+    let document = results::json_document(&Metadata::collect(), records);
+    fs::write(path, document + "\n").map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// One time metric of a configuration in both runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub label: String,
+    pub metric: String,
+    pub unit: String,
+    pub baseline: f64,
+    pub current: f64,
+}
+
+impl Change {
+    /// Relative change in percent; positive is slower.
+    pub fn percent(&self) -> f64 {
+        (self.current - self.baseline) / self.baseline * 100.0
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Comparison {
+    pub changes: Vec<Change>,
+    /// baseline configurations the new run did not measure
+    pub missing: Vec<String>,
+    /// configurations of the new run without a baseline
+    pub new: usize,
+}
+
+impl Comparison {
+    pub fn regressions(&self, threshold: f64) -> impl Iterator<Item = &Change> {
+        self.changes.iter().filter(move |c| c.percent() > threshold)
+    }
+
+    pub fn improvements(&self, threshold: f64) -> impl Iterator<Item = &Change> {
+        self.changes.iter().filter(move |c| c.percent() < -threshold)
+    }
+
+    /// The flagged changes, one line each, then a summary line.
+    pub fn report(&self, threshold: f64) -> String {
+        let mut out = String::new();
+        for (flag, changes) in [("REGRESSED", self.regressions(threshold).collect::<Vec<_>>()), ("improved", self.improvements(threshold).collect())] {
+            for c in changes {
+                out.push_str(&format!(
+                    "{:<9}  {}  {}  {:.6} -> {:.6} {}  ({:+.1}%)\n",
+                    flag, c.label, c.metric, c.baseline, c.current, c.unit, c.percent()
+                ));
+            }
+        }
+        let (regressed, improved) = (self.regressions(threshold).count(), self.improvements(threshold).count());
+        out.push_str(&format!(
+            "{} timing(s) compared: {} regression(s), {} improvement(s), {} within {}%",
+            self.changes.len(),
+            regressed,
+            improved,
+            self.changes.len() - regressed - improved,
+            threshold
+        ));
+        if !self.missing.is_empty() {
+            out.push_str(&format!("; {} baseline configuration(s) not run", self.missing.len()));
+        }
+        if self.new > 0 {
+            out.push_str(&format!("; {} new configuration(s)", self.new));
+        }
+        out.push('\n');
+        out
+    }
+}
+
+fn is_timing(metric: &Metric) -> bool {
+    matches!(metric.unit.as_str(), "sec" | "ms" | "ns" | "s") && !SUMMARIES.iter().any(|s| metric.name.ends_with(s))
+}
+
+/// Compares the timings of `current` with those of `baseline`. Timings
+/// that were 0 in the baseline (not measured) are skipped.
+pub fn compare(baseline: &[Record], current: &[Record]) -> Comparison {
+    let old: HashMap<String, &Record> = baseline.iter().map(|r| (r.label(), r)).collect();
+    let mut comparison = Comparison::default();
+    let mut seen = Vec::new();
+    for record in current {
+        let label = record.label();
+        let Some(old) = old.get(&label) else {
+            comparison.new += 1;
+            continue;
+        };
+        for metric in record.metrics.iter().filter(|m| is_timing(m)) {
+            let before = old.metrics.iter().find(|m| m.name == metric.name && m.unit == metric.unit);
+            if let Some(before) = before.filter(|b| b.value > 0.0) {
+                comparison.changes.push(Change {
+                    label: label.clone(),
+                    metric: metric.name.clone(),
+                    unit: metric.unit.clone(),
+                    baseline: before.value,
+                    current: metric.value,
+                });
+            }
+        }
+        seen.push(label);
+    }
+    comparison.missing = baseline.iter().map(Record::label).filter(|l| !seen.contains(l)).collect();
+    comparison
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(threads: usize, time: f64, seq_time: f64) -> Record {
+        Record::new("matmul", "rayon")
+            .param("T", threads)
+            .metric("seq_time", seq_time, "sec")
+            .metric("time", time, "sec")
+            .metric("time_stddev", time, "sec")
+            .metric("speedup", 1.0, "x")
+    }
+
+    #[test]
+    fn test_compare() {
+        let baseline = [cell(1, 1.0, 1.0), cell(2, 0.5, 0.0), cell(4, 0.25, 0.0)];
+        let current = [cell(1, 1.05, 0.8), cell(2, 0.6, 0.0), cell(8, 0.1, 0.0)];
+        let comparison = compare(&baseline, &current);
+
+        // seq_time of T=2 was not measured; stddev and speedup are no timings
+        let compared: Vec<(&str, &str)> = comparison.changes.iter().map(|c| (c.label.as_str(), c.metric.as_str())).collect();
+        assert_eq!(compared, [("matmul,rayon,T=1", "seq_time"), ("matmul,rayon,T=1", "time"), ("matmul,rayon,T=2", "time")]);
+        assert_eq!((comparison.missing.as_slice(), comparison.new), (["matmul,rayon,T=4".to_string()].as_slice(), 1));
+
+        let regressed: Vec<&Change> = comparison.regressions(10.0).collect();
+        assert_eq!(regressed.len(), 1);
+        assert!((regressed[0].percent() - 20.0).abs() < 1e-9);
+        assert_eq!(comparison.improvements(10.0).count(), 1);
+        assert_eq!(comparison.regressions(25.0).count(), 0);
+        let report = comparison.report(10.0);
+        assert!(report.starts_with("REGRESSED  matmul,rayon,T=2  time  0.500000 -> 0.600000 sec  (+20.0%)\n"), "{}", report);
+        assert!(report.ends_with("3 timing(s) compared: 1 regression(s), 1 improvement(s), 1 within 10%; 1 baseline configuration(s) not run; 1 new configuration(s)\n"));
+    }
+
+    #[test]
+    fn test_save_load() {
+        let path = std::env::temp_dir().join(format!("baseline-{}.json", std::process::id()));
+        let records = [cell(1, 1.0, 1.0)];
+        save(&path, &records).unwrap();
+        let loaded = load(&path).unwrap();
+        assert_eq!(loaded[0].to_json(), records[0].to_json());
+
+        // a plain array of records also loads
+        fs::write(&path, format!("[{}]", records[0].to_json())).unwrap();
+        assert_eq!(load(&path).unwrap().len(), 1);
+        fs::write(&path, "{\"metadata\": {}}").unwrap();
+        assert!(load(&path).unwrap_err().contains("not a --format json document"));
+        fs::remove_file(&path).unwrap();
+        assert!(load(&path).is_err());
+    }
+}
@@ -0,0 +1,5 @@
+// false_sharing binary; the kernel lives in the library (kernels.rs)
+
+fn main() {
+    openmp_rust_benchmarks::kernels::main("false_sharing");
+}
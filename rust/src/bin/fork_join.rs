@@ -0,0 +1,5 @@
+// fork_join binary; the kernel lives in the library (kernels.rs)
+
+fn main() {
+    openmp_rust_benchmarks::kernels::main("fork_join");
+}
@@ -0,0 +1,5 @@
+// histogram2d binary; the kernel lives in the library (kernels.rs)
+
+fn main() {
+    openmp_rust_benchmarks::kernels::main("histogram2d");
+}
@@ -0,0 +1,5 @@
+// histogram_f64 binary; the kernel lives in the library (kernels.rs)
+
+fn main() {
+    openmp_rust_benchmarks::kernels::main("histogram_f64");
+}
@@ -0,0 +1,5 @@
+// matrix_multiply binary; the kernel lives in the library (kernels.rs)
+
+fn main() {
+    openmp_rust_benchmarks::kernels::main("matrix_multiply");
+}
@@ -0,0 +1,5 @@
+// monte_carlo binary; the kernel lives in the library (kernels.rs)
+
+fn main() {
+    openmp_rust_benchmarks::kernels::main("monte_carlo");
+}
@@ -0,0 +1,5 @@
+// parallel_for binary; the kernel lives in the library (kernels.rs)
+
+fn main() {
+    openmp_rust_benchmarks::kernels::main("parallel_for");
+}
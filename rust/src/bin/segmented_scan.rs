@@ -0,0 +1,5 @@
+// segmented_scan binary; the kernel lives in the library (kernels.rs)
+
+fn main() {
+    openmp_rust_benchmarks::kernels::main("segmented_scan");
+}
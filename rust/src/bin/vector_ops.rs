@@ -0,0 +1,5 @@
+// vector_ops binary; the kernel lives in the library (kernels.rs)
+
+fn main() {
+    openmp_rust_benchmarks::kernels::main("vector_ops");
+}
@@ -0,0 +1,71 @@
+// BLAS dgemm reference (--features blas, see build.rs)
+// matrix_multiply --blas 1 adds cblas_dgemm from the system OpenBLAS as one
+// more strategy of the study: a tuned, blocked and vectorized multiply that
+// marks the best time the machine reaches at each cell, so that the naive
+// Rust and OpenMP kernels can be read as fractions of it. OpenBLAS keeps its
+// own threads; openblas_set_num_threads gives it the cell's T before each
+// multiply (OPENBLAS_NUM_THREADS only sets the starting value).
+
+#[cfg(feature = "blas")]
+mod c {
+    use std::ffi::{c_double, c_int};
+
+    // CBLAS_LAYOUT / CBLAS_TRANSPOSE values of cblas.h
+    pub const ROW_MAJOR: c_int = 101;
+    pub const NO_TRANS: c_int = 111;
+
+    extern "C" {
+        pub fn cblas_dgemm(
+            layout: c_int,
+            trans_a: c_int,
+            trans_b: c_int,
+            m: c_int,
+            n: c_int,
+            k: c_int,
+            alpha: c_double,
+            a: *const c_double,
+            lda: c_int,
+            b: *const c_double,
+            ldb: c_int,
+            beta: c_double,
+            c: *mut c_double,
+            ldc: c_int,
+        );
+        pub fn openblas_set_num_threads(threads: c_int);
+    }
+}
+
+/// C = A * B for n x n row-major matrices with dgemm on `threads` OpenBLAS
+/// threads.
+#[cfg(feature = "blas")]
+pub fn dgemm(a: &[f64], b: &[f64], c: &mut [f64], n: usize, threads: usize) {
+    assert!(a.len() == n * n && b.len() == n * n && c.len() == n * n, "matrices of order {} expected", n);
+    let order = i32::try_from(n).expect("n does not fit a C int");
+    // SAFETY: all three hold n * n entries, as asserted; with beta = 0 C is
+    // only written
+    unsafe {
+        c::openblas_set_num_threads(i32::try_from(threads).expect("T does not fit a C int"));
+        c::cblas_dgemm(c::ROW_MAJOR, c::NO_TRANS, c::NO_TRANS, order, order, order, 1.0, a.as_ptr(), order, b.as_ptr(), order, 0.0, c.as_mut_ptr(), order);
+    }
+}
+
+// cli::blas refuses --blas 1 in builds without BLAS, so this is never called
+#[cfg(not(feature = "blas"))]
+pub fn dgemm(_a: &[f64], _b: &[f64], _c: &mut [f64], _n: usize, _threads: usize) {
+    unreachable!("built without BLAS")
+}
+
+#[cfg(all(test, feature = "blas"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dgemm() {
+        let n = 5;
+        let a: Vec<f64> = (0..n * n).map(|i| i as f64).collect();
+        let identity: Vec<f64> = (0..n * n).map(|i| if i % (n + 1) == 0 { 1.0 } else { 0.0 }).collect();
+        let mut c = vec![0.0; n * n];
+        dgemm(&a, &identity, &mut c, n, 2);
+        assert_eq!(c, a);
+    }
+}
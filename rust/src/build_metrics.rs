@@ -0,0 +1,226 @@
+// Build cost of the kernels (run_all_benchmarks build-metrics)
+// How long a kernel takes to compile and how big its binary gets are part of
+// the programmability comparison, so each selected kernel is rebuilt per
+// profile and reported as a "build" record:
+//   compile_time  wall-clock seconds to rebuild the kernel after its source
+//                 changed (Rust: cargo with dependencies and the shared crate
+//                 already built; C: one cc invocation)
+//   binary_size   bytes of the resulting executable
+// Rust builds go to their own target directory (target/build-metrics), so
+// the timed rebuilds neither reuse nor disturb the regular artifacts. The
+// first pass per profile builds everything once, untimed. Kernels hosted in
+// the library (kernels.rs) rebuild the shared crate along with their binary.
+
+use crate::results::Record;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Instant, SystemTime};
+
+/// A kernel binary, its sources (the kernel file first, touched to force a
+/// rebuild, then the binary's wrapper, if separate) and its OpenMP
+/// counterpart, if any; paths are relative to the repository root.
+#[derive(Debug, Clone, Copy)]
+pub struct Kernel {
+    pub bin: &'static str,
+    pub rust: &'static [&'static str],
+    pub c: Option<&'static str>,
+}
+
+pub const KERNELS: [Kernel; 8] = [
+    Kernel { bin: "prefix_sum", rust: &["rust/src/programmability/prefix_sum.rs", "rust/src/bin/prefix_sum.rs"], c: Some("openMP/src/programmability/prefix_sum.c") },
+    Kernel { bin: "fork_join", rust: &["rust/src/programmability/fork_join.rs", "rust/src/bin/fork_join.rs"], c: None },
+    Kernel { bin: "parallel_for", rust: &["rust/src/programmability/parallel_for.rs", "rust/src/bin/parallel_for.rs"], c: None },
+    Kernel { bin: "histogram", rust: &["rust/src/controllability/histogram.rs", "rust/src/bin/histogram.rs"], c: Some("openMP/src/control/control.c") },
+    Kernel { bin: "matrix_multiply", rust: &["rust/src/scalability/matrix_multiply.rs", "rust/src/bin/matrix_multiply.rs"], c: Some("openMP/src/scalability/matrix_multiply.c") },
+    Kernel { bin: "runtime_overhead", rust: &["rust/src/runtime_overhead/mod.rs", "rust/src/bin/runtime_overhead.rs"], c: Some("openMP/src/runtime_overhead/overhead.c") },
+    Kernel { bin: "vector_ops", rust: &["rust/src/scalability/vector_ops.rs", "rust/src/bin/vector_ops.rs"], c: None },
+    Kernel { bin: "first_touch", rust: &["rust/src/scalability/first_touch.rs", "rust/src/bin/first_touch.rs"], c: None },
+];
+
+/// Looks up kernels by binary name; no names selects all of them.
+pub fn select(names: &[String]) -> Result<Vec<Kernel>, String> {
+    if names.is_empty() {
+        return Ok(KERNELS.to_vec());
+    }
+    names
+        .iter()
+        .map(|name| {
+            KERNELS.iter().copied().find(|k| k.bin == name).ok_or_else(|| {
+                let known: Vec<&str> = KERNELS.iter().map(|k| k.bin).collect();
+                format!("unknown kernel: {} (use {})", name, known.join("|"))
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    Debug,
+    Release,
+}
+
+impl Profile {
+    /// debug | release | both
+    pub fn parse_list(s: &str) -> Result<Vec<Profile>, String> {
+        match s {
+            "debug" => Ok(vec![Profile::Debug]),
+            "release" => Ok(vec![Profile::Release]),
+            "both" => Ok(vec![Profile::Debug, Profile::Release]),
+            _ => Err(format!("unknown profile: {} (use debug|release|both)", s)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Profile::Debug => "debug",
+            Profile::Release => "release",
+        }
+    }
+
+    // C flags matching the build lines in the top-level README
+    fn c_flags(self) -> &'static [&'static str] {
+        match self {
+            Profile::Debug => &["-O0", "-g", "-fopenmp", "-std=c11"],
+            Profile::Release => &["-O3", "-march=native", "-fopenmp", "-std=c11"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BuildStats {
+    pub compile_secs: f64,
+    pub binary_bytes: u64,
+}
+
+impl BuildStats {
+    pub fn record(&self, implementation: &str, kernel: &str, profile: Profile) -> Record {
+        Record::new("build", implementation)
+            .param("kernel", kernel)
+            .param("profile", profile.name())
+            .metric("compile_time", self.compile_secs, "s")
+            .metric("binary_size", self.binary_bytes as f64, "bytes")
+    }
+}
+
+/// Rebuilds kernels from the checkout at `repo` (holding rust/ and openMP/).
+pub struct Builder {
+    repo: PathBuf,
+    target_dir: PathBuf,
+    cc: String,
+}
+
+impl Builder {
+    pub fn new(repo: &Path) -> Self {
+        Builder {
+            repo: repo.to_path_buf(),
+            target_dir: repo.join("rust/target/build-metrics"),
+            cc: std::env::var("CC").unwrap_or_else(|_| "cc".to_string()),
+        }
+    }
+
+    fn cargo(&self, profile: Profile, bins: &[&str]) -> Command {
+        let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+        let mut cmd = Command::new(cargo);
+        // the source is only touched, so incremental builds would reuse
+        // nearly everything and time little more than the link
+        cmd.env("CARGO_INCREMENTAL", "0");
+        cmd.arg("build")
+            .arg("--manifest-path")
+            .arg(self.repo.join("rust/Cargo.toml"))
+            .arg("--target-dir")
+            .arg(&self.target_dir);
+        if profile == Profile::Release {
+            cmd.arg("--release");
+        }
+        for bin in bins {
+            cmd.args(["--bin", bin]);
+        }
+        cmd
+    }
+
+    /// Builds `kernels` once, untimed, so the measured rebuilds only cover
+    /// the kernel itself.
+    pub fn prepare(&self, kernels: &[Kernel], profile: Profile) -> Result<(), String> {
+        let bins: Vec<&str> = kernels.iter().map(|k| k.bin).collect();
+        run(self.cargo(profile, &bins), "cargo build")
+    }
+
+    pub fn rust(&self, kernel: &Kernel, profile: Profile) -> Result<BuildStats, String> {
+        // a fresh mtime on the kernel source makes cargo rebuild this binary
+        // (and the library, for hosted kernels)
+        let source = self.repo.join(kernel.rust[0]);
+        File::options()
+            .append(true)
+            .open(&source)
+            .and_then(|f| f.set_modified(SystemTime::now()))
+            .map_err(|e| format!("cannot touch {}: {}", source.display(), e))?;
+
+        let start = Instant::now();
+        run(self.cargo(profile, &[kernel.bin]), "cargo build")?;
+        let compile_secs = start.elapsed().as_secs_f64();
+
+        let binary = self
+            .target_dir
+            .join(profile.name())
+            .join(format!("{}{}", kernel.bin, std::env::consts::EXE_SUFFIX));
+        Ok(BuildStats { compile_secs, binary_bytes: size_of(&binary)? })
+    }
+
+    /// None when the kernel has no OpenMP version.
+    pub fn c(&self, kernel: &Kernel, profile: Profile) -> Result<Option<BuildStats>, String> {
+        let Some(source) = kernel.c else {
+            return Ok(None);
+        };
+        let out_dir = self.target_dir.join("c").join(profile.name());
+        fs::create_dir_all(&out_dir).map_err(|e| format!("cannot create {}: {}", out_dir.display(), e))?;
+        let binary = out_dir.join(kernel.bin);
+
+        let mut cmd = Command::new(&self.cc);
+        cmd.args(profile.c_flags()).arg(self.repo.join(source)).arg("-o").arg(&binary).arg("-lm");
+        let start = Instant::now();
+        run(cmd, &self.cc)?;
+        let compile_secs = start.elapsed().as_secs_f64();
+
+        Ok(Some(BuildStats { compile_secs, binary_bytes: size_of(&binary)? }))
+    }
+}
+
+// runs `cmd` to completion; a failure carries the tail of its stderr
+fn run(mut cmd: Command, what: &str) -> Result<(), String> {
+    let output = cmd.output().map_err(|e| format!("cannot run {}: {}", what, e))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let lines: Vec<&str> = stderr.lines().collect();
+    let tail = lines[lines.len().saturating_sub(20)..].join("\n");
+    Err(format!("{} failed ({}):\n{}", what, output.status, tail))
+}
+
+fn size_of(path: &Path) -> Result<u64, String> {
+    fs::metadata(path)
+        .map(|m| m.len())
+        .map_err(|e| format!("cannot stat {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kernel_sources_exist() {
+        let repo = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+        for kernel in KERNELS {
+            for source in kernel.rust {
+                assert!(repo.join(source).is_file(), "{}", source);
+            }
+            if let Some(c) = kernel.c {
+                assert!(repo.join(c).is_file(), "{}", c);
+            }
+        }
+        assert_eq!(select(&[]).unwrap().len(), KERNELS.len());
+        assert!(select(&["histogram".to_string()]).is_ok());
+        assert!(select(&["sort".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,249 @@
+// Command-line layer shared by every binary (clap)
+// Each binary declares its options as a clap `Parser`. The groups and value
+// parsers here keep the common flags identical everywhere: --size, --threads,
+// --reps and --seed mean the same in every binary that takes them, and the
+// output options come from one place. Errors exit with status 1 like the
+// other usage errors; --help exits with 0.
+
+use crate::logging::{self, LogFormat};
+use crate::results::{Format, Reporter};
+use crate::stats::{Filter, Repeat};
+use crate::timer::Timer;
+use clap::{ArgMatches, Args, Command, FromArgMatches, Parser};
+
+/// The output options every binary takes (the runner applies them once for
+/// the whole run).
+#[derive(Debug, Clone, Args)]
+pub struct Output {
+    /// text (the binary's own console output) | jsonl | json | csv | markdown
+    #[arg(long, global = true, value_name = "FMT", value_parser = Format::parse, default_value = "text")]
+    pub format: Format,
+    /// POST each result as JSON to an HTTP collector
+    #[arg(long, global = true, value_name = "URL")]
+    pub upload: Option<String>,
+    /// keep FILE updated with Prometheus gauges for each result
+    #[arg(long, global = true, value_name = "FILE")]
+    pub prometheus: Option<String>,
+    /// append every run and its results to a SQLite database (--features store)
+    #[arg(long, global = true, value_name = "FILE")]
+    pub store: Option<String>,
+    /// pretty | json execution log on stderr (RUST_LOG filters)
+    #[arg(long, global = true, value_name = "FMT", value_parser = LogFormat::parse)]
+    pub log: Option<LogFormat>,
+}
+
+impl Output {
+    /// Installs the --log subscriber and builds the reporter.
+    pub fn start(&self) -> Result<Reporter, String> {
+        if let Some(format) = self.log {
+            logging::init(format);
+        }
+        Reporter::new(self.format, self.upload.as_deref(), self.prometheus.as_deref(), self.store.as_deref())
+    }
+}
+
+/// How often each cell is timed (--reps, --warmup, --outliers).
+#[derive(Debug, Clone, Copy, Args)]
+pub struct Reps {
+    /// timed runs per cell: the best is reported, with mean, median and stddev for R > 1
+    #[arg(long, value_name = "R", value_parser = positive)]
+    pub reps: Option<usize>,
+    /// untimed full runs before the timed ones; 0 measures the cold start
+    #[arg(long, value_name = "W")]
+    pub warmup: Option<usize>,
+    /// outlier filter for the statistics of the timed runs: none | iqr | mad
+    #[arg(long, value_name = "F", value_parser = Filter::parse, default_value = "iqr")]
+    pub outliers: Filter,
+}
+
+impl Reps {
+    /// The repetitions: `default_reps` unless --reps is given, after one
+    /// warm-up run unless --warmup is given.
+    pub fn repeat(&self, default_reps: usize) -> Repeat {
+        Repeat { reps: self.reps.unwrap_or(default_reps), warmup: self.warmup.unwrap_or(1), filter: self.outliers, timer: Timer::Instant }
+    }
+}
+
+/// The options of every benchmark binary: the output options, the
+/// repetitions and --describe.
+#[derive(Debug, Clone, Args)]
+pub struct Common {
+    #[command(flatten)]
+    pub output: Output,
+    #[command(flatten)]
+    pub reps: Reps,
+    /// print the JSON description of the options and metrics, then exit
+    #[arg(long)]
+    pub describe: bool,
+}
+
+/// A size or count: an integer of at least 1.
+pub fn positive(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(v) if v > 0 => Ok(v),
+        _ => Err(format!("must be a positive integer, got {}", s)),
+    }
+}
+
+/// Parses a comma-separated list of positive integers into sorted, distinct
+/// values.
+pub fn positive_list(list: &str) -> Result<Vec<usize>, String> {
+    let mut values = list.split(',').map(|s| positive(s.trim())).collect::<Result<Vec<_>, _>>()?;
+    values.sort_unstable();
+    values.dedup();
+    Ok(values)
+}
+
+/// An inclusive range of integers `lo:hi:step`, as taken by the sweep
+/// options.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Steps {
+    pub lo: usize,
+    pub hi: usize,
+    pub step: usize,
+}
+
+impl Steps {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = s.split(':').collect();
+        let [lo, hi, step] = fields.as_slice() else {
+            return Err(format!("expected lo:hi:step, got {}", s));
+        };
+        let lo = lo.trim().parse::<usize>().map_err(|_| format!("lo must be an integer, got {}", lo))?;
+        let hi = hi.trim().parse::<usize>().map_err(|_| format!("hi must be an integer, got {}", hi))?;
+        let step = positive(step.trim())?;
+        if lo > hi {
+            return Err(format!("lo {} exceeds hi {}", lo, hi));
+        }
+        Ok(Steps { lo, hi, step })
+    }
+
+    /// lo, lo + step, ... up to hi.
+    pub fn values(self) -> Vec<usize> {
+        (self.lo..=self.hi).step_by(self.step).collect()
+    }
+}
+
+/// A 0 | 1 switch.
+pub fn switch(s: &str) -> Result<bool, String> {
+    match s {
+        "0" => Ok(false),
+        "1" => Ok(true),
+        _ => Err(format!("must be 0 or 1, got {}", s)),
+    }
+}
+
+/// Checks --simd: asking for SIMD paths in a build without them is an error
+/// rather than a silent scalar run.
+pub fn simd(requested: bool) -> Result<bool, String> {
+    if requested && !cfg!(feature = "simd") {
+        return Err("--simd requires a nightly build with --features simd".to_string());
+    }
+    Ok(requested)
+}
+
+/// Checks --ffi: the linked C kernels exist only in builds with the "ffi"
+/// feature.
+pub fn ffi(requested: bool) -> Result<bool, String> {
+    if requested && !cfg!(feature = "ffi") {
+        return Err("--ffi 1 needs a build with --features ffi".to_string());
+    }
+    Ok(requested)
+}
+
+/// Checks --blas: the dgemm reference exists only in builds with the "blas"
+/// feature.
+pub fn blas(requested: bool) -> Result<bool, String> {
+    if requested && !cfg!(feature = "blas") {
+        return Err("--blas 1 needs a build with --features blas".to_string());
+    }
+    Ok(requested)
+}
+
+/// Parses `args` (program name first) or exits.
+pub fn parse<T: Parser>(args: Vec<String>) -> T {
+    T::try_parse_from(args).unwrap_or_else(|e| exit(e))
+}
+
+/// Matches `args` against `command` or exits.
+pub fn matches(command: Command, args: Vec<String>) -> ArgMatches {
+    command.try_get_matches_from(args).unwrap_or_else(|e| exit(e))
+}
+
+/// The typed options of matches made from `T::command()`.
+pub fn from_matches<T: FromArgMatches>(matches: &ArgMatches) -> T {
+    T::from_arg_matches(matches).unwrap_or_else(|e| exit(e))
+}
+
+// prints the error (or --help) and exits: 1 for errors, 0 for help
+fn exit(e: clap::Error) -> ! {
+    let _ = e.print();
+    std::process::exit(if e.use_stderr() { 1 } else { 0 });
+}
+
+/// Unwraps a result, printing the message and exiting on error.
+pub fn or_exit<T>(result: Result<T, String>) -> T {
+    result.unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{ArgAction, CommandFactory};
+
+    #[derive(Debug, Parser)]
+    struct Bin {
+        #[arg(long, value_parser = positive)]
+        size: usize,
+        #[arg(long, action = ArgAction::Set, value_parser = switch, default_value = "0")]
+        pad: bool,
+        #[command(flatten)]
+        common: Common,
+    }
+
+    fn parse_bin(args: &[&str]) -> Result<Bin, clap::Error> {
+        Bin::try_parse_from(std::iter::once("bin").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn test_value_parsers() {
+        assert_eq!(positive("8"), Ok(8));
+        assert_eq!(positive("0"), Err("must be a positive integer, got 0".to_string()));
+        assert_eq!(positive("-5"), Err("must be a positive integer, got -5".to_string()));
+        assert_eq!(positive_list("4096, 256,512,256"), Ok(vec![256, 512, 4096]));
+        assert!(positive_list("256,,512").is_err());
+        assert_eq!(Steps::parse("0:3000:1000").map(Steps::values), Ok(vec![0, 1000, 2000, 3000]));
+        assert_eq!(Steps::parse("256:1000:512").map(Steps::values), Ok(vec![256, 768]));
+        for bad in ["1:2", "5:1:1", "0:10:0", "a:10:1", "0:10:1:1"] {
+            assert!(Steps::parse(bad).is_err(), "{}", bad);
+        }
+        assert_eq!(switch("1"), Ok(true));
+        assert_eq!(switch("2"), Err("must be 0 or 1, got 2".to_string()));
+        assert_eq!(simd(false), Ok(false));
+        assert_eq!(simd(true).is_ok(), cfg!(feature = "simd"));
+    }
+
+    #[test]
+    fn test_common_options() {
+        Bin::command().debug_assert();
+
+        let bin = parse_bin(&["--size=100", "--format", "csv", "--pad", "1"]).unwrap();
+        assert_eq!((bin.size, bin.pad), (100, true));
+        assert_eq!(bin.common.output.format, Format::Csv);
+        assert!(bin.common.output.upload.is_none() && !bin.common.describe);
+        assert_eq!(bin.common.reps.repeat(1), Repeat { reps: 1, warmup: 1, filter: Filter::Iqr, timer: Timer::Instant });
+        assert_eq!(bin.common.reps.repeat(5), Repeat { reps: 5, warmup: 1, filter: Filter::Iqr, timer: Timer::Instant });
+        let reps = parse_bin(&["--size", "1", "--reps", "3", "--warmup", "0", "--outliers", "mad"]).unwrap().common.reps;
+        assert_eq!(reps.repeat(5), Repeat { reps: 3, warmup: 0, filter: Filter::Mad, timer: Timer::Instant });
+
+        assert!(parse_bin(&[]).is_err());
+        assert!(parse_bin(&["--size", "0"]).is_err());
+        assert!(parse_bin(&["--size", "1", "--format", "xml"]).is_err());
+        assert!(parse_bin(&["--size", "1", "extra"]).is_err());
+        assert!(parse_bin(&["--size", "1", "--reps", "0"]).is_err());
+        assert!(parse_bin(&["--size", "1", "--outliers", "sigma"]).is_err());
+    }
+}
@@ -0,0 +1,328 @@
+// Source complexity of the kernels (run_all_benchmarks build-metrics)
+// A small C-family lexer, enough for both the Rust and the OpenMP sources,
+// drives four simple measures per kernel and language:
+//   loc              lines holding at least one token (no blanks, no comments)
+//   tokens           identifiers, literals and punctuation characters
+//   max_nesting      deepest `{` block
+//   sync_primitives  mentions of synchronization constructs: Mutex, RwLock,
+//                    Condvar, Barrier, Atomic*, channels in Rust; the
+//                    critical/atomic/barrier/ordered/flush/taskwait/taskgroup
+//                    directives, OpenMP locks, C11/GCC atomics and pthread
+//                    mutexes/barriers in C
+// Rust items under #[cfg(test)] are left out, as the C sources carry no tests.
+
+use crate::results::Record;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    Rust,
+    C,
+}
+
+impl Lang {
+    /// By file extension: .rs is Rust, anything else C.
+    pub fn of(path: &Path) -> Lang {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("rs") => Lang::Rust,
+            _ => Lang::C,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Complexity {
+    pub loc: usize,
+    pub tokens: usize,
+    pub max_nesting: usize,
+    pub sync_primitives: usize,
+}
+
+impl Complexity {
+    /// Totals over several files; the nesting is the deepest of them.
+    pub fn combine(self, other: Complexity) -> Complexity {
+        Complexity {
+            loc: self.loc + other.loc,
+            tokens: self.tokens + other.tokens,
+            max_nesting: self.max_nesting.max(other.max_nesting),
+            sync_primitives: self.sync_primitives + other.sync_primitives,
+        }
+    }
+
+    pub fn record(&self, implementation: &str, kernel: &str) -> Record {
+        Record::new("complexity", implementation)
+            .param("kernel", kernel)
+            .metric("loc", self.loc as f64, "lines")
+            .metric("tokens", self.tokens as f64, "count")
+            .metric("max_nesting", self.max_nesting as f64, "depth")
+            .metric("sync_primitives", self.sync_primitives as f64, "count")
+    }
+}
+
+/// Measures the files at `paths` (relative to `root`) together.
+pub fn measure_files(root: &Path, paths: &[&str]) -> Result<Complexity, String> {
+    let mut total = Complexity::default();
+    for path in paths {
+        let path = root.join(path);
+        let source = fs::read_to_string(&path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        total = total.combine(measure(&source, Lang::of(&path)));
+    }
+    Ok(total)
+}
+
+pub fn measure(source: &str, lang: Lang) -> Complexity {
+    let mut tokens = lex(source);
+    if lang == Lang::Rust {
+        tokens = without_test_items(tokens);
+    }
+
+    let mut lines: Vec<usize> = tokens.iter().map(|t| t.line).collect();
+    lines.dedup();
+
+    let mut depth = 0usize;
+    let mut max_nesting = 0;
+    for token in &tokens {
+        match token.text.as_str() {
+            "{" => {
+                depth += 1;
+                max_nesting = max_nesting.max(depth);
+            }
+            "}" => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    let sync_primitives = match lang {
+        Lang::Rust => tokens.iter().filter(|t| is_rust_sync(&t.text)).count(),
+        Lang::C => count_c_sync(&tokens),
+    };
+
+    Complexity { loc: lines.len(), tokens: tokens.len(), max_nesting, sync_primitives }
+}
+
+fn is_rust_sync(ident: &str) -> bool {
+    matches!(ident, "Mutex" | "RwLock" | "Condvar" | "Barrier" | "channel" | "unbounded" | "bounded")
+        || ident.starts_with("Atomic")
+}
+
+fn count_c_sync(tokens: &[Token]) -> usize {
+    let mut count = 0;
+    let mut pragma_line = None;
+    for (i, token) in tokens.iter().enumerate() {
+        // `# pragma omp ...` makes the rest of its line a directive
+        if token.text == "#"
+            && tokens.get(i + 1).is_some_and(|t| t.text == "pragma")
+            && tokens.get(i + 2).is_some_and(|t| t.text == "omp")
+        {
+            pragma_line = Some(token.line);
+        }
+        let text = token.text.as_str();
+        let directive = pragma_line == Some(token.line)
+            && matches!(text, "critical" | "atomic" | "barrier" | "ordered" | "flush" | "taskwait" | "taskgroup");
+        let call = (text.starts_with("omp_") && text.contains("lock"))
+            || text == "_Atomic"
+            || text.starts_with("__atomic_")
+            || text.starts_with("__sync_")
+            || text.starts_with("pthread_mutex_")
+            || text.starts_with("pthread_barrier_");
+        if directive || call {
+            count += 1;
+        }
+    }
+    count
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    text: String,
+    line: usize,
+}
+
+fn lex(source: &str) -> Vec<Token> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let start_line = line;
+        let start = i;
+
+        if c.is_whitespace() {
+            line += (c == '\n') as usize;
+            i += 1;
+            continue;
+        }
+        if c == '/' && next == Some('/') {
+            i = chars[i..].iter().position(|&c| c == '\n').map_or(chars.len(), |p| i + p);
+            continue;
+        }
+        if c == '/' && next == Some('*') {
+            // Rust block comments nest; C ones never contain "/*" legitimately
+            let mut depth = 0;
+            let mut end = i;
+            while end < chars.len() {
+                if chars[end] == '/' && chars.get(end + 1) == Some(&'*') {
+                    depth += 1;
+                    end += 2;
+                } else if chars[end] == '*' && chars.get(end + 1) == Some(&'/') {
+                    depth -= 1;
+                    end += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    end += 1;
+                }
+            }
+            let end = end.min(chars.len());
+            line += newlines(&chars[i..end]);
+            i = end;
+            continue;
+        }
+
+        let raw = if c == 'r' { raw_string_end(&chars, i + 1) } else { None };
+        let end = if let Some(end) = raw {
+            end
+        } else if c == '"' {
+            quoted_end(&chars, i, '"')
+        } else if c == '\'' {
+            // a char literal, or a Rust lifetime / label
+            if next == Some('\\') || chars.get(i + 2) == Some(&'\'') {
+                quoted_end(&chars, i, '\'')
+            } else {
+                ident_end(&chars, i + 1)
+            }
+        } else if c.is_alphanumeric() || c == '_' {
+            ident_end(&chars, i)
+        } else {
+            i + 1
+        };
+
+        tokens.push(Token { text: chars[start..end].iter().collect(), line: start_line });
+        line += newlines(&chars[start..end]);
+        i = end;
+    }
+    tokens
+}
+
+fn newlines(chars: &[char]) -> usize {
+    chars.iter().filter(|&&c| c == '\n').count()
+}
+
+// end of an identifier or number starting at `i`
+fn ident_end(chars: &[char], mut i: usize) -> usize {
+    while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+        i += 1;
+    }
+    i
+}
+
+// end of a literal opened by `quote` at `i`, honouring backslash escapes
+fn quoted_end(chars: &[char], mut i: usize, quote: char) -> usize {
+    i += 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            c if c == quote => return i + 1,
+            _ => i += 1,
+        }
+    }
+    chars.len()
+}
+
+// end of a raw string `#*"..."#*` whose hashes start at `i`
+fn raw_string_end(chars: &[char], i: usize) -> Option<usize> {
+    let hashes = chars[i..].iter().take_while(|&&c| c == '#').count();
+    if chars.get(i + hashes) != Some(&'"') {
+        return None;
+    }
+    let closing: Vec<char> = std::iter::once('"').chain(std::iter::repeat_n('#', hashes)).collect();
+    let body = i + hashes + 1;
+    (body..chars.len())
+        .find(|&j| chars[j..].starts_with(&closing))
+        .map(|j| j + closing.len())
+        .or(Some(chars.len()))
+}
+
+// drops every item preceded by #[cfg(test)], up to its closing brace or `;`
+fn without_test_items(tokens: Vec<Token>) -> Vec<Token> {
+    const CFG_TEST: [&str; 7] = ["#", "[", "cfg", "(", "test", ")", "]"];
+    let mut kept = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        let is_cfg_test = tokens.len() - i >= CFG_TEST.len()
+            && tokens[i..i + CFG_TEST.len()].iter().zip(CFG_TEST).all(|(t, s)| t.text == s);
+        if !is_cfg_test {
+            kept.push(tokens[i].clone());
+            i += 1;
+            continue;
+        }
+        i += CFG_TEST.len();
+        let mut depth = 0;
+        while i < tokens.len() {
+            let text = tokens[i].text.as_str();
+            i += 1;
+            match text {
+                "{" => depth += 1,
+                "}" => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                ";" if depth == 0 => break,
+                _ => {}
+            }
+        }
+    }
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measures() {
+        let rust = r##"
+use std::sync::{Arc, Mutex};
+// a Mutex in a comment does not count
+/* nested /* block */ comment */
+fn main() {
+    let s = "{ not a block }";
+    let r = r#"Barrier"#;
+    if true {
+        let m = Arc::new(Mutex::new('{'));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    fn helper() { let _ = std::sync::atomic::AtomicU64::new(0); }
+}
+"##;
+        let c = measure(rust, Lang::Rust);
+        assert_eq!(c.loc, 8);
+        assert_eq!(c.max_nesting, 2);
+        assert_eq!(c.sync_primitives, 2);
+
+        let source = "#include <omp.h>\n\
+                      int main(void) {\n\
+                      \x20   #pragma omp parallel\n\
+                      \x20   {\n\
+                      \x20       #pragma omp critical\n\
+                      \x20       x++; // critical\n\
+                      \x20   }\n\
+                      \x20   return 0;\n\
+                      }\n";
+        let c = measure(source, Lang::C);
+        assert_eq!(c.loc, 9);
+        assert_eq!(c.max_nesting, 2);
+        assert_eq!(c.sync_primitives, 1);
+        assert_eq!(measure("a+b;", Lang::C).tokens, 4);
+    }
+}
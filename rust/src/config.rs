@@ -0,0 +1,196 @@
+// Sweep configuration files (run_all_benchmarks config benchmarks.toml)
+// A campaign lists its sweeps as [[sweep]] tables: `kernel` names a
+// registered benchmark, every other key one of its options (without the
+// leading --) with one value or an array of values. The sweep runs the
+// cartesian product of the arrays, options in the order of the benchmark's
+// description with the last one varying fastest. Integers and strings are
+// passed as written; booleans turn a flag on or off and give 1 / 0 for
+// switches. Every point is checked against the benchmark's clap command
+// before anything runs, so a typo in the last sweep does not cost the first.
+//   [[sweep]]
+//   kernel = "histogram"
+//   strategy = ["atomic", "local"]
+//   dist = "uniform"
+//   size = 10000000
+//   threads = [1, 2, 4, "max"]
+
+use crate::describe::{Description, Kind};
+use crate::kernels::{self, Benchmark};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// The runner's own options, set on its command line rather than per sweep.
+const OUTPUT_OPTIONS: [&str; 5] = ["--format", "--upload", "--prometheus", "--store", "--log"];
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub sweep: Vec<Sweep>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Sweep {
+    pub kernel: String,
+    /// option name (without --) to one value or an array of values
+    #[serde(flatten)]
+    pub grid: BTreeMap<String, toml::Value>,
+}
+
+/// One run of a sweep: the benchmark and its options (no program name).
+pub struct Point {
+    pub benchmark: &'static dyn Benchmark,
+    pub args: Vec<String>,
+}
+
+/// Reads and parses a configuration file.
+pub fn load(path: &Path) -> Result<Config, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+pub fn parse(text: &str) -> Result<Config, String> {
+    toml::from_str(text).map_err(|e| e.message().to_string())
+}
+
+impl Config {
+    /// Every point of every sweep, in run order, each one checked against
+    /// its benchmark's options.
+    pub fn points(&self) -> Result<Vec<Point>, String> {
+        let mut points = Vec::new();
+        for (i, sweep) in self.sweep.iter().enumerate() {
+            let context = |e: String| format!("sweep {} ({}): {}", i + 1, sweep.kernel, e);
+            let benchmark = kernels::find(&sweep.kernel).ok_or_else(|| {
+                let known: Vec<&str> = kernels::REGISTRY.iter().map(|b| b.name()).collect();
+                context(format!("unknown kernel (use {})", known.join("|")))
+            })?;
+            for args in sweep.invocations(&benchmark.description()).map_err(context)? {
+                let argv = std::iter::once(benchmark.name().to_string()).chain(args.iter().cloned());
+                benchmark.command().try_get_matches_from(argv).map_err(|e| context(first_line(&e.to_string())))?;
+                points.push(Point { benchmark, args });
+            }
+        }
+        Ok(points)
+    }
+}
+
+impl Sweep {
+    /// The option lists of the grid's points, in description order.
+    pub fn invocations(&self, description: &Description) -> Result<Vec<Vec<String>>, String> {
+        for key in self.grid.keys() {
+            let name = format!("--{}", key);
+            if OUTPUT_OPTIONS.contains(&name.as_str()) {
+                return Err(format!("{} is a runner option; pass it on the command line", name));
+            }
+            if !description.options.iter().any(|o| o.name == name) {
+                return Err(format!("unknown option {}", name));
+            }
+        }
+
+        // each option's alternatives, as argument lists
+        let mut axes: Vec<Vec<Vec<String>>> = Vec::new();
+        for option in &description.options {
+            let Some(value) = self.grid.get(&option.name[2..]) else { continue };
+            let values = match value {
+                toml::Value::Array(values) if values.is_empty() => return Err(format!("{}: empty array", option.name)),
+                toml::Value::Array(values) => values.clone(),
+                value => vec![value.clone()],
+            };
+            let alternatives = values.iter().map(|v| arguments(option.name, &option.kind, v)).collect::<Result<_, _>>()?;
+            axes.push(alternatives);
+        }
+
+        let mut invocations = vec![Vec::new()];
+        for axis in axes {
+            invocations = invocations
+                .iter()
+                .flat_map(|prefix| {
+                    axis.iter().map(move |args| {
+                        let mut invocation: Vec<String> = prefix.clone();
+                        invocation.extend(args.iter().cloned());
+                        invocation
+                    })
+                })
+                .collect();
+        }
+        Ok(invocations)
+    }
+}
+
+// `--name value`, or the bare flag (or nothing) for a flag
+fn arguments(name: &str, kind: &Kind, value: &toml::Value) -> Result<Vec<String>, String> {
+    let value = match (kind, value) {
+        (Kind::Flag, toml::Value::Boolean(true)) => return Ok(vec![name.to_string()]),
+        (Kind::Flag, toml::Value::Boolean(false)) => return Ok(Vec::new()),
+        (Kind::Flag, _) => return Err(format!("{} is a flag: use true or false", name)),
+        (Kind::Switch, toml::Value::Boolean(on)) => (*on as u8).to_string(),
+        (_, toml::Value::Integer(v)) => v.to_string(),
+        (_, toml::Value::String(s)) => s.clone(),
+        (_, other) => return Err(format!("{}: unsupported value {}", name, other)),
+    };
+    Ok(vec![name.to_string(), value])
+}
+
+// clap's message without its usage and help lines
+fn first_line(message: &str) -> String {
+    message.lines().next().unwrap_or_default().trim_start_matches("error: ").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(text: &str) -> Result<Vec<Vec<String>>, String> {
+        let config = parse(text)?;
+        Ok(config.points()?.into_iter().map(|p| p.args).collect())
+    }
+
+    #[test]
+    fn test_grid() {
+        let runs = points(
+            r#"
+            [[sweep]]
+            kernel = "histogram"
+            threads = [1, "max"]
+            strategy = ["atomic", "local"]
+            dist = "uniform"
+            size = 1000
+            pad = true
+
+            [[sweep]]
+            kernel = "matrix_multiply"
+            size = 64
+            threads = "1,2"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(runs.len(), 5);
+        assert_eq!(runs[0].join(" "), "--strategy atomic --dist uniform --size 1000 --threads 1 --pad 1");
+        assert_eq!(runs[1][7], "max");
+        assert_eq!(runs[2][1], "local");
+        assert_eq!(runs[4].join(" "), "--size 64 --threads 1,2");
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = |text: &str| points(text).unwrap_err();
+        assert!(err("[[sweep]]\nkernel = \"nope\"").contains("unknown kernel"));
+        assert!(err("[[sweep]]\nkernel = \"histogram\"\ngrian = 4").contains("unknown option --grian"));
+        assert!(err("[[sweep]]\nkernel = \"prefix_sum\"\nformat = \"csv\"").contains("runner option"));
+        assert!(err("[[sweep]]\nkernel = \"prefix_sum\"\nsize = []").contains("empty array"));
+        assert!(err("[[sweep]]\nkernel = \"prefix_sum\"\nsize = 1.5").contains("unsupported value"));
+        // checked by clap before anything runs: a value it rejects
+        let rejected = err("[[sweep]]\nkernel = \"histogram\"\nstrategy = \"nope\"");
+        assert!(rejected.starts_with("sweep 1 (histogram):"), "{}", rejected);
+        assert!(err("[[sweep]]\nkernel = \"prefix_sum\"\nsize = -1").contains("sweep 1 (prefix_sum)"));
+        assert!(err("[run]\n").contains("unknown field"));
+    }
+
+    #[test]
+    fn test_shipped_campaign() {
+        let config = load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("benchmarks.toml")).unwrap();
+        let points = config.points().unwrap();
+        assert_eq!(points.len(), 40 + 15 + 10 + 15 + 60 + 25 + 20 + 30 + 10 + 10 + 2 + 40 + 5 + 10 + 10 + 10 + 5 + 20);
+    }
+}
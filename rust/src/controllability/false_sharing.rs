@@ -0,0 +1,267 @@
+// False-sharing benchmark for "amount of control" (Rust version)
+// T threads each increment a counter of their own N times. No two threads
+// ever touch the same counter, yet with the counters side by side eight of
+// them share a 64-byte cache line, and every increment takes the line away
+// from the neighbours. Each run times both layouts:
+//   adjacent   one u64 per thread, consecutive in one array
+//   padded     one counter per 64-byte line, as histogram --pad 1 does for
+//              its bins
+// The adjacent record adds slowdown, its time over the padded time: what
+// the padding is worth, without the histogram's data and true sharing.
+// Updates:
+//   plain      a load and a store per increment (`counts[t] += 1`)
+//   atomic     fetch_add(1, Relaxed) (`#pragma omp atomic`)
+// Each thread is a std thread pinned as --affinity says, the T threads of
+// an OpenMP parallel region.
+//
+// Usage:
+//   ./false_sharing [--size N] [--threads T] [options]
+//   --size N           increments per thread (default 10000000)
+//   --threads T        number of threads (default 8; a count or max, cores, half, 3/4)
+//
+// Options:
+//   --update U         plain (default) | atomic
+//   --affinity A       none (default) | compact | scatter | list:C,C,...
+//   --format FMT       text (default, CSV lines below) | jsonl | json | csv | markdown
+//   --upload URL       POST the result as JSON to an HTTP collector
+//   --prometheus FILE  keep FILE updated with Prometheus gauges for each result
+//   --store FILE       append the run to a SQLite database (--features store)
+//   --log FMT          pretty | json execution log on stderr (RUST_LOG filters)
+//   --reps R           timed runs per layout, the best is reported (default 1);
+//                      R > 1 adds time_mean, time_median and time_stddev
+//   --warmup W         untimed runs before them (default 1; 0 for a cold start)
+//   --describe         print the JSON description of arguments and metrics, then exit
+//
+// Output (CSV-style, one record per layout):
+//   false_sharing,rust,update=plain,layout=adjacent,N=10000000,T=8,affinity=none,time,0.123456,sec
+//   false_sharing,rust,update=plain,layout=adjacent,N=10000000,T=8,affinity=none,per,12.345,ns
+//   false_sharing,rust,update=plain,layout=adjacent,N=10000000,T=8,affinity=none,slowdown,8.12,x
+//   false_sharing,rust,update=plain,layout=adjacent,N=10000000,T=8,affinity=none,correct,1,boolean
+
+use crate::alloc_track::Region;
+use crate::backend::{Affinity, Placement};
+use crate::cli;
+use crate::describe::{Description, Param};
+use crate::kernels::{Benchmark, Suite};
+use crate::logging::Phase;
+use crate::results::{Record, Reporter};
+use crate::threads;
+use clap::{ArgMatches, CommandFactory, Parser};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::Instant;
+use tracing::info_span;
+
+// defaults of --size and --threads
+const N: usize = 10_000_000; // 10^7
+const THREADS: usize = 8;
+
+/// Bytes of a cache line, the distance between padded counters.
+pub const LINE: usize = 64;
+
+// counters per line
+const LINE_WORDS: usize = LINE / std::mem::size_of::<u64>();
+
+/// Where the threads' counters sit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    Adjacent,
+    Padded,
+}
+
+impl Layout {
+    pub const ALL: [Layout; 2] = [Layout::Adjacent, Layout::Padded];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Layout::Adjacent => "adjacent",
+            Layout::Padded => "padded",
+        }
+    }
+
+    // words from one thread's counter to the next
+    fn stride(self) -> usize {
+        match self {
+            Layout::Adjacent => 1,
+            Layout::Padded => LINE_WORDS,
+        }
+    }
+}
+
+// the counters of `threads` threads, thread t's at t * stride; a u64 is
+// 8-aligned, so none straddles two lines
+fn counters(layout: Layout, threads: usize) -> Vec<AtomicU64> {
+    (0..threads * layout.stride()).map(|_| AtomicU64::new(0)).collect()
+}
+
+/// T threads, pinned as `placement` says, each increment their counter of
+/// `layout` `n` times, with fetch_add if `atomic` and a relaxed load and
+/// store otherwise. Returns (seconds, the T counts).
+pub fn count(layout: Layout, threads: usize, n: usize, atomic: bool, placement: &Placement) -> (f64, Vec<u64>) {
+    let counts = counters(layout, threads);
+
+    let start = Instant::now();
+
+    thread::scope(|s| {
+        for t in 0..threads {
+            let counter = &counts[t * layout.stride()];
+            s.spawn(move || {
+                placement.pin(t);
+                if atomic {
+                    for _ in 0..n {
+                        counter.fetch_add(1, Ordering::Relaxed);
+                    }
+                } else {
+                    // relaxed loads and stores are plain moves the compiler
+                    // keeps, one of each per increment
+                    for _ in 0..n {
+                        counter.store(counter.load(Ordering::Relaxed) + 1, Ordering::Relaxed);
+                    }
+                }
+            });
+        }
+    });
+
+    let elapsed = start.elapsed().as_secs_f64();
+    (elapsed, (0..threads).map(|t| counts[t * layout.stride()].load(Ordering::Relaxed)).collect())
+}
+
+// answer to --describe
+pub fn description() -> Description {
+    Description::new("false_sharing", "false_sharing", "per-thread counters side by side vs one per cache line")
+        .option(Param::integer("--size", 1, "increments per thread").default(N))
+        .option(Param::threads("--threads", "threads").default(THREADS))
+        .option(Param::choice("--update", &["plain", "atomic"], "increment: a load and a store, or fetch_add").default("plain"))
+        .option(Param::text("--affinity", "none | compact | scatter | list:C,C,...: thread placement").default("none"))
+        .params(&["update", "layout", "N", "T", "affinity"])
+        .timing("time", "sec")
+        .timing("per", "ns")
+        .metric("slowdown", "x")
+        .metric("correct", "boolean")
+}
+
+/// Per-thread counters side by side vs one per cache line
+#[derive(Debug, Clone, Parser)]
+#[command(name = "false_sharing")]
+pub struct Args {
+    /// increments per thread
+    #[arg(long, value_name = "N", value_parser = cli::positive, default_value_t = N)]
+    pub size: usize,
+    /// threads (a count or max, cores, half, quarter, a/b)
+    #[arg(long, value_name = "T", value_parser = threads::parse_spec, default_value_t = THREADS)]
+    pub threads: usize,
+    /// increment: plain (a load and a store) | atomic (fetch_add)
+    #[arg(long, value_name = "U", value_parser = ["plain", "atomic"], default_value = "plain")]
+    pub update: String,
+    /// none | compact | scatter | list:C,C,...: thread placement
+    #[arg(long, value_name = "A", value_parser = Affinity::parse, default_value = "none")]
+    pub affinity: Affinity,
+    #[command(flatten)]
+    pub common: cli::Common,
+}
+
+pub struct FalseSharing;
+
+impl Benchmark for FalseSharing {
+    fn name(&self) -> &'static str {
+        "false_sharing"
+    }
+
+    fn label(&self) -> &'static str {
+        "False sharing"
+    }
+
+    fn suite(&self) -> Suite {
+        Suite::Controllability
+    }
+
+    fn description(&self) -> Description {
+        description()
+    }
+
+    fn command(&self) -> clap::Command {
+        Args::command()
+    }
+
+    fn run(&self, args: &ArgMatches, reporter: &mut Reporter) -> i32 {
+        run(&cli::from_matches(args), reporter)
+    }
+}
+
+pub fn run(args: &Args, reporter: &mut Reporter) -> i32 {
+    let Args { size: n, threads: t, .. } = *args;
+    let update = args.update.as_str();
+    let atomic = update == "atomic";
+    let affinity = args.affinity.name();
+
+    let _bench = info_span!("false_sharing", %update, n, threads = t, %affinity).entered();
+    let placement = Phase::Setup.run(|| args.affinity.placement(t));
+
+    let runs = Layout::ALL.map(|layout| {
+        let _layout = info_span!("layout", layout = layout.name()).entered();
+        let (elapsed, (counts, allocs)) = args.common.reps.repeat(1).run(|| {
+            let region = Region::start();
+            let (elapsed, counts) = count(layout, t, n, atomic, &placement);
+            (elapsed, (counts, region.finish()))
+        });
+        let correct = Phase::Verify.run(|| counts.iter().all(|&c| c == n as u64));
+        (layout, elapsed, allocs, correct)
+    });
+
+    let padded = runs[1].1.min;
+    for (layout, elapsed, allocs, correct) in &runs {
+        let mut record = Record::new("false_sharing", "rust")
+            .param("update", update)
+            .param("layout", layout.name())
+            .param("N", n)
+            .param("T", t)
+            .param("affinity", &affinity)
+            .timing("time", elapsed, "sec")
+            .timing("per", &elapsed.scaled(1e9 / n as f64), "ns");
+        if *layout == Layout::Adjacent {
+            record = record.metric("slowdown", elapsed.min / padded, "x");
+        }
+        let record = record
+            .metric("correct", if *correct { 1.0 } else { 0.0 }, "boolean")
+            .allocs(*allocs)
+            .energy(elapsed.energy)
+            .peak_rss(elapsed.peak_rss);
+        if reporter.is_text() {
+            print!("{}", record.to_csv());
+        }
+        reporter.record(&record);
+    }
+
+    if runs.iter().all(|run| run.3) {
+        0
+    } else {
+        3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layouts() {
+        // adjacent: the counters of 8 threads fit two lines at most; padded:
+        // every counter on a line of its own
+        let line = |counts: &[AtomicU64], t: usize, layout: Layout| (&counts[t * layout.stride()] as *const AtomicU64 as usize) / LINE;
+        let adjacent = counters(Layout::Adjacent, 8);
+        let mut lines: Vec<usize> = (0..8).map(|t| line(&adjacent, t, Layout::Adjacent)).collect();
+        lines.dedup();
+        assert!(lines.len() <= 2);
+        let padded = counters(Layout::Padded, 8);
+        let mut lines: Vec<usize> = (0..8).map(|t| line(&padded, t, Layout::Padded)).collect();
+        lines.dedup();
+        assert_eq!(lines.len(), 8);
+
+        for layout in Layout::ALL {
+            for atomic in [false, true] {
+                let (_, counts) = count(layout, 4, 10_000, atomic, &Placement::default());
+                assert_eq!(counts, [10_000; 4], "{} atomic={}", layout.name(), atomic);
+            }
+        }
+    }
+}
@@ -1,106 +1,301 @@
-// Histogram benchmark for "amount of control" (Rust/Rayon version)
+// Histogram benchmark for "amount of control" (Rust version)
 // Strategies:
-//   1) Rayon-Atomic: single shared histogram with atomic operations
-//   2) Rayon-Local: thread-local histograms + automatic reduction
+//   1) Atomic: single shared histogram with atomic operations
+//   2) Local: thread-local histograms + reduction
+//   3) Striped: consecutive groups of bins, each behind one mutex of an array
+//      (OpenMP's array of omp_lock_t), locked per element
+//   4) Manual: T plain std::threads over static ranges, each with a local
+//      histogram, merged after the joins; no pool, one-to-one with the OpenMP
+//      parallel for of local
+//   5) Scoped: as manual, on crossbeam scoped threads that borrow their
+//      ranges of the input instead of sharing it through an Arc
+// The first three run on any parallel backend (rayon by default); the local
+// strategy can also run as T worker processes (--multiproc) for an MPI-style
+// comparison.
 //
 // Usage:
-//   ./histogram <strategy> <dist> <N> <T> [grain] [pad] [affinity]
-//   strategy: atomic | local
-//   dist:     uniform | skewed
-//   N:        number of elements (e.g., 10000000)
-//   T:        number of threads (e.g., 1,2,4,8,16)
-//   grain:    chunk size per task (0 = auto)
-//   pad:      0 | 1 (atomic only; 1 = padded bins)
-//   affinity: 0 | 1 (0 = no pinning, 1 = pin threads to cores)
+//   ./histogram [--strategy S] [--dist D] [--size N] [--threads T] [options]
+//   --strategy S       atomic | local (default) | striped | manual | scoped
+//   --dist D           uniform (default) | skewed | zipf[:S] | gaussian[:MU:SIGMA]
+//                      zipf: P(bin k) ~ 1 / (k + 1)^S (default S = 1);
+//                      gaussian: N(MU, SIGMA) rounded and clamped to the bins
+//                      (default 128:32)
+//   --size N           number of elements (default 10000000)
+//   --threads T        number of threads (default 8; a count or max, cores, half, 3/4)
 //
-// Output (CSV-style):
-//   hist,rayon,strategy=atomic,dist=uniform,N=10000000,T=8,grain=0,pad=0,affinity=0,time,0.123456,sec
-//   hist,rayon,strategy=atomic,dist=uniform,N=10000000,T=8,grain=0,pad=0,affinity=0,correct,1,boolean
-
-use rayon::prelude::*;
-use std::env;
-use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+// Options:
+//   --grain G          chunk size per task (default 0 = auto; manual, scoped: 0 only)
+//   --sweep-grain LO:HI:STEP
+//                      time grains LO, LO + STEP, ... up to HI in one run, on
+//                      the same input and pool; one record per grain (atomic,
+//                      local and striped only; replaces --grain)
+//   --pad 0|1          atomic only; 1 = padded bins (default 0)
+//   --stripes S        striped only; mutexes over groups of 256 / S bins
+//                      (default 16, at most 256)
+//   --affinity A       none (default) | compact | scatter | list:C,C,...: pin
+//                      threads as OMP_PROC_BIND=close / spread, or to the
+//                      listed CPUs as OMP_PLACES; 0 and 1 mean none and compact
+//   --numa-first-touch 0|1
+//                      1 = each thread that bins a piece of the input writes
+//                      it first, so its pages sit on that thread's NUMA node
+//                      (default 0: generated on Rayon's global pool); pair
+//                      with --affinity; not with --input or --multiproc
+//   --input FILE       bin the first N bytes of a raw binary file instead of
+//                      generating them (replaces --dist and --seed; records
+//                      carry dist=file, seed=0); control_openmp reads the same
+//                      bytes with dist file:FILE
+//   --seed S           input seed (default 123456789 uniform, 987654321 skewed,
+//                      246813579 zipf, 135792468 gaussian)
+//   --simd             std::simd local-histogram path (local only; nightly + --features simd)
+//   --dump FILE        write the bins as a canonical dump (see dump.rs); compare
+//                      with the C program's: run_all_benchmarks verify A B
+//   --ffi 0|1          1 = also time the C/OpenMP kernel, linked in, on the same
+//                      input; its record has impl openmp (--features ffi)
+//   --backend B        rayon (default) | threads | crossbeam | workers
+//                      (+ tokio-blocking | tokio-async with --features tokio);
+//                      manual and scoped spawn their own threads, their records
+//                      have impl std and crossbeam
+//   --multiproc M      local only: T processes instead of threads, partial
+//                      histograms returned via shm | pipe
+//   --format FMT       text (default, CSV lines below) | jsonl | json | csv | markdown
+//   --upload URL       POST the result as JSON to an HTTP collector
+//   --prometheus FILE  keep FILE updated with Prometheus gauges for each result
+//   --store FILE       append the run to a SQLite database (--features store)
+//   --log FMT          pretty | json execution log on stderr (RUST_LOG filters)
+//   --pages P          default | thp | hugetlb pages for the input array
+//   --reps R           timed runs, the best is reported (default 1); R > 1 adds
+//                      time_mean, time_median and time_stddev
+//   --warmup W         untimed runs before them (default 1; 0 for a cold start)
+//   --describe         print the JSON description of arguments and metrics, then exit
+//
+// Output (CSV-style, second field is the backend):
+//   hist,rayon,strategy=atomic,dist=uniform,N=10000000,T=8,grain=0,pad=0,stripes=0,affinity=none,first_touch=0,simd=0,pages=default,seed=123456789,time,0.123456,sec
+//   hist,rayon,strategy=atomic,dist=uniform,N=10000000,T=8,grain=0,pad=0,stripes=0,affinity=none,first_touch=0,simd=0,pages=default,seed=123456789,correct,1,boolean
+//   hist,rayon,strategy=atomic,dist=uniform,N=10000000,T=8,grain=0,pad=0,stripes=0,affinity=none,first_touch=0,simd=0,pages=default,seed=123456789,node0_pages,2442,pages
+// with a nodeK_pages line for every NUMA node holding pages of the input
+// (from /proc/self/numa_maps, where the kernel has it)
+
+use crate::alloc_track::Region;
+use crate::backend::{Affinity, Backend, Placement, Pool};
+use crate::cli;
+use crate::describe::{Description, Param};
+use crate::dump::Dump;
+use crate::ffi;
+use crate::golden::{self, Digest, Golden, Verdict};
+use crate::kernels::{Benchmark, Suite, WorkerKernel};
+use crate::logging::Phase;
+use crate::multiproc::{Ipc, ProcessPool};
+use crate::numa;
+use crate::openmp::OPENMP;
+use crate::pages::{PageVec, Pages};
+use crate::results::{Record, Reporter};
+use crate::rng;
+use crate::threads;
+use clap::{ArgAction, ArgMatches, CommandFactory, Parser};
+use std::ops::Deref;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
 use std::time::Instant;
-
-const BINS: usize = 256;
+use tracing::info_span;
+
+pub const BINS: usize = 256;
+// defaults of --size and --threads
+const N: usize = 10_000_000; // 10^7
+const THREADS: usize = 8;
+// default of --stripes
+pub const STRIPES: usize = 16;
+// default seeds of the inputs
+pub const UNIFORM_SEED: u64 = 123456789;
+pub const SKEWED_SEED: u64 = 987654321;
+pub const ZIPF_SEED: u64 = 246813579;
+pub const GAUSSIAN_SEED: u64 = 135792468;
+
+// bin-count checksums of the generated inputs, whatever the strategy; the
+// zipf and gaussian bins go through libm (pow, log, cos), so they have none
+const GOLDEN: &[Golden] = &[
+    Golden { key: "dist=uniform,seed=123456789,N=1000000", digest: Digest::Checksum(0xfd179e13ade22359) },
+    Golden { key: "dist=uniform,seed=123456789,N=10000000", digest: Digest::Checksum(0x18b068bff9635dcd) },
+    Golden { key: "dist=skewed,seed=987654321,N=1000000", digest: Digest::Checksum(0xc234874c903c8ee3) },
+    Golden { key: "dist=skewed,seed=987654321,N=10000000", digest: Digest::Checksum(0x7a781f807b6102fb) },
+];
 
 #[repr(align(64))]
 struct PaddedAtomicU64(AtomicU64);
 
-// global counter for thread ID assignment, for affinity
-static THREAD_COUNTER: AtomicUsize = AtomicUsize::new(0);
-
-// pin thread to specific core
-fn set_thread_affinity() -> usize {
-    let thread_id = THREAD_COUNTER.fetch_add(1, Ordering::SeqCst);
-    let core_ids_result = core_affinity::get_core_ids();
-    
-    if let Some(core_ids) = core_ids_result {
-        if thread_id < core_ids.len() {
-            core_affinity::set_for_current(core_ids[thread_id]);
-        }
-    }
-    
-    thread_id
+// inputs come from the counter-based rng module, so they are identical for
+// every thread count and match the C benchmark's splitmix64_at generators;
+// each element uses the top 32 bits of its stream value
+fn draw(r: u64) -> u32 {
+    (r >> 32) as u32
 }
 
-// LCG RNG 
-fn lcg_next(x: u32) -> u32 {
-    x.wrapping_mul(1664525u32).wrapping_add(1013904223u32)
+/// `n` bytes uniformly distributed over [0,255], the same for every thread count.
+pub fn gen_uniform(n: usize, seed: u64, pages: Pages) -> PageVec<u8> {
+    Dist::Uniform.generate(n, seed, pages)
 }
 
-// uniform distribution [0,255]
-fn gen_uniform(n: usize) -> Vec<u8> {
-    let mut data = Vec::with_capacity(n);
-    let mut x = 123456789u32;
-    for _ in 0..n {
-        x = lcg_next(x);
-        data.push((x & 0xFF) as u8);
-    }
-    data
+/// `n` skewed bytes: ~80% in the first 20% of the bins (0..51).
+pub fn gen_skewed(n: usize, seed: u64, pages: Pages) -> PageVec<u8> {
+    Dist::Skewed.generate(n, seed, pages)
 }
 
-// skewed distribution: ~80% in first 20% bins (0..51)
-fn gen_skewed(n: usize) -> Vec<u8> {
-    let mut data = Vec::with_capacity(n);
-    let hot_bins = (BINS as f64 * 0.2) as u8; // 51
-    let threshold = (0.8 * u32::MAX as f64) as u32; // ~80%
-    let mut x = 987654321u32;
-
-    for _ in 0..n {
-        x = lcg_next(x);
-        let val = if x < threshold {
-            // hot range
-            (x % hot_bins as u32) as u8
-        } else {
-            // cold range
-            let mut v = (x & 0xFF) as u8;
-            if v < hot_bins {
-                v += hot_bins;
-            }
-            v
-        };
-        data.push(val);
-    }
-    data
+/// `n` bytes with P(bin k) proportional to 1 / (k + 1)^`s`: bin 0 the
+/// hottest, then a heavy tail over the rest.
+pub fn gen_zipf(n: usize, s: f64, seed: u64, pages: Pages) -> PageVec<u8> {
+    Dist::Zipf(s).generate(n, seed, pages)
+}
+
+/// `n` bytes from N(`mu`, `sigma`), rounded to the nearest bin and clamped
+/// to [0, 255].
+pub fn gen_gaussian(n: usize, mu: f64, sigma: f64, seed: u64, pages: Pages) -> PageVec<u8> {
+    Dist::Gaussian(mu, sigma).generate(n, seed, pages)
 }
 
-// Strategy 1: Rayon Atomic (Shared Histogram)
-fn hist_atomic(data: &[u8], num_threads: usize, grain: usize, pad: bool, use_affinity: bool) -> (f64, Vec<u64>) {
+// the top 53 bits of a stream value as a double in [0, 1)
+fn unit(r: u64) -> f64 {
+    (r >> 11) as f64 / 9007199254740992.0
+}
 
-    if use_affinity {
-        THREAD_COUNTER.store(0, Ordering::SeqCst);
+/// An input distribution of --dist.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dist {
+    Uniform,
+    Skewed,
+    /// exponent s
+    Zipf(f64),
+    /// mean and standard deviation
+    Gaussian(f64, f64),
+}
+
+impl Dist {
+    /// uniform | skewed | zipf[:S] | gaussian[:MU:SIGMA]; S and SIGMA must be
+    /// positive.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut parts = s.split(':');
+        let kind = parts.next().unwrap_or_default();
+        let numbers = parts
+            .map(|part| part.parse::<f64>().ok().filter(|x| x.is_finite()).ok_or_else(|| format!("{}: not a number in {}", part, s)))
+            .collect::<Result<Vec<f64>, String>>()?;
+        match (kind, numbers.as_slice()) {
+            ("uniform", []) => Ok(Dist::Uniform),
+            ("skewed", []) => Ok(Dist::Skewed),
+            ("zipf", []) => Ok(Dist::Zipf(1.0)),
+            ("zipf", &[s]) if s > 0.0 => Ok(Dist::Zipf(s)),
+            ("gaussian", []) => Ok(Dist::Gaussian(128.0, 32.0)),
+            ("gaussian", &[mu, sigma]) if sigma > 0.0 => Ok(Dist::Gaussian(mu, sigma)),
+            _ => Err(format!("unknown distribution {} (uniform | skewed | zipf[:S] | gaussian[:MU:SIGMA], S and SIGMA > 0)", s)),
+        }
+    }
+
+    /// The `dist` param of the records, with every parameter spelled out
+    /// (e.g. zipf:1, gaussian:128:32), as the C program takes it.
+    pub fn name(self) -> String {
+        match self {
+            Dist::Uniform => "uniform".to_string(),
+            Dist::Skewed => "skewed".to_string(),
+            Dist::Zipf(s) => format!("zipf:{}", s),
+            Dist::Gaussian(mu, sigma) => format!("gaussian:{}:{}", mu, sigma),
+        }
     }
-    
-    let pool = rayon::ThreadPoolBuilder::new()
-        .num_threads(num_threads)
-        .start_handler(move |_| {
-            if use_affinity {
-                set_thread_affinity();
+
+    /// The seed of the input unless --seed is given.
+    pub fn default_seed(self) -> u64 {
+        match self {
+            Dist::Uniform => UNIFORM_SEED,
+            Dist::Skewed => SKEWED_SEED,
+            Dist::Zipf(_) => ZIPF_SEED,
+            Dist::Gaussian(..) => GAUSSIAN_SEED,
+        }
+    }
+
+    /// `n` bytes of this distribution from the stream `seed`.
+    pub fn generate(self, n: usize, seed: u64, pages: Pages) -> PageVec<u8> {
+        let mut data = cli::or_exit(PageVec::filled(n, 0, pages));
+        rng::fill(&mut data, seed, self.sampler());
+        data
+    }
+
+    /// As `generate`, but into an untouched buffer whose pieces `place`
+    /// writes: it gets the buffer and a writer of the piece starting at
+    /// element `start`, and should call it on the thread that will bin that
+    /// piece, so Linux backs the piece's pages on that thread's NUMA node.
+    pub fn generate_placed(self, n: usize, seed: u64, pages: Pages, place: impl FnOnce(&mut [u8], &(dyn Fn(usize, &mut [u8]) + Sync))) -> PageVec<u8> {
+        let sample = self.sampler();
+        let mut data = cli::or_exit(PageVec::zeroed(n, pages));
+        place(&mut data, &|start, piece| rng::fill_from(piece, start, seed, &sample));
+        data
+    }
+
+    // the byte of each stream value
+    fn sampler(self) -> impl Fn(u64) -> u8 + Sync {
+        // skewed: ~80% of the draws in the 51 hot bins
+        let hot_bins = (BINS as f64 * 0.2) as u8; // 51
+        let threshold = (0.8 * u32::MAX as f64) as u32; // ~80%
+
+        // zipf: running sums of the weights; a draw takes the first bin past u * total
+        let mut cdf = [0.0; BINS];
+        if let Dist::Zipf(s) = self {
+            let mut total = 0.0;
+            for (k, sum) in cdf.iter_mut().enumerate() {
+                total += ((k + 1) as f64).powf(-s);
+                *sum = total;
             }
-        })
-        .build()
-        .unwrap();
+        }
+
+        move |r| match self {
+            Dist::Uniform => (draw(r) & 0xFF) as u8,
+            Dist::Skewed => {
+                let x = draw(r);
+                if x < threshold {
+                    // hot range
+                    (x % hot_bins as u32) as u8
+                } else {
+                    // cold range
+                    let mut v = (x & 0xFF) as u8;
+                    if v < hot_bins {
+                        v += hot_bins;
+                    }
+                    v
+                }
+            }
+            Dist::Zipf(_) => {
+                let target = unit(r) * cdf[BINS - 1];
+                cdf.partition_point(|&sum| sum <= target).min(BINS - 1) as u8
+            }
+            Dist::Gaussian(mu, sigma) => {
+                // Box-Muller on the two halves of the stream value, u1 in (0, 1]
+                let u1 = ((r >> 32) as f64 + 1.0) / 4294967296.0;
+                let u2 = (r & 0xFFFF_FFFF) as f64 / 4294967296.0;
+                let z = (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos();
+                (mu + sigma * z).round().clamp(0.0, 255.0) as u8
+            }
+        }
+    }
+}
+
+/// The first `n` bytes of the raw binary file at `path`, on `pages`; an
+/// error if it cannot be read or holds fewer.
+pub fn read_input(path: &Path, n: usize, pages: Pages) -> Result<PageVec<u8>, String> {
+    let fail = |e: std::io::Error| format!("{}: {}", path.display(), e);
+    let mut file = File::open(path).map_err(fail)?;
+    let len = file.metadata().map_err(fail)?.len();
+    if len < n as u64 {
+        return Err(format!("{}: {} bytes, {} needed", path.display(), len, n));
+    }
+    let mut data = PageVec::zeroed(n, pages)?;
+    file.read_exact(&mut data).map_err(fail)?;
+    Ok(data)
+}
+
+/// Strategy 1: one shared histogram of atomic bins, `pad` putting each bin on
+/// its own cache line; `grain` 0 picks the backend's default chunk. Returns
+/// (seconds, bins).
+pub fn hist_atomic(pool: &Pool, data: &[u8], grain: usize, pad: bool) -> (f64, Vec<u64>) {
+    let chunk = if grain > 0 { grain } else { pool.default_grain(data.len()) };
 
     let start = Instant::now();
 
@@ -110,21 +305,11 @@ fn hist_atomic(data: &[u8], num_threads: usize, grain: usize, pad: bool, use_aff
             .map(|_| PaddedAtomicU64(AtomicU64::new(0)))
             .collect();
 
-        pool.install(|| {
-            if grain > 0 {
-                data.par_chunks(grain).for_each(|chunk| {
-                    for &val in chunk {
-                        histogram[val as usize]
-                            .0
-                            .fetch_add(1, Ordering::Relaxed);
-                    }
-                });
-            } else {
-                data.par_iter().for_each(|&val| {
-                    histogram[val as usize]
-                        .0
-                        .fetch_add(1, Ordering::Relaxed);
-                });
+        pool.for_each_chunk(data, chunk, |_, c| {
+            for &val in c {
+                histogram[val as usize]
+                    .0
+                    .fetch_add(1, Ordering::Relaxed);
             }
         });
 
@@ -138,17 +323,9 @@ fn hist_atomic(data: &[u8], num_threads: usize, grain: usize, pad: bool, use_aff
             .map(|_| AtomicU64::new(0))
             .collect();
 
-        pool.install(|| {
-            if grain > 0 {
-                data.par_chunks(grain).for_each(|chunk| {
-                    for &val in chunk {
-                        histogram[val as usize].fetch_add(1, Ordering::Relaxed);
-                    }
-                });
-            } else {
-                data.par_iter().for_each(|&val| {
-                    histogram[val as usize].fetch_add(1, Ordering::Relaxed);
-                });
+        pool.for_each_chunk(data, chunk, |_, c| {
+            for &val in c {
+                histogram[val as usize].fetch_add(1, Ordering::Relaxed);
             }
         });
 
@@ -162,152 +339,607 @@ fn hist_atomic(data: &[u8], num_threads: usize, grain: usize, pad: bool, use_aff
     (elapsed, result)
 }
 
-// Strategy 2: Rayon Local (Thread-Local Histograms)
-fn hist_local(data: &[u8], num_threads: usize, grain: usize, use_affinity: bool) -> (f64, Vec<u64>) {
+/// The histogram of one chunk.
+pub fn local_hist_scalar(chunk: &[u8]) -> [u64; BINS] {
+    let mut local_hist = [0u64; BINS];
+    for &val in chunk {
+        local_hist[val as usize] += 1;
+    }
+    local_hist
+}
 
-    if use_affinity {
-        THREAD_COUNTER.store(0, Ordering::SeqCst);
+/// SIMD variant of `local_hist_scalar`: one sub-histogram per lane, so
+/// consecutive elements never update the same counter, then the lanes are
+/// merged with vector adds. Needs a build with the "simd" feature.
+#[cfg(feature = "simd")]
+pub fn local_hist_simd(chunk: &[u8]) -> [u64; BINS] {
+    use crate::simd_util;
+    use std::simd::prelude::*;
+    const LANES: usize = 8;
+
+    let mut lanes = [[0u64; BINS]; LANES];
+    // a zero-padded tail would count phantom zeros, so it stays scalar
+    let (blocks, tail) = simd_util::lanes_exact::<u8, LANES>(chunk);
+    for block in blocks {
+        for (lane, &val) in block.to_array().iter().enumerate() {
+            lanes[lane][val as usize] += 1;
+        }
     }
-    
-    let pool = rayon::ThreadPoolBuilder::new()
-        .num_threads(num_threads)
-        .start_handler(move |_| {
-            if use_affinity {
-                set_thread_affinity();
+    for &val in tail {
+        lanes[0][val as usize] += 1;
+    }
+
+    let mut local_hist = [0u64; BINS];
+    simd_util::update(&mut local_hist, |b, _| {
+        lanes
+            .iter()
+            .fold(u64x8::splat(0), |acc, lane| acc + simd_util::load_prefix(&lane[b..]))
+    });
+    local_hist
+}
+
+#[cfg(not(feature = "simd"))]
+pub fn local_hist_simd(_chunk: &[u8]) -> [u64; BINS] {
+    unreachable!("built without the simd feature")
+}
+
+/// Strategy 3: the bins in `stripes` groups of consecutive bins, each behind
+/// its own mutex, which every element locks to count itself; `grain` 0 picks
+/// the backend's default chunk. 1 stripe is one global lock, 256 one lock
+/// per bin. Returns (seconds, bins).
+pub fn hist_striped(pool: &Pool, data: &[u8], grain: usize, stripes: usize) -> (f64, Vec<u64>) {
+    assert!((1..=BINS).contains(&stripes), "1 to {} stripes", BINS);
+    let chunk = if grain > 0 { grain } else { pool.default_grain(data.len()) };
+    // stripe s holds bins [ceil(s * BINS / stripes), ceil((s + 1) * BINS / stripes)),
+    // so bin v falls in stripe v * stripes / BINS
+    let first = |s: usize| (s * BINS).div_ceil(stripes);
+    let groups: Vec<Mutex<Vec<u64>>> = (0..stripes).map(|s| Mutex::new(vec![0; first(s + 1) - first(s)])).collect();
+
+    let start = Instant::now();
+
+    pool.for_each_chunk(data, chunk, |_, c| {
+        for &val in c {
+            let s = val as usize * stripes / BINS;
+            groups[s].lock().unwrap()[val as usize - first(s)] += 1;
+        }
+    });
+
+    let elapsed = start.elapsed().as_secs_f64();
+    let result = groups.into_iter().flat_map(|group| group.into_inner().unwrap()).collect();
+    (elapsed, result)
+}
+
+/// Strategy 2: a private histogram per chunk, summed at the end; `grain` 0
+/// means one chunk per thread. Returns (seconds, bins).
+pub fn hist_local(pool: &Pool, data: &[u8], grain: usize, simd: bool) -> (f64, Vec<u64>) {
+    // auto grain: one chunk per thread
+    let chunk = if grain > 0 { grain } else { data.len().div_ceil(pool.threads()) };
+
+    let start = Instant::now();
+
+    let histogram = pool.map_reduce(
+        data,
+        chunk,
+        || [0u64; BINS],
+        |_, c| if simd { local_hist_simd(c) } else { local_hist_scalar(c) },
+        |mut acc, local| {
+            for i in 0..BINS {
+                acc[i] += local[i];
             }
-        })
-        .build()
-        .unwrap();
+            acc
+        },
+    );
+
+    let elapsed = start.elapsed().as_secs_f64();
+    (elapsed, histogram.to_vec())
+}
+
+/// Strategy 4: `threads` std::threads spawned on a shared handle of the
+/// input, thread t counting [t * n / T, (t + 1) * n / T) into its own
+/// histogram, which the main thread adds up as it joins them; thread t is
+/// pinned as `placement` says. The spawns and joins are timed, as OpenMP's
+/// fork and join are. Returns (seconds, bins).
+pub fn hist_manual<D>(data: &Arc<D>, threads: usize, placement: &Placement) -> (f64, Vec<u64>)
+where
+    D: Deref<Target = [u8]> + Send + Sync + 'static,
+{
+    let n = data.len();
 
     let start = Instant::now();
 
-    let histogram = pool.install(|| {
-        let par = if grain > 0 {
-            data.par_chunks(grain)
-        } else {
-            let chunk_size = (data.len() + num_threads - 1) / num_threads;
-            data.par_chunks(chunk_size)
-        };
-
-        par.map(|chunk| {
-                let mut local_hist = [0u64; BINS];
-                for &val in chunk {
-                    local_hist[val as usize] += 1;
-                }
-                local_hist
+    let handles: Vec<_> = (0..threads)
+        .map(|t| {
+            let data = Arc::clone(data);
+            let placement = placement.clone();
+            thread::spawn(move || {
+                placement.pin(t);
+                local_hist_scalar(&data[t * n / threads..(t + 1) * n / threads])
             })
-            .reduce(
-                || [0u64; BINS],
-                |mut acc, local| {
-                    for i in 0..BINS {
-                        acc[i] += local[i];
-                    }
-                    acc
-                },
-            )
+        })
+        .collect();
+    let mut histogram = vec![0u64; BINS];
+    for handle in handles {
+        let local = handle.join().unwrap();
+        for i in 0..BINS {
+            histogram[i] += local[i];
+        }
+    }
+
+    let elapsed = start.elapsed().as_secs_f64();
+    (elapsed, histogram)
+}
+
+// thread t of `threads`, pinned as `placement` says, runs f(start, range) on
+// [t * n / T, (t + 1) * n / T): the ranges of hist_manual and hist_scoped
+fn on_ranges(data: &mut [u8], threads: usize, placement: &Placement, f: &(dyn Fn(usize, &mut [u8]) + Sync)) {
+    let n = data.len();
+    thread::scope(|s| {
+        let mut rest = data;
+        for t in 0..threads {
+            let (range, tail) = rest.split_at_mut((t + 1) * n / threads - t * n / threads);
+            rest = tail;
+            s.spawn(move || {
+                placement.pin(t);
+                f(t * n / threads, range)
+            });
+        }
     });
+}
+
+/// Strategy 5: as `hist_manual`, with the threads spawned in a
+/// `crossbeam::scope`, each borrowing its range of `data`; the scope joins
+/// them before returning, so neither an Arc nor a 'static input is needed.
+/// Returns (seconds, bins).
+pub fn hist_scoped(data: &[u8], threads: usize, placement: &Placement) -> (f64, Vec<u64>) {
+    let n = data.len();
+
+    let start = Instant::now();
+
+    let locals: Vec<[u64; BINS]> = crossbeam::scope(|s| {
+        let handles: Vec<_> = (0..threads)
+            .map(|t| {
+                let range = &data[t * n / threads..(t + 1) * n / threads];
+                s.spawn(move |_| {
+                    placement.pin(t);
+                    local_hist_scalar(range)
+                })
+            })
+            .collect();
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+    })
+    .unwrap();
+    let mut histogram = vec![0u64; BINS];
+    for local in locals {
+        for i in 0..BINS {
+            histogram[i] += local[i];
+        }
+    }
 
     let elapsed = start.elapsed().as_secs_f64();
-    (elapsed, histogram.to_vec())
+    (elapsed, histogram)
+}
+
+// Strategy 2 across processes: every worker builds its block's histogram,
+// the parent adds up the partials (timing covers spawn, scatter and gather)
+fn hist_local_procs(procs: &ProcessPool, data: &[u8], simd: bool) -> io::Result<(f64, Vec<u64>)> {
+    let kernel = if simd { "hist-local-simd" } else { "hist-local" };
+
+    let start = Instant::now();
+
+    let partials = procs.map(kernel, data, 1, BINS)?;
+    let mut histogram = vec![0u64; BINS];
+    for partial in partials {
+        for i in 0..BINS {
+            histogram[i] += partial[i];
+        }
+    }
+
+    let elapsed = start.elapsed().as_secs_f64();
+    Ok((elapsed, histogram))
+}
+
+pub fn worker_kernel(name: &str, block: &[u8]) -> Option<Vec<u64>> {
+    match name {
+        "hist-local" => Some(local_hist_scalar(block).to_vec()),
+        "hist-local-simd" => Some(local_hist_simd(block).to_vec()),
+        _ => None,
+    }
 }
 
 // sum(hist) == N
-fn check_correct(hist: &[u64], n: usize) -> bool {
+// every element counted once, and each bin as registered for this input
+fn check_correct(hist: &[u64], n: usize, key: &str) -> bool {
     let total: u64 = hist.iter().sum();
-    total as usize == n
+    let digest = Digest::Checksum(golden::checksum(hist.iter().copied()));
+    total as usize == n && golden::check("hist", GOLDEN, key, digest) != Verdict::Mismatch
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-
-    if args.len() < 5 {
-        eprintln!(
-            "usage: {} <strategy> <dist> <N> <T> [grain] [pad]",
-            args[0]
-        );
-        eprintln!("  strategy: atomic | local");
-        eprintln!("  dist:     uniform | skewed");
-        eprintln!("  N:        number of elements (e.g. 10000000)");
-        eprintln!("  T:        threads (e.g. 1,2,4,8,16)");
-        eprintln!("  grain:    chunk size per task (0 = auto)");
-        eprintln!("  pad:      0 | 1 (atomic only; default 0)");
-        std::process::exit(1);
-    }
-
-    let strategy = &args[1];
-    let dist = &args[2];
-    let n: usize = args[3].parse().expect("N must be a positive integer");
-    let t: usize = args[4].parse().expect("T must be a positive integer");
-    let grain: usize = if args.len() > 5 {
-        args[5].parse().unwrap_or(0)
-    } else {
-        0
+// answer to --describe
+pub fn description() -> Description {
+    Description::new("histogram", "hist", "256-bin histogram with shared atomic or thread-local bins")
+        .option(Param::choice("--strategy", &["atomic", "local", "striped", "manual", "scoped"], "bin update strategy").default("local"))
+        .option(Param::text("--dist", "input distribution: uniform | skewed | zipf[:S] | gaussian[:MU:SIGMA] (unless --input)").default("uniform"))
+        .option(Param::text("--input", "bin the first N bytes of this raw binary file instead of a generated input").optional())
+        .option(Param::integer("--size", 1, "elements").default(N))
+        .option(Param::threads("--threads", "threads").default(THREADS))
+        .option(Param::integer("--grain", 0, "chunk size per task, 0 = auto (manual, scoped: 0 only)").default(0))
+        .option(Param::text("--sweep-grain", "LO:HI:STEP: time every grain of the range on the same input, one record each (pooled strategies only)").optional())
+        .option(Param::switch("--pad", "padded bins (atomic only)").default(0))
+        .option(Param::integer("--stripes", 1, "mutexes over groups of bins, at most 256 (striped only)").default(STRIPES))
+        .option(Param::text("--affinity", "none | compact | scatter | list:C,C,...: thread placement as OMP_PROC_BIND / OMP_PLACES").default("none"))
+        .option(Param::switch("--numa-first-touch", "write each piece of the input on the thread that bins it").default(0))
+        .option(Param::integer("--seed", 0, "input seed (default per distribution)").optional())
+        .backend_option()
+        .multiproc_option("local only: T processes instead of threads")
+        .simd_option("std::simd local-histogram path (local only)")
+        .ffi_option()
+        .dump_option("write the bins to this file as a canonical result dump")
+        .pages_option()
+        .params(&["strategy", "dist", "N", "T", "grain", "pad", "stripes", "affinity", "first_touch", "simd", "pages", "seed"])
+        .timing("time", "sec")
+        .metric("correct", "boolean")
+        // and node1_pages, ... for every node holding input pages
+        .metric("node0_pages", "pages")
+}
+
+/// 256-bin histogram with shared atomic or thread-local bins
+#[derive(Debug, Clone, Parser)]
+#[command(name = "histogram")]
+pub struct Args {
+    /// bin update strategy
+    #[arg(long, value_parser = ["atomic", "local", "striped", "manual", "scoped"], default_value = "local")]
+    pub strategy: String,
+    /// input distribution: uniform (default) | skewed | zipf[:S] (default S = 1) | gaussian[:MU:SIGMA] (default 128:32)
+    #[arg(long, value_name = "D", value_parser = Dist::parse)]
+    pub dist: Option<Dist>,
+    /// bin the first N bytes of this raw binary file instead of a generated input (replaces --dist and --seed)
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["dist", "seed"])]
+    pub input: Option<PathBuf>,
+    /// elements
+    #[arg(long, value_name = "N", value_parser = cli::positive, default_value_t = N)]
+    pub size: usize,
+    /// threads (a count or max, cores, half, quarter, a/b)
+    #[arg(long, value_name = "T", value_parser = threads::parse_spec, default_value_t = THREADS)]
+    pub threads: usize,
+    /// chunk size per task, 0 = auto (manual, scoped: 0 only)
+    #[arg(long, default_value_t = 0)]
+    pub grain: usize,
+    /// LO:HI:STEP: time every grain from LO to HI in steps of STEP on the same input, one record each
+    #[arg(long, value_name = "LO:HI:STEP", value_parser = cli::Steps::parse)]
+    pub sweep_grain: Option<cli::Steps>,
+    /// 0 | 1: padded bins (atomic only)
+    #[arg(long, value_name = "0|1", action = ArgAction::Set, value_parser = cli::switch, default_value = "0")]
+    pub pad: bool,
+    /// striped only: mutexes over groups of 256 / S bins (at most 256)
+    #[arg(long, value_name = "S", value_parser = cli::positive, default_value_t = STRIPES)]
+    pub stripes: usize,
+    /// none | compact | scatter | list:C,C,...: pin threads as OMP_PROC_BIND=close / spread, or to the listed CPUs as OMP_PLACES (0 and 1: none and compact)
+    #[arg(long, value_name = "A", value_parser = Affinity::parse, default_value = "none")]
+    pub affinity: Affinity,
+    /// 0 | 1: write each piece of the input on the thread that bins it, so its pages sit on that thread's NUMA node
+    #[arg(long, value_name = "0|1", action = ArgAction::Set, value_parser = cli::switch, default_value = "0", conflicts_with_all = ["input", "multiproc"])]
+    pub numa_first_touch: bool,
+    /// input seed (default 123456789 uniform, 987654321 skewed, 246813579 zipf, 135792468 gaussian)
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// std::simd local-histogram path (local only; nightly + --features simd)
+    #[arg(long)]
+    pub simd: bool,
+    /// write the bins to FILE as a canonical result dump
+    #[arg(long, value_name = "FILE")]
+    pub dump: Option<PathBuf>,
+    /// 0 | 1: also time the linked C/OpenMP kernel on the same input (--features ffi)
+    #[arg(long, value_name = "0|1", action = ArgAction::Set, value_parser = cli::switch, default_value = "0")]
+    pub ffi: bool,
+    /// local only: T processes instead of threads, partials via shm | pipe
+    #[arg(long, value_name = "M", value_parser = Ipc::parse)]
+    pub multiproc: Option<Ipc>,
+    /// rayon | threads | crossbeam | workers (+ tokio-blocking | tokio-async)
+    #[arg(long, value_name = "B", value_parser = Backend::parse, default_value = "rayon")]
+    pub backend: Backend,
+    /// default | thp | hugetlb pages for the input array
+    #[arg(long, value_name = "P", value_parser = Pages::parse, default_value = "default")]
+    pub pages: Pages,
+    #[command(flatten)]
+    pub common: cli::Common,
+}
+
+pub struct Histogram;
+
+impl Benchmark for Histogram {
+    fn name(&self) -> &'static str {
+        "histogram"
+    }
+
+    fn label(&self) -> &'static str {
+        "Histogram"
+    }
+
+    fn suite(&self) -> Suite {
+        Suite::Controllability
+    }
+
+    fn description(&self) -> Description {
+        description()
+    }
+
+    fn command(&self) -> clap::Command {
+        Args::command()
+    }
+
+    fn run(&self, args: &ArgMatches, reporter: &mut Reporter) -> i32 {
+        run(&cli::from_matches(args), reporter)
+    }
+
+    fn worker(&self) -> Option<WorkerKernel> {
+        Some(worker_kernel)
+    }
+}
+
+pub fn run(args: &Args, reporter: &mut Reporter) -> i32 {
+    let Args { size: n, threads: t, grain, pad, stripes, numa_first_touch, multiproc: ipc, backend, pages, .. } = *args;
+    let affinity = &args.affinity;
+    // the generated input's distribution, none for --input
+    let generated = args.input.is_none().then(|| args.dist.unwrap_or(Dist::Uniform));
+    // a file input is recorded as dist=file, seed=0, as control_openmp does
+    let (strategy, dist) = (args.strategy.as_str(), generated.map_or("file".to_string(), Dist::name));
+    let simd = cli::or_exit(cli::simd(args.simd));
+    let ffi = cli::or_exit(cli::ffi(args.ffi));
+
+    if simd && strategy != "local" {
+        eprintln!("--simd applies to the local strategy only");
+        return 1;
+    }
+
+    if ipc.is_some() && strategy != "local" {
+        eprintln!("--multiproc applies to the local strategy only");
+        return 1;
+    }
+
+    // manual and scoped partition statically on their own threads
+    let own_threads = matches!(strategy, "manual" | "scoped");
+    // the C kernel binds through the proc_bind clause, close only
+    if ffi && !matches!(affinity, Affinity::None | Affinity::Compact) {
+        eprintln!("--ffi 1 supports --affinity none or compact; run control_openmp for {}", affinity.name());
+        return 1;
+    }
+
+    if own_threads && grain != 0 {
+        eprintln!("--grain applies to the pooled strategies only; {} splits into T static ranges", strategy);
+        return 1;
+    }
+
+    if own_threads && backend != Backend::Rayon {
+        eprintln!("--backend does not apply to the {} strategy, which spawns its own threads", strategy);
+        return 1;
+    }
+
+    if args.sweep_grain.is_some() && (grain != 0 || own_threads || ipc.is_some()) {
+        eprintln!("--sweep-grain replaces --grain and needs a pooled strategy (atomic, local or striped) without --multiproc");
+        return 1;
+    }
+
+    if stripes > BINS {
+        eprintln!("--stripes {} exceeds the {} bins", stripes, BINS);
+        return 1;
+    }
+
+    let _bench = info_span!("hist", %strategy, %dist, n, threads = t, grain, pages = pages.name()).entered();
+    let setup = Phase::Setup.span().entered();
+
+    let procs = ipc.map(|ipc| ProcessPool::new(t, ipc));
+    let pool = (procs.is_none() && !own_threads).then(|| Pool::with_affinity(backend, t, affinity));
+    let placement = affinity.placement(t);
+
+    // generate input data 
+    let seed = generated.map_or(0, |dist| args.seed.unwrap_or(dist.default_seed()));
+    // the chunks the first grain bins, or manual's and scoped's ranges
+    let first_grain = args.sweep_grain.map_or(grain, |steps| steps.lo);
+    let place = |data: &mut [u8], write: &(dyn Fn(usize, &mut [u8]) + Sync)| match &pool {
+        Some(pool) => {
+            let chunk = match (first_grain, strategy) {
+                (0, "local") => n.div_ceil(t),
+                (0, _) => pool.default_grain(n),
+                (grain, _) => grain,
+            };
+            pool.for_each_chunk_mut(data, chunk, write);
+        }
+        None => on_ranges(data, t, &placement, write),
     };
-    let pad_flag_raw: i32 = if args.len() > 6 {
-        args[6].parse().unwrap_or(0)
-    } else {
-        0
+    // shared with the manual strategy's threads
+    let data = Arc::new(match (generated, &args.input) {
+        (Some(dist), _) if numa_first_touch => dist.generate_placed(n, seed, pages, place),
+        (Some(dist), _) => dist.generate(n, seed, pages),
+        (None, Some(path)) => cli::or_exit(read_input(path, n, pages)),
+        (None, None) => unreachable!("uniform without --input"),
+    });
+    let node_pages = numa::node_pages(&data);
+
+    // worker processes, or manual's and scoped's own threads, take the place of the thread pool
+    let impl_name = match (ipc, strategy) {
+        (Some(ipc), _) => ipc.name(),
+        (None, "manual") => "std",
+        (None, "scoped") => "crossbeam",
+        (None, _) => backend.name(),
     };
-    let pad = pad_flag_raw != 0;
-    let affinity_raw: i32 = if args.len() > 7 {
-        args[7].parse().unwrap_or(0)
-    } else {
-        0
+
+    drop(setup);
+
+    let key = format!("dist={},seed={},N={}", dist, seed, n);
+    let pad_flag = if strategy == "atomic" && pad { 1 } else { 0 };
+    let stripe_count = if strategy == "striped" { stripes } else { 0 };
+    let simd_flag = if simd { 1 } else { 0 };
+
+    // the cell, for the Rust record and the C kernel's
+    let cell = |implementation: &str, grain: usize, simd_flag: u8| {
+        Record::new("hist", implementation)
+            .param("strategy", strategy)
+            .param("dist", dist.as_str())
+            .param("N", n)
+            .param("T", t)
+            .param("grain", grain)
+            .param("pad", pad_flag)
+            .param("stripes", stripe_count)
+            .param("affinity", affinity.name())
+            .param("first_touch", numa_first_touch as u8)
+            .param("simd", simd_flag)
+            .param("pages", pages.name())
+            .param("seed", seed)
     };
-    let affinity = affinity_raw != 0;
 
-    if n == 0 || t == 0 {
-        eprintln!("N and T must be positive.");
-        std::process::exit(1);
+    // one configuration per grain of --sweep-grain, on the same input and pool
+    let grains = args.sweep_grain.map_or(vec![grain], cli::Steps::values);
+    let mut all_correct = true;
+    for (i, &grain) in grains.iter().enumerate() {
+        // a failed worker pool ends the run with status 2, after the repetitions
+        let mut failure = None;
+        let (elapsed, (histogram, allocs)) = args.common.reps.repeat(1).run(|| {
+            let region = Region::start();
+            let (elapsed, histogram) = match (strategy, &procs, &pool) {
+                ("local", Some(procs), _) => hist_local_procs(procs, &data, simd).unwrap_or_else(|e| {
+                    failure = Some(e);
+                    (0.0, Vec::new())
+                }),
+                ("atomic", None, Some(pool)) => hist_atomic(pool, &data, grain, pad),
+                ("striped", None, Some(pool)) => hist_striped(pool, &data, grain, stripes),
+                ("manual", None, None) => hist_manual(&data, t, &placement),
+                ("scoped", None, None) => hist_scoped(&data, t, &placement),
+                (_, None, Some(pool)) => hist_local(pool, &data, grain, simd),
+                _ => unreachable!("--multiproc is rejected for all strategies but local"),
+            };
+            (elapsed, (histogram, region.finish()))
+        });
+        if let Some(e) = failure {
+            eprintln!("multiproc run failed: {}", e);
+            return 2;
+        }
+
+        let correct = Phase::Verify.run(|| check_correct(&histogram, n, &key));
+        all_correct &= correct;
+        // every grain bins the same input, so the first run's bins stand for all
+        if let (0, Some(path)) = (i, &args.dump) {
+            cli::or_exit(Dump::hist(n, &histogram).write(path));
+        }
+        let record = cell(impl_name, grain, simd_flag)
+            .timing("time", &elapsed, "sec")
+            .metric("correct", if correct { 1.0 } else { 0.0 }, "boolean")
+            .allocs(allocs)
+            .energy(elapsed.energy)
+            .peak_rss(elapsed.peak_rss);
+        let record = node_pages.iter().fold(record, |record, &(node, pages)| record.metric(&format!("node{}_pages", node), pages as f64, "pages"));
+        if reporter.is_text() {
+            print!("{}", record.to_csv());
+        }
+        reporter.record(&record);
+
+        if ffi {
+            let (elapsed, histogram) = args.common.reps.repeat(1).run(|| ffi::hist(&data, t, strategy, pad, stripes, grain, *affinity == Affinity::Compact));
+            let correct = Phase::Verify.run(|| check_correct(&histogram, n, &key));
+            all_correct &= correct;
+            let record = cell(OPENMP, grain, 0)
+                .timing("time", &elapsed, "sec")
+                .metric("correct", if correct { 1.0 } else { 0.0 }, "boolean")
+                .energy(elapsed.energy)
+                .peak_rss(elapsed.peak_rss);
+            if reporter.is_text() {
+                print!("{}", record.to_csv());
+            }
+            reporter.record(&record);
+        }
     }
 
-    // generate input data 
-    let data = match dist.as_str() {
-        "uniform" => gen_uniform(n),
-        "skewed" => gen_skewed(n),
-        _ => {
-            eprintln!("unknown dist: {} (use uniform|skewed)", dist);
-            std::process::exit(1);
+    if all_correct {
+        0
+    } else {
+        3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    // random bytes, the benchmark's own generators, and a single hot bin
+    fn input() -> impl Strategy<Value = Vec<u8>> {
+        prop_oneof![
+            prop::collection::vec(any::<u8>(), 1..5000),
+            (1usize..5000).prop_map(|n| gen_uniform(n, UNIFORM_SEED, Pages::Default).to_vec()),
+            (1usize..5000).prop_map(|n| gen_skewed(n, SKEWED_SEED, Pages::Default).to_vec()),
+            (1usize..5000, 0.5f64..3.0).prop_map(|(n, s)| gen_zipf(n, s, ZIPF_SEED, Pages::Default).to_vec()),
+            (1usize..5000, 0.0f64..255.0, 1.0f64..64.0).prop_map(|(n, mu, sigma)| gen_gaussian(n, mu, sigma, GAUSSIAN_SEED, Pages::Default).to_vec()),
+            (1usize..5000, any::<u8>()).prop_map(|(n, v)| vec![v; n]),
+        ]
+    }
+
+    #[test]
+    fn test_dists() {
+        assert_eq!(Dist::parse("zipf"), Ok(Dist::Zipf(1.0)));
+        assert_eq!(Dist::parse("gaussian:100:7.5").map(Dist::name), Ok("gaussian:100:7.5".to_string()));
+        for bad in ["normal", "zipf:0", "zipf:x", "gaussian:1", "gaussian:1:-2", "uniform:3"] {
+            assert!(Dist::parse(bad).is_err(), "{}", bad);
         }
-    };
 
-    let (elapsed, histogram) = match strategy.as_str() {
-        "atomic" => hist_atomic(&data, t, grain, pad, affinity),
-        "local" => hist_local(&data, t, grain, affinity),
-        _ => {
-            eprintln!("unknown strategy: {} (use atomic|local)", strategy);
-            std::process::exit(1);
+        // zipf: every bin hotter than the next, bin 0 with 1 / H(256) of the
+        // elements at s = 1
+        let zipf = local_hist_scalar(&gen_zipf(1_000_000, 1.0, ZIPF_SEED, Pages::Default));
+        assert!(zipf.windows(2).take(8).all(|pair| pair[0] > pair[1]));
+        assert!((zipf[0] as f64 / 1e6 - 0.1633).abs() < 0.005, "{}", zipf[0]);
+        // gaussian: the mean near mu, ~68% within one sigma
+        let gaussian = local_hist_scalar(&gen_gaussian(1_000_000, 100.0, 10.0, GAUSSIAN_SEED, Pages::Default));
+        let mean = gaussian.iter().enumerate().map(|(bin, &count)| bin as f64 * count as f64).sum::<f64>() / 1e6;
+        let within: u64 = gaussian[90..=110].iter().sum();
+        assert!((mean - 100.0).abs() < 0.1, "{}", mean);
+        assert!((within as f64 / 1e6 - 0.71).abs() < 0.02, "{}", within);
+    }
+
+    #[test]
+    fn test_generate_placed() {
+        // the same bytes whichever threads write which pieces
+        let pool = Pool::new(Backend::Threads, 3);
+        for dist in [Dist::Uniform, Dist::Skewed, Dist::Zipf(1.2), Dist::Gaussian(90.0, 20.0)] {
+            let expected = dist.generate(10_007, 5, Pages::Default);
+            let pooled = dist.generate_placed(10_007, 5, Pages::Default, |data, write| pool.for_each_chunk_mut(data, 1000, write));
+            let ranged = dist.generate_placed(10_007, 5, Pages::Default, |data, write| on_ranges(data, 3, &Placement::default(), write));
+            assert!(*pooled == *expected && *ranged == *expected, "{:?}", dist);
         }
-    };
+    }
 
-    let correct = check_correct(&histogram, n);
-    let pad_flag = if strategy == "atomic" && pad { 1 } else { 0 };
-    let affinity_flag = if affinity { 1 } else { 0 };
-
-    println!(
-        "hist,rayon,strategy={},dist={},N={},T={},grain={},pad={},affinity={},time,{:.6},sec",
-        strategy,
-        dist,
-        n,
-        t,
-        grain,
-        pad_flag,
-        affinity_flag,
-        elapsed
-    );
-    println!(
-        "hist,rayon,strategy={},dist={},N={},T={},grain={},pad={},affinity={},correct,{},boolean",
-        strategy,
-        dist,
-        n,
-        t,
-        grain,
-        pad_flag,
-        affinity_flag,
-        if correct { 1 } else { 0 }
-    );
+    #[test]
+    fn test_read_input() {
+        let path = std::env::temp_dir().join(format!("hist-input-{}.bin", std::process::id()));
+        let bytes = gen_skewed(1000, SKEWED_SEED, Pages::Default);
+        std::fs::write(&path, &*bytes).unwrap();
+
+        assert_eq!(&*read_input(&path, 1000, Pages::Default).unwrap(), &*bytes);
+        assert_eq!(&*read_input(&path, 10, Pages::Default).unwrap(), &bytes[..10]);
+        assert!(read_input(&path, 1001, Pages::Default).map(drop).unwrap_err().ends_with("1000 bytes, 1001 needed"));
+        std::fs::remove_file(&path).unwrap();
+        assert!(read_input(&path, 10, Pages::Default).is_err());
+    }
 
-    if !correct {
-        std::process::exit(3);
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(48))]
+
+        #[test]
+        fn prop_parallel_matches_sequential(
+            data in input(),
+            backend in prop::sample::select(Backend::ALL.to_vec()),
+            threads in 1usize..9,
+            grain in prop_oneof![Just(0usize), 1usize..600],
+            pad in any::<bool>(),
+            stripes in 1usize..=BINS,
+            simd in any::<bool>(),
+        ) {
+            let simd = simd && cfg!(feature = "simd");
+            let expected = local_hist_scalar(&data).to_vec();
+            let pool = Pool::new(backend, threads);
+
+            prop_assert_eq!(&hist_atomic(&pool, &data, grain, pad).1, &expected);
+            prop_assert_eq!(&hist_local(&pool, &data, grain, simd).1, &expected);
+            prop_assert_eq!(&hist_striped(&pool, &data, grain, stripes).1, &expected);
+            prop_assert_eq!(&hist_scoped(&data, threads, &Placement::default()).1, &expected);
+            prop_assert_eq!(&hist_manual(&Arc::new(data), threads, &Placement::default()).1, &expected);
+        }
     }
 }
@@ -0,0 +1,310 @@
+// 2D histogram benchmark for "amount of control" (Rust version)
+// N pairs (x, y) of bytes binned into a 256 x 256 grid, cell x * 256 + y.
+// The grid is 65536 counters (512 KiB), so unlike the 256-bin histogram it
+// spills out of L1 and L2: the atomic bins are spread over 8192 cache lines
+// and rarely collide, while every thread-local grid costs a 512 KiB
+// allocation, clearing and merge.
+// Strategies:
+//   1) Atomic: single shared grid of atomic cells
+//   2) Local: thread-local grids + reduction
+// Inputs: x and y are bytes 4 and 5 of value i of the SplitMix64 stream
+// --seed, so hist2d_openmp draws the same pairs:
+//   uniform    every cell equally likely
+//   diagonal   y replaced by x + (y mod 17) - 8, wrapped: 17 of the 256 cells
+//              of each row, a sparse band across the grid
+//
+// Usage:
+//   ./histogram2d [--strategy S] [--dist D] [--size N] [--threads T] [options]
+//   --strategy S       atomic | local (default)
+//   --dist D           uniform (default) | diagonal
+//   --size N           number of pairs (default 10000000)
+//   --threads T        number of threads (default 8; a count or max, cores, half, 3/4)
+//
+// Options:
+//   --grain G          chunk size per task (default 0 = auto)
+//   --seed S           input seed (default 271828182)
+//   --backend B        rayon (default) | threads | crossbeam | workers
+//                      (+ tokio-blocking | tokio-async with --features tokio)
+//   --pages P          default | thp | hugetlb pages for the input arrays
+//   --format FMT       text (default, CSV lines below) | jsonl | json | csv | markdown
+//   --upload URL       POST the result as JSON to an HTTP collector
+//   --prometheus FILE  keep FILE updated with Prometheus gauges for each result
+//   --store FILE       append the run to a SQLite database (--features store)
+//   --log FMT          pretty | json execution log on stderr (RUST_LOG filters)
+//   --reps R           timed runs, the best is reported (default 1); R > 1 adds
+//                      time_mean, time_median and time_stddev
+//   --warmup W         untimed runs before them (default 1; 0 for a cold start)
+//   --describe         print the JSON description of arguments and metrics, then exit
+//
+// Output (CSV-style, second field is the backend):
+//   hist2d,rayon,strategy=atomic,dist=uniform,N=10000000,T=8,grain=0,pages=default,seed=271828182,time,0.123456,sec
+//   hist2d,rayon,strategy=atomic,dist=uniform,N=10000000,T=8,grain=0,pages=default,seed=271828182,correct,1,boolean
+
+use crate::alloc_track::Region;
+use crate::backend::{Backend, Pool};
+use crate::cli;
+use crate::describe::{Description, Param};
+use crate::kernels::{Benchmark, Suite};
+use crate::logging::Phase;
+use crate::pages::{PageVec, Pages};
+use crate::results::{Record, Reporter};
+use crate::rng;
+use crate::threads;
+use clap::{ArgMatches, CommandFactory, Parser};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+use tracing::info_span;
+
+// defaults of --size and --threads
+const N: usize = 10_000_000; // 10^7
+const THREADS: usize = 8;
+
+/// Bins per axis.
+pub const SIDE: usize = 256;
+/// Cells of the grid.
+pub const CELLS: usize = SIDE * SIDE;
+
+pub const SEED: u64 = 271828182;
+
+// half the width of the diagonal band
+const BAND: u8 = 8;
+
+// the pair of one stream value
+fn pair(r: u64, diagonal: bool) -> (u8, u8) {
+    let (x, y) = ((r >> 32) as u8, (r >> 40) as u8);
+    if diagonal {
+        (x, x.wrapping_add(y % (2 * BAND + 1)).wrapping_sub(BAND))
+    } else {
+        (x, y)
+    }
+}
+
+/// The x and y coordinates of `n` pairs from the SplitMix64 stream `seed`;
+/// `diagonal` keeps y within 8 of x (mod 256).
+pub fn gen_pairs(n: usize, diagonal: bool, seed: u64, pages: Pages) -> (PageVec<u8>, PageVec<u8>) {
+    let mut xs = cli::or_exit(PageVec::zeroed(n, pages));
+    let mut ys = cli::or_exit(PageVec::zeroed(n, pages));
+    rng::fill(&mut xs, seed, |r| pair(r, diagonal).0);
+    rng::fill(&mut ys, seed, |r| pair(r, diagonal).1);
+    (xs, ys)
+}
+
+/// The grid of `xs` and `ys` on the calling thread, row x.
+pub fn hist2d_sequential(xs: &[u8], ys: &[u8]) -> Vec<u64> {
+    assert_eq!(xs.len(), ys.len(), "x and y lengths differ");
+    let mut grid = vec![0u64; CELLS];
+    for (&x, &y) in xs.iter().zip(ys) {
+        grid[x as usize * SIDE + y as usize] += 1;
+    }
+    grid
+}
+
+/// Strategy 1: one shared grid of atomic cells; `grain` 0 picks the
+/// backend's default chunk. Returns (seconds, cells).
+pub fn hist2d_atomic(pool: &Pool, xs: &[u8], ys: &[u8], grain: usize) -> (f64, Vec<u64>) {
+    assert_eq!(xs.len(), ys.len(), "x and y lengths differ");
+    let chunk = if grain > 0 { grain } else { pool.default_grain(xs.len()) };
+
+    let start = Instant::now();
+
+    let grid: Vec<AtomicU64> = (0..CELLS).map(|_| AtomicU64::new(0)).collect();
+    pool.for_each_chunk(xs, chunk, |start, c| {
+        for (&x, &y) in c.iter().zip(&ys[start..]) {
+            grid[x as usize * SIDE + y as usize].fetch_add(1, Ordering::Relaxed);
+        }
+    });
+    let result = grid.iter().map(|cell| cell.load(Ordering::Relaxed)).collect();
+
+    let elapsed = start.elapsed().as_secs_f64();
+    (elapsed, result)
+}
+
+/// Strategy 2: a private grid per chunk, summed at the end; `grain` 0 means
+/// one chunk per thread. Returns (seconds, cells).
+pub fn hist2d_local(pool: &Pool, xs: &[u8], ys: &[u8], grain: usize) -> (f64, Vec<u64>) {
+    assert_eq!(xs.len(), ys.len(), "x and y lengths differ");
+    // auto grain: one chunk per thread
+    let chunk = if grain > 0 { grain } else { xs.len().div_ceil(pool.threads()).max(1) };
+
+    let start = Instant::now();
+
+    let grid = pool.map_reduce(
+        xs,
+        chunk,
+        || vec![0u64; CELLS],
+        |start, c| hist2d_sequential(c, &ys[start..start + c.len()]),
+        |mut acc, local| {
+            for (a, l) in acc.iter_mut().zip(&local) {
+                *a += l;
+            }
+            acc
+        },
+    );
+
+    let elapsed = start.elapsed().as_secs_f64();
+    (elapsed, grid)
+}
+
+// answer to --describe
+pub fn description() -> Description {
+    Description::new("histogram2d", "hist2d", "256 x 256 histogram of byte pairs with a shared atomic or thread-local grid")
+        .option(Param::choice("--strategy", &["atomic", "local"], "cell update strategy").default("local"))
+        .option(Param::choice("--dist", &["uniform", "diagonal"], "input pairs: any cell, or a band of 17 cells around x = y").default("uniform"))
+        .option(Param::integer("--size", 1, "pairs").default(N))
+        .option(Param::threads("--threads", "threads").default(THREADS))
+        .option(Param::integer("--grain", 0, "chunk size per task, 0 = auto").default(0))
+        .option(Param::integer("--seed", 0, "input seed").default(SEED))
+        .backend_option()
+        .pages_option()
+        .params(&["strategy", "dist", "N", "T", "grain", "pages", "seed"])
+        .timing("time", "sec")
+        .metric("correct", "boolean")
+}
+
+/// 256 x 256 histogram of byte pairs with a shared atomic or thread-local grid
+#[derive(Debug, Clone, Parser)]
+#[command(name = "histogram2d")]
+pub struct Args {
+    /// cell update strategy
+    #[arg(long, value_parser = ["atomic", "local"], default_value = "local")]
+    pub strategy: String,
+    /// input pairs: uniform (any cell) | diagonal (a band of 17 cells around x = y)
+    #[arg(long, value_name = "D", value_parser = ["uniform", "diagonal"], default_value = "uniform")]
+    pub dist: String,
+    /// pairs
+    #[arg(long, value_name = "N", value_parser = cli::positive, default_value_t = N)]
+    pub size: usize,
+    /// threads (a count or max, cores, half, quarter, a/b)
+    #[arg(long, value_name = "T", value_parser = threads::parse_spec, default_value_t = THREADS)]
+    pub threads: usize,
+    /// chunk size per task, 0 = auto
+    #[arg(long, default_value_t = 0)]
+    pub grain: usize,
+    /// input seed
+    #[arg(long, default_value_t = SEED)]
+    pub seed: u64,
+    /// rayon | threads | crossbeam | workers (+ tokio-blocking | tokio-async)
+    #[arg(long, value_name = "B", value_parser = Backend::parse, default_value = "rayon")]
+    pub backend: Backend,
+    /// default | thp | hugetlb pages for the input arrays
+    #[arg(long, value_name = "P", value_parser = Pages::parse, default_value = "default")]
+    pub pages: Pages,
+    #[command(flatten)]
+    pub common: cli::Common,
+}
+
+pub struct Histogram2d;
+
+impl Benchmark for Histogram2d {
+    fn name(&self) -> &'static str {
+        "histogram2d"
+    }
+
+    fn label(&self) -> &'static str {
+        "2D histogram"
+    }
+
+    fn suite(&self) -> Suite {
+        Suite::Controllability
+    }
+
+    fn description(&self) -> Description {
+        description()
+    }
+
+    fn command(&self) -> clap::Command {
+        Args::command()
+    }
+
+    fn run(&self, args: &ArgMatches, reporter: &mut Reporter) -> i32 {
+        run(&cli::from_matches(args), reporter)
+    }
+}
+
+pub fn run(args: &Args, reporter: &mut Reporter) -> i32 {
+    let Args { size: n, threads: t, grain, seed, backend, pages, .. } = *args;
+    let (strategy, dist) = (args.strategy.as_str(), args.dist.as_str());
+
+    let _bench = info_span!("hist2d", %strategy, %dist, n, threads = t, grain, pages = pages.name()).entered();
+    let setup = Phase::Setup.span().entered();
+    let (xs, ys) = gen_pairs(n, dist == "diagonal", seed, pages);
+    let pool = Pool::new(backend, t);
+    drop(setup);
+
+    let (elapsed, (grid, allocs)) = args.common.reps.repeat(1).run(|| {
+        let region = Region::start();
+        let (elapsed, grid) = match strategy {
+            "atomic" => hist2d_atomic(&pool, &xs, &ys, grain),
+            _ => hist2d_local(&pool, &xs, &ys, grain),
+        };
+        (elapsed, (grid, region.finish()))
+    });
+
+    let correct = Phase::Verify.run(|| grid == hist2d_sequential(&xs, &ys));
+    let record = Record::new("hist2d", backend.name())
+        .param("strategy", strategy)
+        .param("dist", dist)
+        .param("N", n)
+        .param("T", t)
+        .param("grain", grain)
+        .param("pages", pages.name())
+        .param("seed", seed)
+        .timing("time", &elapsed, "sec")
+        .metric("correct", if correct { 1.0 } else { 0.0 }, "boolean")
+        .allocs(allocs)
+        .energy(elapsed.energy)
+        .peak_rss(elapsed.peak_rss);
+    if reporter.is_text() {
+        print!("{}", record.to_csv());
+    }
+    reporter.record(&record);
+
+    if correct {
+        0
+    } else {
+        3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_pairs() {
+        let (xs, ys) = gen_pairs(100_000, true, SEED, Pages::Default);
+        assert!(xs.iter().zip(ys.iter()).all(|(&x, &y)| {
+            let d = y.wrapping_sub(x).wrapping_add(BAND);
+            d <= 2 * BAND
+        }));
+        let grid = hist2d_sequential(&xs, &ys);
+        assert_eq!(grid.iter().filter(|&&c| c > 0).count(), SIDE * (2 * BAND as usize + 1));
+
+        // uniform: both axes cover every bin, and the cells hold ~N / 65536
+        let (xs, ys) = gen_pairs(1 << 20, false, SEED, Pages::Default);
+        let grid = hist2d_sequential(&xs, &ys);
+        assert_eq!(grid.iter().sum::<u64>(), 1 << 20);
+        assert!(grid.iter().all(|&c| (1..=40).contains(&c)), "{:?}", grid.iter().max());
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn prop_parallel_matches_sequential(
+            pairs in prop::collection::vec(any::<(u8, u8)>(), 1..3000),
+            backend in prop::sample::select(Backend::ALL.to_vec()),
+            threads in 1usize..9,
+            // a local grid per chunk is 512 KiB, so no tiny grains
+            grain in prop_oneof![Just(0usize), 64usize..600],
+        ) {
+            let (xs, ys): (Vec<u8>, Vec<u8>) = pairs.into_iter().unzip();
+            let expected = hist2d_sequential(&xs, &ys);
+            let pool = Pool::new(backend, threads);
+
+            prop_assert_eq!(&hist2d_atomic(&pool, &xs, &ys, grain).1, &expected);
+            prop_assert_eq!(&hist2d_local(&pool, &xs, &ys, grain).1, &expected);
+        }
+    }
+}
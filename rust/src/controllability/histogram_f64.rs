@@ -0,0 +1,355 @@
+// Floating-point histogram benchmark for "amount of control" (Rust version)
+// N doubles in [0, 1) binned by range lookup, the usual scientific-computing
+// histogram: the bin of a value is not the value itself, as with bytes, but
+// found from the bin edges, which costs a multiply or a binary search and a
+// range check per element.
+//   --bins B      B equal-width bins over [0, 1): bin floor(v * B)
+//   --edges LIST  user-supplied edges e0:e1:...:ek, strictly increasing: bin
+//                 i holds [e_i, e_(i+1)), found by binary search; values
+//                 outside [e0, ek) are not counted
+// Strategies:
+//   1) Atomic: single shared histogram with atomic operations
+//   2) Local: thread-local histograms + reduction
+// The values are value i of the SplitMix64 stream --seed, its top 53 bits
+// scaled to [0, 1), so fhist_openmp bins the same doubles.
+//
+// Usage:
+//   ./histogram_f64 [--strategy S] [--size N] [--threads T] [options]
+//   --strategy S       atomic | local (default)
+//   --size N           number of values (default 10000000)
+//   --threads T        number of threads (default 8; a count or max, cores, half, 3/4)
+//
+// Options:
+//   --bins B           equal-width bins over [0, 1) (default 256)
+//   --edges LIST       colon-separated bin edges instead, e.g. 0:0.5:0.9:0.99:1
+//   --grain G          chunk size per task (default 0 = auto)
+//   --seed S           input seed (default 161803398)
+//   --backend B        rayon (default) | threads | crossbeam | workers
+//                      (+ tokio-blocking | tokio-async with --features tokio)
+//   --pages P          default | thp | hugetlb pages for the input array
+//   --format FMT       text (default, CSV lines below) | jsonl | json | csv | markdown
+//   --upload URL       POST the result as JSON to an HTTP collector
+//   --prometheus FILE  keep FILE updated with Prometheus gauges for each result
+//   --store FILE       append the run to a SQLite database (--features store)
+//   --log FMT          pretty | json execution log on stderr (RUST_LOG filters)
+//   --reps R           timed runs, the best is reported (default 1); R > 1 adds
+//                      time_mean, time_median and time_stddev
+//   --warmup W         untimed runs before them (default 1; 0 for a cold start)
+//   --describe         print the JSON description of arguments and metrics, then exit
+//
+// Output (CSV-style, second field is the backend; edges is uniform or the list):
+//   fhist,rayon,strategy=atomic,edges=uniform,bins=256,N=10000000,T=8,grain=0,pages=default,seed=161803398,time,0.123456,sec
+//   fhist,rayon,strategy=atomic,edges=uniform,bins=256,N=10000000,T=8,grain=0,pages=default,seed=161803398,correct,1,boolean
+
+use crate::alloc_track::Region;
+use crate::backend::{Backend, Pool};
+use crate::cli;
+use crate::describe::{Description, Param};
+use crate::kernels::{Benchmark, Suite};
+use crate::logging::Phase;
+use crate::pages::{PageVec, Pages};
+use crate::results::{Record, Reporter};
+use crate::rng;
+use crate::threads;
+use clap::{ArgMatches, CommandFactory, Parser};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+use tracing::info_span;
+
+// defaults of --size and --threads
+const N: usize = 10_000_000; // 10^7
+const THREADS: usize = 8;
+
+pub const BINS: usize = 256;
+pub const SEED: u64 = 161803398;
+
+/// How values map to bins.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Bins {
+    /// this many equal-width bins over [0, 1)
+    Uniform(usize),
+    /// bin i is [edges[i], edges[i + 1])
+    Edges(Vec<f64>),
+}
+
+impl Bins {
+    /// Parses --edges: at least two finite, strictly increasing edges
+    /// separated by colons.
+    pub fn parse_edges(s: &str) -> Result<Self, String> {
+        let edges = s
+            .split(':')
+            .map(|e| e.trim().parse::<f64>().ok().filter(|e| e.is_finite()).ok_or_else(|| format!("bad edge {:?} in {}", e, s)))
+            .collect::<Result<Vec<f64>, String>>()?;
+        if edges.len() < 2 {
+            return Err(format!("at least two edges needed, got {}", s));
+        }
+        if edges.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(format!("edges must increase strictly, got {}", s));
+        }
+        Ok(Bins::Edges(edges))
+    }
+
+    pub fn count(&self) -> usize {
+        match self {
+            Bins::Uniform(count) => *count,
+            Bins::Edges(edges) => edges.len() - 1,
+        }
+    }
+
+    /// The bin of `v`, or None when it lies outside the edges.
+    #[inline]
+    pub fn find(&self, v: f64) -> Option<usize> {
+        match self {
+            Bins::Uniform(count) => (0.0..1.0).contains(&v).then(|| ((v * *count as f64) as usize).min(count - 1)),
+            Bins::Edges(edges) => (edges[0]..edges[edges.len() - 1]).contains(&v).then(|| edges.partition_point(|&e| e <= v) - 1),
+        }
+    }
+}
+
+/// `n` doubles in [0, 1): the top 53 bits of value i of the SplitMix64
+/// stream `seed`, scaled.
+pub fn gen_values(n: usize, seed: u64, pages: Pages) -> PageVec<f64> {
+    let mut values = cli::or_exit(PageVec::zeroed(n, pages));
+    rng::fill(&mut values, seed, |r| (r >> 11) as f64 / (1u64 << 53) as f64);
+    values
+}
+
+/// The histogram of `values` on the calling thread.
+pub fn fhist_sequential(values: &[f64], bins: &Bins) -> Vec<u64> {
+    let mut hist = vec![0u64; bins.count()];
+    for &v in values {
+        if let Some(b) = bins.find(v) {
+            hist[b] += 1;
+        }
+    }
+    hist
+}
+
+/// Strategy 1: one shared histogram of atomic bins; `grain` 0 picks the
+/// backend's default chunk. Returns (seconds, bins).
+pub fn fhist_atomic(pool: &Pool, values: &[f64], bins: &Bins, grain: usize) -> (f64, Vec<u64>) {
+    let chunk = if grain > 0 { grain } else { pool.default_grain(values.len()) };
+
+    let start = Instant::now();
+
+    let hist: Vec<AtomicU64> = (0..bins.count()).map(|_| AtomicU64::new(0)).collect();
+    pool.for_each_chunk(values, chunk, |_, c| {
+        for &v in c {
+            if let Some(b) = bins.find(v) {
+                hist[b].fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    });
+    let result = hist.iter().map(|bin| bin.load(Ordering::Relaxed)).collect();
+
+    let elapsed = start.elapsed().as_secs_f64();
+    (elapsed, result)
+}
+
+/// Strategy 2: a private histogram per chunk, summed at the end; `grain` 0
+/// means one chunk per thread. Returns (seconds, bins).
+pub fn fhist_local(pool: &Pool, values: &[f64], bins: &Bins, grain: usize) -> (f64, Vec<u64>) {
+    // auto grain: one chunk per thread
+    let chunk = if grain > 0 { grain } else { values.len().div_ceil(pool.threads()).max(1) };
+
+    let start = Instant::now();
+
+    let hist = pool.map_reduce(
+        values,
+        chunk,
+        || vec![0u64; bins.count()],
+        |_, c| fhist_sequential(c, bins),
+        |mut acc, local| {
+            for (a, l) in acc.iter_mut().zip(&local) {
+                *a += l;
+            }
+            acc
+        },
+    );
+
+    let elapsed = start.elapsed().as_secs_f64();
+    (elapsed, hist)
+}
+
+// answer to --describe
+pub fn description() -> Description {
+    Description::new("histogram_f64", "fhist", "histogram of doubles in [0, 1) by range lookup over uniform or user-supplied bin edges")
+        .option(Param::choice("--strategy", &["atomic", "local"], "bin update strategy").default("local"))
+        .option(Param::integer("--size", 1, "values").default(N))
+        .option(Param::threads("--threads", "threads").default(THREADS))
+        .option(Param::integer("--bins", 1, "equal-width bins over [0, 1)").default(BINS))
+        .option(Param::text("--edges", "colon-separated, strictly increasing bin edges instead of --bins").optional())
+        .option(Param::integer("--grain", 0, "chunk size per task, 0 = auto").default(0))
+        .option(Param::integer("--seed", 0, "input seed").default(SEED))
+        .backend_option()
+        .pages_option()
+        .params(&["strategy", "edges", "bins", "N", "T", "grain", "pages", "seed"])
+        .timing("time", "sec")
+        .metric("correct", "boolean")
+}
+
+/// Histogram of doubles in [0, 1) by range lookup over uniform or user-supplied bin edges
+#[derive(Debug, Clone, Parser)]
+#[command(name = "histogram_f64")]
+pub struct Args {
+    /// bin update strategy
+    #[arg(long, value_parser = ["atomic", "local"], default_value = "local")]
+    pub strategy: String,
+    /// values
+    #[arg(long, value_name = "N", value_parser = cli::positive, default_value_t = N)]
+    pub size: usize,
+    /// threads (a count or max, cores, half, quarter, a/b)
+    #[arg(long, value_name = "T", value_parser = threads::parse_spec, default_value_t = THREADS)]
+    pub threads: usize,
+    /// equal-width bins over [0, 1)
+    #[arg(long, value_name = "B", value_parser = cli::positive, default_value_t = BINS, conflicts_with = "edges")]
+    pub bins: usize,
+    /// colon-separated, strictly increasing bin edges instead of --bins, e.g. 0:0.5:0.9:0.99:1
+    #[arg(long, value_name = "LIST")]
+    pub edges: Option<String>,
+    /// chunk size per task, 0 = auto
+    #[arg(long, default_value_t = 0)]
+    pub grain: usize,
+    /// input seed
+    #[arg(long, default_value_t = SEED)]
+    pub seed: u64,
+    /// rayon | threads | crossbeam | workers (+ tokio-blocking | tokio-async)
+    #[arg(long, value_name = "B", value_parser = Backend::parse, default_value = "rayon")]
+    pub backend: Backend,
+    /// default | thp | hugetlb pages for the input array
+    #[arg(long, value_name = "P", value_parser = Pages::parse, default_value = "default")]
+    pub pages: Pages,
+    #[command(flatten)]
+    pub common: cli::Common,
+}
+
+pub struct HistogramF64;
+
+impl Benchmark for HistogramF64 {
+    fn name(&self) -> &'static str {
+        "histogram_f64"
+    }
+
+    fn label(&self) -> &'static str {
+        "Floating-point histogram"
+    }
+
+    fn suite(&self) -> Suite {
+        Suite::Controllability
+    }
+
+    fn description(&self) -> Description {
+        description()
+    }
+
+    fn command(&self) -> clap::Command {
+        Args::command()
+    }
+
+    fn run(&self, args: &ArgMatches, reporter: &mut Reporter) -> i32 {
+        run(&cli::from_matches(args), reporter)
+    }
+}
+
+pub fn run(args: &Args, reporter: &mut Reporter) -> i32 {
+    let Args { size: n, threads: t, grain, seed, backend, pages, .. } = *args;
+    let strategy = args.strategy.as_str();
+    let bins = match &args.edges {
+        Some(edges) => cli::or_exit(Bins::parse_edges(edges)),
+        None => Bins::Uniform(args.bins),
+    };
+    // the edges go into the record as given, for fhist_openmp to take the same
+    let edges = args.edges.as_deref().unwrap_or("uniform");
+
+    let _bench = info_span!("fhist", %strategy, %edges, bins = bins.count(), n, threads = t, grain, pages = pages.name()).entered();
+    let setup = Phase::Setup.span().entered();
+    let values = gen_values(n, seed, pages);
+    let pool = Pool::new(backend, t);
+    drop(setup);
+
+    let (elapsed, (hist, allocs)) = args.common.reps.repeat(1).run(|| {
+        let region = Region::start();
+        let (elapsed, hist) = match strategy {
+            "atomic" => fhist_atomic(&pool, &values, &bins, grain),
+            _ => fhist_local(&pool, &values, &bins, grain),
+        };
+        (elapsed, (hist, region.finish()))
+    });
+
+    let correct = Phase::Verify.run(|| hist == fhist_sequential(&values, &bins));
+    let record = Record::new("fhist", backend.name())
+        .param("strategy", strategy)
+        .param("edges", edges)
+        .param("bins", bins.count())
+        .param("N", n)
+        .param("T", t)
+        .param("grain", grain)
+        .param("pages", pages.name())
+        .param("seed", seed)
+        .timing("time", &elapsed, "sec")
+        .metric("correct", if correct { 1.0 } else { 0.0 }, "boolean")
+        .allocs(allocs)
+        .energy(elapsed.energy)
+        .peak_rss(elapsed.peak_rss);
+    if reporter.is_text() {
+        print!("{}", record.to_csv());
+    }
+    reporter.record(&record);
+
+    if correct {
+        0
+    } else {
+        3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_bins() {
+        let uniform = Bins::Uniform(4);
+        assert_eq!([0.0, 0.2499, 0.25, 0.99].map(|v| uniform.find(v)), [Some(0), Some(0), Some(1), Some(3)]);
+        assert_eq!([-0.1, 1.0].map(|v| uniform.find(v)), [None, None]);
+
+        let edges = Bins::parse_edges("0.1:0.5:0.9:0.99").unwrap();
+        assert_eq!(edges.count(), 3);
+        assert_eq!([0.1, 0.4999, 0.5, 0.95].map(|v| edges.find(v)), [Some(0), Some(0), Some(1), Some(2)]);
+        assert_eq!([0.05, 0.99, 0.995].map(|v| edges.find(v)), [None, None, None]);
+        for bad in ["0.5", "0:0.5:0.5", "1:0", "0:x:1", "0:inf", ""] {
+            assert!(Bins::parse_edges(bad).is_err(), "{}", bad);
+        }
+
+        // the values fill equal bins evenly
+        let hist = fhist_sequential(&gen_values(1 << 16, SEED, Pages::Default), &Bins::Uniform(16));
+        assert!(hist.iter().all(|&count| (3800..4400).contains(&count)), "{:?}", hist);
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(48))]
+
+        #[test]
+        fn prop_parallel_matches_sequential(
+            values in prop::collection::vec(0.0f64..1.0, 1..5000),
+            edges in prop::collection::vec(-0.2f64..1.2, 2..40),
+            uniform in 1usize..2000,
+            backend in prop::sample::select(Backend::ALL.to_vec()),
+            threads in 1usize..9,
+            grain in prop_oneof![Just(0usize), 1usize..600],
+        ) {
+            let mut edges = edges;
+            edges.sort_by(f64::total_cmp);
+            edges.dedup();
+            let pool = Pool::new(backend, threads);
+            let mut all = vec![Bins::Uniform(uniform)];
+            all.extend((edges.len() > 1).then_some(Bins::Edges(edges)));
+            for bins in &all {
+                let expected = fhist_sequential(&values, bins);
+                prop_assert_eq!(&fhist_atomic(&pool, &values, bins, grain).1, &expected);
+                prop_assert_eq!(&fhist_local(&pool, &values, bins, grain).1, &expected);
+            }
+        }
+    }
+}
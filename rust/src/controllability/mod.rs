@@ -0,0 +1,6 @@
+// Kernels hosted in the library (see kernels.rs)
+
+pub mod false_sharing;
+pub mod histogram;
+pub mod histogram2d;
+pub mod histogram_f64;
@@ -0,0 +1,292 @@
+// Machine-readable binary descriptions (--describe)
+// Every benchmark binary answers `--describe` with one JSON object listing
+// its options (type, valid range or choices, default), the params
+// identifying a result cell and the metrics it reports,
+// then exits without running anything. Scripts and the runner can check a
+// configuration against it before starting a long sweep. Choices that depend
+// on the build or machine (backends, the simd and alloc-track features,
+// readable RAPL and /proc counters) describe this one; `logical_cpus` /
+// `physical_cores` resolve thread specifiers.
+
+use crate::backend::Backend;
+use crate::energy;
+use crate::rss;
+use crate::threads;
+use serde::Serialize;
+use std::io::Write;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Description {
+    pub binary: &'static str,
+    /// `kernel` field of the records
+    pub kernel: &'static str,
+    pub summary: &'static str,
+    pub options: Vec<Param>,
+    /// record params, in CSV column order
+    pub params: Vec<&'static str>,
+    pub metrics: Vec<MetricSpec>,
+    pub logical_cpus: usize,
+    pub physical_cores: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Param {
+    pub name: &'static str,
+    #[serde(flatten)]
+    pub kind: Kind,
+    pub required: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    pub help: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Kind {
+    /// an integer >= min
+    Integer { min: u64 },
+    Choice { choices: Vec<&'static str> },
+    /// 0 | 1
+    Switch,
+    /// a count >= 1 or max | cores | half | quarter | a/b
+    Threads,
+    /// comma-separated Threads values
+    ThreadList,
+    /// comma-separated integers >= min
+    IntegerList { min: u64 },
+    /// an option without value
+    Flag,
+    /// free-form text (URLs, paths, parameterized choices)
+    Text,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricSpec {
+    pub name: String,
+    pub unit: &'static str,
+}
+
+impl Param {
+    fn new(name: &'static str, kind: Kind, help: &'static str) -> Self {
+        Param { name, kind, required: true, default: None, help }
+    }
+
+    pub fn integer(name: &'static str, min: u64, help: &'static str) -> Self {
+        Param::new(name, Kind::Integer { min }, help)
+    }
+
+    pub fn integer_list(name: &'static str, min: u64, help: &'static str) -> Self {
+        Param::new(name, Kind::IntegerList { min }, help)
+    }
+
+    pub fn choice(name: &'static str, choices: &[&'static str], help: &'static str) -> Self {
+        Param::new(name, Kind::Choice { choices: choices.to_vec() }, help)
+    }
+
+    pub fn text(name: &'static str, help: &'static str) -> Self {
+        Param::new(name, Kind::Text, help)
+    }
+
+    pub fn switch(name: &'static str, help: &'static str) -> Self {
+        Param::new(name, Kind::Switch, help)
+    }
+
+    pub fn threads(name: &'static str, help: &'static str) -> Self {
+        Param::new(name, Kind::Threads, help)
+    }
+
+    /// An option without value.
+    pub fn flag(name: &'static str, help: &'static str) -> Self {
+        Param::new(name, Kind::Flag, help).optional()
+    }
+
+    /// Optional, with `default` when absent.
+    pub fn default(mut self, default: impl ToString) -> Self {
+        self.required = false;
+        self.default = Some(default.to_string());
+        self
+    }
+
+    /// Optional without a default.
+    pub fn optional(mut self) -> Self {
+        self.required = false;
+        self
+    }
+}
+
+impl Description {
+    /// Starts with the options every binary takes: the output options
+    /// (--format, --upload, --prometheus, --store, --log) and --reps /
+    /// --warmup / --outliers.
+    pub fn new(binary: &'static str, kernel: &'static str, summary: &'static str) -> Self {
+        Description {
+            binary,
+            kernel,
+            summary,
+            options: vec![
+                Param::choice("--format", &["text", "jsonl", "json", "csv", "markdown"], "output format").default("text"),
+                Param::new("--upload", Kind::Text, "POST each result as JSON to this http:// URL").optional(),
+                Param::new("--prometheus", Kind::Text, "keep this file updated with Prometheus gauges").optional(),
+                Param::new("--store", Kind::Text, "append the run and its results to this SQLite database").optional(),
+                Param::choice("--log", &["pretty", "json"], "execution log on stderr").optional(),
+                Param::integer("--reps", 1, "timed runs per cell, the best is reported").default(1),
+                Param::integer("--warmup", 0, "untimed full runs before the timed ones; 0 measures the cold start").default(1),
+                Param::choice("--outliers", &["none", "iqr", "mad"], "outlier filter for the statistics of the timed runs").default("iqr"),
+            ],
+            params: Vec::new(),
+            metrics: Vec::new(),
+            logical_cpus: threads::logical(),
+            physical_cores: threads::physical(),
+        }
+    }
+
+    pub fn option(mut self, param: Param) -> Self {
+        self.options.push(param);
+        self
+    }
+
+    /// --backend with the backends compiled into this build.
+    pub fn backend_option(self) -> Self {
+        let backends = Backend::ALL.map(Backend::name).to_vec();
+        self.option(Param::new("--backend", Kind::Choice { choices: backends }, "parallel backend").default("rayon"))
+    }
+
+    /// --simd, listed only when built with the "simd" feature.
+    pub fn simd_option(self, help: &'static str) -> Self {
+        if cfg!(feature = "simd") {
+            self.option(Param::flag("--simd", help))
+        } else {
+            self
+        }
+    }
+
+    /// --ffi, listed only when built with the "ffi" feature.
+    pub fn ffi_option(self) -> Self {
+        if cfg!(feature = "ffi") {
+            self.option(Param::switch("--ffi", "also time the linked C/OpenMP kernel on the same input").default(0))
+        } else {
+            self
+        }
+    }
+
+    /// --blas, listed only when built with the "blas" feature.
+    pub fn blas_option(self, help: &'static str) -> Self {
+        if cfg!(feature = "blas") {
+            self.option(Param::switch("--blas", help).default(0))
+        } else {
+            self
+        }
+    }
+
+    pub fn dump_option(self, help: &'static str) -> Self {
+        self.option(Param::new("--dump", Kind::Text, help).optional())
+    }
+
+    pub fn multiproc_option(self, help: &'static str) -> Self {
+        self.option(Param::choice("--multiproc", &["shm", "pipe"], help).optional())
+    }
+
+    pub fn pages_option(self) -> Self {
+        self.option(Param::choice("--pages", &["default", "thp", "hugetlb"], "page size of the large buffers").default("default"))
+    }
+
+    pub fn threads_option(self, default: &str, help: &'static str) -> Self {
+        self.option(Param::new("--threads", Kind::ThreadList, help).default(default))
+    }
+
+    pub fn params(mut self, params: &[&'static str]) -> Self {
+        self.params.extend_from_slice(params);
+        self
+    }
+
+    pub fn metric(mut self, name: impl ToString, unit: &'static str) -> Self {
+        self.metrics.push(MetricSpec { name: name.to_string(), unit });
+        self
+    }
+
+    /// A timing (`Record::timing`): the best run, then the statistics and
+    /// run counts reported with --reps > 1.
+    pub fn timing(self, name: &str, unit: &'static str) -> Self {
+        let timing = ["", "_mean", "_median", "_stddev", "_ci95"].iter().fold(self, |d, suffix| d.metric(format!("{}{}", name, suffix), unit));
+        timing.metric(format!("{}_runs", name), "runs").metric(format!("{}_kept", name), "runs")
+    }
+
+    /// Sets the default of --reps for binaries that repeat by default.
+    pub fn reps_default(mut self, reps: usize) -> Self {
+        if let Some(option) = self.options.iter_mut().find(|o| o.name == "--reps") {
+            option.default = Some(reps.to_string());
+        }
+        self
+    }
+
+    // Record::allocs adds these in alloc-track builds
+    fn with_alloc_metrics(self) -> Self {
+        if cfg!(feature = "alloc-track") {
+            self.metric("allocs", "count").metric("alloc_bytes", "bytes").metric("peak_heap", "bytes")
+        } else {
+            self
+        }
+    }
+
+    // Record::energy adds these where RAPL can be read
+    fn with_energy_metrics(self) -> Self {
+        if energy::available() {
+            self.metric("energy", "J").metric("power", "W")
+        } else {
+            self
+        }
+    }
+
+    // Record::peak_rss adds this where /proc reports it
+    fn with_rss_metrics(self) -> Self {
+        if rss::available() {
+            self.metric("peak_rss", "bytes")
+        } else {
+            self
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("description serialization cannot fail")
+    }
+}
+
+/// Prints the description and exits when `--describe` is among `args`;
+/// call it before clap, which would first insist on the required options.
+pub fn exit_if_requested(args: &[String], describe: impl FnOnce() -> Description) {
+    if args.iter().skip(1).any(|a| a == "--describe") {
+        // ignore a closed pipe, e.g. `--describe | head`
+        let _ = writeln!(std::io::stdout(), "{}", describe().with_alloc_metrics().with_energy_metrics().with_rss_metrics().to_json());
+        std::process::exit(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_description_json() {
+        let description = Description::new("bin", "kern", "a kernel")
+            .option(Param::integer("--size", 1, "elements"))
+            .option(Param::threads("--threads", "threads").default(8))
+            .backend_option()
+            .reps_default(5)
+            .params(&["N", "T"])
+            .timing("time", "sec");
+        let json: serde_json::Value = serde_json::from_str(&description.to_json()).unwrap();
+
+        assert_eq!(json["options"][8]["name"], "--size");
+        assert_eq!(json["options"][8]["type"], "integer");
+        assert_eq!(json["options"][8]["min"], 1);
+        assert_eq!(json["options"][8]["required"], true);
+        assert_eq!(json["options"][9]["default"], "8");
+        assert_eq!(json["options"][10]["name"], "--backend");
+        assert_eq!(json["options"][10]["choices"][0], "rayon");
+        assert_eq!(json["options"][5]["name"], "--reps");
+        assert_eq!(json["options"][5]["default"], "5");
+        assert_eq!(json["metrics"][0]["unit"], "sec");
+        assert_eq!(json["metrics"][3]["name"], "time_stddev");
+        assert_eq!(json["metrics"][6]["unit"], "runs");
+    }
+}
@@ -0,0 +1,177 @@
+// Canonical result dumps (--dump FILE; run_all_benchmarks verify A B)
+// histogram, matrix_multiply and prefix_sum write their output as a small
+// binary file that the C programs write too (as their last argument), so the
+// two codebases can be checked against each other on the same cell, not
+// only each against its own reference. All fields are little-endian:
+//   0   magic    "BENCHDMP"
+//   8   version  u32 (1)
+//   12  kernel   u32: 1 hist, 2 matmul, 3 prefix_sum
+//   16  size     u64: N elements, or the matrix order n
+//   24  count    u64: values that follow
+//   32  values   count x u64
+// hist holds the 256 bins; matmul the FNV-1a checksum (golden::checksum) of
+// C's entries as f64 bits, row after row; prefix_sum that of the inclusive
+// sums, which the C program derives from its exclusive scan plus the input.
+// The inputs are the programs' defaults on both sides (seeded histogram
+// data, A = 1 and B = 2, N ones), so matching dumps mean matching results.
+
+use crate::golden;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+const MAGIC: &[u8; 8] = b"BENCHDMP";
+const VERSION: u32 = 1;
+const HEADER: usize = 32;
+
+/// The kernel a dump comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kernel {
+    Hist,
+    Matmul,
+    PrefixSum,
+}
+
+impl Kernel {
+    fn id(self) -> u32 {
+        match self {
+            Kernel::Hist => 1,
+            Kernel::Matmul => 2,
+            Kernel::PrefixSum => 3,
+        }
+    }
+
+    fn from_id(id: u32) -> Option<Self> {
+        [Kernel::Hist, Kernel::Matmul, Kernel::PrefixSum].into_iter().find(|k| k.id() == id)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Kernel::Hist => "hist",
+            Kernel::Matmul => "matmul",
+            Kernel::PrefixSum => "prefix_sum",
+        }
+    }
+}
+
+/// One kernel output in the canonical form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dump {
+    pub kernel: Kernel,
+    pub size: u64,
+    pub values: Vec<u64>,
+}
+
+impl fmt::Display for Dump {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let size = if self.kernel == Kernel::Matmul { "n" } else { "N" };
+        write!(f, "{} {}={}", self.kernel.name(), size, self.size)
+    }
+}
+
+impl Dump {
+    /// The bins of a histogram of `n` elements.
+    pub fn hist(n: usize, bins: &[u64]) -> Self {
+        Dump { kernel: Kernel::Hist, size: n as u64, values: bins.to_vec() }
+    }
+
+    /// The n x n product `c`, row-major.
+    pub fn matmul(n: usize, c: &[f64]) -> Self {
+        Dump { kernel: Kernel::Matmul, size: n as u64, values: vec![golden::checksum(c.iter().map(|v| v.to_bits()))] }
+    }
+
+    /// Inclusive prefix sums.
+    pub fn prefix_sum(sums: &[u64]) -> Self {
+        Dump { kernel: Kernel::PrefixSum, size: sums.len() as u64, values: vec![golden::checksum(sums.iter().copied())] }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER + 8 * self.values.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.kernel.id().to_le_bytes());
+        bytes.extend_from_slice(&self.size.to_le_bytes());
+        bytes.extend_from_slice(&(self.values.len() as u64).to_le_bytes());
+        for value in &self.values {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < HEADER || &bytes[..8] != MAGIC {
+            return Err("not a result dump".to_string());
+        }
+        let u32_at = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().expect("4 bytes"));
+        let u64_at = |at: usize| u64::from_le_bytes(bytes[at..at + 8].try_into().expect("8 bytes"));
+        if u32_at(8) != VERSION {
+            return Err(format!("dump version {} (this build reads {})", u32_at(8), VERSION));
+        }
+        let kernel = Kernel::from_id(u32_at(12)).ok_or_else(|| format!("unknown kernel id {}", u32_at(12)))?;
+        let count = u64_at(24);
+        if (bytes.len() - HEADER) as u64 != count * 8 {
+            return Err(format!("{} values announced, {} bytes follow", count, bytes.len() - HEADER));
+        }
+        let values = bytes[HEADER..].chunks_exact(8).map(|b| u64::from_le_bytes(b.try_into().expect("8 bytes"))).collect();
+        Ok(Dump { kernel, size: u64_at(16), values })
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        fs::write(path, self.to_bytes()).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let bytes = fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Dump::from_bytes(&bytes).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+/// Checks that `a` and `b` hold the same output; the error names the first
+/// difference.
+pub fn verify(a: &Dump, b: &Dump) -> Result<(), String> {
+    if (a.kernel, a.size) != (b.kernel, b.size) {
+        return Err(format!("different cells: {} vs {}", a, b));
+    }
+    if a.values.len() != b.values.len() {
+        return Err(format!("{}: {} values vs {}", a, a.values.len(), b.values.len()));
+    }
+    match a.values.iter().zip(&b.values).position(|(x, y)| x != y) {
+        None => Ok(()),
+        Some(i) if a.kernel == Kernel::Hist => {
+            let differing = a.values.iter().zip(&b.values).filter(|(x, y)| x != y).count();
+            Err(format!("{}: {} bin(s) differ, first bin {}: {} vs {}", a, differing, i, a.values[i], b.values[i]))
+        }
+        Some(i) => Err(format!("{}: checksum {:#018x} vs {:#018x}", a, a.values[i], b.values[i])),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let bins: Vec<u64> = (0..256).collect();
+        let dump = Dump::hist(32640, &bins);
+        let bytes = dump.to_bytes();
+        assert_eq!((&bytes[..8], bytes.len()), (&b"BENCHDMP"[..], 32 + 256 * 8));
+        assert_eq!(Dump::from_bytes(&bytes), Ok(dump.clone()));
+        assert!(Dump::from_bytes(&bytes[..40]).unwrap_err().contains("256 values announced"));
+        assert!(Dump::from_bytes(b"not a dump at all, definitely not").is_err());
+
+        assert_eq!(verify(&dump, &dump), Ok(()));
+        let mut other = dump.clone();
+        other.values[3] += 1;
+        other.values[7] -= 1;
+        assert_eq!(verify(&dump, &other), Err("hist N=32640: 2 bin(s) differ, first bin 3: 3 vs 4".to_string()));
+        assert!(verify(&dump, &Dump::prefix_sum(&[1, 2])).unwrap_err().starts_with("different cells"));
+    }
+
+    #[test]
+    fn test_checksums() {
+        // the registered digest of C = A(1) * B(2) at n = 256
+        assert_eq!(Dump::matmul(256, &vec![512.0; 256 * 256]).values, [0xc7fd6d3fa0c22325]);
+        let ones: Vec<u64> = (1..=10_000_000).collect();
+        assert_eq!(Dump::prefix_sum(&ones).values, [0x77a6b5d53cb4bc9b]);
+    }
+}
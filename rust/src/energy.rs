@@ -0,0 +1,143 @@
+// Package energy from RAPL (/sys/class/powercap/intel-rapl)
+// The kernel's powercap driver exposes one cumulative energy counter per CPU
+// package (intel-rapl:N, microjoules; AMD Zen parts use the same interface).
+// A Meter reads every package's counter before and after the timed runs of a
+// cell; records then carry
+//   energy   joules per timed run, all packages together
+//   power    average watts over the timed runs
+// The counters cover the whole package, so anything else running on the
+// machine is counted too. Counters wrap at max_energy_range_uj, which is
+// corrected for one wrap per measurement. Since Linux 5.10 energy_uj is
+// readable by root only; without RAPL or the permission records keep their
+// usual metrics.
+
+use crate::results::Record;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Instant;
+
+const POWERCAP: &str = "/sys/class/powercap";
+
+/// Energy of one cell's timed runs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Energy {
+    /// joules per run
+    pub joules: f64,
+    /// average power while the runs were timed
+    pub watts: f64,
+}
+
+// one package counter
+#[derive(Debug)]
+struct Domain {
+    energy: PathBuf,
+    max_range: u64,
+}
+
+fn read_u64(path: &Path) -> Option<u64> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+// the readable package domains under `root` (intel-rapl:N, not the
+// intel-rapl:N:M subzones, which the package already counts)
+fn domains_in(root: &Path) -> Vec<Domain> {
+    let Ok(entries) = fs::read_dir(root) else { return Vec::new() };
+    let mut zones: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.file_name().and_then(|n| n.to_str()).and_then(|n| n.strip_prefix("intel-rapl:")).is_some_and(|id| !id.contains(':')))
+        .collect();
+    zones.sort();
+    zones
+        .into_iter()
+        .filter_map(|zone| {
+            let energy = zone.join("energy_uj");
+            read_u64(&energy)?;
+            Some(Domain { max_range: read_u64(&zone.join("max_energy_range_uj")).unwrap_or(u64::MAX), energy })
+        })
+        .collect()
+}
+
+fn domains() -> &'static [Domain] {
+    static DOMAINS: OnceLock<Vec<Domain>> = OnceLock::new();
+    DOMAINS.get_or_init(|| domains_in(Path::new(POWERCAP)))
+}
+
+/// True when RAPL counters can be read on this machine.
+pub fn available() -> bool {
+    !domains().is_empty()
+}
+
+/// Energy used between `start` and `finish`.
+pub struct Meter {
+    start: Option<(Vec<u64>, Instant)>,
+}
+
+impl Meter {
+    pub fn start() -> Self {
+        Self::start_on(domains())
+    }
+
+    fn start_on(domains: &[Domain]) -> Self {
+        let counters: Option<Vec<u64>> = domains.iter().map(|d| read_u64(&d.energy)).collect();
+        Meter { start: counters.filter(|c| !c.is_empty()).map(|c| (c, Instant::now())) }
+    }
+
+    /// The energy since `start`, shared out over `runs`; None without
+    /// readable counters.
+    pub fn finish(self, runs: usize) -> Option<Energy> {
+        self.finish_on(domains(), runs)
+    }
+
+    fn finish_on(self, domains: &[Domain], runs: usize) -> Option<Energy> {
+        let (before, started) = self.start?;
+        let seconds = started.elapsed().as_secs_f64();
+        let mut microjoules = 0u64;
+        for (domain, before) in domains.iter().zip(before) {
+            let after = read_u64(&domain.energy)?;
+            microjoules += if after >= before { after - before } else { domain.max_range - before + after };
+        }
+        let joules = microjoules as f64 * 1e-6;
+        Some(Energy { joules: joules / runs.max(1) as f64, watts: if seconds > 0.0 { joules / seconds } else { 0.0 } })
+    }
+}
+
+impl Record {
+    /// Adds the energy metrics, if measured.
+    pub fn energy(self, energy: Option<Energy>) -> Self {
+        match energy {
+            Some(e) => self.metric("energy", e.joules, "J").metric("power", e.watts, "W"),
+            None => self,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_meter() {
+        let root = std::env::temp_dir().join(format!("powercap-{}", std::process::id()));
+        for (zone, energy) in [("intel-rapl:0", "1000"), ("intel-rapl:0:0", "5"), ("intel-rapl:1", "999000000"), ("dtpm", "7")] {
+            fs::create_dir_all(root.join(zone)).unwrap();
+            fs::write(root.join(zone).join("energy_uj"), energy).unwrap();
+            fs::write(root.join(zone).join("max_energy_range_uj"), "1000000000").unwrap();
+        }
+        let domains = domains_in(&root);
+        assert_eq!(domains.len(), 2);
+
+        let meter = Meter::start_on(&domains);
+        // package 0 used 3 J, package 1 wrapped after using 2 J
+        fs::write(root.join("intel-rapl:0/energy_uj"), "3001000").unwrap();
+        fs::write(root.join("intel-rapl:1/energy_uj"), "1000000").unwrap();
+        let energy = meter.finish_on(&domains, 2).unwrap();
+        assert!((energy.joules - 2.5).abs() < 1e-9, "{:?}", energy);
+        assert!(energy.watts > 0.0);
+
+        let record = Record::new("k", "rust").energy(Some(energy)).energy(None);
+        assert_eq!(record.metrics.iter().map(|m| m.unit.as_str()).collect::<Vec<_>>(), ["J", "W"]);
+        assert!(Meter::start_on(&domains_in(&root.join("missing"))).finish_on(&[], 1).is_none());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,120 @@
+// The OpenMP C kernels, linked in (--features ffi, see build.rs)
+// histogram, matrix_multiply and prefix_sum take --ffi 1 to time the C
+// version of their kernel in this process, right after the Rust runs and on
+// the same input: same buffers, pages, warmup, reps and outlier filter, with
+// energy and peak RSS metered the same way. Their records have impl "openmp"
+// and the Rust record's params, so openmp::comparison sets them beside the
+// Rust timings like those of --openmp-dir, minus process launch, a cold page
+// cache and a separate allocator. The C functions time their parallel
+// regions themselves with omp_get_wtime, as their programs do:
+//   bench_hist        control/control.c (atomic, padded atomic, local or
+//                     lock-striped bins)
+//   bench_matmul      scalability/matrix_multiply.c (naive, collapse(2))
+//   bench_prefix_sum  programmability/prefix_sum.c (exclusive block scan)
+// OpenMP keeps its own thread team beside the Rust pools; OMP_* environment
+// variables apply to it as they do to the C programs.
+
+#[cfg(feature = "ffi")]
+mod c {
+    use std::ffi::{c_double, c_int, c_longlong, c_ulonglong};
+
+    extern "C" {
+        pub fn bench_hist(data: *const u8, hist: *mut c_ulonglong, n: c_longlong, threads: c_int, strategy: c_int, pad: c_int, stripes: c_int, chunk: c_int, affinity: c_int) -> c_double;
+        pub fn bench_matmul(a: *const c_double, b: *const c_double, c: *mut c_double, n: c_int, threads: c_int) -> c_double;
+        pub fn bench_prefix_sum(input: *const c_longlong, output: *mut c_longlong, n: c_longlong, threads: c_int) -> c_double;
+    }
+}
+
+#[cfg(feature = "ffi")]
+fn int<T: TryInto<i32>>(value: T, what: &str) -> i32 {
+    value.try_into().unwrap_or_else(|_| panic!("{} does not fit a C int", what))
+}
+
+/// The C histogram of `data` into 256 bins with `threads` OpenMP threads
+/// and chunks of `grain` (0 = default) under the histogram's `strategy`:
+/// thread-local bins for "local", `stripes` omp_lock_t over groups of bins
+/// for "striped", else one shared array of atomic bins, padded to a cache
+/// line each when `pad`. Returns the time of the parallel region and the
+/// bins.
+#[cfg(feature = "ffi")]
+pub fn hist(data: &[u8], threads: usize, strategy: &str, pad: bool, stripes: usize, grain: usize, affinity: bool) -> (f64, Vec<u64>) {
+    let mut bins = vec![0u64; 256];
+    // the C strategy codes; manual and scoped are the C local strategy's parallel for
+    let strategy = match strategy {
+        "local" | "manual" | "scoped" => 1,
+        "striped" => 2,
+        _ => 0,
+    };
+    // SAFETY: data is data.len() readable bytes, bins the 256 counters the C
+    // code writes
+    let time = unsafe {
+        c::bench_hist(data.as_ptr(), bins.as_mut_ptr(), data.len() as i64, int(threads, "T"), strategy, pad as i32, int(stripes, "stripes"), int(grain, "grain"), affinity as i32)
+    };
+    (time, bins)
+}
+
+/// C = A * B for n x n row-major matrices with `threads` OpenMP threads;
+/// returns the time of the multiply.
+#[cfg(feature = "ffi")]
+pub fn matmul(a: &[f64], b: &[f64], c: &mut [f64], n: usize, threads: usize) -> f64 {
+    assert!(a.len() == n * n && b.len() == n * n && c.len() == n * n, "matrices of order {} expected", n);
+    // SAFETY: all three hold n * n entries, as asserted
+    unsafe { c::bench_matmul(a.as_ptr(), b.as_ptr(), c.as_mut_ptr(), int(n, "n"), int(threads, "T")) }
+}
+
+/// Exclusive prefix sum of `input` into `output` with `threads` OpenMP
+/// threads; returns the time of the scan.
+#[cfg(feature = "ffi")]
+pub fn prefix_sum(input: &[u64], output: &mut [u64], threads: usize) -> f64 {
+    assert_eq!(input.len(), output.len(), "input and output lengths differ");
+    // SAFETY: both hold input.len() 64-bit integers (the sums of the
+    // benchmark's inputs stay far below 2^63)
+    let time = unsafe { c::bench_prefix_sum(input.as_ptr().cast(), output.as_mut_ptr().cast(), input.len() as i64, int(threads, "T")) };
+    assert!(time >= 0.0, "prefix_sum: the C scan could not allocate its block arrays");
+    time
+}
+
+// cli::ffi refuses --ffi 1 in builds without the C kernels, so these are
+// never called
+#[cfg(not(feature = "ffi"))]
+pub fn hist(_data: &[u8], _threads: usize, _strategy: &str, _pad: bool, _stripes: usize, _grain: usize, _affinity: bool) -> (f64, Vec<u64>) {
+    unreachable!("built without the C kernels")
+}
+
+#[cfg(not(feature = "ffi"))]
+pub fn matmul(_a: &[f64], _b: &[f64], _c: &mut [f64], _n: usize, _threads: usize) -> f64 {
+    unreachable!("built without the C kernels")
+}
+
+#[cfg(not(feature = "ffi"))]
+pub fn prefix_sum(_input: &[u64], _output: &mut [u64], _threads: usize) -> f64 {
+    unreachable!("built without the C kernels")
+}
+
+#[cfg(all(test, feature = "ffi"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kernels() {
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i * 7 % 256) as u8).collect();
+        let mut expected = vec![0u64; 256];
+        data.iter().for_each(|&v| expected[v as usize] += 1);
+        for (strategy, pad) in [("atomic", false), ("atomic", true), ("local", false), ("striped", false)] {
+            let (time, bins) = hist(&data, 2, strategy, pad, 3, 64, false);
+            assert_eq!(bins, expected, "strategy={} pad={}", strategy, pad);
+            assert!(time >= 0.0);
+        }
+
+        let n = 16;
+        let (a, b) = (vec![1.0; n * n], vec![2.0; n * n]);
+        let mut c = vec![0.0; n * n];
+        matmul(&a, &b, &mut c, n, 2);
+        assert!(c.iter().all(|&v| v == 2.0 * n as f64));
+
+        let input: Vec<u64> = (1..=1000).collect();
+        let mut output = vec![0; input.len()];
+        prefix_sum(&input, &mut output, 3);
+        assert!(output.iter().enumerate().all(|(i, &s)| s == (i * (i + 1) / 2) as u64));
+    }
+}
@@ -0,0 +1,102 @@
+// Golden checksums
+// Every kernel builds its input deterministically (fixed LCG seeds or closed
+// formulas), so the output for a given (seed, N, params) key is known in
+// advance. Binaries keep a table of those known digests next to the kernel
+// and check each run against it, which catches silent races at any size and
+// thread count without a sequential re-computation. Keys without an entry
+// fall back to the binary's own check; their digest is logged (`--log`) so
+// it can be pasted into the table.
+//   Checksum  FNV-1a over the output words, for results that are bit-exact
+//             whatever the schedule (integer outputs, exactly representable sums)
+//   Value     a scalar compared with a relative tolerance, for floating-point
+//             reductions whose rounding depends on the summation order
+
+use std::fmt;
+
+// relative tolerance for Digest::Value
+const VALUE_TOLERANCE: f64 = 1e-9;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Digest {
+    Checksum(u64),
+    Value(f64),
+}
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Digest::Checksum(c) => write!(f, "Checksum({:#018x})", c),
+            Digest::Value(v) => write!(f, "Value({:?})", v),
+        }
+    }
+}
+
+/// One registered digest; `key` lists the seed, size and parameters the
+/// output depends on, e.g. "dist=uniform,seed=123456789,N=10000000".
+#[derive(Debug, Clone, Copy)]
+pub struct Golden {
+    pub key: &'static str,
+    pub digest: Digest,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verdict {
+    Match,
+    Mismatch,
+    Unregistered,
+}
+
+/// FNV-1a (64-bit) over the little-endian bytes of `words`.
+pub fn checksum(words: impl IntoIterator<Item = u64>) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for word in words {
+        for byte in word.to_le_bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+fn matches(expected: Digest, actual: Digest) -> bool {
+    match (expected, actual) {
+        (Digest::Checksum(e), Digest::Checksum(a)) => e == a,
+        (Digest::Value(e), Digest::Value(a)) => (a - e).abs() <= VALUE_TOLERANCE * e.abs().max(1.0),
+        _ => false,
+    }
+}
+
+/// Checks `actual` against the entry for `key` in `table`. Mismatches are
+/// reported on stderr; unregistered keys are logged with their digest.
+pub fn check(kernel: &str, table: &[Golden], key: &str, actual: Digest) -> Verdict {
+    match table.iter().find(|g| g.key == key) {
+        Some(golden) if matches(golden.digest, actual) => Verdict::Match,
+        Some(golden) => {
+            eprintln!("{}: golden mismatch for {}: expected {}, got {}", kernel, key, golden.digest, actual);
+            Verdict::Mismatch
+        }
+        None => {
+            tracing::info!(kernel, key, digest = %actual, "no golden digest registered");
+            Verdict::Unregistered
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let table = [
+            Golden { key: "N=3", digest: Digest::Checksum(checksum([1, 2, 3])) },
+            Golden { key: "N=1", digest: Digest::Value(0.3) },
+        ];
+
+        assert_eq!(check("t", &table, "N=3", Digest::Checksum(checksum([1, 2, 3]))), Verdict::Match);
+        assert_eq!(check("t", &table, "N=3", Digest::Checksum(checksum([1, 3, 2]))), Verdict::Mismatch);
+        assert_eq!(check("t", &table, "N=1", Digest::Value(0.1 + 0.2)), Verdict::Match);
+        assert_eq!(check("t", &table, "N=1", Digest::Value(0.31)), Verdict::Mismatch);
+        assert_eq!(check("t", &table, "N=2", Digest::Value(0.3)), Verdict::Unregistered);
+    }
+}
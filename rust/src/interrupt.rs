@@ -0,0 +1,50 @@
+// Graceful Ctrl-C handling for long sweeps
+// The first SIGINT only raises a flag: sweeps check `requested()` between
+// cells, so the cell being measured finishes and is recorded, the remaining
+// cells are skipped and the Reporter prints a resume hint on the way out.
+// A second SIGINT aborts at once (exit code 130) after flushing stdout.
+// Every record is already written and flushed as it completes, so nothing
+// collected before the interrupt is lost either way.
+
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+static INSTALL: Once = Once::new();
+
+/// Installs the SIGINT handler; later calls are no-ops.
+pub fn install() {
+    install_handler(true);
+}
+
+/// Like `install`, without the notice on the first Ctrl-C; for the runner,
+/// whose benchmark child receives the same signal and announces it.
+pub fn install_quiet() {
+    install_handler(false);
+}
+
+fn install_handler(notice: bool) {
+    INSTALL.call_once(|| {
+        let result = ctrlc::set_handler(move || {
+            if INTERRUPTED.swap(true, Ordering::SeqCst) {
+                let _ = std::io::stdout().flush();
+                if notice {
+                    eprintln!("\ninterrupted again, aborting");
+                }
+                std::process::exit(130);
+            }
+            if notice {
+                eprintln!("\ninterrupt: finishing the current cell (Ctrl-C again to abort)");
+            }
+        });
+        if let Err(e) = result {
+            eprintln!("warning: cannot install Ctrl-C handler: {}", e);
+        }
+    });
+}
+
+/// True once Ctrl-C was pressed; sweeps stop before their next cell.
+pub fn requested() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
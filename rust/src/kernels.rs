@@ -0,0 +1,160 @@
+// Benchmark registry
+// The kernels run_all_benchmarks drives live in the library and implement
+// `Benchmark`; REGISTRY lists them in suite order. The runner finds, lists
+// and runs them through the registry only, so a new kernel plugs in with its
+// impl and one entry here. Their binaries in src/bin are thin wrappers around
+// `main`. `command` is the kernel's clap `Args`; `run` takes matches of it
+// (the output options are the caller's business), records into the
+// reporter and returns the exit code.
+
+use crate::cli::{self, Output};
+use crate::describe::{self, Description};
+use crate::multiproc;
+use crate::results::Reporter;
+use clap::{ArgMatches, Command};
+use crate::{controllability, programmability, runtime_overhead, scalability};
+
+/// A `multiproc` worker kernel: block in, partial result out, or None when
+/// the kernel name is another benchmark's.
+pub type WorkerKernel = fn(&str, &[u8]) -> Option<Vec<u64>>;
+
+/// The runner commands grouping the benchmarks, in `all` order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Suite {
+    Programmability,
+    Scalability,
+    RuntimeOverhead,
+    Controllability,
+}
+
+impl Suite {
+    pub const ALL: [Suite; 4] = [Suite::Programmability, Suite::Scalability, Suite::RuntimeOverhead, Suite::Controllability];
+
+    pub fn parse(s: &str) -> Option<Suite> {
+        Suite::ALL.into_iter().find(|suite| suite.name() == s)
+    }
+
+    /// the runner command
+    pub fn name(self) -> &'static str {
+        match self {
+            Suite::Programmability => "programmability",
+            Suite::Scalability => "scalability",
+            Suite::RuntimeOverhead => "runtime_overhead",
+            Suite::Controllability => "controllability",
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Suite::Programmability => "Programmability",
+            Suite::Scalability => "Scalability",
+            Suite::RuntimeOverhead => "Runtime Overhead",
+            Suite::Controllability => "Controllability",
+        }
+    }
+}
+
+pub trait Benchmark: Sync {
+    /// binary name, also the registry key
+    fn name(&self) -> &'static str;
+
+    /// for messages, e.g. "Prefix sum"
+    fn label(&self) -> &'static str;
+
+    fn suite(&self) -> Suite;
+
+    /// arguments, options, params and metrics (the --describe answer)
+    fn description(&self) -> Description;
+
+    /// the kernel's options, `Args::command()`
+    fn command(&self) -> Command;
+
+    fn run(&self, args: &ArgMatches, reporter: &mut Reporter) -> i32;
+
+    /// for binaries that take --multiproc
+    fn worker(&self) -> Option<WorkerKernel> {
+        None
+    }
+}
+
+pub static REGISTRY: [&dyn Benchmark; 18] = [
+    &programmability::prefix_sum::PrefixSum,
+    &programmability::segmented_scan::SegmentedScan,
+    &programmability::fork_join::ForkJoin,
+    &programmability::parallel_for::ParallelFor,
+    &scalability::matrix_multiply::MatrixMultiply,
+    &scalability::reduction::Reduction,
+    &scalability::stream::Stream,
+    &scalability::jacobi::Jacobi,
+    &scalability::sort::Sort,
+    &scalability::nbody::NBody,
+    &scalability::monte_carlo::MonteCarlo,
+    &scalability::vector_ops::VectorOps,
+    &scalability::first_touch::FirstTouch,
+    &runtime_overhead::RuntimeOverhead,
+    &controllability::histogram::Histogram,
+    &controllability::histogram2d::Histogram2d,
+    &controllability::histogram_f64::HistogramF64,
+    &controllability::false_sharing::FalseSharing,
+];
+
+pub fn find(name: &str) -> Option<&'static dyn Benchmark> {
+    REGISTRY.iter().copied().find(|b| b.name() == name)
+}
+
+/// The registered benchmarks of `suite`, in registry order.
+pub fn suite(suite: Suite) -> impl Iterator<Item = &'static dyn Benchmark> {
+    REGISTRY.iter().copied().filter(move |b| b.suite() == suite)
+}
+
+/// In a process a `ProcessPool` spawned, runs the block on the registered
+/// benchmark that owns the kernel name and exits; returns otherwise. The
+/// runner calls it too, since its in-process kernels spawn their workers
+/// from the runner's own executable.
+pub fn worker_entry() {
+    multiproc::worker_entry(|name: &str, block: &[u8]| {
+        let mut workers = REGISTRY.iter().filter_map(|b| b.worker());
+        workers.find_map(|worker| worker(name, block)).unwrap_or_else(|| panic!("unknown worker kernel: {}", name))
+    });
+}
+
+/// main() of a registered benchmark's binary.
+pub fn main(name: &str) -> ! {
+    worker_entry();
+    let benchmark = find(name).expect("binary of a registered benchmark");
+
+    let args: Vec<String> = std::env::args().collect();
+    describe::exit_if_requested(&args, || benchmark.description());
+    let matches = cli::matches(benchmark.command(), args);
+    let mut reporter = cli::or_exit(cli::from_matches::<Output>(&matches).start());
+    let code = benchmark.run(&matches, &mut reporter);
+    // flushes, and writes a --format json document
+    drop(reporter);
+    std::process::exit(code);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry() {
+        for (i, benchmark) in REGISTRY.iter().enumerate() {
+            assert_eq!(benchmark.description().binary, benchmark.name());
+            assert_eq!(benchmark.command().get_name(), benchmark.name());
+            benchmark.command().debug_assert();
+            // every option --describe lists is one clap accepts
+            let command = benchmark.command();
+            let longs: Vec<_> = command.get_arguments().filter_map(|a| a.get_long()).collect();
+            for option in benchmark.description().options {
+                assert!(longs.contains(&&option.name[2..]), "{}: {} undeclared", benchmark.name(), option.name);
+            }
+            assert!(REGISTRY[..i].iter().all(|b| b.name() != benchmark.name()));
+        }
+        assert!(Suite::ALL.iter().all(|&s| suite(s).count() > 0 && Suite::parse(s.name()) == Some(s)));
+        // the runner's workers try every benchmark's kernel until one owns the name
+        let worker = find("histogram").and_then(|b| b.worker()).expect("histogram has a worker");
+        assert_eq!(worker("hist-local", &[1, 1, 255]).map(|bins| bins[1]), Some(2));
+        assert!(worker("no-such-kernel", &[]).is_none());
+    }
+}
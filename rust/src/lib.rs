@@ -0,0 +1,52 @@
+// Shared harness code for the Rust benchmark binaries
+// This crate holds the pieces the binaries share (parallel backends, result
+// records, output sinks, argument helpers) and the kernels the suite runner
+// calls in-process (see kernels.rs). Those kernels are public functions, so
+// other harnesses and the integration tests call them directly; the other
+// kernels live in their own binaries.
+
+#![cfg_attr(feature = "simd", feature(portable_simd))]
+
+pub mod alloc_track;
+pub mod backend;
+pub mod baseline;
+pub mod blas;
+pub mod build_metrics;
+pub mod cli;
+pub mod complexity;
+pub mod config;
+pub mod controllability;
+pub mod describe;
+pub mod dump;
+pub mod energy;
+pub mod ffi;
+pub mod golden;
+pub mod interrupt;
+pub mod kernels;
+pub mod logging;
+pub mod metadata;
+pub mod multiproc;
+pub mod numa;
+pub mod openmp;
+pub mod pages;
+pub mod parfor;
+pub mod plot;
+pub mod programmability;
+pub mod prometheus;
+pub mod report;
+pub mod results;
+pub mod rng;
+pub mod rss;
+pub mod scan;
+pub mod runtime_overhead;
+pub mod scalability;
+pub mod scheduler;
+#[cfg(feature = "simd")]
+pub mod simd_util;
+pub mod sockets;
+pub mod stats;
+pub mod store;
+pub mod summary;
+pub mod threads;
+pub mod timer;
+pub mod upload;
@@ -0,0 +1,72 @@
+// Structured execution log (tracing)
+// `--log pretty|json` installs a subscriber that writes to stderr, so the
+// results on stdout stay machine-readable; RUST_LOG filters as usual
+// (default: info). Without --log nothing is subscribed and the spans are
+// close to free.
+// Kernels wrap each configuration in a span named after the kernel and each
+// step in a phase span (setup, warmup, measure, verify); closing spans log
+// their busy/idle time, and every reported record is logged as an event.
+
+use tracing::{info_span, Span};
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::EnvFilter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Pretty,
+    Json,
+}
+
+impl LogFormat {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "pretty" => Ok(LogFormat::Pretty),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!("unknown log format: {} (use pretty|json)", s)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LogFormat::Pretty => "pretty",
+            LogFormat::Json => "json",
+        }
+    }
+}
+
+pub fn init(format: LogFormat) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .with_span_events(FmtSpan::CLOSE);
+
+    match format {
+        LogFormat::Pretty => builder.pretty().init(),
+        LogFormat::Json => builder.json().with_current_span(true).with_span_list(true).init(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Setup,
+    Warmup,
+    Measure,
+    Verify,
+}
+
+impl Phase {
+    pub fn span(self) -> Span {
+        match self {
+            Phase::Setup => info_span!("setup"),
+            Phase::Warmup => info_span!("warmup"),
+            Phase::Measure => info_span!("measure"),
+            Phase::Verify => info_span!("verify"),
+        }
+    }
+
+    /// Runs `f` inside this phase's span.
+    pub fn run<R>(self, f: impl FnOnce() -> R) -> R {
+        self.span().in_scope(f)
+    }
+}
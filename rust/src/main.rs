@@ -1,111 +1,804 @@
 // Main benchmark runner for all tests
 // Allows running individual benchmarks or all benchmarks
+// The benchmarks come from the registry (kernels.rs) and run in-process with
+// one shared Reporter, or as their own binaries with --isolate. With
+// --openmp-dir (or --ffi 1) the OpenMP runs of the same cells join the
+// output and a Rust vs OpenMP table ends the run. The other subcommands
+// (config, save-baseline, compare, report, plot, summary, verify) work on
+// such runs and their result files; each is documented in its clap help.
 
+use openmp_rust_benchmarks::backend::Backend;
+use openmp_rust_benchmarks::baseline;
+use openmp_rust_benchmarks::build_metrics::{self, Builder, Profile};
+use openmp_rust_benchmarks::cli::{self, Output};
+use openmp_rust_benchmarks::complexity;
+use openmp_rust_benchmarks::config::{self, Point};
+use openmp_rust_benchmarks::describe::Kind;
+use openmp_rust_benchmarks::dump::{self, Dump};
+use openmp_rust_benchmarks::interrupt;
+use openmp_rust_benchmarks::kernels::{self, Benchmark, Suite};
+use openmp_rust_benchmarks::metadata::Metadata;
+use openmp_rust_benchmarks::openmp;
+use openmp_rust_benchmarks::plot::{self, Image};
+use openmp_rust_benchmarks::report;
+use openmp_rust_benchmarks::results::{self, Format, Record, Reporter};
+use openmp_rust_benchmarks::stats::Filter;
+use openmp_rust_benchmarks::summary;
+use openmp_rust_benchmarks::threads;
+use openmp_rust_benchmarks::timer::Timer;
+use clap::{ArgAction, Args, Command, CommandFactory, Parser};
 use std::env;
-use std::process::Command;
+use std::cell::{Cell, RefCell};
+use std::path::{Path, PathBuf};
+use std::process::{Command as Process, Stdio};
+
+/// Rust Benchmark Suite for OpenMP vs Rust Comparison
+#[derive(Parser)]
+#[command(name = "run_all_benchmarks", subcommand_required = true, arg_required_else_help = true, after_help = AFTER_HELP)]
+struct Cli {
+    /// run each benchmark as its own process instead of in-process
+    #[arg(long, global = true)]
+    isolate: bool,
+    /// with --isolate: directory holding the benchmark binaries (default: next to this runner)
+    #[arg(long, global = true, value_name = "DIR")]
+    bin_dir: Option<PathBuf>,
+    /// skip the benchmarks before BIN (resumes an interrupted run)
+    #[arg(long, global = true, value_name = "BIN")]
+    from: Option<String>,
+    /// parallel backend of the benchmarks that take one: rayon (default) | threads | crossbeam | workers
+    #[arg(long, global = true, value_name = "B", value_parser = Backend::parse)]
+    backend: Option<Backend>,
+    /// thread counts for the sweeps, e.g. 1,2,4,half,max (also cores, quarter, a/b)
+    #[arg(long, global = true, value_name = "LIST", value_parser = thread_list)]
+    threads: Option<String>,
+    /// timed runs per cell of every benchmark (default: each benchmark's own)
+    #[arg(long, global = true, value_name = "R", value_parser = cli::positive)]
+    reps: Option<usize>,
+    /// untimed full runs before the timed ones of every benchmark (default 1; 0 for cold starts)
+    #[arg(long, global = true, value_name = "W")]
+    warmup: Option<usize>,
+    /// outlier filter for the statistics of repeated runs: none | iqr (default) | mad
+    #[arg(long, global = true, value_name = "F", value_parser = Filter::parse)]
+    outliers: Option<Filter>,
+    /// clock of the timed runs of the benchmarks that take one: instant (default) | tsc
+    #[arg(long, global = true, value_name = "CLOCK", value_parser = Timer::parse)]
+    timer: Option<Timer>,
+    /// also run the OpenMP executables in DIR (e.g. ../openMP/src) on the cells of each benchmark
+    #[arg(long, global = true, value_name = "DIR")]
+    openmp_dir: Option<PathBuf>,
+    /// 0 | 1: also time the linked C/OpenMP kernels in-process, on the cells of the benchmarks that take --ffi
+    #[arg(long, global = true, value_name = "0|1", action = ArgAction::Set, value_parser = cli::switch, default_value = "0")]
+    ffi: bool,
+    #[command(flatten)]
+    output: Output,
+}
+
+// config [FILE]: the sweeps of a campaign file
+#[derive(Args)]
+struct Campaign {
+    /// TOML file of [[sweep]] tables
+    #[arg(default_value = "benchmarks.toml")]
+    file: PathBuf,
+}
+
+// save-baseline FILE [TARGET]
+#[derive(Args)]
+struct SaveBaseline {
+    /// JSON file to write the records to
+    file: PathBuf,
+    /// all, a suite or a benchmark
+    #[arg(default_value = "all", value_parser = target)]
+    target: String,
+}
+
+// compare FILE [TARGET]
+#[derive(Args)]
+struct Compare {
+    /// baseline from save-baseline (or any --format json output)
+    file: PathBuf,
+    /// all, a suite or a benchmark
+    #[arg(default_value = "all", value_parser = target)]
+    target: String,
+    /// growth of a time, in percent, that counts as a regression
+    #[arg(long, value_name = "PCT", value_parser = percent, default_value = "10")]
+    threshold: f64,
+}
+
+// what save-baseline / compare do with the records of the run
+enum Gate {
+    Save(PathBuf),
+    Compare { file: PathBuf, baseline: Vec<Record>, threshold: f64 },
+}
+
+// report FILE... [-o FILE]
+#[derive(Args)]
+struct Report {
+    /// result files: --format json, jsonl or csv output, or OpenMP CSV
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+    /// HTML file to write
+    #[arg(long, short, value_name = "FILE", default_value = "report.html")]
+    output: PathBuf,
+}
+
+// plot FILE... [--openmp FILE] [-o DIR] [--image png|svg]
+#[derive(Args)]
+struct Plot {
+    /// result files: --format json, jsonl or csv output
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+    /// OpenMP results (CSV) to overlay as dashed lines
+    #[arg(long, value_name = "FILE")]
+    openmp: Option<PathBuf>,
+    /// directory to write the charts to
+    #[arg(long, short, value_name = "DIR", default_value = "plots")]
+    output: PathBuf,
+    /// png | svg
+    #[arg(long, value_name = "FMT", value_parser = Image::parse, default_value = "png")]
+    image: Image,
+}
+
+// summary FILE...
+#[derive(Args)]
+struct Summary {
+    /// Rust and OpenMP result files: --format json, jsonl or csv output, or OpenMP CSV
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+}
+
+// verify A B
+#[derive(Args)]
+struct Verify {
+    /// result dump, e.g. from histogram --dump FILE
+    a: PathBuf,
+    /// the dump to check it against, e.g. from control_openmp ... FILE
+    b: PathBuf,
+}
+
+// build-metrics [kernel...] (default: every kernel, both profiles)
+#[derive(Args)]
+struct BuildMetrics {
+    /// kernels to measure
+    kernels: Vec<String>,
+    /// debug | release | both
+    #[arg(long, value_name = "P", value_parser = Profile::parse_list, default_value = "both")]
+    profile: ::std::vec::Vec<Profile>,
+    /// only the source complexity measures, no builds
+    #[arg(long)]
+    source_only: bool,
+    /// checkout holding rust/ and openMP/ (default: the one this runner was built from)
+    #[arg(long, value_name = "DIR")]
+    repo: Option<PathBuf>,
+}
+
+const AFTER_HELP: &str = "\
+Build everything first with: cargo build --release
+
+A sweep campaign is a TOML file of [[sweep]] tables, run with: config [FILE]
+
+Performance gate: save-baseline base.json, then after a change compare base.json
+(exit status 4 when a time grew by more than --threshold percent)
+
+HTML report of result files, Rust and OpenMP alike: report a.json b.csv -o report.html
+Charts as images (build with --features plot): plot rust.csv --openmp openmp.csv -o plots
+
+Both sides in one run, with a comparison table at the end: all --openmp-dir ../openMP/src
+(or, built with --features ffi, the C kernels linked in: all --ffi 1)
+The headline table of such results, or of separate Rust and OpenMP files: summary rust.csv openmp.csv
+
+Same results on both sides: prefix_sum --dump rust.dump, mp_prefix_sum 10000000 8 c.dump,
+then verify rust.dump c.dump (exit status 3 when they differ)
+
+You can also run individual benchmarks directly:
+  cargo run --release --bin prefix_sum
+  cargo run --release --bin fork_join -- [--size N] [--threads T]
+  cargo run --release --bin parallel_for -- [--size N] [--threads T]
+  cargo run --release --bin matrix_multiply -- [--size n] [--threads LIST]
+  cargo run --release --bin reduction -- [--op sum|dot] [--strategy S] [--size N] [--threads T]
+  cargo run --release --bin stream -- [--size N] [--threads T]
+  cargo run --release --bin jacobi -- [--size n] [--threads T] [--iters I] [--tol E]
+  cargo run --release --bin sort -- [--type u64|f64] [--algo A] [--size N] [--threads T]
+  cargo run --release --bin nbody -- [--size N] [--threads T] [--steps S]
+  cargo run --release --bin monte_carlo -- [--size N] [--threads T]
+  cargo run --release --bin vector_ops -- [--op all|saxpy|sum] [--size N] [--threads T]
+  cargo run --release --bin first_touch -- [--size MiB] [--threads T]
+  cargo run --release --bin runtime_overhead
+  cargo run --release --bin histogram -- [--strategy S] [--dist D] [--size N] [--threads T]";
+
+// the commands come from the suites and the registry
+fn command() -> Command {
+    let mut command = Cli::command().subcommand(Command::new("all").about("Run all benchmarks"));
+    for suite in Suite::ALL {
+        let names: Vec<_> = kernels::suite(suite).map(|b| b.name()).collect();
+        command = command.subcommand(Command::new(suite.name()).about(format!("Run the {} benchmarks ({})", suite.title(), names.join(", "))));
+    }
+    // a benchmark named like its suite (runtime_overhead) runs as the suite
+    for benchmark in kernels::REGISTRY.iter().filter(|b| Suite::parse(b.name()).is_none()) {
+        command = command.subcommand(Command::new(benchmark.name()).about(benchmark.description().summary));
+    }
+    command
+        .subcommand(Campaign::augment_args(Command::new("config")).about("Run the sweeps declared in a TOML file (see benchmarks.toml)"))
+        .subcommand(SaveBaseline::augment_args(Command::new("save-baseline")).about("Run a target (default all) and save its records as a baseline"))
+        .subcommand(
+            Compare::augment_args(Command::new("compare"))
+                .about("Run a target (default all) and flag the times that regressed against a baseline"),
+        )
+        .subcommand(Report::augment_args(Command::new("report")).about("Render result files as a standalone HTML page with tables and speedup/efficiency charts"))
+        .subcommand(Plot::augment_args(Command::new("plot")).about("Draw speedup and time-over-size charts of result files as PNG or SVG (--features plot)"))
+        .subcommand(
+            Summary::augment_args(Command::new("summary"))
+                .about("Tabulate Rust and OpenMP times, slowdown and parallel efficiency per kernel, size and thread count"),
+        )
+        .subcommand(Verify::augment_args(Command::new("verify")).about("Check two result dumps (--dump FILE, or the C programs' last argument) for the same output"))
+        .subcommand(Command::new("list").about("List the registered benchmarks (descriptions as JSON with --format jsonl|json)"))
+        .subcommand(
+            BuildMetrics::augment_args(Command::new("build-metrics"))
+                .about("Report source complexity, compile time and binary size of the kernels (Rust and C)"),
+        )
+}
+
+// TARGET of save-baseline / compare: all, a suite or a benchmark
+fn target(name: &str) -> Result<String, String> {
+    if name == "all" || Suite::parse(name).is_some() || kernels::find(name).is_some() {
+        Ok(name.to_string())
+    } else {
+        Err(format!("unknown target: {} (use all, a suite or a benchmark from `list`)", name))
+    }
+}
+
+fn percent(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(v) if v >= 0.0 && v.is_finite() => Ok(v),
+        _ => Err(format!("must be a non-negative percentage, got {}", s)),
+    }
+}
+
+// --threads LIST, checked here and passed on as given
+fn thread_list(list: &str) -> Result<String, String> {
+    threads::parse_list(list).map(|_| list.to_string())
+}
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    
-    if args.len() < 2 {
-        print_usage();
-        return;
+    // in-process kernels spawn their --multiproc workers from this executable
+    kernels::worker_entry();
+    let matches = cli::matches(command(), env::args().collect());
+    let options: Cli = cli::from_matches(&matches);
+    let (name, sub) = matches.subcommand().expect("a subcommand is required");
+    if options.isolate {
+        interrupt::install_quiet();
     }
-    
-    match args[1].as_str() {
-        "programmability" => run_programmability_benchmarks(),
-        "scalability" => run_scalability_benchmarks(),
-        "runtime_overhead" => run_runtime_overhead_benchmarks(),
-        "controllability" => run_controllability_benchmarks(),
-        "all" => {
-            run_programmability_benchmarks();
-            println!("\n\n");
-            run_scalability_benchmarks();
-            println!("\n\n");
-            run_runtime_overhead_benchmarks();
-            println!("\n\n");
-            run_controllability_benchmarks();
+
+    let text = options.output.format == Format::Text;
+    // the Rust vs OpenMP table at the end
+    let compare = options.openmp_dir.is_some() || cli::or_exit(cli::ffi(options.ffi));
+    let document = options.output.format.is_document();
+    match name {
+        "build-metrics" => {
+            let mut reporter = cli::or_exit(options.output.start());
+            run_build_metrics(cli::from_matches(sub), &mut reporter);
+            return;
+        }
+        "list" => {
+            list(text);
+            return;
+        }
+        "report" => {
+            write_report(cli::from_matches(sub));
+            return;
+        }
+        "plot" => {
+            write_plots(cli::from_matches(sub));
+            return;
+        }
+        "summary" => {
+            print_summary(cli::from_matches(sub));
+            return;
+        }
+        "verify" => {
+            verify_dumps(cli::from_matches(sub));
+            return;
+        }
+        _ => {}
+    }
+    // the whole campaign is checked before the first run
+    let campaign = (name == "config").then(|| {
+        let file = cli::from_matches::<Campaign>(sub).file;
+        let points = cli::or_exit(config::load(&file).and_then(|c| c.points()));
+        (file, points)
+    });
+    // so is the baseline to compare with
+    let (gate, target) = match name {
+        "save-baseline" => {
+            let args: SaveBaseline = cli::from_matches(sub);
+            (Some(Gate::Save(args.file)), args.target)
+        }
+        "compare" => {
+            let Compare { file, target, threshold } = cli::from_matches(sub);
+            let baseline = cli::or_exit(baseline::load(&file));
+            (Some(Gate::Compare { file, baseline, threshold }), target)
+        }
+        _ => (None, name.to_string()),
+    };
+
+    // options forwarded to every benchmark binary, or handled here for
+    // in-process runs
+    let mut forwarded: Vec<String> = Vec::new();
+    let output = &options.output;
+    let values = [
+        ("--backend", options.backend.map(|b| b.name().to_string())),
+        ("--reps", options.reps.map(|r| r.to_string())),
+        ("--warmup", options.warmup.map(|w| w.to_string())),
+        ("--outliers", options.outliers.map(|f| f.name().to_string())),
+        ("--timer", options.timer.map(|t| t.name().to_string())),
+        ("--ffi", options.ffi.then(|| "1".to_string())),
+        ("--format", (!text).then(|| output.format.name().to_string())),
+        ("--upload", output.upload.clone()),
+        ("--prometheus", output.prometheus.clone()),
+        ("--store", output.store.clone()),
+        ("--log", output.log.map(|l| l.name().to_string())),
+    ];
+    for (option, value) in values {
+        if let Some(value) = value {
+            forwarded.extend([option.to_string(), value]);
+        }
+    }
+    let reporter = (!options.isolate).then(|| {
+        let mut reporter = cli::or_exit(output.start());
+        if gate.is_some() || compare {
+            reporter.keep_records();
+        }
+        RefCell::new(reporter)
+    });
+
+    let runner = Runner {
+        command: match (&campaign, &gate) {
+            (Some((file, _)), _) => format!("config {}", file.display()),
+            (_, Some(Gate::Save(file) | Gate::Compare { file, .. })) => format!("{} {} {}", name, file.display(), target),
+            _ => name.to_string(),
         },
-        "help" | "--help" | "-h" => print_usage(),
-        _ => {
-            println!("Unknown command: {}", args[1]);
-            print_usage();
-        }
-    }
-}
-
-fn print_usage() {
-    println!("Rust Benchmark Suite for OpenMP vs Rust Comparison");
-    println!();
-    println!("Usage: cargo run --release --bin run_all_benchmarks <command>");
-    println!();
-    println!("Commands:");
-    println!("  programmability  - Run prefix sum benchmark (measures code complexity)");
-    println!("  scalability      - Run matrix multiply benchmark (measures scalability)");
-    println!("  runtime_overhead - Run runtime overhead benchmarks (thread operations & sync)");
-    println!("  controllability  - Run histogram benchmark (measures programmer control)");
-    println!("  all              - Run all benchmarks");
-    println!("  help             - Show this help message");
-    println!();
-    println!("You can also run individual benchmarks directly:");
-    println!("  cargo run --release --bin prefix_sum");
-    println!("  cargo run --release --bin matrix_multiply [n] [threads]");
-    println!("  cargo run --release --bin runtime_overhead");
-    println!("  cargo run --release --bin histogram");
-}
-
-fn run_programmability_benchmarks() {
-    println!("Running Programmability Benchmarks...");
-    println!("=====================================\n");
-    
-    let status = Command::new("cargo")
-        .args(&["run", "--release", "--bin", "prefix_sum"])
-        .status()
-        .expect("Failed to run prefix_sum benchmark");
-    
-    if !status.success() {
-        eprintln!("Prefix sum benchmark failed!");
-    }
-}
-
-fn run_scalability_benchmarks() {
-    println!("Running Scalability Benchmarks...");
-    println!("==================================\n");
-    
-    let status = Command::new("cargo")
-        .args(&["run", "--release", "--bin", "matrix_multiply"])
-        .status()
-        .expect("Failed to run matrix_multiply benchmark");
-    
-    if !status.success() {
-        eprintln!("Matrix multiply benchmark failed!");
-    }
-}
-
-fn run_runtime_overhead_benchmarks() {
-    println!("Running Runtime Overhead Benchmarks...");
-    println!("======================================\n");
-    
-    let status = Command::new("cargo")
-        .args(&["run", "--release", "--bin", "runtime_overhead"])
-        .status()
-        .expect("Failed to run runtime_overhead benchmark");
-    
-    if !status.success() {
-        eprintln!("Runtime overhead benchmark failed!");
-    }
-}
-
-fn run_controllability_benchmarks() {
-    println!("Running Controllability Benchmarks...");
-    println!("=====================================\n");
-    
-    let status = Command::new("cargo")
-        .args(&["run", "--release", "--bin", "histogram"])
-        .status()
-        .expect("Failed to run histogram benchmark");
-    
-    if !status.success() {
-        eprintln!("Histogram benchmark failed!");
+        bin_dir: options.bin_dir.unwrap_or_else(sibling_dir),
+        forwarded,
+        thread_list: options.threads,
+        text,
+        format: output.format,
+        skip_until: options.from,
+        skipping: Cell::new(true),
+        collected: ((document || gate.is_some() || compare) && options.isolate).then(|| RefCell::new(Vec::new())),
+        openmp_dir: options.openmp_dir,
+        reporter,
+    };
+
+    match name {
+        "config" => {
+            let (_, points) = campaign.expect("parsed above");
+            run_campaign(&runner, &points);
+        }
+        _ => run_target(&runner, &target),
+    }
+    let records = gate.as_ref().map(|_| runner.records());
+    let comparison = compare.then(|| openmp::comparison(&runner.records()));
+    runner.finish();
+
+    if let (Some(from), true) = (&runner.skip_until, runner.skipping.get()) {
+        eprintln!("--from {}: no such benchmark in `{}`", from, runner.command);
+        std::process::exit(1);
+    }
+
+    if let Some(comparison) = comparison {
+        let report = format!("\nRust vs OpenMP:\n{}", comparison);
+        if text {
+            print!("{}", report);
+        } else {
+            eprint!("{}", report);
+        }
+    }
+
+    match (gate, records) {
+        (Some(Gate::Save(file)), Some(records)) => {
+            cli::or_exit(baseline::save(&file, &records));
+            eprintln!("Saved {} record(s) to {}", records.len(), file.display());
+        }
+        (Some(Gate::Compare { file, baseline, threshold }), Some(records)) => {
+            let comparison = baseline::compare(&baseline, &records);
+            let report = format!("\nCompared with {}:\n{}", file.display(), comparison.report(threshold));
+            // beside structured output the report goes to stderr
+            if text {
+                print!("{}", report);
+            } else {
+                eprint!("{}", report);
+            }
+            if comparison.regressions(threshold).next().is_some() {
+                std::process::exit(4);
+            }
+        }
+        _ => {}
+    }
+}
+
+// all, a suite or one benchmark
+fn run_target(runner: &Runner, target: &str) {
+    if target == "all" {
+        for (i, suite) in Suite::ALL.into_iter().enumerate() {
+            if i > 0 {
+                banner(runner.text, "\n\n");
+            }
+            run_suite(runner, suite);
+        }
+        return;
+    }
+    match (Suite::parse(target), kernels::find(target)) {
+        (Some(suite), _) => run_suite(runner, suite),
+        (None, Some(benchmark)) if !runner.skips(benchmark.name()) => runner.run(benchmark, &[]),
+        (None, Some(_)) => {}
+        (None, None) => unreachable!("clap accepts only the registered commands"),
+    }
+}
+
+struct Runner {
+    command: String,
+    bin_dir: PathBuf,
+    forwarded: Vec<String>,
+    // --threads LIST, passed on to the binaries that sweep thread counts
+    thread_list: Option<String>,
+    text: bool,
+    format: Format,
+    // --from BIN: binaries are skipped until BIN comes up
+    skip_until: Option<String>,
+    skipping: Cell<bool>,
+    // --isolate with --format json / markdown, save-baseline, compare,
+    // --openmp-dir or --ffi: records of the binaries run so far
+    collected: Option<RefCell<Vec<Record>>>,
+    // --openmp-dir: where the OpenMP executables are
+    openmp_dir: Option<PathBuf>,
+    // in-process runs: the reporter all kernels share (None with --isolate)
+    reporter: Option<RefCell<Reporter>>,
+}
+
+impl Runner {
+    // true while --from BIN has not come up yet
+    fn skips(&self, bin: &str) -> bool {
+        if self.skipping.get() {
+            match &self.skip_until {
+                Some(from) if from != bin => return true,
+                _ => self.skipping.set(false),
+            }
+        }
+        false
+    }
+
+    // options for `bin`: the forwarded ones it takes (the format turned into
+    // jsonl when the runner collects the records), plus --threads for the
+    // sweeps, then `extra`, which wins over both
+    fn args_for(&self, bin: &str, extra: &[String]) -> Vec<String> {
+        let mut args = Vec::new();
+        for pair in self.forwarded.chunks(2) {
+            let kernel_option = matches!(pair[0].as_str(), "--backend" | "--timer" | "--ffi");
+            if (!kernel_option || takes_option(bin, &pair[0])) && !extra.contains(&pair[0]) {
+                args.extend_from_slice(pair);
+            }
+        }
+        if self.collected.is_some() {
+            match args.iter().position(|a| a == "--format") {
+                Some(i) => args[i + 1] = "jsonl".to_string(),
+                None => args.extend(["--format".to_string(), "jsonl".to_string()]),
+            }
+        }
+        if let (Some(list), true) = (&self.thread_list, sweeps_threads(bin)) {
+            if !extra.iter().any(|a| a == "--threads") {
+                args.extend(["--threads".to_string(), list.clone()]);
+            }
+        }
+        args.extend(extra.iter().cloned());
+        args
+    }
+
+    // collected records: written now in the runner's format (the binaries
+    // wrote jsonl to the runner)
+    fn finish(&self) {
+        if let Some(collected) = &self.collected {
+            let (records, metadata) = (collected.borrow(), Metadata::collect());
+            match self.format {
+                Format::Json => println!("{}", results::json_document(&metadata, &records)),
+                Format::Markdown => print!("{}", results::markdown_tables(&metadata, &records)),
+                Format::Jsonl => {
+                    println!("{}", metadata.to_jsonl_header());
+                    records.iter().for_each(|r| println!("{}", r.to_json()));
+                }
+                Format::Csv => {
+                    print!("{}", metadata.to_csv_header());
+                    records.iter().for_each(|r| print!("{}", r.to_csv()));
+                }
+                // the binaries' console output went to the runner as jsonl
+                Format::Text => records.iter().for_each(|r| print!("{}", r.to_csv())),
+            }
+        }
+        if let Some(reporter) = &self.reporter {
+            reporter.borrow_mut().finish();
+        }
+    }
+
+    // the records kept for save-baseline / compare / --openmp-dir
+    fn records(&self) -> Vec<Record> {
+        match (&self.collected, &self.reporter) {
+            (Some(collected), _) => collected.borrow().clone(),
+            (None, Some(reporter)) => reporter.borrow().records().to_vec(),
+            (None, None) => Vec::new(),
+        }
+    }
+
+    // runs one benchmark, in-process or as its binary, with its own options
+    // `extra` (a config sweep point) on top of the runner's
+    fn run(&self, benchmark: &dyn Benchmark, extra: &[String]) {
+        let (bin, label) = (benchmark.name(), benchmark.label());
+        let before = self.openmp_dir.as_ref().map(|_| self.records().len());
+        match &self.reporter {
+            Some(reporter) => self.run_in_process(benchmark, extra, &mut reporter.borrow_mut()),
+            None => self.run_binary(bin, label, extra),
+        }
+        if let (Some(dir), Some(before)) = (&self.openmp_dir, before) {
+            self.run_openmp(dir, &self.records()[before..]);
+        }
+
+        // the benchmark already stopped after its current cell; rerun it
+        // from the start, as cells are not resumable within a binary
+        if interrupt::requested() {
+            let mut resume = vec!["run_all_benchmarks".to_string(), self.command.clone(), "--from".to_string(), bin.to_string()];
+            resume.extend(self.forwarded.iter().cloned());
+            if let Some(list) = &self.thread_list {
+                resume.extend(["--threads".to_string(), list.clone()]);
+            }
+            if self.reporter.is_none() {
+                resume.push("--isolate".to_string());
+            }
+            if let Some(dir) = &self.openmp_dir {
+                resume.extend(["--openmp-dir".to_string(), dir.display().to_string()]);
+            }
+            self.finish();
+            eprintln!("{} interrupted; results so far are flushed. Resume with:", label);
+            eprintln!("  {}", resume.join(" "));
+            std::process::exit(130);
+        }
+    }
+
+    // the kernel's own options (--backend, --threads, the repetition
+    // options, --timer, --ffi, `extra`); the output options went to the shared reporter
+    fn run_in_process(&self, benchmark: &dyn Benchmark, extra: &[String], reporter: &mut Reporter) {
+        let bin = benchmark.name();
+        let mut args = vec![bin.to_string()];
+        for pair in self.args_for(bin, &[]).chunks(2) {
+            if matches!(pair[0].as_str(), "--backend" | "--threads" | "--reps" | "--warmup" | "--outliers" | "--timer" | "--ffi") && !extra.contains(&pair[0]) {
+                args.extend_from_slice(pair);
+            }
+        }
+        args.extend(extra.iter().cloned());
+        let code = match benchmark.command().try_get_matches_from(args) {
+            Ok(matches) => benchmark.run(&matches, reporter),
+            Err(e) => {
+                let _ = e.print();
+                1
+            }
+        };
+        if code != 0 {
+            eprintln!("{} benchmark failed!", benchmark.label());
+        }
+    }
+
+    // --openmp-dir: the OpenMP cells matching the records of a benchmark,
+    // added to the output beside them; failures are reported and skipped
+    fn run_openmp(&self, dir: &Path, records: &[Record]) {
+        for invocation in openmp::invocations(records) {
+            if interrupt::requested() {
+                return;
+            }
+            let records = match openmp::run(dir, &invocation) {
+                Ok(records) => records,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    continue;
+                }
+            };
+            for record in records {
+                match (&self.collected, &self.reporter) {
+                    (Some(collected), _) => collected.borrow_mut().push(record),
+                    (None, Some(reporter)) => {
+                        if self.text {
+                            print!("{}", record.to_csv());
+                        }
+                        reporter.borrow_mut().record(&record);
+                    }
+                    (None, None) => {}
+                }
+            }
+        }
+    }
+
+    fn run_binary(&self, bin: &str, label: &str, extra: &[String]) {
+        let path = self.bin_dir.join(format!("{}{}", bin, env::consts::EXE_SUFFIX));
+        if !path.is_file() {
+            eprintln!("{} not found; build it with `cargo build --release` or pass --bin-dir", path.display());
+            return;
+        }
+
+        let mut command = Process::new(&path);
+        command.args(self.args_for(bin, extra));
+        let status = match &self.collected {
+            Some(collected) => command.stderr(Stdio::inherit()).output().map(|output| {
+                for line in String::from_utf8_lossy(&output.stdout).lines() {
+                    // skips each binary's metadata line; the runner writes its own
+                    match serde_json::from_str::<Record>(line) {
+                        Ok(record) => collected.borrow_mut().push(record),
+                        Err(_) if line.starts_with("{\"metadata\":") => {}
+                        Err(_) => eprintln!("{}", line),
+                    }
+                }
+                output.status
+            }),
+            None => command.status(),
+        };
+        match status {
+            Ok(status) if status.success() => {}
+            Ok(_) => eprintln!("{} benchmark failed!", label),
+            Err(e) => eprintln!("Failed to run {}: {}", path.display(), e),
+        }
+    }
+}
+
+// true for the benchmarks that sweep thread counts and accept --threads LIST
+fn sweeps_threads(bin: &str) -> bool {
+    kernels::find(bin).is_some_and(|b| b.description().options.iter().any(|o| o.name == "--threads" && matches!(o.kind, Kind::ThreadList)))
+}
+
+// true for the benchmarks that describe `option` (--backend, --timer, --ffi)
+fn takes_option(bin: &str, option: &str) -> bool {
+    kernels::find(bin).is_some_and(|b| b.description().options.iter().any(|o| o.name == option))
+}
+
+// directory of the running executable, where cargo puts the sibling binaries
+fn sibling_dir() -> PathBuf {
+    env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+// the registered benchmarks of `suite` under one banner
+fn run_suite(runner: &Runner, suite: Suite) {
+    let benchmarks: Vec<&dyn Benchmark> = kernels::suite(suite).filter(|b| !runner.skips(b.name())).collect();
+    if benchmarks.is_empty() {
+        return;
+    }
+    let title = format!("Running {} Benchmarks...", suite.title());
+    banner(runner.text, &title);
+    banner(runner.text, &format!("{}\n", "=".repeat(title.len())));
+
+    for benchmark in benchmarks {
+        runner.run(benchmark, &[]);
+    }
+}
+
+// config: the points of a campaign, one banner per run of points of the
+// same benchmark
+fn run_campaign(runner: &Runner, points: &[Point]) {
+    let points: Vec<&Point> = points.iter().filter(|p| !runner.skips(p.benchmark.name())).collect();
+    for (i, group) in points.chunk_by(|a, b| a.benchmark.name() == b.benchmark.name()).enumerate() {
+        if i > 0 {
+            banner(runner.text, "\n\n");
+        }
+        let plural = if group.len() == 1 { "" } else { "s" };
+        let title = format!("Running {} ({} configuration{})...", group[0].benchmark.label(), group.len(), plural);
+        banner(runner.text, &title);
+        banner(runner.text, &format!("{}\n", "=".repeat(title.len())));
+        for point in group {
+            runner.run(point.benchmark, &point.args);
+        }
+    }
+}
+
+// list: the registry, one benchmark per line (text) or its description
+fn list(text: bool) {
+    for benchmark in kernels::REGISTRY {
+        let description = benchmark.description();
+        if text {
+            println!("{:<18} {:<18} {}", benchmark.name(), benchmark.suite().name(), description.summary);
+        } else {
+            println!("{}", serde_json::to_string(&description).expect("description serialization cannot fail"));
+        }
+    }
+}
+
+// report FILE...: every file is read before the page is written
+fn write_report(args: Report) {
+    let sources: Vec<report::Source> = args.files.iter().map(|file| cli::or_exit(report::load(file))).collect();
+    let records: usize = sources.iter().map(|s| s.records.len()).sum();
+    cli::or_exit(std::fs::write(&args.output, report::render(&sources)).map_err(|e| format!("{}: {}", args.output.display(), e)));
+    eprintln!("Wrote {} ({} records from {} file(s))", args.output.display(), records, sources.len());
+}
+
+// plot FILE...: one line per chart written
+fn write_plots(args: Plot) {
+    let sources: Vec<report::Source> = args.files.iter().map(|file| cli::or_exit(report::load(file))).collect();
+    let openmp = args.openmp.map(|file| cli::or_exit(report::load(&file)));
+    let written = cli::or_exit(plot::plot(&sources, openmp.as_ref(), &args.output, args.image));
+    if written.is_empty() {
+        eprintln!("No charts: no kernel's records vary T or a size (n, N, MiB)");
+    }
+    for path in written {
+        eprintln!("Wrote {}", path.display());
+    }
+}
+
+// summary FILE...: the records of all files in one table
+fn print_summary(args: Summary) {
+    let records: Vec<Record> = args.files.iter().flat_map(|file| cli::or_exit(report::load(file)).records).collect();
+    print!("{}", summary::render(&records));
+}
+
+// verify A B: one line either way, exit status 3 on a difference
+fn verify_dumps(args: Verify) {
+    let (a, b) = (cli::or_exit(Dump::read(&args.a)), cli::or_exit(Dump::read(&args.b)));
+    match dump::verify(&a, &b) {
+        Ok(()) => println!("{}: {} and {} match", a, args.a.display(), args.b.display()),
+        Err(difference) => {
+            println!("{} and {} differ: {}", args.a.display(), args.b.display(), difference);
+            std::process::exit(3);
+        }
+    }
+}
+
+// build-metrics [kernel...]: reports the source complexity of each kernel,
+// then rebuilds it per profile; one record for its Rust and one for its C
+// version each time
+fn run_build_metrics(args: BuildMetrics, reporter: &mut Reporter) {
+    let BuildMetrics { kernels, profile: profiles, source_only, repo } = args;
+    let repo = repo.unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join(".."));
+    let kernels = cli::or_exit(build_metrics::select(&kernels));
+
+    for kernel in &kernels {
+        let rust = cli::or_exit(complexity::measure_files(&repo, kernel.rust));
+        report_metrics(reporter, &rust.record("rust", kernel.bin));
+        if let Some(c) = kernel.c {
+            let c = cli::or_exit(complexity::measure_files(&repo, &[c]));
+            report_metrics(reporter, &c.record("c", kernel.bin));
+        }
+    }
+    if source_only {
+        return;
+    }
+
+    let builder = Builder::new(&repo);
+    for profile in profiles {
+        banner(reporter.is_text(), &format!("Building kernels ({})...", profile.name()));
+        built(builder.prepare(&kernels, profile));
+        for kernel in &kernels {
+            let rust = built(builder.rust(kernel, profile));
+            report_metrics(reporter, &rust.record("rust", kernel.bin, profile));
+            if let Some(c) = built(builder.c(kernel, profile)) {
+                report_metrics(reporter, &c.record("c", kernel.bin, profile));
+            }
+        }
+    }
+}
+
+// a build interrupted by Ctrl-C ends the run quietly, other failures loudly
+fn built<T>(result: Result<T, String>) -> T {
+    if interrupt::requested() {
+        std::process::exit(130);
+    }
+    cli::or_exit(result)
+}
+
+fn report_metrics(reporter: &mut Reporter, record: &Record) {
+    if reporter.is_text() {
+        print!("{}", record.to_csv());
+    }
+    reporter.record(record);
+}
+
+// runner messages go to stderr when stdout carries structured output
+fn banner(text: bool, line: &str) {
+    if text {
+        println!("{}", line);
+    } else {
+        eprintln!("{}", line);
     }
 }
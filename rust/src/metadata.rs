@@ -0,0 +1,137 @@
+// Machine and build metadata for the results
+// Results collected on different machines only stay comparable if each file
+// says where and how it was produced, so the reporter opens every structured
+// output with this block:
+//   csv    `# key=value` comment lines before the first row
+//   jsonl  a first line {"metadata": {...}}
+//   json   {"metadata": {...}, "records": [...]}
+// The machine facts come from /proc and the sysfs topology (Linux; "unknown"
+// elsewhere), rustc, the cargo profile and the git commit from build.rs.
+
+use crate::{sockets, threads};
+use serde::{Deserialize, Serialize};
+use std::{env, fs};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Metadata {
+    pub cpu_model: String,
+    pub sockets: usize,
+    pub physical_cores: usize,
+    pub logical_cpus: usize,
+    /// hardware threads per core
+    pub smt: usize,
+    pub total_ram_bytes: u64,
+    /// OS, architecture and kernel release
+    pub os: String,
+    pub rustc: String,
+    pub profile: String,
+    /// commit of the sources the binary was built from
+    pub git_commit: String,
+    /// RAYON_NUM_THREADS as set in the environment, if it was
+    pub rayon_num_threads: Option<String>,
+}
+
+impl Metadata {
+    /// The facts of this machine, process and build.
+    pub fn collect() -> Self {
+        let (physical_cores, logical_cpus) = (threads::physical(), threads::logical());
+        let os = match fs::read_to_string("/proc/sys/kernel/osrelease") {
+            Ok(release) => format!("{} {} {}", env::consts::OS, env::consts::ARCH, release.trim()),
+            Err(_) => format!("{} {}", env::consts::OS, env::consts::ARCH),
+        };
+        Metadata {
+            cpu_model: cpu_model().unwrap_or_else(|| "unknown".to_string()),
+            sockets: sockets::topology().len(),
+            physical_cores,
+            logical_cpus,
+            smt: (logical_cpus / physical_cores.max(1)).max(1),
+            total_ram_bytes: total_ram().unwrap_or(0),
+            os,
+            rustc: env!("BENCH_RUSTC_VERSION").to_string(),
+            profile: env!("BENCH_PROFILE").to_string(),
+            git_commit: env!("BENCH_GIT_COMMIT").to_string(),
+            rayon_num_threads: env::var("RAYON_NUM_THREADS").ok(),
+        }
+    }
+
+    /// The facts as (key, value) pairs, in declaration order; unset values
+    /// are empty.
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("cpu_model", self.cpu_model.clone()),
+            ("sockets", self.sockets.to_string()),
+            ("physical_cores", self.physical_cores.to_string()),
+            ("logical_cpus", self.logical_cpus.to_string()),
+            ("smt", self.smt.to_string()),
+            ("total_ram_bytes", self.total_ram_bytes.to_string()),
+            ("os", self.os.clone()),
+            ("rustc", self.rustc.clone()),
+            ("profile", self.profile.clone()),
+            ("git_commit", self.git_commit.clone()),
+            ("rayon_num_threads", self.rayon_num_threads.clone().unwrap_or_default()),
+        ]
+    }
+
+    /// `# key=value` lines heading a CSV file.
+    pub fn to_csv_header(&self) -> String {
+        self.fields().iter().map(|(key, value)| format!("# {}={}\n", key, value)).collect()
+    }
+
+    /// The first line of a jsonl stream.
+    pub fn to_jsonl_header(&self) -> String {
+        #[derive(Serialize)]
+        struct Header<'a> {
+            metadata: &'a Metadata,
+        }
+        serde_json::to_string(&Header { metadata: self }).expect("metadata serialization cannot fail")
+    }
+}
+
+// "model name" of the first CPU in /proc/cpuinfo
+fn cpu_model() -> Option<String> {
+    let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok()?;
+    cpuinfo
+        .lines()
+        .find_map(|line| line.strip_prefix("model name")?.split_once(':').map(|(_, model)| model.trim().to_string()))
+}
+
+// MemTotal of /proc/meminfo, in bytes
+fn total_ram() -> Option<u64> {
+    let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+    let kib = meminfo.lines().find_map(|line| line.strip_prefix("MemTotal:"))?.trim().strip_suffix("kB")?.trim().parse::<u64>().ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headers() {
+        let metadata = Metadata {
+            cpu_model: "Example CPU @ 3.00GHz".to_string(),
+            sockets: 1,
+            physical_cores: 8,
+            logical_cpus: 16,
+            smt: 2,
+            total_ram_bytes: 1 << 34,
+            os: "linux x86_64 6.1.0".to_string(),
+            rustc: "rustc 1.80.0".to_string(),
+            profile: "release".to_string(),
+            git_commit: "0123abc".to_string(),
+            rayon_num_threads: None,
+        };
+        let header = metadata.to_csv_header();
+        assert!(header.starts_with("# cpu_model=Example CPU @ 3.00GHz\n# sockets=1\n"), "{}", header);
+        assert!(header.contains("# total_ram_bytes=17179869184\n") && header.ends_with("# rayon_num_threads=\n"));
+        assert!(header.lines().all(|line| line.starts_with("# ")));
+
+        let line: serde_json::Value = serde_json::from_str(&metadata.to_jsonl_header()).unwrap();
+        assert_eq!(line["metadata"]["smt"], 2);
+        assert_eq!(line["metadata"]["profile"], "release");
+        assert_eq!(line["metadata"]["git_commit"], "0123abc");
+
+        let here = Metadata::collect();
+        assert!(here.logical_cpus >= 1 && here.smt >= 1 && !here.rustc.is_empty());
+    }
+}
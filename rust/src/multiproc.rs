@@ -0,0 +1,291 @@
+// Multi-process execution: the MPI-style comparison point
+// Instead of T threads, the parent re-executes its own binary as P worker
+// processes. The input is written once to a file that every worker maps
+// read-only; each worker handles one contiguous block and hands its partial
+// result back either through a shared result mapping (shm) or over its
+// stdout pipe (pipe). The parent then reduces the P partials itself.
+//
+// A binary opts in by calling `worker_entry` first thing in main: in a
+// worker process it runs the requested kernel and exits, otherwise it
+// returns immediately. The registered benchmarks' binaries and
+// run_all_benchmarks go through `kernels::worker_entry`, which finds the
+// kernel among all of theirs.
+
+use memmap2::{Mmap, MmapMut};
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// worker spec: kernel, rank, procs, element size, result words, input, output
+const WORKER_ENV: &str = "BENCH_MULTIPROC_WORKER";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ipc {
+    Shm,
+    Pipe,
+}
+
+impl Ipc {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "shm" => Ok(Ipc::Shm),
+            "pipe" => Ok(Ipc::Pipe),
+            _ => Err(format!("unknown --multiproc mode: {} (use shm|pipe)", s)),
+        }
+    }
+
+    // reported in the implementation column
+    pub fn name(self) -> &'static str {
+        match self {
+            Ipc::Shm => "multiproc-shm",
+            Ipc::Pipe => "multiproc-pipe",
+        }
+    }
+}
+
+pub struct ProcessPool {
+    procs: usize,
+    ipc: Ipc,
+}
+
+impl ProcessPool {
+    pub fn new(procs: usize, ipc: Ipc) -> Self {
+        ProcessPool { procs, ipc }
+    }
+
+    pub fn procs(&self) -> usize {
+        self.procs
+    }
+
+    /// Runs `kernel` in P worker processes over `input` (a byte view of
+    /// `elem_size`-byte elements) and returns every worker's `words`-long
+    /// partial result, in rank order.
+    pub fn map(&self, kernel: &str, input: &[u8], elem_size: usize, words: usize) -> io::Result<Vec<Vec<u64>>> {
+        let input_path = scratch_path("in");
+        let output_path = scratch_path("out");
+        fs::write(&input_path, input)?;
+
+        let result = self.run(kernel, &input_path, &output_path, elem_size, words);
+
+        let _ = fs::remove_file(&input_path);
+        let _ = fs::remove_file(&output_path);
+        result
+    }
+
+    fn run(
+        &self,
+        kernel: &str,
+        input_path: &Path,
+        output_path: &Path,
+        elem_size: usize,
+        words: usize,
+    ) -> io::Result<Vec<Vec<u64>>> {
+        if self.ipc == Ipc::Shm {
+            let out = File::create(output_path)?;
+            out.set_len((self.procs * words * 8) as u64)?;
+        }
+        let output = match self.ipc {
+            Ipc::Shm => output_path.to_str().unwrap_or_default(),
+            Ipc::Pipe => "",
+        };
+
+        let exe = env::current_exe()?;
+        let mut children: Vec<Child> = Vec::with_capacity(self.procs);
+        for rank in 0..self.procs {
+            let spec = [
+                kernel.to_string(),
+                rank.to_string(),
+                self.procs.to_string(),
+                elem_size.to_string(),
+                words.to_string(),
+                input_path.to_str().unwrap_or_default().to_string(),
+                output.to_string(),
+            ]
+            .join("\t");
+            let child = Command::new(&exe)
+                .env(WORKER_ENV, spec)
+                .stdin(Stdio::null())
+                .stdout(if self.ipc == Ipc::Pipe { Stdio::piped() } else { Stdio::null() })
+                .spawn();
+            match child {
+                Ok(c) => children.push(c),
+                Err(e) => {
+                    reap(children);
+                    return Err(e);
+                }
+            }
+        }
+
+        let mut partials = match drain(&mut children, words) {
+            Ok(partials) => partials,
+            Err(e) => {
+                reap(children);
+                return Err(e);
+            }
+        };
+
+        // every worker is waited before the first failure is reported
+        let mut failure = None;
+        for (rank, mut child) in children.into_iter().enumerate() {
+            let error = match child.wait() {
+                Ok(status) if status.success() => continue,
+                Ok(status) => io::Error::other(format!("worker {} failed: {}", rank, status)),
+                Err(e) => e,
+            };
+            failure.get_or_insert(error);
+        }
+        if let Some(e) = failure {
+            return Err(e);
+        }
+
+        if self.ipc == Ipc::Shm {
+            let out = File::open(output_path)?;
+            // SAFETY: every worker has exited, nothing else writes the file
+            let map = unsafe { Mmap::map(&out)? };
+            partials = map.chunks(words * 8).map(decode_words).collect();
+        }
+
+        if partials.len() != self.procs || partials.iter().any(|p| p.len() != words) {
+            return Err(io::Error::other("malformed worker result"));
+        }
+        Ok(partials)
+    }
+}
+
+// the pipe partials, in rank order; they are small, so draining the
+// workers one by one cannot deadlock
+fn drain(children: &mut [Child], words: usize) -> io::Result<Vec<Vec<u64>>> {
+    let mut partials = Vec::with_capacity(children.len());
+    for child in children {
+        if let Some(stdout) = child.stdout.as_mut() {
+            let mut bytes = Vec::with_capacity(words * 8);
+            stdout.read_to_end(&mut bytes)?;
+            partials.push(decode_words(&bytes));
+        }
+    }
+    Ok(partials)
+}
+
+fn reap(children: Vec<Child>) {
+    for mut child in children {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+/// Worker side: if this process was spawned by a `ProcessPool`, maps the
+/// input, runs `kernel(name, block)` on this rank's block, publishes the
+/// result and exits. Returns normally in every other process.
+pub fn worker_entry<F>(kernel: F)
+where
+    F: Fn(&str, &[u8]) -> Vec<u64>,
+{
+    let Ok(spec) = env::var(WORKER_ENV) else { return };
+    match run_worker(&spec, kernel) {
+        Ok(()) => std::process::exit(0),
+        Err(e) => {
+            eprintln!("multiproc worker: {}", e);
+            std::process::exit(2);
+        }
+    }
+}
+
+fn run_worker<F>(spec: &str, kernel: F) -> io::Result<()>
+where
+    F: Fn(&str, &[u8]) -> Vec<u64>,
+{
+    let fields: Vec<&str> = spec.split('\t').collect();
+    let [name, rank, procs, elem_size, words, input, output] = fields[..] else {
+        return Err(io::Error::other(format!("bad worker spec: {:?}", spec)));
+    };
+    let number = |s: &str| s.parse::<usize>().map_err(io::Error::other);
+    let (rank, procs, elem_size, words) = (number(rank)?, number(procs)?, number(elem_size)?, number(words)?);
+
+    let file = File::open(input)?;
+    // SAFETY: the parent does not modify the input while workers run
+    let map = unsafe { Mmap::map(&file)? };
+    let range = block(map.len() / elem_size, procs, rank);
+    let partial = kernel(name, &map[range.start * elem_size..range.end * elem_size]);
+    if partial.len() != words {
+        return Err(io::Error::other("kernel returned the wrong number of words"));
+    }
+
+    if output.is_empty() {
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(&encode_words(&partial))?;
+        stdout.flush()
+    } else {
+        let out = OpenOptions::new().read(true).write(true).open(output)?;
+        // SAFETY: each rank writes only its own disjoint slot
+        let mut map = unsafe { MmapMut::map_mut(&out)? };
+        let slot = rank * words * 8;
+        map[slot..slot + words * 8].copy_from_slice(&encode_words(&partial));
+        map.flush()
+    }
+}
+
+// contiguous block of `len` elements owned by `rank` out of `procs`
+pub fn block(len: usize, procs: usize, rank: usize) -> Range<usize> {
+    let size = len.div_ceil(procs);
+    (rank * size).min(len)..((rank + 1) * size).min(len)
+}
+
+/// Byte view of an f64 slice, for handing it to `ProcessPool::map`.
+pub fn f64_bytes(data: &[f64]) -> &[u8] {
+    // SAFETY: f64 has no padding and every byte pattern is a valid u8
+    unsafe { std::slice::from_raw_parts(data.as_ptr() as *const u8, std::mem::size_of_val(data)) }
+}
+
+/// f64 view of a worker's block; blocks of a mapped f64 input stay 8-byte aligned.
+pub fn bytes_f64(bytes: &[u8]) -> &[f64] {
+    // SAFETY: the prefix/suffix check rejects misaligned or partial input
+    let (pre, data, post) = unsafe { bytes.align_to::<f64>() };
+    assert!(pre.is_empty() && post.is_empty(), "misaligned f64 block");
+    data
+}
+
+fn encode_words(words: &[u64]) -> Vec<u8> {
+    words.iter().flat_map(|w| w.to_le_bytes()).collect()
+}
+
+fn decode_words(bytes: &[u8]) -> Vec<u64> {
+    bytes
+        .chunks_exact(8)
+        .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+        .collect()
+}
+
+// scratch files prefer /dev/shm so "shared memory" is really memory-backed
+fn scratch_path(tag: &str) -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let dir = Path::new("/dev/shm");
+    let dir = if dir.is_dir() { dir.to_path_buf() } else { env::temp_dir() };
+    dir.join(format!(
+        "bench_multiproc_{}_{}_{}",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed),
+        tag
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocks_cover_input() {
+        for (len, procs) in [(10, 3), (7, 8), (1000, 4)] {
+            let blocks: Vec<_> = (0..procs).map(|r| block(len, procs, r)).collect();
+            assert_eq!(blocks[0].start, 0);
+            assert_eq!(blocks[procs - 1].end, len);
+            for pair in blocks.windows(2) {
+                assert_eq!(pair[0].end, pair[1].start);
+            }
+        }
+        assert_eq!(decode_words(&encode_words(&[1, u64::MAX])), vec![1, u64::MAX]);
+    }
+}
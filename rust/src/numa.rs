@@ -0,0 +1,79 @@
+// NUMA node of a buffer's pages (Linux, /proc/self/numa_maps)
+// Linux backs a page on the node of the thread that first writes it, so an
+// input written by one thread sits on one node and the other sockets read it
+// remotely. numa_maps lists every mapping of the process by start address
+// with its resident pages per node ("N0=1200 N1=1244"); /proc/self/maps has
+// the matching end addresses. `node_pages` adds up the mappings that overlap
+// a buffer. A small heap buffer may share its mapping with other data, so
+// those counts cover the whole mapping; buffers of many MiB come from
+// mappings of their own. Without the files (other systems, kernels without
+// NUMA) there is nothing to report.
+
+use std::collections::BTreeMap;
+use std::fs;
+
+const MAPS: &str = "/proc/self/maps";
+const NUMA_MAPS: &str = "/proc/self/numa_maps";
+
+// "7f3a2c000000-7f3a2c021000 rw-p ..." -> (start, end)
+fn ranges(maps: &str) -> BTreeMap<usize, usize> {
+    maps.lines()
+        .filter_map(|line| {
+            let (start, end) = line.split_whitespace().next()?.split_once('-')?;
+            Some((usize::from_str_radix(start, 16).ok()?, usize::from_str_radix(end, 16).ok()?))
+        })
+        .collect()
+}
+
+// pages per node of the numa_maps lines whose mapping overlaps [lo, hi)
+fn overlapping(maps: &str, numa_maps: &str, lo: usize, hi: usize) -> Vec<(usize, u64)> {
+    let ends = ranges(maps);
+    let mut nodes: BTreeMap<usize, u64> = BTreeMap::new();
+    for line in numa_maps.lines() {
+        let mut fields = line.split_whitespace();
+        let Some(start) = fields.next().and_then(|s| usize::from_str_radix(s, 16).ok()) else { continue };
+        let Some(&end) = ends.get(&start) else { continue };
+        if start >= hi || end <= lo {
+            continue;
+        }
+        for field in fields {
+            let node = field.strip_prefix('N').and_then(|f| f.split_once('='));
+            if let Some((node, pages)) = node.and_then(|(n, p)| Some((n.parse::<usize>().ok()?, p.parse::<u64>().ok()?))) {
+                *nodes.entry(node).or_default() += pages;
+            }
+        }
+    }
+    nodes.into_iter().collect()
+}
+
+/// Resident pages of the mappings holding `data`, per NUMA node, in node
+/// order; empty where the kernel does not report them.
+pub fn node_pages<T>(data: &[T]) -> Vec<(usize, u64)> {
+    let (Ok(maps), Ok(numa_maps)) = (fs::read_to_string(MAPS), fs::read_to_string(NUMA_MAPS)) else { return Vec::new() };
+    let lo = data.as_ptr() as usize;
+    overlapping(&maps, &numa_maps, lo, lo + std::mem::size_of_val(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlapping() {
+        let maps = "1000-3000 rw-p 00000000 00:00 0\n\
+                    3000-9000 rw-p 00000000 00:00 0\n\
+                    9000-a000 r--p 00000000 08:01 42 /usr/lib/libc.so\n";
+        let numa_maps = "1000 default anon=2 dirty=2 N0=2 kernelpagesize_kB=4\n\
+                         3000 default anon=6 dirty=6 N0=1 N1=5 kernelpagesize_kB=4\n\
+                         9000 default file=/usr/lib/libc.so mapped=1 N1=1 kernelpagesize_kB=4\n";
+        assert_eq!(overlapping(maps, numa_maps, 0x4000, 0x5000), [(0, 1), (1, 5)]);
+        assert_eq!(overlapping(maps, numa_maps, 0x2000, 0x9001), [(0, 3), (1, 6)]);
+        assert!(overlapping(maps, numa_maps, 0xa000, 0xb000).is_empty());
+
+        // a buffer of this process, once written, is resident somewhere
+        let data = vec![1u8; 1 << 22];
+        if fs::metadata(NUMA_MAPS).is_ok() {
+            assert!(node_pages(&data).iter().map(|&(_, pages)| pages).sum::<u64>() > 0);
+        }
+    }
+}
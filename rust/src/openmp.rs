@@ -0,0 +1,323 @@
+// The OpenMP side of a run (run_all_benchmarks --openmp-dir DIR)
+// After each Rust benchmark the runner takes the cells it just recorded,
+// runs the matching C/OpenMP executable once per distinct cell and adds the
+// records it prints (impl "openmp") to the same output. Executables are
+// looked up in DIR itself, then where the run_*.sh scripts build them when
+// DIR is openMP/src:
+//   histogram         control/control_openmp S D N T static grain pad affinity - seed stripes
+//                     (not for --input runs, whose records do not name the file)
+//   histogram2d       control/hist2d_openmp S D N T grain seed
+//   histogram_f64     control/fhist_openmp S N T bins edges grain seed
+//   false_sharing     control/false_sharing_openmp U N T affinity
+//   matrix_multiply   scalability/mp_matrix_multiply n T
+//   reduction         scalability/mp_reduction op strategy N T
+//   stream            scalability/mp_stream N T
+//   jacobi            scalability/mp_jacobi n iters tol T
+//   sort              scalability/mp_sort type algo N T cutoff oversample buckets seed
+//                     (merge and sample only; 0 for the other sort's tuning,
+//                     the default cutoff for the baseline of a sample sort)
+//   nbody             scalability/mp_nbody N steps T
+//   monte_carlo       scalability/mp_monte_carlo N T seed
+//   prefix_sum        programmability/mp_prefix_sum N T (--op sum only)
+//   segmented_scan    programmability/mp_segmented_scan N T L seed
+//   runtime_overhead  runtime_overhead/overhead_openmp T R, and T R N for parfor
+// The other benchmarks have no OpenMP version. `comparison` then sets every
+// timing of an OpenMP record beside the same timing of each Rust record of
+// the kernel that agrees on the params both have; the C programs' `chunk`
+// counts as Rust's `grain`, and their `critical` primitive as `mutex`.
+
+use crate::report::{self, param};
+use crate::results::Record;
+use crate::scalability::sort;
+use std::fmt;
+use std::path::Path;
+use std::process::Command;
+
+/// The implementation name of the C records.
+pub const OPENMP: &str = "openmp";
+
+// C param names standing for Rust ones
+const ALIASES: [(&str, &str); 1] = [("chunk", "grain")];
+
+// C param values standing for Rust ones: param, C value, Rust value
+const VALUE_ALIASES: [(&str, &str, &str); 1] = [("primitive", "critical", "mutex")];
+
+// the runtime_overhead primitives the C program measures too, but parfor
+const OVERHEAD_PRIMITIVES: [&str; 4] = ["parallel", "barrier", "mutex", "atomic"];
+
+/// One run of an OpenMP executable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invocation {
+    /// directory of the executable under openMP/src
+    pub dir: &'static str,
+    pub exe: &'static str,
+    pub args: Vec<String>,
+}
+
+impl fmt::Display for Invocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.exe, self.args.join(" "))
+    }
+}
+
+// the C executable of a Rust record's kernel, and its arguments for the
+// record's cell (None for cells it cannot run)
+struct Counterpart {
+    kernel: &'static str,
+    dir: &'static str,
+    exe: &'static str,
+    args: fn(&Record) -> Option<Vec<String>>,
+}
+
+fn params(record: &Record, names: &[&str]) -> Option<Vec<String>> {
+    names.iter().map(|name| param(record, name).map(str::to_string)).collect()
+}
+
+const COUNTERPARTS: [Counterpart; 14] = [
+    Counterpart {
+        kernel: "hist",
+        dir: "control",
+        exe: "control_openmp",
+        args: |r| {
+            let [strategy, dist, n, t, grain, pad, affinity]: [String; 7] = params(r, &["strategy", "dist", "N", "T", "grain", "pad", "affinity"])?.try_into().ok()?;
+            // an --input run's record does not name its file
+            if dist == "file" {
+                return None;
+            }
+            let mut args = vec![strategy, dist, n, t, "static".to_string(), grain, pad, affinity];
+            // no dump, then the input seed and the stripe count
+            if let Some(seed) = param(r, "seed") {
+                args.extend(["-".to_string(), seed.to_string()]);
+                args.extend(param(r, "stripes").map(str::to_string));
+            }
+            Some(args)
+        },
+    },
+    Counterpart { kernel: "hist2d", dir: "control", exe: "hist2d_openmp", args: |r| params(r, &["strategy", "dist", "N", "T", "grain", "seed"]) },
+    Counterpart { kernel: "fhist", dir: "control", exe: "fhist_openmp", args: |r| params(r, &["strategy", "N", "T", "bins", "edges", "grain", "seed"]) },
+    Counterpart { kernel: "false_sharing", dir: "control", exe: "false_sharing_openmp", args: |r| params(r, &["update", "N", "T", "affinity"]) },
+    Counterpart { kernel: "matmul", dir: "scalability", exe: "mp_matrix_multiply", args: |r| params(r, &["n", "T"]) },
+    Counterpart { kernel: "reduction", dir: "scalability", exe: "mp_reduction", args: |r| params(r, &["op", "strategy", "N", "T"]) },
+    Counterpart { kernel: "stream", dir: "scalability", exe: "mp_stream", args: |r| params(r, &["N", "T"]) },
+    Counterpart { kernel: "jacobi", dir: "scalability", exe: "mp_jacobi", args: |r| params(r, &["n", "iters", "tol", "T"]) },
+    // the C program has no counterpart of rayon's own sorts
+    Counterpart {
+        kernel: "sort",
+        dir: "scalability",
+        exe: "mp_sort",
+        args: |r| {
+            let [key, algo, n, t, seed]: [String; 5] = params(r, &["type", "algo", "N", "T", "seed"])?.try_into().ok()?;
+            // a record names only the tuning its sort reads
+            let tuning = match algo.as_str() {
+                "merge" => [param(r, "cutoff")?.to_string(), "0".to_string(), "0".to_string()],
+                "sample" => [sort::CUTOFF.to_string(), param(r, "oversample")?.to_string(), param(r, "buckets")?.to_string()],
+                _ => return None,
+            };
+            Some([key, algo, n, t].into_iter().chain(tuning).chain([seed]).collect())
+        },
+    },
+    Counterpart { kernel: "nbody", dir: "scalability", exe: "mp_nbody", args: |r| params(r, &["N", "steps", "T"]) },
+    Counterpart { kernel: "monte_carlo", dir: "scalability", exe: "mp_monte_carlo", args: |r| params(r, &["N", "T", "seed"]) },
+    // the C program only sums
+    Counterpart { kernel: "prefix_sum", dir: "programmability", exe: "mp_prefix_sum", args: |r| param(r, "op").is_none_or(|op| op == "sum").then(|| params(r, &["N", "T"])).flatten() },
+    Counterpart { kernel: "segmented_scan", dir: "programmability", exe: "mp_segmented_scan", args: |r| params(r, &["N", "T", "segment", "seed"]) },
+    Counterpart {
+        kernel: "overhead",
+        dir: "runtime_overhead",
+        exe: "overhead_openmp",
+        args: |r| match param(r, "primitive")? {
+            "parfor" => params(r, &["T", "R", "N"]),
+            primitive => OVERHEAD_PRIMITIVES.contains(&primitive).then(|| params(r, &["T", "R"])).flatten(),
+        },
+    },
+];
+
+/// The distinct OpenMP runs matching the cells of `records`, in order.
+pub fn invocations(records: &[Record]) -> Vec<Invocation> {
+    let mut invocations: Vec<Invocation> = Vec::new();
+    for record in records.iter().filter(|r| r.implementation != OPENMP) {
+        let Some(counterpart) = COUNTERPARTS.iter().find(|c| c.kernel == record.kernel) else { continue };
+        if let Some(args) = (counterpart.args)(record) {
+            let invocation = Invocation { dir: counterpart.dir, exe: counterpart.exe, args };
+            if !invocations.contains(&invocation) {
+                invocations.push(invocation);
+            }
+        }
+    }
+    invocations
+}
+
+/// Runs `invocation` from `dir` and returns the records it printed. A run
+/// that exits non-zero still counts if it printed records (a wrong result
+/// shows in their `correct` metric).
+pub fn run(dir: &Path, invocation: &Invocation) -> Result<Vec<Record>, String> {
+    let path = [dir.join(invocation.exe), dir.join(invocation.dir).join(invocation.exe)]
+        .into_iter()
+        .find(|p| p.is_file())
+        .ok_or_else(|| format!("{} not found in {} (build it with the run_*.sh scripts)", invocation.exe, dir.display()))?;
+    let output = Command::new(&path).args(&invocation.args).output().map_err(|e| format!("cannot run {}: {}", path.display(), e))?;
+    let (_, records) = report::parse_csv(&String::from_utf8_lossy(&output.stdout));
+    if records.is_empty() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("{} printed no results ({}): {}", invocation, output.status, stderr.trim()));
+    }
+    Ok(records)
+}
+
+fn rust_name(name: &str) -> String {
+    ALIASES.iter().find(|(c, _)| *c == name).map_or(name, |(_, rust)| rust).to_string()
+}
+
+fn rust_value<'a>(name: &str, value: &'a str) -> &'a str {
+    VALUE_ALIASES.iter().find(|(n, c, _)| *n == name && *c == value).map_or(value, |(_, _, rust)| rust)
+}
+
+/// One timing of an OpenMP cell beside the same timing of a Rust record.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pair {
+    /// the OpenMP record's label, with Rust param names and values
+    pub cell: String,
+    pub kernel: String,
+    /// the Rust record's params, then those only the OpenMP record has
+    pub params: Vec<(String, String)>,
+    pub implementation: String,
+    pub metric: String,
+    pub unit: String,
+    pub rust: f64,
+    pub openmp: f64,
+}
+
+impl Pair {
+    /// Rust time over OpenMP time: below 1 Rust was faster.
+    pub fn ratio(&self) -> f64 {
+        self.rust / self.openmp
+    }
+}
+
+/// The timings the OpenMP and Rust records of `records` have in common.
+pub fn pairs(records: &[Record]) -> Vec<Pair> {
+    let mut pairs = Vec::new();
+    for openmp in records.iter().filter(|r| r.implementation == OPENMP) {
+        let cell: Vec<(String, &str)> = openmp.params.iter().map(|(k, v)| (rust_name(k), rust_value(k, v))).collect();
+        let label = std::iter::once(openmp.kernel.clone()).chain(cell.iter().map(|(k, v)| format!("{}={}", k, v))).collect::<Vec<_>>().join(",");
+        let matching = records.iter().filter(|r| {
+            r.implementation != OPENMP && r.kernel == openmp.kernel && cell.iter().all(|(k, v)| param(r, k).is_none_or(|rv| rv == *v))
+        });
+        for rust in matching {
+            for metric in openmp.metrics.iter().filter(|m| matches!(m.unit.as_str(), "sec" | "ms" | "ns") && m.value > 0.0) {
+                if let Some(r) = rust.metrics.iter().find(|m| m.name == metric.name && m.unit == metric.unit) {
+                    let only_openmp = cell.iter().filter(|(k, _)| param(rust, k).is_none()).map(|(k, v)| (k.clone(), v.to_string()));
+                    pairs.push(Pair {
+                        cell: label.clone(),
+                        kernel: openmp.kernel.clone(),
+                        params: rust.params.iter().cloned().chain(only_openmp).collect(),
+                        implementation: rust.implementation.clone(),
+                        metric: metric.name.clone(),
+                        unit: metric.unit.clone(),
+                        rust: r.value,
+                        openmp: metric.value,
+                    });
+                }
+            }
+        }
+    }
+    pairs
+}
+
+/// The comparison table of `records`, one line per pair of timings.
+pub fn comparison(records: &[Record]) -> String {
+    let pairs = pairs(records);
+    if pairs.is_empty() {
+        return "No OpenMP timings to compare (no OpenMP cell matched a Rust record)\n".to_string();
+    }
+    let width = |f: fn(&Pair) -> usize| pairs.iter().map(f).max().unwrap_or(0);
+    let (cell, implementation, metric) = (width(|p| p.cell.len()), width(|p| p.implementation.len()).max(4), width(|p| p.metric.len()));
+    let mut out = format!("{:<cell$}  {:<metric$}  {:<implementation$}  {:>12}  {:>12}       rust/openmp\n", "cell", "metric", "impl", "rust", "openmp");
+    for p in &pairs {
+        out.push_str(&format!(
+            "{:<cell$}  {:<metric$}  {:<implementation$}  {:>12.6}  {:>12.6} {:<4}  {:.2}x\n",
+            p.cell, p.metric, p.implementation, p.rust, p.openmp, p.unit, p.ratio()
+        ));
+    }
+    let faster = pairs.iter().filter(|p| p.ratio() < 1.0).count();
+    out.push_str(&format!("{} timing(s) compared: Rust faster in {}, OpenMP faster in {}\n", pairs.len(), faster, pairs.len() - faster));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hist(implementation: &str, t: usize, grain: usize, time: f64) -> Record {
+        Record::new("hist", implementation)
+            .param("strategy", "atomic")
+            .param("dist", "uniform")
+            .param("N", 1000)
+            .param("T", t)
+            .param("grain", grain)
+            .param("pad", 0)
+            .param("affinity", "none")
+            .metric("time", time, "sec")
+            .metric("time_mean", time, "sec")
+    }
+
+    #[test]
+    fn test_invocations() {
+        let overhead = |primitive: &str| Record::new("overhead", "rust").param("primitive", primitive).param("T", 2).param("R", 10).metric("total", 1.0, "ms");
+        let parfor = |implementation: &str| Record::new("overhead", implementation).param("primitive", "parfor").param("T", 2).param("N", 100).param("R", 1000).metric("total", 1.0, "ms");
+        let records = [hist("rayon", 1, 0, 0.2), hist("threads", 1, 0, 0.3), hist("rayon", 2, 64, 0.1), overhead("barrier"), overhead("mutex"), overhead("fib"), parfor("sequential"), parfor("rayon")];
+        let runs: Vec<String> = invocations(&records).iter().map(|i| i.to_string()).collect();
+        assert_eq!(
+            runs,
+            [
+                "control_openmp atomic uniform 1000 1 static 0 0 none",
+                "control_openmp atomic uniform 1000 2 static 64 0 none",
+                "overhead_openmp 2 10",
+                "overhead_openmp 2 1000 100"
+            ]
+        );
+        assert!(invocations(&[Record::new("vector", "rayon").param("N", 1)]).is_empty());
+        let scan = |op: &str| Record::new("prefix_sum", "rayon").param("N", 100).param("T", 2).param("op", op);
+        let runs: Vec<String> = invocations(&[scan("mat2"), scan("sum")]).iter().map(|i| i.to_string()).collect();
+        assert_eq!(runs, ["mp_prefix_sum 100 2"]);
+        let sort = |algo: &str| Record::new("sort", "rayon").param("type", "u64").param("algo", algo).param("N", 100).param("T", 2);
+        let records = [sort("merge").param("cutoff", 16).param("seed", 1), sort("sample").param("oversample", 4).param("buckets", 8).param("seed", 1), sort("par_sort").param("seed", 1)];
+        let runs: Vec<String> = invocations(&records).iter().map(|i| i.to_string()).collect();
+        assert_eq!(runs, ["mp_sort u64 merge 100 2 16 0 0 1", "mp_sort u64 sample 100 2 4096 4 8 1"]);
+        let zipf = Record::new("hist", "rayon")
+            .param("strategy", "striped")
+            .param("dist", "zipf:1.5")
+            .param("N", 1000)
+            .param("T", 2)
+            .param("grain", 0)
+            .param("pad", 0)
+            .param("stripes", 4)
+            .param("affinity", "none")
+            .param("seed", 7);
+        assert_eq!(invocations(&[zipf])[0].to_string(), "control_openmp striped zipf:1.5 1000 2 static 0 0 none - 7 4");
+        let file = Record::new("hist", "rayon").param("strategy", "local").param("dist", "file").param("N", 1000).param("T", 2).param("grain", 0).param("pad", 0).param("affinity", "none").param("seed", 0);
+        assert!(invocations(&[file]).is_empty());
+    }
+
+    #[test]
+    fn test_pairs() {
+        let (_, openmp) = report::parse_csv(
+            "hist,openmp,strategy=atomic,dist=uniform,N=1000,T=2,sched=static,chunk=64,pad=0,affinity=none,time,0.050000,sec\n\
+             hist,openmp,strategy=atomic,dist=uniform,N=1000,T=2,sched=static,chunk=64,pad=0,affinity=none,correct,1,boolean\n\
+             overhead,openmp,primitive=critical,T=2,R=10,total,2.000000,ms\n",
+        );
+        let mut records = vec![hist("rayon", 1, 0, 0.2), hist("rayon", 2, 64, 0.1), hist("rayon", 2, 0, 0.3)];
+        records.push(Record::new("overhead", "rust").param("primitive", "barrier").param("T", 2).param("R", 10).metric("total", 0.5, "ms"));
+        records.push(Record::new("overhead", "rust").param("primitive", "mutex").param("T", 2).param("R", 10).metric("total", 1.0, "ms"));
+        records.extend(openmp);
+
+        let pairs = pairs(&records);
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(pairs[0].cell, "hist,strategy=atomic,dist=uniform,N=1000,T=2,sched=static,grain=64,pad=0,affinity=none");
+        assert_eq!(pairs[0].params.last(), Some(&("sched".to_string(), "static".to_string())));
+        assert_eq!((pairs[0].metric.as_str(), pairs[0].rust, pairs[0].ratio()), ("time", 0.1, 2.0));
+        assert_eq!(pairs[1].cell, "overhead,primitive=mutex,T=2,R=10");
+        assert_eq!((pairs[1].metric.as_str(), pairs[1].implementation.as_str(), pairs[1].ratio()), ("total", "rust", 0.5));
+        assert!(comparison(&records).ends_with("2 timing(s) compared: Rust faster in 1, OpenMP faster in 1\n"));
+    }
+}
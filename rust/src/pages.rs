@@ -0,0 +1,159 @@
+// Huge-page backing for the large benchmark buffers (--pages)
+// TLB pressure on multi-GB inputs shifts the Rust/OpenMP comparison, so the
+// big arrays can be placed on 2 MiB pages:
+//   default   the global allocator (4 KiB pages, THP as the system decides)
+//   thp       an anonymous mapping advised with madvise(MADV_HUGEPAGE)
+//   hugetlb   an explicit MAP_HUGETLB mapping from the reserved pool
+//             (vm.nr_hugepages); needs --features hugetlb
+// Mappings are rounded up to whole huge pages. The choice is recorded as the
+// `pages` param of every result of the binaries that take the option.
+
+use memmap2::{MmapMut, MmapOptions};
+use std::ops::{Deref, DerefMut};
+
+const HUGE_PAGE: usize = 2 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pages {
+    Default,
+    Thp,
+    Hugetlb,
+}
+
+impl Pages {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "default" => Ok(Pages::Default),
+            "thp" if cfg!(target_os = "linux") => Ok(Pages::Thp),
+            "hugetlb" if cfg!(all(target_os = "linux", feature = "hugetlb")) => Ok(Pages::Hugetlb),
+            "hugetlb" => Err("--pages hugetlb needs a Linux build with --features hugetlb".to_string()),
+            _ => Err(format!("unknown page mode: {} (use default|thp|hugetlb)", s)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Pages::Default => "default",
+            Pages::Thp => "thp",
+            Pages::Hugetlb => "hugetlb",
+        }
+    }
+}
+
+/// Element types for which the all-zero bit pattern of a fresh mapping is a
+/// valid value, and the default.
+pub trait Element: Copy + Default + Send + Sync + private::Sealed {}
+
+mod private {
+    pub trait Sealed {}
+}
+
+macro_rules! elements {
+    ($($t:ty),*) => {
+        $(
+            impl private::Sealed for $t {}
+            impl Element for $t {}
+        )*
+    };
+}
+
+elements!(u8, u32, u64, f32, f64, crate::scan::Mat2);
+
+/// A fixed-length buffer on the pages chosen by `Pages`; derefs to a slice.
+pub struct PageVec<T: Element> {
+    storage: Storage<T>,
+}
+
+enum Storage<T> {
+    Heap(Vec<T>),
+    Mapped { map: MmapMut, len: usize },
+}
+
+impl<T: Element> PageVec<T> {
+    /// `len` copies of `value`.
+    pub fn filled(len: usize, value: T, pages: Pages) -> Result<Self, String> {
+        let bytes = len * std::mem::size_of::<T>();
+        if pages == Pages::Default || bytes == 0 {
+            return Ok(PageVec { storage: Storage::Heap(vec![value; len]) });
+        }
+
+        let mut buffer = PageVec::zeroed(len, pages)?;
+        buffer.fill(value);
+        Ok(buffer)
+    }
+
+    /// `len` zeroes, none of their pages touched yet: large heap buffers come
+    /// from calloc, which maps fresh zero pages, and mappings start out empty.
+    pub fn zeroed(len: usize, pages: Pages) -> Result<Self, String> {
+        let bytes = len * std::mem::size_of::<T>();
+        if pages == Pages::Default || bytes == 0 {
+            return Ok(PageVec { storage: Storage::Heap(vec![T::default(); len]) });
+        }
+
+        let rounded = bytes.div_ceil(HUGE_PAGE) * HUGE_PAGE;
+        let map = map(rounded, pages).map_err(|e| format!("cannot map {} bytes with --pages {}: {}", rounded, pages.name(), e))?;
+        Ok(PageVec { storage: Storage::Mapped { map, len } })
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn map(bytes: usize, pages: Pages) -> std::io::Result<MmapMut> {
+    let mut options = MmapOptions::new();
+    options.len(bytes);
+    if pages == Pages::Hugetlb {
+        options.huge(None);
+    }
+    let map = options.map_anon()?;
+    if pages == Pages::Thp {
+        map.advise(memmap2::Advice::HugePage)?;
+    }
+    Ok(map)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn map(bytes: usize, _pages: Pages) -> std::io::Result<MmapMut> {
+    MmapOptions::new().len(bytes).map_anon()
+}
+
+impl<T: Element> Deref for PageVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        match &self.storage {
+            Storage::Heap(v) => v,
+            // SAFETY: the mapping is page-aligned, at least len * size_of::<T>()
+            // bytes long and lives as long as self; T accepts any bit pattern
+            // the buffer can hold (zeroes or values written through DerefMut)
+            Storage::Mapped { map, len } => unsafe { std::slice::from_raw_parts(map.as_ptr() as *const T, *len) },
+        }
+    }
+}
+
+impl<T: Element> DerefMut for PageVec<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        match &mut self.storage {
+            Storage::Heap(v) => v,
+            // SAFETY: as in deref, and &mut self makes the access exclusive
+            Storage::Mapped { map, len } => unsafe { std::slice::from_raw_parts_mut(map.as_mut_ptr() as *mut T, *len) },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_vec() {
+        assert!(Pages::parse("huge").is_err());
+        for pages in [Pages::Default, Pages::Thp] {
+            let mut v = PageVec::filled(1_000_003, 7u64, pages).unwrap();
+            assert_eq!(v.len(), 1_000_003);
+            assert!(v.iter().all(|&x| x == 7));
+            v[1_000_002] = 9;
+            assert_eq!(v.iter().sum::<u64>(), 7 * 1_000_002 + 9);
+        }
+        assert!(PageVec::<f64>::filled(0, 1.0, Pages::Thp).unwrap().is_empty());
+        assert!(PageVec::<f32>::zeroed(4097, Pages::Thp).unwrap().iter().all(|&x| x == 0.0));
+    }
+}
@@ -0,0 +1,160 @@
+// OpenMP-style loop layer on top of the Chase-Lev scheduler
+//   parallel_for!(i in 0..n, schedule = dynamic(64), { ... })
+// mirrors `#pragma omp parallel for schedule(dynamic, 64)`: the loop is split
+// among one task per scheduler worker, and the schedule decides how those
+// tasks pick iterations:
+//   static        one contiguous block per task (OpenMP's default)
+//   static(c)     chunks of c dealt round-robin
+//   dynamic(c)    tasks grab the next c iterations from a shared counter
+//   guided(c)     like dynamic, chunks shrink with the remaining work, never below c
+// Inside `Scheduler::install` the loop runs on that scheduler; elsewhere it
+// runs sequentially on the calling thread. The body is `Fn(usize) + Sync`,
+// so shared results are written through atomics or locks, as shared
+// variables are in OpenMP.
+
+use crate::scheduler;
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Schedule {
+    /// chunk 0 = one block per task
+    Static(usize),
+    Dynamic(usize),
+    Guided(usize),
+}
+
+impl Schedule {
+    // e.g. "static", "dynamic(64)"
+    pub fn name(self) -> String {
+        match self {
+            Schedule::Static(0) => "static".to_string(),
+            Schedule::Static(c) => format!("static({})", c),
+            Schedule::Dynamic(c) => format!("dynamic({})", c),
+            Schedule::Guided(c) => format!("guided({})", c),
+        }
+    }
+}
+
+/// Runs `body(i)` for every `i` in `range` according to `schedule`.
+pub fn parallel_for<F>(range: Range<usize>, schedule: Schedule, body: F)
+where
+    F: Fn(usize) + Sync,
+{
+    let (start, end) = (range.start, range.end);
+    if start >= end {
+        return;
+    }
+    let tasks = scheduler::current_num_threads();
+    let body = &body;
+    let run = |from: usize, to: usize| (from..to.min(end)).for_each(body);
+
+    match schedule {
+        Schedule::Static(chunk) => {
+            let chunk = if chunk == 0 { (end - start).div_ceil(tasks) } else { chunk };
+            scheduler::scope(|s| {
+                for t in 0..tasks {
+                    s.spawn(move |_| {
+                        let mut from = start + t * chunk;
+                        while from < end {
+                            run(from, from + chunk);
+                            from += tasks * chunk;
+                        }
+                    });
+                }
+            });
+        }
+        Schedule::Dynamic(chunk) => {
+            let chunk = chunk.max(1);
+            let next = AtomicUsize::new(start);
+            let next = &next;
+            scheduler::scope(|s| {
+                for _ in 0..tasks {
+                    s.spawn(move |_| loop {
+                        let from = next.fetch_add(chunk, Ordering::Relaxed);
+                        if from >= end {
+                            break;
+                        }
+                        run(from, from + chunk);
+                    });
+                }
+            });
+        }
+        Schedule::Guided(min_chunk) => {
+            let min_chunk = min_chunk.max(1);
+            let next = AtomicUsize::new(start);
+            let next = &next;
+            scheduler::scope(|s| {
+                for _ in 0..tasks {
+                    s.spawn(move |_| loop {
+                        let from = next.load(Ordering::Relaxed);
+                        if from >= end {
+                            break;
+                        }
+                        let chunk = ((end - from) / (2 * tasks)).max(min_chunk);
+                        if next
+                            .compare_exchange_weak(from, from + chunk, Ordering::Relaxed, Ordering::Relaxed)
+                            .is_ok()
+                        {
+                            run(from, from + chunk);
+                        }
+                    });
+                }
+            });
+        }
+    }
+}
+
+/// `parallel_for!(i in range, [schedule = kind[(chunk)],] { body })`
+/// with kind one of `static`, `dynamic`, `guided`; see the module docs.
+#[macro_export]
+macro_rules! parallel_for {
+    ($i:ident in $range:expr, schedule = static($chunk:expr), $body:block) => {
+        $crate::parfor::parallel_for($range, $crate::parfor::Schedule::Static($chunk), |$i: usize| $body)
+    };
+    ($i:ident in $range:expr, schedule = static, $body:block) => {
+        $crate::parfor::parallel_for($range, $crate::parfor::Schedule::Static(0), |$i: usize| $body)
+    };
+    ($i:ident in $range:expr, schedule = dynamic($chunk:expr), $body:block) => {
+        $crate::parfor::parallel_for($range, $crate::parfor::Schedule::Dynamic($chunk), |$i: usize| $body)
+    };
+    ($i:ident in $range:expr, schedule = dynamic, $body:block) => {
+        $crate::parfor::parallel_for($range, $crate::parfor::Schedule::Dynamic(1), |$i: usize| $body)
+    };
+    ($i:ident in $range:expr, schedule = guided($chunk:expr), $body:block) => {
+        $crate::parfor::parallel_for($range, $crate::parfor::Schedule::Guided($chunk), |$i: usize| $body)
+    };
+    ($i:ident in $range:expr, schedule = guided, $body:block) => {
+        $crate::parfor::parallel_for($range, $crate::parfor::Schedule::Guided(1), |$i: usize| $body)
+    };
+    ($i:ident in $range:expr, $body:block) => {
+        $crate::parfor::parallel_for($range, $crate::parfor::Schedule::Static(0), |$i: usize| $body)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scheduler::Scheduler;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn test_every_index_once() {
+        let scheduler = Scheduler::new(3);
+        let hits: Vec<AtomicU32> = (0..1000).map(|_| AtomicU32::new(0)).collect();
+        let hit = |i: usize| {
+            hits[i].fetch_add(1, Ordering::Relaxed);
+        };
+
+        scheduler.install(|| {
+            parallel_for!(i in 10..1000, { hit(i) });
+            parallel_for!(i in 10..1000, schedule = static(7), { hit(i) });
+            parallel_for!(i in 10..1000, schedule = dynamic(16), { hit(i) });
+            parallel_for!(i in 10..1000, schedule = guided(4), { hit(i) });
+        });
+        // outside install: sequential on the caller
+        parallel_for!(i in 10..1000, schedule = dynamic, { hit(i) });
+
+        assert!(hits[..10].iter().all(|h| h.load(Ordering::Relaxed) == 0));
+        assert!(hits[10..].iter().all(|h| h.load(Ordering::Relaxed) == 5));
+    }
+}
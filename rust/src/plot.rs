@@ -0,0 +1,242 @@
+// Charts of result files as images (run_all_benchmarks plot)
+// For every kernel in the files, plotters draws up to two charts into the
+// output directory:
+//   <kernel>_speedup.<ext>  speedup over the thread count T, with the ideal
+//                           speedup dashed (report::speedups)
+//   <kernel>_time.<ext>     time over the problem size (n, N or MiB), both
+//                           axes logarithmic (report::times)
+// The records of an --openmp file are overlaid on the same charts as dashed
+// lines, so both implementations of a kernel read off one picture. Drawing
+// needs --features plot; PNG text uses the system's sans-serif font, SVG
+// leaves fonts to the viewer.
+
+use crate::report::{self, Series, Source};
+use crate::results::Record;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Image {
+    Png,
+    Svg,
+}
+
+impl Image {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "png" => Ok(Image::Png),
+            "svg" => Ok(Image::Svg),
+            _ => Err(format!("unknown image format: {} (use png|svg)", s)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Image::Png => "png",
+            Image::Svg => "svg",
+        }
+    }
+}
+
+/// One chart to draw: its lines, each marked when it is overlaid.
+#[derive(Debug)]
+pub struct Chart {
+    /// file name without extension
+    pub name: String,
+    pub title: String,
+    pub x_desc: String,
+    pub y_desc: String,
+    pub lines: Vec<(Series, bool)>,
+    /// speedup chart: linear axes and the ideal line; otherwise log axes
+    pub speedup: bool,
+}
+
+/// The charts of `sources`, with the records of `openmp` overlaid.
+pub fn charts(sources: &[Source], openmp: Option<&Source>) -> Vec<Chart> {
+    let tag = sources.len() > 1;
+    let own: Vec<(&str, &Record)> = sources.iter().flat_map(|s| s.records.iter().map(move |r| (s.name.as_str(), r))).collect();
+    let overlay: Vec<(&str, &Record)> = openmp.map(|s| s.records.iter().map(|r| (s.name.as_str(), r)).collect()).unwrap_or_default();
+    let mut kernels: Vec<&str> = Vec::new();
+    for (_, record) in own.iter().chain(&overlay) {
+        if !kernels.contains(&record.kernel.as_str()) {
+            kernels.push(&record.kernel);
+        }
+    }
+
+    let mut charts = Vec::new();
+    for kernel in kernels {
+        let (mine, theirs) = (of_kernel(&own, kernel), of_kernel(&overlay, kernel));
+        let file = kernel.replace(|c: char| !c.is_ascii_alphanumeric() && c != '-', "_");
+
+        let lines = |mine: Vec<Series>, theirs: Vec<Series>| -> Vec<(Series, bool)> { mine.into_iter().map(|s| (s, false)).chain(theirs.into_iter().map(|s| (s, true))).collect() };
+        let speedups = lines(report::speedups(&mine, tag), report::speedups(&theirs, false));
+        if !speedups.is_empty() {
+            charts.push(Chart {
+                name: format!("{}_speedup", file),
+                title: format!("{}: speedup", kernel),
+                x_desc: "threads (T)".to_string(),
+                y_desc: "speedup (x)".to_string(),
+                lines: speedups,
+                speedup: true,
+            });
+        }
+        let all: Vec<(&str, &Record)> = mine.iter().chain(&theirs).copied().collect();
+        if let Some(size) = report::size_param(&all) {
+            let times = lines(report::times(&mine, size, tag), report::times(&theirs, size, false));
+            if !times.is_empty() {
+                charts.push(Chart {
+                    name: format!("{}_time", file),
+                    title: format!("{}: time over {}", kernel, size),
+                    x_desc: size.to_string(),
+                    y_desc: "time (sec)".to_string(),
+                    lines: times,
+                    speedup: false,
+                });
+            }
+        }
+    }
+    charts
+}
+
+fn of_kernel<'a>(records: &[(&'a str, &'a Record)], kernel: &str) -> Vec<(&'a str, &'a Record)> {
+    records.iter().copied().filter(|(_, r)| r.kernel == kernel).collect()
+}
+
+/// Draws the charts of `sources`, with `openmp` overlaid, into `dir`;
+/// returns the files written.
+#[cfg(feature = "plot")]
+pub fn plot(sources: &[Source], openmp: Option<&Source>, dir: &Path, image: Image) -> Result<Vec<PathBuf>, String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let mut written = Vec::new();
+    for chart in charts(sources, openmp) {
+        let path = dir.join(format!("{}.{}", chart.name, image.name()));
+        draw::draw(&chart, &path, image).map_err(|e| format!("{}: {}", path.display(), e))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+#[cfg(not(feature = "plot"))]
+pub fn plot(_sources: &[Source], _openmp: Option<&Source>, _dir: &Path, _image: Image) -> Result<Vec<PathBuf>, String> {
+    Err("plot needs a build with --features plot".to_string())
+}
+
+#[cfg(feature = "plot")]
+mod draw {
+    use super::{Chart, Image};
+    use plotters::coord::ranged1d::{AsRangedCoord, ValueFormatter};
+    use plotters::coord::Shift;
+    use plotters::prelude::*;
+    use std::path::Path;
+
+    const SIZE: (u32, u32) = (900, 560);
+
+    pub fn draw(chart: &Chart, path: &Path, image: Image) -> Result<(), String> {
+        let (xs, ys) = (chart.lines.iter().flat_map(|(s, _)| s.points.iter().map(|&(x, _)| x as f64)), chart.lines.iter().flat_map(|(s, _)| s.points.iter().map(|&(_, y)| y)));
+        let (x_min, x_max) = xs.fold((f64::INFINITY, 0.0f64), |(lo, hi), x| (lo.min(x), hi.max(x)));
+        let (y_min, y_max) = ys.fold((f64::INFINITY, 0.0f64), |(lo, hi), y| (lo.min(y), hi.max(y)));
+        if chart.speedup {
+            // the ideal line runs from (x_min, x_min) to (x_max, x_max)
+            let (x, y) = ((x_min - 0.5).max(0.0)..x_max + 0.5, 0.0..y_max.max(x_max) * 1.1);
+            match image {
+                Image::Png => on(BitMapBackend::new(path, SIZE).into_drawing_area(), chart, x, y, Some((x_min, x_max))),
+                Image::Svg => on(SVGBackend::new(path, SIZE).into_drawing_area(), chart, x, y, Some((x_min, x_max))),
+            }
+        } else {
+            let (x, y) = ((x_min * 0.8..x_max * 1.25).log_scale(), (y_min * 0.8..y_max * 1.25).log_scale());
+            match image {
+                Image::Png => on(BitMapBackend::new(path, SIZE).into_drawing_area(), chart, x, y, None),
+                Image::Svg => on(SVGBackend::new(path, SIZE).into_drawing_area(), chart, x, y, None),
+            }
+        }
+    }
+
+    // tick labels without float noise: 0.01 rather than 0.0100000000001
+    fn label(v: f64) -> String {
+        if v != 0.0 && v.abs() < 1e-3 {
+            return format!("{:.0e}", v);
+        }
+        let fixed = format!("{:.6}", v);
+        fixed.trim_end_matches('0').trim_end_matches('.').to_string()
+    }
+
+    fn on<DB, X, Y>(root: DrawingArea<DB, Shift>, chart: &Chart, x: X, y: Y, ideal: Option<(f64, f64)>) -> Result<(), String>
+    where
+        DB: DrawingBackend,
+        X: AsRangedCoord<Value = f64>,
+        Y: AsRangedCoord<Value = f64>,
+        X::CoordDescType: ValueFormatter<f64>,
+        Y::CoordDescType: ValueFormatter<f64>,
+    {
+        let error = |e: DrawingAreaErrorKind<DB::ErrorType>| e.to_string();
+        root.fill(&WHITE).map_err(error)?;
+        let mut context = ChartBuilder::on(&root)
+            .caption(&chart.title, ("sans-serif", 22))
+            .margin(16)
+            .x_label_area_size(44)
+            .y_label_area_size(64)
+            .build_cartesian_2d(x, y)
+            .map_err(error)?;
+        context
+            .configure_mesh()
+            .x_desc(&chart.x_desc)
+            .y_desc(&chart.y_desc)
+            .x_label_formatter(&|v| label(*v))
+            .y_label_formatter(&|v| label(*v))
+            .draw()
+            .map_err(error)?;
+
+        if let Some((lo, hi)) = ideal {
+            let style = BLACK.mix(0.45).stroke_width(1);
+            context
+                .draw_series(DashedLineSeries::new([(lo, lo), (hi, hi)], 6, 4, style))
+                .map_err(error)?
+                .label("ideal")
+                .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], style));
+        }
+        for (i, (series, overlaid)) in chart.lines.iter().enumerate() {
+            let color = Palette99::pick(i).to_rgba();
+            let style = color.stroke_width(2);
+            let points: Vec<(f64, f64)> = series.points.iter().map(|&(x, y)| (x as f64, y)).collect();
+            let drawn = if *overlaid {
+                context.draw_series(DashedLineSeries::new(points.clone(), 8, 5, style))
+            } else {
+                context.draw_series(LineSeries::new(points.clone(), style))
+            };
+            drawn.map_err(error)?.label(&series.name).legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], style));
+            context.draw_series(points.into_iter().map(|p| Circle::new(p, 3, color.filled()))).map_err(error)?;
+        }
+        context
+            .configure_series_labels()
+            .position(SeriesLabelPosition::UpperLeft)
+            .background_style(WHITE.mix(0.85))
+            .border_style(BLACK)
+            .draw()
+            .map_err(error)?;
+        root.present().map_err(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_charts() {
+        let cell = |implementation: &str, n: usize, t: usize, time: f64| Record::new("hist", implementation).param("N", n).param("T", t).metric("time", time, "sec");
+        let rust = Source { name: "rust.csv".to_string(), metadata: Vec::new(), records: vec![cell("rayon", 100, 1, 0.4), cell("rayon", 100, 2, 0.2), cell("rayon", 400, 1, 1.6)] };
+        let openmp = Source { name: "omp.csv".to_string(), metadata: Vec::new(), records: vec![cell("openmp", 100, 1, 0.5), cell("openmp", 100, 4, 0.125)] };
+
+        let charts = charts(std::slice::from_ref(&rust), Some(&openmp));
+        let names: Vec<&str> = charts.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["hist_speedup", "hist_time"]);
+        let speedup: Vec<(&str, bool)> = charts[0].lines.iter().map(|(s, overlaid)| (s.name.as_str(), *overlaid)).collect();
+        assert_eq!(speedup, [("rayon N=100", false), ("openmp", true)]);
+        assert_eq!(charts[0].lines[1].0.points, [(1, 1.0), (4, 4.0)]);
+        // only the Rust T=1 records cover two sizes
+        assert_eq!(charts[1].lines.len(), 1);
+        assert_eq!(charts[1].lines[0].0.points, [(100, 0.4), (400, 1.6)]);
+
+        assert_eq!(Image::parse("svg"), Ok(Image::Svg));
+        assert!(Image::parse("gif").is_err());
+    }
+}
@@ -0,0 +1,209 @@
+// Fork-join idioms benchmark (Rust version)
+// The same sum reduction written three ways with Rayon:
+//   par_iter    data.par_iter().with_min_len(grain).sum()
+//   join_tree   manual binary split with rayon::join down to `grain` elements
+//   scope_chan  rayon::scope + one spawn per `grain` chunk, partials sent over a channel
+// Each idiom runs across a sweep of grain sizes; `relative` is its time over
+// par_iter's at the same grain, so >1 is extra overhead of that idiom.
+// par_iter is the direct counterpart of
+//   #pragma omp parallel for reduction(+:sum) schedule(dynamic, grain)
+//
+// Options:
+//   --size N           elements (default 10000000)
+//   --threads T        threads (default 8; a count or max, cores, half, a/b)
+//   --reps R           timed runs per cell, the best is reported (default 5)
+//   --warmup W         untimed runs before the timed ones (default 1; 0 for a cold start)
+//   --format FMT       text (default, CSV lines below) | jsonl | json | csv | markdown
+//   --upload URL       POST each result as JSON to an HTTP collector
+//   --prometheus FILE  keep FILE updated with Prometheus gauges for each result
+//   --store FILE       append the run to a SQLite database (--features store)
+//   --log FMT          pretty | json execution log on stderr (RUST_LOG filters)
+//   --describe         print the JSON description of arguments and metrics, then exit
+//
+// Output (CSV-style):
+//   forkjoin,rayon,idiom=join_tree,N=10000000,T=8,grain=4096,time,0.001234,sec
+//   forkjoin,rayon,idiom=join_tree,N=10000000,T=8,grain=4096,relative,1.05,x
+// With R > 1 the records also carry time_mean, time_median and time_stddev.
+
+use crate::alloc_track::AllocStats;
+use crate::cli;
+use crate::describe::{Description, Param};
+use crate::interrupt;
+use crate::kernels::{Benchmark, Suite};
+use crate::logging::Phase;
+use crate::results::{Record, Reporter};
+use crate::stats::{Repeat, Stats};
+use crate::threads;
+use clap::{ArgMatches, CommandFactory, Parser};
+use rayon::prelude::*;
+use std::sync::mpsc;
+use tracing::info_span;
+
+const DEFAULT_N: usize = 10_000_000;
+const DEFAULT_THREADS: usize = 8;
+const GRAINS: [usize; 6] = [256, 1024, 4096, 16384, 65536, 262144];
+// best-of repetitions per cell unless --reps is given
+const REPS: usize = 5;
+
+const IDIOMS: [&str; 3] = ["par_iter", "join_tree", "scope_chan"];
+
+fn sum_par_iter(data: &[u64], grain: usize) -> u64 {
+    data.par_iter().with_min_len(grain).sum()
+}
+
+fn sum_join_tree(data: &[u64], grain: usize) -> u64 {
+    if data.len() <= grain {
+        return data.iter().sum();
+    }
+    let (left, right) = data.split_at(data.len() / 2);
+    let (a, b) = rayon::join(|| sum_join_tree(left, grain), || sum_join_tree(right, grain));
+    a + b
+}
+
+fn sum_scope_chan(data: &[u64], grain: usize) -> u64 {
+    let (tx, rx) = mpsc::channel();
+    rayon::scope(|s| {
+        for chunk in data.chunks(grain) {
+            let tx = tx.clone();
+            s.spawn(move |_| {
+                let _ = tx.send(chunk.iter().sum::<u64>());
+            });
+        }
+    });
+    drop(tx);
+    rx.iter().sum()
+}
+
+fn run_idiom(idiom: &str, data: &[u64], grain: usize) -> u64 {
+    match idiom {
+        "par_iter" => sum_par_iter(data, grain),
+        "join_tree" => sum_join_tree(data, grain),
+        "scope_chan" => sum_scope_chan(data, grain),
+        _ => unreachable!("unknown idiom {}", idiom),
+    }
+}
+
+// times of the repeated runs in seconds and the fastest run's allocations;
+// exits if any run returns the wrong sum
+fn time_idiom(idiom: &str, data: &[u64], grain: usize, expected: u64, repeat: Repeat) -> (Stats, Option<AllocStats>) {
+    let (stats, _, allocs) = repeat.measure(|| {
+        let sum = run_idiom(idiom, data, grain);
+        if sum != expected {
+            eprintln!("{} returned {} instead of {} (grain={})", idiom, sum, expected, grain);
+            std::process::exit(3);
+        }
+    });
+    (stats, allocs)
+}
+
+// answer to --describe
+pub fn description() -> Description {
+    Description::new("fork_join", "forkjoin", "one sum reduction as par_iter, join_tree and scope_chan over a sweep of grains")
+        .option(Param::integer("--size", 1, "elements").default(DEFAULT_N))
+        .option(Param::threads("--threads", "threads").default(DEFAULT_THREADS))
+        .params(&["idiom", "N", "T", "grain"])
+        .timing("time", "sec")
+        .metric("relative", "x")
+        .reps_default(REPS)
+}
+
+/// One sum reduction as par_iter, join_tree and scope_chan over a sweep of grains
+#[derive(Debug, Clone, Parser)]
+#[command(name = "fork_join")]
+pub struct Args {
+    /// elements
+    #[arg(long, value_name = "N", value_parser = cli::positive, default_value_t = DEFAULT_N)]
+    pub size: usize,
+    /// threads (a count or max, cores, half, quarter, a/b)
+    #[arg(long, value_name = "T", value_parser = threads::parse_spec, default_value_t = DEFAULT_THREADS)]
+    pub threads: usize,
+    #[command(flatten)]
+    pub common: cli::Common,
+}
+
+pub struct ForkJoin;
+
+impl Benchmark for ForkJoin {
+    fn name(&self) -> &'static str {
+        "fork_join"
+    }
+
+    fn label(&self) -> &'static str {
+        "Fork-join idioms"
+    }
+
+    fn suite(&self) -> Suite {
+        Suite::Programmability
+    }
+
+    fn description(&self) -> Description {
+        description()
+    }
+
+    fn command(&self) -> clap::Command {
+        Args::command()
+    }
+
+    fn run(&self, args: &ArgMatches, reporter: &mut Reporter) -> i32 {
+        run(&cli::from_matches(args), reporter)
+    }
+}
+
+pub fn run(args: &Args, reporter: &mut Reporter) -> i32 {
+    let repeat = args.common.reps.repeat(REPS);
+    let Args { size: n, threads, .. } = *args;
+
+    let (pool, data, expected) = Phase::Setup.run(|| {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        let data: Vec<u64> = (0..n as u64).collect();
+        let expected = data.iter().sum::<u64>();
+        (pool, data, expected)
+    });
+
+    if reporter.is_text() {
+        println!("=== Rust Fork-Join Idioms Benchmark (Programmability) ===");
+        println!("Reduction over N = {} elements, T = {} threads, best of {}", n, threads, repeat.reps);
+        println!();
+    }
+
+    pool.install(|| {
+        for grain in GRAINS {
+            if interrupt::requested() {
+                return;
+            }
+            let (baseline, baseline_allocs) = info_span!("forkjoin", idiom = "par_iter", n, threads, grain)
+                .in_scope(|| time_idiom("par_iter", &data, grain, expected, repeat));
+            for idiom in IDIOMS {
+                let (time, allocs) = if idiom == "par_iter" {
+                    (baseline, baseline_allocs)
+                } else {
+                    info_span!("forkjoin", idiom, n, threads, grain).in_scope(|| time_idiom(idiom, &data, grain, expected, repeat))
+                };
+                let relative = time.min / baseline.min;
+
+                if reporter.is_text() {
+                    let prefix = format!("forkjoin,rayon,idiom={},N={},T={},grain={}", idiom, n, threads, grain);
+                    println!("{},time,{:.6},sec{}", prefix, time.min, time.summary());
+                    println!("{},relative,{:.2},x", prefix, relative);
+                }
+
+                reporter.record(
+                    &Record::new("forkjoin", "rayon")
+                        .param("idiom", idiom)
+                        .param("N", n)
+                        .param("T", threads)
+                        .param("grain", grain)
+                        .timing("time", &time, "sec")
+                        .metric("relative", relative, "x")
+                        .allocs(allocs)
+                        .energy(time.energy)
+                        .peak_rss(time.peak_rss),
+                );
+            }
+        }
+    });
+    0
+}
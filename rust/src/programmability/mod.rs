@@ -0,0 +1,6 @@
+// Kernels hosted in the library (see kernels.rs)
+
+pub mod fork_join;
+pub mod parallel_for;
+pub mod prefix_sum;
+pub mod segmented_scan;
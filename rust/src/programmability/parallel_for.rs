@@ -0,0 +1,209 @@
+// parallel_for! vs raw Rayon (Rust version)
+// Compares the pragma-like loop layer (`parfor`, on the Chase-Lev scheduler)
+// with the equivalent Rayon code, for a balanced and an imbalanced loop body:
+//   uniform     every iteration does the same work
+//   triangular  iteration i does work proportional to i (same total)
+// `relative` is the time over raw Rayon's for the same workload.
+//
+// Options:
+//   --size N           iterations (default 200000)
+//   --threads T        threads (default 8; a count or max, cores, half, a/b)
+//   --reps R           timed runs per cell, the best is reported (default 5)
+//   --warmup W         untimed runs before the timed ones (default 1; 0 for a cold start)
+//   --format FMT       text (default, CSV lines below) | jsonl | json | csv | markdown
+//   --upload URL       POST each result as JSON to an HTTP collector
+//   --prometheus FILE  keep FILE updated with Prometheus gauges for each result
+//   --store FILE       append the run to a SQLite database (--features store)
+//   --log FMT          pretty | json execution log on stderr (RUST_LOG filters)
+//   --describe         print the JSON description of arguments and metrics, then exit
+//
+// Output (CSV-style):
+//   parfor,parfor,work=triangular,schedule=dynamic(64),N=200000,T=8,time,0.004321,sec
+//   parfor,parfor,work=triangular,schedule=dynamic(64),N=200000,T=8,relative,1.02,x
+// With R > 1 the records also carry time_mean, time_median and time_stddev.
+
+use crate::alloc_track::Region;
+use crate::cli;
+use crate::describe::{Description, Param};
+use crate::interrupt;
+use crate::kernels::{Benchmark, Suite};
+use crate::logging::Phase;
+use crate::parallel_for;
+use crate::results::{Record, Reporter};
+use crate::scheduler::Scheduler;
+use crate::threads;
+use clap::{ArgMatches, CommandFactory, Parser};
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+use tracing::info_span;
+
+const DEFAULT_N: usize = 200_000;
+const DEFAULT_THREADS: usize = 8;
+// average inner-loop length per iteration
+const WORK: usize = 64;
+// best-of repetitions per cell unless --reps is given
+const REPS: usize = 5;
+
+// (impl, schedule) variants in output order; rayon first as the baseline
+const VARIANTS: [(&str, &str); 5] = [
+    ("rayon", "adaptive"),
+    ("parfor", "static"),
+    ("parfor", "static(64)"),
+    ("parfor", "dynamic(64)"),
+    ("parfor", "guided(64)"),
+];
+
+fn cost(work: &str, i: usize, n: usize) -> usize {
+    match work {
+        "uniform" => WORK,
+        _ => 2 * WORK * i / n,
+    }
+}
+
+// deterministic busy work whose result depends on every step
+fn kernel(i: usize, steps: usize) -> u64 {
+    let mut acc = i as u64;
+    for j in 0..steps as u64 {
+        acc = acc.wrapping_mul(6364136223846793005).wrapping_add(j | 1);
+    }
+    acc
+}
+
+fn run_variant(variant: (&str, &str), work: &str, n: usize, out: &[AtomicU64], rayon: &rayon::ThreadPool, sched: &Scheduler) {
+    let body = |i: usize| out[i].store(kernel(i, cost(work, i, n)), Ordering::Relaxed);
+
+    match variant {
+        ("rayon", _) => rayon.install(|| (0..n).into_par_iter().for_each(body)),
+        (_, "static") => sched.install(|| parallel_for!(i in 0..n, schedule = static, { body(i) })),
+        (_, "static(64)") => sched.install(|| parallel_for!(i in 0..n, schedule = static(64), { body(i) })),
+        (_, "dynamic(64)") => sched.install(|| parallel_for!(i in 0..n, schedule = dynamic(64), { body(i) })),
+        (_, "guided(64)") => sched.install(|| parallel_for!(i in 0..n, schedule = guided(64), { body(i) })),
+        _ => unreachable!("unknown variant {:?}", variant),
+    }
+}
+
+// answer to --describe
+pub fn description() -> Description {
+    Description::new("parallel_for", "parfor", "parallel_for! schedules vs raw Rayon on uniform and triangular loops")
+        .option(Param::integer("--size", 1, "iterations").default(DEFAULT_N))
+        .option(Param::threads("--threads", "threads").default(DEFAULT_THREADS))
+        .params(&["work", "schedule", "N", "T"])
+        .timing("time", "sec")
+        .metric("relative", "x")
+        .reps_default(REPS)
+}
+
+/// parallel_for! schedules vs raw Rayon on uniform and triangular loops
+#[derive(Debug, Clone, Parser)]
+#[command(name = "parallel_for")]
+pub struct Args {
+    /// iterations
+    #[arg(long, value_name = "N", value_parser = cli::positive, default_value_t = DEFAULT_N)]
+    pub size: usize,
+    /// threads (a count or max, cores, half, quarter, a/b)
+    #[arg(long, value_name = "T", value_parser = threads::parse_spec, default_value_t = DEFAULT_THREADS)]
+    pub threads: usize,
+    #[command(flatten)]
+    pub common: cli::Common,
+}
+
+pub struct ParallelFor;
+
+impl Benchmark for ParallelFor {
+    fn name(&self) -> &'static str {
+        "parallel_for"
+    }
+
+    fn label(&self) -> &'static str {
+        "parallel_for! schedules"
+    }
+
+    fn suite(&self) -> Suite {
+        Suite::Programmability
+    }
+
+    fn description(&self) -> Description {
+        description()
+    }
+
+    fn command(&self) -> clap::Command {
+        Args::command()
+    }
+
+    fn run(&self, args: &ArgMatches, reporter: &mut Reporter) -> i32 {
+        run(&cli::from_matches(args), reporter)
+    }
+}
+
+pub fn run(args: &Args, reporter: &mut Reporter) -> i32 {
+    let repeat = args.common.reps.repeat(REPS);
+    let Args { size: n, threads, .. } = *args;
+
+    let (rayon, sched, out) = Phase::Setup.run(|| {
+        let rayon = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        let out: Vec<AtomicU64> = (0..n).map(|_| AtomicU64::new(0)).collect();
+        (rayon, Scheduler::new(threads), out)
+    });
+
+    if reporter.is_text() {
+        println!("=== Rust parallel_for! vs Rayon Benchmark (Programmability) ===");
+        println!("N = {}, T = {}, best of {}", n, threads, repeat.reps);
+        println!();
+    }
+
+    for work in ["uniform", "triangular"] {
+        let expected: Vec<u64> = Phase::Setup.run(|| (0..n).map(|i| kernel(i, cost(work, i, n))).collect());
+        let mut baseline = 0.0;
+
+        for variant in VARIANTS {
+            if interrupt::requested() {
+                return 0;
+            }
+            let _bench = info_span!("parfor", implementation = variant.0, schedule = variant.1, work, n, threads).entered();
+            // the repeated runs, with the allocations of the fastest; `out`
+            // is cleared outside the timed region
+            let (time, allocs) = repeat.run(|| {
+                out.iter().for_each(|o| o.store(0, Ordering::Relaxed));
+                let region = Region::start();
+                let start = Instant::now();
+                run_variant(variant, work, n, &out, &rayon, &sched);
+                (start.elapsed().as_secs_f64(), region.finish())
+            });
+            let wrong = Phase::Verify.run(|| out.iter().zip(&expected).any(|(o, &e)| o.load(Ordering::Relaxed) != e));
+            if wrong {
+                eprintln!("{} {} produced wrong results for work={}", variant.0, variant.1, work);
+                return 3;
+            }
+
+            if variant.0 == "rayon" {
+                baseline = time.min;
+            }
+            let relative = time.min / baseline;
+            let (implementation, schedule) = variant;
+
+            if reporter.is_text() {
+                let prefix = format!("parfor,{},work={},schedule={},N={},T={}", implementation, work, schedule, n, threads);
+                println!("{},time,{:.6},sec{}", prefix, time.min, time.summary());
+                println!("{},relative,{:.2},x", prefix, relative);
+            }
+
+            reporter.record(
+                &Record::new("parfor", implementation)
+                    .param("work", work)
+                    .param("schedule", schedule)
+                    .param("N", n)
+                    .param("T", threads)
+                    .timing("time", &time, "sec")
+                    .metric("relative", relative, "x")
+                    .allocs(allocs)
+                    .energy(time.energy)
+                    .peak_rss(time.peak_rss),
+            );
+        }
+    }
+    0
+}
@@ -1,106 +1,561 @@
-use rayon::prelude::*;
-use std::time::Instant;
+// Prefix sum benchmark for programmability (Rust version)
+//
+// Options:
+//   --size N           elements (default 10000000)
+//   --threads T        threads (default 8; a count or max, cores, half, a/b)
+//   --algo A           all (default): every scan below, side by side on the same
+//                      input | chunked: local scans of one block per thread,
+//                      then the block offsets | blelloch: work-efficient
+//                      up-sweep/down-sweep tree, one parallel pass per level |
+//                      hillis-steele: log2(N) double-buffered passes of
+//                      x[i] + x[i - d], O(N log N) adds
+//   --grain G          block length of the chunked scan (default 0 = one block
+//                      per thread); more blocks than threads balance the load
+//                      at the cost of a longer serial pass over their totals
+//   --grain-sweep 0|1  1 = time the chunked scan (and its in-place form with
+//                      --in-place 1) at every grain of sweep_grains for the
+//                      given N and T, one record and table row per grain
+//   --op OP            sum (default) | max | min | mat2: the operator scanned
+//                      (see scan.rs); mat2 scans the 2x2 matrices [[x, 1], [1, 0]]
+//                      of the input values x, a product that does not commute
+//   --in-place 0|1     1 = also time the chunked and Blelloch scans in place,
+//                      overwriting a copy of the input restored before each
+//                      run (untimed), as records with in_place=1: no result
+//                      buffer is allocated or first touched in the timed region
+//   --backend B        rayon (default) | threads | crossbeam | workers
+//                      (+ tokio-blocking | tokio-async with --features tokio)
+//   --format FMT       text (default) | jsonl | json | csv | markdown
+//   --upload URL       POST the result as JSON to an HTTP collector
+//   --prometheus FILE  keep FILE updated with Prometheus gauges for each result
+//   --store FILE       append the run to a SQLite database (--features store)
+//   --log FMT          pretty | json execution log on stderr (RUST_LOG filters)
+//   --pages P          default | thp | hugetlb pages for the input and results
+//   --init I           const (default: N ones) | random: values in [0, 1024)
+//                      from the SplitMix64 stream --seed, checked against the
+//                      sequential scan
+//   --seed S           seed of --init random (default 20240602)
+//   --dump FILE        write the checksum of the sums as a canonical dump (see dump.rs;
+//                      --op sum --init const only, as the C program sums N ones)
+//   --ffi 0|1          1 = also time the C/OpenMP scan, linked in, on the same
+//                      input; its record has impl openmp (--op sum only;
+//                      --features ffi)
+//   --reps R           timed runs of each version, the best is reported (default 1)
+//   --warmup W         untimed runs before them (default 1; 0 for a cold start)
+//   --describe         print the JSON description of arguments and metrics, then exit
 
+use crate::backend::{Backend, Pool};
+use crate::cli;
+use crate::describe::{Description, Param};
+use crate::dump::Dump;
+use crate::ffi;
+use crate::golden::{self, Digest, Golden, Verdict};
+use crate::kernels::{Benchmark, Suite};
+use crate::logging::Phase;
+use crate::openmp::OPENMP;
+use crate::pages::{Element, PageVec, Pages};
+use crate::results::{Record, Reporter};
+use crate::rng;
+use crate::scan::{self, Mat2};
+use crate::stats::Repeat;
+use crate::threads;
+use clap::{ArgAction, ArgMatches, CommandFactory, Parser};
+use std::path::PathBuf;
+use tracing::info_span;
+
+// defaults of --size and --threads
 const N: usize = 10_000_000; // 10^7
 const THREADS: usize = 8;
 const INPUT_VALUE: u64 = 1;
+// seed of --init random unless --seed is given
+const INPUT_SEED: u64 = 20240602;
 
-fn prefix_sum_sequential(arr: &[u64]) -> Vec<u64> {
-    let mut result = vec![0u64; arr.len()];
-    result[0] = arr[0];
-    for i in 1..arr.len() {
-        result[i] = result[i - 1] + arr[i];
-    }
-    result
+/// The parallel scans of --algo, in the order --algo all runs them.
+pub const ALGOS: &[&str] = &["chunked", "blelloch", "hillis-steele"];
+
+/// The operators of --op.
+pub const OPS: &[&str] = &["sum", "max", "min", "mat2"];
+
+/// The grains of --grain-sweep 1 for `n` elements: 0 (one block per
+/// thread), then 256, 1024, 4096, ... below `n`.
+pub fn sweep_grains(n: usize) -> Vec<usize> {
+    std::iter::once(0).chain((8..usize::BITS).step_by(2).map(|k| 1 << k).take_while(|&grain| grain < n)).collect()
 }
 
-fn prefix_sum_parallel(arr: &[u64]) -> Vec<u64> {
-    // parallel prefix sum
-    let n = arr.len();
-    let chunk_size = (n + THREADS - 1) / THREADS;
-    
-    // local prefix sums in parallel
-    let local_sums: Vec<Vec<u64>> = arr
-        .par_chunks(chunk_size)
-        .map(|chunk| {
-            let mut local = vec![0u64; chunk.len()];
-            local[0] = chunk[0];
-            for i in 1..chunk.len() {
-                local[i] = local[i - 1] + chunk[i];
-            }
-            local
-        })
-        .collect();
-    
-    // compute offsets from last element of each chunk
-    let mut offsets = vec![0u64; local_sums.len()];
-    for i in 1..offsets.len() {
-        offsets[i] = offsets[i - 1] + local_sums[i - 1].last().unwrap();
-    }
-
-    // add offsets to local sums in parallel
-    let result: Vec<u64> = local_sums
-        .into_par_iter()
-        .zip(offsets.into_par_iter())
-        .flat_map_iter(|(local, offset)| {
-            local.into_iter().map(move |val| val + offset)
-        })
-        .collect();
-    
+const GOLDEN: &[Golden] = &[
+    Golden { key: "input=1,N=10000000", digest: Digest::Checksum(0x77a6b5d53cb4bc9b) },
+];
+
+/// `n` values in [0, 1024) from the SplitMix64 stream `seed`, the same
+/// whatever the thread count.
+pub fn random_input(n: usize, seed: u64, pages: Pages) -> PageVec<u64> {
+    let mut input = cli::or_exit(PageVec::filled(n, 0, pages));
+    rng::fill(&mut input, seed, |r| r >> 54);
+    input
+}
+
+/// Inclusive prefix sum of `arr`, one pass on the calling thread.
+pub fn prefix_sum_sequential(arr: &[u64], pages: Pages) -> PageVec<u64> {
+    let mut result = cli::or_exit(PageVec::filled(arr.len(), 0, pages));
+    scan::scan_sequential(arr, &mut result, |a, b| a + b);
     result
 }
 
-fn verify_results(sequential: &[u64], parallel: &[u64]) -> bool {
+/// Inclusive prefix sum of `arr` on `pool`: local sums of one block per
+/// thread, then each block shifted by the totals of the blocks before it.
+pub fn prefix_sum_parallel(pool: &Pool, arr: &[u64], pages: Pages) -> PageVec<u64> {
+    parallel_scan("chunked", pool, arr, 0, pages, 0, |a, b| a + b)
+}
+
+/// Inclusive prefix sum of `arr` on `pool` with Blelloch's work-efficient
+/// up-sweep/down-sweep tree (scan::scan_blelloch).
+pub fn prefix_sum_blelloch(pool: &Pool, arr: &[u64], pages: Pages) -> PageVec<u64> {
+    parallel_scan("blelloch", pool, arr, 0, pages, 0, |a, b| a + b)
+}
+
+/// Inclusive prefix sum of `arr` on `pool` with the Hillis-Steele scan,
+/// log2(n) passes of O(n) adds (scan::scan_hillis_steele).
+pub fn prefix_sum_hillis_steele(pool: &Pool, arr: &[u64], pages: Pages) -> PageVec<u64> {
+    parallel_scan("hillis-steele", pool, arr, 0, pages, 0, |a, b| a + b)
+}
+
+// the scan of --algo `algo` under `op`, into buffers on `pages`; `grain`
+// only applies to the chunked scan
+fn parallel_scan<T, F>(algo: &str, pool: &Pool, input: &[T], grain: usize, pages: Pages, identity: T, op: F) -> PageVec<T>
+where
+    T: Element,
+    F: Fn(T, T) -> T + Sync,
+{
+    let mut output = cli::or_exit(PageVec::filled(input.len(), identity, pages));
+    match algo {
+        "blelloch" => scan::scan_blelloch(pool, input, &mut output, op),
+        "hillis-steele" => {
+            let mut scratch = cli::or_exit(PageVec::filled(input.len(), identity, pages));
+            scan::scan_hillis_steele(pool, input, &mut output, &mut scratch, op);
+        }
+        _ => scan::scan(pool, input, &mut output, grain, identity, op),
+    }
+    output
+}
+
+// the registered checksum when there is one (constant input only), else
+// the sequential result
+fn verify_results(sequential: &[u64], parallel: &[u64], seed: Option<u64>) -> bool {
+    if seed.is_none() {
+        let key = format!("input={},N={}", INPUT_VALUE, parallel.len());
+        let digest = Digest::Checksum(golden::checksum(parallel.iter().copied()));
+        match golden::check("prefix_sum", GOLDEN, &key, digest) {
+            Verdict::Match => return true,
+            Verdict::Mismatch => return false,
+            Verdict::Unregistered => {}
+        }
+    }
+
     if sequential.len() != parallel.len() {
         return false;
     }
     sequential.iter().zip(parallel.iter()).all(|(s, p)| s == p)
 }
 
-fn main() {
+// answer to --describe
+pub fn description() -> Description {
+    Description::new("prefix_sum", "prefix_sum", "inclusive prefix sum of N ones (default 10^7 on 8 threads)")
+        .option(Param::integer("--size", 1, "elements").default(N))
+        .option(Param::threads("--threads", "threads").default(THREADS))
+        .option(Param::choice("--algo", &["all", "chunked", "blelloch", "hillis-steele"], "parallel scan: all of them, block-local scans plus offsets, Blelloch's up-sweep/down-sweep tree or Hillis-Steele's log2(N) passes").default("all"))
+        .option(Param::integer("--grain", 0, "block length of the chunked scan, 0 = one block per thread").default(0))
+        .option(Param::switch("--grain-sweep", "time the chunked scan at every grain of a sweep for the given N and T").default(0))
+        .option(Param::switch("--in-place", "also time the chunked and Blelloch scans in place, overwriting a copy of the input").default(0))
+        .option(Param::choice("--op", OPS, "operator scanned: +, max, min, or the product of the 2x2 matrices [[x, 1], [1, 0]]").default("sum"))
+        .backend_option()
+        .pages_option()
+        .ffi_option()
+        .dump_option("write the checksum of the sums to this file as a canonical result dump")
+        .option(Param::choice("--init", &["const", "random"], "input: N ones, or seeded values in [0, 1024) checked against the sequential scan").default("const"))
+        .option(Param::integer("--seed", 0, "seed of --init random").default(INPUT_SEED))
+        .params(&["N", "T", "algo", "grain", "in_place", "op", "pages", "init", "seed"])
+        .timing("par_time", "sec")
+        .timing("seq_time", "sec")
+        .metric("speedup", "x")
+        .metric("correct", "boolean")
+}
+
+/// Inclusive prefix sum of N ones
+#[derive(Debug, Clone, Parser)]
+#[command(name = "prefix_sum")]
+pub struct Args {
+    /// elements
+    #[arg(long, value_name = "N", value_parser = cli::positive, default_value_t = N)]
+    pub size: usize,
+    /// threads (a count or max, cores, half, quarter, a/b)
+    #[arg(long, value_name = "T", value_parser = threads::parse_spec, default_value_t = THREADS)]
+    pub threads: usize,
+    /// all | chunked (block-local scans plus offsets) | blelloch (up-sweep/down-sweep tree) | hillis-steele (log2 N passes)
+    #[arg(long, value_name = "A", value_parser = ["all", "chunked", "blelloch", "hillis-steele"], default_value = "all")]
+    pub algo: String,
+    /// block length of the chunked scan (0 = one block per thread)
+    #[arg(long, value_name = "G", default_value_t = 0)]
+    pub grain: usize,
+    /// 0 | 1: time the chunked scan at every grain of a sweep (0, 256, 1024, ... below N)
+    #[arg(long, value_name = "0|1", action = ArgAction::Set, value_parser = cli::switch, default_value = "0")]
+    pub grain_sweep: bool,
+    /// sum | max | min | mat2 (products of the 2x2 matrices [[x, 1], [1, 0]])
+    #[arg(long, value_name = "OP", value_parser = ["sum", "max", "min", "mat2"], default_value = "sum")]
+    pub op: String,
+    /// 0 | 1: also time the chunked and Blelloch scans in place, on a copy of the input
+    #[arg(long, value_name = "0|1", action = ArgAction::Set, value_parser = cli::switch, default_value = "0")]
+    pub in_place: bool,
+    /// rayon | threads | crossbeam | workers (+ tokio-blocking | tokio-async)
+    #[arg(long, value_name = "B", value_parser = Backend::parse, default_value = "rayon")]
+    pub backend: Backend,
+    /// default | thp | hugetlb pages for the input and results
+    #[arg(long, value_name = "P", value_parser = Pages::parse, default_value = "default")]
+    pub pages: Pages,
+    /// write the checksum of the sums to FILE as a canonical result dump
+    #[arg(long, value_name = "FILE")]
+    pub dump: Option<PathBuf>,
+    /// 0 | 1: also time the linked C/OpenMP scan on the same input (--features ffi)
+    #[arg(long, value_name = "0|1", action = ArgAction::Set, value_parser = cli::switch, default_value = "0")]
+    pub ffi: bool,
+    /// const (N ones) | random: seeded values in [0, 1024)
+    #[arg(long, value_name = "I", value_parser = ["const", "random"], default_value = "const")]
+    pub init: String,
+    /// seed of --init random
+    #[arg(long, value_name = "S", default_value_t = INPUT_SEED)]
+    pub seed: u64,
+    #[command(flatten)]
+    pub common: cli::Common,
+}
+
+pub struct PrefixSum;
+
+impl Benchmark for PrefixSum {
+    fn name(&self) -> &'static str {
+        "prefix_sum"
+    }
+
+    fn label(&self) -> &'static str {
+        "Prefix sum"
+    }
+
+    fn suite(&self) -> Suite {
+        Suite::Programmability
+    }
+
+    fn description(&self) -> Description {
+        description()
+    }
+
+    fn command(&self) -> clap::Command {
+        Args::command()
+    }
+
+    fn run(&self, args: &ArgMatches, reporter: &mut Reporter) -> i32 {
+        run(&cli::from_matches(args), reporter)
+    }
+}
+
+// (algo, in place, grain, best time, speedup, correct) of a parallel scan
+type Timing<'a> = (&'a str, bool, usize, f64, f64, bool);
+
+// what the scans of one run share
+struct Scans<'a> {
+    pool: &'a Pool,
+    algos: &'a [&'a str],
+    // grains of the chunked scan; the others run once, as grain 0
+    grains: &'a [usize],
+    pages: Pages,
+    repeat: Repeat,
+    text: bool,
+    in_place: bool,
+}
+
+impl<'a> Scans<'a> {
+    // times the sequential scan of `input` under `op`, then each parallel
+    // one at each of its grains (and its in-place form with --in-place 1),
+    // checked against it by `check` and recorded as
+    // `record(algo, in_place, grain)`; `first` gets the first parallel
+    // result. Returns the sequential result.
+    #[allow(clippy::too_many_arguments)]
+    fn time<T, F>(
+        &self,
+        input: &[T],
+        identity: T,
+        op: F,
+        check: impl Fn(&[T], &[T]) -> bool,
+        first: impl FnOnce(&[T]),
+        record: impl Fn(&str, bool, usize) -> Record,
+        reporter: &mut Reporter,
+    ) -> (PageVec<T>, Vec<Timing<'a>>)
+    where
+        T: Element,
+        F: Fn(T, T) -> T + Sync + Copy,
+    {
+        if self.text {
+            println!("Running sequential version...");
+        }
+        let (seq_time, sequential, _) = self.repeat.measure(|| {
+            let mut output = cli::or_exit(PageVec::filled(input.len(), identity, self.pages));
+            scan::scan_sequential(input, &mut output, op);
+            output
+        });
+        if self.text {
+            println!("Sequential time: {:.6} seconds{}", seq_time.min, seq_time.summary());
+        }
+
+        let mut first = Some(first);
+        let mut timings = Vec::new();
+        for &algo in self.algos {
+            let grains = if algo == "chunked" { self.grains } else { &[0] };
+            for &grain in grains {
+                if self.text {
+                    println!("Running parallel version: {}...", self.label(algo, false, grain));
+                }
+                let (par_time, parallel, allocs) = self.repeat.measure(|| parallel_scan(algo, self.pool, input, grain, self.pages, identity, op));
+                if self.text {
+                    println!("Parallel time: {:.6} seconds{}", par_time.min, par_time.summary());
+                }
+                let correct = Phase::Verify.run(|| check(&sequential, &parallel));
+                if let Some(first) = first.take() {
+                    first(&parallel);
+                }
+                let speedup = seq_time.min / par_time.min;
+                reporter.record(
+                    &record(algo, false, grain)
+                        .timing("seq_time", &seq_time, "sec")
+                        .timing("par_time", &par_time, "sec")
+                        .metric("speedup", speedup, "x")
+                        .metric("correct", if correct { 1.0 } else { 0.0 }, "boolean")
+                        .allocs(allocs)
+                        .energy(par_time.energy)
+                        .peak_rss(par_time.peak_rss),
+                );
+                timings.push((algo, false, grain, par_time.min, speedup, correct));
+
+                if !self.in_place || algo == "hillis-steele" {
+                    continue;
+                }
+                if self.text {
+                    println!("Running parallel version: {}...", self.label(algo, true, grain));
+                }
+                let mut data = cli::or_exit(PageVec::filled(input.len(), identity, self.pages));
+                self.repeat.timer.calibrate();
+                let (par_time, ()) = self.repeat.run(|| {
+                    data.copy_from_slice(input);
+                    let stopwatch = self.repeat.timer.start();
+                    match algo {
+                        "blelloch" => scan::scan_blelloch_in_place(self.pool, &mut data, op),
+                        _ => scan::scan_in_place(self.pool, &mut data, grain, identity, op),
+                    }
+                    (stopwatch.elapsed(), ())
+                });
+                if self.text {
+                    println!("Parallel time: {:.6} seconds{}", par_time.min, par_time.summary());
+                }
+                let correct = Phase::Verify.run(|| check(&sequential, &data));
+                let speedup = seq_time.min / par_time.min;
+                reporter.record(
+                    &record(algo, true, grain)
+                        .timing("seq_time", &seq_time, "sec")
+                        .timing("par_time", &par_time, "sec")
+                        .metric("speedup", speedup, "x")
+                        .metric("correct", if correct { 1.0 } else { 0.0 }, "boolean")
+                        .energy(par_time.energy)
+                        .peak_rss(par_time.peak_rss),
+                );
+                timings.push((algo, true, grain, par_time.min, speedup, correct));
+            }
+        }
+        (sequential, timings)
+    }
+
+    // a scan's name in the text output, e.g. "chunked (in place, grain
+    // 4096)"; the grain shows when the chunked scan was given any but 0
+    fn label(&self, algo: &str, in_place: bool, grain: usize) -> String {
+        let mut notes = Vec::new();
+        if in_place {
+            notes.push("in place".to_string());
+        }
+        if algo == "chunked" && self.grains != [0] {
+            notes.push(if grain > 0 { format!("grain {}", grain) } else { "grain auto".to_string() });
+        }
+        if notes.is_empty() { algo.to_string() } else { format!("{} ({})", algo, notes.join(", ")) }
+    }
+}
+
+pub fn run(args: &Args, reporter: &mut Reporter) -> i32 {
+    let Args { size: n, threads, backend, pages, .. } = *args;
+    let op = args.op.as_str();
+    let repeat = args.common.reps.repeat(1);
+    let ffi = cli::or_exit(cli::ffi(args.ffi));
+    let seed = (args.init == "random").then_some(args.seed);
+    if seed.is_some() && args.dump.is_some() {
+        eprintln!("--dump compares against the C program, whose input is N ones; drop --init random");
+        return 1;
+    }
+    if op != "sum" && (ffi || args.dump.is_some()) {
+        eprintln!("--ffi 1 and --dump compare against the C program, which only sums; drop --op {}", op);
+        return 1;
+    }
+    let chunked = args.algo == "all" || args.algo == "chunked";
+    if (args.grain > 0 || args.grain_sweep) && !chunked {
+        eprintln!("--grain and --grain-sweep apply to the chunked scan; drop --algo {}", args.algo);
+        return 1;
+    }
+    if args.grain > 0 && args.grain_sweep {
+        eprintln!("--grain-sweep 1 sweeps the grain itself; drop --grain");
+        return 1;
+    }
+    let _bench = info_span!("prefix_sum", backend = backend.name(), n, threads, algo = args.algo.as_str(), grain = args.grain, op, pages = pages.name(), init = args.init.as_str()).entered();
+    // the sweep times the chunked scan alone, the only one with a grain
+    let algos: Vec<&str> = match args.algo.as_str() {
+        _ if args.grain_sweep => vec!["chunked"],
+        "all" => ALGOS.to_vec(),
+        algo => vec![algo],
+    };
+    let grains = if args.grain_sweep { sweep_grains(n) } else { vec![args.grain] };
+
     // thread pool size
-    rayon::ThreadPoolBuilder::new()
-        .num_threads(THREADS)
-        .build_global()
-        .unwrap();
+    let pool = Pool::new(backend, threads);
+
+    // human-readable output only in text format
+    let text = reporter.is_text();
     
-    println!("=== Rust Prefix Sum Benchmark (Programmability) ===");
-    println!("Array size: N = {}", N);
-    println!("Threads: T = {}", THREADS);
-    println!("Input value: {}", INPUT_VALUE);
-    println!();
+    if text {
+        println!("=== Rust Prefix Sum Benchmark (Programmability) ===");
+        println!("Array size: N = {}", n);
+        println!("Threads: T = {}", threads);
+        println!("Algorithms: {}", algos.join(", "));
+        if args.grain_sweep {
+            println!("Grains: auto, {}", grains[1..].iter().map(|grain| grain.to_string()).collect::<Vec<_>>().join(", "));
+        } else if args.grain > 0 {
+            println!("Grain: {}", args.grain);
+        }
+        println!("Operator: {}", op);
+        println!("Backend: {}", backend.name());
+        println!("Pages: {}", pages.name());
+        match seed {
+            None => println!("Input value: {}", INPUT_VALUE),
+            Some(seed) => println!("Input: random, seed {}", seed),
+        }
+        println!();
+    }
     
     // Init input array
-    let input: Vec<u64> = vec![INPUT_VALUE; N];
-    
-    // warm-up 
-    let _ = prefix_sum_parallel(&input[..1000]);
-    
-    // sequential
-    println!("Running sequential version...");
-    let start = Instant::now();
-    let sequential_result = prefix_sum_sequential(&input);
-    let seq_time = start.elapsed();
-    println!("Sequential time: {:.6} seconds", seq_time.as_secs_f64());
-    
-    // parallel 
-    println!("Running parallel version...");
-    let start = Instant::now();
-    let parallel_result = prefix_sum_parallel(&input);
-    let par_time = start.elapsed();
-    println!("Parallel time: {:.6} seconds", par_time.as_secs_f64());
-    
-    //correctness
-    println!("\nVerifying results...");
-    if verify_results(&sequential_result, &parallel_result) {
+    let input = Phase::Setup.run(|| match seed {
+        None => cli::or_exit(PageVec::filled(n, INPUT_VALUE, pages)),
+        Some(seed) => random_input(n, seed, pages),
+    });
+
+    let scans = Scans { pool: &pool, algos: &algos, grains: &grains, pages, repeat, text, in_place: args.in_place };
+    let record = |algo: &str, in_place: bool, grain: usize| {
+        Record::new("prefix_sum", backend.name())
+            .param("N", n)
+            .param("T", threads)
+            .param("algo", algo)
+            .param("grain", grain)
+            .param("in_place", in_place as u8)
+            .param("op", op)
+            .param("pages", pages.name())
+            .param("init", args.init.as_str())
+            .param("seed", seed.unwrap_or(0))
+    };
+    let same = |sequential: &[_], parallel: &[_]| sequential == parallel;
+    let timings = match op {
+        "max" => scans.time(&input, 0, u64::max, same, |_| {}, record, reporter).1,
+        "min" => scans.time(&input, u64::MAX, u64::min, same, |_| {}, record, reporter).1,
+        "mat2" => {
+            let steps = Phase::Setup.run(|| {
+                let mut steps = cli::or_exit(PageVec::filled(n, Mat2::IDENTITY, pages));
+                steps.iter_mut().zip(input.iter()).for_each(|(step, &x)| *step = Mat2::step(x));
+                steps
+            });
+            scans.time(&steps, Mat2::IDENTITY, |a, b| a * b, |s: &[Mat2], p: &[Mat2]| s == p, |_| {}, record, reporter).1
+        }
+        _ => {
+            // every scan is checked against the same sequential sums: the
+            // first one's dump stands for all
+            let dump = |sums: &[u64]| {
+                if let Some(path) = &args.dump {
+                    cli::or_exit(Dump::prefix_sum(sums).write(path));
+                }
+            };
+            let check = |sequential: &[u64], parallel: &[u64]| verify_results(sequential, parallel, seed);
+            let (sequential_result, timings) = scans.time(&input, 0, |a, b| a + b, check, dump, record, reporter);
+
+            // the C scan is exclusive: each of its sums plus the element is Rust's
+            if ffi {
+                let mut exclusive = cli::or_exit(PageVec::filled(n, 0, pages));
+                let (omp_time, ()) = repeat.run(|| (ffi::prefix_sum(&input, &mut exclusive, threads), ()));
+                let omp_correct = Phase::Verify.run(|| exclusive.iter().zip(input.iter()).map(|(s, v)| s + v).eq(sequential_result.iter().copied()));
+                if text {
+                    println!("OpenMP (linked) time: {:.6} seconds{}", omp_time.min, omp_time.summary());
+                }
+                reporter.record(
+                    &Record::new("prefix_sum", OPENMP)
+                        .param("N", n)
+                        .param("T", threads)
+                        .param("algo", "chunked")
+                        .param("op", "sum")
+                        .param("pages", pages.name())
+                        .param("init", args.init.as_str())
+                        .param("seed", seed.unwrap_or(0))
+                        .timing("par_time", &omp_time, "sec")
+                        .metric("correct", if omp_correct { 1.0 } else { 0.0 }, "boolean")
+                        .energy(omp_time.energy)
+                        .peak_rss(omp_time.peak_rss),
+                );
+            }
+            timings
+        }
+    };
+
+    if !text {
+        return 0;
+    }
+
+    println!();
+    if timings.iter().all(|&(.., correct)| correct) {
         println!("✓ Results match!");
     } else {
-        println!("✗ Results do not match!");
-        return;
+        for &(algo, in_place, grain, ..) in timings.iter().filter(|&&(.., correct)| !correct) {
+            println!("✗ Results do not match! ({})", scans.label(algo, in_place, grain));
+        }
+        return 0;
+    }
+
+    //  speedup, the algorithms (or the grains of the sweep) side by side
+    println!("\n{:<36} {:>12} {:>9}", "algo", "time (s)", "speedup");
+    for &(algo, in_place, grain, time, speedup, _) in &timings {
+        println!("{:<36} {:>12.6} {:>8.2}x", scans.label(algo, in_place, grain), time, speedup);
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(48))]
+
+        #[test]
+        fn prop_parallel_matches_sequential(
+            input in prop::collection::vec(0u64..1_000_000, 1..5000),
+            backend in prop::sample::select(Backend::ALL.to_vec()),
+            threads in 1usize..17,
+            grain in prop_oneof![Just(0usize), 1usize..600],
+        ) {
+            let pool = Pool::new(backend, threads);
+            let parallel = prefix_sum_parallel(&pool, &input, Pages::Default);
+            let sequential = prefix_sum_sequential(&input, Pages::Default);
+            prop_assert_eq!(&parallel[..], &sequential[..]);
+            prop_assert_eq!(&prefix_sum_blelloch(&pool, &input, Pages::Default)[..], &sequential[..]);
+            prop_assert_eq!(&prefix_sum_hillis_steele(&pool, &input, Pages::Default)[..], &sequential[..]);
+            prop_assert_eq!(&parallel_scan("chunked", &pool, &input, grain, Pages::Default, 0, |a, b| a + b)[..], &sequential[..]);
+            let mut data = input.clone();
+            scan::scan_in_place(&pool, &mut data, grain, 0, |a, b| a + b);
+            prop_assert_eq!(&data[..], &sequential[..]);
+        }
     }
-    
-    //  speedup
-    let speedup = seq_time.as_secs_f64() / par_time.as_secs_f64();
-    println!("\nSpeedup: {:.2}x", speedup);
-    
 }
@@ -0,0 +1,358 @@
+// Segmented prefix sum benchmark for programmability (Rust version)
+// An inclusive scan that restarts at every flagged element, the building
+// block of sparse matrix-vector products and group-by sums. The input is N
+// ones; element i starts a segment when value i of the SplitMix64 stream
+// --seed is a multiple of --segment L (element 0 always does), so segments
+// average L elements and mp_segmented_scan draws the same ones. Unlike the
+// plain scan, a block's carry-in stops at its first flag, so the parallel
+// versions track where segments start as well as their sums:
+//   chunked    one block per thread scanned locally, a serial pass over the
+//              block carries (a block passes on its tail segment's sum, plus
+//              its own carry-in when it holds no flag), then each block's head
+//              up to its first flag adds its carry-in; the C program's phases
+//   operator   scan::scan_in_place over (flag, value) pairs with the segmented
+//              operator (f1, v1) . (f2, v2) = (f1 | f2, f2 ? v2 : v1 + v2),
+//              which associates, so any of the library's scans computes it;
+//              packing and unpacking the pairs are timed too
+//
+// Options:
+//   --size N           elements (default 10000000)
+//   --threads T        threads (default 8; a count or max, cores, half, a/b)
+//   --segment L        mean segment length (default 1000; 1 = every element)
+//   --seed S           seed of the segment starts (default 20240603)
+//   --algo A           all (default) | chunked | operator
+//   --backend B        rayon (default) | threads | crossbeam | workers
+//                      (+ tokio-blocking | tokio-async with --features tokio)
+//   --pages P          default | thp | hugetlb pages for the input, flags and results
+//   --format FMT       text (default) | jsonl | json | csv | markdown
+//   --upload URL       POST the result as JSON to an HTTP collector
+//   --prometheus FILE  keep FILE updated with Prometheus gauges for each result
+//   --store FILE       append the run to a SQLite database (--features store)
+//   --log FMT          pretty | json execution log on stderr (RUST_LOG filters)
+//   --reps R           timed runs of each version, the best is reported (default 1)
+//   --warmup W         untimed runs before them (default 1; 0 for a cold start)
+//   --describe         print the JSON description of arguments and metrics, then exit
+
+use crate::backend::{Backend, Pool};
+use crate::cli;
+use crate::describe::{Description, Param};
+use crate::kernels::{Benchmark, Suite};
+use crate::logging::Phase;
+use crate::pages::{PageVec, Pages};
+use crate::results::{Record, Reporter};
+use crate::rng;
+use crate::scan;
+use crate::threads;
+use clap::{ArgMatches, CommandFactory, Parser};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::info_span;
+
+// defaults of --size, --threads, --segment and --seed
+const N: usize = 10_000_000; // 10^7
+const THREADS: usize = 8;
+const SEGMENT: u64 = 1000;
+const FLAG_SEED: u64 = 20240603;
+
+/// The parallel scans of --algo, in the order --algo all runs them.
+pub const ALGOS: &[&str] = &["chunked", "operator"];
+
+/// Segment starts of `n` elements: element i starts one when value i of the
+/// SplitMix64 stream `seed` is a multiple of `segment`, and element 0 always.
+pub fn gen_flags(n: usize, segment: u64, seed: u64, pages: Pages) -> PageVec<u8> {
+    let mut flags = cli::or_exit(PageVec::filled(n, 0, pages));
+    rng::fill(&mut flags, seed, |r| (r % segment == 0) as u8);
+    if let Some(first) = flags.first_mut() {
+        *first = 1;
+    }
+    flags
+}
+
+/// Inclusive sums of `values`, restarted at every element whose flag is
+/// set, one pass on the calling thread.
+pub fn segmented_scan_sequential(values: &[u64], flags: &[u8], pages: Pages) -> PageVec<u64> {
+    assert_eq!(values.len(), flags.len(), "values and flags lengths differ");
+    let mut result = cli::or_exit(PageVec::filled(values.len(), 0, pages));
+    let mut running = 0;
+    for ((out, &value), &flag) in result.iter_mut().zip(values).zip(flags) {
+        running = if flag != 0 { value } else { running + value };
+        *out = running;
+    }
+    result
+}
+
+/// The segmented scan on `pool`: local segmented scans of one block per
+/// thread, then each block's head, up to its first segment start, shifted by
+/// the sum of the segment running into the block.
+pub fn segmented_scan_parallel(pool: &Pool, values: &[u64], flags: &[u8], pages: Pages) -> PageVec<u64> {
+    assert_eq!(values.len(), flags.len(), "values and flags lengths differ");
+    let n = values.len();
+    let mut result = cli::or_exit(PageVec::filled(n, 0, pages));
+    if n == 0 {
+        return result;
+    }
+    let chunk_size = n.div_ceil(pool.threads());
+    let blocks = n.div_ceil(chunk_size);
+    // whether each block holds a segment start
+    let flagged: Vec<AtomicBool> = (0..blocks).map(|_| AtomicBool::new(false)).collect();
+
+    // local segmented scans in parallel
+    pool.for_each_chunk_mut(&mut result, chunk_size, |start, local| {
+        let (values, flags) = (&values[start..start + local.len()], &flags[start..start + local.len()]);
+        let mut running = 0;
+        for ((out, &value), &flag) in local.iter_mut().zip(values).zip(flags) {
+            running = if flag != 0 { value } else { running + value };
+            *out = running;
+        }
+        flagged[start / chunk_size].store(flags.iter().any(|&f| f != 0), Ordering::Relaxed);
+    });
+
+    // carry into each block: its predecessor's tail segment, which runs on
+    // from the carry before it when the predecessor holds no start
+    let mut carries = vec![0u64; blocks];
+    for b in 1..blocks {
+        let tail = result[b * chunk_size - 1];
+        carries[b] = if flagged[b - 1].load(Ordering::Relaxed) { tail } else { carries[b - 1] + tail };
+    }
+
+    // carries onto the block heads in parallel
+    pool.for_each_chunk_mut(&mut result, chunk_size, |start, local| {
+        let carry = carries[start / chunk_size];
+        for (out, &flag) in local.iter_mut().zip(&flags[start..]) {
+            if flag != 0 {
+                break;
+            }
+            *out += carry;
+        }
+    });
+    result
+}
+
+// the segmented operator on (segment start, sum): a start on the right
+// discards what came before it
+fn segmented(a: (bool, u64), b: (bool, u64)) -> (bool, u64) {
+    (a.0 | b.0, if b.0 { b.1 } else { a.1 + b.1 })
+}
+
+/// The segmented scan as a plain scan over (flag, value) pairs with the
+/// segmented operator (scan::scan_in_place), packing and unpacking the
+/// pairs in parallel.
+pub fn segmented_scan_operator(pool: &Pool, values: &[u64], flags: &[u8], pages: Pages) -> PageVec<u64> {
+    assert_eq!(values.len(), flags.len(), "values and flags lengths differ");
+    let n = values.len();
+    let chunk_size = n.div_ceil(pool.threads()).max(1);
+    let mut pairs = vec![(false, 0u64); n];
+    pool.for_each_chunk_mut(&mut pairs, chunk_size, |start, local| {
+        for ((pair, &value), &flag) in local.iter_mut().zip(&values[start..]).zip(&flags[start..]) {
+            *pair = (flag != 0, value);
+        }
+    });
+    scan::scan_in_place(pool, &mut pairs, 0, (false, 0), segmented);
+
+    let mut result = cli::or_exit(PageVec::filled(n, 0, pages));
+    pool.for_each_chunk_mut(&mut result, chunk_size, |start, local| {
+        for (out, &(_, sum)) in local.iter_mut().zip(&pairs[start..]) {
+            *out = sum;
+        }
+    });
+    result
+}
+
+// the scan of --algo `algo`
+fn parallel_scan(algo: &str) -> fn(&Pool, &[u64], &[u8], Pages) -> PageVec<u64> {
+    match algo {
+        "operator" => segmented_scan_operator,
+        _ => segmented_scan_parallel,
+    }
+}
+
+// answer to --describe
+pub fn description() -> Description {
+    Description::new("segmented_scan", "segmented_scan", "inclusive prefix sum of N ones restarted at seeded segment starts (default 10^7, L = 1000, on 8 threads)")
+        .option(Param::integer("--size", 1, "elements").default(N))
+        .option(Param::threads("--threads", "threads").default(THREADS))
+        .option(Param::integer("--segment", 1, "mean segment length").default(SEGMENT))
+        .option(Param::integer("--seed", 0, "seed of the segment starts").default(FLAG_SEED))
+        .option(Param::choice("--algo", &["all", "chunked", "operator"], "parallel scan: both, block-local scans plus carries, or scan::scan over (flag, value) pairs").default("all"))
+        .backend_option()
+        .pages_option()
+        .params(&["N", "T", "algo", "segment", "seed", "pages"])
+        .timing("par_time", "sec")
+        .timing("seq_time", "sec")
+        .metric("speedup", "x")
+        .metric("correct", "boolean")
+}
+
+/// Inclusive prefix sum of N ones, restarted at seeded segment starts
+#[derive(Debug, Clone, Parser)]
+#[command(name = "segmented_scan")]
+pub struct Args {
+    /// elements
+    #[arg(long, value_name = "N", value_parser = cli::positive, default_value_t = N)]
+    pub size: usize,
+    /// threads (a count or max, cores, half, quarter, a/b)
+    #[arg(long, value_name = "T", value_parser = threads::parse_spec, default_value_t = THREADS)]
+    pub threads: usize,
+    /// mean segment length
+    #[arg(long, value_name = "L", value_parser = cli::positive, default_value_t = SEGMENT as usize)]
+    pub segment: usize,
+    /// seed of the segment starts
+    #[arg(long, value_name = "S", default_value_t = FLAG_SEED)]
+    pub seed: u64,
+    /// all | chunked (block-local scans plus carries) | operator (scan::scan over (flag, value) pairs)
+    #[arg(long, value_name = "A", value_parser = ["all", "chunked", "operator"], default_value = "all")]
+    pub algo: String,
+    /// rayon | threads | crossbeam | workers (+ tokio-blocking | tokio-async)
+    #[arg(long, value_name = "B", value_parser = Backend::parse, default_value = "rayon")]
+    pub backend: Backend,
+    /// default | thp | hugetlb pages for the input, flags and results
+    #[arg(long, value_name = "P", value_parser = Pages::parse, default_value = "default")]
+    pub pages: Pages,
+    #[command(flatten)]
+    pub common: cli::Common,
+}
+
+pub struct SegmentedScan;
+
+impl Benchmark for SegmentedScan {
+    fn name(&self) -> &'static str {
+        "segmented_scan"
+    }
+
+    fn label(&self) -> &'static str {
+        "Segmented scan"
+    }
+
+    fn suite(&self) -> Suite {
+        Suite::Programmability
+    }
+
+    fn description(&self) -> Description {
+        description()
+    }
+
+    fn command(&self) -> clap::Command {
+        Args::command()
+    }
+
+    fn run(&self, args: &ArgMatches, reporter: &mut Reporter) -> i32 {
+        run(&cli::from_matches(args), reporter)
+    }
+}
+
+pub fn run(args: &Args, reporter: &mut Reporter) -> i32 {
+    let Args { size: n, threads, segment, seed, backend, pages, .. } = *args;
+    let repeat = args.common.reps.repeat(1);
+    let algos: Vec<&str> = if args.algo == "all" { ALGOS.to_vec() } else { vec![args.algo.as_str()] };
+    let _bench = info_span!("segmented_scan", backend = backend.name(), n, threads, segment, algo = args.algo.as_str(), pages = pages.name()).entered();
+    let pool = Pool::new(backend, threads);
+    let text = reporter.is_text();
+
+    if text {
+        println!("=== Rust Segmented Scan Benchmark (Programmability) ===");
+        println!("Array size: N = {}", n);
+        println!("Threads: T = {}", threads);
+        println!("Mean segment length: L = {} (seed {})", segment, seed);
+        println!("Algorithms: {}", algos.join(", "));
+        println!("Backend: {}", backend.name());
+        println!("Pages: {}", pages.name());
+        println!();
+    }
+
+    let (values, flags) = Phase::Setup.run(|| (cli::or_exit(PageVec::filled(n, 1, pages)), gen_flags(n, segment as u64, seed, pages)));
+    if text {
+        println!("Segments: {}", flags.iter().filter(|&&f| f != 0).count());
+        println!("Running sequential version...");
+    }
+    let (seq_time, sequential, _) = repeat.measure(|| segmented_scan_sequential(&values, &flags, pages));
+    if text {
+        println!("Sequential time: {:.6} seconds{}", seq_time.min, seq_time.summary());
+    }
+
+    let mut timings = Vec::new();
+    for &algo in &algos {
+        if text {
+            println!("Running parallel version ({})...", algo);
+        }
+        let scan = parallel_scan(algo);
+        let (par_time, parallel, allocs) = repeat.measure(|| scan(&pool, &values, &flags, pages));
+        if text {
+            println!("Parallel time: {:.6} seconds{}", par_time.min, par_time.summary());
+        }
+        let correct = Phase::Verify.run(|| parallel[..] == sequential[..]);
+        let speedup = seq_time.min / par_time.min;
+        reporter.record(
+            &Record::new("segmented_scan", backend.name())
+                .param("N", n)
+                .param("T", threads)
+                .param("algo", algo)
+                .param("segment", segment)
+                .param("seed", seed)
+                .param("pages", pages.name())
+                .timing("seq_time", &seq_time, "sec")
+                .timing("par_time", &par_time, "sec")
+                .metric("speedup", speedup, "x")
+                .metric("correct", if correct { 1.0 } else { 0.0 }, "boolean")
+                .allocs(allocs)
+                .energy(par_time.energy)
+                .peak_rss(par_time.peak_rss),
+        );
+        timings.push((algo, par_time.min, speedup, correct));
+    }
+
+    if !text {
+        return 0;
+    }
+
+    println!();
+    if timings.iter().all(|&(.., correct)| correct) {
+        println!("✓ Results match!");
+    } else {
+        for (algo, ..) in timings.iter().filter(|&&(.., correct)| !correct) {
+            println!("✗ Results do not match! ({})", algo);
+        }
+        return 0;
+    }
+
+    println!("\n{:<14} {:>12} {:>9}", "algo", "time (s)", "speedup");
+    for (algo, time, speedup, _) in &timings {
+        println!("{:<14} {:>12.6} {:>8.2}x", algo, time, speedup);
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_segments() {
+        let values: Vec<u64> = (1..=10).collect();
+        let flags = [1, 0, 0, 1, 0, 0, 0, 1, 1, 0];
+        let sums = segmented_scan_sequential(&values, &flags, Pages::Default);
+        assert_eq!(sums[..], [1, 3, 6, 4, 9, 15, 22, 8, 9, 19]);
+
+        let flags = gen_flags(100_000, 100, FLAG_SEED, Pages::Default);
+        let starts = flags.iter().filter(|&&f| f != 0).count();
+        assert!(flags[0] == 1 && (800..1200).contains(&starts), "{} starts", starts);
+        assert_eq!(gen_flags(1000, 1, 7, Pages::Default).iter().filter(|&&f| f == 1).count(), 1000);
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(48))]
+
+        #[test]
+        fn prop_parallel_matches_sequential(
+            pairs in prop::collection::vec((0u64..1_000_000, prop::bool::weighted(0.05)), 1..5000),
+            backend in prop::sample::select(Backend::ALL.to_vec()),
+            threads in 1usize..17,
+        ) {
+            let values: Vec<u64> = pairs.iter().map(|&(v, _)| v).collect();
+            let flags: Vec<u8> = pairs.iter().map(|&(_, f)| f as u8).collect();
+            let pool = Pool::new(backend, threads);
+            let sequential = segmented_scan_sequential(&values, &flags, Pages::Default);
+            prop_assert_eq!(&segmented_scan_parallel(&pool, &values, &flags, Pages::Default)[..], &sequential[..]);
+            prop_assert_eq!(&segmented_scan_operator(&pool, &values, &flags, Pages::Default)[..], &sequential[..]);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+// Prometheus textfile export for long soak / regression runs
+// Every metric of every record becomes a gauge labelled with kernel, impl and
+// the record's parameters. The file is rewritten atomically after each record
+// so node_exporter's textfile collector always sees a complete snapshot.
+// Series already present in the file are kept, which lets several binaries
+// (or repeated soak iterations) share one file.
+
+use crate::results::Record;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+const PREFIX: &str = "bench";
+
+pub struct TextfileExporter {
+    path: PathBuf,
+    // family name -> label set -> latest value
+    families: BTreeMap<String, BTreeMap<String, f64>>,
+}
+
+impl TextfileExporter {
+    pub fn new(path: &str) -> Self {
+        let mut families: BTreeMap<String, BTreeMap<String, f64>> = BTreeMap::new();
+
+        if let Ok(existing) = fs::read_to_string(path) {
+            for line in existing.lines().filter(|l| !l.starts_with('#')) {
+                let Some((series, value)) = line.rsplit_once(' ') else { continue };
+                let Ok(value) = value.parse::<f64>() else { continue };
+                let (family, labels) = match series.find('{') {
+                    Some(i) => (&series[..i], &series[i..]),
+                    None => (series, ""),
+                };
+                families
+                    .entry(family.to_string())
+                    .or_default()
+                    .insert(labels.to_string(), value);
+            }
+        }
+
+        TextfileExporter {
+            path: PathBuf::from(path),
+            families,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        self.path.to_str().unwrap_or("<non-utf8 path>")
+    }
+
+    pub fn update(&mut self, record: &Record) -> io::Result<()> {
+        let mut labels = vec![
+            ("kernel".to_string(), record.kernel.clone()),
+            ("impl".to_string(), record.implementation.clone()),
+        ];
+        labels.extend(record.params.iter().map(|(k, v)| (sanitize(k), v.clone())));
+        let labels = format!(
+            "{{{}}}",
+            labels
+                .iter()
+                .map(|(k, v)| format!("{}=\"{}\"", k, escape(v)))
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+
+        for metric in &record.metrics {
+            let (suffix, divisor) = unit_suffix(&metric.unit);
+            let family = format!("{}_{}{}", PREFIX, sanitize(&metric.name), suffix);
+            self.families
+                .entry(family)
+                .or_default()
+                .insert(labels.clone(), metric.value / divisor);
+        }
+
+        self.write()
+    }
+
+    fn write(&self) -> io::Result<()> {
+        let mut out = String::new();
+        for (family, series) in &self.families {
+            out.push_str(&format!("# TYPE {} gauge\n", family));
+            for (labels, value) in series {
+                out.push_str(&format!("{}{} {}\n", family, labels, value));
+            }
+        }
+
+        // write-then-rename so scrapers never read a half-written file
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        fs::write(&tmp, out)?;
+        fs::rename(&tmp, &self.path)
+    }
+}
+
+// time units are normalized to seconds, as Prometheus conventions expect
+fn unit_suffix(unit: &str) -> (&'static str, f64) {
+    match unit {
+        "sec" => ("_seconds", 1.0),
+        "ms" => ("_seconds", 1e3),
+        "ns" => ("_seconds", 1e9),
+        _ => ("", 1.0),
+    }
+}
+
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+        .collect()
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_textfile_roundtrip() {
+        let path = std::env::temp_dir().join(format!("bench_prom_{}.prom", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = fs::remove_file(path);
+
+        let mut exporter = TextfileExporter::new(path);
+        exporter
+            .update(
+                &Record::new("overhead", "rust")
+                    .param("primitive", "mutex")
+                    .param("T", 4)
+                    .metric("per_op", 250.0, "ns")
+                    .metric("speedup", 2.0, "x"),
+            )
+            .unwrap();
+
+        // a second exporter keeps the series written by the first
+        let mut exporter = TextfileExporter::new(path);
+        exporter
+            .update(&Record::new("hist", "rayon").param("dist", "uniform").metric("time", 0.5, "sec"))
+            .unwrap();
+
+        let text = fs::read_to_string(path).unwrap();
+        assert!(text.contains("# TYPE bench_per_op_seconds gauge\n"));
+        assert!(text.contains("bench_per_op_seconds{kernel=\"overhead\",impl=\"rust\",primitive=\"mutex\",T=\"4\"} 0.00000025"));
+        assert!(text.contains("bench_speedup{kernel=\"overhead\",impl=\"rust\",primitive=\"mutex\",T=\"4\"} 2\n"));
+        assert!(text.contains("bench_time_seconds{kernel=\"hist\",impl=\"rayon\",dist=\"uniform\"} 0.5\n"));
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,505 @@
+// HTML reports of result files (run_all_benchmarks report)
+// Renders any mix of result files as one standalone page: per kernel, SVG
+// charts of speedup and parallel efficiency over the thread count T (where
+// its records vary T), then a table of every record. Accepted inputs:
+//   json   the --format json document, or a plain array of records
+//   jsonl  --format jsonl output, metadata line included
+//   csv    rows of the shared schema kernel,impl,name=value,...,metric,value,unit
+//          (--format csv, and the OpenMP programs' output); other lines,
+//          such as console banners, are skipped
+// A chart series is one implementation with one setting of the other
+// params. Its speedup at T is the record's own `speedup` metric when it has
+// one, otherwise the series' time at T=1 over its time at T (`time`, else
+// `par_time`); efficiency is speedup / T. Styles and charts are embedded, so
+// the page displays without anything else.
+
+use crate::metadata::Metadata;
+use crate::results::{Metric, Record};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// One loaded result file.
+pub struct Source {
+    pub name: String,
+    pub metadata: Vec<(String, String)>,
+    pub records: Vec<Record>,
+}
+
+type Parsed = (Vec<(String, String)>, Vec<Record>);
+
+pub fn load(path: &Path) -> Result<Source, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let (metadata, records) = parse(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let name = path.file_name().map_or_else(|| path.display().to_string(), |n| n.to_string_lossy().into_owned());
+    Ok(Source { name, metadata, records })
+}
+
+/// The metadata and records of a json, jsonl or csv result file.
+pub fn parse(text: &str) -> Result<Parsed, String> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Json {
+        Document { metadata: Option<serde_json::Value>, records: Vec<Record> },
+        Records(Vec<Record>),
+    }
+    #[derive(Deserialize)]
+    struct Header {
+        metadata: serde_json::Value,
+    }
+
+    match serde_json::from_str::<Json>(text) {
+        Ok(Json::Document { metadata, records }) => return Ok((metadata.map(metadata_fields).unwrap_or_default(), records)),
+        Ok(Json::Records(records)) => return Ok((Vec::new(), records)),
+        Err(_) => {}
+    }
+    let mut lines = text.lines().map(str::trim).enumerate().filter(|(_, l)| !l.is_empty()).peekable();
+    if lines.peek().is_some_and(|(_, l)| l.starts_with('{')) {
+        let mut parsed = Parsed::default();
+        for (i, line) in lines {
+            match serde_json::from_str::<Record>(line) {
+                Ok(record) => parsed.1.push(record),
+                Err(e) => match serde_json::from_str::<Header>(line) {
+                    Ok(header) => parsed.0 = metadata_fields(header.metadata),
+                    Err(_) => return Err(format!("line {}: {}", i + 1, e)),
+                },
+            }
+        }
+        return Ok(parsed);
+    }
+    let parsed = parse_csv(text);
+    if parsed.1.is_empty() {
+        return Err("no records (expected json, jsonl or csv results)".to_string());
+    }
+    Ok(parsed)
+}
+
+// in the declared order when the metadata is this version's
+fn metadata_fields(value: serde_json::Value) -> Vec<(String, String)> {
+    if let Ok(metadata) = Metadata::deserialize(&value) {
+        return metadata.fields().into_iter().map(|(key, value)| (key.to_string(), value)).collect();
+    }
+    let serde_json::Value::Object(fields) = value else { return Vec::new() };
+    fields
+        .into_iter()
+        .map(|(key, value)| match value {
+            serde_json::Value::String(s) => (key, s),
+            serde_json::Value::Null => (key, String::new()),
+            other => (key, other.to_string()),
+        })
+        .collect()
+}
+
+/// The `# key=value` metadata and the records of CSV rows; consecutive rows
+/// of one label make one record. Lines of any other shape are skipped.
+pub fn parse_csv(text: &str) -> Parsed {
+    let mut parsed = Parsed::default();
+    for line in text.lines().map(str::trim) {
+        // concatenated outputs repeat the header; the first one counts
+        if let Some((key, value)) = line.strip_prefix("# ").and_then(|l| l.split_once('=')) {
+            if !parsed.0.iter().any(|(k, _)| k == key) {
+                parsed.0.push((key.to_string(), value.to_string()));
+            }
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').collect();
+        if fields.len() < 5 {
+            continue;
+        }
+        let (head, tail) = fields.split_at(fields.len() - 3);
+        let (kernel, implementation, (metric, value, unit)) = (head[0], head[1], (tail[0], tail[1], tail[2]));
+        let params: Option<Vec<(String, String)>> = head[2..].iter().map(|p| p.split_once('=').map(|(k, v)| (k.to_string(), v.to_string()))).collect();
+        let (Some(params), Ok(value)) = (params, value.parse::<f64>()) else { continue };
+        if kernel.is_empty() || implementation.is_empty() || metric.is_empty() || metric.contains('=') {
+            continue;
+        }
+
+        let records = &mut parsed.1;
+        let same_cell = records.last().is_some_and(|r: &Record| {
+            r.kernel == kernel && r.implementation == implementation && r.params == params && !r.metrics.iter().any(|m| m.name == metric)
+        });
+        if !same_cell {
+            records.push(Record { params, ..Record::new(kernel, implementation) });
+        }
+        let record = records.last_mut().expect("pushed above");
+        record.metrics.push(Metric { name: metric.to_string(), value, unit: unit.to_string() });
+    }
+    parsed
+}
+
+/// One line of a chart: (x, y) points in x order, e.g. (T, speedup).
+#[derive(Debug, PartialEq)]
+pub struct Series {
+    pub name: String,
+    pub points: Vec<(usize, f64)>,
+}
+
+/// Params whose growth makes a kernel's problem larger, by preference.
+pub const SIZE_PARAMS: [&str; 3] = ["n", "N", "MiB"];
+
+pub fn param<'a>(record: &'a Record, name: &str) -> Option<&'a str> {
+    record.params.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+}
+
+fn metric(record: &Record, name: &str) -> Option<f64> {
+    record.metrics.iter().find(|m| m.name == name).map(|m| m.value)
+}
+
+/// The time a record reports: `time`, else `par_time`.
+pub fn time(record: &Record) -> Option<f64> {
+    metric(record, "time").or_else(|| metric(record, "par_time"))
+}
+
+/// The name of `record`'s series among `records` (source name, record) when
+/// charted over the param `x`: its implementation and the other params that
+/// differ between the records, then the source when `tag_sources`.
+pub fn series_name(record: &Record, source: &str, records: &[(&str, &Record)], x: &str, tag_sources: bool) -> String {
+    let varies = |name: &str| records.iter().any(|(_, r)| param(r, name) != param(record, name));
+    let mut name = record.implementation.clone();
+    for (k, v) in record.params.iter().filter(|(k, _)| k != x && varies(k)) {
+        name.push_str(&format!(" {}={}", k, v));
+    }
+    if tag_sources {
+        name.push_str(&format!(" [{}]", source));
+    }
+    name
+}
+
+/// The speedup series of one kernel's records (source name, record), named
+/// by `series_name` over T. Series with fewer than two points are left out.
+pub fn speedups(records: &[(&str, &Record)], tag_sources: bool) -> Vec<Series> {
+    // per series: (T, time, own speedup)
+    type Cell = (usize, Option<f64>, Option<f64>);
+    let mut cells: Vec<(String, Vec<Cell>)> = Vec::new();
+    for (source, record) in records {
+        let Some(threads) = param(record, "T").and_then(|t| t.parse::<usize>().ok()) else { continue };
+        let (time, speedup) = (time(record), metric(record, "speedup"));
+        if time.is_none() && speedup.is_none() {
+            continue;
+        }
+        let name = series_name(record, source, records, "T", tag_sources);
+        match cells.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, points)) => points.push((threads, time, speedup)),
+            None => cells.push((name, vec![(threads, time, speedup)])),
+        }
+    }
+
+    let mut series = Vec::new();
+    for (name, cells) in cells {
+        let base = cells.iter().find(|(t, _, _)| *t == 1).and_then(|(_, time, _)| *time);
+        let mut points: Vec<(usize, f64)> = cells
+            .iter()
+            .filter_map(|&(t, time, speedup)| {
+                let s = speedup.or_else(|| Some(base? / time?))?;
+                (s.is_finite() && s > 0.0).then_some((t, s))
+            })
+            .collect();
+        points.sort_by_key(|&(t, _)| t);
+        points.dedup_by_key(|&mut (t, _)| t);
+        if points.len() >= 2 {
+            series.push(Series { name, points });
+        }
+    }
+    series
+}
+
+/// The first of `SIZE_PARAMS` that takes more than one value in `records`.
+pub fn size_param(records: &[(&str, &Record)]) -> Option<&'static str> {
+    SIZE_PARAMS.into_iter().find(|&name| {
+        let mut sizes = records.iter().filter_map(|(_, r)| param(r, name));
+        sizes.next().is_some_and(|first| sizes.any(|s| s != first))
+    })
+}
+
+/// The time series of one kernel's records over the param `size`, named by
+/// `series_name`; the fastest record of a size counts. Series with fewer
+/// than two points are left out.
+pub fn times(records: &[(&str, &Record)], size: &str, tag_sources: bool) -> Vec<Series> {
+    let mut series: Vec<Series> = Vec::new();
+    for (source, record) in records {
+        let (Some(x), Some(time)) = (param(record, size).and_then(|v| v.parse::<usize>().ok()), time(record)) else { continue };
+        if !(time.is_finite() && time > 0.0) {
+            continue;
+        }
+        let name = series_name(record, source, records, size, tag_sources);
+        match series.iter_mut().find(|s| s.name == name) {
+            Some(s) => s.points.push((x, time)),
+            None => series.push(Series { name, points: vec![(x, time)] }),
+        }
+    }
+    for s in &mut series {
+        s.points.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+        s.points.dedup_by_key(|&mut (x, _)| x);
+    }
+    series.retain(|s| s.points.len() >= 2);
+    series
+}
+
+const COLORS: [&str; 8] = ["#1f77b4", "#d62728", "#2ca02c", "#ff7f0e", "#9467bd", "#8c564b", "#e377c2", "#17becf"];
+
+pub fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+// a round step of about `raw`: 1, 2 or 5 times a power of ten
+fn nice_step(raw: f64) -> f64 {
+    let magnitude = 10f64.powf(raw.log10().floor());
+    [1.0, 2.0, 5.0, 10.0].into_iter().map(|m| m * magnitude).find(|s| *s >= raw).unwrap_or(10.0 * magnitude)
+}
+
+// an SVG line chart of `value(T, speedup)` for every series, over the thread
+// counts as evenly spaced categories, with the dashed `ideal(T)` line
+fn chart(title: &str, series: &[Series], value: impl Fn(usize, f64) -> f64, ideal: impl Fn(usize) -> f64) -> String {
+    let (width, plot_height, left, right, top) = (640.0, 260.0, 56.0, 16.0, 32.0);
+    let legend_top = top + plot_height + 44.0;
+    let height = legend_top + 18.0 * series.len() as f64 + 8.0;
+    let plot_width = width - left - right;
+
+    let mut threads: Vec<usize> = series.iter().flat_map(|s| s.points.iter().map(|&(t, _)| t)).collect();
+    threads.sort_unstable();
+    threads.dedup();
+    let x = |t: usize| {
+        let i = threads.iter().position(|&u| u == t).expect("a charted thread count");
+        left + plot_width * (i as f64 + 0.5) / threads.len() as f64
+    };
+    let highest = series
+        .iter()
+        .flat_map(|s| s.points.iter().map(|&(t, v)| value(t, v)))
+        .chain(threads.iter().map(|&t| ideal(t)))
+        .fold(0.0, f64::max);
+    let step = nice_step((highest * 1.05).max(f64::MIN_POSITIVE) / 5.0);
+    let y_max = step * (highest * 1.05 / step).ceil().max(1.0);
+    let y = |v: f64| top + plot_height * (1.0 - v / y_max);
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n<text x=\"{}\" y=\"20\" class=\"title\">{}</text>\n",
+        left,
+        escape(title),
+        w = width,
+        h = height
+    );
+    let mut tick = 0.0;
+    while tick <= y_max + step / 2.0 {
+        svg.push_str(&format!(
+            "<line x1=\"{}\" x2=\"{}\" y1=\"{y:.1}\" y2=\"{y:.1}\" class=\"grid\"/><text x=\"{}\" y=\"{:.1}\" class=\"tick\" text-anchor=\"end\">{}</text>\n",
+            left,
+            width - right,
+            left - 6.0,
+            y(tick) + 4.0,
+            (tick * 1000.0).round() / 1000.0,
+            y = y(tick)
+        ));
+        tick += step;
+    }
+    for &t in &threads {
+        svg.push_str(&format!("<text x=\"{:.1}\" y=\"{}\" class=\"tick\" text-anchor=\"middle\">{}</text>\n", x(t), top + plot_height + 16.0, t));
+    }
+    svg.push_str(&format!(
+        "<text x=\"{:.1}\" y=\"{}\" class=\"tick\" text-anchor=\"middle\">threads (T)</text>\n",
+        left + plot_width / 2.0,
+        top + plot_height + 34.0
+    ));
+
+    let ideal_points: Vec<String> = threads.iter().map(|&t| format!("{:.1},{:.1}", x(t), y(ideal(t)))).collect();
+    svg.push_str(&format!("<polyline points=\"{}\" class=\"ideal\"/>\n", ideal_points.join(" ")));
+    for (i, s) in series.iter().enumerate() {
+        let color = COLORS[i % COLORS.len()];
+        let points: Vec<String> = s.points.iter().map(|&(t, v)| format!("{:.1},{:.1}", x(t), y(value(t, v)))).collect();
+        svg.push_str(&format!("<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"/>\n", points.join(" "), color));
+        for &(t, v) in &s.points {
+            svg.push_str(&format!(
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" fill=\"{}\"><title>{}: T={}, {:.3}</title></circle>\n",
+                x(t),
+                y(value(t, v)),
+                color,
+                escape(&s.name),
+                t,
+                value(t, v)
+            ));
+        }
+        let row = legend_top + 18.0 * i as f64;
+        svg.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"12\" height=\"12\" fill=\"{}\"/><text x=\"{}\" y=\"{}\" class=\"tick\">{}</text>\n",
+            left,
+            row,
+            color,
+            left + 18.0,
+            row + 10.0,
+            escape(&s.name)
+        ));
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// every record of one kernel, one row each; the columns are the union of
+// their params and metrics in first-seen order
+fn table(records: &[(&str, &Record)], with_sources: bool) -> String {
+    let mut params: Vec<&str> = Vec::new();
+    let mut metrics: Vec<(&str, &str)> = Vec::new();
+    for (_, record) in records {
+        for (k, _) in &record.params {
+            if !params.contains(&k.as_str()) {
+                params.push(k);
+            }
+        }
+        for m in &record.metrics {
+            if !metrics.iter().any(|(n, _)| *n == m.name) {
+                metrics.push((&m.name, &m.unit));
+            }
+        }
+    }
+
+    let mut html = String::from("<table>\n<tr>");
+    if with_sources {
+        html.push_str("<th>file</th>");
+    }
+    html.push_str("<th>impl</th>");
+    for p in &params {
+        html.push_str(&format!("<th>{}</th>", escape(p)));
+    }
+    for (name, unit) in &metrics {
+        html.push_str(&format!("<th class=\"num\">{} ({})</th>", escape(name), escape(unit)));
+    }
+    html.push_str("</tr>\n");
+    for (source, record) in records {
+        html.push_str("<tr>");
+        if with_sources {
+            html.push_str(&format!("<td>{}</td>", escape(source)));
+        }
+        html.push_str(&format!("<td>{}</td>", escape(&record.implementation)));
+        for p in &params {
+            html.push_str(&format!("<td>{}</td>", escape(param(record, p).unwrap_or(""))));
+        }
+        for (name, _) in &metrics {
+            match record.metrics.iter().find(|m| m.name == *name) {
+                Some(m) => html.push_str(&format!("<td class=\"num\">{:.*}</td>", m.precision(), m.value)),
+                None => html.push_str("<td></td>"),
+            }
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em; color: #222; }
+table { border-collapse: collapse; margin: 1em 0; font-size: 0.85em; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.5em; }
+th { background: #f2f2f2; }
+td.num, th.num { text-align: right; }
+svg { margin: 0.5em 1em 0.5em 0; }
+svg .title { font-size: 14px; font-weight: bold; }
+svg .tick { font-size: 11px; fill: #444; }
+svg .grid { stroke: #e4e4e4; }
+svg .ideal { fill: none; stroke: #999; stroke-dasharray: 4 4; }";
+
+/// The standalone HTML page of `sources`.
+pub fn render(sources: &[Source]) -> String {
+    let with_sources = sources.len() > 1;
+    let records: Vec<(&str, &Record)> = sources.iter().flat_map(|s| s.records.iter().map(move |r| (s.name.as_str(), r))).collect();
+    let mut kernels: Vec<&str> = Vec::new();
+    for (_, record) in &records {
+        if !kernels.contains(&record.kernel.as_str()) {
+            kernels.push(&record.kernel);
+        }
+    }
+
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Benchmark report</title>\n<style>\n{}\n</style>\n</head>\n<body>\n<h1>Benchmark report</h1>\n",
+        STYLE
+    );
+    html.push_str("<h2>Sources</h2>\n");
+    for source in sources {
+        html.push_str(&format!("<h3>{} ({} records)</h3>\n", escape(&source.name), source.records.len()));
+        if !source.metadata.is_empty() {
+            html.push_str("<table>\n");
+            for (key, value) in &source.metadata {
+                html.push_str(&format!("<tr><th>{}</th><td>{}</td></tr>\n", escape(key), escape(value)));
+            }
+            html.push_str("</table>\n");
+        }
+    }
+    for kernel in kernels {
+        let of_kernel: Vec<(&str, &Record)> = records.iter().copied().filter(|(_, r)| r.kernel == kernel).collect();
+        html.push_str(&format!("<h2>{}</h2>\n", escape(kernel)));
+        let series = speedups(&of_kernel, with_sources);
+        if !series.is_empty() {
+            html.push_str(&chart(&format!("{}: speedup", kernel), &series, |_, s| s, |t| t as f64));
+            html.push_str(&chart(&format!("{}: efficiency", kernel), &series, |t, s| s / t as f64, |_| 1.0));
+        }
+        html.push_str(&table(&of_kernel, with_sources));
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let csv = "=== banner ===\n# cpu_model=Example CPU\n# cpu_model=Other CPU\nhist,openmp,N=100,T=1,time,0.400000,sec\nhist,openmp,N=100,T=1,correct,1,boolean\n\
+                   hist,openmp,N=100,T=2,time,0.200000,sec\nThreads: 2, of 4\nhist,openmp,N=100,T=2,time,0.210000,sec\n";
+        let (metadata, records) = parse(csv).unwrap();
+        assert_eq!(metadata, [("cpu_model".to_string(), "Example CPU".to_string())]);
+        // a repeated metric starts a new record of the same cell
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].label(), "hist,openmp,N=100,T=1");
+        assert_eq!(records[0].metrics.len(), 2);
+
+        let record = Record::new("matmul", "rayon").param("n", 64).param("T", 2).metric("time", 0.5, "sec");
+        let jsonl = format!("{}\n{}\n", Metadata::collect().to_jsonl_header(), record.to_json());
+        let (metadata, records) = parse(&jsonl).unwrap();
+        assert_eq!(metadata[0].0, "cpu_model");
+        assert_eq!(records[0].label(), "matmul,rayon,n=64,T=2");
+        let document = crate::results::json_document(&Metadata::collect(), std::slice::from_ref(&record));
+        assert_eq!(parse(&document).unwrap().1.len(), 1);
+        assert_eq!(parse(&format!("[{}]", record.to_json())).unwrap().0, []);
+
+        assert!(parse("nothing to see\n").unwrap_err().contains("no records"));
+        assert!(parse("{\"kernel\": 1}\n").unwrap_err().starts_with("line 1:"));
+    }
+
+    #[test]
+    fn test_speedups() {
+        let cell = |implementation: &str, t: usize, time: f64| Record::new("hist", implementation).param("N", 100).param("seed", 1).param("T", t).metric("time", time, "sec");
+        let own = Record::new("hist", "own").param("seed", 1).param("T", 4).metric("time", 9.0, "sec").metric("speedup", 3.0, "x");
+        let own1 = Record::new("hist", "own").param("seed", 1).param("T", 1).metric("time", 1.0, "sec").metric("speedup", 1.0, "x");
+        let records = [cell("rayon", 2, 0.25), cell("rayon", 1, 0.4), cell("single", 1, 1.0), own1, own];
+        let tagged: Vec<(&str, &Record)> = records.iter().map(|r| ("a.csv", r)).collect();
+
+        let series = speedups(&tagged, false);
+        // seed is the same throughout and left out of the names
+        assert_eq!(series, [
+            Series { name: "rayon N=100".to_string(), points: vec![(1, 1.0), (2, 1.6)] },
+            Series { name: "own".to_string(), points: vec![(1, 1.0), (4, 3.0)] },
+        ]);
+        assert_eq!(speedups(&tagged, true)[0].name, "rayon N=100 [a.csv]");
+
+        let sized = [cell("rayon", 1, 0.4), Record::new("hist", "rayon").param("N", 400).param("seed", 1).param("T", 1).metric("par_time", 1.5, "sec")];
+        let sized: Vec<(&str, &Record)> = sized.iter().map(|r| ("a.csv", r)).collect();
+        assert_eq!(size_param(&sized), Some("N"));
+        assert_eq!(size_param(&tagged), None);
+        assert_eq!(times(&sized, "N", false), [Series { name: "rayon".to_string(), points: vec![(100, 0.4), (400, 1.5)] }]);
+    }
+
+    #[test]
+    fn test_render() {
+        let records = vec![
+            Record::new("matmul", "rayon").param("n", "<64>").param("T", 1).metric("time", 1.0, "sec"),
+            Record::new("matmul", "rayon").param("n", "<64>").param("T", 4).metric("time", 0.5, "sec"),
+            Record::new("matmul", "rayon").param("n", 32).param("T", 1).metric("time", 0.1, "sec"),
+        ];
+        let source = Source { name: "run.json".to_string(), metadata: vec![("os".to_string(), "linux".to_string())], records };
+        let html = render(&[source]);
+        assert!(html.starts_with("<!DOCTYPE html>") && html.ends_with("</html>\n"));
+        assert_eq!(html.matches("<svg").count(), 2);
+        assert!(html.contains("<h2>matmul</h2>") && html.contains("<td>&lt;64&gt;</td>"));
+        assert!(html.contains("<td class=\"num\">0.500000</td>"));
+        assert!(html.contains("<title>rayon n=&lt;64&gt;: T=4, 2.000</title>"));
+        assert_eq!(nice_step(0.42), 0.5);
+        assert_eq!(nice_step(3.0), 5.0);
+    }
+}
@@ -0,0 +1,439 @@
+// Structured benchmark results
+// A Record is one measured configuration (one "cell" of a sweep): kernel,
+// implementation, the parameters identifying the cell, and its metrics.
+// Binaries hand every finished record to a Reporter, which writes it in the
+// selected --format and forwards it to whichever sinks were enabled.
+// The CSV rows share one schema across the suites and with the OpenMP side:
+//   kernel,impl,name=value,...,metric,value,unit
+// one row per metric, the params in the order the binary added them.
+// Structured outputs open with the machine and build metadata (metadata.rs).
+
+use crate::alloc_track::AllocStats;
+use crate::interrupt;
+use crate::metadata::Metadata;
+use crate::prometheus::TextfileExporter;
+use crate::store::Store;
+use crate::upload::Uploader;
+use serde::de::{Deserializer, MapAccess, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
+use std::io::{self, Write};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Metric {
+    pub name: String,
+    pub value: f64,
+    pub unit: String,
+}
+
+impl Metric {
+    /// Decimals in CSV rows, as the binaries have always printed them.
+    pub fn precision(&self) -> usize {
+        match self.unit.as_str() {
+            "estimate" => 9,
+            "sec" | "ms" => 6,
+            "ns" | "s" | "GB/s" | "GFLOP/s" | "J" | "W" => 3,
+            "x" | "ratio" => 2,
+            _ => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Record {
+    pub kernel: String,
+    #[serde(rename = "impl")]
+    pub implementation: String,
+    // kept in insertion order so JSON matches the CSV column order
+    #[serde(serialize_with = "serialize_params", deserialize_with = "deserialize_params")]
+    pub params: Vec<(String, String)>,
+    pub metrics: Vec<Metric>,
+}
+
+fn serialize_params<S: Serializer>(params: &[(String, String)], s: S) -> Result<S::Ok, S::Error> {
+    s.collect_map(params.iter().map(|(k, v)| (k, v)))
+}
+
+// the params object back in its written order
+fn deserialize_params<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<(String, String)>, D::Error> {
+    struct Params;
+    impl<'de> Visitor<'de> for Params {
+        type Value = Vec<(String, String)>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("an object of string params")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut params = Vec::new();
+            while let Some(entry) = map.next_entry()? {
+                params.push(entry);
+            }
+            Ok(params)
+        }
+    }
+    d.deserialize_map(Params)
+}
+
+impl Record {
+    pub fn new(kernel: &str, implementation: &str) -> Self {
+        Record {
+            kernel: kernel.to_string(),
+            implementation: implementation.to_string(),
+            params: Vec::new(),
+            metrics: Vec::new(),
+        }
+    }
+
+    pub fn param(mut self, name: &str, value: impl ToString) -> Self {
+        self.params.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn metric(mut self, name: &str, value: f64, unit: &str) -> Self {
+        self.metrics.push(Metric {
+            name: name.to_string(),
+            value,
+            unit: unit.to_string(),
+        });
+        self
+    }
+
+    /// Adds the counts of an `alloc_track::Region` (nothing when untracked).
+    pub fn allocs(self, stats: Option<AllocStats>) -> Self {
+        match stats {
+            Some(s) => self
+                .metric("allocs", s.allocs as f64, "count")
+                .metric("alloc_bytes", s.bytes as f64, "bytes")
+                .metric("peak_heap", s.peak as f64, "bytes"),
+            None => self,
+        }
+    }
+
+    /// "kernel,impl,name=value,..." as in the CSV output
+    pub fn label(&self) -> String {
+        let mut label = format!("{},{}", self.kernel, self.implementation);
+        for (name, value) in &self.params {
+            label.push_str(&format!(",{}={}", name, value));
+        }
+        label
+    }
+
+    /// One CSV row per metric, newline-terminated.
+    pub fn to_csv(&self) -> String {
+        let label = self.label();
+        self.metrics
+            .iter()
+            .map(|m| format!("{},{},{:.*},{}\n", label, m.name, m.precision(), m.value, m.unit))
+            .collect()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("record serialization cannot fail")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    // the binary's own human-readable / CSV console output
+    Text,
+    // a metadata line, then one JSON object per record, written and flushed
+    // as soon as it completes
+    Jsonl,
+    // {"metadata": .., "records": [..]}, written when the reporter is dropped
+    Json,
+    // `# key=value` metadata lines, then the CSV rows of every record and
+    // nothing else, flushed per record
+    Csv,
+    // GitHub-flavored markdown tables of the records, written when the
+    // reporter is dropped
+    Markdown,
+}
+
+impl Format {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "text" => Ok(Format::Text),
+            "jsonl" => Ok(Format::Jsonl),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            "markdown" => Ok(Format::Markdown),
+            _ => Err(format!("unknown format: {} (use text|jsonl|json|csv|markdown)", s)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::Text => "text",
+            Format::Jsonl => "jsonl",
+            Format::Json => "json",
+            Format::Csv => "csv",
+            Format::Markdown => "markdown",
+        }
+    }
+
+    /// True for the formats written once all records are in.
+    pub fn is_document(self) -> bool {
+        matches!(self, Format::Json | Format::Markdown)
+    }
+}
+
+/// Writes finished records in the selected format and fans them out to the
+/// optional sinks. Sink failures are reported on stderr and never abort a run.
+/// After a Ctrl-C (see `interrupt`) dropping the reporter reports how far the
+/// sweep got. With --format json nothing reaches stdout before the drop, so
+/// a binary that exits after a failed check drops the reporter first; the
+/// same holds for --format markdown.
+pub struct Reporter {
+    format: Format,
+    uploader: Option<Uploader>,
+    prometheus: Option<TextfileExporter>,
+    store: Option<Store>,
+    recorded: usize,
+    last: Option<String>,
+    // --format json / markdown or `keep_records`: the records so far (json,
+    // markdown: until `finish` writes them)
+    collected: Option<Vec<Record>>,
+    metadata: Metadata,
+}
+
+/// The --format json document: the metadata and the records.
+pub fn json_document<T: Serialize>(metadata: &Metadata, records: &[T]) -> String {
+    #[derive(Serialize)]
+    struct Document<'a, T> {
+        metadata: &'a Metadata,
+        records: &'a [T],
+    }
+    serde_json::to_string_pretty(&Document { metadata, records }).expect("record serialization cannot fail")
+}
+
+/// The --format markdown document: the metadata as `<!-- key=value -->`
+/// comments, then one GitHub-flavored table per kernel, implementation and
+/// set of metrics, in first-seen order. Params with one value throughout a
+/// table go in its heading, the others and the metrics (`name (unit)`)
+/// become columns.
+pub fn markdown_tables(metadata: &Metadata, records: &[Record]) -> String {
+    let mut out: String = metadata.fields().iter().map(|(key, value)| format!("<!-- {}={} -->\n", key, value)).collect();
+    let mut tables: Vec<Vec<&Record>> = Vec::new();
+    for record in records {
+        let same = |r: &&Record| r.kernel == record.kernel && r.implementation == record.implementation && r.metrics.iter().map(|m| &m.name).eq(record.metrics.iter().map(|m| &m.name));
+        match tables.iter_mut().find(|t| same(&t[0])) {
+            Some(table) => table.push(record),
+            None => tables.push(vec![record]),
+        }
+    }
+    let cell = |s: &str| s.replace('|', "\\|");
+    for rows in tables {
+        let first = rows[0];
+        let mut names: Vec<&str> = Vec::new();
+        for (name, _) in rows.iter().flat_map(|r| &r.params) {
+            if !names.contains(&name.as_str()) {
+                names.push(name);
+            }
+        }
+        let value = |r: &Record, name: &str| r.params.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone()).unwrap_or_default();
+        let (fixed, varying): (Vec<&str>, Vec<&str>) = names.into_iter().partition(|name| rows.iter().all(|r| value(r, name) == value(first, name)));
+
+        let mut heading = format!("\n### {} ({})", cell(&first.kernel), cell(&first.implementation));
+        if !fixed.is_empty() {
+            let params: Vec<String> = fixed.iter().map(|name| format!("{}={}", name, value(first, name))).collect();
+            heading.push_str(&format!(" {}", cell(&params.join(", "))));
+        }
+        out.push_str(&heading);
+        out.push_str("\n\n|");
+        let mut rule = String::from("|");
+        for name in &varying {
+            out.push_str(&format!(" {} |", cell(name)));
+            rule.push_str(" --- |");
+        }
+        for m in &first.metrics {
+            out.push_str(&format!(" {} ({}) |", cell(&m.name), cell(&m.unit)));
+            rule.push_str(" ---: |");
+        }
+        out.push_str(&format!("\n{}\n", rule));
+        for record in rows {
+            out.push('|');
+            for name in &varying {
+                out.push_str(&format!(" {} |", cell(&value(record, name))));
+            }
+            for m in &record.metrics {
+                out.push_str(&format!(" {:.*} |", m.precision(), m.value));
+            }
+            out.push('\n');
+        }
+    }
+    out
+}
+
+impl Reporter {
+    /// A reporter writing `format`, optionally POSTing each record to the
+    /// `upload` URL, keeping the `prometheus` textfile updated and appending
+    /// the run to the `store` database; installs the Ctrl-C handler and
+    /// writes the metadata header of csv and jsonl.
+    pub fn new(format: Format, upload: Option<&str>, prometheus: Option<&str>, store: Option<&str>) -> Result<Self, String> {
+        let uploader = upload.map(Uploader::new).transpose()?;
+        let prometheus = prometheus.map(TextfileExporter::new);
+        let metadata = Metadata::collect();
+        let command: Vec<String> = std::env::args().collect();
+        let store = store.map(|path| Store::open(path, &metadata, &command.join(" "))).transpose()?;
+
+        let mut out = io::stdout().lock();
+        let _ = match format {
+            Format::Csv => write!(out, "{}", metadata.to_csv_header()),
+            Format::Jsonl => writeln!(out, "{}", metadata.to_jsonl_header()),
+            Format::Json | Format::Markdown | Format::Text => Ok(()),
+        };
+        let _ = out.flush();
+        drop(out);
+
+        interrupt::install();
+        Ok(Reporter {
+            format,
+            uploader,
+            prometheus,
+            store,
+            recorded: 0,
+            last: None,
+            collected: format.is_document().then(Vec::new),
+            metadata,
+        })
+    }
+
+    /// Keeps every record from now on for `records`, whatever the format.
+    pub fn keep_records(&mut self) {
+        self.collected.get_or_insert_with(Vec::new);
+    }
+
+    /// The records kept so far (see `keep_records`).
+    pub fn records(&self) -> &[Record] {
+        self.collected.as_deref().unwrap_or_default()
+    }
+
+    /// True when the binary should print its own human-readable output.
+    pub fn is_text(&self) -> bool {
+        self.format == Format::Text
+    }
+
+    pub fn record(&mut self, record: &Record) {
+        tracing::info!(kernel = %record.kernel, implementation = %record.implementation, record = %record.to_json(), "result");
+
+        match self.format {
+            Format::Jsonl => {
+                let mut out = io::stdout().lock();
+                let _ = writeln!(out, "{}", record.to_json());
+                let _ = out.flush();
+            }
+            Format::Csv => {
+                let mut out = io::stdout().lock();
+                let _ = write!(out, "{}", record.to_csv());
+                let _ = out.flush();
+            }
+            Format::Json | Format::Markdown | Format::Text => {}
+        }
+        if let Some(collected) = &mut self.collected {
+            collected.push(record.clone());
+        }
+        if let Some(uploader) = &self.uploader {
+            if let Err(e) = uploader.post(&record.to_json()) {
+                eprintln!("warning: upload to {} failed: {}", uploader.url(), e);
+            }
+        }
+        if let Some(exporter) = &mut self.prometheus {
+            if let Err(e) = exporter.update(record) {
+                eprintln!("warning: writing {} failed: {}", exporter.path(), e);
+            }
+        }
+        if let Some(store) = &mut self.store {
+            if let Err(e) = store.insert(record) {
+                eprintln!("warning: storing the result in {} failed: {}", store.path(), e);
+            }
+        }
+        self.recorded += 1;
+        self.last = Some(record.label());
+    }
+
+    /// Writes the --format json or markdown document, if not written yet;
+    /// dropping the reporter does the same.
+    pub fn finish(&mut self) {
+        let document = match self.format {
+            Format::Json => self.collected.take().map(|records| json_document(&self.metadata, &records) + "\n"),
+            Format::Markdown => self.collected.take().map(|records| markdown_tables(&self.metadata, &records)),
+            Format::Jsonl | Format::Csv | Format::Text => None,
+        };
+        if let Some(document) = document {
+            let _ = write!(io::stdout(), "{}", document);
+        }
+        let _ = io::stdout().flush();
+    }
+}
+
+impl Drop for Reporter {
+    fn drop(&mut self) {
+        self.finish();
+        if interrupt::requested() {
+            match &self.last {
+                Some(last) => eprintln!("interrupted after {} result(s); last completed cell: {}", self.recorded, last),
+                None => eprintln!("interrupted before the first result"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_json() {
+        let record = Record::new("hist", "rayon")
+            .param("strategy", "atomic")
+            .param("N", 1000)
+            .metric("time", 0.5, "sec");
+
+        assert_eq!(
+            record.to_json(),
+            r#"{"kernel":"hist","impl":"rayon","params":{"strategy":"atomic","N":"1000"},"metrics":[{"name":"time","value":0.5,"unit":"sec"}]}"#
+        );
+        assert_eq!(
+            Record::new("matmul", "rayon").param("n", 256).metric("time", 0.5, "sec").metric("speedup", 3.456, "x").to_csv(),
+            "matmul,rayon,n=256,time,0.500000,sec\nmatmul,rayon,n=256,speedup,3.46,x\n"
+        );
+        let parsed: Record = serde_json::from_str(&record.to_json()).unwrap();
+        assert_eq!(parsed.params, [("strategy".to_string(), "atomic".to_string()), ("N".to_string(), "1000".to_string())]);
+        assert_eq!(parsed.to_json(), record.to_json());
+        assert_eq!(Format::parse("json"), Ok(Format::Json));
+        assert_eq!(Format::parse("csv"), Ok(Format::Csv));
+        assert!(Format::parse("xml").is_err());
+    }
+
+    #[test]
+    fn test_csv_precision() {
+        assert_eq!(
+            Record::new("matmul", "rayon").param("n", 64).metric("gflops", 0.4321, "GFLOP/s").to_csv(),
+            "matmul,rayon,n=64,gflops,0.432,GFLOP/s\n"
+        );
+        assert_eq!(
+            Record::new("hist", "rayon").metric("energy", 0.4, "J").metric("power", 38.7, "W").to_csv(),
+            "hist,rayon,energy,0.400,J\nhist,rayon,power,38.700,W\n"
+        );
+    }
+
+    #[test]
+    fn test_markdown_tables() {
+        let cell = |threads: usize, time: f64| Record::new("matmul", "rayon").param("n", 64).param("T", threads).metric("time", time, "sec").metric("speedup", 0.5 / time, "x");
+        let records = [
+            cell(1, 0.5),
+            Record::new("overhead", "rayon").param("primitive", "barrier").param("T", 2).param("R", "a|b").metric("per_op", 12.5, "ns"),
+            cell(2, 0.25),
+        ];
+        let markdown = markdown_tables(&Metadata::collect(), &records);
+        assert!(markdown.starts_with("<!-- cpu_model="));
+        let tables = markdown.split_once("\n\n### ").unwrap().1;
+        assert_eq!(
+            tables,
+            "matmul (rayon) n=64\n\n| T | time (sec) | speedup (x) |\n| --- | ---: | ---: |\n| 1 | 0.500000 | 1.00 |\n| 2 | 0.250000 | 2.00 |\n\n\
+             ### overhead (rayon) primitive=barrier, T=2, R=a\\|b\n\n| per_op (ns) |\n| ---: |\n| 12.500 |\n"
+        );
+        assert_eq!(Format::parse("markdown").map(Format::is_document), Ok(true));
+    }
+}
@@ -0,0 +1,84 @@
+// Peak resident set size (Linux, /proc/self/status)
+// A Peak resets the process's RSS high-water mark (VmHWM) by writing 5 to
+// /proc/self/clear_refs, and reads it back when the timed runs are done, so
+// records carry
+//   peak_rss   highest resident memory, in bytes, while the runs were timed
+// This is the figure /usr/bin/time -v and getrusage report for the C/OpenMP
+// binaries, but confined to the measure phase: inputs built during setup
+// count only as far as they are still resident. Heap bytes allocated during
+// the runs come from alloc-track builds (alloc_track.rs). Where the kernel
+// offers neither file records keep their usual metrics.
+
+use crate::results::Record;
+use std::fs;
+use std::path::Path;
+
+const STATUS: &str = "/proc/self/status";
+const CLEAR_REFS: &str = "/proc/self/clear_refs";
+
+// "VmHWM:     1784 kB" in bytes
+fn high_water_mark(status: &str) -> Option<u64> {
+    let line = status.lines().find_map(|l| l.strip_prefix("VmHWM:"))?;
+    let kb: u64 = line.trim().strip_suffix("kB")?.trim().parse().ok()?;
+    Some(kb * 1024)
+}
+
+/// The process's peak RSS so far (since the last reset), in bytes.
+pub fn peak() -> Option<u64> {
+    high_water_mark(&fs::read_to_string(STATUS).ok()?)
+}
+
+/// True when the high-water mark can be reset and read on this system.
+pub fn available() -> bool {
+    Path::new(CLEAR_REFS).exists() && peak().is_some()
+}
+
+/// The peak RSS between `start` and `finish`.
+pub struct Peak {
+    reset: bool,
+}
+
+impl Peak {
+    pub fn start() -> Self {
+        Peak { reset: fs::write(CLEAR_REFS, "5").is_ok() }
+    }
+
+    /// The peak in bytes; None when the mark could not be reset (it would
+    /// cover the whole process) or read.
+    pub fn finish(self) -> Option<u64> {
+        if self.reset {
+            peak()
+        } else {
+            None
+        }
+    }
+}
+
+impl Record {
+    /// Adds the peak RSS metric, if measured.
+    pub fn peak_rss(self, bytes: Option<u64>) -> Self {
+        match bytes {
+            Some(b) => self.metric("peak_rss", b as f64, "bytes"),
+            None => self,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peak() {
+        assert_eq!(high_water_mark("Name:\tx\nVmPeak:\t  9000 kB\nVmHWM:\t    1784 kB\nVmRSS:\t 1000 kB\n"), Some(1784 * 1024));
+        assert_eq!(high_water_mark("VmRSS:\t 1000 kB\n"), None);
+        if cfg!(target_os = "linux") {
+            let peak = Peak::start();
+            let buffer = vec![1u8; 64 << 20];
+            std::hint::black_box(&buffer);
+            assert!(peak.finish().unwrap() >= 64 << 20);
+        }
+        let record = Record::new("k", "rust").peak_rss(Some(4096)).peak_rss(None);
+        assert_eq!(record.metrics.len(), 1);
+    }
+}
@@ -0,0 +1,179 @@
+// Lock types of the runtime_overhead benchmarks
+// The mutex, rwlock and condvar benchmarks are written once against these
+// traits and run on std::sync's types, and with --features parking-lot on
+// parking_lot's as well. parking_lot's locks are one word, spin briefly
+// before parking and never poison; much production Rust uses them (or std's
+// newer futex locks, which borrowed their design) rather than a pthread
+// mutex. SpinLock is a test-and-test-and-set lock of the kind OpenMP
+// runtimes spin on before they sleep.
+
+use std::cell::UnsafeCell;
+use std::hint;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex, RwLock};
+
+// longest pause of SpinLock's backoff, in spin-loop hints
+const MAX_BACKOFF: u32 = 1 << 10;
+
+/// A counter behind a mutual-exclusion lock.
+pub trait CounterLock: Default + Send + Sync + 'static {
+    /// Runs `f` on the counter with the lock held.
+    fn with<R>(&self, f: impl FnOnce(&mut u64) -> R) -> R;
+}
+
+/// A counter behind a readers-writer lock.
+pub trait CounterRwLock: Default + Send + Sync + 'static {
+    /// Runs `f` on the counter under a shared lock.
+    fn read<R>(&self, f: impl FnOnce(&u64) -> R) -> R;
+    /// Runs `f` on the counter under the exclusive lock.
+    fn write<R>(&self, f: impl FnOnce(&mut u64) -> R) -> R;
+}
+
+/// A turn number (starting at 0) behind a mutex, and condvars to wait for
+/// it on.
+pub trait TurnLock: Send + Sync + 'static {
+    fn new(condvars: usize) -> Self;
+    /// Waits on condvar `cv` until the turn is `t`, then makes it `next`.
+    fn take(&self, t: usize, cv: usize, next: usize);
+    /// Wakes one, or all, of the threads waiting on condvar `cv`.
+    fn notify(&self, cv: usize, all: bool);
+    #[cfg(test)]
+    fn turn(&self) -> usize;
+}
+
+impl CounterLock for Mutex<u64> {
+    fn with<R>(&self, f: impl FnOnce(&mut u64) -> R) -> R {
+        f(&mut self.lock().unwrap())
+    }
+}
+
+/// A counter behind a test-and-test-and-set spinlock: a waiter spins on
+/// plain loads until the lock looks free, and only then tries to take it,
+/// so waiting does not write to the contended line. With `BACKOFF` a waiter
+/// that lost the race pauses 1, 2, 4, ... up to MAX_BACKOFF spin hints
+/// before it looks again.
+#[derive(Default)]
+pub struct SpinLock<const BACKOFF: bool> {
+    locked: AtomicBool,
+    value: UnsafeCell<u64>,
+}
+
+// SAFETY: `value` is only touched by the thread holding `locked`
+unsafe impl<const BACKOFF: bool> Sync for SpinLock<BACKOFF> {}
+
+impl<const BACKOFF: bool> CounterLock for SpinLock<BACKOFF> {
+    fn with<R>(&self, f: impl FnOnce(&mut u64) -> R) -> R {
+        let mut backoff = 1;
+        while self.locked.swap(true, Ordering::Acquire) {
+            if BACKOFF {
+                for _ in 0..backoff {
+                    hint::spin_loop();
+                }
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+            while self.locked.load(Ordering::Relaxed) {
+                hint::spin_loop();
+            }
+        }
+        // SAFETY: the swap above took the lock, so no other thread is here
+        let result = f(unsafe { &mut *self.value.get() });
+        self.locked.store(false, Ordering::Release);
+        result
+    }
+}
+
+impl CounterRwLock for RwLock<u64> {
+    fn read<R>(&self, f: impl FnOnce(&u64) -> R) -> R {
+        f(&self.read().unwrap())
+    }
+
+    fn write<R>(&self, f: impl FnOnce(&mut u64) -> R) -> R {
+        f(&mut self.write().unwrap())
+    }
+}
+
+/// std's `Mutex` and `Condvar`.
+pub struct StdTurns {
+    turn: Mutex<usize>,
+    condvars: Vec<Condvar>,
+}
+
+impl TurnLock for StdTurns {
+    fn new(condvars: usize) -> Self {
+        StdTurns { turn: Mutex::new(0), condvars: (0..condvars).map(|_| Condvar::new()).collect() }
+    }
+
+    fn take(&self, t: usize, cv: usize, next: usize) {
+        let mut turn = self.turn.lock().unwrap();
+        while *turn != t {
+            turn = self.condvars[cv].wait(turn).unwrap();
+        }
+        *turn = next;
+    }
+
+    fn notify(&self, cv: usize, all: bool) {
+        if all {
+            self.condvars[cv].notify_all();
+        } else {
+            self.condvars[cv].notify_one();
+        }
+    }
+
+    #[cfg(test)]
+    fn turn(&self) -> usize {
+        *self.turn.lock().unwrap()
+    }
+}
+
+#[cfg(feature = "parking-lot")]
+impl CounterLock for parking_lot::Mutex<u64> {
+    fn with<R>(&self, f: impl FnOnce(&mut u64) -> R) -> R {
+        f(&mut self.lock())
+    }
+}
+
+#[cfg(feature = "parking-lot")]
+impl CounterRwLock for parking_lot::RwLock<u64> {
+    fn read<R>(&self, f: impl FnOnce(&u64) -> R) -> R {
+        f(&self.read())
+    }
+
+    fn write<R>(&self, f: impl FnOnce(&mut u64) -> R) -> R {
+        f(&mut self.write())
+    }
+}
+
+/// parking_lot's `Mutex` and `Condvar`.
+#[cfg(feature = "parking-lot")]
+pub struct ParkingLotTurns {
+    turn: parking_lot::Mutex<usize>,
+    condvars: Vec<parking_lot::Condvar>,
+}
+
+#[cfg(feature = "parking-lot")]
+impl TurnLock for ParkingLotTurns {
+    fn new(condvars: usize) -> Self {
+        ParkingLotTurns { turn: parking_lot::Mutex::new(0), condvars: (0..condvars).map(|_| parking_lot::Condvar::new()).collect() }
+    }
+
+    fn take(&self, t: usize, cv: usize, next: usize) {
+        let mut turn = self.turn.lock();
+        while *turn != t {
+            self.condvars[cv].wait(&mut turn);
+        }
+        *turn = next;
+    }
+
+    fn notify(&self, cv: usize, all: bool) {
+        if all {
+            self.condvars[cv].notify_all();
+        } else {
+            self.condvars[cv].notify_one();
+        }
+    }
+
+    #[cfg(test)]
+    fn turn(&self) -> usize {
+        *self.turn.lock()
+    }
+}
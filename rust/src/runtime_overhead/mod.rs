@@ -1,37 +1,282 @@
 // Runtime Overhead Benchmarks
 // Measures the cost of thread operations and synchronization primitives
+//
+// Options:
+//   --format FMT       text (default, CSV lines) | jsonl | json | csv | markdown
+//   --upload URL       POST each result as JSON to an HTTP collector
+//   --prometheus FILE  keep FILE updated with Prometheus gauges for each result
+//   --store FILE       append the run to a SQLite database (--features store)
+//   --log FMT          pretty | json execution log on stderr (RUST_LOG filters)
+//   --describe         print the JSON description of arguments and metrics, then exit
+//   --threads LIST     thread counts (default 1,2,4,8,16), e.g. 1,2,4,half,max
+//   --iters LIST       repetitions R of all but fib, task, spawn_join and parfor
+//                      (default 10000,25000,50000,75000,100000)
+//   --only LIST        primitives to measure (default all), e.g. barrier,mutex:
+//                      parallel (or spawn) | barrier | mutex | atomic | fib | task
+//                      | reuse | condvar_one | condvar_all | rwlock | channel
+//                      | spawn_join | cas | parfor | call
+//   --write-ratio LIST percentages of writes in the rwlock cells (default
+//                      1,10,50), e.g. 0,1,10,50,100
+//   --reps R           timed runs per cell, the best is reported (default 1);
+//                      R > 1 adds _mean, _median and _stddev to each metric
+//   --warmup W         untimed runs before them (default 1; 0 for a cold start)
+//   --timer CLOCK      instant (default) | tsc: time the runs with the CPU's
+//                      time-stamp counter (x86_64 with an invariant TSC)
 
-use rayon;
-use std::sync::{Arc, Barrier, Mutex};
+mod locks;
+
+use crate::alloc_track::AllocStats;
+use crate::backend::{Backend, Pool};
+use crate::cli;
+use crate::describe::{Description, Param};
+use crate::interrupt;
+use crate::kernels::{Benchmark, Suite};
+use crate::logging::Phase;
+use crate::results::{Record, Reporter};
+use crate::scheduler::{self, Scheduler};
+use crate::stats::{Repeat, Stats};
+use crate::threads;
+use crate::timer::Timer;
+use clap::{ArgMatches, CommandFactory, Parser};
+use crossbeam::utils::CachePadded;
+use rayon::prelude::*;
+use std::fmt::Display;
+use std::sync::{Arc, Barrier, Mutex, RwLock};
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::Instant;
+use tracing::info_span;
+use locks::{CounterLock, CounterRwLock, SpinLock, StdTurns, TurnLock};
+#[cfg(feature = "parking-lot")]
+use locks::ParkingLotTurns;
+
+// thread counts unless --threads is given
+pub const THREAD_COUNTS: &str = "1,2,4,8,16";
+// repetitions unless --iters is given
+pub const ITERATIONS: &str = "10000,25000,50000,75000,100000";
+
+// task benchmarks: fib(FIB_N) as a binary join tree, and BATCHES scopes of
+// TASKS_PER_BATCH empty tasks each
+const FIB_N: u64 = 25;
+const BATCHES: usize = 1_000;
+const TASKS_PER_BATCH: usize = 256;
+
+// spawn_join: rounds of T OS threads, each reading SHARED_WORDS words
+const SPAWN_ROUNDS: usize = 1_000;
+const SHARED_WORDS: usize = 64;
+
+// parfor: loops over 10 to 10^7 elements, each size repeated about
+// PARFOR_WORK / N times, within PARFOR_REPS
+const PARFOR_SIZES: [usize; 7] = [10, 100, 1_000, 10_000, 100_000, 1_000_000, 10_000_000];
+const PARFOR_WORK: usize = 100_000_000;
+const PARFOR_REPS: (usize, usize) = (10, 100_000);
+
+// primitives in output order; each cell is a record with primitive=<name>
+// and its total and per_op times
+const PRIMITIVES: [&str; 15] = ["parallel", "barrier", "mutex", "atomic", "fib", "task", "reuse", "condvar_one", "condvar_all", "rwlock", "channel", "spawn_join", "cas", "parfor", "call"];
+
+// orderings of the atomic benchmark; local is a plain per-thread counter
+const ATOMIC_ORDERS: [(&str, Option<Ordering>); 4] = [("seqcst", Some(Ordering::SeqCst)), ("acqrel", Some(Ordering::AcqRel)), ("relaxed", Some(Ordering::Relaxed)), ("local", None)];
+
+// percentages of writes unless --write-ratio is given
+pub const WRITE_RATIOS: &str = "1,10,50";
+
+// pools of the reuse benchmark: threads spawns and joins T threads for every
+// round, workers and rayon keep theirs
+const REUSE_BACKENDS: [Backend; 3] = [Backend::Threads, Backend::Workers, Backend::Rayon];
+
+// the Rayon entry points of the call benchmark, its impl names
+const RAYON_CALLS: [&str; 3] = ["join", "scope_spawn", "spawn"];
+
+/// Parses `--only`: `all`, or a comma-separated list of primitives, with
+/// `spawn` for parallel; the result is in output order.
+pub fn parse_only(list: &str) -> Result<Vec<&'static str>, String> {
+    if list == "all" {
+        return Ok(PRIMITIVES.to_vec());
+    }
+    let mut only = Vec::new();
+    for name in list.split(',').map(str::trim) {
+        let name = if name == "spawn" { "parallel" } else { name };
+        let Some(&primitive) = PRIMITIVES.iter().find(|&&p| p == name) else {
+            return Err(format!("unknown primitive: {} (use all or a list of {}, spawn)", name, PRIMITIVES.join(", ")));
+        };
+        only.push(primitive);
+    }
+    Ok(PRIMITIVES.into_iter().filter(|p| only.contains(p)).collect())
+}
+
+/// Parses `--write-ratio`: a comma-separated list of percentages from 0 to
+/// 100, with or without `%`, into sorted, distinct values.
+pub fn parse_write_ratios(list: &str) -> Result<Vec<usize>, String> {
+    let mut ratios = Vec::new();
+    for ratio in list.split(',').map(str::trim) {
+        match ratio.trim_end_matches('%').parse::<usize>() {
+            Ok(pct) if pct <= 100 => ratios.push(pct),
+            _ => return Err(format!("write ratio must be a percentage from 0 to 100, got {}", ratio)),
+        }
+    }
+    ratios.sort_unstable();
+    ratios.dedup();
+    Ok(ratios)
+}
+
+// answer to --describe
+pub fn description() -> Description {
+    Description::new("runtime_overhead", "overhead", "cost of parallel regions, barriers, locks, atomics and tasks")
+        .threads_option(THREAD_COUNTS, "thread counts")
+        .option(Param::integer_list("--iters", 1, "repetitions R of the cells of every primitive but fib, task, spawn_join and parfor").default(ITERATIONS))
+        .option(Param::text("--only", "primitives to measure: all, or a list of parallel (spawn), barrier, mutex, atomic, fib, task, reuse, condvar_one, condvar_all, rwlock, channel, spawn_join, cas, parfor, call").default("all"))
+        .option(Param::integer_list("--write-ratio", 0, "percentages of writes in the rwlock cells").default(WRITE_RATIOS))
+        .option(Param::choice("--timer", &["instant", "tsc"], "clock the timed runs are measured with").default("instant"))
+        .params(&["primitive", "T", "R", "write_pct", "order", "contended", "N"])
+        .timing("total", "ms")
+        .timing("per_op", "ns")
+        // cas and parfor only
+        .metric("retries", "ratio")
+        .metric("crossover", "elements")
+}
 
-const THREAD_COUNTS: &[usize] = &[1, 2, 4, 8, 16];
-const ITERATIONS: &[usize] = &[10_000, 25_000, 50_000, 75_000, 100_000];
+/// Cost of parallel regions, barriers, locks, atomics and tasks
+#[derive(Debug, Clone, Parser)]
+#[command(name = "runtime_overhead")]
+pub struct Args {
+    /// thread counts, e.g. 1,2,4,half,max
+    #[arg(long, value_name = "LIST", value_parser = threads::parse_list, default_value = THREAD_COUNTS)]
+    pub threads: ::std::vec::Vec<usize>,
+    /// repetitions of the cells of every primitive but fib, task, spawn_join and parfor
+    #[arg(long, value_name = "LIST", value_parser = cli::positive_list, default_value = ITERATIONS)]
+    pub iters: ::std::vec::Vec<usize>,
+    /// primitives to measure: all, or e.g. spawn,barrier (parallel | spawn,
+    /// barrier, mutex, atomic, fib, task, reuse, condvar_one, condvar_all,
+    /// rwlock, channel, spawn_join, cas, parfor, call)
+    #[arg(long, value_name = "LIST", value_parser = parse_only, default_value = "all")]
+    pub only: ::std::vec::Vec<&'static str>,
+    /// percentages of writes in the rwlock cells, e.g. 1,10,50
+    #[arg(long, value_name = "LIST", value_parser = parse_write_ratios, default_value = WRITE_RATIOS)]
+    pub write_ratio: ::std::vec::Vec<usize>,
+    /// clock of the timed runs: instant | tsc (x86_64 time-stamp counter)
+    #[arg(long, value_name = "CLOCK", value_parser = Timer::parse, default_value = "instant")]
+    pub timer: Timer,
+    #[command(flatten)]
+    pub common: cli::Common,
+}
+
+pub struct RuntimeOverhead;
+
+impl Benchmark for RuntimeOverhead {
+    fn name(&self) -> &'static str {
+        "runtime_overhead"
+    }
+
+    fn label(&self) -> &'static str {
+        "Runtime overhead"
+    }
+
+    fn suite(&self) -> Suite {
+        Suite::RuntimeOverhead
+    }
+
+    fn description(&self) -> Description {
+        description()
+    }
 
-pub fn run_all_benchmarks() {
+    fn command(&self) -> clap::Command {
+        Args::command()
+    }
+
+    fn run(&self, args: &ArgMatches, reporter: &mut Reporter) -> i32 {
+        run(&cli::from_matches(args), reporter)
+    }
+}
+
+pub fn run(args: &Args, reporter: &mut Reporter) -> i32 {
+    run_all_benchmarks(&args.threads, &args.iters, &args.write_ratio, &args.only, Repeat { timer: args.timer, ..args.common.reps.repeat(1) }, reporter);
+    0
+}
+
+/// Measures the primitives of `only` (names from `parse_only`) at every
+/// thread count, and all but fib, task, spawn_join and parfor, which have fixed sizes, also at
+/// every count of `iterations`; rwlock also at every percentage of writes.
+pub fn run_all_benchmarks(thread_counts: &[usize], iterations: &[usize], write_ratios: &[usize], only: &[&str], repeat: Repeat, reporter: &mut Reporter) {
     // CSV output format matching OpenMP for easy comparison and data processing
-    spawn_join_benchmark();
-    barrier_benchmark();
-    mutex_benchmark();
-    atomic_benchmark();
+    for &primitive in only {
+        match primitive {
+            "parallel" => spawn_join_benchmark(thread_counts, iterations, repeat, reporter),
+            "barrier" => barrier_benchmark(thread_counts, iterations, repeat, reporter),
+            "mutex" => mutex_benchmark(thread_counts, iterations, repeat, reporter),
+            "atomic" => atomic_benchmark(thread_counts, iterations, repeat, reporter),
+            "fib" => fib_benchmark(thread_counts, repeat, reporter),
+            "task" => batched_tasks_benchmark(thread_counts, repeat, reporter),
+            "reuse" => pool_reuse_benchmark(thread_counts, iterations, repeat, reporter),
+            "condvar_one" => condvar_benchmark(thread_counts, iterations, false, repeat, reporter),
+            "condvar_all" => condvar_benchmark(thread_counts, iterations, true, repeat, reporter),
+            "rwlock" => rwlock_benchmark(thread_counts, iterations, write_ratios, repeat, reporter),
+            "channel" => channel_benchmark(thread_counts, iterations, repeat, reporter),
+            "spawn_join" => os_threads_benchmark(thread_counts, repeat, reporter),
+            "cas" => cas_benchmark(thread_counts, iterations, repeat, reporter),
+            "parfor" => parallel_for_benchmark(thread_counts, repeat, reporter),
+            "call" => rayon_call_benchmark(thread_counts, iterations, repeat, reporter),
+            _ => panic!("unknown primitive: {}", primitive),
+        }
+    }
+}
+
+// the times, result and allocations of a cell's runs (Repeat::measure)
+type Measured = (Stats, (), Option<AllocStats>);
+
+// one record per (primitive, T, R, ...) cell, `params` after the primitive,
+// from the times of its runs (in seconds) over `ops` operations: the total
+// and the time per operation; text format prints the two CSV lines
+fn report_cell(reporter: &mut Reporter, implementation: &str, primitive: &str, params: &[(&str, &dyn Display)], time: &Stats, ops: usize, allocs: Option<AllocStats>) {
+    report_cell_with(reporter, implementation, primitive, params, time, ops, allocs, &[]);
+}
+
+// report_cell with `extra` metrics (name, value, unit) after the timings,
+// one more CSV line each
+#[allow(clippy::too_many_arguments)]
+fn report_cell_with(reporter: &mut Reporter, implementation: &str, primitive: &str, params: &[(&str, &dyn Display)], time: &Stats, ops: usize, allocs: Option<AllocStats>, extra: &[(&str, f64, &str)]) {
+    let total_ms = time.scaled(1e3);
+    let avg_ns = time.scaled(1e9 / ops as f64);
+    if reporter.is_text() {
+        let cell: String = params.iter().map(|(name, value)| format!(",{}={}", name, value)).collect();
+        println!("overhead,{},primitive={}{},total,{:.6},ms",
+            implementation, primitive, cell, total_ms.min);
+        println!("overhead,{},primitive={}{},per_op,{:.3},ns",
+            implementation, primitive, cell, avg_ns.min);
+        for (name, value, unit) in extra {
+            println!("overhead,{},primitive={}{},{},{:.3},{}", implementation, primitive, cell, name, value, unit);
+        }
+    }
+
+    let record = params.iter().fold(Record::new("overhead", implementation).param("primitive", primitive), |record, (name, value)| record.param(name, value))
+        .timing("total", &total_ms, "ms")
+        .timing("per_op", &avg_ns, "ns");
+    let record = extra.iter().fold(record, |record, &(name, value, unit)| record.metric(name, value, unit));
+    reporter.record(
+        &record
+            .allocs(allocs)
+            .energy(time.energy)
+            .peak_rss(time.peak_rss),
+    );
 }
 
 /// 1: Parallel Scope (Rayon)
 /// overhead of parallel regions using Rayon thread pool (comparable to OpenMP)
 /// Measures cost per parallel scope creation (like OpenMP's parallel region)
-fn spawn_join_benchmark() {
-    for &num_threads in THREAD_COUNTS {
-        for &iterations in ITERATIONS {
+fn spawn_join_benchmark(thread_counts: &[usize], iteration_counts: &[usize], repeat: Repeat, reporter: &mut Reporter) {
+    for &num_threads in thread_counts {
+        for &iterations in iteration_counts {
+            if interrupt::requested() {
+                return;
+            }
             //  thread pool with specific size ( OpenMP's omp_set_num_threads)
-            let pool = rayon::ThreadPoolBuilder::new()
-                .num_threads(num_threads)
-                .build()
-                .unwrap();
-            
-            let start = Instant::now();
+            let _cell = info_span!("overhead", primitive = "parallel", threads = num_threads, iterations).entered();
+            let pool = Phase::Setup.run(|| {
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(num_threads)
+                    .build()
+                    .unwrap()
+            });
             
-            pool.install(|| {
+            let (time, (), allocs) = repeat.measure(|| pool.install(|| {
                 for _ in 0..iterations {
                     // creates parallel region like OpenMP
                     rayon::scope(|s| {
@@ -42,35 +287,31 @@ fn spawn_join_benchmark() {
                         }
                     });
                 }
-            });
-            
-            let duration = start.elapsed();
-            // cost per parallel scope,
-            let total_ms = duration.as_secs_f64() * 1000.0;
-            let avg_ns = duration.as_nanos() as f64 / iterations as f64;
-            
-            println!("overhead,rust,T={},R={},parallel_total,{:.6},ms",
-                num_threads, iterations, total_ms);
-            println!("overhead,rust,T={},R={},parallel_per,{:.3},ns",
-                num_threads, iterations, avg_ns);
+            }));
+            // cost per parallel scope
+            report_cell(reporter, "rust", "parallel", &[("T", &num_threads), ("R", &iterations)], &time, iterations, allocs);
         }
     }
 }
 
 /// 2: Barrier Synchronization
 /// overhead of barrier synchronization using Rayon thread pool
-fn barrier_benchmark() {
-    for &num_threads in THREAD_COUNTS {
-        for &iterations in ITERATIONS {
-            let pool = rayon::ThreadPoolBuilder::new()
-                .num_threads(num_threads)
-                .build()
-                .unwrap();
+fn barrier_benchmark(thread_counts: &[usize], iteration_counts: &[usize], repeat: Repeat, reporter: &mut Reporter) {
+    for &num_threads in thread_counts {
+        for &iterations in iteration_counts {
+            if interrupt::requested() {
+                return;
+            }
+            let _cell = info_span!("overhead", primitive = "barrier", threads = num_threads, iterations).entered();
+            let pool = Phase::Setup.run(|| {
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(num_threads)
+                    .build()
+                    .unwrap()
+            });
             
             let barrier = Arc::new(Barrier::new(num_threads));
-            let start = Instant::now();
-            
-            pool.install(|| {
+            let (time, (), allocs) = repeat.measure(|| pool.install(|| {
                 rayon::scope(|s| {
                     for _ in 0..num_threads {
                         let barrier_clone = Arc::clone(&barrier);
@@ -81,101 +322,722 @@ fn barrier_benchmark() {
                         });
                     }
                 });
+            }));
+            report_cell(reporter, "rust", "barrier", &[("T", &num_threads), ("R", &iterations)], &time, iterations * num_threads, allocs);
+        }
+    }
+}
+
+/// 3: Mutex Lock/Unlock
+/// overhead of mutex operations using Rayon thread pool; the same counter
+/// behind the test-and-test-and-set SpinLock, without and with backoff.
+/// Every lock runs contended (one lock for all threads) and uncontended
+/// (a lock per thread), telling the cost of acquiring a lock apart from
+/// the cost of fighting over it
+fn mutex_benchmark(thread_counts: &[usize], iteration_counts: &[usize], repeat: Repeat, reporter: &mut Reporter) {
+    for &num_threads in thread_counts {
+        for &iterations in iteration_counts {
+            if interrupt::requested() {
+                return;
+            }
+            let _cell = info_span!("overhead", primitive = "mutex", threads = num_threads, iterations).entered();
+            let pool = Phase::Setup.run(|| {
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(num_threads)
+                    .build()
+                    .unwrap()
             });
             
-            let duration = start.elapsed();
-            let total_ops = iterations * num_threads;
-            let total_ms = duration.as_secs_f64() * 1000.0;
-            let avg_ns = duration.as_nanos() as f64 / total_ops as f64;
-            
-            println!("overhead,rust,T={},R={},barrier_total,{:.6},ms",
-                num_threads, iterations, total_ms);
-            println!("overhead,rust,T={},R={},barrier_per,{:.3},ns",
-                num_threads, iterations, avg_ns);
+            for contended in [true, false] {
+                let params: [(&str, &dyn Display); 3] = [("T", &num_threads), ("R", &iterations), ("contended", &u8::from(contended))];
+                let (time, (), allocs) = mutex_cell::<Mutex<u64>>(&pool, num_threads, iterations, contended, repeat);
+                report_cell(reporter, "rust", "mutex", &params, &time, iterations * num_threads, allocs);
+                #[cfg(feature = "parking-lot")]
+                {
+                    let (time, (), allocs) = mutex_cell::<parking_lot::Mutex<u64>>(&pool, num_threads, iterations, contended, repeat);
+                    report_cell(reporter, "parking_lot", "mutex", &params, &time, iterations * num_threads, allocs);
+                }
+                let (time, (), allocs) = mutex_cell::<SpinLock<false>>(&pool, num_threads, iterations, contended, repeat);
+                report_cell(reporter, "spin", "mutex", &params, &time, iterations * num_threads, allocs);
+                let (time, (), allocs) = mutex_cell::<SpinLock<true>>(&pool, num_threads, iterations, contended, repeat);
+                report_cell(reporter, "spin-backoff", "mutex", &params, &time, iterations * num_threads, allocs);
+            }
         }
     }
 }
 
-/// 3: Mutex Lock/Unlock
-/// overhead of mutex operations using Rayon thread pool
-fn mutex_benchmark() {
-    for &num_threads in THREAD_COUNTS {
-        for &iterations in ITERATIONS {
+// T threads increment a counter behind an M, R times each: all the same
+// one if `contended`, otherwise each its own, a cache line apart
+fn mutex_cell<M: CounterLock>(pool: &rayon::ThreadPool, num_threads: usize, iterations: usize, contended: bool, repeat: Repeat) -> Measured {
+    let counters: Arc<Vec<CachePadded<M>>> = Arc::new((0..if contended { 1 } else { num_threads }).map(|_| CachePadded::default()).collect());
+    repeat.measure(|| pool.install(|| {
+        rayon::scope(|s| {
+            for t in 0..num_threads {
+                let counters_clone = Arc::clone(&counters);
+                s.spawn(move |_| {
+                    let counter = &counters_clone[t % counters_clone.len()];
+                    for _ in 0..iterations {
+                        counter.with(|val| *val += 1);
+                        // lock is automatically released here
+                    }
+                });
+            }
+        });
+    }))
+}
+
+/// 4: Atomic Operations
+/// overhead of atomic fetch_add operations using Rayon thread pool, at every
+/// memory ordering of ATOMIC_ORDERS; the local baseline has each thread
+/// count in a variable of its own, with neither atomicity nor contention
+fn atomic_benchmark(thread_counts: &[usize], iteration_counts: &[usize], repeat: Repeat, reporter: &mut Reporter) {
+    for &num_threads in thread_counts {
+        for &iterations in iteration_counts {
+            if interrupt::requested() {
+                return;
+            }
+            let _cell = info_span!("overhead", primitive = "atomic", threads = num_threads, iterations).entered();
+            let pool = Phase::Setup.run(|| {
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(num_threads)
+                    .build()
+                    .unwrap()
+            });
+
+            for (order_name, order) in ATOMIC_ORDERS {
+                let counter = Arc::new(AtomicU64::new(0));
+                let (time, (), allocs) = repeat.measure(|| pool.install(|| {
+                    rayon::scope(|s| {
+                        for _ in 0..num_threads {
+                            let counter_clone = Arc::clone(&counter);
+                            s.spawn(move |_| match order {
+                                Some(order) => {
+                                    for _ in 0..iterations {
+                                        counter_clone.fetch_add(1, order);
+                                    }
+                                }
+                                None => {
+                                    // volatile, like the C baseline's: one load and
+                                    // one store per iteration
+                                    let mut local = 0u64;
+                                    for _ in 0..iterations {
+                                        // SAFETY: `local` is a live, aligned u64 of this thread
+                                        unsafe { std::ptr::write_volatile(&mut local, std::ptr::read_volatile(&local) + 1) };
+                                    }
+                                    counter_clone.fetch_add(local, Ordering::Relaxed);
+                                }
+                            });
+                        }
+                    });
+                }));
+                report_cell(reporter, "rust", "atomic", &[("T", &num_threads), ("R", &iterations), ("order", &order_name)], &time, iterations * num_threads, allocs);
+            }
+        }
+    }
+}
+
+/// 5: Fork-Join Tasks (fib)
+/// fib(FIB_N) recursing through join down to single calls, on Rayon and on
+/// the minimal Chase-Lev scheduler; cost per join tells work stealing in
+/// general apart from Rayon's implementation of it
+fn fib_benchmark(thread_counts: &[usize], repeat: Repeat, reporter: &mut Reporter) {
+    let joins = fib_joins(FIB_N);
+
+    for &num_threads in thread_counts {
+        if interrupt::requested() {
+            return;
+        }
+        let _cell = info_span!("overhead", primitive = "fib", threads = num_threads, iterations = joins).entered();
+        let (pool, sched) = Phase::Setup.run(|| {
             let pool = rayon::ThreadPoolBuilder::new()
                 .num_threads(num_threads)
                 .build()
                 .unwrap();
-            
-            let counter = Arc::new(Mutex::new(0u64));
-            let start = Instant::now();
-            
-            pool.install(|| {
+            (pool, Scheduler::new(num_threads))
+        });
+
+        let (rayon_time, rayon_result, rayon_allocs) = repeat.measure(|| pool.install(|| fib_rayon(FIB_N)));
+        let (chase_lev_time, chase_lev_result, chase_lev_allocs) = repeat.measure(|| sched.install(|| fib_chase_lev(FIB_N)));
+
+        Phase::Verify.run(|| {
+            let expected = fib_seq(FIB_N);
+            assert_eq!(rayon_result, expected);
+            assert_eq!(chase_lev_result, expected);
+        });
+        report_cell(reporter, "rayon", "fib", &[("T", &num_threads), ("R", &joins)], &rayon_time, joins, rayon_allocs);
+        report_cell(reporter, "chase-lev", "fib", &[("T", &num_threads), ("R", &joins)], &chase_lev_time, joins, chase_lev_allocs);
+    }
+}
+
+/// fib(n) by naive recursion, the baseline of the fork-join variants.
+pub fn fib_seq(n: u64) -> u64 {
+    if n < 2 { n } else { fib_seq(n - 1) + fib_seq(n - 2) }
+}
+
+/// fib(n) with one `rayon::join` per call, on the current Rayon pool.
+pub fn fib_rayon(n: u64) -> u64 {
+    if n < 2 {
+        return n;
+    }
+    let (a, b) = rayon::join(|| fib_rayon(n - 1), || fib_rayon(n - 2));
+    a + b
+}
+
+/// fib(n) with one `scheduler::join` per call, on the current scheduler.
+pub fn fib_chase_lev(n: u64) -> u64 {
+    if n < 2 {
+        return n;
+    }
+    let (a, b) = scheduler::join(|| fib_chase_lev(n - 1), || fib_chase_lev(n - 2));
+    a + b
+}
+
+// joins in the fib(n) tree: one per call with n >= 2
+fn fib_joins(n: u64) -> usize {
+    let (mut prev, mut cur) = (0usize, 0usize);
+    for _ in 1..n {
+        (prev, cur) = (cur, cur + prev + 1);
+    }
+    cur
+}
+
+/// 6: Batched Small Tasks
+/// BATCHES rounds of TASKS_PER_BATCH empty tasks spawned into a scope and
+/// waited for (like `omp task` in a loop followed by `taskwait`)
+fn batched_tasks_benchmark(thread_counts: &[usize], repeat: Repeat, reporter: &mut Reporter) {
+    let tasks = BATCHES * TASKS_PER_BATCH;
+
+    for &num_threads in thread_counts {
+        if interrupt::requested() {
+            return;
+        }
+        let _cell = info_span!("overhead", primitive = "task", threads = num_threads, iterations = tasks).entered();
+        let (pool, sched) = Phase::Setup.run(|| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .unwrap();
+            (pool, Scheduler::new(num_threads))
+        });
+
+        let (rayon_time, (), rayon_allocs) = repeat.measure(|| pool.install(|| {
+            for _ in 0..BATCHES {
                 rayon::scope(|s| {
-                    for _ in 0..num_threads {
-                        let counter_clone = Arc::clone(&counter);
+                    for i in 0..TASKS_PER_BATCH {
                         s.spawn(move |_| {
-                            for _ in 0..iterations {
-                                let mut val = counter_clone.lock().unwrap();
+                            std::hint::black_box(i);
+                        });
+                    }
+                });
+            }
+        }));
+        let (chase_lev_time, (), chase_lev_allocs) = repeat.measure(|| sched.install(|| {
+            for _ in 0..BATCHES {
+                scheduler::scope(|s| {
+                    for i in 0..TASKS_PER_BATCH {
+                        s.spawn(move |_| {
+                            std::hint::black_box(i);
+                        });
+                    }
+                });
+            }
+        }));
+
+        report_cell(reporter, "rayon", "task", &[("T", &num_threads), ("R", &tasks)], &rayon_time, tasks, rayon_allocs);
+        report_cell(reporter, "chase-lev", "task", &[("T", &num_threads), ("R", &tasks)], &chase_lev_time, tasks, chase_lev_allocs);
+    }
+}
+
+/// 7: Pool Reuse vs Spawn per Round
+/// R rounds of T empty jobs handed to a pool and waited for. The threads
+/// backend spawns and joins T threads every round; workers (a channel-fed
+/// pool) and Rayon start theirs once, as an OpenMP team is reused from one
+/// parallel region to the next. Cost per round.
+fn pool_reuse_benchmark(thread_counts: &[usize], iteration_counts: &[usize], repeat: Repeat, reporter: &mut Reporter) {
+    for &num_threads in thread_counts {
+        for &iterations in iteration_counts {
+            for backend in REUSE_BACKENDS {
+                if interrupt::requested() {
+                    return;
+                }
+                let _cell = info_span!("overhead", primitive = "reuse", backend = backend.name(), threads = num_threads, iterations).entered();
+                let pool = Phase::Setup.run(|| Pool::new(backend, num_threads));
+
+                // one job per thread
+                let jobs = vec![0u8; num_threads];
+                let (time, (), allocs) = repeat.measure(|| {
+                    for _ in 0..iterations {
+                        pool.for_each_chunk(&jobs, 1, |_, job| {
+                            std::hint::black_box(job);
+                        });
+                    }
+                });
+                report_cell(reporter, backend.name(), "reuse", &[("T", &num_threads), ("R", &iterations)], &time, iterations, allocs);
+            }
+        }
+    }
+}
+
+/// 8: Condition Variable Ping-Pong
+/// T threads pass a token around a ring R times: each waits on a condvar
+/// until the shared turn is its own, then hands the turn to the next thread
+/// and wakes it. condvar_one gives every thread its own condvar and wakes
+/// the next with notify_one; condvar_all has all T wait on one condvar and
+/// wakes them all with notify_all, so T - 1 of them wake only to wait again.
+/// Cost per hand-off, i.e. per wake-up of the next thread.
+fn condvar_benchmark(thread_counts: &[usize], iteration_counts: &[usize], notify_all: bool, repeat: Repeat, reporter: &mut Reporter) {
+    let primitive = if notify_all { "condvar_all" } else { "condvar_one" };
+    for &num_threads in thread_counts {
+        for &iterations in iteration_counts {
+            if interrupt::requested() {
+                return;
+            }
+            let _cell = info_span!("overhead", primitive, threads = num_threads, iterations).entered();
+            let pool = Phase::Setup.run(|| {
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(num_threads)
+                    .build()
+                    .unwrap()
+            });
+
+            let (time, (), allocs) = condvar_cell::<StdTurns>(&pool, num_threads, iterations, notify_all, repeat);
+            report_cell(reporter, "rust", primitive, &[("T", &num_threads), ("R", &iterations)], &time, iterations * num_threads, allocs);
+            #[cfg(feature = "parking-lot")]
+            {
+                let (time, (), allocs) = condvar_cell::<ParkingLotTurns>(&pool, num_threads, iterations, notify_all, repeat);
+                report_cell(reporter, "parking_lot", primitive, &[("T", &num_threads), ("R", &iterations)], &time, iterations * num_threads, allocs);
+            }
+        }
+    }
+}
+
+// T threads on a ring of L pass the turn R times each
+fn condvar_cell<L: TurnLock>(pool: &rayon::ThreadPool, num_threads: usize, iterations: usize, notify_all: bool, repeat: Repeat) -> Measured {
+    let ring = Arc::new(Ring::<L>::new(num_threads, notify_all));
+    repeat.measure(|| pool.install(|| {
+        rayon::scope(|s| {
+            for t in 0..num_threads {
+                let ring_clone = Arc::clone(&ring);
+                s.spawn(move |_| ring_clone.pass(t, iterations));
+            }
+        });
+    }))
+}
+
+// `threads` threads taking turns, and the turn lock they wait on with one
+// condvar per thread, or one shared by all
+struct Ring<L> {
+    threads: usize,
+    shared: bool,
+    lock: L,
+}
+
+impl<L: TurnLock> Ring<L> {
+    fn new(threads: usize, notify_all: bool) -> Self {
+        Ring { threads, shared: notify_all, lock: L::new(if notify_all { 1 } else { threads }) }
+    }
+
+    // condvar thread t waits on
+    fn condvar(&self, t: usize) -> usize {
+        if self.shared { 0 } else { t }
+    }
+
+    // thread t takes its turn `rounds` times; once every thread has, the
+    // turn is back at 0
+    fn pass(&self, t: usize, rounds: usize) {
+        let next = (t + 1) % self.threads;
+        for _ in 0..rounds {
+            self.lock.take(t, self.condvar(t), next);
+            self.lock.notify(self.condvar(next), self.shared);
+        }
+    }
+}
+
+/// 9: RwLock vs Mutex at a Read/Write Mix
+/// T threads each take a lock R times; operation i is a write (an
+/// increment) for write_pct of every 100 operations, spread evenly, and a
+/// read of the value otherwise. The RwLock lets readers in together, the
+/// Mutex (like omp critical) takes them one at a time; the gap shows what
+/// reader parallelism is worth at each mix. Cost per operation.
+fn rwlock_benchmark(thread_counts: &[usize], iteration_counts: &[usize], write_ratios: &[usize], repeat: Repeat, reporter: &mut Reporter) {
+    for &num_threads in thread_counts {
+        for &iterations in iteration_counts {
+            for &write_pct in write_ratios {
+                if interrupt::requested() {
+                    return;
+                }
+                let _cell = info_span!("overhead", primitive = "rwlock", threads = num_threads, iterations, write_pct).entered();
+                let pool = Phase::Setup.run(|| {
+                    rayon::ThreadPoolBuilder::new()
+                        .num_threads(num_threads)
+                        .build()
+                        .unwrap()
+                });
+                let params: [(&str, &dyn Display); 3] = [("T", &num_threads), ("R", &iterations), ("write_pct", &write_pct)];
+
+                let (rwlock_time, mutex_time) = rwlock_cell::<RwLock<u64>, Mutex<u64>>(&pool, num_threads, iterations, write_pct, repeat);
+                report_cell(reporter, "rwlock", "rwlock", &params, &rwlock_time.0, iterations * num_threads, rwlock_time.2);
+                report_cell(reporter, "mutex", "rwlock", &params, &mutex_time.0, iterations * num_threads, mutex_time.2);
+                #[cfg(feature = "parking-lot")]
+                {
+                    let (rwlock_time, mutex_time) = rwlock_cell::<parking_lot::RwLock<u64>, parking_lot::Mutex<u64>>(&pool, num_threads, iterations, write_pct, repeat);
+                    report_cell(reporter, "parking_lot-rwlock", "rwlock", &params, &rwlock_time.0, iterations * num_threads, rwlock_time.2);
+                    report_cell(reporter, "parking_lot-mutex", "rwlock", &params, &mutex_time.0, iterations * num_threads, mutex_time.2);
+                }
+            }
+        }
+    }
+}
+
+// the same read/write mix on a counter behind an RW, then behind an M
+fn rwlock_cell<RW: CounterRwLock, M: CounterLock>(pool: &rayon::ThreadPool, num_threads: usize, iterations: usize, write_pct: usize, repeat: Repeat) -> (Measured, Measured) {
+    let rwlock = Arc::new(RW::default());
+    let rwlock_time = repeat.measure(|| pool.install(|| {
+        rayon::scope(|s| {
+            for _ in 0..num_threads {
+                let rwlock_clone = Arc::clone(&rwlock);
+                s.spawn(move |_| {
+                    for i in 0..iterations {
+                        if is_write(i, write_pct) {
+                            rwlock_clone.write(|val| *val += 1);
+                        } else {
+                            rwlock_clone.read(|val| std::hint::black_box(*val));
+                        }
+                    }
+                });
+            }
+        });
+    }));
+
+    let mutex = Arc::new(M::default());
+    let mutex_time = repeat.measure(|| pool.install(|| {
+        rayon::scope(|s| {
+            for _ in 0..num_threads {
+                let mutex_clone = Arc::clone(&mutex);
+                s.spawn(move |_| {
+                    for i in 0..iterations {
+                        mutex_clone.with(|val| {
+                            if is_write(i, write_pct) {
                                 *val += 1;
-                                // lock is automatically released here
+                            } else {
+                                std::hint::black_box(*val);
                             }
                         });
                     }
                 });
+            }
+        });
+    }));
+    (rwlock_time, mutex_time)
+}
+
+/// 10: Channel Send/Receive
+/// T producers on the pool send R messages each down one unbounded
+/// channel to a consumer on the calling thread, which counts them until the
+/// last sender is gone; std::sync::mpsc and crossbeam-channel. The
+/// message-passing answer to the shared counters above. Cost per message.
+fn channel_benchmark(thread_counts: &[usize], iteration_counts: &[usize], repeat: Repeat, reporter: &mut Reporter) {
+    for &num_threads in thread_counts {
+        for &iterations in iteration_counts {
+            if interrupt::requested() {
+                return;
+            }
+            let _cell = info_span!("overhead", primitive = "channel", threads = num_threads, iterations).entered();
+            let pool = Phase::Setup.run(|| {
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(num_threads)
+                    .build()
+                    .unwrap()
             });
-            
-            let duration = start.elapsed();
-            let total_ops = iterations * num_threads;
-            let total_ms = duration.as_secs_f64() * 1000.0;
-            let avg_ns = duration.as_nanos() as f64 / total_ops as f64;
-            
-            println!("overhead,rust,T={},R={},mutex_total,{:.6},ms",
-                num_threads, iterations, total_ms);
-            println!("overhead,rust,T={},R={},mutex_per,{:.3},ns",
-                num_threads, iterations, avg_ns);
+            let messages = iterations * num_threads;
+
+            let (mpsc_time, mpsc_received, mpsc_allocs) = repeat.measure(|| {
+                let (tx, rx) = std::sync::mpsc::channel();
+                pool.in_place_scope(|s| {
+                    for _ in 0..num_threads {
+                        let tx_clone = tx.clone();
+                        s.spawn(move |_| {
+                            for i in 0..iterations {
+                                tx_clone.send(i).unwrap();
+                            }
+                        });
+                    }
+                    drop(tx);
+                    rx.iter().count()
+                })
+            });
+            let (crossbeam_time, crossbeam_received, crossbeam_allocs) = repeat.measure(|| {
+                let (tx, rx) = crossbeam::channel::unbounded();
+                pool.in_place_scope(|s| {
+                    for _ in 0..num_threads {
+                        let tx_clone = tx.clone();
+                        s.spawn(move |_| {
+                            for i in 0..iterations {
+                                tx_clone.send(i).unwrap();
+                            }
+                        });
+                    }
+                    drop(tx);
+                    rx.iter().count()
+                })
+            });
+
+            Phase::Verify.run(|| {
+                assert_eq!(mpsc_received, messages);
+                assert_eq!(crossbeam_received, messages);
+            });
+            report_cell(reporter, "mpsc", "channel", &[("T", &num_threads), ("R", &iterations)], &mpsc_time, messages, mpsc_allocs);
+            report_cell(reporter, "crossbeam", "channel", &[("T", &num_threads), ("R", &iterations)], &crossbeam_time, messages, crossbeam_allocs);
         }
     }
 }
 
-/// 4: Atomic Operations
-/// overhead of atomic fetch_add operations using Rayon thread pool
-fn atomic_benchmark() {
-    for &num_threads in THREAD_COUNTS {
-        for &iterations in ITERATIONS {
-            let pool = rayon::ThreadPoolBuilder::new()
+/// 11: OS Threads, Owning vs Scoped
+/// SPAWN_ROUNDS rounds of T std threads spawned and joined, each summing the
+/// same SHARED_WORDS words. thread::spawn needs 'static data, so every thread
+/// owns a clone of an Arc (the pattern of the benchmarks above); a
+/// thread::scope lets them borrow the words from the caller's stack instead.
+/// Cost per thread spawned and joined.
+fn os_threads_benchmark(thread_counts: &[usize], repeat: Repeat, reporter: &mut Reporter) {
+    for &num_threads in thread_counts {
+        if interrupt::requested() {
+            return;
+        }
+        let _cell = info_span!("overhead", primitive = "spawn_join", threads = num_threads, iterations = SPAWN_ROUNDS).entered();
+        let spawns = SPAWN_ROUNDS * num_threads;
+        let words: [u64; SHARED_WORDS] = std::array::from_fn(|i| i as u64);
+        let expected = words.iter().sum::<u64>() * spawns as u64;
+
+        let shared = Arc::new(words);
+        let (owning_time, owning_sum, owning_allocs) = repeat.measure(|| {
+            let mut sum = 0;
+            for _ in 0..SPAWN_ROUNDS {
+                let handles: Vec<_> = (0..num_threads)
+                    .map(|_| {
+                        let shared_clone = Arc::clone(&shared);
+                        std::thread::spawn(move || std::hint::black_box(shared_clone.iter().sum::<u64>()))
+                    })
+                    .collect();
+                sum += handles.into_iter().map(|h| h.join().unwrap()).sum::<u64>();
+            }
+            sum
+        });
+        let (scoped_time, scoped_sum, scoped_allocs) = repeat.measure(|| {
+            let mut sum = 0;
+            for _ in 0..SPAWN_ROUNDS {
+                sum += std::thread::scope(|s| {
+                    let handles: Vec<_> = (0..num_threads)
+                        .map(|_| s.spawn(|| std::hint::black_box(words.iter().sum::<u64>())))
+                        .collect();
+                    handles.into_iter().map(|h| h.join().unwrap()).sum::<u64>()
+                });
+            }
+            sum
+        });
+
+        Phase::Verify.run(|| {
+            assert_eq!(owning_sum, expected);
+            assert_eq!(scoped_sum, expected);
+        });
+        report_cell(reporter, "owning", "spawn_join", &[("T", &num_threads), ("R", &SPAWN_ROUNDS)], &owning_time, spawns, owning_allocs);
+        report_cell(reporter, "scoped", "spawn_join", &[("T", &num_threads), ("R", &SPAWN_ROUNDS)], &scoped_time, spawns, scoped_allocs);
+    }
+}
+
+/// 12: Compare-and-Swap Retry Loop
+/// T threads each add 1 to one shared AtomicU64 R times, not with fetch_add
+/// but by the load / compare_exchange_weak loop every other lock-free update
+/// (max, push, ...) is written as; a thread whose CAS fails on a changed
+/// value retries with the value it saw. Cost per successful update, and
+/// retries, the failed attempts per success, which grows with T.
+fn cas_benchmark(thread_counts: &[usize], iteration_counts: &[usize], repeat: Repeat, reporter: &mut Reporter) {
+    for &num_threads in thread_counts {
+        for &iterations in iteration_counts {
+            if interrupt::requested() {
+                return;
+            }
+            let _cell = info_span!("overhead", primitive = "cas", threads = num_threads, iterations).entered();
+            let pool = Phase::Setup.run(|| {
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(num_threads)
+                    .build()
+                    .unwrap()
+            });
+            let updates = iterations * num_threads;
+
+            let counter = Arc::new(AtomicU64::new(0));
+            let retries = Arc::new(AtomicU64::new(0));
+            let (time, retried, allocs) = repeat.measure(|| {
+                counter.store(0, Ordering::Relaxed);
+                retries.store(0, Ordering::Relaxed);
+                pool.install(|| {
+                    rayon::scope(|s| {
+                        for _ in 0..num_threads {
+                            let counter_clone = Arc::clone(&counter);
+                            let retries_clone = Arc::clone(&retries);
+                            s.spawn(move |_| {
+                                let failed: u64 = (0..iterations).map(|_| cas_increment(&counter_clone)).sum();
+                                retries_clone.fetch_add(failed, Ordering::Relaxed);
+                            });
+                        }
+                    });
+                });
+                retries.load(Ordering::Relaxed)
+            });
+
+            Phase::Verify.run(|| assert_eq!(counter.load(Ordering::Relaxed), updates as u64));
+            let extra = [("retries", retried as f64 / updates as f64, "ratio")];
+            report_cell_with(reporter, "rust", "cas", &[("T", &num_threads), ("R", &iterations)], &time, updates, allocs, &extra);
+        }
+    }
+}
+
+/// 13: Parallel For over Tiny to Large Loops
+/// The classic OpenMP parallel-for overhead measurement: a loop adding 1 to
+/// every element of an array of N, from 10 to 10^7 elements, run R times
+/// with par_iter_mut().for_each and as a plain sequential loop. Cost per
+/// loop. Below some N the fork and join outweigh the work, and the
+/// sequential loop wins; the crossover is the smallest N of the sweep
+/// from which Rayon stays ahead (no record when it never does).
+fn parallel_for_benchmark(thread_counts: &[usize], repeat: Repeat, reporter: &mut Reporter) {
+    for &num_threads in thread_counts {
+        let _threads = info_span!("overhead", primitive = "parfor", threads = num_threads).entered();
+        let pool = Phase::Setup.run(|| {
+            rayon::ThreadPoolBuilder::new()
                 .num_threads(num_threads)
                 .build()
-                .unwrap();
-            
-            let counter = Arc::new(AtomicU64::new(0));
-            let start = Instant::now();
-            
-            pool.install(|| {
-                rayon::scope(|s| {
-                    for _ in 0..num_threads {
-                        let counter_clone = Arc::clone(&counter);
-                        s.spawn(move |_| {
+                .unwrap()
+        });
+
+        let mut wins = Vec::new();
+        for n in PARFOR_SIZES {
+            if interrupt::requested() {
+                return;
+            }
+            let _cell = info_span!("size", n).entered();
+            let loops = parfor_reps(n);
+            let mut data = vec![0u64; n];
+
+            let (seq_time, (), seq_allocs) = repeat.measure(|| {
+                for _ in 0..loops {
+                    data.iter_mut().for_each(|x| *x += 1);
+                    std::hint::black_box(&mut data);
+                }
+            });
+            let (par_time, (), par_allocs) = repeat.measure(|| pool.install(|| {
+                for _ in 0..loops {
+                    data.par_iter_mut().for_each(|x| *x += 1);
+                    std::hint::black_box(&mut data);
+                }
+            }));
+
+            let params: [(&str, &dyn Display); 3] = [("T", &num_threads), ("N", &n), ("R", &loops)];
+            report_cell(reporter, "sequential", "parfor", &params, &seq_time, loops, seq_allocs);
+            report_cell(reporter, "rayon", "parfor", &params, &par_time, loops, par_allocs);
+            wins.push((n, par_time.min < seq_time.min));
+        }
+
+        if let Some(n) = crossover(&wins) {
+            let record = Record::new("overhead", "rayon").param("primitive", "parfor").param("T", num_threads).metric("crossover", n as f64, "elements");
+            if reporter.is_text() {
+                print!("{}", record.to_csv());
+            }
+            reporter.record(&record);
+        }
+    }
+}
+
+/// 14: rayon::join, scope + spawn and ThreadPool::spawn per Call
+/// The Rayon calls behind OpenMP sections and tasks, each invoked R times in
+/// a row with an empty body, from inside a pool of T threads: join of two
+/// closures (two `omp section`s), a scope spawning one task and waiting for
+/// it (`omp task` then `taskwait`), and the pool's own spawn, detached, the
+/// caller waiting once for all R to have run. Cost per call, which fib and
+/// task only show mixed with their recursion and batching.
+fn rayon_call_benchmark(thread_counts: &[usize], iteration_counts: &[usize], repeat: Repeat, reporter: &mut Reporter) {
+    for &num_threads in thread_counts {
+        for &iterations in iteration_counts {
+            let pool = Phase::Setup.run(|| {
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(num_threads)
+                    .build()
+                    .unwrap()
+            });
+            for call in RAYON_CALLS {
+                if interrupt::requested() {
+                    return;
+                }
+                let _cell = info_span!("overhead", primitive = "call", call, threads = num_threads, iterations).entered();
+
+                let done = Arc::new(AtomicU64::new(0));
+                let (time, (), allocs) = repeat.measure(|| {
+                    done.store(0, Ordering::Relaxed);
+                    match call {
+                        "join" => pool.install(|| {
                             for _ in 0..iterations {
-                                counter_clone.fetch_add(1, Ordering::SeqCst);
+                                let (a, b) = rayon::join(|| std::hint::black_box(1), || std::hint::black_box(1));
+                                done.fetch_add((a + b) / 2, Ordering::Relaxed);
                             }
-                        });
+                        }),
+                        "scope_spawn" => pool.install(|| {
+                            for _ in 0..iterations {
+                                rayon::scope(|s| {
+                                    s.spawn(|_| {
+                                        done.fetch_add(1, Ordering::Relaxed);
+                                    });
+                                });
+                            }
+                        }),
+                        _ => {
+                            for _ in 0..iterations {
+                                let done_clone = Arc::clone(&done);
+                                pool.spawn(move || {
+                                    done_clone.fetch_add(1, Ordering::Release);
+                                });
+                            }
+                            while done.load(Ordering::Acquire) < iterations as u64 {
+                                std::thread::yield_now();
+                            }
+                        }
                     }
                 });
-            });
-            
-            let duration = start.elapsed();
-            let total_ops = iterations * num_threads;
-            let total_ms = duration.as_secs_f64() * 1000.0;
-            let avg_ns = duration.as_nanos() as f64 / total_ops as f64;
-            
-            println!("overhead,rust,T={},R={},atomic_total,{:.6},ms",
-                num_threads, iterations, total_ms);
-            println!("overhead,rust,T={},R={},atomic_per,{:.3},ns",
-                num_threads, iterations, avg_ns);
+
+                Phase::Verify.run(|| assert_eq!(done.load(Ordering::Relaxed), iterations as u64));
+                report_cell(reporter, call, "call", &[("T", &num_threads), ("R", &iterations)], &time, iterations, allocs);
+            }
         }
     }
 }
 
+// adds 1 to `counter` by compare-and-swap; returns the failed attempts
+fn cas_increment(counter: &AtomicU64) -> u64 {
+    let mut failed = 0;
+    let mut current = counter.load(Ordering::Relaxed);
+    while let Err(seen) = counter.compare_exchange_weak(current, current + 1, Ordering::AcqRel, Ordering::Relaxed) {
+        current = seen;
+        failed += 1;
+    }
+    failed
+}
+
+// operation i of a thread writes for `write_pct` of every 100 values of i,
+// evenly spaced
+fn is_write(i: usize, write_pct: usize) -> bool {
+    i * write_pct % 100 < write_pct
+}
+
+// repetitions of the parfor loop over n elements
+fn parfor_reps(n: usize) -> usize {
+    (PARFOR_WORK / n).clamp(PARFOR_REPS.0, PARFOR_REPS.1)
+}
+
+// the smallest size from which the parallel loop wins at every larger size
+// of the (size, parallel won) sweep, in ascending order of size
+fn crossover(wins: &[(usize, bool)]) -> Option<usize> {
+    let losers = wins.iter().rposition(|&(_, won)| !won).map_or(0, |i| i + 1);
+    wins.get(losers).map(|&(n, _)| n)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -236,4 +1098,103 @@ mod tests {
         handle.join().unwrap();
         assert_eq!(counter.load(Ordering::SeqCst), 100);
     }
+
+    #[test]
+    fn test_parse_only() {
+        assert_eq!(parse_only("all").unwrap(), PRIMITIVES);
+        assert_eq!(parse_only("mutex,spawn").unwrap(), ["parallel", "mutex"]);
+        assert_eq!(parse_only("fib").unwrap(), ["fib"]);
+        assert!(parse_only("critical").is_err());
+        assert!(parse_only("").is_err());
+    }
+
+    #[test]
+    fn test_write_ratios() {
+        assert_eq!(parse_write_ratios("50,1,10%,1").unwrap(), [1, 10, 50]);
+        assert_eq!(parse_write_ratios("0,100").unwrap(), [0, 100]);
+        assert!(parse_write_ratios("101").is_err());
+        assert!(parse_write_ratios("ten").is_err());
+        for pct in [0, 1, 10, 33, 50, 100] {
+            assert_eq!((0..1000).filter(|&i| is_write(i, pct)).count(), pct * 10);
+        }
+    }
+
+    #[test]
+    fn test_spin_lock() {
+        fn count<L: CounterLock>() -> u64 {
+            let lock = L::default();
+            thread::scope(|s| {
+                for _ in 0..4 {
+                    s.spawn(|| {
+                        for _ in 0..1000 {
+                            lock.with(|val| *val += 1);
+                        }
+                    });
+                }
+            });
+            lock.with(|val| *val)
+        }
+        assert_eq!(count::<SpinLock<false>>(), 4000);
+        assert_eq!(count::<SpinLock<true>>(), 4000);
+    }
+
+    #[test]
+    fn test_cas_increment() {
+        let counter = AtomicU64::new(0);
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..1000 {
+                        cas_increment(&counter);
+                    }
+                });
+            }
+        });
+        assert_eq!(counter.load(Ordering::Relaxed), 4000);
+    }
+
+    #[test]
+    fn test_ring() {
+        for notify_all in [false, true] {
+            let ring = Ring::<StdTurns>::new(3, notify_all);
+            thread::scope(|s| {
+                for t in 0..3 {
+                    let ring = &ring;
+                    s.spawn(move || ring.pass(t, 100));
+                }
+            });
+            assert_eq!(ring.lock.turn(), 0);
+        }
+        // a ring of one never waits
+        Ring::<StdTurns>::new(1, false).pass(0, 10);
+        #[cfg(feature = "parking-lot")]
+        {
+            let ring = Ring::<ParkingLotTurns>::new(2, false);
+            thread::scope(|s| {
+                s.spawn(|| ring.pass(1, 100));
+                ring.pass(0, 100);
+            });
+            assert_eq!(ring.lock.turn(), 0);
+        }
+    }
+
+    #[test]
+    fn test_crossover() {
+        assert_eq!(crossover(&[(10, false), (100, false), (1000, true), (10_000, true)]), Some(1000));
+        // a win among losses is noise, not the crossover
+        assert_eq!(crossover(&[(10, false), (100, true), (1000, false), (10_000, true)]), Some(10_000));
+        assert_eq!(crossover(&[(10, true), (100, true)]), Some(10));
+        assert_eq!(crossover(&[(10, false), (100, false)]), None);
+        assert_eq!((parfor_reps(10), parfor_reps(10_000), parfor_reps(10_000_000)), (100_000, 10_000, 10));
+    }
+
+    #[test]
+    fn test_fib_joins() {
+        // fib(5) = fib(4) + fib(3): 4 + 2 + the root
+        assert_eq!(fib_joins(5), 7);
+        assert_eq!(fib_joins(1), 0);
+        let sched = Scheduler::new(2);
+        assert_eq!(sched.install(|| fib_chase_lev(15)), fib_seq(15));
+        assert_eq!(fib_rayon(15), fib_seq(15));
+    }
 }
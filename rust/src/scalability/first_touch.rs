@@ -0,0 +1,285 @@
+// First-touch page placement benchmark (Rust version)
+// Linux backs a fresh page on the NUMA node of the core that first writes
+// it. A buffer initialized by one thread therefore sits on one node and
+// every later parallel pass reads remote memory from the other sockets,
+// which quietly skews the setup and baselines of matmul and histogram.
+// Each init mode gets a fresh, untouched buffer of MiB mebibytes of f64:
+//   serial    the main thread writes every element
+//   parallel  T threads write one static block each
+// and is measured by
+//   init_time     the first-touch pass (page faults + writes)
+//   retouch_time  the same pass again, pages already present
+//   fault_time    init_time - retouch_time
+//   sweep_time    a parallel sum over the buffer with all T threads
+// The parallel record adds init_speedup and sweep_speedup over serial.
+// Placement only matches the sweep with a static backend (threads |
+// crossbeam | workers), where block i always runs on worker i; rayon may
+// hand a block to any worker. Pick sizes well beyond 32 MiB so the buffers
+// come from fresh mappings rather than recycled heap. With --reps every run
+// gets a fresh buffer of its own.
+//
+// Usage:
+//   ./first_touch [--size MiB] [--threads T] [options]
+//   --size MiB         buffer size in MiB (default 1024)
+//   --threads T        number of threads (default 8; a count, or max, cores, half, quarter, a/b)
+//
+// Options:
+//   --affinity A       none | compact (default) | scatter | list:C,C,...;
+//                      compact pins worker i to CPU i (0 and 1: none, compact)
+//   --backend B        rayon (default) | threads | crossbeam | workers
+//                      (+ tokio-blocking | tokio-async with --features tokio)
+//   --pages P          default | thp | hugetlb pages for the buffer
+//   --reps R           runs per init mode, the best of each time is reported
+//                      (default 1); R > 1 adds <time>_mean, _median, _stddev
+//   --warmup W         untimed runs before them (default 1; 0 for a cold start)
+//   --format FMT       text (default, CSV lines below) | jsonl | json | csv | markdown
+//   --upload URL       POST the result as JSON to an HTTP collector
+//   --prometheus FILE  keep FILE updated with Prometheus gauges for each result
+//   --store FILE       append the run to a SQLite database (--features store)
+//   --log FMT          pretty | json execution log on stderr (RUST_LOG filters)
+//   --describe         print the JSON description of arguments and metrics, then exit
+//
+// Output (CSV-style, one line per metric):
+//   first_touch,threads,MiB=4096,T=8,init=serial,affinity=compact,pages=default,init_time,1.234567,sec
+//   first_touch,threads,MiB=4096,T=8,init=parallel,affinity=compact,pages=default,sweep_speedup,1.85,x
+
+use crate::alloc_track::{AllocStats, Region};
+use crate::backend::{Affinity, Backend, Pool};
+use crate::cli;
+use crate::describe::{Description, Param};
+use crate::energy::{Energy, Meter};
+use crate::kernels::{Benchmark, Suite};
+use crate::logging::Phase;
+use crate::pages::{PageVec, Pages};
+use crate::results::{Record, Reporter};
+use crate::rss::Peak;
+use crate::stats::{Repeat, Stats};
+use crate::threads;
+use clap::{ArgMatches, CommandFactory, Parser};
+use std::time::Instant;
+use tracing::info_span;
+
+// defaults of --size (MiB) and --threads
+const SIZE: usize = 1024;
+const THREADS: usize = 8;
+
+const MIB: usize = 1 << 20;
+const INIT_VALUE: f64 = 1.0;
+const RETOUCH_VALUE: f64 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Init {
+    Serial,
+    Parallel,
+}
+
+impl Init {
+    fn name(self) -> &'static str {
+        match self {
+            Init::Serial => "serial",
+            Init::Parallel => "parallel",
+        }
+    }
+}
+
+// one run on a fresh buffer
+struct Touch {
+    init_time: f64,
+    retouch_time: f64,
+    sweep_time: f64,
+    correct: bool,
+    allocs: Option<AllocStats>,
+    energy: Option<Energy>,
+    peak_rss: Option<u64>,
+}
+
+// the runs of one init mode
+struct Outcome {
+    init_time: Stats,
+    retouch_time: Stats,
+    fault_time: Stats,
+    sweep_time: Stats,
+    correct: bool,
+    // of the run with the fastest init
+    allocs: Option<AllocStats>,
+    energy: Option<Energy>,
+    peak_rss: Option<u64>,
+}
+
+// one static block per thread, the same split for writes and the sweep
+fn block(len: usize, pool: &Pool) -> usize {
+    len.div_ceil(pool.threads()).max(1)
+}
+
+fn write(pool: &Pool, init: Init, data: &mut [f64], value: f64) {
+    match init {
+        Init::Serial => data.fill(value),
+        Init::Parallel => pool.for_each_chunk_mut(data, block(data.len(), pool), |_, c| c.fill(value)),
+    }
+}
+
+fn sweep(pool: &Pool, data: &[f64]) -> f64 {
+    pool.map_reduce(data, block(data.len(), pool), || 0.0, |_, c| c.iter().sum::<f64>(), |a, b| a + b)
+}
+
+fn timed(f: impl FnOnce()) -> f64 {
+    let start = Instant::now();
+    f();
+    start.elapsed().as_secs_f64()
+}
+
+fn touch(pool: &Pool, init: Init, len: usize, pages: Pages) -> Touch {
+    let mut data = Phase::Setup.run(|| cli::or_exit(PageVec::<f64>::zeroed(len, pages)));
+
+    let measure = Phase::Measure.span().entered();
+    let region = Region::start();
+    let meter = Meter::start();
+    let peak = Peak::start();
+    let init_time = timed(|| write(pool, init, &mut data, INIT_VALUE));
+    let retouch_time = timed(|| write(pool, init, &mut data, RETOUCH_VALUE));
+    let mut total = 0.0;
+    let sweep_time = timed(|| total = sweep(pool, &data));
+    let energy = meter.finish(1);
+    let peak_rss = peak.finish();
+    let allocs = region.finish();
+    drop(measure);
+
+    // small integers, so every partial sum is exact
+    let correct = Phase::Verify.run(|| total == RETOUCH_VALUE * len as f64);
+    Touch { init_time, retouch_time, sweep_time, correct, allocs, energy, peak_rss }
+}
+
+fn run_init(pool: &Pool, init: Init, len: usize, pages: Pages, repeat: Repeat) -> Outcome {
+    let _mode = info_span!("init", mode = init.name()).entered();
+    // every run maps a fresh buffer, so warm-up runs leave its pages cold
+    repeat.warm_up(|| touch(pool, init, len, pages));
+    let runs: Vec<Touch> = (0..repeat.reps).map(|_| touch(pool, init, len, pages)).collect();
+    let stats = |time: fn(&Touch) -> f64| repeat.stats(&runs.iter().map(time).collect::<Vec<_>>());
+    let fastest = runs.iter().min_by(|a, b| a.init_time.total_cmp(&b.init_time)).expect("at least one run");
+    Outcome {
+        init_time: stats(|r| r.init_time),
+        retouch_time: stats(|r| r.retouch_time),
+        fault_time: stats(|r| (r.init_time - r.retouch_time).max(0.0)),
+        sweep_time: stats(|r| r.sweep_time),
+        correct: runs.iter().all(|r| r.correct),
+        allocs: fastest.allocs,
+        energy: fastest.energy,
+        peak_rss: fastest.peak_rss,
+    }
+}
+
+// answer to --describe
+pub fn description() -> Description {
+    Description::new("first_touch", "first_touch", "first-touch initialization of a large buffer by one thread vs all threads")
+        .option(Param::integer("--size", 1, "buffer size in MiB").default(SIZE))
+        .option(Param::threads("--threads", "threads").default(THREADS))
+        .option(Param::text("--affinity", "none | compact | scatter | list:C,C,...: worker placement").default("compact"))
+        .backend_option()
+        .pages_option()
+        .params(&["MiB", "T", "init", "affinity", "pages"])
+        .timing("init_time", "sec")
+        .timing("retouch_time", "sec")
+        .timing("fault_time", "sec")
+        .timing("sweep_time", "sec")
+        .metric("init_speedup", "x")
+        .metric("sweep_speedup", "x")
+        .metric("correct", "boolean")
+}
+
+/// First-touch initialization of a large buffer by one thread vs all threads
+#[derive(Debug, Clone, Parser)]
+#[command(name = "first_touch")]
+pub struct Args {
+    /// buffer size in MiB
+    #[arg(long, value_name = "MiB", value_parser = cli::positive, default_value_t = SIZE)]
+    pub size: usize,
+    /// threads (a count or max, cores, half, quarter, a/b)
+    #[arg(long, value_name = "T", value_parser = threads::parse_spec, default_value_t = THREADS)]
+    pub threads: usize,
+    /// none | compact | scatter | list:C,C,...: worker placement (0 and 1: none and compact)
+    #[arg(long, value_name = "A", value_parser = Affinity::parse, default_value = "compact")]
+    pub affinity: Affinity,
+    /// rayon | threads | crossbeam | workers (+ tokio-blocking | tokio-async)
+    #[arg(long, value_name = "B", value_parser = Backend::parse, default_value = "rayon")]
+    pub backend: Backend,
+    /// default | thp | hugetlb pages for the buffer
+    #[arg(long, value_name = "P", value_parser = Pages::parse, default_value = "default")]
+    pub pages: Pages,
+    #[command(flatten)]
+    pub common: cli::Common,
+}
+
+pub struct FirstTouch;
+
+impl Benchmark for FirstTouch {
+    fn name(&self) -> &'static str {
+        "first_touch"
+    }
+
+    fn label(&self) -> &'static str {
+        "First touch"
+    }
+
+    fn suite(&self) -> Suite {
+        Suite::Scalability
+    }
+
+    fn description(&self) -> Description {
+        description()
+    }
+
+    fn command(&self) -> clap::Command {
+        Args::command()
+    }
+
+    fn run(&self, args: &ArgMatches, reporter: &mut Reporter) -> i32 {
+        run(&cli::from_matches(args), reporter)
+    }
+}
+
+pub fn run(args: &Args, reporter: &mut Reporter) -> i32 {
+    let Args { size: mib, threads: t, backend, pages, .. } = *args;
+    let affinity = &args.affinity;
+    let len = mib * MIB / std::mem::size_of::<f64>();
+
+    let _bench = info_span!("first_touch", backend = backend.name(), mib, threads = t, affinity = affinity.name(), pages = pages.name()).entered();
+    let pool = Phase::Setup.run(|| Pool::with_affinity(backend, t, affinity));
+
+    let repeat = args.common.reps.repeat(1);
+    let serial = run_init(&pool, Init::Serial, len, pages, repeat);
+    let parallel = run_init(&pool, Init::Parallel, len, pages, repeat);
+
+    for (init, outcome) in [(Init::Serial, &serial), (Init::Parallel, &parallel)] {
+        let mut record = Record::new("first_touch", backend.name())
+            .param("MiB", mib)
+            .param("T", t)
+            .param("init", init.name())
+            .param("affinity", affinity.name())
+            .param("pages", pages.name())
+            .timing("init_time", &outcome.init_time, "sec")
+            .timing("retouch_time", &outcome.retouch_time, "sec")
+            .timing("fault_time", &outcome.fault_time, "sec")
+            .timing("sweep_time", &outcome.sweep_time, "sec");
+        if init == Init::Parallel {
+            record = record
+                .metric("init_speedup", serial.init_time.min / parallel.init_time.min, "x")
+                .metric("sweep_speedup", serial.sweep_time.min / parallel.sweep_time.min, "x");
+        }
+        let record = record
+            .metric("correct", if outcome.correct { 1.0 } else { 0.0 }, "boolean")
+            .allocs(outcome.allocs)
+            .energy(outcome.energy)
+            .peak_rss(outcome.peak_rss);
+
+        if reporter.is_text() {
+            print!("{}", record.to_csv());
+        }
+        reporter.record(&record);
+    }
+
+    if serial.correct && parallel.correct {
+        0
+    } else {
+        3
+    }
+}
@@ -0,0 +1,265 @@
+// 2D Jacobi stencil benchmark for scalability (Rust version)
+// Laplace's equation on an n x n grid by Jacobi iteration: every interior
+// point becomes the mean of its four neighbours (a 5-point stencil) from
+// the previous grid, and the two grids swap. The top row is held at 1, the
+// other edges at 0, and the interior starts at 0. Each iteration is one
+// parallel pass over the interior rows, a max-reduction of the change
+// |new - old| for the convergence check, and the join (the barrier of an
+// OpenMP loop) before the next; the solve stops once the change is at most
+// --tol, or after --iters iterations. Unlike the kernels before it, every
+// point reads its neighbours' rows, so the threads share the rows at the
+// edges of their chunks, and every iteration pays for a fork and a join.
+// Each point is updated by the same expression in any order, so the grid
+// matches the sequential solve, timed too as the baseline of speedup, bit
+// for bit.
+//
+// Usage:
+//   ./jacobi [--size n] [--threads T] [options]
+//   --size n           grid order, boundary included (at least 3; default 1024)
+//   --threads T        number of threads (default 8; a count or max, cores, half, 3/4)
+//
+// Options:
+//   --iters I          most iterations (default 1000)
+//   --tol E            stop once no point changes by more than E (default
+//                      0.000001; 0 runs all --iters)
+//   --format FMT       text (default, CSV lines below) | jsonl | json | csv | markdown
+//   --upload URL       POST the result as JSON to an HTTP collector
+//   --prometheus FILE  keep FILE updated with Prometheus gauges for each result
+//   --store FILE       append the run to a SQLite database (--features store)
+//   --log FMT          pretty | json execution log on stderr (RUST_LOG filters)
+//   --reps R           timed solves, the best is reported (default 1); R > 1
+//                      adds time_mean, time_median and time_stddev
+//   --warmup W         untimed solves before them (default 1; 0 for a cold start)
+//   --describe         print the JSON description of arguments and metrics, then exit
+//
+// Output (CSV-style):
+//   jacobi,rayon,n=1024,iters=1000,tol=0.000001,T=8,time,0.123456,sec
+//   jacobi,rayon,n=1024,iters=1000,tol=0.000001,T=8,per,0.123456,ms
+//   jacobi,rayon,n=1024,iters=1000,tol=0.000001,T=8,iterations,1000,iterations
+//   jacobi,rayon,n=1024,iters=1000,tol=0.000001,T=8,converged,0,boolean
+//   jacobi,rayon,n=1024,iters=1000,tol=0.000001,T=8,speedup,6.10,x
+//   jacobi,rayon,n=1024,iters=1000,tol=0.000001,T=8,correct,1,boolean
+
+use crate::alloc_track::Region;
+use crate::cli;
+use crate::describe::{Description, Param};
+use crate::kernels::{Benchmark, Suite};
+use crate::logging::Phase;
+use crate::results::{Record, Reporter};
+use crate::threads;
+use clap::{ArgMatches, CommandFactory, Parser};
+use rayon::prelude::*;
+use std::time::Instant;
+use tracing::info_span;
+
+// defaults of --size and --threads
+const N: usize = 1024;
+const THREADS: usize = 8;
+
+// iterations and tolerance unless --iters and --tol are given
+const ITERS: usize = 1000;
+const TOL: f64 = 1e-6;
+
+/// The starting grid of order n, row-major: 1 along the top row, 0
+/// everywhere else.
+pub fn initial(n: usize) -> Vec<f64> {
+    let mut grid = vec![0.0; n * n];
+    grid[..n].fill(1.0);
+    grid
+}
+
+// interior row i of the next grid from `old`; returns its largest change
+fn sweep_row(old: &[f64], n: usize, i: usize, row: &mut [f64]) -> f64 {
+    let mut change: f64 = 0.0;
+    for j in 1..n - 1 {
+        let v = 0.25 * (old[(i - 1) * n + j] + old[(i + 1) * n + j] + old[i * n + j - 1] + old[i * n + j + 1]);
+        change = change.max((v - old[i * n + j]).abs());
+        row[j] = v;
+    }
+    change
+}
+
+// the solve on grids `cur` and `next` that both hold the boundary, each
+// pass computing the interior of `next` from `cur` and returning its
+// largest change; leaves the result in `cur` and returns the iterations
+fn solve(cur: &mut Vec<f64>, next: &mut Vec<f64>, iters: usize, tol: f64, pass: impl Fn(&[f64], &mut [f64]) -> f64) -> usize {
+    for iteration in 1..=iters {
+        let change = pass(cur, next);
+        std::mem::swap(cur, next);
+        if change <= tol {
+            return iteration;
+        }
+    }
+    iters
+}
+
+/// Jacobi iteration on the calling thread, from `initial(n)`: (the grid,
+/// the iterations run).
+pub fn jacobi_sequential(n: usize, iters: usize, tol: f64) -> (Vec<f64>, usize) {
+    let (mut cur, mut next) = (initial(n), initial(n));
+    let iterations = solve(&mut cur, &mut next, iters, tol, |old, new| {
+        new[n..n * (n - 1)].chunks_mut(n).enumerate().map(|(k, row)| sweep_row(old, n, k + 1, row)).fold(0.0, f64::max)
+    });
+    (cur, iterations)
+}
+
+/// Jacobi iteration on the current Rayon pool, one task per interior row
+/// or run of rows, from the grids `cur` and `next` (both `initial(n)`):
+/// the iterations run, with the grid left in `cur`.
+pub fn jacobi_parallel(cur: &mut Vec<f64>, next: &mut Vec<f64>, n: usize, iters: usize, tol: f64) -> usize {
+    solve(cur, next, iters, tol, |old, new| {
+        new[n..n * (n - 1)].par_chunks_mut(n).enumerate().map(|(k, row)| sweep_row(old, n, k + 1, row)).reduce(|| 0.0, f64::max)
+    })
+}
+
+// grid order: room for at least one interior point
+fn parse_order(s: &str) -> Result<usize, String> {
+    match cli::positive(s)? {
+        n if n >= 3 => Ok(n),
+        n => Err(format!("must be at least 3, got {}", n)),
+    }
+}
+
+// tolerance: a finite, non-negative change
+fn parse_tol(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(tol) if tol.is_finite() && tol >= 0.0 => Ok(tol),
+        _ => Err(format!("must be a non-negative number, got {}", s)),
+    }
+}
+
+// answer to --describe
+pub fn description() -> Description {
+    Description::new("jacobi", "jacobi", "2D 5-point Jacobi iteration to a tolerance, one barrier per iteration")
+        .option(Param::integer("--size", 3, "grid order, boundary included").default(N))
+        .option(Param::threads("--threads", "threads").default(THREADS))
+        .option(Param::integer("--iters", 1, "most iterations").default(ITERS))
+        .option(Param::text("--tol", "stop once no point changes by more than this (0: run all --iters)").default(TOL))
+        .params(&["n", "iters", "tol", "T"])
+        .timing("time", "sec")
+        .timing("per", "ms")
+        .metric("iterations", "iterations")
+        .metric("converged", "boolean")
+        .metric("speedup", "x")
+        .metric("correct", "boolean")
+}
+
+/// 2D 5-point Jacobi iteration to a tolerance, one barrier per iteration
+#[derive(Debug, Clone, Parser)]
+#[command(name = "jacobi")]
+pub struct Args {
+    /// grid order, boundary included (at least 3)
+    #[arg(long, value_name = "n", value_parser = parse_order, default_value_t = N)]
+    pub size: usize,
+    /// threads (a count or max, cores, half, quarter, a/b)
+    #[arg(long, value_name = "T", value_parser = threads::parse_spec, default_value_t = THREADS)]
+    pub threads: usize,
+    /// most iterations
+    #[arg(long, value_name = "I", value_parser = cli::positive, default_value_t = ITERS)]
+    pub iters: usize,
+    /// stop once no point changes by more than E (0: run all --iters)
+    #[arg(long, value_name = "E", value_parser = parse_tol, default_value_t = TOL)]
+    pub tol: f64,
+    #[command(flatten)]
+    pub common: cli::Common,
+}
+
+pub struct Jacobi;
+
+impl Benchmark for Jacobi {
+    fn name(&self) -> &'static str {
+        "jacobi"
+    }
+
+    fn label(&self) -> &'static str {
+        "Jacobi stencil"
+    }
+
+    fn suite(&self) -> Suite {
+        Suite::Scalability
+    }
+
+    fn description(&self) -> Description {
+        description()
+    }
+
+    fn command(&self) -> clap::Command {
+        Args::command()
+    }
+
+    fn run(&self, args: &ArgMatches, reporter: &mut Reporter) -> i32 {
+        run(&cli::from_matches(args), reporter)
+    }
+}
+
+pub fn run(args: &Args, reporter: &mut Reporter) -> i32 {
+    let Args { size: n, threads: t, iters, tol, .. } = *args;
+    let repeat = args.common.reps.repeat(1);
+
+    let _bench = info_span!("jacobi", n, threads = t, iters, tol).entered();
+    let pool = Phase::Setup.run(|| rayon::ThreadPoolBuilder::new().num_threads(t).build().unwrap());
+
+    let (seq_time, (expected, _)) = repeat.run(|| {
+        let start = Instant::now();
+        let solved = jacobi_sequential(n, iters, tol);
+        (start.elapsed().as_secs_f64(), solved)
+    });
+    // every solve starts from fresh grids, made outside the timed region
+    let (elapsed, (grid, iterations, allocs)) = repeat.run(|| {
+        let (mut cur, mut next) = (initial(n), initial(n));
+        let region = Region::start();
+        let start = Instant::now();
+        let iterations = pool.install(|| jacobi_parallel(&mut cur, &mut next, n, iters, tol));
+        (start.elapsed().as_secs_f64(), (cur, iterations, region.finish()))
+    });
+
+    let correct = Phase::Verify.run(|| grid == expected);
+    let record = Record::new("jacobi", "rayon")
+        .param("n", n)
+        .param("iters", iters)
+        .param("tol", tol)
+        .param("T", t)
+        .timing("time", &elapsed, "sec")
+        .timing("per", &elapsed.scaled(1e3 / iterations as f64), "ms")
+        .metric("iterations", iterations as f64, "iterations")
+        .metric("converged", if iterations < iters { 1.0 } else { 0.0 }, "boolean")
+        .metric("speedup", seq_time.min / elapsed.min, "x")
+        .metric("correct", if correct { 1.0 } else { 0.0 }, "boolean")
+        .allocs(allocs)
+        .energy(elapsed.energy)
+        .peak_rss(elapsed.peak_rss);
+    if reporter.is_text() {
+        print!("{}", record.to_csv());
+    }
+    reporter.record(&record);
+
+    if correct {
+        0
+    } else {
+        3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jacobi() {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(3).build().unwrap();
+        let n = 17;
+        let (grid, iterations) = jacobi_sequential(n, 10_000, 1e-9);
+        assert!(iterations < 10_000);
+        // the boundary holds, and the interior lies between its values,
+        // falling away from the top row
+        assert!(grid[..n].iter().all(|&v| v == 1.0) && grid[n * (n - 1)..].iter().all(|&v| v == 0.0));
+        assert!((1..n - 1).all(|i| grid[i * n + n / 2] > 0.0 && grid[i * n + n / 2] < 1.0 && grid[(i + 1) * n + n / 2] < grid[i * n + n / 2]));
+
+        let (mut cur, mut next) = (initial(n), initial(n));
+        assert_eq!(pool.install(|| jacobi_parallel(&mut cur, &mut next, n, 10_000, 1e-9)), iterations);
+        assert_eq!(cur, grid);
+        // without a tolerance every iteration runs
+        assert_eq!(jacobi_sequential(n, 50, 0.0).1, 50);
+        assert!(parse_order("2").is_err() && parse_tol("-1").is_err());
+    }
+}
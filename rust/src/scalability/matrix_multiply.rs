@@ -1,30 +1,232 @@
 
-use rayon::prelude::*;
-use std::time::Instant;
-use std::env;
+// Matrix multiply benchmark for scalability (Rust version)
+//
+// Usage:
+//   ./matrix_multiply                          full study over --sizes x --threads
+//   ./matrix_multiply --size n --threads LIST  one matrix order, one run per thread count
+//
+// Options:
+//   --size n           matrix order of a single configuration instead of the study
+//   --sizes LIST       matrix orders of the study and of --sockets (default
+//                      256,512,1024,1536,2048), e.g. 256,512,4096
+//   --threads LIST     thread counts (default 1,2,4,8,16), e.g. 1,2,4,half,max;
+//                      the study always includes T=1 as the baseline
+//   --simd             also run the std::simd i-k-j row kernel (nightly +
+//                      --features simd), the counterpart of an `omp simd`
+//                      inner loop, as a strategy beside the scalar one; its
+//                      speedups are taken against the scalar T=1 time
+//   --block B          also run the cache-blocked kernel with B x B tiles
+//                      (default 64; e.g. 32, 128; 0 = naive kernel only) as
+//                      a second strategy with its own T=1 baseline; its
+//                      records have block=B, the naive ones block=0
+//   --transpose 0|1    1 = also run the transposed-B kernel: B^T is built
+//                      (in the timed region) so that each entry is a
+//                      unit-stride dot product of two rows; records have
+//                      transpose=1 (default 0)
+//   --algo A           classic (default) | strassen: also run Strassen's
+//                      recursion, its seven half-size products forked with
+//                      rayon::join down to STRASSEN_CUTOFF (rayon backend
+//                      only); records have algo=strassen, the others classic
+//   --blas 0|1         1 = also run OpenBLAS dgemm on T threads as the
+//                      best-achievable reference (--features blas); its
+//                      records have impl blas
+//   --pages P          default | thp | hugetlb pages for A, B and C (see pages.rs)
+//   --init I           const (default: A = 1, B = 2) | random: entries uniform
+//                      in [-1, 1) from the SplitMix64 streams --seed and
+//                      --seed + 1 (default MATRIX_SEED, see rng.rs), checked
+//                      by row checksums in O(n^2) instead of the golden
+//                      digests or a sequential re-run
+//   --dump FILE        with --size: write the checksum of C as a canonical dump
+//                      (see dump.rs; the last thread count's product)
+//   --ffi 0|1          1 = also time the C/OpenMP multiply, linked in, on each
+//                      cell; its records have impl openmp (--features ffi)
+//   --reps R           timed multiplies per cell, the best is reported (default 1)
+//   --warmup W         untimed multiplies before them (default 1; 0 for a cold start)
+//   --sockets          socket study instead: one pinned Rayon pool per socket,
+//                      1, 2, 4, ... threads on socket 0 (scope=within), then
+//                      2, 3, ... full sockets (scope=across, socket_speedup
+//                      against one full socket)
+//   --backend B        rayon (default) | threads | crossbeam | workers
+//                      (+ tokio-blocking | tokio-async with --features tokio)
+//   --format FMT       text (default, tables) | jsonl | json | csv | markdown
+//   --upload URL       POST each (n, T) result as JSON to an HTTP collector
+//   --prometheus FILE  keep FILE updated with Prometheus gauges for each result
+//   --store FILE       append the run to a SQLite database (--features store)
+//   --log FMT          pretty | json execution log on stderr (RUST_LOG filters)
+//   --describe         print the JSON description of arguments and metrics, then exit
+//
+// Every record has gflops, 2 n^3 / time in GFLOP/s of the best run: the
+// arithmetic throughput, comparable with the OpenMP program's and with the
+// machine's peak (for Strassen, which does fewer operations, an effective
+// rate on the same 2 n^3).
 
-// problem sizes 
-const PROBLEM_SIZES: [usize; 5] = [256, 512, 1024, 1536, 2048];
-// thread counts to test
-const THREAD_COUNTS: [usize; 5] = [1, 2, 4, 8, 16];
+use crate::alloc_track::AllocStats;
+use crate::backend::{Backend, Pool};
+use crate::blas;
+use crate::cli;
+use crate::describe::{Description, Param};
+use crate::dump::Dump;
+use crate::ffi;
+use crate::golden::{self, Digest, Golden, Verdict};
+use crate::interrupt;
+use crate::kernels::{Benchmark, Suite};
+use crate::logging::Phase;
+use crate::openmp::OPENMP;
+use crate::pages::{PageVec, Pages};
+use crate::results::{Record, Reporter};
+use crate::rng;
+use crate::sockets::{self, SocketPools};
+use crate::stats::{Repeat, Stats};
+use crate::threads;
+use clap::{ArgAction, ArgMatches, CommandFactory, Parser};
+use std::ops::{Index, IndexMut};
+use std::path::{Path, PathBuf};
+use tracing::info_span;
 
-// row-major order
-type Matrix = Vec<Vec<f64>>;
+// problem sizes unless --sizes is given
+const PROBLEM_SIZES: &str = "256,512,1024,1536,2048";
+// thread counts to test unless --threads is given
+const THREAD_COUNTS: &str = "1,2,4,8,16";
+// Strassen multiplies orders up to this (and odd ones) with the i-j-k kernel
+const STRASSEN_CUTOFF: usize = 128;
+// seed of --init random unless --seed is given
+const MATRIX_SEED: u64 = 20240601;
 
-fn create_matrix(n: usize, init_value: f64) -> Matrix {
-    vec![vec![init_value; n]; n]
+/// n x n, row-major in one buffer, as the C version's `double*`: entry
+/// (i, j) is `as_slice()[i * n + j]` and `m[i]` is row i. The kernels index
+/// the buffer directly, so no product pays for a row pointer per access.
+pub struct Matrix {
+    n: usize,
+    pages: Pages,
+    data: PageVec<f64>,
 }
 
-fn matrix_multiply_sequential(a: &Matrix, b: &Matrix, n: usize) -> Matrix {
-    let mut c = create_matrix(n, 0.0);
+impl Matrix {
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// All n * n entries, row after row.
+    pub fn as_slice(&self) -> &[f64] {
+        &self.data
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [f64] {
+        &mut self.data
+    }
+
+    /// An n x n matrix holding `entries`, row after row.
+    pub fn from_slice(n: usize, entries: &[f64], pages: Pages) -> Matrix {
+        assert_eq!(entries.len(), n * n, "a matrix of order {} has {} entries", n, n * n);
+        let mut m = create_matrix(n, 0.0, pages);
+        m.data.copy_from_slice(entries);
+        m
+    }
+}
+
+impl Index<usize> for Matrix {
+    type Output = [f64];
+
+    fn index(&self, i: usize) -> &[f64] {
+        &self.data[i * self.n..(i + 1) * self.n]
+    }
+}
+
+impl IndexMut<usize> for Matrix {
+    fn index_mut(&mut self, i: usize) -> &mut [f64] {
+        &mut self.data[i * self.n..(i + 1) * self.n]
+    }
+}
+
+// checksums of C = A(1.0) * B(2.0); every entry is exactly 2n in any order
+const GOLDEN: &[Golden] = &[
+    Golden { key: "a=1,b=2,n=256", digest: Digest::Checksum(0xc7fd6d3fa0c22325) },
+    Golden { key: "a=1,b=2,n=512", digest: Digest::Checksum(0x85d3781ab2a22325) },
+    Golden { key: "a=1,b=2,n=1024", digest: Digest::Checksum(0xe1cc5f4224222325) },
+    Golden { key: "a=1,b=2,n=1536", digest: Digest::Checksum(0xd85d37f473222325) },
+    Golden { key: "a=1,b=2,n=2048", digest: Digest::Checksum(0x995f58ecd4222325) },
+];
+
+/// An n x n matrix with every entry `init_value`.
+pub fn create_matrix(n: usize, init_value: f64, pages: Pages) -> Matrix {
+    let data = cli::or_exit(PageVec::filled(n * n, init_value, pages));
+    Matrix { n, pages, data }
+}
+
+/// An n x n matrix of uniform entries in [-1, 1), entry i from value i of
+/// the stream `seed` (rng::at), so the same whatever the thread count.
+pub fn random_matrix(n: usize, seed: u64, pages: Pages) -> Matrix {
+    let mut m = create_matrix(n, 0.0, pages);
+    rng::fill(&mut m.data, seed, |r| (r >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0);
+    m
+}
+
+/// What A and B hold, and the pages of all three matrices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Inputs {
+    pub pages: Pages,
+    /// None: A = 1 and B = 2 throughout; Some(seed): random_matrix of
+    /// `seed` and of `seed + 1`
+    pub seed: Option<u64>,
+}
+
+impl Inputs {
+    pub fn matrices(self, n: usize) -> (Matrix, Matrix) {
+        match self.seed {
+            None => (create_matrix(n, 1.0, self.pages), create_matrix(n, 2.0, self.pages)),
+            Some(seed) => (random_matrix(n, seed, self.pages), random_matrix(n, seed.wrapping_add(1), self.pages)),
+        }
+    }
+
+    /// "const" or "random", the records' init param.
+    pub fn init(self) -> &'static str {
+        if self.seed.is_some() { "random" } else { "const" }
+    }
+
+    // the records' seed param, 0 for constant inputs
+    fn seed(self) -> u64 {
+        self.seed.unwrap_or(0)
+    }
+
+    // the product of these inputs is right: row checksums for random ones,
+    // else the golden digest where one is registered (`fallback` where not)
+    fn check(self, a: &Matrix, b: &Matrix, c: &Matrix, n: usize, fallback: impl FnOnce() -> bool) -> bool {
+        if self.seed.is_some() {
+            return verify_checksum(a, b, c, n);
+        }
+        match check_golden(c, n) {
+            Verdict::Match => true,
+            Verdict::Mismatch => false,
+            Verdict::Unregistered => fallback(),
+        }
+    }
+}
+
+/// Checks C = A * B in O(n^2), without a second multiply: the row sums of C
+/// must equal A (B 1) up to rounding, i.e. row i within 1e-9 of
+/// sum_k |a_ik| sum_j |b_kj|, the magnitude of the terms it adds up.
+pub fn verify_checksum(a: &Matrix, b: &Matrix, c: &Matrix, n: usize) -> bool {
+    // (sum, sum of magnitudes) of each row of B
+    let b_rows: Vec<(f64, f64)> = b.as_slice().chunks(n).map(|row| (row.iter().sum(), row.iter().map(|v| v.abs()).sum())).collect();
+    a.as_slice().chunks(n).zip(c.as_slice().chunks(n)).all(|(a_row, c_row)| {
+        let expected: f64 = a_row.iter().zip(&b_rows).map(|(x, (sum, _))| x * sum).sum();
+        let bound: f64 = a_row.iter().zip(&b_rows).map(|(x, (_, magnitude))| x.abs() * magnitude).sum();
+        (c_row.iter().sum::<f64>() - expected).abs() <= 1e-9 * bound
+    })
+}
+
+/// C = A * B in i-j-k order on the calling thread.
+pub fn matrix_multiply_sequential(a: &Matrix, b: &Matrix, n: usize) -> Matrix {
+    let mut c = create_matrix(n, 0.0, a.pages);
+    let (a, b) = (a.as_slice(), b.as_slice());
     
     for i in 0..n {
         for j in 0..n {
             let mut sum = 0.0;
             for k in 0..n {
-                sum += a[i][k] * b[k][j];
+                sum += a[i * n + k] * b[k * n + j];
             }
-            c[i][j] = sum;
+            c.data[i * n + j] = sum;
         }
     }
     
@@ -34,126 +236,519 @@ fn matrix_multiply_sequential(a: &Matrix, b: &Matrix, n: usize) -> Matrix {
 fn verify_results(sequential: &Matrix, parallel: &Matrix, n: usize) -> bool {
     const EPSILON: f64 = 1e-6;
     
-    for i in 0..n {
-        for j in 0..n {
-            if (sequential[i][j] - parallel[i][j]).abs() > EPSILON {
-                return false;
-            }
-        }
+    let entries = n * n;
+    sequential.as_slice()[..entries].iter().zip(&parallel.as_slice()[..entries]).all(|(s, p)| (s - p).abs() <= EPSILON)
+}
+
+// c_row = a_row * B in i-k-j order, so the inner loop streams rows of B
+// through f64x4 lanes instead of striding down a column
+#[cfg(feature = "simd")]
+fn row_times_matrix_simd(a_row: &[f64], b: &Matrix, c_row: &mut [f64]) {
+    use crate::simd_util;
+    use std::simd::prelude::*;
+
+    c_row.fill(0.0);
+    for (k, &aik) in a_row.iter().enumerate() {
+        let b_row = &b[k];
+        let va = f64x4::splat(aik);
+        simd_util::update(c_row, |j, vc| vc + va * simd_util::load_prefix(&b_row[j..]));
     }
-    true
 }
 
-fn matrix_multiply_parallel_with_pool(
-    pool: &rayon::ThreadPool, 
+#[cfg(not(feature = "simd"))]
+fn row_times_matrix_simd(_a_row: &[f64], _b: &Matrix, _c_row: &mut [f64]) {
+    unreachable!("built without the simd feature")
+}
+
+/// C = A * B on `pool`; `simd` selects the i-k-j std::simd row kernel and
+/// needs a build with the "simd" feature.
+pub fn matrix_multiply_parallel(
+    pool: &Pool, 
     a: &Matrix, 
     b: &Matrix, 
-    n: usize
+    n: usize,
+    simd: bool,
 ) -> Matrix {
-    let mut c = create_matrix(n, 0.0);
+    let mut c = create_matrix(n, 0.0, a.pages);
     
-    pool.install(|| {
-        c.par_iter_mut()
-            .enumerate()
-            .for_each(|(i, row)| {
-                for j in 0..n {
-                    let mut sum = 0.0;
-                    for k in 0..n {
-                        sum += a[i][k] * b[k][j];
-                    }
-                    row[j] = sum;
-                }
-            });
+    // rows are the unit of work: one row per task on rayon, row blocks otherwise
+    pool.for_each_chunk_mut(&mut c.data, pool.default_grain(n) * n, |start, rows| {
+        multiply_rows(a, b, n, simd, start / n, rows);
     });
     
     c
 }
 
-fn run_benchmark(n: usize, threads: usize) -> (f64, f64, f64) {
-    // Create a custom thread pool for this benchmark
-    let pool = rayon::ThreadPoolBuilder::new()
-        .num_threads(threads)
-        .build()
-        .unwrap();
-    
-    // init matrices
-    let a = create_matrix(n, 1.0);
-    let b = create_matrix(n, 2.0);
-    
-    // warm-up 
-    if n >= 256 {
-        let warm_n = 128;
-        let warm_a = create_matrix(warm_n, 1.0);
-        let warm_b = create_matrix(warm_n, 2.0);
-        let _ = matrix_multiply_parallel_with_pool(&pool, &warm_a, &warm_b, warm_n);
+/// C = A * B with the cache-blocked kernel on `pool`, in `block` x `block`
+/// tiles.
+pub fn matrix_multiply_tiled(pool: &Pool, a: &Matrix, b: &Matrix, n: usize, block: usize) -> Matrix {
+    let mut c = create_matrix(n, 0.0, a.pages);
+
+    // row blocks of up to `block` rows per task on rayon (but no fewer tasks
+    // than threads), one contiguous block per thread otherwise
+    let rows = match pool.backend() {
+        Backend::Rayon => block.min(n.div_ceil(pool.threads())),
+        _ => pool.default_grain(n),
+    };
+    pool.for_each_chunk_mut(&mut c.data, rows * n, |start, rows| {
+        multiply_rows_tiled(a, b, n, block, start / n, rows);
+    });
+
+    c
+}
+
+/// C = A * B on `pool` via B^T: the transpose (rows of B^T in parallel), then
+/// one row of C per task as dot products of rows of A and of B^T.
+pub fn matrix_multiply_transposed(pool: &Pool, a: &Matrix, b: &Matrix, n: usize) -> Matrix {
+    let mut bt = create_matrix(n, 0.0, b.pages);
+    pool.for_each_chunk_mut(&mut bt.data, pool.default_grain(n) * n, |start, rows| {
+        for (r, row) in rows.chunks_mut(n).enumerate() {
+            let j = start / n + r;
+            for (k, entry) in row.iter_mut().enumerate() {
+                *entry = b.data[k * n + j];
+            }
+        }
+    });
+
+    let mut c = create_matrix(n, 0.0, a.pages);
+    pool.for_each_chunk_mut(&mut c.data, pool.default_grain(n) * n, |start, rows| {
+        multiply_rows_transposed(a, &bt, n, start / n, rows);
+    });
+
+    c
+}
+
+/// C = A * B by Strassen's recursion on `pool`, which must be a Rayon pool:
+/// each level forks its seven half-size products with `rayon::join`; orders
+/// that are odd or at most `cutoff` multiply with the i-j-k kernel.
+pub fn matrix_multiply_strassen(pool: &Pool, a: &Matrix, b: &Matrix, n: usize, cutoff: usize) -> Matrix {
+    let mut c = create_matrix(n, 0.0, a.pages);
+    pool.install(|| strassen(a.as_slice(), b.as_slice(), c.as_mut_slice(), n, cutoff));
+    c
+}
+
+// c = a * b for row-major n x n slices
+fn strassen(a: &[f64], b: &[f64], c: &mut [f64], n: usize, cutoff: usize) {
+    if n <= cutoff || n % 2 == 1 {
+        for i in 0..n {
+            for j in 0..n {
+                let mut sum = 0.0;
+                for k in 0..n {
+                    sum += a[i * n + k] * b[k * n + j];
+                }
+                c[i * n + j] = sum;
+            }
+        }
+        return;
+    }
+
+    let h = n / 2;
+    // quadrant (qi, qj) of an n x n matrix as an h x h one
+    let quadrant = |m: &[f64], qi: usize, qj: usize| -> Vec<f64> {
+        (0..h).flat_map(|i| m[(qi * h + i) * n + qj * h..][..h].iter().copied()).collect()
+    };
+    let [a11, a12, a21, a22] = [(0, 0), (0, 1), (1, 0), (1, 1)].map(|(qi, qj)| quadrant(a, qi, qj));
+    let [b11, b12, b21, b22] = [(0, 0), (0, 1), (1, 0), (1, 1)].map(|(qi, qj)| quadrant(b, qi, qj));
+    let add = |x: &[f64], y: &[f64]| -> Vec<f64> { x.iter().zip(y).map(|(x, y)| x + y).collect() };
+    let sub = |x: &[f64], y: &[f64]| -> Vec<f64> { x.iter().zip(y).map(|(x, y)| x - y).collect() };
+    let product = |x: &[f64], y: &[f64]| {
+        let mut m = vec![0.0; h * h];
+        strassen(x, y, &mut m, h, cutoff);
+        m
+    };
+
+    let (((m1, m2), (m3, m4)), ((m5, m6), m7)) = rayon::join(
+        || {
+            rayon::join(
+                || rayon::join(|| product(&add(&a11, &a22), &add(&b11, &b22)), || product(&add(&a21, &a22), &b11)),
+                || rayon::join(|| product(&a11, &sub(&b12, &b22)), || product(&a22, &sub(&b21, &b11))),
+            )
+        },
+        || {
+            rayon::join(
+                || rayon::join(|| product(&add(&a11, &a12), &b22), || product(&sub(&a21, &a11), &add(&b11, &b12))),
+                || product(&sub(&a12, &a22), &add(&b21, &b22)),
+            )
+        },
+    );
+
+    for i in 0..h {
+        for j in 0..h {
+            let q = i * h + j;
+            c[i * n + j] = m1[q] + m4[q] - m5[q] + m7[q];
+            c[i * n + h + j] = m3[q] + m5[q];
+            c[(h + i) * n + j] = m2[q] + m4[q];
+            c[(h + i) * n + h + j] = m1[q] - m2[q] + m3[q] + m6[q];
+        }
+    }
+}
+
+/// C = A * B with the dgemm of the linked BLAS on as many threads as `pool`
+/// has; needs a build with the "blas" feature.
+pub fn matrix_multiply_blas(pool: &Pool, a: &Matrix, b: &Matrix, n: usize) -> Matrix {
+    let mut c = create_matrix(n, 0.0, a.pages);
+    blas::dgemm(a.as_slice(), b.as_slice(), c.as_mut_slice(), n, pool.threads());
+    c
+}
+
+/// C = A * B with one row per task, each socket's pool working through its
+/// block of rows.
+pub fn matrix_multiply_on_sockets(pools: &SocketPools, a: &Matrix, b: &Matrix, n: usize, simd: bool) -> Matrix {
+    let mut c = create_matrix(n, 0.0, a.pages);
+    pools.for_each_chunk_mut(&mut c.data, n, |start, rows| multiply_rows(a, b, n, simd, start / n, rows));
+    c
+}
+
+/// The inner kernel: rows `first..` of C = A * B, laid out back to back in
+/// `rows`; i-j-k per row, or the std::simd i-k-j row kernel with `simd`.
+pub fn multiply_rows(a: &Matrix, b: &Matrix, n: usize, simd: bool, first: usize, rows: &mut [f64]) {
+    for (r, row) in rows.chunks_mut(n).enumerate() {
+        let i = first + r;
+        if simd {
+            row_times_matrix_simd(&a[i], b, row);
+            continue;
+        }
+        let (a_row, b) = (&a[i], b.as_slice());
+        for (j, c) in row.iter_mut().enumerate() {
+            let mut sum = 0.0;
+            for k in 0..n {
+                sum += a_row[k] * b[k * n + j];
+            }
+            *c = sum;
+        }
+    }
+}
+
+/// The cache-blocked kernel: rows `first..` of C = A * B, laid out back to
+/// back in `rows`, tile by tile: for each `block`-wide band of k and of j,
+/// every row adds its `block` x `block` products, so a tile of B stays in
+/// cache for all the rows. Each entry still sums over k in ascending order,
+/// so the result is bit-identical to the i-j-k kernel.
+pub fn multiply_rows_tiled(a: &Matrix, b: &Matrix, n: usize, block: usize, first: usize, rows: &mut [f64]) {
+    rows.fill(0.0);
+    let b = b.as_slice();
+    for kk in (0..n).step_by(block) {
+        let k_end = (kk + block).min(n);
+        for jj in (0..n).step_by(block) {
+            let j_end = (jj + block).min(n);
+            for (r, row) in rows.chunks_mut(n).enumerate() {
+                let a_row = &a[first + r];
+                let c_tile = &mut row[jj..j_end];
+                for k in kk..k_end {
+                    let aik = a_row[k];
+                    for (c, &bkj) in c_tile.iter_mut().zip(&b[k * n + jj..k * n + j_end]) {
+                        *c += aik * bkj;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The transposed-B kernel: rows `first..` of C = A * B, laid out back to
+/// back in `rows`, given `bt` = B^T. Entry (i, j) is the dot product of row i
+/// of A and row j of B^T, so both operands are read with unit stride; the
+/// sum runs over k in ascending order as in the i-j-k kernel.
+pub fn multiply_rows_transposed(a: &Matrix, bt: &Matrix, n: usize, first: usize, rows: &mut [f64]) {
+    for (r, row) in rows.chunks_mut(n).enumerate() {
+        let a_row = &a[first + r];
+        for (j, c) in row.iter_mut().enumerate() {
+            *c = a_row.iter().zip(&bt[j]).fold(0.0, |sum, (x, y)| sum + x * y);
+        }
+    }
+}
+
+/// The loop nest a run times.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// one row per task, i-j-k
+    Naive,
+    /// one row per task, the std::simd i-k-j kernel ("simd" feature)
+    Simd,
+    /// the cache-blocked kernel with `block` x `block` tiles (scalar)
+    Tiled { block: usize },
+    /// dot products against B^T, transposed in the timed region (scalar)
+    Transposed,
+    /// Strassen's recursion down to STRASSEN_CUTOFF (Rayon only)
+    Strassen,
+    /// OpenBLAS dgemm, the reference ("blas" feature)
+    Blas,
+}
+
+impl Strategy {
+    /// The strategies of a run: the naive kernel, the SIMD one with `simd`,
+    /// the tiled one unless `block` is 0, the transposed-B one with
+    /// `transpose`, Strassen's with `strassen`, then dgemm with `blas`.
+    pub fn all(simd: bool, block: usize, transpose: bool, strassen: bool, blas: bool) -> Vec<Strategy> {
+        let mut strategies = vec![Strategy::Naive];
+        if simd {
+            strategies.push(Strategy::Simd);
+        }
+        if block > 0 {
+            strategies.push(Strategy::Tiled { block });
+        }
+        if transpose {
+            strategies.push(Strategy::Transposed);
+        }
+        if strassen {
+            strategies.push(Strategy::Strassen);
+        }
+        if blas {
+            strategies.push(Strategy::Blas);
+        }
+        strategies
+    }
+
+    pub fn simd(self) -> bool {
+        self == Strategy::Simd
+    }
+
+    /// The tile edge, 0 for the naive kernel.
+    pub fn block(self) -> usize {
+        match self {
+            Strategy::Tiled { block } => block,
+            _ => 0,
+        }
+    }
+
+    pub fn transpose(self) -> bool {
+        self == Strategy::Transposed
+    }
+
+    /// The impl of the strategy's records: "blas" for dgemm, else the
+    /// backend's name.
+    pub fn implementation(self, backend: Backend) -> &'static str {
+        if self == Strategy::Blas { "blas" } else { backend.name() }
+    }
+
+    /// "strassen" for Strassen's recursion, "classic" for the O(n^3) loops.
+    pub fn algo(self) -> &'static str {
+        if self == Strategy::Strassen { "strassen" } else { "classic" }
+    }
+
+    // tells the strategy's rows of the summary table apart: "512" for the
+    // naive kernel, "512 simd", "512 b64" tiled, "512 bt" transposed,
+    // "512 strassen", "512 blas"
+    fn suffix(self) -> String {
+        match self {
+            Strategy::Naive => String::new(),
+            Strategy::Simd => " simd".to_string(),
+            Strategy::Tiled { block } => format!(" b{}", block),
+            Strategy::Transposed => " bt".to_string(),
+            Strategy::Strassen => " strassen".to_string(),
+            Strategy::Blas => " blas".to_string(),
+        }
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            Strategy::Naive => "naive i-j-k".to_string(),
+            Strategy::Simd => "std::simd i-k-j".to_string(),
+            Strategy::Tiled { block } => format!("tiled {}x{}", block, block),
+            Strategy::Transposed => "transposed B".to_string(),
+            Strategy::Strassen => format!("strassen (cutoff {})", STRASSEN_CUTOFF),
+            Strategy::Blas => "BLAS dgemm".to_string(),
+        }
     }
+
+    fn multiply(self, pool: &Pool, a: &Matrix, b: &Matrix, n: usize) -> Matrix {
+        match self {
+            Strategy::Naive => matrix_multiply_parallel(pool, a, b, n, false),
+            Strategy::Simd => matrix_multiply_parallel(pool, a, b, n, true),
+            Strategy::Tiled { block } => matrix_multiply_tiled(pool, a, b, n, block),
+            Strategy::Transposed => matrix_multiply_transposed(pool, a, b, n),
+            Strategy::Strassen => matrix_multiply_strassen(pool, a, b, n, STRASSEN_CUTOFF),
+            Strategy::Blas => matrix_multiply_blas(pool, a, b, n),
+        }
+    }
+}
+
+/// GFLOP/s of one n x n multiply in `seconds`: 2 n^3 floating-point
+/// operations (a multiply and an add per term).
+pub fn gflops(n: usize, seconds: f64) -> f64 {
+    2.0 * (n as f64).powi(3) / seconds / 1e9
+}
+
+fn check_golden(c: &Matrix, n: usize) -> Verdict {
+    let key = format!("a=1,b=2,n={}", n);
+    let digest = Digest::Checksum(golden::checksum(c.data.iter().map(|v| v.to_bits())));
+    golden::check("matmul", GOLDEN, &key, digest)
+}
+
+// returns (best sequential time, parallel times, fastest parallel run's
+// allocations); the product goes to `dump` if given
+fn run_benchmark(backend: Backend, n: usize, threads: usize, strategy: Strategy, inputs: Inputs, repeat: Repeat, dump: Option<&Path>) -> (f64, Stats, Option<AllocStats>) {
+    let _bench = info_span!("matmul", backend = backend.name(), n, threads, simd = strategy.simd(), block = strategy.block(), transpose = strategy.transpose(), algo = strategy.algo(), pages = inputs.pages.name(), init = inputs.init()).entered();
+    let setup = Phase::Setup.span().entered();
+
+    // a pool of this cell's own, dropped with it: a sweep never touches the
+    // global Rayon pool, whose size can be set only once per process
+    let pool = Pool::new(backend, threads);
     
-    // sequential 
+    // init matrices
+    let (a, b) = inputs.matrices(n);
+    drop(setup);
+
+    // sequential (always scalar: it is the baseline for SIMD speedups)
     let seq_time = if threads == 1 {
-        let start = Instant::now();
-        let _ = matrix_multiply_sequential(&a, &b, n);
-        start.elapsed().as_secs_f64()
+        repeat.measure(|| matrix_multiply_sequential(&a, &b, n)).0.min
     } else {
         0.0 
     };
     
     // parallel version
-    let start = Instant::now();
-    let result_parallel = matrix_multiply_parallel_with_pool(&pool, &a, &b, n);
-    let par_time = start.elapsed().as_secs_f64();
+    let (par_time, result_parallel, allocs) = repeat.measure(|| strategy.multiply(&pool, &a, &b, n));
     
-    // correctness 
-    if threads == 1 {
-        let result_sequential = matrix_multiply_sequential(&a, &b, n);
-        if !verify_results(&result_sequential, &result_parallel, n) {
+    // correctness: every run against its row checksums (random inputs) or
+    // the registered checksum, otherwise the T=1 run against a sequential
+    // re-computation
+    Phase::Verify.run(|| {
+        let correct = inputs.check(&a, &b, &result_parallel, n, || {
+            threads != 1 || verify_results(&matrix_multiply_sequential(&a, &b, n), &result_parallel, n)
+        });
+        if !correct {
             eprintln!("Warning: Results do not match for n={}, threads={}", n, threads);
         }
-    }
-    
-    let efficiency = if threads == 1 {
-        1.0
-    } else {
-        0.0 
-    };
+        if let Some(path) = dump {
+            cli::or_exit(Dump::matmul(n, result_parallel.as_slice()).write(path));
+        }
+    });
     
-    (seq_time, par_time, efficiency)
+    (seq_time, par_time, allocs)
 }
 
-fn run_scalability_study() {
-    println!("=== Rust Matrix Multiply Benchmark (Scalability) ===");
-    println!("Testing problem sizes: {:?}", PROBLEM_SIZES);
-    println!("Testing thread counts: {:?}", THREAD_COUNTS);
-    println!();
-    
-    //  baseline 
-    let mut baselines: Vec<f64> = Vec::new();
-    
-    for &n in &PROBLEM_SIZES {
+// the linked C multiply on the cell (n, T), on inputs built as for the Rust
+// runs and under the same repeat; printed in text mode and recorded
+fn run_openmp(n: usize, threads: usize, inputs: Inputs, repeat: Repeat, reporter: &mut Reporter) {
+    let _bench = info_span!("matmul", backend = OPENMP, n, threads, pages = inputs.pages.name(), init = inputs.init()).entered();
+    let setup = Phase::Setup.span().entered();
+    let (a, b) = inputs.matrices(n);
+    let mut c = create_matrix(n, 0.0, inputs.pages);
+    drop(setup);
+
+    let (time, ()) = repeat.run(|| (ffi::matmul(a.as_slice(), b.as_slice(), c.as_mut_slice(), n, threads), ()));
+    let correct = Phase::Verify.run(|| inputs.check(&a, &b, &c, n, || c.as_slice().iter().all(|&v| v == 2.0 * n as f64)));
+    if !correct {
+        eprintln!("Warning: OpenMP results do not match for n={}, threads={}", n, threads);
+    }
+    if reporter.is_text() {
+        println!("         OpenMP (linked) ... Time: {:.6}s, GFLOPS: {:.2}{}", time.min, gflops(n, time.min), time.summary());
+    }
+
+    reporter.record(
+        &Record::new("matmul", OPENMP)
+            .param("n", n)
+            .param("T", threads)
+            .param("simd", 0)
+            .param("block", 0)
+            .param("transpose", 0)
+            .param("algo", "classic")
+            .param("pages", inputs.pages.name())
+            .param("init", inputs.init())
+            .param("seed", inputs.seed())
+            .timing("time", &time, "sec")
+            .metric("gflops", gflops(n, time.min), "GFLOP/s")
+            .metric("correct", if correct { 1.0 } else { 0.0 }, "boolean")
+            .energy(time.energy)
+            .peak_rss(time.peak_rss),
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_scalability_study(backend: Backend, strategies: &[Strategy], ffi: bool, inputs: Inputs, sizes: &[usize], thread_counts: &[usize], repeat: Repeat, reporter: &mut Reporter) {
+    // human-readable tables only in text format
+    let text = reporter.is_text();
+
+    if text {
+        println!("=== Rust Matrix Multiply Benchmark (Scalability) ===");
+        println!("Backend: {}", backend.name());
+        if strategies.iter().any(|s| s.simd()) {
+            println!("SIMD: std::simd row kernel (speedups vs scalar T=1)");
+        }
+        let names: Vec<String> = strategies.iter().map(|s| s.name()).collect();
+        println!("Strategies: {} (each against its own T=1)", names.join(", "));
+        println!("Pages: {}", inputs.pages.name());
+        match inputs.seed {
+            Some(seed) => println!("Inputs: random, seed {} (row checksums)", seed),
+            None => println!("Inputs: A = 1, B = 2"),
+        }
+        println!("Testing problem sizes: {:?}", sizes);
+        println!("Testing thread counts: {:?}", thread_counts);
         println!();
-        println!("{}", "=".repeat(60));
-        println!("Problem Size: n = {}", n);
-        println!("{}", "=".repeat(60));
-        
-        let mut baseline_time = 0.0;
+    }
+    
+    for &n in sizes {
+        if text {
+            println!();
+            println!("{}", "=".repeat(60));
+            println!("Problem Size: n = {}", n);
+            println!("{}", "=".repeat(60));
+        }
         
-        for &threads in &THREAD_COUNTS {
-            print!("Threads = {:2} ... ", threads);
-            std::io::Write::flush(&mut std::io::stdout()).unwrap();
-            
-            let (_seq_time, par_time, _) = run_benchmark(n, threads);
-            
-            if threads == 1 {
-                baseline_time = par_time;
-                println!("Time: {:.6}s (baseline)", par_time);
-            } else {
-                let speedup = baseline_time / par_time;
+        for &strategy in strategies {
+            if text && strategies.len() > 1 {
+                println!("Strategy: {}", strategy.name());
+            }
+            let simd = strategy.simd();
+            let mut baseline_time = 0.0;
+
+            for &threads in thread_counts {
+                if interrupt::requested() {
+                    return;
+                }
+                if text {
+                    print!("Threads = {:2} ... ", threads);
+                    std::io::Write::flush(&mut std::io::stdout()).unwrap();
+                }
+
+                let (seq_time, par_time, allocs) = run_benchmark(backend, n, threads, strategy, inputs, repeat, None);
+
+                // with --simd the baseline is the scalar loop, so T=1 already
+                // shows the SIMD-only gain and T>1 the combined one
+                if threads == 1 {
+                    baseline_time = if simd { seq_time } else { par_time.min };
+                }
+                let speedup = baseline_time / par_time.min;
                 let efficiency = speedup / threads as f64;
-                println!("Time: {:.6}s, Speedup: {:.2}x, Efficiency: {:.2}%", 
-                         par_time, speedup, efficiency * 100.0);
+
+                if text {
+                    if threads == 1 && !simd {
+                        println!("Time: {:.6}s (baseline), GFLOPS: {:.2}{}", par_time.min, gflops(n, par_time.min), par_time.summary());
+                    } else {
+                        println!("Time: {:.6}s, Speedup: {:.2}x, Efficiency: {:.2}%, GFLOPS: {:.2}{}", 
+                                 par_time.min, speedup, efficiency * 100.0, gflops(n, par_time.min), par_time.summary());
+                    }
+                }
+
+                reporter.record(
+                    &Record::new("matmul", strategy.implementation(backend))
+                        .param("n", n)
+                        .param("T", threads)
+                        .param("simd", simd as u8)
+                        .param("block", strategy.block())
+                        .param("transpose", strategy.transpose() as u8)
+                        .param("algo", strategy.algo())
+                        .param("pages", inputs.pages.name())
+                        .param("init", inputs.init())
+                        .param("seed", inputs.seed())
+                        .timing("time", &par_time, "sec")
+                        .metric("speedup", speedup, "x")
+                        .metric("efficiency", efficiency, "ratio")
+                        .metric("gflops", gflops(n, par_time.min), "GFLOP/s")
+                        .allocs(allocs)
+                        .energy(par_time.energy)
+                        .peak_rss(par_time.peak_rss),
+                );
+                // the C multiply is the naive one
+                if ffi && strategy == Strategy::Naive {
+                    run_openmp(n, threads, inputs, repeat, reporter);
+                }
             }
         }
-        
-        baselines.push(baseline_time);
+    }
+
+    if !text {
+        return;
     }
     
     println!();
@@ -161,17 +756,25 @@ fn run_scalability_study() {
     println!("{}", "=".repeat(60));
     println!("Summary: Execution Times (seconds)");
     println!("{}", "=".repeat(60));
-    println!("{:>8} {:>10} {:>10} {:>10} {:>10} {:>10}", 
-             "n \\ T", "1", "2", "4", "8", "16");
+    print!("{:>12}", "n \\ T");
+    for threads in thread_counts {
+        print!(" {:>10}", threads);
+    }
+    println!();
     println!("{}", "-".repeat(60));
     
-    for &n in &PROBLEM_SIZES {
-        print!("{:>8}", n);
-        for &threads in &THREAD_COUNTS {
-            let (_, par_time, _) = run_benchmark(n, threads);
-            print!(" {:>10.4}", par_time);
+    for &n in sizes {
+        for &strategy in strategies {
+            print!("{:>12}", format!("{}{}", n, strategy.suffix()));
+            for &threads in thread_counts {
+                if interrupt::requested() {
+                    return;
+                }
+                let (_, par_time, _) = run_benchmark(backend, n, threads, strategy, inputs, repeat, None);
+                print!(" {:>10.4}", par_time.min);
+            }
+            println!();
         }
-        println!();
     }
     
     println!();
@@ -189,24 +792,345 @@ fn run_scalability_study() {
     println!("  - Memory-safe concurrent access");
 }
 
-fn main() {
-    // if specific configuration
-    let args: Vec<String> = env::args().collect();
-    
-    if args.len() == 3 {
-        let n: usize = args[1].parse().expect("Invalid problem size");
-        let threads: usize = args[2].parse().expect("Invalid thread count");
-        
-        println!("Running single benchmark: n={}, threads={}", n, threads);
-        let (_seq_time, par_time, _) = run_benchmark(n, threads);
-        
-        if threads == 1 {
-            println!("Time: {:.6}s", par_time);
-        } else {
-            println!("Parallel time: {:.6}s", par_time);
+// times of the multiplies on `pools`, and the fastest one's allocations
+fn run_on_sockets(topology: &[Vec<usize>], threads_per_socket: usize, n: usize, simd: bool, inputs: Inputs, repeat: Repeat) -> (Stats, Option<AllocStats>) {
+    let _bench = info_span!("matmul", backend = "sockets", n, sockets = topology.len(), threads_per_socket, simd, pages = inputs.pages.name(), init = inputs.init()).entered();
+    let (pools, (a, b)) = Phase::Setup.run(|| (SocketPools::new(topology, threads_per_socket), inputs.matrices(n)));
+    let (time, c, allocs) = repeat.measure(|| matrix_multiply_on_sockets(&pools, &a, &b, n, simd));
+
+    Phase::Verify.run(|| {
+        if !inputs.check(&a, &b, &c, n, || true) {
+            eprintln!("Warning: Results do not match for n={}, sockets={}", n, topology.len());
+        }
+    });
+    (time, allocs)
+}
+
+// --sockets: scaling within socket 0 (1, 2, 4, ... of its CPUs), then
+// across 2, 3, ... full sockets, each with its own pinned pool
+fn run_socket_study(sizes: &[usize], simd: bool, inputs: Inputs, repeat: Repeat, reporter: &mut Reporter) {
+    let topology = sockets::topology();
+    let per_socket = topology.iter().map(Vec::len).min().unwrap_or(1);
+    let mut within: Vec<usize> = (0..).map(|p| 1 << p).take_while(|&t| t < per_socket).collect();
+    within.push(per_socket);
+    let text = reporter.is_text();
+
+    if text {
+        println!("=== Rust Matrix Multiply Benchmark (Socket Scaling) ===");
+        println!("Sockets: {} x {} CPUs", topology.len(), per_socket);
+        println!("Pages: {}", inputs.pages.name());
+        println!("Inputs: {}", inputs.init());
+        println!("Speedups against 1 thread, socket speedups against 1 full socket");
+        println!();
+    }
+
+    for &n in sizes {
+        if text {
+            println!("Problem Size: n = {}", n);
+        }
+        let mut baseline = 0.0;
+        let mut one_socket = 0.0;
+        let cells = within.iter().map(|&t| ("within", 1, t)).chain((2..=topology.len()).map(|k| ("across", k, per_socket)));
+        for (scope, socket_count, threads_per_socket) in cells {
+            if interrupt::requested() {
+                return;
+            }
+            let threads = socket_count * threads_per_socket;
+            let (time, allocs) = run_on_sockets(&topology[..socket_count], threads_per_socket, n, simd, inputs, repeat);
+            if threads == 1 {
+                baseline = time.min;
+            }
+            if socket_count == 1 && threads_per_socket == per_socket {
+                one_socket = time.min;
+            }
+            let speedup = baseline / time.min;
+            let socket_speedup = one_socket / time.min;
+
+            if text {
+                print!("  {:6} sockets={} T={:3} Time: {:.6}s, GFLOPS: {:.2}, Speedup: {:.2}x", scope, socket_count, threads, time.min, gflops(n, time.min), speedup);
+                if scope == "across" {
+                    print!(", Socket speedup: {:.2}x", socket_speedup);
+                }
+                println!();
+            }
+            let mut record = Record::new("matmul", "sockets")
+                .param("n", n)
+                .param("scope", scope)
+                .param("sockets", socket_count)
+                .param("T", threads)
+                .param("simd", simd as u8)
+                .param("block", 0)
+                .param("transpose", 0)
+                .param("algo", "classic")
+                .param("pages", inputs.pages.name())
+                .param("init", inputs.init())
+                .param("seed", inputs.seed())
+                .timing("time", &time, "sec")
+                .metric("speedup", speedup, "x")
+                .metric("efficiency", speedup / threads as f64, "ratio")
+                .metric("gflops", gflops(n, time.min), "GFLOP/s");
+            if scope == "across" {
+                record = record.metric("socket_speedup", socket_speedup, "x");
+            }
+            reporter.record(&record.allocs(allocs).energy(time.energy).peak_rss(time.peak_rss));
+        }
+    }
+}
+
+// answer to --describe
+pub fn description() -> Description {
+    Description::new("matrix_multiply", "matmul", "n x n f64 matrix multiply; without --size, the study over --sizes x --threads")
+        .option(Param::integer("--size", 1, "matrix order of a single configuration").optional())
+        .option(Param::integer_list("--sizes", 1, "matrix orders of the study and of --sockets").default(PROBLEM_SIZES))
+        .threads_option(THREAD_COUNTS, "thread counts; the study always includes T=1")
+        .backend_option()
+        .simd_option("also run the std::simd i-k-j row kernel as a strategy")
+        .option(Param::integer("--block", 0, "tile edge of the cache-blocked kernel run as a second strategy; 0 = naive kernel only").default(64))
+        .option(Param::switch("--transpose", "also run the transposed-B kernel, unit-stride on both operands").default(0))
+        .blas_option("also run OpenBLAS dgemm as the best-achievable reference; records have impl blas")
+        .option(Param::choice("--algo", &["classic", "strassen"], "strassen: also run Strassen's recursion, forked with rayon::join (rayon backend only)").default("classic"))
+        .ffi_option()
+        .dump_option("with --size: write the checksum of the product to this file as a canonical result dump")
+        .option(Param::flag("--sockets", "socket study: one pinned Rayon pool per socket, within- and cross-socket scaling"))
+        .pages_option()
+        .option(Param::choice("--init", &["const", "random"], "inputs: A = 1 and B = 2, or seeded uniform entries checked by row checksums").default("const"))
+        .option(Param::integer("--seed", 0, "seed of --init random").default(MATRIX_SEED))
+        .params(&["n", "T", "simd", "block", "transpose", "algo", "pages", "init", "seed"])
+        .timing("time", "sec")
+        .metric("speedup", "x")
+        .metric("efficiency", "ratio")
+        .metric("gflops", "GFLOP/s")
+}
+
+/// n x n f64 matrix multiply: the study over --sizes, or one order
+#[derive(Debug, Clone, Parser)]
+#[command(name = "matrix_multiply")]
+pub struct Args {
+    /// matrix order of a single configuration instead of the study
+    #[arg(long, value_name = "n", value_parser = cli::positive)]
+    pub size: Option<usize>,
+    /// matrix orders of the study and of --sockets, e.g. 256,512,4096
+    #[arg(long, value_name = "LIST", value_parser = cli::positive_list, default_value = PROBLEM_SIZES, conflicts_with = "size")]
+    pub sizes: ::std::vec::Vec<usize>,
+    /// thread counts, e.g. 1,2,4,half,max; the study always includes T=1
+    #[arg(long, value_name = "LIST", value_parser = threads::parse_list, default_value = THREAD_COUNTS)]
+    pub threads: ::std::vec::Vec<usize>,
+    /// also run the std::simd i-k-j row kernel as a strategy (nightly + --features simd)
+    #[arg(long)]
+    pub simd: bool,
+    /// tile edge of the cache-blocked kernel run beside the naive one, e.g. 32, 64, 128; 0 = naive only
+    #[arg(long, value_name = "B", default_value_t = 64)]
+    pub block: usize,
+    /// 0 | 1: also run the transposed-B kernel, unit-stride on both operands
+    #[arg(long, value_name = "0|1", action = ArgAction::Set, value_parser = cli::switch, default_value = "0")]
+    pub transpose: bool,
+    /// classic | strassen: also run Strassen's recursion, forked with rayon::join (rayon backend only)
+    #[arg(long, value_name = "A", value_parser = ["classic", "strassen"], default_value = "classic")]
+    pub algo: String,
+    /// 0 | 1: also run OpenBLAS dgemm as the best-achievable reference (--features blas)
+    #[arg(long, value_name = "0|1", action = ArgAction::Set, value_parser = cli::switch, default_value = "0")]
+    pub blas: bool,
+    /// with --size: write the checksum of the product to FILE as a canonical result dump
+    #[arg(long, value_name = "FILE", requires = "size")]
+    pub dump: Option<PathBuf>,
+    /// 0 | 1: also time the linked C/OpenMP multiply on each cell (--features ffi)
+    #[arg(long, value_name = "0|1", action = ArgAction::Set, value_parser = cli::switch, default_value = "0")]
+    pub ffi: bool,
+    /// socket study: one pinned Rayon pool per socket
+    #[arg(long, conflicts_with = "size")]
+    pub sockets: bool,
+    /// rayon | threads | crossbeam | workers (+ tokio-blocking | tokio-async)
+    #[arg(long, value_name = "B", value_parser = Backend::parse, default_value = "rayon")]
+    pub backend: Backend,
+    /// default | thp | hugetlb pages for A, B and C
+    #[arg(long, value_name = "P", value_parser = Pages::parse, default_value = "default")]
+    pub pages: Pages,
+    /// const (A = 1, B = 2) | random: seeded uniform entries, checked by row checksums
+    #[arg(long, value_name = "I", value_parser = ["const", "random"], default_value = "const")]
+    pub init: String,
+    /// seed of --init random
+    #[arg(long, value_name = "S", default_value_t = MATRIX_SEED)]
+    pub seed: u64,
+    #[command(flatten)]
+    pub common: cli::Common,
+}
+
+pub struct MatrixMultiply;
+
+impl Benchmark for MatrixMultiply {
+    fn name(&self) -> &'static str {
+        "matrix_multiply"
+    }
+
+    fn label(&self) -> &'static str {
+        "Matrix multiply"
+    }
+
+    fn suite(&self) -> Suite {
+        Suite::Scalability
+    }
+
+    fn description(&self) -> Description {
+        description()
+    }
+
+    fn command(&self) -> clap::Command {
+        Args::command()
+    }
+
+    fn run(&self, args: &ArgMatches, reporter: &mut Reporter) -> i32 {
+        run(&cli::from_matches(args), reporter)
+    }
+}
+
+pub fn run(args: &Args, reporter: &mut Reporter) -> i32 {
+    let backend = args.backend;
+    let inputs = Inputs { pages: args.pages, seed: (args.init == "random").then_some(args.seed) };
+    if inputs.seed.is_some() && args.dump.is_some() {
+        eprintln!("--dump compares against the C program, whose inputs are constant; drop --init random");
+        return 1;
+    }
+    let repeat = args.common.reps.repeat(1);
+    let simd = cli::or_exit(cli::simd(args.simd));
+    let ffi = cli::or_exit(cli::ffi(args.ffi));
+    let blas = cli::or_exit(cli::blas(args.blas));
+    let strategies = Strategy::all(simd, args.block, args.transpose, args.algo == "strassen", blas);
+    if strategies.contains(&Strategy::Strassen) && backend != Backend::Rayon {
+        eprintln!("--algo strassen forks with rayon::join; drop --backend");
+        return 1;
+    }
+    if args.sockets {
+        if backend != Backend::Rayon {
+            eprintln!("--sockets runs on per-socket Rayon pools; drop --backend");
+            return 1;
+        }
+        if ffi {
+            eprintln!("--ffi times the cells of the study or of --size, not the socket study");
+            return 1;
+        }
+        run_socket_study(&args.sizes, simd, inputs, repeat, reporter);
+        return 0;
+    }
+
+    // single configurations, else the whole study
+    let Some(n) = args.size else {
+        let mut thread_counts = args.threads.clone();
+        if thread_counts[0] != 1 {
+            thread_counts.insert(0, 1);
+        }
+        run_scalability_study(backend, &strategies, ffi, inputs, &args.sizes, &thread_counts, repeat, reporter);
+        return 0;
+    };
+    for &threads in &args.threads {
+        for &strategy in &strategies {
+            if interrupt::requested() {
+                return 0;
+            }
+            if reporter.is_text() {
+                println!("Running single benchmark: n={}, threads={}, backend={}, strategy={}, pages={}, init={}", n, threads, backend.name(), strategy.name(), inputs.pages.name(), inputs.init());
+            }
+            let (_seq_time, par_time, allocs) = run_benchmark(backend, n, threads, strategy, inputs, repeat, args.dump.as_deref());
+
+            if reporter.is_text() {
+                if threads == 1 {
+                    println!("Time: {:.6}s, GFLOPS: {:.2}{}", par_time.min, gflops(n, par_time.min), par_time.summary());
+                } else {
+                    println!("Parallel time: {:.6}s, GFLOPS: {:.2}{}", par_time.min, gflops(n, par_time.min), par_time.summary());
+                }
+            }
+
+            reporter.record(
+                &Record::new("matmul", strategy.implementation(backend))
+                    .param("n", n)
+                    .param("T", threads)
+                    .param("simd", strategy.simd() as u8)
+                    .param("block", strategy.block())
+                    .param("transpose", strategy.transpose() as u8)
+                    .param("algo", strategy.algo())
+                    .param("pages", inputs.pages.name())
+                    .param("init", inputs.init())
+                    .param("seed", inputs.seed())
+                    .timing("time", &par_time, "sec")
+                    .metric("gflops", gflops(n, par_time.min), "GFLOP/s")
+                    .allocs(allocs)
+                    .energy(par_time.energy)
+                    .peak_rss(par_time.peak_rss),
+            );
+            if ffi && strategy == Strategy::Naive {
+                run_openmp(n, threads, inputs, repeat, reporter);
+            }
+        }
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    // this module's enum by name, proptest's trait for its methods
+    use super::Strategy;
+    use proptest::strategy::Strategy as _;
+
+    // n and two n x n matrices, row-major, with entries in [-10, 10)
+    fn matrices() -> impl proptest::strategy::Strategy<Value = (usize, Vec<f64>, Vec<f64>)> {
+        (1usize..24).prop_flat_map(|n| {
+            let matrix = prop::collection::vec(-10.0f64..10.0, n * n);
+            (Just(n), matrix.clone(), matrix)
+        })
+    }
+
+    // every cell builds and drops its own pool, so thread counts and
+    // strategies follow each other in one process
+    #[test]
+    fn test_thread_sweep() {
+        let strategies = Strategy::all(cfg!(feature = "simd"), 8, true, true, false);
+        for threads in [1, 2, 3, 1] {
+            for &strategy in &strategies {
+                let (_, time, _) = run_benchmark(Backend::Rayon, 32, threads, strategy, Inputs { pages: Pages::Default, seed: None }, Repeat::ONCE, None);
+                assert!(time.min > 0.0, "{} at T={}", strategy.name(), threads);
+            }
+        }
+    }
+
+    #[test]
+    fn test_verify_checksum() {
+        let n = 40;
+        let (a, b) = Inputs { pages: Pages::Default, seed: Some(7) }.matrices(n);
+        assert!(a.as_slice().iter().all(|v| (-1.0..1.0).contains(v)));
+        assert_ne!(a.as_slice(), b.as_slice());
+        let mut c = matrix_multiply_sequential(&a, &b, n);
+        assert!(verify_checksum(&a, &b, &c, n));
+        assert!(verify_checksum(&a, &b, &matrix_multiply_strassen(&Pool::new(Backend::Rayon, 2), &a, &b, n, 4), n));
+        c[17][3] += 1e-6;
+        assert!(!verify_checksum(&a, &b, &c, n));
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(48))]
+
+        #[test]
+        fn prop_parallel_matches_sequential(
+            (n, a, b) in matrices(),
+            backend in prop::sample::select(Backend::ALL.to_vec()),
+            threads in 1usize..9,
+            simd in any::<bool>(),
+            block in 1usize..9,
+        ) {
+            let (a, b) = (Matrix::from_slice(n, &a, Pages::Default), Matrix::from_slice(n, &b, Pages::Default));
+            let simd = simd && cfg!(feature = "simd");
+            let pool = Pool::new(backend, threads);
+            let expected = matrix_multiply_sequential(&a, &b, n);
+
+            prop_assert!(verify_results(&expected, &matrix_multiply_parallel(&pool, &a, &b, n, simd), n));
+            // same k order per entry: bit-identical, not just within epsilon
+            let tiled = matrix_multiply_tiled(&pool, &a, &b, n, block);
+            prop_assert_eq!(tiled.as_slice(), expected.as_slice());
+            let transposed = matrix_multiply_transposed(&pool, &a, &b, n);
+            prop_assert_eq!(transposed.as_slice(), expected.as_slice());
+            // a cutoff below n so that even orders recurse
+            let strassen = matrix_multiply_strassen(&Pool::new(Backend::Rayon, threads), &a, &b, n, block);
+            prop_assert!(verify_results(&expected, &strassen, n));
         }
-    } else {
-        // run all
-        run_scalability_study();
     }
 }
@@ -0,0 +1,11 @@
+// Kernels hosted in the library (see kernels.rs)
+
+pub mod first_touch;
+pub mod jacobi;
+pub mod matrix_multiply;
+pub mod monte_carlo;
+pub mod nbody;
+pub mod reduction;
+pub mod sort;
+pub mod stream;
+pub mod vector_ops;
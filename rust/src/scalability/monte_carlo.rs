@@ -0,0 +1,201 @@
+// Monte Carlo pi benchmark for scalability (Rust version)
+// The embarrassingly parallel end of the study, and so the upper bound of
+// the speedup the machine allows: N points thrown into the unit square, pi
+// estimated as 4 times the fraction inside the quarter circle. The samples
+// are split into T chunks, one task per thread on a Rayon pool, each with a
+// generator of its own (SplitMix64 positioned at its chunk, so no state is
+// shared), and the T hit counts are added up after the join: no memory
+// traffic, no synchronization but the final reduction. Point i is values
+// 2i and 2i + 1 of the stream --seed, their top 53 bits scaled to [0, 1),
+// so the hits do not depend on T and must equal those of the sequential
+// loop, timed too as the baseline of speedup; mp_monte_carlo throws the
+// same points.
+//
+// Usage:
+//   ./monte_carlo [--size N] [--threads T] [options]
+//   --size N           samples (default 1000000000)
+//   --threads T        number of threads (default 8; a count or max, cores, half, 3/4)
+//
+// Options:
+//   --seed S           seed of the points (default 20240606)
+//   --format FMT       text (default, CSV lines below) | jsonl | json | csv | markdown
+//   --upload URL       POST the result as JSON to an HTTP collector
+//   --prometheus FILE  keep FILE updated with Prometheus gauges for each result
+//   --store FILE       append the run to a SQLite database (--features store)
+//   --log FMT          pretty | json execution log on stderr (RUST_LOG filters)
+//   --reps R           timed runs, the best is reported (default 1); R > 1 adds
+//                      time_mean, time_median and time_stddev
+//   --warmup W         untimed runs before them (default 1; 0 for a cold start)
+//   --describe         print the JSON description of arguments and metrics, then exit
+//
+// Output (CSV-style):
+//   monte_carlo,rayon,N=1000000000,T=8,seed=20240606,time,0.123456,sec
+//   monte_carlo,rayon,N=1000000000,T=8,seed=20240606,rate,8100000000,samples/s
+//   monte_carlo,rayon,N=1000000000,T=8,seed=20240606,speedup,7.95,x
+//   monte_carlo,rayon,N=1000000000,T=8,seed=20240606,pi,3.141592653,estimate
+//   monte_carlo,rayon,N=1000000000,T=8,seed=20240606,correct,1,boolean
+
+use crate::alloc_track::Region;
+use crate::cli;
+use crate::describe::{Description, Param};
+use crate::kernels::{Benchmark, Suite};
+use crate::logging::Phase;
+use crate::results::{Record, Reporter};
+use crate::rng::SplitMix64;
+use crate::threads;
+use clap::{ArgMatches, CommandFactory, Parser};
+use rayon::prelude::*;
+use std::time::Instant;
+use tracing::info_span;
+
+// defaults of --size and --threads
+const N: usize = 1_000_000_000; // 10^9
+const THREADS: usize = 8;
+
+// seed of the points unless --seed is given
+const POINT_SEED: u64 = 20240606;
+
+/// Hits among points start..end of the stream `seed`: point i is values 2i
+/// and 2i + 1, drawn by a generator of the caller's own.
+pub fn count_hits(seed: u64, start: usize, end: usize) -> u64 {
+    let mut rng = SplitMix64::at_index(seed, 2 * start as u64);
+    let mut hits = 0;
+    for _ in start..end {
+        let x = (rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        let y = (rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        hits += (x * x + y * y < 1.0) as u64;
+    }
+    hits
+}
+
+/// Hits among the first n points on the calling thread.
+pub fn monte_carlo_sequential(n: usize, seed: u64) -> u64 {
+    count_hits(seed, 0, n)
+}
+
+/// Hits among the first n points on the current Rayon pool: one chunk of
+/// n / threads per task, the counts added up after the join.
+pub fn monte_carlo_parallel(n: usize, seed: u64, threads: usize) -> u64 {
+    let chunk = n.div_ceil(threads).max(1);
+    (0..n.div_ceil(chunk)).into_par_iter().map(|c| count_hits(seed, c * chunk, ((c + 1) * chunk).min(n))).sum()
+}
+
+// answer to --describe
+pub fn description() -> Description {
+    Description::new("monte_carlo", "monte_carlo", "Monte Carlo pi: N independent samples, per-thread generators and one reduction")
+        .option(Param::integer("--size", 1, "samples").default(N))
+        .option(Param::threads("--threads", "threads").default(THREADS))
+        .option(Param::integer("--seed", 0, "seed of the points").default(POINT_SEED))
+        .params(&["N", "T", "seed"])
+        .timing("time", "sec")
+        .metric("rate", "samples/s")
+        .metric("speedup", "x")
+        .metric("pi", "estimate")
+        .metric("correct", "boolean")
+}
+
+/// Monte Carlo pi: N independent samples, per-thread generators and one reduction
+#[derive(Debug, Clone, Parser)]
+#[command(name = "monte_carlo")]
+pub struct Args {
+    /// samples
+    #[arg(long, value_name = "N", value_parser = cli::positive, default_value_t = N)]
+    pub size: usize,
+    /// threads (a count or max, cores, half, quarter, a/b)
+    #[arg(long, value_name = "T", value_parser = threads::parse_spec, default_value_t = THREADS)]
+    pub threads: usize,
+    /// seed of the points
+    #[arg(long, value_name = "S", default_value_t = POINT_SEED)]
+    pub seed: u64,
+    #[command(flatten)]
+    pub common: cli::Common,
+}
+
+pub struct MonteCarlo;
+
+impl Benchmark for MonteCarlo {
+    fn name(&self) -> &'static str {
+        "monte_carlo"
+    }
+
+    fn label(&self) -> &'static str {
+        "Monte Carlo pi"
+    }
+
+    fn suite(&self) -> Suite {
+        Suite::Scalability
+    }
+
+    fn description(&self) -> Description {
+        description()
+    }
+
+    fn command(&self) -> clap::Command {
+        Args::command()
+    }
+
+    fn run(&self, args: &ArgMatches, reporter: &mut Reporter) -> i32 {
+        run(&cli::from_matches(args), reporter)
+    }
+}
+
+pub fn run(args: &Args, reporter: &mut Reporter) -> i32 {
+    let Args { size: n, threads: t, seed, .. } = *args;
+    let repeat = args.common.reps.repeat(1);
+
+    let _bench = info_span!("monte_carlo", n, threads = t, seed).entered();
+    let pool = Phase::Setup.run(|| rayon::ThreadPoolBuilder::new().num_threads(t).build().unwrap());
+
+    let (seq_time, expected, _) = repeat.measure(|| monte_carlo_sequential(n, seed));
+    let (elapsed, (hits, allocs)) = repeat.run(|| {
+        let region = Region::start();
+        let start = Instant::now();
+        let hits = pool.install(|| monte_carlo_parallel(n, seed, t));
+        (start.elapsed().as_secs_f64(), (hits, region.finish()))
+    });
+
+    // the points do not depend on the chunking
+    let correct = Phase::Verify.run(|| hits == expected);
+    let record = Record::new("monte_carlo", "rayon")
+        .param("N", n)
+        .param("T", t)
+        .param("seed", seed)
+        .timing("time", &elapsed, "sec")
+        .metric("rate", n as f64 / elapsed.min, "samples/s")
+        .metric("speedup", seq_time.min / elapsed.min, "x")
+        .metric("pi", 4.0 * hits as f64 / n as f64, "estimate")
+        .metric("correct", if correct { 1.0 } else { 0.0 }, "boolean")
+        .allocs(allocs)
+        .energy(elapsed.energy)
+        .peak_rss(elapsed.peak_rss);
+    if reporter.is_text() {
+        print!("{}", record.to_csv());
+    }
+    reporter.record(&record);
+
+    if correct {
+        0
+    } else {
+        3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monte_carlo() {
+        let n = 1_000_003;
+        let hits = monte_carlo_sequential(n, POINT_SEED);
+        // within 5 standard deviations (about 0.0082 of pi at 10^6 samples)
+        assert!((4.0 * hits as f64 / n as f64 - std::f64::consts::PI).abs() < 0.0082, "{}", hits);
+        assert_eq!(count_hits(POINT_SEED, 0, 1000) + count_hits(POINT_SEED, 1000, n), hits);
+
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(3).build().unwrap();
+        for threads in [1, 3, 7] {
+            assert_eq!(pool.install(|| monte_carlo_parallel(n, POINT_SEED, threads)), hits, "{}", threads);
+        }
+        assert_eq!(monte_carlo_parallel(2, POINT_SEED, 8), monte_carlo_sequential(2, POINT_SEED));
+    }
+}
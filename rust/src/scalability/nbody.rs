@@ -0,0 +1,267 @@
+// All-pairs N-body benchmark for scalability (Rust version)
+// The classic OpenMP showcase after matrix multiply: N bodies under their
+// mutual gravity, every step computing each body's acceleration from all N
+// bodies (O(N^2), softened by EPS^2 so no pair diverges), then moving them:
+// v += a dt, x += v dt (symplectic Euler). The acceleration pass is a
+// parallel loop over the bodies on a Rayon pool of T threads, the update a
+// second one, and the positions, 24 N bytes, stay in cache for the whole
+// pass, so the kernel is compute-bound and should scale almost linearly.
+// The bodies start at rest, uniform in [-1, 1)^3 from the SplitMix64 stream
+// BODY_SEED (coordinate k of body i is value 3i + k), each of mass 1 / N,
+// as mp_nbody places them. Every body sums its pairs in index order, so the
+// parallel run matches the sequential one, timed too as the baseline of
+// speedup, bit for bit.
+//
+// Usage:
+//   ./nbody [--size N] [--threads T] [options]
+//   --size N           bodies (default 4096)
+//   --threads T        number of threads (default 8; a count or max, cores, half, 3/4)
+//
+// Options:
+//   --steps S          time steps (default 10)
+//   --format FMT       text (default, CSV lines below) | jsonl | json | csv | markdown
+//   --upload URL       POST the result as JSON to an HTTP collector
+//   --prometheus FILE  keep FILE updated with Prometheus gauges for each result
+//   --store FILE       append the run to a SQLite database (--features store)
+//   --log FMT          pretty | json execution log on stderr (RUST_LOG filters)
+//   --reps R           timed simulations, the best is reported (default 1);
+//                      R > 1 adds time_mean, time_median and time_stddev
+//   --warmup W         untimed simulations before them (default 1; 0 for a cold start)
+//   --describe         print the JSON description of arguments and metrics, then exit
+//
+// Output (CSV-style):
+//   nbody,rayon,N=16384,steps=10,T=8,time,0.123456,sec
+//   nbody,rayon,N=16384,steps=10,T=8,per,12.345600,ms
+//   nbody,rayon,N=16384,steps=10,T=8,rate,21743486000,interactions/s
+//   nbody,rayon,N=16384,steps=10,T=8,speedup,7.60,x
+//   nbody,rayon,N=16384,steps=10,T=8,correct,1,boolean
+
+use crate::alloc_track::Region;
+use crate::cli;
+use crate::describe::{Description, Param};
+use crate::kernels::{Benchmark, Suite};
+use crate::logging::Phase;
+use crate::results::{Record, Reporter};
+use crate::rng;
+use crate::threads;
+use clap::{ArgMatches, CommandFactory, Parser};
+use rayon::prelude::*;
+use std::time::Instant;
+use tracing::info_span;
+
+// defaults of --size and --threads
+const N: usize = 4096;
+const THREADS: usize = 8;
+
+/// Time step.
+pub const DT: f64 = 0.01;
+
+/// Squared softening length, added to every squared distance.
+pub const EPS2: f64 = 0.01;
+
+/// Seed of the initial positions.
+pub const BODY_SEED: u64 = 20240605;
+
+// time steps unless --steps is given
+const STEPS: usize = 10;
+
+/// Positions, velocities and masses of N bodies.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bodies {
+    pub pos: Vec<[f64; 3]>,
+    pub vel: Vec<[f64; 3]>,
+    pub mass: Vec<f64>,
+}
+
+impl Bodies {
+    /// N bodies at rest, uniform in [-1, 1)^3, each of mass 1 / N.
+    pub fn new(n: usize) -> Bodies {
+        let mut coords = vec![0.0; 3 * n];
+        rng::fill(&mut coords, BODY_SEED, |r| (r >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0);
+        let pos = coords.chunks_exact(3).map(|c| [c[0], c[1], c[2]]).collect();
+        Bodies { pos, vel: vec![[0.0; 3]; n], mass: vec![1.0 / n as f64; n] }
+    }
+}
+
+// the acceleration of body i from all the bodies, pairs in index order
+fn acceleration(pos: &[[f64; 3]], mass: &[f64], i: usize) -> [f64; 3] {
+    let [xi, yi, zi] = pos[i];
+    let mut a = [0.0; 3];
+    for (p, &m) in pos.iter().zip(mass) {
+        let (dx, dy, dz) = (p[0] - xi, p[1] - yi, p[2] - zi);
+        let r2 = dx * dx + dy * dy + dz * dz + EPS2;
+        let s = m / (r2 * r2.sqrt());
+        a[0] += dx * s;
+        a[1] += dy * s;
+        a[2] += dz * s;
+    }
+    a
+}
+
+// a body's velocity after a time step at acceleration a
+fn kick(v: &mut [f64; 3], a: [f64; 3]) {
+    v.iter_mut().zip(a).for_each(|(v, a)| *v += a * DT);
+}
+
+// a body's position after a time step at velocity v
+fn drift(p: &mut [f64; 3], v: &[f64; 3]) {
+    p.iter_mut().zip(v).for_each(|(p, v)| *p += v * DT);
+}
+
+/// `steps` time steps on the calling thread.
+pub fn nbody_sequential(bodies: &mut Bodies, steps: usize) {
+    let Bodies { pos, vel, mass } = bodies;
+    for _ in 0..steps {
+        for (i, v) in vel.iter_mut().enumerate() {
+            kick(v, acceleration(pos, mass, i));
+        }
+        pos.iter_mut().zip(vel.iter()).for_each(|(p, v)| drift(p, v));
+    }
+}
+
+/// `steps` time steps on the current Rayon pool: each step one parallel
+/// loop over the bodies for the accelerations and one for the positions.
+pub fn nbody_parallel(bodies: &mut Bodies, steps: usize) {
+    let Bodies { pos, vel, mass } = bodies;
+    for _ in 0..steps {
+        vel.par_iter_mut().enumerate().for_each(|(i, v)| kick(v, acceleration(pos, mass, i)));
+        pos.par_iter_mut().zip(vel.par_iter()).for_each(|(p, v)| drift(p, v));
+    }
+}
+
+// answer to --describe
+pub fn description() -> Description {
+    Description::new("nbody", "nbody", "all-pairs N-body steps: O(N^2) gravity per step, compute-bound")
+        .option(Param::integer("--size", 1, "bodies").default(N))
+        .option(Param::threads("--threads", "threads").default(THREADS))
+        .option(Param::integer("--steps", 1, "time steps").default(STEPS))
+        .params(&["N", "steps", "T"])
+        .timing("time", "sec")
+        .timing("per", "ms")
+        .metric("rate", "interactions/s")
+        .metric("speedup", "x")
+        .metric("correct", "boolean")
+}
+
+/// All-pairs N-body steps: O(N^2) gravity per step, compute-bound
+#[derive(Debug, Clone, Parser)]
+#[command(name = "nbody")]
+pub struct Args {
+    /// bodies
+    #[arg(long, value_name = "N", value_parser = cli::positive, default_value_t = N)]
+    pub size: usize,
+    /// threads (a count or max, cores, half, quarter, a/b)
+    #[arg(long, value_name = "T", value_parser = threads::parse_spec, default_value_t = THREADS)]
+    pub threads: usize,
+    /// time steps
+    #[arg(long, value_name = "S", value_parser = cli::positive, default_value_t = STEPS)]
+    pub steps: usize,
+    #[command(flatten)]
+    pub common: cli::Common,
+}
+
+pub struct NBody;
+
+impl Benchmark for NBody {
+    fn name(&self) -> &'static str {
+        "nbody"
+    }
+
+    fn label(&self) -> &'static str {
+        "N-body"
+    }
+
+    fn suite(&self) -> Suite {
+        Suite::Scalability
+    }
+
+    fn description(&self) -> Description {
+        description()
+    }
+
+    fn command(&self) -> clap::Command {
+        Args::command()
+    }
+
+    fn run(&self, args: &ArgMatches, reporter: &mut Reporter) -> i32 {
+        run(&cli::from_matches(args), reporter)
+    }
+}
+
+pub fn run(args: &Args, reporter: &mut Reporter) -> i32 {
+    let Args { size: n, threads: t, steps, .. } = *args;
+    let repeat = args.common.reps.repeat(1);
+
+    let _bench = info_span!("nbody", n, threads = t, steps).entered();
+    let (pool, initial) = Phase::Setup.run(|| {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(t).build().unwrap();
+        let initial = pool.install(|| Bodies::new(n));
+        (pool, initial)
+    });
+
+    // every simulation starts from a fresh copy, made outside the timed region
+    let (seq_time, expected) = repeat.run(|| {
+        let mut bodies = initial.clone();
+        let start = Instant::now();
+        nbody_sequential(&mut bodies, steps);
+        (start.elapsed().as_secs_f64(), bodies)
+    });
+    let (elapsed, (bodies, allocs)) = repeat.run(|| {
+        let mut bodies = initial.clone();
+        let region = Region::start();
+        let start = Instant::now();
+        pool.install(|| nbody_parallel(&mut bodies, steps));
+        (start.elapsed().as_secs_f64(), (bodies, region.finish()))
+    });
+
+    let correct = Phase::Verify.run(|| bodies == expected);
+    let interactions = n as f64 * n as f64 * steps as f64;
+    let record = Record::new("nbody", "rayon")
+        .param("N", n)
+        .param("steps", steps)
+        .param("T", t)
+        .timing("time", &elapsed, "sec")
+        .timing("per", &elapsed.scaled(1e3 / steps as f64), "ms")
+        .metric("rate", interactions / elapsed.min, "interactions/s")
+        .metric("speedup", seq_time.min / elapsed.min, "x")
+        .metric("correct", if correct { 1.0 } else { 0.0 }, "boolean")
+        .allocs(allocs)
+        .energy(elapsed.energy)
+        .peak_rss(elapsed.peak_rss);
+    if reporter.is_text() {
+        print!("{}", record.to_csv());
+    }
+    reporter.record(&record);
+
+    if correct {
+        0
+    } else {
+        3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nbody() {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(3).build().unwrap();
+        let initial = Bodies::new(101);
+        assert!(initial.pos.iter().flatten().all(|c| (-1.0..1.0).contains(c)));
+
+        let mut expected = initial.clone();
+        nbody_sequential(&mut expected, 5);
+        let mut bodies = initial.clone();
+        pool.install(|| nbody_parallel(&mut bodies, 5));
+        assert_eq!(bodies, expected);
+
+        // gravity pulls the cloud together, and momentum (zero) is conserved
+        let spread = |b: &Bodies| b.pos.iter().map(|p| p.iter().map(|c| c * c).sum::<f64>()).sum::<f64>();
+        assert!(spread(&expected) < spread(&initial));
+        for k in 0..3 {
+            let momentum: f64 = expected.vel.iter().zip(&expected.mass).map(|(v, m)| v[k] * m).sum();
+            assert!(momentum.abs() < 1e-12, "{}", momentum);
+        }
+    }
+}
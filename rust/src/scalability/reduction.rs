@@ -0,0 +1,269 @@
+// Reduction benchmark for scalability (Rust version)
+// The most common OpenMP idiom: N doubles summed (--op sum), or the dot
+// product of two vectors of N (--op dot), reduced four ways on a Rayon pool
+// of T threads:
+//   sequential  one loop on the calling thread
+//   builtin     the runtime's own reduction: par_iter().sum(), as
+//               `reduction(+:sum)` on a parallel for
+//   chunked     by hand: T chunks of N / T, a partial per task written to
+//               its slot, the T partials added up after the join (a private
+//               partial per thread and a partials array in OpenMP)
+//   atomic      every element added to one shared f64 by a compare-and-swap
+//               loop, as `#pragma omp atomic` on a shared sum
+// Element i of x is (i mod 1000) / 1024 and of y (i mod 7 + 1) / 4, so every
+// partial sum is exact and all four orders agree bit for bit, with each
+// other and with mp_reduction. Each run also times the sequential loop,
+// the baseline of speedup.
+//
+// Usage:
+//   ./reduction [--strategy S] [--size N] [--threads T] [options]
+//   --strategy S       sequential | builtin (default) | chunked | atomic
+//   --size N           elements (default 100000000)
+//   --threads T        number of threads (default 8; a count or max, cores, half, 3/4)
+//
+// Options:
+//   --op OP            sum (default) | dot
+//   --format FMT       text (default, CSV lines below) | jsonl | json | csv | markdown
+//   --upload URL       POST the result as JSON to an HTTP collector
+//   --prometheus FILE  keep FILE updated with Prometheus gauges for each result
+//   --store FILE       append the run to a SQLite database (--features store)
+//   --log FMT          pretty | json execution log on stderr (RUST_LOG filters)
+//   --reps R           timed runs, the best is reported (default 1); R > 1 adds
+//                      time_mean, time_median and time_stddev
+//   --warmup W         untimed runs before them (default 1; 0 for a cold start)
+//   --describe         print the JSON description of arguments and metrics, then exit
+//
+// Output (CSV-style):
+//   reduction,rayon,op=sum,strategy=builtin,N=100000000,T=8,time,0.012345,sec
+//   reduction,rayon,op=sum,strategy=builtin,N=100000000,T=8,speedup,6.10,x
+//   reduction,rayon,op=sum,strategy=builtin,N=100000000,T=8,correct,1,boolean
+
+use crate::alloc_track::Region;
+use crate::cli;
+use crate::describe::{Description, Param};
+use crate::kernels::{Benchmark, Suite};
+use crate::logging::Phase;
+use crate::results::{Record, Reporter};
+use crate::threads;
+use clap::{ArgMatches, CommandFactory, Parser};
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+use tracing::info_span;
+
+// defaults of --size and --threads
+const N: usize = 100_000_000; // 10^8
+const THREADS: usize = 8;
+
+/// The reductions, in their --strategy names.
+pub const STRATEGIES: [&str; 4] = ["sequential", "builtin", "chunked", "atomic"];
+
+/// Element i of x.
+pub fn x(i: usize) -> f64 {
+    (i % 1000) as f64 / 1024.0
+}
+
+/// Element i of y.
+pub fn y(i: usize) -> f64 {
+    (i % 7 + 1) as f64 / 4.0
+}
+
+/// The inputs of `op`: x, and y for dot (empty for sum).
+pub fn inputs(op: &str, n: usize) -> (Vec<f64>, Vec<f64>) {
+    let xs = (0..n).into_par_iter().map(x).collect();
+    let ys = if op == "dot" { (0..n).into_par_iter().map(y).collect() } else { Vec::new() };
+    (xs, ys)
+}
+
+// term i of the reduction: x_i for sum (ys empty), x_i * y_i for dot
+fn term(xs: &[f64], ys: &[f64], i: usize) -> f64 {
+    if ys.is_empty() { xs[i] } else { xs[i] * ys[i] }
+}
+
+// the terms of xs (and ys), added up in order
+fn partial(xs: &[f64], ys: &[f64]) -> f64 {
+    if ys.is_empty() {
+        xs.iter().sum()
+    } else {
+        xs.iter().zip(ys).map(|(a, b)| a * b).sum()
+    }
+}
+
+pub fn reduce_sequential(xs: &[f64], ys: &[f64]) -> f64 {
+    partial(xs, ys)
+}
+
+/// par_iter().sum() on the current Rayon pool.
+pub fn reduce_builtin(xs: &[f64], ys: &[f64]) -> f64 {
+    if ys.is_empty() {
+        xs.par_iter().sum()
+    } else {
+        xs.par_iter().zip(ys).map(|(a, b)| a * b).sum()
+    }
+}
+
+/// One task per chunk of `threads`, each writing its partial to a slot of
+/// its own; the partials are added after the scope joins.
+pub fn reduce_chunked(pool: &rayon::ThreadPool, xs: &[f64], ys: &[f64], threads: usize) -> f64 {
+    let chunk = xs.len().div_ceil(threads).max(1);
+    let mut partials = vec![0.0; xs.len().div_ceil(chunk)];
+    pool.scope(|s| {
+        for (c, slot) in partials.iter_mut().enumerate() {
+            let range = c * chunk..((c + 1) * chunk).min(xs.len());
+            let (xc, yc) = (&xs[range.clone()], if ys.is_empty() { ys } else { &ys[range] });
+            s.spawn(move |_| *slot = partial(xc, yc));
+        }
+    });
+    partials.iter().sum()
+}
+
+/// Every term added to one shared f64 (its bits in an AtomicU64) by a
+/// compare-and-swap loop, from one chunk of the elements per thread.
+pub fn reduce_atomic(pool: &rayon::ThreadPool, xs: &[f64], ys: &[f64], threads: usize) -> f64 {
+    let sum = AtomicU64::new(0.0f64.to_bits());
+    let chunk = xs.len().div_ceil(threads).max(1);
+    pool.scope(|s| {
+        for start in (0..xs.len()).step_by(chunk) {
+            let sum = &sum;
+            s.spawn(move |_| {
+                for i in start..(start + chunk).min(xs.len()) {
+                    let t = term(xs, ys, i);
+                    let _ = sum.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| Some((f64::from_bits(bits) + t).to_bits()));
+                }
+            });
+        }
+    });
+    f64::from_bits(sum.into_inner())
+}
+
+// answer to --describe
+pub fn description() -> Description {
+    Description::new("reduction", "reduction", "sum or dot product of N doubles: sequential, builtin, chunked or atomic reduction")
+        .option(Param::choice("--strategy", &STRATEGIES, "reduction").default("builtin"))
+        .option(Param::integer("--size", 1, "elements").default(N))
+        .option(Param::threads("--threads", "threads").default(THREADS))
+        .option(Param::choice("--op", &["sum", "dot"], "the sum of x, or the dot product of x and y").default("sum"))
+        .params(&["op", "strategy", "N", "T"])
+        .timing("time", "sec")
+        .metric("speedup", "x")
+        .metric("correct", "boolean")
+}
+
+/// Sum or dot product of N doubles: sequential, builtin, chunked or atomic reduction
+#[derive(Debug, Clone, Parser)]
+#[command(name = "reduction")]
+pub struct Args {
+    /// sequential | builtin (par_iter().sum()) | chunked (partials by hand) | atomic (CAS per element)
+    #[arg(long, value_name = "S", value_parser = STRATEGIES, default_value = "builtin")]
+    pub strategy: String,
+    /// elements
+    #[arg(long, value_name = "N", value_parser = cli::positive, default_value_t = N)]
+    pub size: usize,
+    /// threads (a count or max, cores, half, quarter, a/b)
+    #[arg(long, value_name = "T", value_parser = threads::parse_spec, default_value_t = THREADS)]
+    pub threads: usize,
+    /// sum (of x) | dot (product of x and y)
+    #[arg(long, value_name = "OP", value_parser = ["sum", "dot"], default_value = "sum")]
+    pub op: String,
+    #[command(flatten)]
+    pub common: cli::Common,
+}
+
+pub struct Reduction;
+
+impl Benchmark for Reduction {
+    fn name(&self) -> &'static str {
+        "reduction"
+    }
+
+    fn label(&self) -> &'static str {
+        "Reduction"
+    }
+
+    fn suite(&self) -> Suite {
+        Suite::Scalability
+    }
+
+    fn description(&self) -> Description {
+        description()
+    }
+
+    fn command(&self) -> clap::Command {
+        Args::command()
+    }
+
+    fn run(&self, args: &ArgMatches, reporter: &mut Reporter) -> i32 {
+        run(&cli::from_matches(args), reporter)
+    }
+}
+
+pub fn run(args: &Args, reporter: &mut Reporter) -> i32 {
+    let Args { size: n, threads: t, .. } = *args;
+    let (strategy, op) = (args.strategy.as_str(), args.op.as_str());
+
+    let _bench = info_span!("reduction", %op, %strategy, n, threads = t).entered();
+    let (pool, (xs, ys)) = Phase::Setup.run(|| {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(t).build().unwrap();
+        let inputs = pool.install(|| inputs(op, n));
+        (pool, inputs)
+    });
+    let repeat = args.common.reps.repeat(1);
+
+    let (seq_time, expected, _) = repeat.measure(|| reduce_sequential(&xs, &ys));
+    let (elapsed, (total, allocs)) = repeat.run(|| {
+        let region = Region::start();
+        let start = Instant::now();
+        let total = match strategy {
+            "sequential" => reduce_sequential(&xs, &ys),
+            "builtin" => pool.install(|| reduce_builtin(&xs, &ys)),
+            "chunked" => reduce_chunked(&pool, &xs, &ys, t),
+            _ => reduce_atomic(&pool, &xs, &ys, t),
+        };
+        (start.elapsed().as_secs_f64(), (total, region.finish()))
+    });
+
+    // every partial is exact, so any order gives the sequential total
+    let correct = Phase::Verify.run(|| total == expected);
+    let record = Record::new("reduction", "rayon")
+        .param("op", op)
+        .param("strategy", strategy)
+        .param("N", n)
+        .param("T", t)
+        .timing("time", &elapsed, "sec")
+        .metric("speedup", seq_time.min / elapsed.min, "x")
+        .metric("correct", if correct { 1.0 } else { 0.0 }, "boolean")
+        .allocs(allocs)
+        .energy(elapsed.energy)
+        .peak_rss(elapsed.peak_rss);
+    if reporter.is_text() {
+        print!("{}", record.to_csv());
+    }
+    reporter.record(&record);
+
+    if correct {
+        0
+    } else {
+        3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strategies_agree() {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(3).build().unwrap();
+        // 100_003: the last chunk is short
+        for op in ["sum", "dot"] {
+            let (xs, ys) = inputs(op, 100_003);
+            let expected = reduce_sequential(&xs, &ys);
+            let closed: f64 = (0..xs.len()).map(|i| if op == "dot" { x(i) * y(i) } else { x(i) }).rev().sum();
+            assert_eq!(expected, closed, "{}", op);
+            assert_eq!(pool.install(|| reduce_builtin(&xs, &ys)), expected, "{}", op);
+            assert_eq!(reduce_chunked(&pool, &xs, &ys, 3), expected, "{}", op);
+            assert_eq!(reduce_atomic(&pool, &xs, &ys, 3), expected, "{}", op);
+        }
+        assert_eq!(reduce_chunked(&pool, &[1.0], &[], 4), 1.0);
+    }
+}
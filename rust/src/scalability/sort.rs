@@ -0,0 +1,515 @@
+// Parallel sort benchmark for scalability (Rust version)
+// Sorting is the divide-and-conquer workload of the OpenMP task suites
+// (BOTS sort, cilksort): N random u64 or f64 keys sorted on a Rayon pool of
+// T threads, by
+//   merge              a merge sort written with rayon::join: both halves
+//                      sorted as two tasks, then merged by a parallel merge
+//                      that splits the larger run at its median and the
+//                      smaller at the same key, also as two tasks; runs of at
+//                      most --cutoff elements are sorted (slice::sort) or
+//                      merged on one thread. The halves alternate between the
+//                      array and one scratch buffer, so nothing is copied back
+//   sample             a sample sort: --buckets - 1 splitters picked from
+//                      --oversample evenly spaced keys per bucket, each
+//                      thread's chunk dealt into buckets, then every bucket
+//                      copied to its place and sorted (sort_unstable) as a
+//                      task of its own; how evenly the data fills the buckets
+//                      decides the balance, reported as imbalance (largest
+//                      bucket over the mean)
+//   par_sort           rayon's stable par_sort
+//   par_sort_unstable  rayon's par_sort_unstable (a parallel pdqsort)
+// Key i is value i of the SplitMix64 stream --seed: the raw u64, or its top
+// 53 bits scaled to [0, 1) for f64 (compared by total_cmp), as mp_sort draws
+// them. The baseline of speedup is the same merge sort on the calling
+// thread, timed in every run, and every algorithm must reproduce its output.
+//
+// Usage:
+//   ./sort [--size N] [--threads T] [options]
+//   --size N           keys (default 100000000)
+//   --threads T        number of threads (default 8; a count or max, cores, half, 3/4)
+//
+// Options:
+//   --type K           u64 (default) | f64
+//   --algo A           all (default) | merge | sample | par_sort | par_sort_unstable
+//   --cutoff C         merge and the baseline: largest run sorted or merged
+//                      on one thread (default 4096)
+//   --oversample S     sample: keys sampled per bucket (default 32)
+//   --buckets B        sample: buckets (default 256)
+//   --seed S           seed of the keys (default 20240604)
+//   --format FMT       text (default, CSV lines below) | jsonl | json | csv | markdown
+//   --upload URL       POST the result as JSON to an HTTP collector
+//   --prometheus FILE  keep FILE updated with Prometheus gauges for each result
+//   --store FILE       append the run to a SQLite database (--features store)
+//   --log FMT          pretty | json execution log on stderr (RUST_LOG filters)
+//   --reps R           timed sorts of each algorithm, the best is reported
+//                      (default 1); R > 1 adds time_mean, time_median and time_stddev
+//   --warmup W         untimed sorts before them (default 1; 0 for a cold start)
+//   --describe         print the JSON description of arguments and metrics, then exit
+//
+// Output (CSV-style, one record per algorithm; cutoff only for the merge
+// sort, oversample and buckets only for the sample sort):
+//   sort,rayon,type=u64,algo=merge,N=100000000,T=8,cutoff=4096,seed=20240604,time,0.123456,sec
+//   sort,rayon,type=u64,algo=merge,N=100000000,T=8,cutoff=4096,seed=20240604,speedup,6.10,x
+//   sort,rayon,type=u64,algo=merge,N=100000000,T=8,cutoff=4096,seed=20240604,correct,1,boolean
+//   sort,rayon,type=u64,algo=sample,N=100000000,T=8,oversample=32,buckets=256,seed=20240604,imbalance,1.48,x
+
+use crate::alloc_track::Region;
+use crate::cli;
+use crate::describe::{Description, Param};
+use crate::kernels::{Benchmark, Suite};
+use crate::logging::Phase;
+use crate::results::{Record, Reporter};
+use crate::rng;
+use crate::threads;
+use clap::{ArgMatches, CommandFactory, Parser};
+use rayon::prelude::*;
+use std::cmp::Ordering;
+use std::time::Instant;
+use tracing::info_span;
+
+// defaults of --size and --threads
+const N: usize = 100_000_000; // 10^8
+const THREADS: usize = 8;
+
+// defaults of --cutoff, --oversample, --buckets and --seed
+pub const CUTOFF: usize = 4096;
+const OVERSAMPLE: usize = 32;
+const BUCKETS: usize = 256;
+const KEY_SEED: u64 = 20240604;
+
+/// The sorts of --algo, in the order --algo all runs them.
+pub const ALGOS: [&str; 4] = ["merge", "sample", "par_sort", "par_sort_unstable"];
+
+/// A key type of --type.
+pub trait Key: Copy + PartialEq + Send + Sync {
+    /// The key drawn from a SplitMix64 value.
+    fn from_random(r: u64) -> Self;
+    /// The order the sorts use.
+    fn order(a: &Self, b: &Self) -> Ordering;
+}
+
+impl Key for u64 {
+    fn from_random(r: u64) -> Self {
+        r
+    }
+
+    fn order(a: &Self, b: &Self) -> Ordering {
+        a.cmp(b)
+    }
+}
+
+impl Key for f64 {
+    fn from_random(r: u64) -> Self {
+        (r >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn order(a: &Self, b: &Self) -> Ordering {
+        a.total_cmp(b)
+    }
+}
+
+/// N keys: key i from value i of the SplitMix64 stream `seed`.
+pub fn gen_keys<T: Key + Default>(n: usize, seed: u64) -> Vec<T> {
+    let mut keys = vec![T::default(); n];
+    rng::fill(&mut keys, seed, T::from_random);
+    keys
+}
+
+// a and b as two tasks, or one after the other on the calling thread
+fn fork(parallel: bool, a: impl FnOnce() + Send, b: impl FnOnce() + Send) {
+    if parallel {
+        rayon::join(a, b);
+    } else {
+        a();
+        b();
+    }
+}
+
+// the sorted runs a and b merged into out on the calling thread; ties are
+// taken from a first
+fn merge_sequential<T: Key>(a: &[T], b: &[T], out: &mut [T]) {
+    let (mut i, mut j) = (0, 0);
+    for slot in out.iter_mut() {
+        if j == b.len() || (i < a.len() && T::order(&b[j], &a[i]) != Ordering::Less) {
+            *slot = a[i];
+            i += 1;
+        } else {
+            *slot = b[j];
+            j += 1;
+        }
+    }
+}
+
+// the sorted runs a and b merged into out: the larger run split at its
+// median, the smaller where that key would go, and the two halves merged as
+// two tasks; runs of at most `cutoff` (and at least 2) merge sequentially
+fn merge<T: Key>(a: &[T], b: &[T], out: &mut [T], cutoff: usize, parallel: bool) {
+    if !parallel || a.len() + b.len() <= cutoff.max(2) {
+        return merge_sequential(a, b, out);
+    }
+    // ties stay in a before b
+    let (i, j) = if a.len() >= b.len() {
+        let i = a.len() / 2;
+        (i, b.partition_point(|x| T::order(x, &a[i]) == Ordering::Less))
+    } else {
+        let j = b.len() / 2;
+        (a.partition_point(|x| T::order(x, &b[j]) != Ordering::Greater), j)
+    };
+    let (lo, hi) = out.split_at_mut(i + j);
+    fork(parallel, || merge(&a[..i], &b[..j], lo, cutoff, parallel), || merge(&a[i..], &b[j..], hi, cutoff, parallel));
+}
+
+// sorts v in place, with buf (as long) as scratch: the halves are sorted
+// into buf, then merged back into v
+fn sort_in<T: Key>(v: &mut [T], buf: &mut [T], cutoff: usize, parallel: bool) {
+    if v.len() <= cutoff {
+        return v.sort_by(T::order);
+    }
+    let mid = v.len() / 2;
+    let ((vl, vr), (bl, br)) = (v.split_at_mut(mid), buf.split_at_mut(mid));
+    fork(parallel, || sort_into(vl, bl, cutoff, parallel), || sort_into(vr, br, cutoff, parallel));
+    merge(bl, br, v, cutoff, parallel);
+}
+
+// sorts v into out, leaving v as scratch: the halves are sorted in place,
+// then merged into out
+fn sort_into<T: Key>(v: &mut [T], out: &mut [T], cutoff: usize, parallel: bool) {
+    if v.len() <= cutoff {
+        v.sort_by(T::order);
+        return out.copy_from_slice(v);
+    }
+    let mid = v.len() / 2;
+    let ((vl, vr), (ol, or)) = (v.split_at_mut(mid), out.split_at_mut(mid));
+    fork(parallel, || sort_in(vl, ol, cutoff, parallel), || sort_in(vr, or, cutoff, parallel));
+    merge(vl, vr, out, cutoff, parallel);
+}
+
+/// The merge sort on the calling thread, the baseline of speedup.
+pub fn merge_sort_sequential<T: Key>(v: &mut [T], cutoff: usize) {
+    let mut buf = v.to_vec();
+    sort_in(v, &mut buf, cutoff, false);
+}
+
+/// The merge sort on the current Rayon pool, splitting sorts and merges
+/// into rayon::join tasks down to runs of `cutoff`.
+pub fn merge_sort_parallel<T: Key>(v: &mut [T], cutoff: usize) {
+    let mut buf = v.to_vec();
+    sort_in(v, &mut buf, cutoff, true);
+}
+
+/// The `buckets - 1` splitters of a sample sort of `v`: `oversample` keys
+/// per bucket at evenly spaced positions, sorted, and every
+/// `oversample`-th of them.
+pub fn splitters<T: Key>(v: &[T], oversample: usize, buckets: usize) -> Vec<T> {
+    let count = (oversample * buckets).min(v.len());
+    let mut samples: Vec<T> = (0..count).map(|i| v[i * v.len() / count]).collect();
+    samples.sort_unstable_by(T::order);
+    (1..buckets).map(|b| samples[b * count / buckets]).collect()
+}
+
+// the bucket of key: how many splitters are at most key
+fn bucket<T: Key>(splitters: &[T], key: &T) -> usize {
+    splitters.partition_point(|s| T::order(s, key) != Ordering::Greater)
+}
+
+/// The sample sort on the current Rayon pool: each thread's chunk of `v`
+/// dealt into the buckets between the `splitters` of `v`, then every
+/// bucket gathered into its place in `v` and sorted as a task. Returns
+/// the largest bucket.
+pub fn sample_sort<T: Key>(v: &mut [T], oversample: usize, buckets: usize) -> usize {
+    if v.is_empty() {
+        return 0;
+    }
+    let splitters = splitters(v, oversample, buckets);
+    let chunk = v.len().div_ceil(rayon::current_num_threads()).max(1);
+    let dealt: Vec<Vec<Vec<T>>> = v
+        .par_chunks(chunk)
+        .map(|keys| {
+            let mut local = vec![Vec::new(); buckets];
+            for key in keys {
+                local[bucket(&splitters, key)].push(*key);
+            }
+            local
+        })
+        .collect();
+    let sizes: Vec<usize> = (0..buckets).map(|b| dealt.iter().map(|local| local[b].len()).sum()).collect();
+
+    // v carved into the buckets' places, in order
+    let mut places = Vec::with_capacity(buckets);
+    let mut rest = v;
+    for &size in &sizes {
+        let (place, tail) = std::mem::take(&mut rest).split_at_mut(size);
+        places.push(place);
+        rest = tail;
+    }
+    places.into_par_iter().enumerate().for_each(|(b, place)| {
+        let mut at = 0;
+        for local in &dealt {
+            place[at..at + local[b].len()].copy_from_slice(&local[b]);
+            at += local[b].len();
+        }
+        place.sort_unstable_by(T::order);
+    });
+    sizes.into_iter().max().unwrap_or(0)
+}
+
+// the sort of --algo `algo`, on the current Rayon pool; the largest bucket
+// of a sample sort
+fn parallel_sort<T: Key>(algo: &str, v: &mut [T], cutoff: usize, oversample: usize, buckets: usize) -> Option<usize> {
+    match algo {
+        "merge" => merge_sort_parallel(v, cutoff),
+        "sample" => return Some(sample_sort(v, oversample, buckets)),
+        "par_sort" => v.par_sort_by(T::order),
+        _ => v.par_sort_unstable_by(T::order),
+    }
+    None
+}
+
+// answer to --describe
+pub fn description() -> Description {
+    Description::new("sort", "sort", "N random u64 or f64 keys: rayon::join merge sort, sample sort, par_sort and par_sort_unstable")
+        .option(Param::integer("--size", 1, "keys").default(N))
+        .option(Param::threads("--threads", "threads").default(THREADS))
+        .option(Param::choice("--type", &["u64", "f64"], "key type").default("u64"))
+        .option(Param::choice("--algo", &["all", "merge", "sample", "par_sort", "par_sort_unstable"], "sort: all four, the rayon::join merge sort, the sample sort or rayon's stable or unstable sort").default("all"))
+        .option(Param::integer("--cutoff", 1, "merge and the baseline: largest run sorted or merged on one thread").default(CUTOFF))
+        .option(Param::integer("--oversample", 1, "sample: keys sampled per bucket").default(OVERSAMPLE))
+        .option(Param::integer("--buckets", 1, "sample: buckets").default(BUCKETS))
+        .option(Param::integer("--seed", 0, "seed of the keys").default(KEY_SEED))
+        .params(&["type", "algo", "N", "T", "cutoff", "oversample", "buckets", "seed"])
+        .timing("time", "sec")
+        .metric("speedup", "x")
+        .metric("imbalance", "x")
+        .metric("correct", "boolean")
+}
+
+/// N random u64 or f64 keys: rayon::join merge sort, sample sort, par_sort and par_sort_unstable
+#[derive(Debug, Clone, Parser)]
+#[command(name = "sort")]
+pub struct Args {
+    /// keys
+    #[arg(long, value_name = "N", value_parser = cli::positive, default_value_t = N)]
+    pub size: usize,
+    /// threads (a count or max, cores, half, quarter, a/b)
+    #[arg(long, value_name = "T", value_parser = threads::parse_spec, default_value_t = THREADS)]
+    pub threads: usize,
+    /// u64 | f64 (top 53 bits in [0, 1))
+    #[arg(long = "type", value_name = "K", value_parser = ["u64", "f64"], default_value = "u64")]
+    pub key: String,
+    /// all | merge (rayon::join) | sample | par_sort | par_sort_unstable
+    #[arg(long, value_name = "A", value_parser = ["all", "merge", "sample", "par_sort", "par_sort_unstable"], default_value = "all")]
+    pub algo: String,
+    /// merge and the baseline: largest run sorted or merged on one thread
+    #[arg(long, value_name = "C", value_parser = cli::positive, default_value_t = CUTOFF)]
+    pub cutoff: usize,
+    /// sample: keys sampled per bucket
+    #[arg(long, value_name = "S", value_parser = cli::positive, default_value_t = OVERSAMPLE)]
+    pub oversample: usize,
+    /// sample: buckets
+    #[arg(long, value_name = "B", value_parser = cli::positive, default_value_t = BUCKETS)]
+    pub buckets: usize,
+    /// seed of the keys
+    #[arg(long, value_name = "S", default_value_t = KEY_SEED)]
+    pub seed: u64,
+    #[command(flatten)]
+    pub common: cli::Common,
+}
+
+pub struct Sort;
+
+impl Benchmark for Sort {
+    fn name(&self) -> &'static str {
+        "sort"
+    }
+
+    fn label(&self) -> &'static str {
+        "Sort"
+    }
+
+    fn suite(&self) -> Suite {
+        Suite::Scalability
+    }
+
+    fn description(&self) -> Description {
+        description()
+    }
+
+    fn command(&self) -> clap::Command {
+        Args::command()
+    }
+
+    fn run(&self, args: &ArgMatches, reporter: &mut Reporter) -> i32 {
+        run(&cli::from_matches(args), reporter)
+    }
+}
+
+pub fn run(args: &Args, reporter: &mut Reporter) -> i32 {
+    match args.key.as_str() {
+        "f64" => run_keys::<f64>(args, reporter),
+        _ => run_keys::<u64>(args, reporter),
+    }
+}
+
+fn run_keys<T: Key + Default>(args: &Args, reporter: &mut Reporter) -> i32 {
+    let Args { size: n, threads: t, cutoff, oversample, buckets, seed, .. } = *args;
+    let key = args.key.as_str();
+    let algos: Vec<&str> = if args.algo == "all" { ALGOS.to_vec() } else { vec![args.algo.as_str()] };
+    let repeat = args.common.reps.repeat(1);
+
+    let _bench = info_span!("sort", %key, n, threads = t, cutoff, oversample, buckets).entered();
+    let (pool, keys) = Phase::Setup.run(|| {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(t).build().unwrap();
+        let keys = pool.install(|| gen_keys::<T>(n, seed));
+        (pool, keys)
+    });
+
+    // every sort gets a fresh copy of the keys, made outside the timed region
+    let (seq_time, expected) = repeat.run(|| {
+        let mut v = keys.clone();
+        let start = Instant::now();
+        merge_sort_sequential(&mut v, cutoff);
+        (start.elapsed().as_secs_f64(), v)
+    });
+
+    let mut all_correct = true;
+    for algo in algos {
+        let _algo = info_span!("algo", algo).entered();
+        let (elapsed, (sorted, largest, allocs)) = repeat.run(|| {
+            let mut v = keys.clone();
+            let region = Region::start();
+            let start = Instant::now();
+            let largest = pool.install(|| parallel_sort(algo, &mut v, cutoff, oversample, buckets));
+            (start.elapsed().as_secs_f64(), (v, largest, region.finish()))
+        });
+
+        let correct = Phase::Verify.run(|| sorted == expected);
+        all_correct &= correct;
+        let mut record = Record::new("sort", "rayon")
+            .param("type", key)
+            .param("algo", algo)
+            .param("N", n)
+            .param("T", t);
+        // only the tuning the algorithm reads
+        record = match algo {
+            "merge" => record.param("cutoff", cutoff),
+            "sample" => record.param("oversample", oversample).param("buckets", buckets),
+            _ => record,
+        };
+        let mut record = record
+            .param("seed", seed)
+            .timing("time", &elapsed, "sec")
+            .metric("speedup", seq_time.min / elapsed.min, "x");
+        // the largest bucket over the mean, n / buckets
+        if let Some(largest) = largest {
+            record = record.metric("imbalance", largest as f64 * buckets as f64 / n as f64, "x");
+        }
+        let record = record
+            .metric("correct", if correct { 1.0 } else { 0.0 }, "boolean")
+            .allocs(allocs)
+            .energy(elapsed.energy)
+            .peak_rss(elapsed.peak_rss);
+        if reporter.is_text() {
+            print!("{}", record.to_csv());
+        }
+        reporter.record(&record);
+    }
+
+    if all_correct {
+        0
+    } else {
+        3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_sorts_agree() {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(3).build().unwrap();
+        // 10_007 keys: odd runs at every level; cutoff 1 splits down to pairs
+        for cutoff in [1, 64, 20_000] {
+            let keys = gen_keys::<u64>(10_007, KEY_SEED);
+            let mut expected = keys.clone();
+            expected.sort();
+            let mut v = keys.clone();
+            merge_sort_sequential(&mut v, cutoff);
+            assert_eq!(v, expected, "cutoff {}", cutoff);
+            for algo in ALGOS {
+                let mut v = keys.clone();
+                pool.install(|| parallel_sort(algo, &mut v, cutoff, 4, cutoff));
+                assert_eq!(v, expected, "{} cutoff {}", algo, cutoff);
+            }
+        }
+        // the sample sort with more buckets than keys, and with one bucket
+        let keys = gen_keys::<u64>(100, KEY_SEED);
+        let mut expected = keys.clone();
+        expected.sort();
+        for (oversample, buckets) in [(1, 1000), (8, 1), (3, 7)] {
+            let mut v = keys.clone();
+            let largest = pool.install(|| sample_sort(&mut v, oversample, buckets));
+            assert_eq!(v, expected, "oversample {} buckets {}", oversample, buckets);
+            assert!(largest >= 100usize.div_ceil(buckets));
+        }
+
+        // f64 keys in [0, 1), and many ties
+        let keys = gen_keys::<f64>(5000, 7);
+        assert!(keys.iter().all(|&k| (0.0..1.0).contains(&k)));
+        let mut expected = keys.clone();
+        expected.sort_by(f64::total_cmp);
+        let mut v = keys.clone();
+        pool.install(|| merge_sort_parallel(&mut v, 16));
+        assert_eq!(v, expected);
+        let mut ties: Vec<u64> = gen_keys::<u64>(5000, 7).iter().map(|k| k % 4).collect();
+        pool.install(|| merge_sort_parallel(&mut ties, 16));
+        assert!(ties.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    // every parallel sort of keys against slice::sort_by, and the sample
+    // sort's largest bucket at least the mean
+    fn check_sorts<T: Key + std::fmt::Debug>(keys: &[T], threads: usize, cutoff: usize, oversample: usize, buckets: usize) -> Result<(), TestCaseError> {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+        let mut expected = keys.to_vec();
+        expected.sort_by(T::order);
+        let mut v = keys.to_vec();
+        pool.install(|| merge_sort_parallel(&mut v, cutoff));
+        prop_assert_eq!(&v, &expected);
+        let mut v = keys.to_vec();
+        let largest = pool.install(|| sample_sort(&mut v, oversample, buckets));
+        prop_assert_eq!(&v, &expected);
+        prop_assert!(largest >= keys.len().div_ceil(buckets));
+        for algo in ALGOS {
+            let mut v = keys.to_vec();
+            pool.install(|| parallel_sort(algo, &mut v, cutoff, oversample, buckets));
+            prop_assert_eq!(&v, &expected, "{}", algo);
+        }
+        Ok(())
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(48))]
+
+        #[test]
+        fn prop_sorts_match_sort_by(
+            // wide keys, or few distinct ones for ties; often 0 or 1 of them
+            random in prop_oneof![
+                prop::collection::vec(any::<u64>(), 0..2),
+                prop::collection::vec(prop_oneof![any::<u64>(), 0u64..8], 0..3000),
+            ],
+            f64_keys in any::<bool>(),
+            threads in 1usize..9,
+            cutoff in prop_oneof![1usize..4, 1usize..5000],
+            oversample in 1usize..40,
+            buckets in 1usize..300,
+        ) {
+            if f64_keys {
+                let keys: Vec<f64> = random.iter().map(|&r| f64::from_random(r)).collect();
+                check_sorts(&keys, threads, cutoff, oversample, buckets)?;
+            } else {
+                check_sorts(&random, threads, cutoff, oversample, buckets)?;
+            }
+        }
+    }
+}
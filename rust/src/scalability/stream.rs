@@ -0,0 +1,258 @@
+// STREAM benchmark for scalability (Rust version)
+// The memory-bound end of the study, after McCalpin's STREAM: three arrays
+// a, b, c of N doubles, each run timing the four kernels in order,
+//   copy    c = a
+//   scale   b = s * c
+//   add     c = a + b
+//   triad   a = b + s * c
+// with s = 3, each over T static chunks of N / T (schedule(static)) on the
+// pool. The arrays are first touched by the same chunks, so on a NUMA
+// machine each thread streams from its own node with a static backend.
+// --pages puts the arrays on huge pages, the case for TLB pressure.
+// bandwidth counts the bytes STREAM counts: 16 N for copy and scale, 24 N
+// for add and triad (no write-allocate traffic), over the best time. The
+// arrays start as a = 1, b = 2, c = 0, so after the four kernels every
+// element is a = 15, b = 3, c = 4, however often each ran.
+//
+// Usage:
+//   ./stream [--size N] [--threads T] [options]
+//   --size N           elements per array (default 100000000: 2.4 GB in all)
+//   --threads T        number of threads (default 8; a count or max, cores, half, 3/4)
+//
+// Options:
+//   --backend B        rayon (default) | threads | crossbeam | workers
+//                      (+ tokio-blocking | tokio-async with --features tokio)
+//   --pages P          default | thp | hugetlb pages for a, b and c (see pages.rs)
+//   --format FMT       text (default, CSV lines below) | jsonl | json | csv | markdown
+//   --upload URL       POST the result as JSON to an HTTP collector
+//   --prometheus FILE  keep FILE updated with Prometheus gauges for each result
+//   --store FILE       append the run to a SQLite database (--features store)
+//   --log FMT          pretty | json execution log on stderr (RUST_LOG filters)
+//   --reps R           timed runs of each kernel, the best is reported
+//                      (default 10, as STREAM's NTIMES); R > 1 adds
+//                      time_mean, time_median and time_stddev
+//   --warmup W         untimed runs before them (default 1; 0 for a cold start)
+//   --describe         print the JSON description of arguments and metrics, then exit
+//
+// Output (CSV-style, one record per kernel, second field is the backend):
+//   stream,rayon,op=triad,N=100000000,T=8,pages=default,time,0.123456,sec
+//   stream,rayon,op=triad,N=100000000,T=8,pages=default,bandwidth,19.440,GB/s
+//   stream,rayon,op=triad,N=100000000,T=8,pages=default,correct,1,boolean
+
+use crate::backend::{Backend, Pool};
+use crate::cli;
+use crate::describe::{Description, Param};
+use crate::kernels::{Benchmark, Suite};
+use crate::logging::Phase;
+use crate::pages::{PageVec, Pages};
+use crate::results::{Record, Reporter};
+use crate::threads;
+use clap::{ArgMatches, CommandFactory, Parser};
+use tracing::info_span;
+
+// defaults of --size and --threads
+const N: usize = 100_000_000; // 10^8
+const THREADS: usize = 8;
+
+/// The kernels, in the order every run times them.
+pub const OPS: [&str; 4] = ["copy", "scale", "add", "triad"];
+
+/// The scalar of scale and triad.
+pub const SCALAR: f64 = 3.0;
+
+// timed runs of each kernel unless --reps is given
+const REPS: usize = 10;
+
+/// The three arrays of N.
+pub struct Arrays {
+    pub a: PageVec<f64>,
+    pub b: PageVec<f64>,
+    pub c: PageVec<f64>,
+}
+
+impl Arrays {
+    /// a = 1, b = 2, c = 0 on `pages`, each written first by the pool's
+    /// static chunks.
+    pub fn new(pool: &Pool, n: usize, pages: Pages) -> Arrays {
+        let chunk = chunk(pool, n);
+        let array = |value: f64| {
+            let mut v = cli::or_exit(PageVec::zeroed(n, pages));
+            pool.for_each_chunk_mut(&mut v, chunk, |_, c| c.fill(value));
+            v
+        };
+        Arrays { a: array(1.0), b: array(2.0), c: array(0.0) }
+    }
+}
+
+// one chunk per thread, as schedule(static)
+fn chunk(pool: &Pool, n: usize) -> usize {
+    n.div_ceil(pool.threads()).max(1)
+}
+
+/// Bytes one pass of `op` reads and writes over arrays of `n`, as STREAM
+/// counts them.
+pub fn bytes(op: &str, n: usize) -> usize {
+    let words = match op {
+        "copy" | "scale" => 2,
+        _ => 3,
+    };
+    words * std::mem::size_of::<f64>() * n
+}
+
+/// One pass of `op` over `arrays`, in static chunks on `pool`.
+pub fn stream(pool: &Pool, op: &str, arrays: &mut Arrays) {
+    let Arrays { a, b, c } = arrays;
+    let chunk = chunk(pool, a.len());
+    match op {
+        "copy" => pool.for_each_chunk_mut(c, chunk, |start, c| c.copy_from_slice(&a[start..start + c.len()])),
+        "scale" => pool.for_each_chunk_mut(b, chunk, |start, b| {
+            for (b, &c) in b.iter_mut().zip(&c[start..]) {
+                *b = SCALAR * c;
+            }
+        }),
+        "add" => pool.for_each_chunk_mut(c, chunk, |start, c| {
+            for ((c, &a), &b) in c.iter_mut().zip(&a[start..]).zip(&b[start..]) {
+                *c = a + b;
+            }
+        }),
+        _ => pool.for_each_chunk_mut(a, chunk, |start, a| {
+            for ((a, &b), &c) in a.iter_mut().zip(&b[start..]).zip(&c[start..]) {
+                *a = b + SCALAR * c;
+            }
+        }),
+    }
+}
+
+// after copy, scale, add and triad: a = b + s c, b = s a0, c = a0 + b
+fn verify(arrays: &Arrays) -> bool {
+    let b = SCALAR * 1.0;
+    let c = 1.0 + b;
+    let a = b + SCALAR * c;
+    arrays.a.iter().all(|&v| v == a) && arrays.b.iter().all(|&v| v == b) && arrays.c.iter().all(|&v| v == c)
+}
+
+// answer to --describe
+pub fn description() -> Description {
+    Description::new("stream", "stream", "STREAM copy, scale, add and triad: memory bandwidth per thread count")
+        .option(Param::integer("--size", 1, "elements per array").default(N))
+        .option(Param::threads("--threads", "threads").default(THREADS))
+        .backend_option()
+        .pages_option()
+        .params(&["op", "N", "T", "pages"])
+        .timing("time", "sec")
+        .metric("bandwidth", "GB/s")
+        .metric("correct", "boolean")
+        .reps_default(REPS)
+}
+
+/// STREAM copy, scale, add and triad: memory bandwidth per thread count
+#[derive(Debug, Clone, Parser)]
+#[command(name = "stream")]
+pub struct Args {
+    /// elements per array
+    #[arg(long, value_name = "N", value_parser = cli::positive, default_value_t = N)]
+    pub size: usize,
+    /// threads (a count or max, cores, half, quarter, a/b)
+    #[arg(long, value_name = "T", value_parser = threads::parse_spec, default_value_t = THREADS)]
+    pub threads: usize,
+    /// rayon | threads | crossbeam | workers (+ tokio-blocking | tokio-async)
+    #[arg(long, value_name = "B", value_parser = Backend::parse, default_value = "rayon")]
+    pub backend: Backend,
+    /// default | thp | hugetlb pages for a, b and c
+    #[arg(long, value_name = "P", value_parser = Pages::parse, default_value = "default")]
+    pub pages: Pages,
+    #[command(flatten)]
+    pub common: cli::Common,
+}
+
+pub struct Stream;
+
+impl Benchmark for Stream {
+    fn name(&self) -> &'static str {
+        "stream"
+    }
+
+    fn label(&self) -> &'static str {
+        "STREAM"
+    }
+
+    fn suite(&self) -> Suite {
+        Suite::Scalability
+    }
+
+    fn description(&self) -> Description {
+        description()
+    }
+
+    fn command(&self) -> clap::Command {
+        Args::command()
+    }
+
+    fn run(&self, args: &ArgMatches, reporter: &mut Reporter) -> i32 {
+        run(&cli::from_matches(args), reporter)
+    }
+}
+
+pub fn run(args: &Args, reporter: &mut Reporter) -> i32 {
+    let Args { size: n, threads: t, backend, pages, .. } = *args;
+    let repeat = args.common.reps.repeat(REPS);
+
+    let _bench = info_span!("stream", n, threads = t, backend = backend.name(), pages = pages.name()).entered();
+    let (pool, mut arrays) = Phase::Setup.run(|| {
+        let pool = Pool::new(backend, t);
+        let arrays = Arrays::new(&pool, n, pages);
+        (pool, arrays)
+    });
+
+    let runs = OPS.map(|op| {
+        let _op = info_span!("op", op).entered();
+        let (time, (), allocs) = repeat.measure(|| stream(&pool, op, &mut arrays));
+        (op, time, allocs)
+    });
+
+    let correct = Phase::Verify.run(|| verify(&arrays));
+    for (op, time, allocs) in &runs {
+        let record = Record::new("stream", backend.name())
+            .param("op", op)
+            .param("N", n)
+            .param("T", t)
+            .param("pages", pages.name())
+            .timing("time", time, "sec")
+            .metric("bandwidth", bytes(op, n) as f64 / time.min / 1e9, "GB/s")
+            .metric("correct", if correct { 1.0 } else { 0.0 }, "boolean")
+            .allocs(*allocs)
+            .energy(time.energy)
+            .peak_rss(time.peak_rss);
+        if reporter.is_text() {
+            print!("{}", record.to_csv());
+        }
+        reporter.record(&record);
+    }
+
+    if correct {
+        0
+    } else {
+        3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream() {
+        for backend in [Backend::Rayon, Backend::Threads] {
+            let pool = Pool::new(backend, 3);
+            // 1000: the last chunk is short
+            let mut arrays = Arrays::new(&pool, 1000, Pages::Default);
+            assert!(!verify(&arrays));
+            for op in OPS {
+                stream(&pool, op, &mut arrays);
+                stream(&pool, op, &mut arrays);
+            }
+            assert!(verify(&arrays), "{}", backend.name());
+        }
+        assert_eq!((bytes("copy", 10), bytes("triad", 10)), (160, 240));
+    }
+}
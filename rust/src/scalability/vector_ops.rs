@@ -0,0 +1,344 @@
+// Vector kernels for the SIMD + threads comparison (Rust version)
+// Kernels:
+//   saxpy: y = a*x + y over f32
+//   sum:   reduction of an f64 array
+// Each run reports its time and the speedup over the scalar sequential loop,
+// so the same binary yields thread-only (T>1), SIMD-only (T=1 --simd) and
+// combined (T>1 --simd) speedups, like OpenMP `simd` vs `parallel for simd`.
+//
+// Usage:
+//   ./vector_ops [--op OP] [--size N] [--threads T] [options]
+//   --op OP            all (default: saxpy, then sum) | saxpy | sum
+//   --size N           number of elements (default 100000000)
+//   --threads T        number of threads (default 8; a count, or max, cores, half, quarter, a/b)
+//
+// Options:
+//   --simd             std::simd kernels (nightly + --features simd)
+//   --backend B        rayon (default) | threads | crossbeam | workers
+//   --multiproc M      sum only: T processes instead of threads, partial
+//                      sums returned via shm | pipe
+//   --reps R           timed runs of each loop, the best is reported (default 1)
+//   --warmup W         untimed runs before them (default 1; 0 for a cold start)
+//   --format FMT       text (default, CSV lines below) | jsonl | json | csv | markdown
+//   --upload URL       POST the result as JSON to an HTTP collector
+//   --prometheus FILE  keep FILE updated with Prometheus gauges for each result
+//   --store FILE       append the run to a SQLite database (--features store)
+//   --log FMT          pretty | json execution log on stderr (RUST_LOG filters)
+//   --describe         print the JSON description of arguments and metrics, then exit
+//
+// Output (CSV-style):
+//   vector,rayon,op=saxpy,N=100000000,T=8,simd=1,time,0.012345,sec
+//   vector,rayon,op=saxpy,N=100000000,T=8,simd=1,speedup,6.10,x
+//   vector,rayon,op=saxpy,N=100000000,T=8,simd=1,correct,1,boolean
+
+use crate::alloc_track::{AllocStats, Region};
+use crate::backend::{Backend, Pool};
+use crate::cli;
+use crate::describe::{Description, Param};
+use crate::golden::{self, Digest, Golden, Verdict};
+use crate::kernels::{Benchmark, Suite, WorkerKernel};
+use crate::logging::Phase;
+use crate::multiproc::{self, Ipc, ProcessPool};
+use crate::results::{Record, Reporter};
+use crate::stats::{Repeat, Stats};
+use crate::threads;
+use clap::{ArgMatches, CommandFactory, Parser};
+use std::time::Instant;
+use tracing::info_span;
+
+// defaults of --size and --threads
+const N: usize = 100_000_000; // 10^8
+const THREADS: usize = 8;
+
+/// The kernels --op all runs, in order.
+pub const OPS: [&str; 2] = ["saxpy", "sum"];
+
+const SAXPY_A: f32 = 2.5;
+
+// saxpy: checksum of y (element-wise, bit-exact); sum: the total
+const GOLDEN: &[Golden] = &[
+    Golden { key: "op=saxpy,N=10000000", digest: Digest::Checksum(0xf75b4fa60432aa7c) },
+    Golden { key: "op=saxpy,N=100000000", digest: Digest::Checksum(0x02ec6c13df255ef3) },
+    Golden { key: "op=sum,N=10000000", digest: Digest::Value(4995000.0) },
+    Golden { key: "op=sum,N=100000000", digest: Digest::Value(49950000.0) },
+];
+
+// golden result when registered, else `fallback` (the check against the
+// scalar sequential run)
+fn verify(op: &str, n: usize, digest: Digest, fallback: impl FnOnce() -> bool) -> bool {
+    match golden::check("vector", GOLDEN, &format!("op={},N={}", op, n), digest) {
+        Verdict::Match => true,
+        Verdict::Mismatch => false,
+        Verdict::Unregistered => fallback(),
+    }
+}
+
+fn saxpy_scalar(a: f32, x: &[f32], y: &mut [f32]) {
+    for (yi, &xi) in y.iter_mut().zip(x) {
+        *yi += a * xi;
+    }
+}
+
+#[cfg(feature = "simd")]
+fn saxpy_simd(a: f32, x: &[f32], y: &mut [f32]) {
+    use crate::simd_util;
+    use std::simd::prelude::*;
+
+    let va = f32x8::splat(a);
+    simd_util::update(y, |offset, vy| va * simd_util::load_prefix(&x[offset..]) + vy);
+}
+
+#[cfg(not(feature = "simd"))]
+fn saxpy_simd(_a: f32, _x: &[f32], _y: &mut [f32]) {
+    unreachable!("built without the simd feature")
+}
+
+fn sum_scalar(x: &[f64]) -> f64 {
+    x.iter().sum()
+}
+
+#[cfg(feature = "simd")]
+fn sum_simd(x: &[f64]) -> f64 {
+    crate::simd_util::sum::<f64, 4>(x)
+}
+
+#[cfg(not(feature = "simd"))]
+fn sum_simd(_x: &[f64]) -> f64 {
+    unreachable!("built without the simd feature")
+}
+
+fn saxpy_parallel(pool: &Pool, a: f32, x: &[f32], y: &mut [f32], simd: bool) {
+    let chunk = y.len().div_ceil(pool.threads());
+    pool.for_each_chunk_mut(y, chunk, |start, yc| {
+        let xc = &x[start..start + yc.len()];
+        if simd {
+            saxpy_simd(a, xc, yc);
+        } else {
+            saxpy_scalar(a, xc, yc);
+        }
+    });
+}
+
+fn sum_parallel(pool: &Pool, x: &[f64], simd: bool) -> f64 {
+    let chunk = x.len().div_ceil(pool.threads());
+    pool.map_reduce(
+        x,
+        chunk,
+        || 0.0,
+        |_, c| if simd { sum_simd(c) } else { sum_scalar(c) },
+        |a, b| a + b,
+    )
+}
+
+// reduction across processes; timing covers spawn, scatter and gather
+fn sum_procs(procs: &ProcessPool, x: &[f64], simd: bool) -> f64 {
+    let kernel = if simd { "sum-simd" } else { "sum" };
+    let partials = procs
+        .map(kernel, multiproc::f64_bytes(x), std::mem::size_of::<f64>(), 1)
+        .unwrap_or_else(|e| {
+            eprintln!("multiproc run failed: {}", e);
+            std::process::exit(2);
+        });
+    partials.iter().map(|p| f64::from_bits(p[0])).sum()
+}
+
+pub fn worker_kernel(name: &str, block: &[u8]) -> Option<Vec<u64>> {
+    match name {
+        "sum" => Some(vec![sum_scalar(multiproc::bytes_f64(block)).to_bits()]),
+        "sum-simd" => Some(vec![sum_simd(multiproc::bytes_f64(block)).to_bits()]),
+        _ => None,
+    }
+}
+
+// one measured op
+struct Outcome {
+    time: Stats,
+    // scalar sequential time
+    seq_time: f64,
+    correct: bool,
+    allocs: Option<AllocStats>,
+}
+
+fn run_saxpy(pool: &Pool, n: usize, simd: bool, repeat: Repeat) -> Outcome {
+    let (x, y0) = Phase::Setup.run(|| {
+        let x: Vec<f32> = (0..n).map(|i| (i % 1000) as f32 * 0.001).collect();
+        let y0: Vec<f32> = (0..n).map(|i| (i % 7) as f32).collect();
+        (x, y0)
+    });
+
+    // every run updates a fresh copy of y, made outside the timed region
+    let (seq_time, expected) = repeat.run(|| {
+        let mut expected = y0.clone();
+        let start = Instant::now();
+        saxpy_scalar(SAXPY_A, &x, &mut expected);
+        (start.elapsed().as_secs_f64(), expected)
+    });
+    let seq_time = seq_time.min;
+
+    let (time, (y, allocs)) = repeat.run(|| {
+        let mut y = y0.clone();
+        let region = Region::start();
+        let start = Instant::now();
+        saxpy_parallel(pool, SAXPY_A, &x, &mut y, simd);
+        let time = start.elapsed().as_secs_f64();
+        (time, (y, region.finish()))
+    });
+
+    // element-wise a*x + y rounds identically in every variant
+    let digest = Digest::Checksum(golden::checksum(y.iter().map(|v| v.to_bits() as u64)));
+    let correct = Phase::Verify.run(|| verify("saxpy", n, digest, || y == expected));
+    Outcome { time, seq_time, correct, allocs }
+}
+
+fn run_sum(pool: Option<&Pool>, procs: Option<&ProcessPool>, n: usize, simd: bool, repeat: Repeat) -> Outcome {
+    let x: Vec<f64> = Phase::Setup.run(|| (0..n).map(|i| (i % 1000) as f64 * 0.001).collect());
+
+    let (seq_time, expected, _) = repeat.measure(|| sum_scalar(&x));
+    let seq_time = seq_time.min;
+
+    let (time, total, allocs) = repeat.measure(|| match (pool, procs) {
+        (_, Some(procs)) => sum_procs(procs, &x, simd),
+        (Some(pool), None) => sum_parallel(pool, &x, simd),
+        (None, None) => unreachable!("sum needs a thread or process pool"),
+    });
+
+    // reassociation changes rounding, so compare with a relative tolerance
+    let correct = Phase::Verify.run(|| {
+        verify("sum", n, Digest::Value(total), || (total - expected).abs() <= 1e-9 * expected.abs().max(1.0))
+    });
+    Outcome { time, seq_time, correct, allocs }
+}
+
+// answer to --describe
+pub fn description() -> Description {
+    Description::new("vector_ops", "vector", "saxpy over f32 and sum over f64, speedup against the scalar sequential loop")
+        .option(Param::choice("--op", &["all", "saxpy", "sum"], "kernel: both, or one of them").default("all"))
+        .option(Param::integer("--size", 1, "elements").default(N))
+        .option(Param::threads("--threads", "threads").default(THREADS))
+        .backend_option()
+        .multiproc_option("sum only: T processes instead of threads")
+        .simd_option("std::simd kernels")
+        .params(&["op", "N", "T", "simd"])
+        .timing("time", "sec")
+        .metric("speedup", "x")
+        .metric("correct", "boolean")
+}
+
+/// saxpy over f32 and sum over f64, speedup against the scalar sequential loop
+#[derive(Debug, Clone, Parser)]
+#[command(name = "vector_ops")]
+pub struct Args {
+    /// all (saxpy, then sum) | saxpy | sum
+    #[arg(long, value_parser = ["all", "saxpy", "sum"], default_value = "all")]
+    pub op: String,
+    /// elements
+    #[arg(long, value_name = "N", value_parser = cli::positive, default_value_t = N)]
+    pub size: usize,
+    /// threads (a count or max, cores, half, quarter, a/b)
+    #[arg(long, value_name = "T", value_parser = threads::parse_spec, default_value_t = THREADS)]
+    pub threads: usize,
+    /// std::simd kernels (nightly + --features simd)
+    #[arg(long)]
+    pub simd: bool,
+    /// sum only: T processes instead of threads, partials via shm | pipe
+    #[arg(long, value_name = "M", value_parser = Ipc::parse)]
+    pub multiproc: Option<Ipc>,
+    /// rayon | threads | crossbeam | workers (+ tokio-blocking | tokio-async)
+    #[arg(long, value_name = "B", value_parser = Backend::parse, default_value = "rayon")]
+    pub backend: Backend,
+    #[command(flatten)]
+    pub common: cli::Common,
+}
+
+pub struct VectorOps;
+
+impl Benchmark for VectorOps {
+    fn name(&self) -> &'static str {
+        "vector_ops"
+    }
+
+    fn label(&self) -> &'static str {
+        "Vector ops"
+    }
+
+    fn suite(&self) -> Suite {
+        Suite::Scalability
+    }
+
+    fn description(&self) -> Description {
+        description()
+    }
+
+    fn command(&self) -> clap::Command {
+        Args::command()
+    }
+
+    fn run(&self, args: &ArgMatches, reporter: &mut Reporter) -> i32 {
+        run(&cli::from_matches(args), reporter)
+    }
+
+    fn worker(&self) -> Option<WorkerKernel> {
+        Some(worker_kernel)
+    }
+}
+
+pub fn run(args: &Args, reporter: &mut Reporter) -> i32 {
+    let simd = cli::or_exit(cli::simd(args.simd));
+    let Args { size: n, threads: t, multiproc: ipc, backend, .. } = *args;
+    let repeat = args.common.reps.repeat(1);
+    let ops: Vec<&str> = if args.op == "all" { OPS.to_vec() } else { vec![args.op.as_str()] };
+
+    if ipc.is_some() && ops != ["sum"] {
+        eprintln!("--multiproc applies to the sum op only");
+        return 1;
+    }
+
+    // worker processes take the place of the thread pool
+    let impl_name = ipc.map_or(backend.name(), Ipc::name);
+    let procs = ipc.map(|ipc| ProcessPool::new(t, ipc));
+    let pool = Phase::Setup.run(|| procs.is_none().then(|| Pool::new(backend, t)));
+    let simd_flag = if simd { 1 } else { 0 };
+    let mut all_correct = true;
+
+    for op in ops {
+        let _bench = info_span!("vector", %op, implementation = impl_name, n, threads = t, simd).entered();
+        let Outcome { time, seq_time, correct, allocs } = match op {
+            "saxpy" => run_saxpy(pool.as_ref().unwrap(), n, simd, repeat),
+            _ => run_sum(pool.as_ref(), procs.as_ref(), n, simd, repeat),
+        };
+        let speedup = seq_time / time.min;
+        all_correct &= correct;
+
+        if reporter.is_text() {
+            let prefix = format!("vector,{},op={},N={},T={},simd={}", impl_name, op, n, t, simd_flag);
+            println!("{},time,{:.6},sec", prefix, time.min);
+            if time.reps > 1 {
+                for (name, value) in [("mean", time.mean), ("median", time.median), ("stddev", time.stddev)] {
+                    println!("{},time_{},{:.6},sec", prefix, name, value);
+                }
+            }
+            println!("{},speedup,{:.2},x", prefix, speedup);
+            println!("{},correct,{},boolean", prefix, if correct { 1 } else { 0 });
+        }
+
+        reporter.record(
+            &Record::new("vector", impl_name)
+                .param("op", op)
+                .param("N", n)
+                .param("T", t)
+                .param("simd", simd_flag)
+                .timing("time", &time, "sec")
+                .metric("speedup", speedup, "x")
+                .metric("correct", if correct { 1.0 } else { 0.0 }, "boolean")
+                .allocs(allocs)
+                .energy(time.energy)
+                .peak_rss(time.peak_rss),
+        );
+    }
+
+    if all_correct {
+        0
+    } else {
+        3
+    }
+}
@@ -0,0 +1,262 @@
+// Inclusive scans over any associative operator
+// prefix_sum's three parallel scans, generic over the element type and the
+// operator, so the same code computes running sums, maxima, minima or the
+// running products of any monoid. `op(a, b)` always has `a` from earlier in
+// the input, so the operator need not commute; it has to associate, as the
+// parallel scans regroup it. Each scan writes `output` from `input`:
+//   scan                 blocks of `grain` elements (0: one per thread) scanned
+//                        locally, then each block combined with the total of
+//                        the blocks before it
+//   scan_blelloch        Blelloch's up-sweep/down-sweep tree, O(n) ops
+//   scan_hillis_steele   log2(n) double-buffered passes, O(n log n) ops
+// The chunked and Blelloch scans also come in place (scan_in_place,
+// scan_blelloch_in_place), overwriting their input without a second buffer;
+// Hillis-Steele reads one pass's values while writing the next, so it has no
+// in-place form.
+// Mat2 is the non-trivial operator of prefix_sum --op mat2: 2x2 matrix
+// products, which neither commute nor vectorize like an add.
+
+use crate::backend::Pool;
+use std::ops::Mul;
+
+/// Inclusive scan of `input` into `output` on the calling thread.
+pub fn scan_sequential<T: Copy, F: Fn(T, T) -> T>(input: &[T], output: &mut [T], op: F) {
+    assert_eq!(input.len(), output.len(), "input and output lengths differ");
+    let mut running = match input.first() {
+        Some(&first) => first,
+        None => return,
+    };
+    output[0] = running;
+    for (out, &value) in output[1..].iter_mut().zip(&input[1..]) {
+        running = op(running, value);
+        *out = running;
+    }
+}
+
+/// Inclusive scan of `input` into `output` on `pool`: local scans of blocks
+/// of `grain` elements, then each block combined with the totals of the
+/// blocks before it; `grain` 0 gives every thread one block. `identity` is
+/// the operator's neutral element.
+pub fn scan<T, F>(pool: &Pool, input: &[T], output: &mut [T], grain: usize, identity: T, op: F)
+where
+    T: Copy + Send + Sync,
+    F: Fn(T, T) -> T + Sync,
+{
+    assert_eq!(input.len(), output.len(), "input and output lengths differ");
+    let n = input.len();
+    if n == 0 {
+        return;
+    }
+    let chunk_size = block(pool, n, grain);
+
+    // local scans in parallel
+    pool.for_each_chunk_mut(output, chunk_size, |start, local| scan_sequential(&input[start..start + local.len()], local, &op));
+    add_offsets(pool, output, chunk_size, identity, op);
+}
+
+/// `scan` of `data` in place.
+pub fn scan_in_place<T, F>(pool: &Pool, data: &mut [T], grain: usize, identity: T, op: F)
+where
+    T: Copy + Send + Sync,
+    F: Fn(T, T) -> T + Sync,
+{
+    if data.is_empty() {
+        return;
+    }
+    let chunk_size = block(pool, data.len(), grain);
+    pool.for_each_chunk_mut(data, chunk_size, |_, local| {
+        for i in 1..local.len() {
+            local[i] = op(local[i - 1], local[i]);
+        }
+    });
+    add_offsets(pool, data, chunk_size, identity, op);
+}
+
+// block length of the chunked scan of `n` elements: `grain`, or one block
+// per thread for 0
+fn block(pool: &Pool, n: usize, grain: usize) -> usize {
+    if grain > 0 { grain } else { n.div_ceil(pool.threads()) }
+}
+
+// the second half of the chunked scan: each block of `chunk_size` local
+// scans combined with the total of the blocks before it
+fn add_offsets<T, F>(pool: &Pool, data: &mut [T], chunk_size: usize, identity: T, op: F)
+where
+    T: Copy + Send + Sync,
+    F: Fn(T, T) -> T + Sync,
+{
+    let mut offsets = vec![identity; data.len().div_ceil(chunk_size)];
+    for i in 1..offsets.len() {
+        offsets[i] = op(offsets[i - 1], data[i * chunk_size - 1]);
+    }
+
+    // offsets onto the local scans in parallel (the first block has none)
+    pool.for_each_chunk_mut(data, chunk_size, |start, local| {
+        if start == 0 {
+            return;
+        }
+        let offset = offsets[start / chunk_size];
+        for value in local.iter_mut() {
+            *value = op(offset, *value);
+        }
+    });
+}
+
+/// Inclusive scan of `input` into `output` on `pool` with Blelloch's
+/// work-efficient tree: the up-sweep leaves each node the total of its
+/// subtree, the down-sweep combines every left subtree's total into the node
+/// right after it, O(n) ops over 2 log2(n) parallel passes. This is the
+/// inclusive form of the tree, which needs no padding to a power of two.
+pub fn scan_blelloch<T, F>(pool: &Pool, input: &[T], output: &mut [T], op: F)
+where
+    T: Copy + Send + Sync,
+    F: Fn(T, T) -> T + Sync,
+{
+    assert_eq!(input.len(), output.len(), "input and output lengths differ");
+    output.copy_from_slice(input);
+    scan_blelloch_in_place(pool, output, op);
+}
+
+/// `scan_blelloch` of `data` in place.
+pub fn scan_blelloch_in_place<T, F>(pool: &Pool, data: &mut [T], op: F)
+where
+    T: Copy + Send + Sync,
+    F: Fn(T, T) -> T + Sync,
+{
+    let n = data.len();
+
+    // one pass of the tree: in every `stride`-wide node of `data`, the last
+    // element takes in the one at the middle of the node
+    let level = |data: &mut [T], stride: usize| {
+        let nodes = (data.len() / stride).max(1);
+        let chunk = stride * nodes.div_ceil(pool.threads());
+        pool.for_each_chunk_mut(data, chunk, |_, local| {
+            for i in (stride - 1..local.len()).step_by(stride) {
+                local[i] = op(local[i - stride / 2], local[i]);
+            }
+        });
+    };
+
+    let mut stride = 2;
+    while stride <= n {
+        level(data, stride);
+        stride *= 2;
+    }
+    // the down-sweep pairs lie `stride / 2` further on: the same pass over
+    // the tail from there
+    while stride > 2 {
+        stride /= 2;
+        level(&mut data[stride / 2..], stride);
+    }
+}
+
+/// Inclusive scan of `input` into `output` on `pool` with the Hillis-Steele
+/// scan: for d = 1, 2, 4, ... each element takes in the one d before it,
+/// reading the last pass's buffer and writing the other (`scratch`, as long
+/// as the input). log2(n) passes, the fewest steps of any scan, at the cost
+/// of O(n log n) ops.
+pub fn scan_hillis_steele<T, F>(pool: &Pool, input: &[T], output: &mut [T], scratch: &mut [T], op: F)
+where
+    T: Copy + Send + Sync,
+    F: Fn(T, T) -> T + Sync,
+{
+    assert!(input.len() == output.len() && input.len() == scratch.len(), "input, output and scratch lengths differ");
+    let n = input.len();
+    let chunk_size = n.div_ceil(pool.threads()).max(1);
+    output.copy_from_slice(input);
+
+    // each pass reads `current` and writes `next`; an odd number of passes
+    // leaves the result in the scratch buffer
+    let (mut current, mut next) = (&mut *output, &mut *scratch);
+    let mut d = 1;
+    while d < n {
+        let read = &*current;
+        pool.for_each_chunk_mut(next, chunk_size, |start, local| {
+            for (i, value) in (start..).zip(local.iter_mut()) {
+                *value = if i >= d { op(read[i - d], read[i]) } else { read[i] };
+            }
+        });
+        std::mem::swap(&mut current, &mut next);
+        d *= 2;
+    }
+    if n.next_power_of_two().trailing_zeros() % 2 == 1 {
+        output.copy_from_slice(scratch);
+    }
+}
+
+/// A 2x2 matrix of integers mod 2^64, row-major. Its product is associative
+/// but not commutative, and wrapping keeps it exact, so every scan of a
+/// sequence of them must match the sequential one bit for bit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Mat2(pub [u64; 4]);
+
+impl Mat2 {
+    pub const IDENTITY: Mat2 = Mat2([1, 0, 0, 1]);
+
+    /// [[x, 1], [1, 0]]; a run of x = 1 multiplies out to the Fibonacci
+    /// numbers.
+    pub fn step(x: u64) -> Self {
+        Mat2([x, 1, 1, 0])
+    }
+}
+
+impl Mul for Mat2 {
+    type Output = Mat2;
+
+    fn mul(self, other: Mat2) -> Mat2 {
+        let ([a, b, c, d], [e, f, g, h]) = (self.0, other.0);
+        Mat2([
+            a.wrapping_mul(e).wrapping_add(b.wrapping_mul(g)),
+            a.wrapping_mul(f).wrapping_add(b.wrapping_mul(h)),
+            c.wrapping_mul(e).wrapping_add(d.wrapping_mul(g)),
+            c.wrapping_mul(f).wrapping_add(d.wrapping_mul(h)),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::Backend;
+
+    #[test]
+    fn test_operators() {
+        let input: Vec<u64> = (0..1000u64).map(|i| i * 7919 % 1009).collect();
+        let pool = Pool::new(Backend::Rayon, 3);
+        let sum: fn(u64, u64) -> u64 = |a, b| a + b;
+        for (identity, op) in [(0, sum), (0, u64::max), (u64::MAX, u64::min)] {
+            let mut expected = vec![0; input.len()];
+            scan_sequential(&input, &mut expected, op);
+            let (mut output, mut scratch) = (vec![0; input.len()], vec![0; input.len()]);
+            scan(&pool, &input, &mut output, 0, identity, op);
+            assert_eq!(output, expected);
+            scan(&pool, &input, &mut output, 7, identity, op);
+            assert_eq!(output, expected);
+            scan_blelloch(&pool, &input, &mut output, op);
+            assert_eq!(output, expected);
+            scan_hillis_steele(&pool, &input, &mut output, &mut scratch, op);
+            assert_eq!(output, expected);
+            output.copy_from_slice(&input);
+            scan_in_place(&pool, &mut output, 64, identity, op);
+            assert_eq!(output, expected);
+            output.copy_from_slice(&input);
+            scan_blelloch_in_place(&pool, &mut output, op);
+            assert_eq!(output, expected);
+        }
+
+        // ones: the running products are the Fibonacci matrices
+        let steps = vec![Mat2::step(1); 90];
+        let mut products = vec![Mat2::IDENTITY; steps.len()];
+        scan(&pool, &steps, &mut products, 0, Mat2::IDENTITY, |a, b| a * b);
+        assert_eq!(products[9], Mat2([89, 55, 55, 34]));
+        assert_eq!(products[89].0[0], 4660046610375530309); // F(91)
+        // order matters: a commuted operator gives another product
+        let mixed: Vec<Mat2> = (0..100).map(Mat2::step).collect();
+        let mut forward = vec![Mat2::IDENTITY; mixed.len()];
+        scan_blelloch(&pool, &mixed, &mut forward, |a, b| a * b);
+        let mut reversed = vec![Mat2::IDENTITY; mixed.len()];
+        scan_sequential(&mixed, &mut reversed, |a, b| b * a);
+        assert_eq!(forward[0], reversed[0]);
+        assert_ne!(forward[1], reversed[1]);
+    }
+}
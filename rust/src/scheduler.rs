@@ -0,0 +1,645 @@
+// Minimal Chase-Lev work-stealing scheduler
+// A deliberately small counterpart to Rayon, used by the overhead study to
+// separate "work stealing in general" from "Rayon in particular":
+//   - one fixed-capacity Chase-Lev deque per worker (owner pushes/pops at the
+//     bottom, thieves steal from the top; a push into a full deque runs the
+//     job inline instead of growing the buffer)
+//   - jobs are (pointer, fn) pairs; `join` keeps its second closure on the
+//     caller's stack, `Scope::spawn` boxes its closure
+//   - idle workers steal from a random victim, yield for a while and then
+//     sleep on a condvar with a short timeout
+// `join` and `scope` run on the scheduler when called inside `install`, and
+// inline on the calling thread anywhere else.
+
+use std::any::Any;
+use std::cell::{Cell, UnsafeCell};
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::sync::atomic::{fence, AtomicBool, AtomicIsize, AtomicPtr, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+// power of two, so indices wrap with a mask
+const DEQUE_CAPACITY: usize = 4096;
+// failed find-work rounds before an idle worker goes to sleep
+const SPIN_ROUNDS: usize = 256;
+
+#[derive(Clone, Copy)]
+struct JobRef {
+    data: *const (),
+    execute: unsafe fn(*const ()),
+}
+
+// SAFETY: a JobRef is only executed once, by whichever thread takes it; the
+// job types below are built from Send closures
+unsafe impl Send for JobRef {}
+
+impl JobRef {
+    unsafe fn execute(self) {
+        (self.execute)(self.data)
+    }
+}
+
+struct Slot {
+    data: AtomicPtr<()>,
+    execute: AtomicUsize,
+}
+
+impl Slot {
+    fn store(&self, job: JobRef) {
+        self.data.store(job.data as *mut (), Ordering::Relaxed);
+        self.execute.store(job.execute as usize, Ordering::Relaxed);
+    }
+
+    fn load(&self) -> JobRef {
+        let execute = self.execute.load(Ordering::Relaxed);
+        JobRef {
+            data: self.data.load(Ordering::Relaxed),
+            // SAFETY: only ever written from a valid `unsafe fn(*const ())`;
+            // a torn read is discarded because the caller's CAS on top fails
+            execute: unsafe { std::mem::transmute::<usize, unsafe fn(*const ())>(execute) },
+        }
+    }
+}
+
+enum Steal {
+    Empty,
+    Retry,
+    Success(JobRef),
+}
+
+// Chase-Lev deque with the memory orderings of Le et al., "Correct and
+// Efficient Work-Stealing for Weak Memory Models" (PPoPP'13)
+struct Deque {
+    top: AtomicIsize,
+    bottom: AtomicIsize,
+    slots: Box<[Slot]>,
+}
+
+impl Deque {
+    fn new() -> Self {
+        Deque {
+            top: AtomicIsize::new(0),
+            bottom: AtomicIsize::new(0),
+            slots: (0..DEQUE_CAPACITY)
+                .map(|_| Slot {
+                    data: AtomicPtr::new(ptr::null_mut()),
+                    execute: AtomicUsize::new(0),
+                })
+                .collect(),
+        }
+    }
+
+    fn slot(&self, index: isize) -> &Slot {
+        &self.slots[index as usize & (DEQUE_CAPACITY - 1)]
+    }
+
+    // owner only; hands the job back when the deque is full
+    fn push(&self, job: JobRef) -> Result<(), JobRef> {
+        let b = self.bottom.load(Ordering::Relaxed);
+        let t = self.top.load(Ordering::Acquire);
+        if b - t >= DEQUE_CAPACITY as isize {
+            return Err(job);
+        }
+        self.slot(b).store(job);
+        fence(Ordering::Release);
+        self.bottom.store(b + 1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    // owner only
+    fn pop(&self) -> Option<JobRef> {
+        let b = self.bottom.load(Ordering::Relaxed) - 1;
+        self.bottom.store(b, Ordering::Relaxed);
+        fence(Ordering::SeqCst);
+        let t = self.top.load(Ordering::Relaxed);
+
+        if t > b {
+            self.bottom.store(b + 1, Ordering::Relaxed);
+            return None;
+        }
+        let job = self.slot(b).load();
+        if t == b {
+            // last element: race the thieves for it
+            let won = self
+                .top
+                .compare_exchange(t, t + 1, Ordering::SeqCst, Ordering::Relaxed)
+                .is_ok();
+            self.bottom.store(b + 1, Ordering::Relaxed);
+            return won.then_some(job);
+        }
+        Some(job)
+    }
+
+    fn steal(&self) -> Steal {
+        let t = self.top.load(Ordering::Acquire);
+        fence(Ordering::SeqCst);
+        let b = self.bottom.load(Ordering::Acquire);
+
+        if t >= b {
+            return Steal::Empty;
+        }
+        let job = self.slot(t).load();
+        match self.top.compare_exchange(t, t + 1, Ordering::SeqCst, Ordering::Relaxed) {
+            Ok(_) => Steal::Success(job),
+            Err(_) => Steal::Retry,
+        }
+    }
+}
+
+struct Registry {
+    deques: Vec<Deque>,
+    // jobs submitted from outside the pool by `install`
+    injector: Mutex<VecDeque<JobRef>>,
+    injected: AtomicUsize,
+    sleep: Mutex<()>,
+    wakeup: Condvar,
+    sleeping: AtomicUsize,
+    shutdown: AtomicBool,
+}
+
+impl Registry {
+    fn inject(&self, job: JobRef) {
+        self.injector.lock().unwrap().push_back(job);
+        self.injected.fetch_add(1, Ordering::SeqCst);
+        self.wakeup.notify_all();
+    }
+
+    fn pop_injected(&self) -> Option<JobRef> {
+        if self.injected.load(Ordering::SeqCst) == 0 {
+            return None;
+        }
+        let job = self.injector.lock().unwrap().pop_front();
+        if job.is_some() {
+            self.injected.fetch_sub(1, Ordering::SeqCst);
+        }
+        job
+    }
+
+    // a missed wakeup only costs one sleep timeout
+    fn notify(&self) {
+        if self.sleeping.load(Ordering::SeqCst) > 0 {
+            self.wakeup.notify_one();
+        }
+    }
+
+    fn sleep(&self) {
+        let guard = self.sleep.lock().unwrap();
+        self.sleeping.fetch_add(1, Ordering::SeqCst);
+        if self.injected.load(Ordering::SeqCst) == 0 && !self.shutdown.load(Ordering::SeqCst) {
+            let _ = self.wakeup.wait_timeout(guard, Duration::from_millis(1)).unwrap();
+        }
+        self.sleeping.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+thread_local! {
+    static WORKER: Cell<*const WorkerThread> = const { Cell::new(ptr::null()) };
+    static LOCK_LATCH: LockLatch = LockLatch::new();
+}
+
+struct WorkerThread {
+    index: usize,
+    registry: Arc<Registry>,
+    rng: Cell<u64>,
+}
+
+impl WorkerThread {
+    fn current() -> Option<&'static WorkerThread> {
+        let worker = WORKER.with(Cell::get);
+        // SAFETY: set only while the worker's main loop (which owns the
+        // WorkerThread) runs on this very thread
+        unsafe { worker.as_ref() }
+    }
+
+    fn deque(&self) -> &Deque {
+        &self.registry.deques[self.index]
+    }
+
+    fn push(&self, job: JobRef) {
+        match self.deque().push(job) {
+            Ok(()) => self.registry.notify(),
+            // SAFETY: the job was never published, so this is its only run
+            Err(job) => unsafe { job.execute() },
+        }
+    }
+
+    fn find_work(&self) -> Option<JobRef> {
+        self.deque()
+            .pop()
+            .or_else(|| self.steal())
+            .or_else(|| self.registry.pop_injected())
+    }
+
+    fn steal(&self) -> Option<JobRef> {
+        let deques = &self.registry.deques;
+        if deques.len() < 2 {
+            return None;
+        }
+        loop {
+            let mut retry = false;
+            let start = self.next_random() as usize % deques.len();
+            for victim in (start..start + deques.len()).map(|v| v % deques.len()) {
+                if victim == self.index {
+                    continue;
+                }
+                match deques[victim].steal() {
+                    Steal::Success(job) => return Some(job),
+                    Steal::Retry => retry = true,
+                    Steal::Empty => {}
+                }
+            }
+            if !retry {
+                return None;
+            }
+        }
+    }
+
+    // xorshift64
+    fn next_random(&self) -> u64 {
+        let mut x = self.rng.get();
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.rng.set(x);
+        x
+    }
+
+    // keep executing other jobs until `done` holds
+    fn wait_until(&self, done: impl Fn() -> bool) {
+        while !done() {
+            match self.find_work() {
+                // SAFETY: a job taken from a deque or the injector runs exactly once
+                Some(job) => unsafe { job.execute() },
+                None => thread::yield_now(),
+            }
+        }
+    }
+}
+
+fn main_loop(registry: Arc<Registry>, index: usize) {
+    let worker = WorkerThread {
+        index,
+        registry,
+        rng: Cell::new(0x9E37_79B9_7F4A_7C15 ^ (index as u64 + 1)),
+    };
+    WORKER.with(|w| w.set(&worker));
+
+    let mut idle = 0;
+    loop {
+        if let Some(job) = worker.find_work() {
+            // SAFETY: as in wait_until
+            unsafe { job.execute() };
+            idle = 0;
+            continue;
+        }
+        if worker.registry.shutdown.load(Ordering::Acquire) {
+            break;
+        }
+        idle += 1;
+        if idle < SPIN_ROUNDS {
+            thread::yield_now();
+        } else {
+            worker.registry.sleep();
+            idle = 0;
+        }
+    }
+
+    WORKER.with(|w| w.set(ptr::null()));
+}
+
+trait Latch {
+    fn set(&self);
+}
+
+struct SpinLatch(AtomicBool);
+
+impl SpinLatch {
+    fn new() -> Self {
+        SpinLatch(AtomicBool::new(false))
+    }
+
+    fn probe(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+}
+
+impl Latch for SpinLatch {
+    fn set(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+}
+
+// blocking latch for threads outside the pool; thread-local, so it outlives
+// the setter's last access to it
+struct LockLatch {
+    done: Mutex<bool>,
+    cond: Condvar,
+}
+
+impl LockLatch {
+    fn new() -> Self {
+        LockLatch {
+            done: Mutex::new(false),
+            cond: Condvar::new(),
+        }
+    }
+
+    fn reset(&self) {
+        *self.done.lock().unwrap() = false;
+    }
+
+    fn wait(&self) {
+        let mut done = self.done.lock().unwrap();
+        while !*done {
+            done = self.cond.wait(done).unwrap();
+        }
+    }
+}
+
+impl Latch for &LockLatch {
+    fn set(&self) {
+        *self.done.lock().unwrap() = true;
+        self.cond.notify_all();
+    }
+}
+
+// a closure and its result slot living on the stack of the thread that waits for it
+struct StackJob<L, F, R> {
+    latch: L,
+    func: UnsafeCell<Option<F>>,
+    result: UnsafeCell<Option<thread::Result<R>>>,
+}
+
+impl<L: Latch, F: FnOnce() -> R, R> StackJob<L, F, R> {
+    fn new(func: F, latch: L) -> Self {
+        StackJob {
+            latch,
+            func: UnsafeCell::new(Some(func)),
+            result: UnsafeCell::new(None),
+        }
+    }
+
+    fn as_job_ref(&self) -> JobRef {
+        JobRef {
+            data: self as *const Self as *const (),
+            execute: Self::execute,
+        }
+    }
+
+    unsafe fn execute(data: *const ()) {
+        let this = &*(data as *const Self);
+        let func = (*this.func.get()).take().unwrap();
+        *this.result.get() = Some(panic::catch_unwind(AssertUnwindSafe(func)));
+        // the owner may free the job as soon as the latch is set
+        this.latch.set();
+    }
+
+    fn run_inline(self) -> R {
+        self.func.into_inner().unwrap()()
+    }
+
+    fn into_result(self) -> R {
+        match self.result.into_inner().unwrap() {
+            Ok(r) => r,
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    }
+}
+
+pub struct Scheduler {
+    registry: Arc<Registry>,
+    workers: Vec<thread::JoinHandle<()>>,
+}
+
+impl Scheduler {
+    pub fn new(threads: usize) -> Self {
+        let registry = Arc::new(Registry {
+            deques: (0..threads).map(|_| Deque::new()).collect(),
+            injector: Mutex::new(VecDeque::new()),
+            injected: AtomicUsize::new(0),
+            sleep: Mutex::new(()),
+            wakeup: Condvar::new(),
+            sleeping: AtomicUsize::new(0),
+            shutdown: AtomicBool::new(false),
+        });
+
+        let workers = (0..threads)
+            .map(|index| {
+                let registry = Arc::clone(&registry);
+                thread::spawn(move || main_loop(registry, index))
+            })
+            .collect();
+
+        Scheduler { registry, workers }
+    }
+
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Runs `f` on one of the scheduler's workers and returns its result;
+    /// `join` and `scope` inside `f` use this scheduler.
+    pub fn install<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce() -> R + Send,
+        R: Send,
+    {
+        if let Some(worker) = WorkerThread::current() {
+            if Arc::ptr_eq(&worker.registry, &self.registry) {
+                return f();
+            }
+        }
+
+        LOCK_LATCH.with(|latch| {
+            latch.reset();
+            let job = StackJob::new(f, latch);
+            self.registry.inject(job.as_job_ref());
+            latch.wait();
+            job.into_result()
+        })
+    }
+}
+
+impl Drop for Scheduler {
+    fn drop(&mut self) {
+        self.registry.shutdown.store(true, Ordering::Release);
+        self.registry.wakeup.notify_all();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Workers of the scheduler the caller runs on, 1 outside any scheduler.
+pub fn current_num_threads() -> usize {
+    WorkerThread::current().map_or(1, |worker| worker.registry.deques.len())
+}
+
+/// Runs `a` and `b`, potentially in parallel: `b` is offered to thieves
+/// while the calling worker runs `a`.
+pub fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA + Send,
+    B: FnOnce() -> RB + Send,
+    RA: Send,
+    RB: Send,
+{
+    let Some(worker) = WorkerThread::current() else {
+        return (a(), b());
+    };
+
+    let job_b = StackJob::new(b, SpinLatch::new());
+    let job_b_ref = job_b.as_job_ref();
+    worker.push(job_b_ref);
+
+    let ra = match panic::catch_unwind(AssertUnwindSafe(a)) {
+        Ok(ra) => ra,
+        Err(payload) => {
+            // b may still point into this frame
+            worker.wait_until(|| job_b.latch.probe());
+            panic::resume_unwind(payload);
+        }
+    };
+
+    while !job_b.latch.probe() {
+        match worker.deque().pop() {
+            // not stolen: run it here without the result round-trip
+            Some(job) if ptr::eq(job.data, job_b_ref.data) => return (ra, job_b.run_inline()),
+            // SAFETY: popped from our own deque, so not run anywhere else
+            Some(job) => unsafe { job.execute() },
+            None => {
+                worker.wait_until(|| job_b.latch.probe());
+                break;
+            }
+        }
+    }
+    (ra, job_b.into_result())
+}
+
+pub struct Scope<'scope> {
+    pending: AtomicUsize,
+    panic: Mutex<Option<Box<dyn Any + Send>>>,
+    marker: PhantomData<&'scope mut &'scope ()>,
+}
+
+/// Runs `op` and waits for every job it (transitively) spawns.
+pub fn scope<'scope, OP, R>(op: OP) -> R
+where
+    OP: FnOnce(&Scope<'scope>) -> R,
+{
+    let scope = Scope {
+        // the body itself counts as one pending job
+        pending: AtomicUsize::new(1),
+        panic: Mutex::new(None),
+        marker: PhantomData,
+    };
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| op(&scope)));
+    scope.job_done();
+    if let Some(worker) = WorkerThread::current() {
+        worker.wait_until(|| scope.pending.load(Ordering::Acquire) == 0);
+    }
+
+    if let Some(payload) = scope.panic.lock().unwrap().take() {
+        panic::resume_unwind(payload);
+    }
+    result.unwrap_or_else(|payload| panic::resume_unwind(payload))
+}
+
+impl<'scope> Scope<'scope> {
+    pub fn spawn<F>(&self, f: F)
+    where
+        F: FnOnce(&Scope<'scope>) + Send + 'scope,
+    {
+        self.pending.fetch_add(1, Ordering::Relaxed);
+        let job = Box::new(HeapJob { scope: self, func: f });
+        let job = JobRef {
+            data: Box::into_raw(job) as *const (),
+            execute: HeapJob::<'scope, F>::execute,
+        };
+        match WorkerThread::current() {
+            Some(worker) => worker.push(job),
+            // SAFETY: never published, runs exactly once here
+            None => unsafe { job.execute() },
+        }
+    }
+
+    fn job_done(&self) {
+        self.pending.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+struct HeapJob<'scope, F> {
+    scope: *const Scope<'scope>,
+    func: F,
+}
+
+impl<'scope, F: FnOnce(&Scope<'scope>)> HeapJob<'scope, F> {
+    unsafe fn execute(data: *const ()) {
+        let HeapJob { scope, func } = *Box::from_raw(data as *mut Self);
+        // the scope outlives its jobs: `scope()` waits for pending == 0
+        let scope = &*scope;
+        if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| func(scope))) {
+            scope.panic.lock().unwrap().get_or_insert(payload);
+        }
+        scope.job_done();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fib(n: u64) -> u64 {
+        if n < 2 {
+            return n;
+        }
+        let (a, b) = join(|| fib(n - 1), || fib(n - 2));
+        a + b
+    }
+
+    #[test]
+    fn test_join_and_scope() {
+        let scheduler = Scheduler::new(4);
+        assert_eq!(scheduler.install(|| fib(20)), 6765);
+        // outside install everything runs inline
+        assert_eq!(fib(10), 55);
+
+        let counter = AtomicUsize::new(0);
+        scheduler.install(|| {
+            scope(|s| {
+                for _ in 0..100 {
+                    s.spawn(|s| {
+                        for _ in 0..10 {
+                            s.spawn(|_| {
+                                counter.fetch_add(1, Ordering::Relaxed);
+                            });
+                        }
+                    });
+                }
+            })
+        });
+        assert_eq!(counter.load(Ordering::Relaxed), 1000);
+    }
+
+    #[test]
+    fn test_panic_propagates() {
+        let scheduler = Scheduler::new(2);
+        let joined = panic::catch_unwind(AssertUnwindSafe(|| {
+            scheduler.install(|| join(|| 1, || -> i32 { panic!("job b failed") }))
+        }));
+        assert!(joined.is_err());
+
+        let scoped = panic::catch_unwind(AssertUnwindSafe(|| {
+            scheduler.install(|| scope(|s| s.spawn(|_| panic!("spawned job failed"))))
+        }));
+        assert!(scoped.is_err());
+
+        // the workers survive and keep serving jobs
+        assert_eq!(scheduler.install(|| join(|| 2, || 3)), (2, 3));
+    }
+}
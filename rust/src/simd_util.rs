@@ -0,0 +1,93 @@
+// Vectorization scaffolding shared by the --simd kernel paths (nightly std::simd)
+// Kernels only supply the per-vector arithmetic; the helpers here take care
+// of splitting slices into lanes, the partial last vector and the final
+// horizontal reduction.
+//   lanes_exact  full N-lane vectors plus the scalar remainder
+//   load_prefix  up to N leading elements, missing lanes zeroed
+//   store_prefix masked store of the leading lanes that fit
+//   update       read-modify-write a slice vector by vector, tail included
+//   sum          horizontal sum of a float slice
+
+use std::iter::Sum;
+use std::ops::AddAssign;
+use std::simd::prelude::*;
+use std::simd::SimdElement;
+
+/// Splits `data` into full N-lane vectors and the elements left over.
+/// For kernels where a zero-padded lane would change the result.
+pub fn lanes_exact<T, const N: usize>(data: &[T]) -> (impl Iterator<Item = Simd<T, N>> + '_, &[T])
+where
+    T: SimdElement,
+{
+    let split = data.len() - data.len() % N;
+    let (body, tail) = data.split_at(split);
+    (body.chunks_exact(N).map(Simd::from_slice), tail)
+}
+
+/// Loads the first min(N, len) elements of `data`; missing lanes are zero.
+pub fn load_prefix<T, const N: usize>(data: &[T]) -> Simd<T, N>
+where
+    T: SimdElement + Default,
+{
+    Simd::load_or_default(&data[..data.len().min(N)])
+}
+
+/// Stores the first min(N, len) lanes of `v` into `out`, leaving the rest of
+/// `out` untouched.
+pub fn store_prefix<T, const N: usize>(v: Simd<T, N>, out: &mut [T])
+where
+    T: SimdElement + Default,
+{
+    if out.len() >= N {
+        v.copy_to_slice(&mut out[..N]);
+    } else {
+        v.store_select(out, Mask::from_bitmask((1u64 << out.len()) - 1));
+    }
+}
+
+/// Replaces every N-wide piece of `data` with `f(offset, piece)`; the last,
+/// partial piece is zero-padded on load and masked on store.
+pub fn update<T, const N: usize, F>(data: &mut [T], mut f: F)
+where
+    T: SimdElement + Default,
+    F: FnMut(usize, Simd<T, N>) -> Simd<T, N>,
+{
+    for (i, piece) in data.chunks_mut(N).enumerate() {
+        let v = f(i * N, load_prefix(piece));
+        store_prefix(v, piece);
+    }
+}
+
+/// Horizontal sum of `data`, accumulated in N lanes and reduced once.
+pub fn sum<T, const N: usize>(data: &[T]) -> T
+where
+    T: SimdElement + Default + Sum<T> + Copy + std::ops::Add<Output = T>,
+    Simd<T, N>: SimdFloat<Scalar = T> + AddAssign,
+{
+    let (vectors, tail) = lanes_exact::<T, N>(data);
+    let mut acc = Simd::<T, N>::splat(T::default());
+    for v in vectors {
+        acc += v;
+    }
+    acc.reduce_sum() + tail.iter().copied().sum::<T>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_lanes() {
+        let data: Vec<f64> = (1..=11).map(|i| i as f64).collect();
+        assert_eq!(sum::<f64, 4>(&data), 66.0);
+
+        let (vectors, tail) = lanes_exact::<f64, 4>(&data);
+        assert_eq!(vectors.count(), 2);
+        assert_eq!(tail, &[9.0, 10.0, 11.0]);
+
+        // the masked tail store must not touch anything past the slice
+        let mut out = vec![0.0f64; 6];
+        update::<f64, 4, _>(&mut out[..5], |offset, v| v + f64x4::splat(offset as f64 + 1.0));
+        assert_eq!(out, vec![1.0, 1.0, 1.0, 1.0, 5.0, 0.0]);
+    }
+}
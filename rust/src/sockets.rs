@@ -0,0 +1,120 @@
+// Per-socket thread pools for cross-socket scaling
+// On a multi-socket machine a 16-thread run may span two sockets, and its
+// speedup then mixes core scaling with interconnect and remote-memory cost.
+// SocketPools builds one Rayon pool per socket, each worker pinned to a CPU
+// of its own socket, and gives every pool one contiguous block of the work,
+// so 1, 2, ... sockets can be measured on their own. The topology comes
+// from the Linux sysfs; elsewhere every CPU counts as one socket.
+
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+use std::fs;
+
+/// Online CPU ids grouped by physical package, in package order.
+pub fn topology() -> Vec<Vec<usize>> {
+    let mut packages: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    if let Some(cpus) = online_cpus() {
+        for cpu in cpus {
+            let path = format!("/sys/devices/system/cpu/cpu{}/topology/physical_package_id", cpu);
+            if let Ok(package) = fs::read_to_string(path) {
+                packages.entry(package.trim().to_string()).or_default().push(cpu);
+            }
+        }
+    }
+    if packages.is_empty() {
+        return vec![(0..crate::threads::logical()).collect()];
+    }
+    packages.into_values().collect()
+}
+
+// /sys/devices/system/cpu/online, e.g. "0-15,32-47"
+fn online_cpus() -> Option<Vec<usize>> {
+    let list = fs::read_to_string("/sys/devices/system/cpu/online").ok()?;
+    let mut cpus = Vec::new();
+    for range in list.trim().split(',') {
+        match range.split_once('-') {
+            Some((a, b)) => cpus.extend(a.parse::<usize>().ok()?..=b.parse::<usize>().ok()?),
+            None => cpus.push(range.parse().ok()?),
+        }
+    }
+    Some(cpus)
+}
+
+pub struct SocketPools {
+    pools: Vec<rayon::ThreadPool>,
+    threads_per_socket: usize,
+}
+
+impl SocketPools {
+    /// One pool per entry of `sockets` with `threads_per_socket` workers,
+    /// worker i pinned to CPU i (mod the socket's CPUs) of its socket.
+    pub fn new(sockets: &[Vec<usize>], threads_per_socket: usize) -> Self {
+        let pools = sockets
+            .iter()
+            .map(|cpus| {
+                let cpus = cpus.clone();
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads_per_socket)
+                    .start_handler(move |i| {
+                        core_affinity::set_for_current(core_affinity::CoreId { id: cpus[i % cpus.len()] });
+                    })
+                    .build()
+                    .unwrap()
+            })
+            .collect();
+        SocketPools { pools, threads_per_socket }
+    }
+
+    pub fn sockets(&self) -> usize {
+        self.pools.len()
+    }
+
+    pub fn threads(&self) -> usize {
+        self.pools.len() * self.threads_per_socket
+    }
+
+    /// Calls `f(start, chunk)` for every `chunk`-sized piece of `data`; each
+    /// socket works through its own contiguous share of whole pieces.
+    pub fn for_each_chunk_mut<T, F>(&self, data: &mut [T], chunk: usize, f: F)
+    where
+        T: Send,
+        F: Fn(usize, &mut [T]) + Sync,
+    {
+        let share = data.len().div_ceil(chunk).div_ceil(self.pools.len()).max(1) * chunk;
+        let f = &f;
+        std::thread::scope(|s| {
+            for (socket, (pool, block)) in self.pools.iter().zip(data.chunks_mut(share)).enumerate() {
+                s.spawn(move || {
+                    pool.install(|| {
+                        block
+                            .par_chunks_mut(chunk)
+                            .enumerate()
+                            .for_each(|(i, c)| f(socket * share + i * chunk, c));
+                    })
+                });
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocks_cover_data() {
+        let sockets = topology();
+        assert!(!sockets.is_empty() && sockets.iter().all(|cpus| !cpus.is_empty()));
+
+        // the same socket twice stands in for a two-socket machine
+        let pools = SocketPools::new(&[sockets[0].clone(), sockets[0].clone()], 2);
+        assert_eq!(pools.threads(), 4);
+        let mut data = vec![0usize; 1001];
+        pools.for_each_chunk_mut(&mut data, 7, |start, chunk| {
+            for (i, v) in chunk.iter_mut().enumerate() {
+                *v += start + i;
+            }
+        });
+        assert!(data.iter().enumerate().all(|(i, &v)| v == i));
+    }
+}
@@ -0,0 +1,307 @@
+// Repeated timings (--reps, --warmup, --outliers)
+// A cell runs `warmup` untimed passes of the full workload in the warmup
+// phase (caches, page tables, pool threads and branch predictors settle;
+// --warmup 0 measures the cold start), then `reps` timed ones in the measure
+// phase; its records
+// report the best time under the metric's own name, as the best-of-N
+// benchmarks always have, and with more than one rep add
+//   <name>_mean, _median, _stddev   of the runs kept by the outlier filter
+//   <name>_ci95                     half-width of the 95% confidence interval
+//                                   of the mean (Student's t)
+//   <name>_runs, _kept              timed runs, and those the filter kept
+// The filter drops runs outside [Q1 - 1.5 IQR, Q3 + 1.5 IQR] (iqr, the
+// default) or more than 3 scaled MADs from the median (mad); it needs at
+// least 4 runs and never touches the best time. With the default single rep
+// records are unchanged. The measure phase is also metered as a whole: the
+// stats carry its energy per run where RAPL is readable (energy.rs) and its
+// peak RSS (rss.rs).
+
+use crate::alloc_track::{AllocStats, Region};
+use crate::energy::{Energy, Meter};
+use crate::logging::Phase;
+use crate::rss::Peak;
+use crate::timer::Timer;
+use crate::results::Record;
+
+/// Summary of the timed runs of one cell.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub reps: usize,
+    /// runs left after the outlier filter
+    pub kept: usize,
+    /// the best run, filtered or not
+    pub min: f64,
+    pub mean: f64,
+    pub median: f64,
+    /// sample standard deviation, 0 for a single run
+    pub stddev: f64,
+    /// half-width of the 95% confidence interval of the mean, 0 for a
+    /// single run
+    pub ci95: f64,
+    /// energy of the timed runs, when RAPL could be read
+    pub energy: Option<Energy>,
+    /// peak resident bytes during the timed runs
+    pub peak_rss: Option<u64>,
+}
+
+impl Stats {
+    pub fn of(samples: &[f64], filter: Filter) -> Stats {
+        assert!(!samples.is_empty(), "no samples");
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let kept = filter.keep(&sorted);
+        let n = kept.len();
+        let mean = kept.iter().sum::<f64>() / n as f64;
+        let stddev = if n > 1 { (kept.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1) as f64).sqrt() } else { 0.0 };
+        let ci95 = if n > 1 { t95(n - 1) * stddev / (n as f64).sqrt() } else { 0.0 };
+        Stats { reps: sorted.len(), kept: n, min: sorted[0], mean, median: quantile(&kept, 0.5), stddev, ci95, energy: None, peak_rss: None }
+    }
+
+    /// " (best of R; mean .. ± .., median .., stddev ..)" for console lines
+    /// in seconds, noting dropped outliers; empty for a single run.
+    pub fn summary(&self) -> String {
+        if self.reps == 1 {
+            return String::new();
+        }
+        let dropped = match self.reps - self.kept {
+            0 => String::new(),
+            k => format!("; {} outlier(s) dropped", k),
+        };
+        format!(" (best of {}; mean {:.6} ± {:.6}, median {:.6}, stddev {:.6}{})", self.reps, self.mean, self.ci95, self.median, self.stddev, dropped)
+    }
+
+    /// The same summary in another unit, e.g. seconds to ns per operation.
+    pub fn scaled(self, factor: f64) -> Stats {
+        Stats {
+            min: self.min * factor,
+            mean: self.mean * factor,
+            median: self.median * factor,
+            stddev: self.stddev * factor,
+            ci95: self.ci95 * factor,
+            ..self
+        }
+    }
+}
+
+/// Outlier rejection applied to the timed runs (--outliers).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter {
+    None,
+    Iqr,
+    Mad,
+}
+
+impl Filter {
+    /// Fewer runs than this are never filtered.
+    pub const MIN_RUNS: usize = 4;
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "none" => Ok(Filter::None),
+            "iqr" => Ok(Filter::Iqr),
+            "mad" => Ok(Filter::Mad),
+            _ => Err(format!("unknown outlier filter: {} (use none|iqr|mad)", s)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Filter::None => "none",
+            Filter::Iqr => "iqr",
+            Filter::Mad => "mad",
+        }
+    }
+
+    /// The samples (sorted ascending) that are not outliers.
+    pub fn keep(self, sorted: &[f64]) -> Vec<f64> {
+        if sorted.len() < Self::MIN_RUNS {
+            return sorted.to_vec();
+        }
+        let (low, high) = match self {
+            Filter::None => return sorted.to_vec(),
+            Filter::Iqr => {
+                let (q1, q3) = (quantile(sorted, 0.25), quantile(sorted, 0.75));
+                (q1 - 1.5 * (q3 - q1), q3 + 1.5 * (q3 - q1))
+            }
+            Filter::Mad => {
+                let median = quantile(sorted, 0.5);
+                let mut deviations: Vec<f64> = sorted.iter().map(|x| (x - median).abs()).collect();
+                deviations.sort_by(f64::total_cmp);
+                // 1.4826 MAD estimates the standard deviation of normal data;
+                // a MAD of 0 gives no spread to judge by, so nothing is dropped
+                let limit = 3.0 * 1.4826 * quantile(&deviations, 0.5);
+                if limit == 0.0 {
+                    return sorted.to_vec();
+                }
+                (median - limit, median + limit)
+            }
+        };
+        sorted.iter().copied().filter(|x| (low..=high).contains(x)).collect()
+    }
+}
+
+// quantile of sorted samples, interpolating between neighbours
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let (below, fraction) = (position.floor() as usize, position.fract());
+    match sorted.get(below + 1) {
+        Some(above) => sorted[below] + fraction * (above - sorted[below]),
+        None => sorted[below],
+    }
+}
+
+// two-sided 95% quantile of Student's t with `dof` degrees of freedom; past
+// 30 the next smaller tabulated dof, which errs on the wide side
+fn t95(dof: usize) -> f64 {
+    const TABLE: [f64; 30] = [
+        12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160, 2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086,
+        2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042,
+    ];
+    match dof {
+        0 => f64::NAN,
+        1..=30 => TABLE[dof - 1],
+        31..=40 => 2.042,
+        41..=60 => 2.021,
+        61..=120 => 2.000,
+        _ => 1.980,
+    }
+}
+
+/// How often a cell is timed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Repeat {
+    pub reps: usize,
+    pub warmup: usize,
+    pub filter: Filter,
+    /// the clock `measure` times each run with (--timer)
+    pub timer: Timer,
+}
+
+impl Repeat {
+    pub const ONCE: Repeat = Repeat { reps: 1, warmup: 0, filter: Filter::None, timer: Timer::Instant };
+
+    /// Runs `f` `warmup` times in the warmup phase and discards what it
+    /// returns.
+    pub fn warm_up<T>(self, mut f: impl FnMut() -> T) {
+        if self.warmup > 0 {
+            Phase::Warmup.run(|| {
+                for _ in 0..self.warmup {
+                    let _ = f();
+                }
+            });
+        }
+    }
+
+    /// Warms up with `f` (which returns its time in seconds and its output),
+    /// then runs it `reps` times in the measure phase; returns the
+    /// statistics of the timed runs (with their energy and peak RSS) and the
+    /// output of the fastest one.
+    pub fn run<T>(self, mut f: impl FnMut() -> (f64, T)) -> (Stats, T) {
+        self.warm_up(&mut f);
+        let _measure = Phase::Measure.span().entered();
+        let meter = Meter::start();
+        let peak = Peak::start();
+        let mut times = Vec::with_capacity(self.reps);
+        let mut best: Option<(f64, T)> = None;
+        for _ in 0..self.reps.max(1) {
+            let (time, output) = f();
+            times.push(time);
+            if best.as_ref().is_none_or(|(t, _)| time < *t) {
+                best = Some((time, output));
+            }
+        }
+        let stats = Stats { energy: meter.finish(times.len()), peak_rss: peak.finish(), ..self.stats(&times) };
+        (stats, best.expect("at least one timed run").1)
+    }
+
+    /// The statistics of a cell's times, with this repeat's filter.
+    pub fn stats(self, samples: &[f64]) -> Stats {
+        Stats::of(samples, self.filter)
+    }
+
+    /// `run` with the timing done here: each run of `f` is timed with the
+    /// repeat's timer and counted in an `alloc_track::Region`; returns the
+    /// statistics, and the output and allocations of the fastest run.
+    pub fn measure<T>(self, mut f: impl FnMut() -> T) -> (Stats, T, Option<AllocStats>) {
+        self.timer.calibrate();
+        let (stats, (output, allocs)) = self.run(|| {
+            let region = Region::start();
+            let stopwatch = self.timer.start();
+            let output = f();
+            let time = stopwatch.elapsed();
+            (time, (output, region.finish()))
+        });
+        (stats, output, allocs)
+    }
+}
+
+impl Record {
+    /// Adds a timing: the best run as `name`, and with more than one rep the
+    /// statistics of the kept runs and the run counts.
+    pub fn timing(self, name: &str, stats: &Stats, unit: &str) -> Self {
+        let record = self.metric(name, stats.min, unit);
+        if stats.reps == 1 {
+            return record;
+        }
+        record
+            .metric(&format!("{}_mean", name), stats.mean, unit)
+            .metric(&format!("{}_median", name), stats.median, unit)
+            .metric(&format!("{}_stddev", name), stats.stddev, unit)
+            .metric(&format!("{}_ci95", name), stats.ci95, unit)
+            .metric(&format!("{}_runs", name), stats.reps as f64, "runs")
+            .metric(&format!("{}_kept", name), stats.kept as f64, "runs")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let stats = Stats::of(&[4.0, 1.0, 3.0, 2.0], Filter::None);
+        assert_eq!((stats.reps, stats.kept, stats.min, stats.mean, stats.median), (4, 4, 1.0, 2.5, 2.5));
+        assert!((stats.stddev - (5.0f64 / 3.0).sqrt()).abs() < 1e-12);
+        assert!((stats.ci95 - 3.182 * stats.stddev / 2.0).abs() < 1e-12);
+        assert_eq!(Stats::of(&[7.0, 1.0, 3.0], Filter::None).median, 3.0);
+        assert_eq!(Stats::of(&[2.0], Filter::Iqr).ci95, 0.0);
+        assert_eq!(Stats::of(&[2.0, 4.0], Filter::None).scaled(1e3).mean, 3e3);
+        assert_eq!(t95(120), 2.000);
+    }
+
+    #[test]
+    fn test_filters() {
+        let times = [1.00, 1.02, 0.98, 1.01, 0.99, 1.03, 5.0];
+        for filter in [Filter::Iqr, Filter::Mad] {
+            let stats = Stats::of(&times, filter);
+            assert_eq!((stats.reps, stats.kept), (7, 6), "{:?}", filter);
+            assert!((stats.mean - 1.005).abs() < 1e-12);
+        }
+        assert_eq!(Stats::of(&times, Filter::None).kept, 7);
+        // too few runs to judge, and no spread to judge by
+        assert_eq!(Stats::of(&[1.0, 1.0, 9.0], Filter::Iqr).kept, 3);
+        assert_eq!(Stats::of(&[1.0, 1.0, 1.0, 9.0, 1.0], Filter::Mad).kept, 5);
+        // the best run is reported even when it is the outlier
+        assert_eq!(Stats::of(&[0.1, 1.00, 1.01, 1.02, 0.99], Filter::Iqr).min, 0.1);
+        assert_eq!(Filter::parse("mad"), Ok(Filter::Mad));
+        assert!(Filter::parse("sigma").is_err());
+    }
+
+    #[test]
+    fn test_repeat() {
+        let mut calls = 0;
+        let times = [9.0, 3.0, 1.0, 2.0];
+        let (stats, output) = Repeat { reps: 3, warmup: 1, filter: Filter::Iqr, timer: Timer::Instant }.run(|| {
+            calls += 1;
+            (times[calls - 1], calls)
+        });
+        // the warm-up's 9.0 is discarded; the fastest run was the third call
+        assert_eq!((calls, output, stats.reps, stats.min, stats.mean), (4, 3, 3, 1.0, 2.0));
+
+        let record = Record::new("k", "rust").timing("time", &stats, "sec");
+        let names: Vec<&str> = record.metrics.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["time", "time_mean", "time_median", "time_stddev", "time_ci95", "time_runs", "time_kept"]);
+        let once = Record::new("k", "rust").timing("time", &Repeat::ONCE.run(|| (1.0, ())).0, "sec");
+        assert_eq!(once.metrics.len(), 1);
+    }
+}
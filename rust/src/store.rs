@@ -0,0 +1,151 @@
+// SQLite result store (--store FILE, --features store)
+// Every run appends to one database, so sweeps from months of work can be
+// queried together. A reporter opens the file (creating the schema if
+// needed), adds one `runs` row for its process and one `records` row per
+// record, with its params and metrics beside it:
+//   runs     id, started (UTC), git_commit, command, metadata (JSON)
+//   records  id, run_id, recorded (UTC), kernel, impl, label
+//   params   record_id, name, value
+//   metrics  record_id, name, value, unit
+// The `results` view joins them to one row per metric, e.g.
+//   SELECT git_commit, label, value FROM results
+//   WHERE kernel = 'matmul' AND metric = 'time' ORDER BY started;
+// With --isolate every benchmark binary stores its own run.
+
+use crate::metadata::Metadata;
+use crate::results::Record;
+
+#[cfg(feature = "store")]
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    started TEXT NOT NULL,
+    git_commit TEXT NOT NULL,
+    command TEXT NOT NULL,
+    metadata TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS records (
+    id INTEGER PRIMARY KEY,
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    recorded TEXT NOT NULL,
+    kernel TEXT NOT NULL,
+    impl TEXT NOT NULL,
+    label TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS params (
+    record_id INTEGER NOT NULL REFERENCES records(id),
+    name TEXT NOT NULL,
+    value TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS metrics (
+    record_id INTEGER NOT NULL REFERENCES records(id),
+    name TEXT NOT NULL,
+    value REAL NOT NULL,
+    unit TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS records_kernel ON records(kernel, label);
+CREATE INDEX IF NOT EXISTS params_record ON params(record_id);
+CREATE INDEX IF NOT EXISTS metrics_record ON metrics(record_id);
+CREATE VIEW IF NOT EXISTS results AS
+    SELECT runs.id AS run_id, runs.started, runs.git_commit, records.kernel, records.impl, records.label,
+           metrics.name AS metric, metrics.value, metrics.unit
+    FROM metrics JOIN records ON records.id = metrics.record_id JOIN runs ON runs.id = records.run_id;
+";
+
+#[cfg(feature = "store")]
+const NOW: &str = "strftime('%Y-%m-%dT%H:%M:%SZ', 'now')";
+
+/// An open result database and the run this process adds to it.
+pub struct Store {
+    path: String,
+    #[cfg(feature = "store")]
+    connection: rusqlite::Connection,
+    #[cfg(feature = "store")]
+    run: i64,
+}
+
+impl Store {
+    /// Opens (or creates) the database at `path` and starts a run of
+    /// `command` on the machine and build of `metadata`.
+    #[cfg(feature = "store")]
+    pub fn open(path: &str, metadata: &Metadata, command: &str) -> Result<Self, String> {
+        let error = |e: rusqlite::Error| format!("{}: {}", path, e);
+        let connection = rusqlite::Connection::open(path).map_err(error)?;
+        connection.execute_batch(SCHEMA).map_err(error)?;
+        let metadata_json = serde_json::to_string(metadata).expect("metadata serialization cannot fail");
+        connection
+            .execute(
+                &format!("INSERT INTO runs (started, git_commit, command, metadata) VALUES ({}, ?1, ?2, ?3)", NOW),
+                (&metadata.git_commit, command, &metadata_json),
+            )
+            .map_err(error)?;
+        let run = connection.last_insert_rowid();
+        Ok(Store { path: path.to_string(), connection, run })
+    }
+
+    #[cfg(not(feature = "store"))]
+    pub fn open(path: &str, _metadata: &Metadata, _command: &str) -> Result<Self, String> {
+        Err(format!("--store {} needs a build with --features store", path))
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Adds `record` to the run, all of it or nothing.
+    #[cfg(feature = "store")]
+    pub fn insert(&mut self, record: &Record) -> Result<(), String> {
+        let insert = |connection: &mut rusqlite::Connection| -> rusqlite::Result<()> {
+            let transaction = connection.transaction()?;
+            transaction.execute(
+                &format!("INSERT INTO records (run_id, recorded, kernel, impl, label) VALUES (?1, {}, ?2, ?3, ?4)", NOW),
+                (self.run, &record.kernel, &record.implementation, record.label()),
+            )?;
+            let id = transaction.last_insert_rowid();
+            for (name, value) in &record.params {
+                transaction.execute("INSERT INTO params (record_id, name, value) VALUES (?1, ?2, ?3)", (id, name, value))?;
+            }
+            for metric in &record.metrics {
+                transaction.execute(
+                    "INSERT INTO metrics (record_id, name, value, unit) VALUES (?1, ?2, ?3, ?4)",
+                    (id, &metric.name, metric.value, &metric.unit),
+                )?;
+            }
+            transaction.commit()
+        };
+        insert(&mut self.connection).map_err(|e| e.to_string())
+    }
+
+    #[cfg(not(feature = "store"))]
+    pub fn insert(&mut self, _record: &Record) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "store"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store() {
+        let path = std::env::temp_dir().join(format!("store-{}.db", std::process::id()));
+        let path = path.to_str().unwrap();
+        let metadata = Metadata::collect();
+        let record = Record::new("matmul", "rayon").param("n", 64).param("T", 2).metric("time", 0.5, "sec").metric("speedup", 1.9, "x");
+        for _ in 0..2 {
+            let mut store = Store::open(path, &metadata, "matrix_multiply --size 64").unwrap();
+            store.insert(&record).unwrap();
+        }
+
+        let connection = rusqlite::Connection::open(path).unwrap();
+        let runs: i64 = connection.query_row("SELECT count(*) FROM runs", [], |r| r.get(0)).unwrap();
+        let (label, value): (String, f64) = connection
+            .query_row("SELECT label, value FROM results WHERE metric = 'time' AND run_id = 2", [], |r| Ok((r.get(0)?, r.get(1)?)))
+            .unwrap();
+        let threads: String = connection
+            .query_row("SELECT value FROM params JOIN records ON records.id = record_id WHERE run_id = 1 AND name = 'T'", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!((runs, label.as_str(), value, threads.as_str()), (2, "matmul,rayon,n=64,T=2", 0.5, "2"));
+        std::fs::remove_file(path).unwrap();
+    }
+}
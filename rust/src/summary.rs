@@ -0,0 +1,223 @@
+// Rust vs OpenMP summary (run_all_benchmarks summary FILE...)
+// The headline numbers of the comparison, from Rust and OpenMP result files
+// in any mix (json, jsonl or csv; runs with --openmp-dir or --ffi already
+// hold both). Every OpenMP timing is matched to the Rust records of its cell
+// as by openmp::pairs, then one row per kernel, variant, size, thread count,
+// Rust implementation and timing gives
+//   rust, openmp   the two best times
+//   rust/openmp    relative slowdown of Rust; below 1 Rust was faster
+//   eff            each side's parallel efficiency, t(T=1) / (T * t(T)),
+//                  where the same variant and size also ran at T=1
+// The variant is the params, besides T and the size (report::SIZE_PARAMS),
+// that take more than one value among the kernel's rows. Each kernel and
+// implementation ends with the geometric mean slowdown over its rows and
+// over those at its largest T.
+
+use crate::baseline::SUMMARIES;
+use crate::openmp::{self, Pair};
+use crate::report::SIZE_PARAMS;
+use crate::results::Record;
+
+/// One cell of the summary table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    pub kernel: String,
+    /// the params that tell the kernel's rows apart, "k=v k=v"
+    pub variant: String,
+    /// "n=512", when the kernel has a size param
+    pub size: Option<String>,
+    pub threads: Option<usize>,
+    pub implementation: String,
+    pub metric: String,
+    pub unit: String,
+    pub rust: f64,
+    pub openmp: f64,
+    pub rust_efficiency: Option<f64>,
+    pub openmp_efficiency: Option<f64>,
+}
+
+impl Row {
+    /// Rust time over OpenMP time.
+    pub fn slowdown(&self) -> f64 {
+        self.rust / self.openmp
+    }
+}
+
+fn value<'a>(pair: &'a Pair, name: &str) -> Option<&'a str> {
+    pair.params.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+}
+
+// the first size param of the pair, as "k=v"
+fn size(pair: &Pair) -> Option<String> {
+    SIZE_PARAMS.iter().find_map(|&name| value(pair, name).map(|v| format!("{}={}", name, v)))
+}
+
+// numeric order where the values are numbers ("n=1024" after "n=256")
+fn size_key(size: &Option<String>) -> (u64, String) {
+    let number = size.as_ref().and_then(|s| s.split_once('=')).and_then(|(_, v)| v.parse().ok()).unwrap_or(0);
+    (number, size.clone().unwrap_or_default())
+}
+
+/// The rows of `records`, in kernel, variant, size, metric, implementation
+/// and thread order.
+pub fn rows(records: &[Record]) -> Vec<Row> {
+    let pairs: Vec<Pair> = openmp::pairs(records).into_iter().filter(|p| !SUMMARIES.iter().any(|s| p.metric.ends_with(s))).collect();
+    let varies = |kernel: &str, name: &str| {
+        let mut values = pairs.iter().filter(|p| p.kernel == kernel).map(|p| value(p, name));
+        values.next().is_some_and(|first| values.any(|v| v != first))
+    };
+
+    let mut rows: Vec<Row> = Vec::new();
+    for pair in &pairs {
+        let size = size(pair);
+        let variant: Vec<String> = pair
+            .params
+            .iter()
+            .filter(|(k, _)| k != "T" && !SIZE_PARAMS.contains(&k.as_str()) && varies(&pair.kernel, k))
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect();
+        let row = Row {
+            kernel: pair.kernel.clone(),
+            variant: variant.join(" "),
+            size,
+            threads: value(pair, "T").and_then(|t| t.parse().ok()),
+            implementation: pair.implementation.clone(),
+            metric: pair.metric.clone(),
+            unit: pair.unit.clone(),
+            rust: pair.rust,
+            openmp: pair.openmp,
+            rust_efficiency: None,
+            openmp_efficiency: None,
+        };
+        // a cell given twice (e.g. in two files) counts once
+        let same = |r: &Row| (&r.kernel, &r.variant, &r.size, r.threads, &r.implementation, &r.metric) == (&row.kernel, &row.variant, &row.size, row.threads, &row.implementation, &row.metric);
+        if !rows.iter().any(same) {
+            rows.push(row);
+        }
+    }
+
+    let bases: Vec<(usize, f64, f64)> = rows
+        .iter()
+        .enumerate()
+        .filter_map(|(i, row)| {
+            let base = rows.iter().find(|b| {
+                b.threads == Some(1)
+                    && (&b.kernel, &b.variant, &b.size, &b.implementation, &b.metric) == (&row.kernel, &row.variant, &row.size, &row.implementation, &row.metric)
+            })?;
+            Some((i, base.rust, base.openmp))
+        })
+        .collect();
+    for (i, rust_base, openmp_base) in bases {
+        let row = &mut rows[i];
+        if let Some(t) = row.threads.filter(|&t| t > 0) {
+            row.rust_efficiency = Some(rust_base / (t as f64 * row.rust));
+            row.openmp_efficiency = Some(openmp_base / (t as f64 * row.openmp));
+        }
+    }
+
+    rows.sort_by(|a, b| {
+        (&a.kernel, &a.variant, size_key(&a.size), &a.metric, &a.implementation, a.threads).cmp(&(&b.kernel, &b.variant, size_key(&b.size), &b.metric, &b.implementation, b.threads))
+    });
+    rows
+}
+
+fn geometric_mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), v| (sum + v.ln(), count + 1));
+    (count > 0).then(|| (sum / count as f64).exp())
+}
+
+fn percent(efficiency: Option<f64>) -> String {
+    efficiency.map_or_else(|| "-".to_string(), |e| format!("{:.1}%", e * 100.0))
+}
+
+/// The summary table of `records`, then the geometric means per kernel and
+/// implementation.
+pub fn render(records: &[Record]) -> String {
+    let rows = rows(records);
+    if rows.is_empty() {
+        return "No Rust and OpenMP timings of the same cells in these files\n".to_string();
+    }
+    let cells: Vec<[String; 11]> = rows
+        .iter()
+        .map(|r| {
+            [
+                r.kernel.clone(),
+                r.variant.clone(),
+                r.size.clone().unwrap_or_else(|| "-".to_string()),
+                r.threads.map_or_else(|| "-".to_string(), |t| t.to_string()),
+                r.implementation.clone(),
+                r.metric.clone(),
+                format!("{:.6} {}", r.rust, r.unit),
+                format!("{:.6} {}", r.openmp, r.unit),
+                format!("{:.2}x", r.slowdown()),
+                percent(r.rust_efficiency),
+                percent(r.openmp_efficiency),
+            ]
+        })
+        .collect();
+    let header = ["kernel", "variant", "size", "T", "impl", "metric", "rust", "openmp", "rust/openmp", "eff rust", "eff openmp"];
+    let widths: Vec<usize> = (0..header.len()).map(|i| cells.iter().map(|c| c[i].len()).chain([header[i].len()]).max().unwrap_or(0)).collect();
+    let line = |fields: &[&str]| {
+        let padded: Vec<String> = fields
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            // the text columns left-aligned, the numbers right-aligned
+            .map(|(i, (f, &w))| if i < 3 || i == 4 || i == 5 { format!("{:<w$}", f) } else { format!("{:>w$}", f) })
+            .collect();
+        format!("{}\n", padded.join("  ").trim_end())
+    };
+
+    let mut out = line(&header);
+    for cell in &cells {
+        out.push_str(&line(&cell.iter().map(String::as_str).collect::<Vec<_>>()));
+    }
+    out.push('\n');
+    let mut groups: Vec<(&str, &str)> = rows.iter().map(|r| (r.kernel.as_str(), r.implementation.as_str())).collect();
+    groups.dedup();
+    for (kernel, implementation) in groups {
+        let group: Vec<&Row> = rows.iter().filter(|r| r.kernel == kernel && r.implementation == implementation).collect();
+        let overall = geometric_mean(group.iter().map(|r| r.slowdown())).expect("a group has rows");
+        out.push_str(&format!("{} {}: Rust/OpenMP {:.2}x (geometric mean of {} timing(s))", kernel, implementation, overall, group.len()));
+        if let Some(max) = group.iter().filter_map(|r| r.threads).max() {
+            let at_max = geometric_mean(group.iter().filter(|r| r.threads == Some(max)).map(|r| r.slowdown())).expect("a row has T=max");
+            out.push_str(&format!(", {:.2}x at T={}", at_max, max));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report;
+
+    #[test]
+    fn test_rows() {
+        let rust = |n: usize, t: usize, time: f64| Record::new("matmul", "rayon").param("n", n).param("T", t).param("simd", 0).metric("time", time, "sec").metric("time_mean", time, "sec");
+        let mut records = vec![rust(512, 1, 4.0), rust(512, 2, 2.5), rust(256, 1, 0.5), rust(256, 2, 0.25)];
+        let (_, openmp) = report::parse_csv(
+            "matmul,openmp,n=512,T=1,time,2.000000,sec\n\
+             matmul,openmp,n=512,T=2,time,1.000000,sec\n\
+             matmul,openmp,n=256,T=2,time,0.500000,sec\n\
+             matmul,openmp,n=256,T=2,correct,1,boolean\n",
+        );
+        records.extend(openmp.iter().cloned());
+        records.extend(openmp);
+
+        let rows = rows(&records);
+        let cells: Vec<(&str, Option<usize>, f64)> = rows.iter().map(|r| (r.size.as_deref().unwrap(), r.threads, r.slowdown())).collect();
+        assert_eq!(cells, [("n=256", Some(2), 0.5), ("n=512", Some(1), 2.0), ("n=512", Some(2), 2.5)]);
+        assert!(rows.iter().all(|r| r.variant.is_empty() && r.metric == "time"));
+        // no OpenMP run at n=256, T=1: no efficiencies there
+        assert_eq!((rows[0].rust_efficiency, rows[0].openmp_efficiency), (None, None));
+        assert_eq!((rows[2].rust_efficiency, rows[2].openmp_efficiency), (Some(0.8), Some(1.0)));
+
+        let table = render(&records);
+        assert!(table.starts_with("kernel  variant  size   T  impl   metric"), "{}", table);
+        assert!(table.contains("  80.0%      100.0%\n"), "{}", table);
+        assert!(table.ends_with("matmul rayon: Rust/OpenMP 1.36x (geometric mean of 3 timing(s)), 1.12x at T=2\n"), "{}", table);
+        assert!(render(&records[..4]).starts_with("No Rust and OpenMP"));
+    }
+}
@@ -0,0 +1,102 @@
+// Thread-count specifiers
+// Sweeps take `--threads LIST` and single runs `--threads T`, so one
+// configuration works on any machine instead of hard-coding 1..16:
+//   8          a fixed count
+//   max        logical CPUs (std::thread::available_parallelism)
+//   cores      physical cores (Linux sysfs topology; logical CPUs elsewhere)
+//   half       max / 2
+//   quarter    max / 4
+//   a/b        max * a / b, e.g. 3/4
+// Every specifier resolves to at least 1. A list such as `1,2,4,half,max` is
+// resolved, sorted and de-duplicated, so small machines just get fewer cells.
+
+use std::collections::HashSet;
+use std::fs;
+
+/// Logical CPUs available to this process.
+pub fn logical() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Physical cores: distinct (package, core) pairs of the online CPUs.
+pub fn physical() -> usize {
+    let mut cores = HashSet::new();
+    if let Ok(entries) = fs::read_dir("/sys/devices/system/cpu") {
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if !name.strip_prefix("cpu").is_some_and(|id| !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit())) {
+                continue;
+            }
+            let topology = entry.path().join("topology");
+            let read = |file: &str| fs::read_to_string(topology.join(file)).ok().map(|s| s.trim().to_string());
+            if let (Some(package), Some(core)) = (read("physical_package_id"), read("core_id")) {
+                cores.insert((package, core));
+            }
+        }
+    }
+    // containers may see more topology than they may use
+    if cores.is_empty() {
+        logical()
+    } else {
+        cores.len().min(logical())
+    }
+}
+
+/// Resolves one specifier (see the module docs).
+pub fn parse_spec(spec: &str) -> Result<usize, String> {
+    let invalid = || format!("invalid thread count: {} (use a number, max, cores, half, quarter or a/b)", spec);
+    let count = match spec {
+        "max" => logical(),
+        "cores" => physical(),
+        "half" => logical() / 2,
+        "quarter" => logical() / 4,
+        _ => match spec.split_once('/') {
+            Some((a, b)) => {
+                let a: usize = a.parse().map_err(|_| invalid())?;
+                let b: usize = b.parse().map_err(|_| invalid())?;
+                if a == 0 || b == 0 {
+                    return Err(invalid());
+                }
+                logical().saturating_mul(a) / b
+            }
+            None => match spec.parse::<usize>() {
+                Ok(n) if n > 0 => n,
+                _ => return Err(invalid()),
+            },
+        },
+    };
+    Ok(count.max(1))
+}
+
+/// Resolves a comma-separated list into sorted, distinct counts.
+pub fn parse_list(list: &str) -> Result<Vec<usize>, String> {
+    let mut counts = list.split(',').map(|s| parse_spec(s.trim())).collect::<Result<Vec<_>, _>>()?;
+    counts.sort_unstable();
+    counts.dedup();
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_specifiers() {
+        let max = logical();
+        assert_eq!(parse_spec("6"), Ok(6));
+        assert_eq!(parse_spec("max"), Ok(max));
+        assert_eq!(parse_spec("half"), Ok((max / 2).max(1)));
+        assert_eq!(parse_spec("3/4"), Ok((max * 3 / 4).max(1)));
+        assert!(parse_spec("cores").unwrap() <= max);
+        for bad in ["0", "-2", "", "1/0", "x/2", "all"] {
+            assert!(parse_spec(bad).is_err(), "{}", bad);
+        }
+
+        let list = parse_list("16, 1,2,max,2").unwrap();
+        let mut expected = vec![1, 2, 16, max];
+        expected.sort_unstable();
+        expected.dedup();
+        assert_eq!(list, expected);
+    }
+}
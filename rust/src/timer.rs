@@ -0,0 +1,163 @@
+// Timing backends (--timer, runtime_overhead)
+//   instant  std::time::Instant (clock_gettime(CLOCK_MONOTONIC) on Linux),
+//            the default everywhere
+//   tsc      the CPU's time-stamp counter: rdtsc fenced by lfence to start,
+//            rdtscp then lfence to stop, so the timed code can neither
+//            drift out of the interval nor have the reads reordered into it
+// Reading the TSC costs a few dozen cycles and no call into the vDSO, which
+// matters when a cell times single parallel regions of about a microsecond.
+// Ticks become seconds through a rate calibrated once per process: ticks
+// counted over CALIBRATION of Instant time. The counter has to tick at a
+// constant rate through frequency changes and sleep states (the constant_tsc
+// and nonstop_tsc CPU flags); `--timer tsc` is refused on x86_64 CPUs
+// without them, and everywhere else.
+
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+const CALIBRATION: Duration = Duration::from_millis(20);
+
+/// The clock the timed runs are measured with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Timer {
+    Instant,
+    Tsc,
+}
+
+impl Timer {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s {
+            "instant" => Ok(Timer::Instant),
+            "tsc" => tsc_usable().map(|_| Timer::Tsc),
+            _ => Err(format!("unknown timer: {} (use instant|tsc)", s)),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Timer::Instant => "instant",
+            Timer::Tsc => "tsc",
+        }
+    }
+
+    /// Calibrates the clock if it needs it, so that the first timed run
+    /// does not pay for it.
+    pub fn calibrate(self) {
+        if self == Timer::Tsc {
+            tsc_hz();
+        }
+    }
+
+    pub fn start(self) -> Stopwatch {
+        match self {
+            Timer::Instant => Stopwatch::Instant(Instant::now()),
+            Timer::Tsc => Stopwatch::Tsc(tsc::start()),
+        }
+    }
+}
+
+/// A started measurement of one of the timers.
+pub enum Stopwatch {
+    Instant(Instant),
+    Tsc(u64),
+}
+
+impl Stopwatch {
+    /// Seconds since `start`.
+    pub fn elapsed(&self) -> f64 {
+        match *self {
+            Stopwatch::Instant(start) => start.elapsed().as_secs_f64(),
+            Stopwatch::Tsc(start) => tsc::stop().wrapping_sub(start) as f64 / tsc_hz(),
+        }
+    }
+}
+
+// the TSC rate in ticks per second, measured on first use
+fn tsc_hz() -> f64 {
+    static HZ: OnceLock<f64> = OnceLock::new();
+    *HZ.get_or_init(|| {
+        let (ticks, started) = (tsc::start(), Instant::now());
+        while started.elapsed() < CALIBRATION {
+            std::hint::spin_loop();
+        }
+        let ticks = tsc::stop() - ticks;
+        let hz = ticks as f64 / started.elapsed().as_secs_f64();
+        tracing::info!(ghz = hz * 1e-9, "calibrated the time-stamp counter");
+        hz
+    })
+}
+
+#[cfg(target_arch = "x86_64")]
+fn tsc_usable() -> Result<(), String> {
+    let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").unwrap_or_default();
+    let flags: Vec<&str> = cpuinfo.lines().find_map(|l| l.strip_prefix("flags")).map(|l| l.split_whitespace().collect()).unwrap_or_default();
+    match ["constant_tsc", "nonstop_tsc", "rdtscp"].into_iter().find(|f| !flags.contains(f)) {
+        None => Ok(()),
+        Some(missing) => Err(format!("--timer tsc needs an invariant time-stamp counter; this CPU lacks {}", missing)),
+    }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn tsc_usable() -> Result<(), String> {
+    Err("--timer tsc needs an x86_64 CPU".to_string())
+}
+
+#[cfg(target_arch = "x86_64")]
+mod tsc {
+    use std::arch::x86_64::{__rdtscp, _mm_lfence, _rdtsc};
+
+    pub fn start() -> u64 {
+        // SAFETY: rdtsc and lfence exist on every x86_64 CPU
+        unsafe {
+            _mm_lfence();
+            let ticks = _rdtsc();
+            _mm_lfence();
+            ticks
+        }
+    }
+
+    pub fn stop() -> u64 {
+        let mut aux = 0;
+        // SAFETY: Timer::parse only admits Tsc on CPUs with rdtscp
+        unsafe {
+            let ticks = __rdtscp(&mut aux);
+            _mm_lfence();
+            ticks
+        }
+    }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+mod tsc {
+    // Timer::parse never yields Tsc here
+    pub fn start() -> u64 {
+        unreachable!("no time-stamp counter")
+    }
+
+    pub fn stop() -> u64 {
+        unreachable!("no time-stamp counter")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timers() {
+        assert_eq!(Timer::parse("instant"), Ok(Timer::Instant));
+        assert!(Timer::parse("hpet").is_err());
+        let mut timers = vec![Timer::Instant];
+        if let Ok(tsc) = Timer::parse("tsc") {
+            assert_eq!(tsc.name(), "tsc");
+            timers.push(tsc);
+        }
+        for timer in timers {
+            timer.calibrate();
+            let stopwatch = timer.start();
+            std::thread::sleep(Duration::from_millis(30));
+            let elapsed = stopwatch.elapsed();
+            assert!((0.029..1.0).contains(&elapsed), "{}: {}", timer.name(), elapsed);
+        }
+    }
+}
@@ -0,0 +1,197 @@
+// Minimal HTTP uploader for streaming results to a remote collector
+// Plain HTTP/1.1 over std::net so cluster nodes need nothing beyond the
+// binary; the collector only has to accept `POST <path>` with a JSON body.
+
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+// for the connect, and for every read and write after it
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug)]
+pub struct Uploader {
+    url: String,
+    host: String,
+    port: u16,
+    path: String,
+}
+
+impl Uploader {
+    // accepts http://host[:port][/path], with an IPv6 host as [addr]
+    pub fn new(url: &str) -> Result<Self, String> {
+        let rest = url
+            .strip_prefix("http://")
+            .ok_or_else(|| format!("unsupported upload URL: {} (only http:// is supported)", url))?;
+
+        let (authority, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+
+        // the brackets of an IPv6 literal go, its colons are not the port's
+        let (host, port) = match authority.strip_prefix('[') {
+            Some(bracketed) => {
+                let (h, p) = bracketed
+                    .split_once(']')
+                    .ok_or_else(|| format!("unclosed [ in upload URL: {}", url))?;
+                match p {
+                    "" => (h, None),
+                    _ => (h, Some(p.strip_prefix(':').ok_or_else(|| format!("invalid port in upload URL: {}", url))?)),
+                }
+            }
+            None => match authority.rsplit_once(':') {
+                Some((h, p)) => (h, Some(p)),
+                None => (authority, None),
+            },
+        };
+        let port = match port {
+            Some(p) => p
+                .parse::<u16>()
+                .map_err(|_| format!("invalid port in upload URL: {}", url))?,
+            None => 80,
+        };
+
+        if host.is_empty() {
+            return Err(format!("missing host in upload URL: {}", url));
+        }
+
+        Ok(Uploader {
+            url: url.to_string(),
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    // POST one JSON document; any non-2xx status is an error
+    pub fn post(&self, body: &str) -> io::Result<()> {
+        let mut stream = self.connect()?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+
+        // an IPv6 host is bracketed again in the header only
+        let host = if self.host.contains(':') { format!("[{}]", self.host) } else { self.host.clone() };
+        write!(
+            stream,
+            "POST {} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.path,
+            host,
+            self.port,
+            body.len(),
+            body
+        )?;
+        stream.flush()?;
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+
+        let status = response
+            .split_whitespace()
+            .nth(1)
+            .and_then(|s| s.parse::<u16>().ok())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed HTTP response"))?;
+
+        if !(200..300).contains(&status) {
+            return Err(io::Error::other(format!("collector returned HTTP {}", status)));
+        }
+
+        Ok(())
+    }
+
+    // the first address of the host that accepts within TIMEOUT, so an
+    // unreachable collector costs one timeout rather than the OS's SYN retries
+    fn connect(&self) -> io::Result<TcpStream> {
+        let mut last = io::Error::new(io::ErrorKind::NotFound, format!("no address for {}", self.host));
+        for addr in (self.host.as_str(), self.port).to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, TIMEOUT) {
+                Ok(stream) => return Ok(stream),
+                Err(e) => last = e,
+            }
+        }
+        Err(last)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufRead;
+    use std::net::TcpListener;
+    use std::thread;
+
+    #[test]
+    fn test_parse_url() {
+        let up = Uploader::new("http://collector:8080/results").unwrap();
+        assert_eq!((up.host.as_str(), up.port, up.path.as_str()), ("collector", 8080, "/results"));
+
+        let up = Uploader::new("http://collector").unwrap();
+        assert_eq!((up.port, up.path.as_str()), (80, "/"));
+
+        assert!(Uploader::new("https://collector/").is_err());
+        assert!(Uploader::new("http://:80/").is_err());
+
+        let up = Uploader::new("http://[::1]/x").unwrap();
+        assert_eq!((up.host.as_str(), up.port, up.path.as_str()), ("::1", 80, "/x"));
+
+        let up = Uploader::new("http://[::1]:8080/x").unwrap();
+        assert_eq!((up.host.as_str(), up.port, up.path.as_str()), ("::1", 8080, "/x"));
+
+        assert!(Uploader::new("http://[::1/x").is_err());
+        assert!(Uploader::new("http://[::1]8080/x").is_err());
+        assert!(Uploader::new("http://[]:80/").is_err());
+    }
+
+    #[test]
+    fn test_post_unreachable() {
+        // a port nobody listens on any more
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let up = Uploader::new(&format!("http://127.0.0.1:{}/results", port)).unwrap();
+
+        let start = std::time::Instant::now();
+        assert!(up.post("{}").is_err());
+        assert!(start.elapsed() < TIMEOUT);
+    }
+
+    #[test]
+    fn test_post() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = io::BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+
+            // skip headers, then read the body
+            let mut len = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(v) = line.strip_prefix("Content-Length: ") {
+                    len = v.trim().parse().unwrap();
+                }
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut body = vec![0u8; len];
+            reader.read_exact(&mut body).unwrap();
+            reader.get_mut().write_all(b"HTTP/1.1 204 No Content\r\n\r\n").unwrap();
+
+            (request_line, String::from_utf8(body).unwrap())
+        });
+
+        let up = Uploader::new(&format!("http://127.0.0.1:{}/ingest", port)).unwrap();
+        up.post("{\"a\":1}").unwrap();
+
+        let (request_line, body) = server.join().unwrap();
+        assert_eq!(request_line, "POST /ingest HTTP/1.1\r\n");
+        assert_eq!(body, "{\"a\":1}");
+    }
+}
@@ -0,0 +1,37 @@
+// The library kernels called the way an external harness would, through the
+// crate's public API only.
+
+use openmp_rust_benchmarks::backend::{Backend, Pool};
+use openmp_rust_benchmarks::controllability::histogram;
+use openmp_rust_benchmarks::pages::Pages;
+use openmp_rust_benchmarks::programmability::prefix_sum;
+use openmp_rust_benchmarks::runtime_overhead;
+use openmp_rust_benchmarks::scalability::matrix_multiply;
+
+#[test]
+fn test_public_kernels() {
+    for backend in Backend::ALL {
+        let pool = Pool::new(backend, 3);
+
+        let input: Vec<u64> = (1..=1000).collect();
+        let sums = prefix_sum::prefix_sum_parallel(&pool, &input, Pages::Default);
+        assert_eq!(sums[..], prefix_sum::prefix_sum_sequential(&input, Pages::Default)[..]);
+        assert_eq!(sums[999], 500_500);
+
+        let data = histogram::gen_skewed(10_000, histogram::SKEWED_SEED, Pages::Default);
+        let (_, local) = histogram::hist_local(&pool, &data, 0, false);
+        let (_, atomic) = histogram::hist_atomic(&pool, &data, 64, true);
+        assert_eq!(local, atomic);
+        assert_eq!(local.len(), histogram::BINS);
+        assert_eq!(local.iter().sum::<u64>(), 10_000);
+
+        let a = matrix_multiply::create_matrix(17, 1.0, Pages::Default);
+        let b = matrix_multiply::create_matrix(17, 2.0, Pages::Default);
+        let c = matrix_multiply::matrix_multiply_parallel(&pool, &a, &b, 17, false);
+        assert_eq!(c.n(), 17);
+        assert!(c.as_slice().iter().all(|&v| v == 34.0));
+    }
+
+    assert_eq!(runtime_overhead::fib_rayon(20), runtime_overhead::fib_seq(20));
+    assert_eq!(runtime_overhead::fib_chase_lev(20), 6765);
+}